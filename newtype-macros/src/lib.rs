@@ -56,7 +56,6 @@ fn ast_node_enum(attrs: AstNodeAttributes, item: &mut syn::ItemEnum) -> TokenStr
         #[serde(rename_all = "kebab-case")]
     });
 
-
     item.variants.iter_mut().for_each(|variant| {
         // check if the variant has the container attribute `ast_node(span)`
         // if it does, then add the span field to the variant
@@ -103,9 +102,15 @@ fn ast_node_struct(attrs: AstNodeAttributes, item: &mut ItemStruct) -> TokenStre
 
     // Get the struct fields
     if let Fields::Named(ref mut fields) = item.fields {
+        // The span is only serialized as the origin of a node the simplifier built, by the
+        // verbose sexpr printer.
         let skip = if !attrs.transparent {
             quote! {
-                #[serde(skip)]
+                #[serde(
+                    rename = "origin",
+                    skip_serializing_if = "crate::ast::origin::hides",
+                    serialize_with = "crate::ast::origin::serialize"
+                )]
             }
         } else {
             quote! {}
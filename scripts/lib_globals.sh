@@ -0,0 +1,22 @@
+#!/usr/bin/env sh
+# Regenerates src/lint/lib_globals.rs from the ECMAScript lib files of a TypeScript install:
+#
+#     scripts/lib_globals.sh node_modules/typescript/lib > src/lint/lib_globals.rs
+#
+# The DOM and web worker libs are left out, they declare too many names to be worth a warning.
+set -eu
+
+lib_dir=${1:?usage: lib_globals.sh <typescript lib directory>}
+
+cat <<HEADER
+// @generated by scripts/lib_globals.sh, do not edit.
+
+/// Names of the types declared by the ECMAScript lib files of TypeScript, sorted.
+pub const LIB_GLOBALS: &[&str] = &[
+HEADER
+
+sed -n -E 's/^(declare )?(interface|type) ([A-Za-z_][A-Za-z0-9_]*).*/\3/p' "$lib_dir"/lib.es*.d.ts |
+    LC_ALL=C sort -u |
+    sed 's/.*/    "&",/'
+
+echo "];"
@@ -0,0 +1,378 @@
+/// Metrics of how expensive the emitted types are for TypeScript to check.
+use std::{fmt::Display, str::FromStr};
+
+mod describe;
+mod graph;
+
+use serde::Serialize;
+
+pub use describe::{describe, DescribeFormat, Description};
+pub use graph::{dependency_graph, Graph, GraphFormat};
+
+use crate::{
+    ast::{
+        visit::{walk_apply_generic, walk_ast, walk_extends_expr, Visit},
+        ApplyGeneric, Ast, ExtendsExpr, Program, TypeAlias,
+    },
+    diagnostic::{codes, Diagnostic},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ComplexityReport {
+    /// Number of nodes in the tree.
+    pub nodes: usize,
+    /// Deepest nesting of conditional types. Every arm of a `match` or `cond` nests one level
+    /// deeper than the previous one.
+    pub conditional_depth: usize,
+    /// Number of types inferred by conditionals.
+    pub infer_bindings: usize,
+    /// Estimate of the number of instantiations TypeScript goes through in sequence to evaluate
+    /// the type once: the deepest nesting of conditional types and generic applications.
+    pub instantiation_depth: usize,
+}
+
+/// Metric a limit can be put on, named as in `--limit depth=50`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Nodes,
+    Depth,
+    Infer,
+    Instantiation,
+}
+
+impl Metric {
+    const ALL: [Metric; 4] = [
+        Metric::Nodes,
+        Metric::Depth,
+        Metric::Infer,
+        Metric::Instantiation,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Nodes => "nodes",
+            Metric::Depth => "depth",
+            Metric::Infer => "infer",
+            Metric::Instantiation => "instantiation",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Metric::Nodes => "node count",
+            Metric::Depth => "conditional depth",
+            Metric::Infer => "number of infer bindings",
+            Metric::Instantiation => "instantiation depth",
+        }
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An upper bound on a metric, parsed from `metric=value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub metric: Metric,
+    pub max: usize,
+}
+
+impl FromStr for Limit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, max) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `METRIC=VALUE`, found `{s}`"))?;
+
+        let metric = Metric::ALL
+            .into_iter()
+            .find(|metric| metric.name() == name.trim())
+            .ok_or_else(|| {
+                let names: Vec<_> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+                format!(
+                    "unknown metric `{name}`, expected one of {}",
+                    names.join(", ")
+                )
+            })?;
+
+        let max = max
+            .trim()
+            .parse()
+            .map_err(|_| format!("expected a number, found `{max}`"))?;
+
+        Ok(Limit { metric, max })
+    }
+}
+
+impl ComplexityReport {
+    pub fn get(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Nodes => self.nodes,
+            Metric::Depth => self.conditional_depth,
+            Metric::Infer => self.infer_bindings,
+            Metric::Instantiation => self.instantiation_depth,
+        }
+    }
+}
+
+/// Measures `node`.
+pub fn complexity(node: &Ast) -> ComplexityReport {
+    let mut complexity = Complexity::default();
+    complexity.visit_ast(node);
+    complexity.report
+}
+
+/// Measures a tree as it is visited, the depths being those of the node visited.
+#[derive(Default)]
+struct Complexity {
+    report: ComplexityReport,
+    conditionals: usize,
+    instantiations: usize,
+}
+
+impl Complexity {
+    /// Visits the children of a node `conditionals` and `instantiations` levels deeper.
+    fn nested(&mut self, conditionals: usize, instantiations: usize, walk: impl FnOnce(&mut Self)) {
+        self.conditionals += conditionals;
+        self.instantiations += instantiations;
+
+        let report = &mut self.report;
+        report.conditional_depth = report.conditional_depth.max(self.conditionals);
+        report.instantiation_depth = report.instantiation_depth.max(self.instantiations);
+
+        walk(self);
+
+        self.conditionals -= conditionals;
+        self.instantiations -= instantiations;
+    }
+}
+
+impl<'a> Visit<'a> for Complexity {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        self.report.nodes += 1;
+        walk_ast(self, node);
+    }
+
+    fn visit_extends_expr(&mut self, expr: &'a ExtendsExpr) {
+        self.nested(1, 1, |visitor| walk_extends_expr(visitor, expr));
+    }
+
+    fn visit_apply_generic(&mut self, apply: &'a ApplyGeneric) {
+        self.nested(0, 1, |visitor| walk_apply_generic(visitor, apply));
+    }
+
+    // The name an infer declares is not a node of the type.
+    fn visit_infer(&mut self, _binding: &'a Ast) {
+        self.report.infer_bindings += 1;
+    }
+}
+
+/// Reports of the exported type aliases of a simplified program, in order of declaration.
+pub fn exported_aliases(program: &Ast) -> Vec<(&TypeAlias, ComplexityReport)> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(alias) if alias.export => Some(alias),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|alias| (alias, complexity(&alias.body)))
+        .collect()
+}
+
+/// Renders reports as a table with a row per alias.
+pub fn table(reports: &[(&TypeAlias, ComplexityReport)]) -> String {
+    let width = reports
+        .iter()
+        .map(|(alias, _)| alias.name.name.chars().count())
+        .chain(["alias".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut out = format!(
+        "{:<width$}  {:>8}  {:>8}  {:>8}  {:>13}\n",
+        "alias", "nodes", "depth", "infer", "instantiation"
+    );
+
+    for (alias, report) in reports {
+        out += &format!(
+            "{:<width$}  {:>8}  {:>8}  {:>8}  {:>13}\n",
+            alias.name.name,
+            report.nodes,
+            report.conditional_depth,
+            report.infer_bindings,
+            report.instantiation_depth
+        );
+    }
+
+    out
+}
+
+/// Errors for every exported alias of a simplified program going over one of `limits`.
+pub fn check_limits(program: &Ast, limits: &[Limit]) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for (alias, report) in exported_aliases(program) {
+        for limit in limits {
+            let value = report.get(limit.metric);
+
+            if value > limit.max {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "`{}` has a {} of {value}, over the limit of {}",
+                            alias.name.name,
+                            limit.metric.describe(),
+                            limit.max
+                        ),
+                        alias.span,
+                    )
+                    .with_code(codes::COMPLEXITY_LIMIT),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{
+        ast::{ApplyGeneric, ExtendsExpr, Ident, Span},
+        test_support::parse,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn ident(name: &str) -> Ast {
+        Ast::Ident(Ident {
+            name: name.to_string(),
+            span: Span::default(),
+            raw: false,
+        })
+    }
+
+    fn extends(lhs: Ast, rhs: Ast, then_branch: Ast, else_branch: Ast) -> Ast {
+        Ast::ExtendsExpr(ExtendsExpr {
+            lhs: lhs.into(),
+            rhs: rhs.into(),
+            then_branch: then_branch.into(),
+            else_branch: else_branch.into(),
+            non_distributive: false,
+            span: Span::default(),
+        })
+    }
+
+    #[test]
+    fn metrics_of_a_hand_built_tree() {
+        // T extends Array<infer U> ? (U extends string ? U : never) : Box<T>
+        let tree = extends(
+            ident("T"),
+            Ast::ApplyGeneric(ApplyGeneric {
+                receiver: ident("Array").into(),
+                args: vec![Ast::Infer(Rc::new(ident("U")))],
+                span: Span::default(),
+            }),
+            extends(ident("U"), ident("string"), ident("U"), ident("never")),
+            Ast::ApplyGeneric(ApplyGeneric {
+                receiver: ident("Box").into(),
+                args: vec![ident("T")],
+                span: Span::default(),
+            }),
+        );
+
+        assert_eq!(
+            complexity(&tree),
+            ComplexityReport {
+                nodes: 13,
+                conditional_depth: 2,
+                infer_bindings: 1,
+                instantiation_depth: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn leaf() {
+        assert_eq!(
+            complexity(&ident("A")),
+            ComplexityReport {
+                nodes: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn limits_fail_a_deep_alias() {
+        let program = parse!(
+            "export type Deep(T) as match T do 1 -> :a, 2 -> :b, 3 -> :c, else -> :d end\n\
+             export type Shallow(T) as if T <: 1 then :a else :b end\n\
+             type Private(T) as match T do 1 -> :a, 2 -> :b, 3 -> :c, else -> :d end"
+        )
+        .simplify();
+
+        let limits = ["depth=2".parse().unwrap()];
+
+        let messages: Vec<_> = check_limits(&program, &limits)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec!["`Deep` has a conditional depth of 3, over the limit of 2"]
+        );
+    }
+
+    #[test]
+    fn table_of_exported_aliases() {
+        let program =
+            parse!("export type A(T) as if T <: ?U[] then U end\nexport type Long as 1").simplify();
+
+        assert_eq!(
+            table(&exported_aliases(&program)),
+            dedent!(
+                "
+                alias     nodes     depth     infer  instantiation
+                A             6         1         1              1
+                Long          1         0         0              0
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn parse_limits() {
+        assert_eq!(
+            "depth=50".parse(),
+            Ok(Limit {
+                metric: Metric::Depth,
+                max: 50
+            })
+        );
+        assert_eq!(
+            "size=1".parse::<Limit>(),
+            Err("unknown metric `size`, expected one of nodes, depth, infer, instantiation".into())
+        );
+        assert_eq!(
+            "depth".parse::<Limit>(),
+            Err("expected `METRIC=VALUE`, found `depth`".into())
+        );
+    }
+}
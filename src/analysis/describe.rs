@@ -0,0 +1,385 @@
+/// A reference card of an alias, for `describe`: its signature, its parameters, the types its
+/// body infers and the TypeScript it is emitted as, without reading its source.
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        match_expr::MatchExpr,
+        visit::{walk_ast, Visit},
+        Ast, ExtendsExpr, ExtendsInfixOp, InfixOp, Program, Statement, TypeAlias, TypeParameter,
+    },
+    typescript::Pretty,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Description {
+    pub name: String,
+    /// The declaration without its body, `type Head(L, D)`.
+    pub signature: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    pub params: Vec<Parameter>,
+    /// The types inferred by the body, in the order they are written.
+    pub inferred: Vec<Inferred>,
+    /// The alias as it is emitted.
+    pub typescript: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub rest: bool,
+    /// Declared `const`, its argument is inferred as a literal.
+    #[serde(rename = "const", skip_serializing_if = "std::ops::Not::not")]
+    pub is_const: bool,
+}
+
+/// A `?T` of the body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Inferred {
+    pub name: String,
+    /// The pattern holding it, the right-hand side of a condition or the pattern of an arm.
+    pub pattern: String,
+}
+
+/// Output of `describe`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DescribeFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+/// The description of the alias `name` of `program`, or an error if it declares none.
+pub fn describe(program: &Ast, name: &str) -> Result<Description, String> {
+    let (statement, alias) =
+        find(program, name).ok_or_else(|| format!("there is no alias named `{name}`"))?;
+
+    let mut inferred = Infers::default();
+    inferred.visit_ast(&alias.body);
+
+    // The alias is emitted on its own, the references to the other declarations are left as they
+    // are.
+    let simplified = Ast::Program(Program {
+        statements: vec![Ast::Statement(statement.clone())],
+        span: alias.name.span,
+    })
+    .simplify();
+
+    let params: Vec<_> = alias.params.iter().map(Parameter::new).collect();
+
+    let signature = format!(
+        "{}type {}{}",
+        if alias.export { "export " } else { "" },
+        alias.name.name,
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "({})",
+                params
+                    .iter()
+                    .map(Parameter::written)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+    );
+
+    Ok(Description {
+        name: alias.name.name.clone(),
+        signature,
+        doc: alias.doc.clone(),
+        params,
+        inferred: inferred.0,
+        typescript: simplified.render_pretty_ts(80).trim_end().to_string(),
+    })
+}
+
+fn find<'a>(program: &'a Ast, name: &str) -> Option<(&'a Statement, &'a TypeAlias)> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return None;
+    };
+
+    statements.iter().find_map(|statement| match statement {
+        Ast::Statement(statement) => match statement.inner.as_ref() {
+            Ast::TypeAlias(alias) if alias.name.name == name => Some((statement, alias)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+impl Parameter {
+    fn new(param: &TypeParameter) -> Self {
+        Self {
+            name: param.name.name.clone(),
+            constraint: param.constraint.as_ref().map(ToString::to_string),
+            default: param.default.as_ref().map(ToString::to_string),
+            rest: param.rest,
+            is_const: param.is_const,
+        }
+    }
+
+    /// The name as the declaration lists it, `...T` for a rest parameter and `const T` for a
+    /// `const` one.
+    fn written(&self) -> String {
+        let modifier = if self.is_const { "const " } else { "" };
+        let rest = if self.rest { "..." } else { "" };
+
+        format!("{modifier}{rest}{}", self.name)
+    }
+}
+
+/// The inferred types of a tree, with the innermost pattern holding each of them.
+#[derive(Default)]
+struct Infers<'a>(Vec<Inferred>, Option<&'a Ast>);
+
+impl<'a> Infers<'a> {
+    fn visit_pattern(&mut self, pattern: &'a Ast) {
+        let outer = self.1.replace(pattern);
+        self.visit_ast(pattern);
+        self.1 = outer;
+    }
+}
+
+impl<'a> Visit<'a> for Infers<'a> {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        walk_ast(self, node);
+    }
+
+    fn visit_extends_infix_op(&mut self, op: &'a ExtendsInfixOp) {
+        self.visit_ast(&op.lhs);
+
+        match op.op {
+            InfixOp::Extends | InfixOp::NotExtends => self.visit_pattern(&op.rhs),
+            _ => self.visit_ast(&op.rhs),
+        }
+    }
+
+    fn visit_extends_expr(&mut self, expr: &'a ExtendsExpr) {
+        self.visit_ast(&expr.lhs);
+        self.visit_pattern(&expr.rhs);
+        self.visit_ast(&expr.then_branch);
+        self.visit_ast(&expr.else_branch);
+    }
+
+    fn visit_match_expr(&mut self, expr: &'a MatchExpr) {
+        self.visit_ast(&expr.value);
+
+        for arm in &expr.arms {
+            self.visit_pattern(&arm.pattern);
+            self.visit_ast(&arm.body);
+        }
+
+        self.visit_ast(&expr.else_arm);
+    }
+
+    fn visit_infer(&mut self, binding: &'a Ast) {
+        if let (Ast::Ident(ident), Some(pattern)) = (binding, self.1) {
+            self.0.push(Inferred {
+                name: ident.name.clone(),
+                pattern: pattern.to_string(),
+            });
+        }
+
+        self.visit_ast(binding);
+    }
+}
+
+impl Description {
+    pub fn render(&self, format: DescribeFormat) -> String {
+        match format {
+            DescribeFormat::Human => self.to_human(),
+            DescribeFormat::Json => {
+                serde_json::to_string_pretty(self).expect("descriptions serialize") + "\n"
+            }
+        }
+    }
+
+    /// The sections of the description, each under a heading, the empty ones left out.
+    fn to_human(&self) -> String {
+        let mut out = format!("{}\n", self.signature);
+
+        if let Some(doc) = &self.doc {
+            out += &format!("\n{}\n", doc.trim());
+        }
+
+        let indented = |lines: Vec<String>| {
+            lines.iter().fold(String::new(), |mut out, line| {
+                out += &format!("    {line}\n");
+                out
+            })
+        };
+
+        if !self.params.is_empty() {
+            out += "\nParameters:\n";
+            out += &indented(
+                self.params
+                    .iter()
+                    .map(|param| {
+                        let mut line = param.written();
+
+                        if let Some(constraint) = &param.constraint {
+                            line += &format!(" <: {constraint}");
+                        }
+                        if let Some(default) = &param.default {
+                            line += &format!(" = {default}");
+                        }
+
+                        line
+                    })
+                    .collect(),
+            );
+        }
+
+        if !self.inferred.is_empty() {
+            out += "\nInferred:\n";
+            out += &indented(
+                self.inferred
+                    .iter()
+                    .map(|inferred| format!("{} in {}", inferred.name, inferred.pattern))
+                    .collect(),
+            );
+        }
+
+        out += "\nTypeScript:\n";
+        out += &indented(self.typescript.lines().map(str::to_string).collect());
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn fixture() -> Ast {
+        parse!(dedent!(
+            r#"
+            /** The elements of a pair swapped, or the default. */
+            export type Swap(L, D) defaults D = never where L <: unknown[] as
+                match L do
+                    [?H, ?T] -> [T, H],
+                    else -> D,
+                end
+            "#
+        ))
+    }
+
+    #[test]
+    fn human() {
+        assert_eq!(
+            describe(&fixture(), "Swap")
+                .unwrap()
+                .render(DescribeFormat::Human),
+            dedent!(
+                "
+                export type Swap(L, D)
+
+                The elements of a pair swapped, or the default.
+
+                Parameters:
+                    L <: unknown[]
+                    D = never
+
+                Inferred:
+                    H in [?H, ?T]
+                    T in [?H, ?T]
+
+                TypeScript:
+                    /** The elements of a pair swapped, or the default. */
+                    export type Swap<L extends unknown[], D = never> =
+                        L extends [infer H, infer T]
+                            ? [T, H]
+                            : D;
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json: serde_json::Value = serde_json::from_str(
+            &describe(&fixture(), "Swap")
+                .unwrap()
+                .render(DescribeFormat::Json),
+        )
+        .unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "Swap",
+                "signature": "export type Swap(L, D)",
+                "doc": "The elements of a pair swapped, or the default.",
+                "params": [
+                    {"name": "L", "constraint": "unknown[]"},
+                    {"name": "D", "default": "never"},
+                ],
+                "inferred": [
+                    {"name": "H", "pattern": "[?H, ?T]"},
+                    {"name": "T", "pattern": "[?H, ?T]"},
+                ],
+                "typescript": "/** The elements of a pair swapped, or the default. */\n\
+                               export type Swap<L extends unknown[], D = never> =\n    \
+                               L extends [infer H, infer T]\n        ? [T, H]\n        : D;",
+            })
+        );
+    }
+
+    #[test]
+    fn infers_of_conditions() {
+        let program =
+            parse!("type Unwrap(T) as if T <: Promise(?V) or T <: {value: ?V} then V else T end");
+
+        assert_eq!(
+            describe(&program, "Unwrap").unwrap().inferred,
+            vec![
+                Inferred {
+                    name: "V".to_string(),
+                    pattern: "Promise(?V)".to_string()
+                },
+                Inferred {
+                    name: "V".to_string(),
+                    pattern: "{value: ?V}".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn const_parameters() {
+        let program = parse!("type Tuple(const T, U) where T <: unknown[] as [T, U]");
+        let description = describe(&program, "Tuple").unwrap();
+
+        assert_eq!(description.signature, "type Tuple(const T, U)");
+        assert!(description
+            .render(DescribeFormat::Human)
+            .contains("Parameters:\n    const T <: unknown[]\n    U\n"));
+        assert_eq!(
+            serde_json::to_value(&description.params).unwrap(),
+            serde_json::json!([
+                {"name": "T", "constraint": "unknown[]", "const": true},
+                {"name": "U"},
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_alias() {
+        assert_eq!(
+            describe(&fixture(), "Head"),
+            Err("there is no alias named `Head`".to_string())
+        );
+    }
+}
@@ -0,0 +1,417 @@
+/// The graph of the references between the top-level declarations of a program, with the cycles
+/// of declarations using one another.
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    ast::{Ast, ExportDefault, Interface, Program, TypeAlias},
+    compile::tags::Declaration,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Graph {
+    /// The declarations, in the order of the program.
+    pub nodes: Vec<Node>,
+    /// Uses of a declaration by another, ordered by user then by declaration used.
+    pub edges: Vec<Edge>,
+    /// The strongly connected components of more than one declaration, or of a declaration using
+    /// itself, their declarations in the order of the program.
+    pub cycles: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Node {
+    pub name: String,
+    pub kind: NodeKind,
+    pub export: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NodeKind {
+    Alias,
+    Interface,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Output of `graph`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz, the cycles drawn in red
+    #[default]
+    Dot,
+    Json,
+}
+
+/// The aliases and interfaces of `program`, once simplified, and the uses of one by another
+/// through its name, as in `A`, `A(T)` or `A::B`. Declarations merged under one name are one node.
+pub fn dependency_graph(program: &Ast) -> Graph {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return Graph::default();
+    };
+
+    let mut nodes: Vec<Node> = vec![];
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut references = vec![];
+
+    for statement in statements {
+        let (name, kind, export) = match inner(statement) {
+            Ast::TypeAlias(TypeAlias { name, export, .. }) => {
+                (name.name.as_str(), NodeKind::Alias, *export)
+            }
+            Ast::Interface(Interface { name, export, .. }) => {
+                (name.as_str(), NodeKind::Interface, *export)
+            }
+            _ => continue,
+        };
+
+        let i = *index.entry(name).or_insert_with(|| {
+            nodes.push(Node {
+                name: name.to_string(),
+                kind,
+                export: false,
+            });
+            references.push(vec![]);
+            nodes.len() - 1
+        });
+
+        nodes[i].export |= export;
+        references[i].extend(Declaration::new(statement).references);
+    }
+
+    for statement in statements {
+        if let Ast::ExportDefault(ExportDefault { name, .. }) = inner(statement) {
+            if let Some(&i) = index.get(name.name.as_str()) {
+                nodes[i].export = true;
+            }
+        }
+    }
+
+    let successors: Vec<Vec<usize>> = references
+        .iter()
+        .map(|names| {
+            let mut uses: Vec<usize> = names
+                .iter()
+                .filter_map(|name| index.get(name.as_str()).copied())
+                .collect();
+            uses.sort_unstable();
+            uses.dedup();
+            uses
+        })
+        .collect();
+
+    let edges = successors
+        .iter()
+        .enumerate()
+        .flat_map(|(i, uses)| {
+            let nodes = &nodes;
+            uses.iter().map(move |&j| Edge {
+                from: nodes[i].name.clone(),
+                to: nodes[j].name.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut cycles: Vec<Vec<usize>> = strongly_connected(&successors)
+        .into_iter()
+        .filter(|component| match component.as_slice() {
+            [i] => successors[*i].contains(i),
+            _ => true,
+        })
+        .map(|mut component| {
+            component.sort_unstable();
+            component
+        })
+        .collect();
+    cycles.sort_unstable();
+
+    Graph {
+        cycles: cycles
+            .into_iter()
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|i| nodes[i].name.clone())
+                    .collect()
+            })
+            .collect(),
+        nodes,
+        edges,
+    }
+}
+
+fn inner(statement: &Ast) -> &Ast {
+    match statement {
+        Ast::Statement(inner) => inner.as_ref(),
+        other => other,
+    }
+}
+
+/// The strongly connected components of the graph of `successors`, by Tarjan's algorithm.
+fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'s> {
+        successors: &'s [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+
+            for &w in &self.successors[v] {
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(index) if self.on_stack[w] => self.low[v] = self.low[v].min(index),
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.low[v]) == self.index[v] {
+                let mut component = vec![];
+
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    component.push(w);
+
+                    if w == v {
+                        break;
+                    }
+                }
+
+                self.components.push(component);
+            }
+        }
+    }
+
+    let n = successors.len();
+    let mut tarjan = Tarjan {
+        successors,
+        index: vec![None; n],
+        low: vec![0; n],
+        stack: vec![],
+        on_stack: vec![false; n],
+        next: 0,
+        components: vec![],
+    };
+
+    for v in 0..n {
+        if tarjan.index[v].is_none() {
+            tarjan.visit(v);
+        }
+    }
+
+    tarjan.components
+}
+
+impl Graph {
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Graphviz source of the graph. Interfaces are boxes, exported declarations are bold, and the
+    /// declarations of a cycle and the edges between them are red.
+    pub fn to_dot(&self) -> String {
+        let cycle_of: HashMap<&str, usize> = self
+            .cycles
+            .iter()
+            .enumerate()
+            .flat_map(|(i, cycle)| cycle.iter().map(move |name| (name.as_str(), i)))
+            .collect();
+
+        let mut out = String::from("digraph {\n");
+
+        for node in &self.nodes {
+            let mut attributes = vec![];
+
+            if node.kind == NodeKind::Interface {
+                attributes.push("shape=box");
+            }
+            if node.export {
+                attributes.push("style=bold");
+            }
+            if cycle_of.contains_key(node.name.as_str()) {
+                attributes.push("color=red");
+            }
+
+            out.push_str(&format!("    {}", quote(&node.name)));
+            if !attributes.is_empty() {
+                out.push_str(&format!(" [{}]", attributes.join(", ")));
+            }
+            out.push_str(";\n");
+        }
+
+        for Edge { from, to } in &self.edges {
+            let in_cycle = matches!(
+                (cycle_of.get(from.as_str()), cycle_of.get(to.as_str())),
+                (Some(a), Some(b)) if a == b
+            );
+
+            out.push_str(&format!(
+                "    {} -> {}{};\n",
+                quote(from),
+                quote(to),
+                if in_cycle { " [color=red]" } else { "" }
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap() + "\n"
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn graph(source: &str) -> Graph {
+        dependency_graph(&parse!(source).simplify())
+    }
+
+    fn edges(graph: &Graph) -> Vec<(&str, &str)> {
+        graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn edge_list() {
+        let graph = graph(dedent!(
+            r#"
+            type Id as string
+            type Box(T) as {value: T, id: Id}
+            interface Named {name: string}
+            interface User extends Named {box: Box(Id)}
+            export type Users as User[]
+            type Unused as 1
+            "#
+        ));
+
+        assert_eq!(
+            edges(&graph),
+            vec![
+                ("Box", "Id"),
+                ("User", "Id"),
+                ("User", "Box"),
+                ("User", "Named"),
+                ("Users", "User"),
+            ]
+        );
+        assert_eq!(graph.cycles, Vec::<Vec<String>>::new());
+    }
+
+    #[test]
+    fn mutually_recursive_aliases_are_a_cycle() {
+        let graph = graph(dedent!(
+            r#"
+            type Tree as {children: Forest}
+            type Leaf as 1
+            type Forest as (Tree | Leaf)[]
+            type List(T) as [T, List(T)] | []
+            "#
+        ));
+
+        assert_eq!(
+            graph.cycles,
+            vec![
+                vec!["Tree".to_string(), "Forest".to_string()],
+                vec!["List".to_string()]
+            ]
+        );
+        assert_eq!(
+            graph.to_dot(),
+            dedent!(
+                r#"
+                digraph {
+                    "Tree" [color=red];
+                    "Leaf";
+                    "Forest" [color=red];
+                    "List" [color=red];
+                    "Tree" -> "Forest" [color=red];
+                    "Forest" -> "Tree" [color=red];
+                    "Forest" -> "Leaf";
+                    "List" -> "List" [color=red];
+                }
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn json() {
+        let graph = graph("export type A as B\ninterface B {a: A}\nexport default B");
+
+        assert_eq!(
+            graph.to_json(),
+            dedent!(
+                r#"
+                {
+                  "nodes": [
+                    {
+                      "name": "A",
+                      "kind": "alias",
+                      "export": true
+                    },
+                    {
+                      "name": "B",
+                      "kind": "interface",
+                      "export": true
+                    }
+                  ],
+                  "edges": [
+                    {
+                      "from": "A",
+                      "to": "B"
+                    },
+                    {
+                      "from": "B",
+                      "to": "A"
+                    }
+                  ],
+                  "cycles": [
+                    [
+                      "A",
+                      "B"
+                    ]
+                  ]
+                }
+                "#
+            )
+            .trim_start()
+        );
+    }
+}
@@ -1,5 +1,7 @@
-use std::{collections::HashMap, fmt::Display, rc::Rc};
+use std::{borrow::Cow, collections::HashMap, fmt::Display, ops::Deref, rc::Rc};
 
+use attribute::Attribute;
+use brand_declaration::{BrandDeclaration, BrandEncoding};
 use cond_expr::CondExpr;
 use derivative::Derivative;
 use if_expr::IfExpr;
@@ -7,55 +9,34 @@ use itertools::Itertools;
 use let_expr::LetExpr;
 use match_expr::MatchExpr;
 use newtype_macros_lib::ast_node;
+use origin::OriginId;
+use pattern_declaration::PatternDeclaration;
 use pretty::RcDoc as D;
 use serde_derive::Serialize;
+use syntax_error::SyntaxError;
+use union_declaration::UnionDeclaration;
 
 use crate::{
+    companion,
+    diagnostic::{Diagnostic, Severity},
     extends_result::ExtendsResult,
     parser::{Pair, ParserError, Rule},
-    pretty::{parens, string_literal, surround},
-    runtime::{self, builtin},
+    pretty::{
+        indent, jsdoc, parens, string_literal, style, surround, template_literal, trailing_comma,
+    },
+    runtime::{self, builtin, Deep},
     typescript,
 };
 
+#[cfg(test)]
+pub(crate) mod arbitrary;
+pub mod build;
+pub(crate) mod dl_type;
 pub(crate) mod errors;
 pub(crate) mod macros;
+pub mod visit;
 
-pub type Bindings = HashMap<String, Ast>;
-
-#[ast_node]
-pub struct Path {
-    pub segments: Vec<Ast>,
-}
-
-impl Path {
-    pub fn map<F>(&self, f: F) -> Self
-    where
-        F: Fn(&Ast) -> Ast,
-    {
-        Self {
-            span: self.span,
-            segments: self.segments.iter().map(f).collect(),
-        }
-    }
-
-    fn simplify(&self) -> Ast {
-        let mut acc = vec![];
-
-        for seg in self.segments.iter() {
-            if let Ast::Path(path) = seg {
-                acc.extend(path.segments.clone());
-            } else {
-                acc.push(seg.clone());
-            }
-        }
-
-        Ast::Path(Path {
-            span: self.span,
-            segments: acc,
-        })
-    }
-}
+pub type Bindings = HashMap<Ident, Ast>;
 
 #[ast_node]
 pub struct ExtendsExpr {
@@ -63,6 +44,10 @@ pub struct ExtendsExpr {
     pub rhs: Rc<Ast>,
     pub then_branch: Rc<Ast>,
     pub else_branch: Rc<Ast>,
+    /// Tested with `<:!`, emitted as `[lhs] extends [rhs]` so that it doesn't distribute over a
+    /// union `lhs`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub non_distributive: bool,
 }
 
 impl ExtendsExpr {
@@ -85,6 +70,7 @@ impl ExtendsExpr {
         rhs: Rc<Ast>,
         then_branch: Rc<Ast>,
         else_branch: Rc<Ast>,
+        non_distributive: bool,
     ) -> Self {
         if !lhs.is_typescript_feature() {
             dbg!(&lhs);
@@ -109,10 +95,82 @@ impl ExtendsExpr {
             rhs,
             then_branch,
             else_branch,
+            non_distributive,
         }
     }
 }
 
+impl ExtendsExpr {
+    /// The condition of the conditional type, `lhs extends rhs`, or `[lhs] extends [rhs]` when it
+    /// doesn't distribute.
+    fn condition_to_ts(&self) -> D<()> {
+        extends_to_ts(&self.lhs, &self.rhs, self.non_distributive)
+    }
+
+    /// A chain of conditional types, each one the else branch of the one before, rendered flat:
+    /// every arm on a line of its own, at the indentation of the first.
+    ///
+    /// ```ts
+    /// T extends 1 ? 'a'
+    /// : T extends 2 ? 'b'
+    /// : never
+    /// ```
+    fn chain_to_ts(&self) -> D<()> {
+        use typescript::Pretty as _;
+
+        let mut arms = vec![];
+        let mut expr = self;
+
+        let els = loop {
+            arms.push(
+                expr.condition_to_ts()
+                    .append(
+                        D::line()
+                            .append("?")
+                            .append(D::space())
+                            .append(expr.then_branch.to_ts())
+                            .nest(indent()),
+                    )
+                    .group(),
+            );
+
+            match expr.else_branch.as_ref() {
+                Ast::ExtendsExpr(next) => expr = next,
+                els => break els,
+            }
+        };
+
+        let sep = D::line().append(":").append(D::space());
+
+        D::intersperse(arms, sep.clone())
+            .append(sep)
+            .append(els.to_ts())
+    }
+}
+
+/// `lhs extends rhs`, each side wrapped in a tuple when the test is `non_distributive`, which
+/// TypeScript only distributes over a union that is a naked type parameter.
+fn extends_to_ts<'a>(lhs: &'a Ast, rhs: &'a Ast, non_distributive: bool) -> D<'a, ()> {
+    use typescript::Pretty as _;
+
+    let (lhs, rhs) = if non_distributive {
+        (
+            D::text("[").append(lhs.to_ts()).append("]"),
+            D::text("[").append(rhs.to_ts()).append("]"),
+        )
+    } else {
+        (
+            lhs.to_ts_operand(Precedence::Union),
+            rhs.to_ts_operand(Precedence::Union),
+        )
+    };
+
+    lhs.append(D::space())
+        .append("extends")
+        .append(D::space())
+        .append(rhs)
+}
+
 #[ast_node]
 pub struct Tuple {
     pub items: Vec<Ast>,
@@ -156,7 +214,11 @@ impl typescript::Pretty for ApplyGeneric {
 
         let generic_params = D::text("<").append(generic_inner).append(D::text(">"));
 
-        self.receiver.to_ts().append(generic_params)
+        // Only names can be applied in TypeScript, a callee binding more loosely than the
+        // application is parenthesized to keep its meaning even though it is rejected.
+        self.receiver
+            .to_ts_operand(Precedence::Postfix)
+            .append(generic_params)
     }
 }
 
@@ -208,6 +270,17 @@ impl TypeLiteral {
         self.properties.is_empty()
     }
 
+    /// The names of the properties, `keyof` the object, if they are all named.
+    pub fn literal_keys(&self) -> Option<Vec<&str>> {
+        self.properties
+            .iter()
+            .map(|property| match &property.key {
+                ObjectPropertyKey::Key(key) => Some(key.as_str()),
+                ObjectPropertyKey::Index(_) | ObjectPropertyKey::Computed(_) => None,
+            })
+            .collect()
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, ObjectProperty> {
         self.properties.iter()
     }
@@ -217,19 +290,68 @@ impl TypeLiteral {
     }
 }
 
+/// `import { A } from 'm'` with `keyword` in place of `import`.
+fn import_to_ts<'a>(import: &'a ImportStatement, keyword: &'static str) -> D<'a, ()> {
+    use typescript::Pretty as _;
+
+    D::text(keyword)
+        .append(D::space())
+        .append(import.import_clause.to_ts())
+        .append(D::space())
+        .append("from")
+        .append(D::space())
+        .append(string_literal(&import.module))
+}
+
+/// The members of a union separated by `|`, to be grouped, with a leading `|` when the group
+/// is broken if the style has one.
+fn union_to_ts(types: &[Ast]) -> D<()> {
+    use typescript::Pretty as _;
+
+    let sep = D::line().append(D::text("|")).append(D::space());
+    let leading = if style().leading_pipe {
+        D::text("| ").flat_alt(D::nil())
+    } else {
+        D::nil()
+    };
+
+    leading.append(D::intersperse(
+        types.iter().map(|t| match t {
+            Ast::IntersectionType(IntersectionType { .. }) => surround(t.to_ts(), "(", ")"),
+            _ => t.to_ts_operand(Precedence::Union),
+        }),
+        sep,
+    ))
+}
+
 impl typescript::Pretty for TypeLiteral {
     fn to_ts(&self) -> D<()> {
         let props = &self.properties;
 
-        let sep = D::text(",").append(D::line());
+        let separator = if style().semicolons { ";" } else { "," };
+        let sep = D::text(separator).append(D::line());
 
         let props = D::intersperse(props.iter().map(|prop| prop.to_ts()), sep);
 
+        let line = if style().bracket_spacing {
+            D::line()
+        } else {
+            D::line_()
+        };
+
+        if self.is_empty() {
+            return D::text("{}");
+        }
+
         D::nil()
             .append("{")
-            .append(D::line_())
-            .append(props.nest(4))
-            .append(D::line_())
+            .append(
+                line.clone()
+                    .append(props)
+                    .append(D::text(separator).flat_alt(D::nil()))
+                    .nest(indent()),
+            )
+            .append(line)
             .append(D::text("}"))
             .group()
     }
@@ -237,7 +359,15 @@ impl typescript::Pretty for TypeLiteral {
 
 #[ast_node]
 pub struct Interface {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
     pub export: bool,
+    /// Declared with `global`, emitted in a `declare global` block.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub global: bool,
+    /// Declared with `export default`, the default export of its module.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
     pub name: String,
     pub extends: Option<String>,
     pub params: Vec<TypeParameter>,
@@ -247,7 +377,9 @@ pub struct Interface {
 impl typescript::Pretty for Interface {
     fn to_ts(&self) -> D<()> {
         let Interface {
+            doc,
             export,
+            default,
             name,
             extends,
             params,
@@ -255,7 +387,14 @@ impl typescript::Pretty for Interface {
             ..
         } = self;
 
-        let doc = if *export {
+        let comment = match doc {
+            Some(doc) => jsdoc(doc),
+            None => D::nil(),
+        };
+
+        let doc = if *default {
+            D::text("export default").append(D::space())
+        } else if *export {
             D::text("export").append(D::space())
         } else {
             D::nil()
@@ -265,8 +404,7 @@ impl typescript::Pretty for Interface {
             Some(extends) => D::space()
                 .append("extends")
                 .append(D::space())
-                .append(extends)
-                .append(D::space()),
+                .append(extends),
             None => D::nil(),
         };
 
@@ -279,7 +417,13 @@ impl typescript::Pretty for Interface {
                     D::intersperse(list.iter().map(|param| param.to_ts().group()), seperator);
 
                 D::text("<")
-                    .append(D::line_().append(params_body).append(D::line_()).nest(4))
+                    .append(
+                        D::line_()
+                            .append(params_body)
+                            .append(trailing_comma())
+                            .nest(indent()),
+                    )
+                    .append(D::line_())
                     .append(D::text(">"))
                     .group()
             }
@@ -292,7 +436,7 @@ impl typescript::Pretty for Interface {
 
             let body = D::intersperse(body, D::text(";").append(D::hardline())).append(";");
 
-            let body = D::hardline().append(body).nest(4);
+            let body = D::hardline().append(body).nest(indent());
 
             D::nil()
                 .append("{")
@@ -301,14 +445,17 @@ impl typescript::Pretty for Interface {
                 .append("}")
         };
 
-        doc.append("interface")
+        let doc = doc
+            .append("interface")
             .append(D::space())
             .append(name)
             .append(params_doc)
             .append(extends)
             .append(D::space())
             .append(body)
-            .group()
+            .group();
+
+        comment.append(doc)
     }
 }
 
@@ -324,32 +471,70 @@ pub struct MacroCall {
     pub args: Vec<Ast>,
 }
 
-impl MacroCall {
-    fn eval(&self) -> Ast {
-        let name = self.name.strip_suffix("!").unwrap();
+#[ast_node]
+pub struct FunctionType {
+    pub params: Vec<Parameter>,
+    /// `boolean` for a type predicate and `void` for an assertion.
+    pub return_type: Rc<Ast>,
+    /// The type predicate written in place of the return type.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<TypePredicate>,
+}
+
+/// `x is T`, the function returns `true` when its parameter `x` is a `T`, or `asserts x is T`,
+/// it only returns if `x` is a `T`. `asserts x` only returns if `x` is truthy.
+#[ast_node]
+pub struct TypePredicate {
+    pub asserts: bool,
+    pub param: Ident,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<Rc<Ast>>,
+}
 
-        match name {
-            "dbg!" => match self.args.as_slice() {
-                [node] => builtin::dbg(node.to_owned()),
-                _ => panic!("dbg! expects exactly one argument"),
-            },
-            "assert_equal" => match self.args.as_slice() {
-                [lhs, rhs] => builtin::assert_equal(lhs.to_owned(), rhs.to_owned()),
-                _ => panic!("assert_equal! expects exactly two arguments"),
-            },
-            "unquote" => match self.args.as_slice() {
-                [node] => builtin::unquote(node.to_owned()),
-                _ => panic!("unquote! expects exactly one argument"),
-            },
-            id => unimplemented!("macro {} not implemented", id),
+impl FunctionType {
+    /// The type the predicate narrows its parameter to, if the function has one.
+    pub fn predicate_target(&self) -> Option<&Ast> {
+        self.predicate.as_ref()?.target.as_deref()
+    }
+
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&Ast) -> Ast,
+    {
+        Self {
+            params: self
+                .params
+                .iter()
+                .map(|param| Parameter {
+                    kind: f(&param.kind),
+                    ..param.clone()
+                })
+                .collect(),
+            return_type: f(&self.return_type).into(),
+            predicate: self.predicate.as_ref().map(|predicate| TypePredicate {
+                target: predicate.target.as_deref().map(|target| f(target).into()),
+                ..predicate.clone()
+            }),
+            span: self.span,
         }
     }
 }
 
-#[ast_node]
-pub struct FunctionType {
-    pub params: Vec<Parameter>,
-    pub return_type: Rc<Ast>,
+impl typescript::Pretty for TypePredicate {
+    fn to_ts(&self) -> D<()> {
+        let asserts = if self.asserts {
+            D::text("asserts ")
+        } else {
+            D::nil()
+        };
+
+        let predicate = asserts.append(self.param.to_ts());
+
+        match &self.target {
+            Some(target) => predicate.append(" is ").append(target.to_ts()),
+            None => predicate,
+        }
+    }
 }
 
 impl typescript::Pretty for FunctionType {
@@ -360,7 +545,10 @@ impl typescript::Pretty for FunctionType {
 
         let params = D::text("(").append(params).append(D::text(")")).group();
 
-        let return_type = self.return_type.to_ts();
+        let return_type = match &self.predicate {
+            Some(predicate) => predicate.to_ts(),
+            None => self.return_type.to_ts(),
+        };
 
         params
             .append(D::space())
@@ -393,22 +581,40 @@ impl typescript::Pretty for Parameter {
     }
 }
 
+/// `lhs[rhs]`, `lhs.rhs` or `lhs::rhs`.
 #[ast_node]
 pub struct Access {
     pub lhs: Rc<Ast>,
     pub rhs: Rc<Ast>,
-    pub is_dot: bool,
+    pub kind: AccessKind,
+}
+
+/// How the `rhs` of an [`Access`] is written, which is a type only for an index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessKind {
+    /// `T["a"]`, a property of a type.
+    Index,
+    /// `T.a`, a property of a type named by an identifier, the same as `T["a"]`.
+    Dot,
+    /// `A::B`, a type declared in a namespace, emitted as `A.B`.
+    Namespace,
 }
 
 impl Access {
+    /// Maps the `lhs`, and the `rhs` if it is a type. The names after a `.` or a `::` are not
+    /// references, so passes that resolve names don't see them.
     fn map<F>(&self, f: F) -> Self
     where
         F: Fn(&Ast) -> Ast,
     {
         Self {
             lhs: f(&self.lhs).into(),
-            rhs: f(&self.rhs).into(),
-            is_dot: self.is_dot,
+            rhs: match self.kind {
+                AccessKind::Index => f(&self.rhs).into(),
+                AccessKind::Dot | AccessKind::Namespace => self.rhs.clone(),
+            },
+            kind: self.kind,
             span: self.span,
         }
     }
@@ -466,11 +672,66 @@ impl UnionType {
     }
 }
 
+/// Removes the members of a union that add nothing to it: nested unions are flattened, then
+/// `never`, duplicates, and literals of a primitive type that is itself a member are dropped, so
+/// `"a" | "a" | string` is `string`. Only `never` is left when every member is `never`.
+///
+/// Members are compared regardless of their spans. A member that mentions a name is kept as it
+/// is, what the name stands for isn't known.
+pub fn normalize_union(members: Vec<Ast>) -> Vec<Ast> {
+    let members: Vec<Ast> = members
+        .into_iter()
+        .flat_map(|member| match member {
+            Ast::UnionType(union) => normalize_union(union.types),
+            member => vec![member],
+        })
+        .collect();
+
+    // Each member is compared with every other, generated unions often repeat many. Interned,
+    // the duplicates are dropped by address, only members with a `unique symbol`, which are never
+    // shared, are left to compare.
+    let members = consed::Consed::default().distinct(members);
+
+    let Some(never) = members.first().cloned() else {
+        return members;
+    };
+
+    if members
+        .iter()
+        .all(|member| matches!(member, Ast::NeverKeyword(_)))
+    {
+        return vec![never];
+    }
+
+    let mut normalized: Vec<Ast> = vec![];
+
+    for member in &members {
+        if matches!(member, Ast::NeverKeyword(_))
+            || (member.has_unique_symbol() && normalized.contains(member))
+        {
+            continue;
+        }
+
+        let subsumed =
+            !member.mentions_names() && members.iter().any(|other| other.subsumes(member));
+
+        if !subsumed {
+            normalized.push(member.clone());
+        }
+    }
+
+    normalized
+}
+
 #[ast_node]
 pub struct ExtendsInfixOp {
     pub lhs: Rc<Ast>,
     pub op: InfixOp,
     pub rhs: Rc<Ast>,
+    /// Written `<:!` or `</:!`, the conditional type it lowers to doesn't distribute, see
+    /// [`ExtendsExpr::non_distributive`].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub non_distributive: bool,
 }
 
 impl ExtendsInfixOp {
@@ -510,9 +771,30 @@ pub struct ImportStatement {
     pub module: String,
 }
 
+/// `import("./m")`, the module `./m` as a type, whose exports are accessed with `::`, e.g.
+/// `import("./m")::Foo(T)`.
+#[ast_node]
+pub struct ImportType {
+    pub module: String,
+}
+
+/// `export default A`, making a declaration of the module its default export.
+#[ast_node]
+pub struct ExportDefault {
+    pub name: Ident,
+}
+
 #[ast_node]
 pub struct TypeAlias {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
     pub export: bool,
+    /// Declared with `global`, emitted in a `declare global` block.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub global: bool,
+    /// Declared with `export default`, the default export of its module.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
     pub name: Ident,
     pub params: Vec<TypeParameter>,
     pub body: Rc<Ast>,
@@ -531,11 +813,87 @@ impl TypeAlias {
     }
 }
 
+/// How [`Ast::traverse_ctl`] goes on from a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    /// Into the children of the node.
+    Continue,
+    /// Past the children of the node, to its next sibling.
+    Skip,
+    /// Nowhere, the walk is over.
+    Break,
+}
+
+/// Version of the JSON shape of the nodes, see [`Ast::to_json`] and [`schema`], bumped when it
+/// changes so that the tools reading it can tell. A program without a version has the bindings of
+/// its `let`s as an object keyed by name; version 1 has its version in the program rather than at
+/// the root, and `true` as `unknown`.
+pub const AST_VERSION: u32 = 2;
+
 #[ast_node]
 pub struct Program {
     pub statements: Vec<Ast>,
 }
 
+/// A statement of a program, the node it declares and the attributes written before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub attributes: Vec<Attribute>,
+    pub inner: Rc<Ast>,
+}
+
+impl Statement {
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&Ast) -> Ast,
+    {
+        Self {
+            attributes: self.attributes.clone(),
+            inner: f(&self.inner).into(),
+        }
+    }
+}
+
+impl Deref for Statement {
+    type Target = Ast;
+
+    fn deref(&self) -> &Ast {
+        &self.inner
+    }
+}
+
+impl AsRef<Ast> for Statement {
+    fn as_ref(&self) -> &Ast {
+        &self.inner
+    }
+}
+
+impl From<Ast> for Statement {
+    fn from(inner: Ast) -> Self {
+        Self {
+            attributes: vec![],
+            inner: inner.into(),
+        }
+    }
+}
+
+/// A statement without attributes is serialized as the node it declares, as it was before
+/// statements had attributes.
+impl serde::Serialize for Statement {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct as _;
+
+        if self.attributes.is_empty() {
+            return self.inner.serialize(serializer);
+        }
+
+        let mut statement = serializer.serialize_struct("Statement", 2)?;
+        statement.serialize_field("attributes", &self.attributes)?;
+        statement.serialize_field("inner", &self.inner)?;
+        statement.end()
+    }
+}
+
 impl Program {
     pub fn map<F>(&self, f: F) -> Self
     where
@@ -548,6 +906,52 @@ impl Program {
     }
 }
 
+/// `declare module "express" do ... end`, declarations of the module of that name rather than of
+/// the program, such as the properties an augmentation adds to an interface of a library. The
+/// declarations of the program are in scope of the block, its own aren't outside of it.
+#[ast_node]
+pub struct AmbientModule {
+    /// The name of the module, unquoted.
+    pub name: String,
+    pub body: Vec<Ast>,
+}
+
+impl AmbientModule {
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&Ast) -> Ast,
+    {
+        Self {
+            name: self.name.clone(),
+            body: self.body.iter().map(f).collect(),
+            span: self.span,
+        }
+    }
+}
+
+/// The declarations a simplified statement of a program or a `declare module` block stands for.
+/// Each union declaration is replaced by the declarations of its variants, and each brand by an
+/// alias. Pattern synonyms are expanded before simplifying, see
+/// [`pattern_declaration::Patterns`], and declare nothing.
+fn declarations(statement: Ast, helpers: &runtime::Helpers) -> Vec<Ast> {
+    let Ast::Statement(ref inner) = statement else {
+        return vec![statement];
+    };
+
+    match inner.as_ref() {
+        Ast::UnionDeclaration(union) => union
+            .simplify()
+            .into_iter()
+            .map(|alias| alias.with_attributes(&inner.attributes))
+            .collect(),
+        Ast::BrandDeclaration(brand) => vec![brand
+            .simplify(helpers.brands())
+            .with_attributes(&inner.attributes)],
+        Ast::PatternDeclaration(_) => vec![],
+        _ => vec![statement],
+    }
+}
+
 #[ast_node]
 pub enum Ast {
     #[serde(rename(serialize = "."))]
@@ -572,30 +976,39 @@ pub enum Ast {
     IfExpr(IfExpr),
     #[serde(rename = "import")]
     ImportStatement(ImportStatement),
+    #[serde(rename = "import-type")]
+    ImportType(ImportType),
     #[serde(rename = "let")]
     LetExpr(LetExpr),
     MappedType(MappedType),
     #[serde(rename = "match")]
     MatchExpr(MatchExpr),
-    #[serde(rename = "::")]
-    Path(Path),
     #[serde(rename = "number")]
     TypeNumber(TypeNumber),
     TypeLiteral(TypeLiteral),
     #[ast_node(span)]
     Primitive(PrimitiveType),
     Program(Program),
-    Statement(Rc<Ast>),
+    Statement(Statement),
     UnitTest(UnitTest),
+    #[serde(rename(serialize = "declare-module"))]
+    AmbientModule(AmbientModule),
     TypeString(TypeString),
     TemplateString(TemplateString),
     Tuple(Tuple),
     #[serde(rename(serialize = "type"))]
     TypeAlias(TypeAlias),
+    #[serde(rename(serialize = "union"))]
+    UnionDeclaration(UnionDeclaration),
+    #[serde(rename(serialize = "brand"))]
+    BrandDeclaration(BrandDeclaration),
+    #[serde(rename(serialize = "pattern"))]
+    PatternDeclaration(PatternDeclaration),
+    #[serde(rename(serialize = "export-default"))]
+    ExportDefault(ExportDefault),
     #[serde(rename(serialize = "never"))]
     #[ast_node(span)]
     NeverKeyword(),
-    #[serde(rename(serialize = "unknown"))]
     #[ast_node(span)]
     TrueKeyword(),
     #[ast_node(span)]
@@ -607,13 +1020,39 @@ pub enum Ast {
     #[serde(rename = "any")]
     #[ast_node(span)]
     AnyKeyword(),
+    #[serde(rename = "this")]
+    #[ast_node(span)]
+    ThisType(),
     #[ast_node(span)]
     NoOp(),
+    #[serde(rename(serialize = "error"))]
+    SyntaxError(SyntaxError),
 }
 
 impl Ast {
+    /// The node as an s-expression, each struct being an association list of its fields but the
+    /// `let`s, written `(let ((a value) (b value)) body)`.
     pub fn to_sexp(&self) -> serde_lexpr::Result<serde_lexpr::Value> {
-        serde_lexpr::to_value(self)
+        serde_lexpr::to_value(self).map(let_expr::let_forms)
+    }
+
+    /// [`Ast::to_sexp`], the nodes a desugaring pass built having an `origin` field, the
+    /// backtrace of where they come from, innermost first, such as "in the 2nd arm of this
+    /// `match`".
+    pub fn to_sexp_verbose(&self) -> serde_lexpr::Result<serde_lexpr::Value> {
+        origin::verbose(|| self.to_sexp())
+    }
+
+    /// The node as JSON. A program carries the [`AST_VERSION`] of the shape of its nodes, as the
+    /// `schema_version` of the root object.
+    pub fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self)?;
+
+        if let (Ast::Program(_), Some(root)) = (self, value.as_object_mut()) {
+            root.insert("schema_version".to_string(), AST_VERSION.into());
+        }
+
+        Ok(value)
     }
 
     pub fn map<F>(&self, f: F) -> Self
@@ -645,40 +1084,151 @@ impl Ast {
 
             Ast::MatchExpr(expr) => Ast::MatchExpr(expr.map(f)),
 
-            Ast::Path(expr) => Ast::Path(expr.map(f)),
-
             Ast::TypeLiteral(expr) => Ast::TypeLiteral(expr.map(f)),
 
             Ast::Program(expr) => Ast::Program(expr.map(f)),
 
-            Ast::Statement(node) => Ast::Statement(f(node).into()),
+            Ast::Statement(statement) => Ast::Statement(statement.map(f)),
+
+            Ast::AmbientModule(module) => Ast::AmbientModule(module.map(f)),
 
             Ast::Tuple(expr) => Ast::Tuple(expr.map(f)),
 
             Ast::TypeAlias(expr) => Ast::TypeAlias(expr.map(f)),
 
+            Ast::UnionDeclaration(expr) => Ast::UnionDeclaration(expr.map(f)),
+
+            Ast::BrandDeclaration(expr) => Ast::BrandDeclaration(expr.map(f)),
+
+            Ast::PatternDeclaration(expr) => Ast::PatternDeclaration(expr.map(f)),
+
             Ast::UnionType(expr) => Ast::UnionType(expr.map(f)),
 
             Ast::IntersectionType(expr) => Ast::IntersectionType(expr.map(f)),
 
+            Ast::FunctionType(function) => Ast::FunctionType(function.map(f)),
+
             _ => self.clone(),
         }
     }
 
+    /// Simplifies the tree with the builtin macros, panicking if a macro call fails.
     pub fn simplify(&self) -> Self {
-        let bindings: Bindings = Default::default();
+        self.simplify_with_macros(&runtime::MacroRegistry::default(), BrandEncoding::default())
+            .map(|(simplified, _)| simplified)
+            .unwrap_or_else(|diagnostics| panic!("{}", diagnostics[0].message))
+    }
+
+    /// Simplifies the tree, expanding calls with the macros of `macros`, together with the
+    /// warnings and notes the macros reported. Fails with every diagnostic if a call can't be
+    /// expanded. Brands are tagged as `brands` says.
+    pub fn simplify_with_macros(
+        &self,
+        macros: &runtime::MacroRegistry,
+        brands: BrandEncoding,
+    ) -> Result<(Self, Vec<Diagnostic>), Vec<Diagnostic>> {
+        self.simplify_with_helpers(&runtime::Helpers::new(self, macros).with_brands(brands))
+    }
+
+    /// [`Ast::simplify_with_macros`], with helpers set up by the caller.
+    pub fn simplify_with_helpers(
+        &self,
+        helpers: &runtime::Helpers,
+    ) -> Result<(Self, Vec<Diagnostic>), Vec<Diagnostic>> {
+        let simplified = self.simplify_with(helpers);
+
+        let diagnostics = helpers.take_diagnostics();
+
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            Err(diagnostics)
+        } else {
+            Ok((simplified, diagnostics))
+        }
+    }
 
-        let identity = |node, ctx| (node, ctx);
+    /// Simplifies the tree, the helpers the macros use being declared at the end of the program.
+    pub(crate) fn simplify_with(&self, helpers: &runtime::Helpers) -> Self {
+        // The spans of the arms the node is in, each with its origin.
+        let arms: Vec<Span> = vec![];
+
+        let tag_arms = |ast, mut arms: Vec<Span>| match ast {
+            Ast::MatchExpr(mut match_expr) => {
+                let parent = helpers.origin_of(&arms, match_expr.span);
+
+                for (index, arm) in match_expr.arms.iter_mut().enumerate() {
+                    arm.span = arm
+                        .span
+                        .with_origin(OriginId::new(origin::Origin::MatchArm { index }, parent));
+                    arms.push(arm.span);
+                }
+
+                (Ast::MatchExpr(match_expr), arms)
+            }
+            Ast::CondExpr(mut cond_expr) => {
+                let parent = helpers.origin_of(&arms, cond_expr.span);
+
+                for (index, arm) in cond_expr.arms.iter_mut().enumerate() {
+                    arm.span = arm
+                        .span
+                        .with_origin(OriginId::new(origin::Origin::CondArm { index }, parent));
+                    arms.push(arm.span);
+                }
+
+                (Ast::CondExpr(cond_expr), arms)
+            }
+            ast => (ast, arms),
+        };
 
-        let (tree, _) = self.traverse(bindings, &identity, &|ast, ctx| {
+        let (tree, _) = self.traverse(arms, &tag_arms, &|ast, ctx| {
             let span = ast.as_span();
 
             match ast {
-                Ast::IfExpr(if_expr) => (if_expr.simplify(), ctx),
+                Ast::IfExpr(if_expr) if !if_expr::check_condition(&if_expr.condition, helpers) => {
+                    (Ast::NeverKeyword(span), ctx)
+                }
+                Ast::IfExpr(if_expr) => (if_expr.simplify(helpers.missing_else()), ctx),
                 Ast::MatchExpr(match_expr) => (match_expr.simplify(), ctx),
-                Ast::CondExpr(cond_expr) => (cond_expr.simplify(), ctx),
+                // Each arm is checked, to report every one that isn't a condition.
+                Ast::CondExpr(cond_expr)
+                    if !cond_expr.arms.iter().fold(true, |checked, arm| {
+                        if_expr::check_condition(&arm.condition, helpers) && checked
+                    }) =>
+                {
+                    (Ast::NeverKeyword(span), ctx)
+                }
+                Ast::CondExpr(cond_expr) => (cond_expr.simplify(helpers.missing_else()), ctx),
                 Ast::LetExpr(let_expr) => (let_expr.simplify(), ctx),
-                Ast::Path(path) => (path.simplify(), ctx),
+                Ast::MacroCall(call) => {
+                    let origin = helpers.origin_of(&ctx, call.span);
+                    (helpers.expand_in(&call, origin), ctx)
+                }
+                Ast::Program(Program { statements, span }) => {
+                    let statements = statements
+                        .into_iter()
+                        .flat_map(|statement| declarations(statement, helpers))
+                        .chain(helpers.take_declarations())
+                        .map(attribute::deprecate)
+                        .collect();
+
+                    (Ast::Program(Program { statements, span }), ctx)
+                }
+                // The helpers the macros of the block use are declared at the end of the program,
+                // which the block sees.
+                Ast::AmbientModule(AmbientModule { name, body, span }) => {
+                    let body = body
+                        .into_iter()
+                        .flat_map(|statement| declarations(statement, helpers))
+                        .map(attribute::deprecate)
+                        .collect();
+
+                    (Ast::AmbientModule(AmbientModule { name, body, span }), ctx)
+                }
+                // Unit tests are checked, not emitted.
+                Ast::UnitTest(_) => (Ast::noop(), ctx),
+                Ast::Access(access) => (access.simplify(helpers), ctx),
                 Ast::UnionType(UnionType { types, .. }) => match types.as_slice() {
                     // Flatten nested union types (both)
                     [Ast::UnionType(UnionType {
@@ -739,6 +1289,185 @@ impl Ast {
         tree
     }
 
+    /// Drops the statements of a program that were replaced by [`Ast::noop`].
+    pub fn remove_noops(&self) -> Self {
+        match self {
+            Ast::Program(Program { statements, span }) => Ast::Program(Program {
+                statements: statements
+                    .iter()
+                    .filter(|statement| !statement.is_noop())
+                    .cloned()
+                    .collect(),
+                span: *span,
+            }),
+            _ => self.clone(),
+        }
+    }
+
+    /// Merges the adjacent arms of a chain of conditional types that test the same type and have
+    /// the same body, `T extends A ? X : T extends B ? X : Y` becoming `T extends A | B ? X : Y`.
+    ///
+    /// This isn't done by default: `T extends A | B` also holds for a type that extends the union
+    /// but neither pattern, such as `A | B` itself when `T` isn't a distributed type parameter.
+    /// Patterns that infer types are never merged, their bindings are only in scope of their arm.
+    pub fn merge_arms(&self) -> Self {
+        let (tree, _) = self.postwalk((), &|ast, ctx| {
+            let Ast::ExtendsExpr(outer) = &ast else {
+                return (ast, ctx);
+            };
+
+            let Ast::ExtendsExpr(inner) = outer.else_branch.as_ref() else {
+                return (ast, ctx);
+            };
+
+            if outer.lhs != inner.lhs
+                || outer.non_distributive != inner.non_distributive
+                || outer.then_branch != inner.then_branch
+                || outer.rhs.infers()
+                || inner.rhs.infers()
+            {
+                return (ast, ctx);
+            }
+
+            let types = [&outer.rhs, &inner.rhs]
+                .into_iter()
+                .map(|pattern| pattern.as_ref().clone())
+                .collect();
+
+            let ast = Ast::ExtendsExpr(ExtendsExpr {
+                lhs: outer.lhs.clone(),
+                rhs: Ast::UnionType(UnionType {
+                    types: normalize_union(types),
+                    span: outer.rhs.as_span().merge(&inner.rhs.as_span()),
+                })
+                .into(),
+                then_branch: outer.then_branch.clone(),
+                else_branch: inner.else_branch.clone(),
+                non_distributive: outer.non_distributive,
+                span: outer.span,
+            });
+
+            (ast, ctx)
+        });
+
+        tree
+    }
+
+    /// Writes the conditional types filtering a type parameter with the utility types of
+    /// TypeScript, `T extends U ? T : never` becoming `Extract<T, U>` and `T extends U ? never : T`
+    /// becoming `Exclude<T, U>`.
+    ///
+    /// Only a test of a type parameter of the declaration is rewritten: `Extract` and `Exclude`
+    /// distribute over a union argument, as a conditional type does over a type parameter but not
+    /// over any other type, nor when it is non-distributive. Patterns that infer types are kept,
+    /// `infer` is only allowed in the `extends` clause. A program declaring its own `Extract` or
+    /// `Exclude` is left as is.
+    pub fn extract_exclude(&self) -> Self {
+        let Ast::Program(program) = self else {
+            return self.clone();
+        };
+
+        let declares_utility = program.statements.iter().any(|statement| {
+            let name = match statement {
+                Ast::Statement(inner) => match inner.as_ref() {
+                    Ast::TypeAlias(alias) => alias.name.name.as_str(),
+                    Ast::Interface(interface) => interface.name.as_str(),
+                    _ => return false,
+                },
+                _ => return false,
+            };
+
+            name == "Extract" || name == "Exclude"
+        });
+
+        if declares_utility {
+            return self.clone();
+        }
+
+        Ast::Program(program.map(|statement| {
+            let params: &[TypeParameter] = match statement {
+                Ast::Statement(inner) => match inner.as_ref() {
+                    Ast::TypeAlias(alias) => &alias.params,
+                    Ast::Interface(interface) => &interface.params,
+                    _ => &[],
+                },
+                _ => &[],
+            };
+
+            let (tree, _) = statement.postwalk((), &|ast, ctx| {
+                let Ast::ExtendsExpr(expr) = &ast else {
+                    return (ast, ctx);
+                };
+
+                let is_param = matches!(
+                    expr.lhs.as_ref(),
+                    Ast::Ident(ident) if params.iter().any(|param| param.name.name == ident.name)
+                );
+
+                if !is_param || expr.non_distributive || expr.rhs.infers() {
+                    return (ast, ctx);
+                }
+
+                let utility = match (expr.then_branch.as_ref(), expr.else_branch.as_ref()) {
+                    (then_branch, Ast::NeverKeyword(_)) if *then_branch == *expr.lhs => "Extract",
+                    (Ast::NeverKeyword(_), else_branch) if *else_branch == *expr.lhs => "Exclude",
+                    _ => return (ast, ctx),
+                };
+
+                let ast = Ast::ApplyGeneric(ApplyGeneric {
+                    receiver: Ast::Ident(Ident {
+                        name: utility.to_string(),
+                        span: expr.span,
+                        raw: false,
+                    })
+                    .into(),
+                    args: vec![(*expr.lhs).clone(), (*expr.rhs).clone()],
+                    span: expr.span,
+                });
+
+                (ast, ctx)
+            });
+
+            tree
+        }))
+    }
+
+    /// Whether `self` is a wider member of a union than `other`, which is a literal of its
+    /// primitive type or a `unique symbol`, or any type when `self` is `unknown` or `any`. Of the
+    /// two top types, `any` wins: `any | unknown` is `any`.
+    fn subsumes(&self, other: &Ast) -> bool {
+        match (self, other) {
+            (Ast::AnyKeyword(_), other) => !matches!(other, Ast::AnyKeyword(_)),
+            (Ast::UnknownKeyword(_), other) => {
+                !matches!(other, Ast::UnknownKeyword(_) | Ast::AnyKeyword(_))
+            }
+            (
+                Ast::Primitive(PrimitiveType::String, _),
+                Ast::TypeString(_) | Ast::TemplateString(_),
+            )
+            | (Ast::Primitive(PrimitiveType::Number, _), Ast::TypeNumber(_))
+            | (
+                Ast::Primitive(PrimitiveType::Symbol, _),
+                Ast::Primitive(PrimitiveType::UniqueSymbol, _),
+            )
+            | (
+                Ast::Primitive(PrimitiveType::Boolean, _),
+                Ast::TrueKeyword(_) | Ast::FalseKeyword(_),
+            ) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether a name is mentioned anywhere in the type.
+    fn mentions_names(&self) -> bool {
+        self.any(|node| matches!(node, Ast::Ident(_)))
+    }
+
+    /// Whether the type declares an inferred type anywhere in it.
+    fn infers(&self) -> bool {
+        self.any(|node| matches!(node, Ast::Infer(_)))
+    }
+
     pub fn prewalk<Context, F>(&self, ctx: Context, pre: &F) -> (Self, Context)
     where
         Context: Clone,
@@ -777,6 +1506,97 @@ impl Ast {
         (ast, acc)
     }
 
+    /// Like [`Ast::traverse`], the pre hook also telling how the walk goes on from the node:
+    /// [`Walk::Skip`] leaves its children as they are, the post hook still seeing the node, and
+    /// [`Walk::Break`] stops the walk, leaving the rest of the tree as it is without calling any
+    /// hook.
+    pub fn traverse_ctl<Context, Pre, Post>(
+        &self,
+        ctx: Context,
+        pre: &Pre,
+        post: &Post,
+    ) -> (Self, Context)
+    where
+        Context: Clone,
+        Pre: Fn(Self, Context) -> (Self, Context, Walk),
+        Post: Fn(Self, Context) -> (Self, Context),
+    {
+        self.traverse_until(ctx, pre, post, &std::cell::Cell::new(false))
+    }
+
+    fn traverse_until<Context, Pre, Post>(
+        &self,
+        ctx: Context,
+        pre: &Pre,
+        post: &Post,
+        stopped: &std::cell::Cell<bool>,
+    ) -> (Self, Context)
+    where
+        Context: Clone,
+        Pre: Fn(Self, Context) -> (Self, Context, Walk),
+        Post: Fn(Self, Context) -> (Self, Context),
+    {
+        let (ast, ctx, walk) = pre(self.clone(), ctx);
+
+        let ast = match walk {
+            Walk::Continue => ast.map(|child| {
+                if stopped.get() {
+                    child.clone()
+                } else {
+                    child.traverse_until(ctx.clone(), pre, post, stopped).0
+                }
+            }),
+            Walk::Skip => ast,
+            Walk::Break => {
+                stopped.set(true);
+                return (ast, ctx);
+            }
+        };
+
+        if stopped.get() {
+            return (ast, ctx);
+        }
+
+        post(ast, ctx)
+    }
+
+    /// Whether a node of the tree satisfies `pred`, the walk stopping at the first one.
+    pub fn any(&self, pred: impl Fn(&Ast) -> bool) -> bool {
+        self.find_map(|node| pred(node).then_some(())).is_some()
+    }
+
+    /// The first value `f` returns for a node of the tree, in prefix order, the walk stopping
+    /// there.
+    pub fn find_map<T>(&self, f: impl Fn(&Ast) -> Option<T>) -> Option<T> {
+        let found = std::cell::RefCell::new(None);
+
+        self.traverse_ctl(
+            (),
+            &|node, ctx| match f(&node) {
+                Some(value) => {
+                    found.replace(Some(value));
+                    (node, ctx, Walk::Break)
+                }
+                None => (node, ctx, Walk::Continue),
+            },
+            &|node, ctx| (node, ctx),
+        );
+
+        found.into_inner()
+    }
+
+    /// Number of nodes visited by a traversal of the tree.
+    pub fn node_count(&self) -> usize {
+        let count = std::cell::Cell::new(0);
+
+        self.prewalk((), &|node, ctx| {
+            count.set(count.get() + 1);
+            (node, ctx)
+        });
+
+        count.get()
+    }
+
     /// Operators that the `not` prefix operator can be applied to.
     pub fn is_compatible_with_not_prefix_op(&self) -> bool {
         match self {
@@ -790,6 +1610,14 @@ impl Ast {
         matches!(self, Ast::AnyKeyword(_) | Ast::UnknownKeyword(_))
     }
 
+    /// The keys of an object literal whose properties are all named.
+    pub fn literal_keys(&self) -> Option<Vec<&str>> {
+        match self {
+            Ast::TypeLiteral(literal) => literal.literal_keys(),
+            _ => None,
+        }
+    }
+
     pub fn is_empty_object(&self) -> bool {
         matches!(self, Ast::TypeLiteral(TypeLiteral { properties, .. }) if properties.is_empty())
     }
@@ -901,7 +1729,7 @@ impl Ast {
                     P::Number => "Number",
                     P::String => "String",
                     P::Object => "Object",
-                    P::Symbol => "Symbol",
+                    P::Symbol | P::UniqueSymbol => "Symbol",
                     P::BigInt => "BigInt",
                     P::Void | P::Undefined | P::Null => return None,
                 }
@@ -912,6 +1740,7 @@ impl Ast {
         Some(Ast::Ident(Ident {
             name: name.to_string(),
             span: self.as_span(),
+            raw: false,
         }))
     }
 
@@ -925,8 +1754,8 @@ impl Ast {
                 | Ast::Ident(_)
                 | Ast::Infer { .. }
                 | Ast::ImportStatement { .. }
+                | Ast::ImportType(_)
                 | Ast::MappedType(_)
-                | Ast::Path(_)
                 | Ast::NeverKeyword(_)
                 | Ast::TypeNumber(_)
                 | Ast::TypeLiteral(_)
@@ -943,6 +1772,7 @@ impl Ast {
                 | Ast::UnionType { .. }
                 | Ast::Access { .. }
                 | Ast::AnyKeyword(_)
+                | Ast::ThisType(_)
                 | Ast::ApplyGeneric(_)
                 | Ast::Array(_)
                 | Ast::FunctionType(_)
@@ -966,6 +1796,22 @@ impl Ast {
         matches!(self, Self::ExtendsInfixOp { .. })
     }
 
+    /// Marker left in place of a node a pass removes. Statements replaced by it are dropped from
+    /// the program by [`Ast::remove_noops`].
+    pub fn noop() -> Self {
+        Ast::NoOp(Span::default())
+    }
+
+    /// Returns `true` if the ast is [`Ast::NoOp`], or a statement of one.
+    #[must_use]
+    pub fn is_noop(&self) -> bool {
+        match self {
+            Ast::NoOp(_) => true,
+            Ast::Statement(inner) => inner.is_noop(),
+            _ => false,
+        }
+    }
+
     /// Returns `true` if the ast is [`InfixOp`].
     ///
     /// [`InfixOp`]: Ast::InfixOp
@@ -989,6 +1835,64 @@ impl Ast {
         matches!(self, Self::Ident(_))
     }
 
+    /// Whether this is a declaration marked `global`.
+    #[must_use]
+    pub fn is_global(&self) -> bool {
+        match self {
+            Self::Statement(inner) => inner.is_global(),
+            Self::TypeAlias(TypeAlias { global, .. })
+            | Self::Interface(Interface { global, .. }) => *global,
+            _ => false,
+        }
+    }
+
+    /// The attributes written before a statement, none for any other node.
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Self::Statement(statement) => &statement.attributes,
+            _ => &[],
+        }
+    }
+
+    /// The statement with `attributes` added to its own, for the statements a declaration is
+    /// replaced by.
+    #[must_use]
+    pub fn with_attributes(self, attributes: &[Attribute]) -> Self {
+        match self {
+            Self::Statement(mut statement) => {
+                statement.attributes.extend_from_slice(attributes);
+                Self::Statement(statement)
+            }
+            node => node,
+        }
+    }
+
+    /// The doc comment of a declaration.
+    pub fn doc(&self) -> Option<&str> {
+        match self {
+            Self::Statement(statement) => statement.inner.doc(),
+            Self::TypeAlias(TypeAlias { doc, .. })
+            | Self::Interface(Interface { doc, .. })
+            | Self::UnionDeclaration(UnionDeclaration { doc, .. })
+            | Self::BrandDeclaration(BrandDeclaration { doc, .. })
+            | Self::PatternDeclaration(PatternDeclaration { doc, .. }) => doc.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The doc comment of a declaration, `None` for the nodes that can't have one.
+    pub fn doc_mut(&mut self) -> Option<&mut Option<String>> {
+        match self {
+            Self::Statement(statement) => Rc::make_mut(&mut statement.inner).doc_mut(),
+            Self::TypeAlias(TypeAlias { doc, .. })
+            | Self::Interface(Interface { doc, .. })
+            | Self::UnionDeclaration(UnionDeclaration { doc, .. })
+            | Self::BrandDeclaration(BrandDeclaration { doc, .. })
+            | Self::PatternDeclaration(PatternDeclaration { doc, .. }) => Some(doc),
+            _ => None,
+        }
+    }
+
     pub fn as_ident(&self) -> Option<&Ident> {
         if let Self::Ident(v) = self {
             Some(v)
@@ -1003,7 +1907,7 @@ impl Ast {
 
         if let Ast::Primitive(other, _) = other {
             if let Some(value) = self.get_primitive_type() {
-                if value == *other {
+                if value == *other && value != PrimitiveType::UniqueSymbol {
                     return T::True;
                 }
             }
@@ -1016,7 +1920,14 @@ impl Ast {
 
             (A::NeverKeyword(_), _) => T::Never,
 
-            (lhs, rhs) if lhs == rhs => T::True,
+            // Each `unique symbol` is a type of its own, told apart from the others by where it is
+            // written, which equality ignores.
+            (
+                A::Primitive(PrimitiveType::UniqueSymbol, lhs),
+                A::Primitive(PrimitiveType::UniqueSymbol, rhs),
+            ) => Into::into((lhs.start, lhs.end) == (rhs.start, rhs.end)),
+
+            (lhs, rhs) if lhs == rhs && !lhs.has_unique_symbol() => T::True,
 
             (_, rhs) if rhs.is_top_type() => T::True,
 
@@ -1034,7 +1945,19 @@ impl Ast {
 
             (A::Builtin(Builtin { .. }), _) => todo!(),
 
-            (A::Path(_), _) => todo!(),
+            // A string or number literal is a key of an object whose properties are all named if
+            // it names one of them.
+            (
+                A::TypeString(TypeString { ty: key, .. })
+                | A::TypeNumber(TypeNumber { ty: key, .. }),
+                A::Builtin(Builtin {
+                    name: BuiltinKeyword::Keyof,
+                    argument,
+                    ..
+                }),
+            ) if argument.literal_keys().is_some() => {
+                Into::into(argument.literal_keys().unwrap().contains(&key.as_str()))
+            }
 
             (lhs, rhs) if lhs.is_non_nullish() && rhs.is_object_object_wrapper() => T::True,
 
@@ -1044,8 +1967,40 @@ impl Ast {
                 _ => T::False,
             },
 
+            // An intersection is a subtype of each of its members, and a type is a subtype of an
+            // intersection if it is one of all its members.
+            (_, A::IntersectionType(IntersectionType { types, .. })) => {
+                Into::into(types.iter().all(|ty| self.is_subtype(ty) == T::True))
+            }
+
+            (A::IntersectionType(IntersectionType { types, .. }), _) => {
+                Into::into(types.iter().any(|ty| ty.is_subtype(other) == T::True))
+            }
+
+            // An object whose properties are all named is a subtype of another if it has each
+            // property of the other, optional ones aside, with a subtype of its value.
+            (A::TypeLiteral(lhs), A::TypeLiteral(rhs))
+                if lhs.literal_keys().is_some() && rhs.literal_keys().is_some() =>
+            {
+                Into::into(rhs.iter().all(|expected| {
+                    match lhs.iter().find(|property| property.key == expected.key) {
+                        Some(property) => {
+                            (!property.optional || expected.optional)
+                                && property.value.is_subtype(&expected.value) == T::True
+                        }
+                        None => expected.optional,
+                    }
+                }))
+            }
+
             (A::TypeLiteral(_), _) => todo!(),
 
+            // Every `unique symbol` is a `symbol`, but a `symbol` is not one in particular.
+            (
+                A::Primitive(PrimitiveType::UniqueSymbol, _),
+                A::Primitive(PrimitiveType::Symbol, _),
+            ) => T::True,
+
             (A::Primitive(lhs, _), A::Primitive(rhs, _)) => Into::into(lhs == rhs),
 
             (A::TemplateString(_) | A::TypeString(_), A::Primitive(PrimitiveType::String, _)) => {
@@ -1067,6 +2022,20 @@ impl Ast {
                 ast.is_subtype(rhs)
             }
 
+            // Literals and primitives are subtypes of one another only in the cases above.
+            (
+                A::TypeString(_)
+                | A::TypeNumber(_)
+                | A::TrueKeyword(_)
+                | A::FalseKeyword(_)
+                | A::Primitive(..),
+                A::TypeString(_)
+                | A::TypeNumber(_)
+                | A::TrueKeyword(_)
+                | A::FalseKeyword(_)
+                | A::Primitive(..),
+            ) => T::False,
+
             (A::Ident(_), _) => {
                 todo!()
             }
@@ -1080,10 +2049,32 @@ impl Ast {
         }
     }
 
+    /// Whether a `unique symbol` occurs in the type, making it equal to another type only if their
+    /// symbols are the same, see [`Ast::is_subtype`].
+    fn has_unique_symbol(&self) -> bool {
+        // A visitor, which borrows the nodes, as interning asks it of every node.
+        struct Found(bool);
+
+        impl<'a> visit::Visit<'a> for Found {
+            fn visit_ast(&mut self, node: &'a Ast) {
+                match node {
+                    Ast::Primitive(PrimitiveType::UniqueSymbol, _) => self.0 = true,
+                    node if !self.0 => visit::walk_ast(self, node),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut found = Found(false);
+        visit::Visit::visit_ast(&mut found, self);
+        found.0
+    }
+
     pub fn as_span(&self) -> Span {
         match self {
             Ast::Access(x) => x.span,
             Ast::AnyKeyword(x) => *x,
+            Ast::ThisType(x) => *x,
             Ast::ApplyGeneric(x) => x.span,
             Ast::Array(ast) => ast.as_span(),
             Ast::Builtin(x) => x.span,
@@ -1096,6 +2087,7 @@ impl Ast {
             Ast::Ident(x) => x.span,
             Ast::IfExpr(x) => x.span,
             Ast::ImportStatement(x) => x.span,
+            Ast::ImportType(x) => x.span,
             Ast::Infer(ast) => ast.as_span(),
             Ast::Interface(x) => x.span,
             Ast::IntersectionType(x) => x.span,
@@ -1105,8 +2097,8 @@ impl Ast {
             Ast::MatchExpr(x) => x.span,
             Ast::NeverKeyword(x) => *x,
             Ast::NoOp(x) => *x,
+            Ast::SyntaxError(x) => x.span,
             Ast::TypeNumber(x) => x.span,
-            Ast::Path(x) => x.span,
             Ast::Primitive(_, x) => *x,
             Ast::Program(x) => x.span,
             Ast::Statement(ast) => ast.as_span(),
@@ -1115,12 +2107,74 @@ impl Ast {
             Ast::TrueKeyword(x) => *x,
             Ast::Tuple(x) => x.span,
             Ast::TypeAlias(x) => x.span,
+            Ast::UnionDeclaration(x) => x.span,
+            Ast::BrandDeclaration(x) => x.span,
+            Ast::PatternDeclaration(x) => x.span,
+            Ast::ExportDefault(x) => x.span,
             Ast::TypeLiteral(x) => x.span,
             Ast::UnionType(x) => x.span,
             Ast::UnitTest(x) => x.span,
+            Ast::AmbientModule(x) => x.span,
             Ast::UnknownKeyword(x) => *x,
         }
     }
+
+    /// [`Ast::as_span`], to change it.
+    pub fn span_mut(&mut self) -> &mut Span {
+        match self {
+            Ast::Access(x) => &mut x.span,
+            Ast::AnyKeyword(x) => x,
+            Ast::ThisType(x) => x,
+            Ast::ApplyGeneric(x) => &mut x.span,
+            Ast::Array(ast) => Rc::make_mut(ast).span_mut(),
+            Ast::Builtin(x) => &mut x.span,
+            Ast::CondExpr(x) => &mut x.span,
+            Ast::ExtendsExpr(x) => &mut x.span,
+            Ast::ExtendsInfixOp(x) => &mut x.span,
+            Ast::ExtendsPrefixOp(x) => &mut x.span,
+            Ast::FalseKeyword(x) => x,
+            Ast::FunctionType(x) => &mut x.span,
+            Ast::Ident(x) => &mut x.span,
+            Ast::IfExpr(x) => &mut x.span,
+            Ast::ImportStatement(x) => &mut x.span,
+            Ast::ImportType(x) => &mut x.span,
+            Ast::Infer(ast) => Rc::make_mut(ast).span_mut(),
+            Ast::Interface(x) => &mut x.span,
+            Ast::IntersectionType(x) => &mut x.span,
+            Ast::LetExpr(x) => &mut x.span,
+            Ast::MacroCall(x) => &mut x.span,
+            Ast::MappedType(x) => &mut x.span,
+            Ast::MatchExpr(x) => &mut x.span,
+            Ast::NeverKeyword(x) => x,
+            Ast::NoOp(x) => x,
+            Ast::SyntaxError(x) => &mut x.span,
+            Ast::TypeNumber(x) => &mut x.span,
+            Ast::Primitive(_, x) => x,
+            Ast::Program(x) => &mut x.span,
+            Ast::Statement(ast) => Rc::make_mut(&mut ast.inner).span_mut(),
+            Ast::TypeString(x) => &mut x.span,
+            Ast::TemplateString(x) => &mut x.span,
+            Ast::TrueKeyword(x) => x,
+            Ast::Tuple(x) => &mut x.span,
+            Ast::TypeAlias(x) => &mut x.span,
+            Ast::UnionDeclaration(x) => &mut x.span,
+            Ast::BrandDeclaration(x) => &mut x.span,
+            Ast::PatternDeclaration(x) => &mut x.span,
+            Ast::ExportDefault(x) => &mut x.span,
+            Ast::TypeLiteral(x) => &mut x.span,
+            Ast::UnionType(x) => &mut x.span,
+            Ast::UnitTest(x) => &mut x.span,
+            Ast::AmbientModule(x) => &mut x.span,
+            Ast::UnknownKeyword(x) => x,
+        }
+    }
+
+    /// The node, built by a desugaring pass for `origin`.
+    pub fn with_origin(mut self, origin: OriginId) -> Self {
+        let span = self.span_mut();
+        *span = span.with_origin(origin);
+        self
+    }
 }
 
 #[ast_node(transparent)]
@@ -1133,20 +2187,43 @@ pub struct TypeString {
     pub ty: String,
 }
 
+/// A template literal as it is written, backticks included. Its text is taken as it is, like that
+/// of a string, and escaped when emitted, see [`template_literal`].
 #[ast_node(transparent)]
 pub struct TemplateString {
     pub ty: String,
 }
 
-#[derive(Debug, Clone, Copy, Eq, Serialize)]
+/// Byte range of a node in the source. Nodes that were not parsed, such as the ones built by the
+/// importers, use the default empty span.
+#[derive(Debug, Clone, Copy, Default, Eq, Serialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
+    /// Where the node comes from, if a desugaring pass built it, see [`origin`].
+    #[serde(skip)]
+    origin: Option<OriginId>,
 }
 
 impl Span {
     pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+        Self {
+            start,
+            end,
+            origin: None,
+        }
+    }
+
+    pub fn origin(&self) -> Option<OriginId> {
+        self.origin
+    }
+
+    /// The span, the node having been built for `origin`.
+    pub fn with_origin(self, origin: OriginId) -> Self {
+        Self {
+            origin: Some(origin),
+            ..self
+        }
     }
 
     pub fn start(&self) -> usize {
@@ -1158,10 +2235,7 @@ impl Span {
     }
 
     pub fn merge(&self, other: &Self) -> Self {
-        Self {
-            start: self.start.min(other.start),
-            end: self.end.max(other.end),
-        }
+        Self::new(self.start.min(other.start), self.end.max(other.end))
     }
 
     pub fn as_pest<'a>(&self, input: &'a str) -> pest::Span<'a> {
@@ -1182,11 +2256,7 @@ impl Span {
         pest::error::Error::new_from_span(variant, span)
     }
 
-    pub(crate) fn as_custom_error(
-        &self,
-        input: &str,
-        message: String,
-    ) -> pest::error::Error<Rule> {
+    pub(crate) fn as_custom_error(&self, input: &str, message: String) -> pest::error::Error<Rule> {
         let span = self.as_pest(input);
         let variant = pest::error::ErrorVariant::CustomError { message };
         pest::error::Error::new_from_span(variant, span)
@@ -1197,7 +2267,7 @@ impl From<Vec<Pair<'_>>> for Span {
     fn from(list: Vec<Pair<'_>>) -> Self {
         let start = list.iter().map(|p| p.as_span().start()).min().unwrap_or(0);
         let end = list.iter().map(|p| p.as_span().end()).max().unwrap_or(0);
-        Self { start, end }
+        Self::new(start, end)
     }
 }
 
@@ -1213,12 +2283,25 @@ impl From<Pair<'_>> for Span {
     }
 }
 
+thread_local! {
+    /// Position in the source of the text being parsed, when it is only a part of the source.
+    static SPAN_OFFSET: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Runs `f` with the spans of the pairs it converts shifted by `offset`, for pairs parsed from the
+/// text starting at `offset` in the source, see [`crate::parser::statements`].
+pub(crate) fn with_span_offset<T>(offset: usize, f: impl FnOnce() -> T) -> T {
+    let previous = SPAN_OFFSET.replace(offset);
+    let result = f();
+    SPAN_OFFSET.set(previous);
+    result
+}
+
 impl From<pest::Span<'_>> for Span {
     fn from(value: pest::Span<'_>) -> Self {
-        Self {
-            start: value.start().to_owned(),
-            end: value.end().to_owned(),
-        }
+        let offset = SPAN_OFFSET.get();
+
+        Self::new(value.start() + offset, value.end() + offset)
     }
 }
 
@@ -1270,27 +2353,96 @@ impl From<&Rc<Ast>> for Box<Ast> {
     }
 }
 
+/// How loosely a type binds in TypeScript, from the loosest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    /// Conditional and function types, which extend as far right as possible.
+    Conditional,
+    Union,
+    Intersection,
+    /// Prefix type operators, `keyof T` or `infer U`.
+    Operator,
+    /// Arrays, indexed access and everything that is not an operator.
+    Postfix,
+}
+
+impl Ast {
+    fn ts_precedence(&self) -> Precedence {
+        match self {
+            Ast::ExtendsExpr(_) | Ast::FunctionType(_) => Precedence::Conditional,
+            Ast::UnionType(_) => Precedence::Union,
+            Ast::IntersectionType(_) => Precedence::Intersection,
+            Ast::Builtin(_) | Ast::Infer(_) => Precedence::Operator,
+            _ => Precedence::Postfix,
+        }
+    }
+
+    /// The TypeScript of an operand in a position that binds at least as tightly as `min`,
+    /// parenthesized if the operand binds more loosely.
+    fn to_ts_operand(&self, min: Precedence) -> D<()> {
+        use typescript::Pretty as _;
+
+        if self.ts_precedence() < min {
+            // The lines the operand breaks into are indented past the parenthesis.
+            parens(self.to_ts().nest(indent()))
+        } else {
+            self.to_ts()
+        }
+    }
+}
+
 impl typescript::Pretty for Ast {
     fn to_ts(&self) -> D<()> {
         match self {
             Ast::Program(Program { statements, .. }) => {
+                let (globals, statements): (Vec<_>, Vec<_>) =
+                    statements.iter().partition(|stmnt| stmnt.is_global());
+
                 let mut doc = D::nil();
-                for stmnt in statements {
+                for stmnt in statements.into_iter().filter(|stmnt| !stmnt.is_noop()) {
                     doc = doc
                         .append(stmnt.to_ts())
                         .append(D::hardline())
                         .append(D::hardline());
                 }
+
+                if !globals.is_empty() {
+                    // Statements are separated by a line break outside of the indentation to not
+                    // leave it behind on the blank line as trailing whitespace.
+                    let body = D::intersperse(
+                        globals
+                            .iter()
+                            .map(|stmnt| D::hardline().append(stmnt.to_ts()).nest(indent())),
+                        D::hardline(),
+                    );
+
+                    doc = doc
+                        .append("declare global {")
+                        .append(body)
+                        .append(D::hardline())
+                        .append("}")
+                        .append(D::hardline())
+                        .append(D::hardline());
+                }
+
                 doc
             }
             Ast::TypeAlias(TypeAlias {
+                doc,
                 export,
+                default,
                 name,
                 params,
                 body,
                 ..
             }) => {
-                let body = (*body).to_ts();
+                let body_node = body.as_ref();
+                let body = body_node.to_ts();
+
+                let comment = match doc {
+                    Some(doc) => jsdoc(doc),
+                    None => D::nil(),
+                };
 
                 let doc = if *export {
                     D::text("export").append(D::space())
@@ -1309,47 +2461,85 @@ impl typescript::Pretty for Ast {
                         );
 
                         D::text("<")
-                            .append(D::line_().append(body).append(D::line_()).nest(4))
+                            .append(
+                                D::line_()
+                                    .append(body)
+                                    .append(trailing_comma())
+                                    .nest(indent()),
+                            )
+                            .append(D::line_())
                             .append(D::text(">"))
                             .group()
                     }
                 };
 
-                doc.append("type")
+                let doc = doc
+                    .append("type")
                     .append(D::space())
                     .append(name.pretty())
                     .append(params_doc)
                     .append(D::space())
                     .append("=")
-                    .append(D::line().append(body).nest(4))
-                    .group()
+                    .append(match body_node {
+                        Ast::TypeLiteral(_) if style().hug_object_types => D::space().append(body),
+                        // A union that doesn't fit on the line of the `=` has a member per line.
+                        Ast::UnionType(UnionType { types, .. }) if style().leading_pipe => {
+                            D::line().append(union_to_ts(types)).nest(indent())
+                        }
+                        _ => D::line().append(body).nest(indent()),
+                    })
+                    .group();
+
+                // TypeScript has no default export of an alias in its declaration.
+                let default = if *default {
+                    D::text(";")
+                        .append(D::hardline())
+                        .append("export default ")
+                        .append(name.pretty())
+                } else {
+                    D::nil()
+                };
+
+                comment.append(doc).append(default)
+            }
+            Ast::ExportDefault(ExportDefault { name, .. }) => {
+                D::text("export default ").append(name.pretty())
             }
             Ast::Ident(identifier) => identifier.pretty(),
             Ast::TypeNumber(inner) => D::text(inner.ty.clone()),
             Ast::Primitive(primitive, _) => D::text(primitive.to_string()),
             Ast::TypeString(inner) => string_literal(inner.ty.as_str()),
-            Ast::TemplateString(inner) => D::text(inner.ty.clone()),
+            Ast::TemplateString(inner) => template_literal(&inner.ty),
             Ast::IfExpr(..) => {
                 unreachable!("IfExpr should be desugared before this point");
             }
             Ast::Access(Access {
                 lhs,
                 rhs,
-                is_dot: true,
+                kind: AccessKind::Namespace,
+                ..
+            }) => lhs
+                .to_ts_operand(Precedence::Postfix)
+                .append(D::text("."))
+                .append(rhs.to_ts()),
+            Ast::Access(Access {
+                lhs,
+                rhs,
+                kind: AccessKind::Dot,
                 ..
             }) => {
                 let rhs = rhs
                     .as_ident()
                     .expect("rhs of dot access should be an ident");
 
-                lhs.to_ts()
+                lhs.to_ts_operand(Precedence::Postfix)
                     .append(D::text("["))
                     .append(string_literal(rhs.name.as_str()))
                     .append(D::text("]"))
                     .group()
             }
             Ast::Access(Access { lhs, rhs, .. }) => lhs
-                .to_ts()
+                .to_ts_operand(Precedence::Postfix)
                 .append(D::text("["))
                 .append(rhs.to_ts())
                 .append(D::text("]"))
@@ -1363,51 +2553,48 @@ impl typescript::Pretty for Ast {
 
                 D::text("[").append(items).append(D::text("]"))
             }
-            Ast::Array(node) => {
-                let doc = if node.is_set_op() {
-                    parens(node.to_ts())
-                } else {
-                    node.to_ts()
-                };
-
-                doc.append(D::text("[]"))
-            }
+            Ast::Array(node) => node
+                .to_ts_operand(Precedence::Postfix)
+                .append(D::text("[]")),
             Ast::NeverKeyword(_) => D::text("never"),
             Ast::AnyKeyword(_) => D::text("any"),
+            Ast::ThisType(_) => D::text("this"),
+            Ast::ImportType(ImportType { module, .. }) => D::text("import(")
+                .append(string_literal(module))
+                .append(")"),
             Ast::UnknownKeyword(_) => D::text("unknown"),
             Ast::TrueKeyword(_) => D::text("true"),
             Ast::FalseKeyword(_) => D::text("false"),
             Ast::Infer(value) => D::text("infer").append(D::space()).append(value.to_ts()),
 
-            Ast::Builtin(Builtin { name, argument, .. }) => {
-                name.to_ts().append(" ").append(argument.to_ts())
-            }
+            Ast::Builtin(Builtin { name, argument, .. }) => name
+                .to_ts()
+                .append(" ")
+                .append(argument.to_ts_operand(Precedence::Operator)),
 
-            Ast::ExtendsExpr(ExtendsExpr {
-                lhs,
-                rhs,
-                then_branch: then,
-                else_branch: els,
-                ..
-            }) => {
-                let condition_doc = lhs
-                    .to_ts()
-                    .append(D::space())
-                    .append("extends")
-                    .append(D::space())
-                    .append(rhs.to_ts());
+            Ast::ExtendsExpr(expr) if matches!(*expr.else_branch, Ast::ExtendsExpr(_)) => {
+                expr.chain_to_ts()
+            }
+            Ast::ExtendsExpr(
+                expr @ ExtendsExpr {
+                    then_branch: then,
+                    else_branch: els,
+                    ..
+                },
+            ) => {
+                let condition_doc = expr.condition_to_ts();
 
                 let then_doc = D::line()
                     .append("?")
                     .append(D::space())
                     .append(then.to_ts())
-                    .nest(4);
+                    .nest(indent());
 
                 let else_doc = D::line()
                     .append(":")
                     .append(D::space())
                     .append(els.to_ts())
-                    .nest(4);
+                    .nest(indent());
 
                 condition_doc.append(then_doc).append(else_doc)
             }
@@ -1415,14 +2602,19 @@ impl typescript::Pretty for Ast {
                 lhs,
                 op: InfixOp::Extends,
                 rhs,
+                non_distributive,
                 ..
-            }) => lhs
-                .to_ts()
-                .append(D::space())
-                .append("extends")
-                .append(D::space())
-                .append(rhs.to_ts())
-                .group(),
+            }) => extends_to_ts(lhs, rhs, *non_distributive).group(),
+            // An interface is not terminated, a stray `;` is an error in a declaration file.
+            Ast::Statement(stmnt)
+                if matches!(**stmnt, Ast::Interface(_) | Ast::AmbientModule(_)) =>
+            {
+                stmnt.to_ts()
+            }
+            Ast::Statement(stmnt) if stmnt.is_noop() => D::nil(),
+            Ast::Statement(stmnt) if stmnt.attribute(attribute::COMPANION).is_some() => {
+                companion::to_ts(stmnt).append(D::text(";"))
+            }
             Ast::Statement(stmnt) => stmnt.to_ts().append(D::text(";")),
             Ast::MappedType(MappedType {
                 index: key,
@@ -1452,11 +2644,11 @@ impl typescript::Pretty for Ast {
 
                 let rhs_doc = body.to_ts();
 
-                let rhs_doc = D::line().append(rhs_doc).nest(4).group();
+                let rhs_doc = D::line().append(rhs_doc).nest(indent()).group();
 
                 let readonly_doc = match readonly_mod {
-                    Some(MappingModifier::Add) => D::text("readonly"),
-                    Some(MappingModifier::Remove) => D::text("-readonly"),
+                    Some(MappingModifier::Add) => D::text("readonly "),
+                    Some(MappingModifier::Remove) => D::text("-readonly "),
                     None => D::nil(),
                 };
 
@@ -1475,7 +2667,7 @@ impl typescript::Pretty for Ast {
                     .append(":")
                     .append(rhs_doc)
                     .append(D::line())
-                    .nest(4)
+                    .nest(indent())
                     .group();
 
                 D::nil().append("{").append(inner_doc).append("}").group()
@@ -1483,54 +2675,64 @@ impl typescript::Pretty for Ast {
             Ast::LetExpr(..) => {
                 unreachable!("LetExpr should be desugared before this point")
             }
-            Ast::ImportStatement(ImportStatement {
-                import_clause,
-                module,
-                ..
-            }) => {
-                let import_clause = import_clause.to_ts();
-
-                D::text("import type")
-                    .append(D::space())
-                    .append(import_clause)
-                    .append(D::space())
-                    .append("from")
-                    .append(D::space())
-                    .append(string_literal(module))
-            }
-            Ast::Path(Path { segments, .. }) => {
-                let sep = D::text(".");
-
-                let segments = D::intersperse(segments.iter().map(|seg| seg.to_ts()), sep);
-
-                segments
-            }
+            Ast::ImportStatement(import) => import_to_ts(import, "import type"),
             Ast::Interface(value) => {
                 return value.to_ts();
             }
             Ast::UnitTest(_) => D::nil(),
+            Ast::AmbientModule(AmbientModule { name, body, .. }) => {
+                let body: Vec<_> = body.iter().filter(|stmnt| !stmnt.is_noop()).collect();
+
+                if body.is_empty() {
+                    return D::text("declare module ")
+                        .append(string_literal(name))
+                        .append(" {}");
+                }
+
+                let statements = body.into_iter().map(|stmnt| {
+                    let doc = match stmnt {
+                        // A block is only types, its imports are written as plain imports.
+                        Ast::Statement(inner) => match inner.as_ref() {
+                            Ast::ImportStatement(import) => {
+                                import_to_ts(import, "import").append(";")
+                            }
+                            _ => stmnt.to_ts(),
+                        },
+                        _ => stmnt.to_ts(),
+                    };
+
+                    D::hardline().append(doc).nest(indent())
+                });
+
+                D::text("declare module ")
+                    .append(string_literal(name))
+                    .append(" {")
+                    .append(D::intersperse(statements, D::hardline()))
+                    .append(D::hardline())
+                    .append("}")
+            }
             Ast::MacroCall(_) => unreachable!("MacroCall should be desugared before this point"),
-            Ast::UnionType(UnionType { types, .. }) => {
-                let sep = D::line().append(D::text("|")).append(D::space());
+            Ast::UnionType(UnionType { types, .. }) => union_to_ts(types).group(),
+            Ast::IntersectionType(IntersectionType { types, .. }) => {
+                let sep = D::line().append(D::text("&")).append(D::space());
                 D::intersperse(
-                    types.iter().map(|t| match t {
-                        Ast::IntersectionType(IntersectionType { .. }) => {
-                            surround(t.to_ts(), "(", ")")
-                        }
-                        _ => t.to_ts(),
-                    }),
+                    types
+                        .iter()
+                        .map(|t| t.to_ts_operand(Precedence::Intersection)),
                     sep,
                 )
                 .group()
             }
-            Ast::IntersectionType(IntersectionType { types, .. }) => {
-                let sep = D::line().append(D::text("&")).append(D::space());
-                D::intersperse(types.iter().map(|t| t.to_ts()), sep).group()
-            }
             Ast::NoOp(_) => D::nil(),
+            // Syntax errors stop the compiler before it emits, this is for the printing of a tree
+            // parsed with them.
+            Ast::SyntaxError(_) => D::text("/* syntax error */"),
             node @ (Ast::ExtendsPrefixOp(ExtendsPrefixOp { .. })
             | Ast::MatchExpr(match_expr::MatchExpr { .. })
             | Ast::CondExpr(cond_expr::CondExpr { .. })
+            | Ast::UnionDeclaration(UnionDeclaration { .. })
+            | Ast::BrandDeclaration(BrandDeclaration { .. })
+            | Ast::PatternDeclaration(PatternDeclaration { .. })
             | Ast::ExtendsInfixOp(ExtendsInfixOp { .. })) => {
                 unreachable!("Ast should be desugared before this point {:#?}", node)
             }
@@ -1669,6 +2871,20 @@ mod tests {
     #[case("String", "null", FALSE)]
     #[case("String", "undefined", FALSE)]
     #[case("String", "never", FALSE)]
+    // symbol
+    #[case("symbol", "symbol", TRUE)]
+    #[case("symbol", "unique symbol", FALSE)]
+    #[case("unique symbol", "symbol", TRUE)]
+    #[case("unique symbol", "unique symbol", TRUE)]
+    #[case("unique symbol", "string", FALSE)]
+    #[case("unique symbol", "Object", TRUE)]
+    #[case("unique symbol", "{}", FALSE)]
+    #[case("Symbol", "unique symbol", FALSE)]
+    // Keys of object literals
+    #[case("'a'", "keyof({a: 1, b: 2})", TRUE)]
+    #[case("'c'", "keyof({a: 1, b: 2})", FALSE)]
+    #[case("1", "keyof({\"1\": 'one'})", TRUE)]
+    #[case("'a'", "keyof({})", FALSE)]
     #[trace]
     fn is_subtype(#[case] a: &str, #[case] b: &str, #[case] expected: ExtendsResult) {
         assert_eq!(ast!(a).is_subtype(&ast!(b)), expected);
@@ -1691,10 +2907,92 @@ mod simplify_tests {
     use pretty_assertions::{assert_eq, assert_ne};
     use rstest::rstest;
 
+    #[test]
+    fn identifiers_are_compared_by_name() {
+        use std::collections::HashSet;
+
+        let ident = |start| ast::Ident {
+            name: "A".to_string(),
+            span: Span::new(start, start + 1),
+            raw: false,
+        };
+
+        assert_eq!(ident(0), ident(10));
+        assert_eq!(HashSet::from([ident(0), ident(10)]).len(), 1);
+
+        let bindings: Bindings = HashMap::from([(ident(4), Ast::noop())]);
+        assert_eq!(bindings.get("A"), Some(&Ast::noop()));
+        assert_eq!(ident(4).to_string(), "A");
+    }
+
+    #[test]
+    fn else_if_chains_are_flat() {
+        use crate::typescript::Pretty as _;
+        use textwrap_macros::dedent;
+
+        let source = r#"
+            type Name(T) as match T do
+                1 -> :one,
+                2 -> :two,
+                3 -> :three,
+                4 -> :four,
+                5 -> :five,
+                else -> never
+            end
+        "#;
+
+        let render = |width| parse!(source).simplify().render_pretty_ts(width);
+
+        assert_eq!(
+            render(200).trim(),
+            "type Name<T> = T extends 1 ? 'one' : T extends 2 ? 'two' : T extends 3 ? 'three' \
+             : T extends 4 ? 'four' : T extends 5 ? 'five' : never;"
+        );
+        assert_eq!(
+            render(40).trim(),
+            dedent!(
+                "
+                type Name<T> =
+                    T extends 1 ? 'one'
+                    : T extends 2 ? 'two'
+                    : T extends 3 ? 'three'
+                    : T extends 4 ? 'four'
+                    : T extends 5 ? 'five'
+                    : never;
+                "
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn access_names_are_not_references() {
+        use crate::typescript::Pretty as _;
+
+        // Only an index is a type, the names after `.` and `::` aren't replaced by bindings.
+        assert_eq!(
+            parse!(
+                expr,
+                r#"let A = 1, k = 2, NS = Other, Obj = Value in [NS::A, Obj[k], Obj.k, Obj["k"]]"#
+            )
+            .simplify()
+            .render_pretty_ts(80),
+            "[Other.A, Value[2], Value['k'], Value['k']]"
+        );
+    }
+
     #[rstest]
     #[case(
         "A::B::C::D",
-        sexp!((:: (segments (ident . "A") (ident . "B") (ident . "C") (ident . "D"))))
+        sexp!(
+            (#"."
+                (lhs #"."
+                    (lhs #"." (lhs ident . "A") (rhs ident . "B") (kind . namespace))
+                    (rhs ident . "C")
+                    (kind . namespace))
+                (rhs ident . "D")
+                (kind . namespace))
+        )
     )]
     fn test_simplify_expr(#[case] input: &str, #[case] expected: lexpr::Value) {
         use crate::parser::{self, Rule};
@@ -1868,6 +3166,174 @@ mod simplify_tests {
             .to_string()
         )
     }
+
+    #[rstest]
+    #[case::adjacent(
+        "match T do 1 -> :a, 2 -> :a, 3 -> :a, else -> :b end",
+        "if T <: 1 | 2 | 3 then :a else :b end"
+    )]
+    #[case::not_adjacent(
+        "match T do 1 -> :a, 2 -> :b, 3 -> :a, else -> :b end",
+        "cond do T <: 1 -> :a, T <: 2 -> :b, T <: 3 -> :a, else -> :b end"
+    )]
+    #[case::infer(
+        "match T do [?U] -> :a, [?U, ?V] -> :a, 1 -> :a, 2 -> :a, else -> :b end",
+        "cond do T <: [?U] -> :a, T <: [?U, ?V] -> :a, T <: 1 | 2 -> :a, else -> :b end"
+    )]
+    #[case::cond(
+        "cond do T <: 1 -> :a, T <: 2 | 3 -> :a, U <: 4 -> :a, else -> :b end",
+        "cond do T <: 1 | 2 | 3 -> :a, U <: 4 -> :a, else -> :b end"
+    )]
+    #[case::subsumed(
+        "match T do :a -> 1, string -> 1, :a -> 1, else -> 2 end",
+        "if T <: string then 1 else 2 end"
+    )]
+    fn merge_arms(#[case] source: &str, #[case] expected: &str) {
+        use crate::typescript::Pretty as _;
+
+        assert_eq!(
+            parse!(expr, source)
+                .simplify()
+                .merge_arms()
+                .render_pretty_ts(80),
+            parse!(expr, expected).simplify().render_pretty_ts(80)
+        );
+    }
+
+    #[rstest]
+    #[case::extract(
+        "type A(T) as if T <: string then T else never end",
+        "type A<T> = Extract<T, string>;"
+    )]
+    #[case::exclude(
+        "type A(T) as if T <: null | undefined then never else T end",
+        "type A<T> = Exclude<T, null | undefined>;"
+    )]
+    #[case::nested(
+        "type A(T, U) as [if T <: U then T else never end, if U <: T then never else U end]",
+        "type A<T, U> = [Extract<T, U>, Exclude<U, T>];"
+    )]
+    #[case::scrutinee_mismatch(
+        "type A(T, U) as if T <: string then U else never end",
+        "type A<T, U> = T extends string ? U : never;"
+    )]
+    #[case::non_distributive(
+        "type A(T) as if T <:! string then T else never end",
+        "type A<T> = [T] extends [string] ? T : never;"
+    )]
+    #[case::not_a_parameter(
+        "type U as 1 | 2\ntype A as if U <: 1 then U else never end",
+        "type U = 1 | 2;\n\ntype A = U extends 1 ? U : never;"
+    )]
+    #[case::infers(
+        "type A(T) as if T <: [?U] then T else never end",
+        "type A<T> = T extends [infer U] ? T : never;"
+    )]
+    #[case::declared_utility(
+        "type Extract(T) as T\ntype A(T) as if T <: 1 then T else never end",
+        "type Extract<T> = T;\n\ntype A<T> = T extends 1 ? T : never;"
+    )]
+    fn extract_exclude(#[case] source: &str, #[case] expected: &str) {
+        use crate::typescript::Pretty as _;
+
+        assert_eq!(
+            parse!(source)
+                .simplify()
+                .extract_exclude()
+                .render_pretty_ts(80)
+                .trim(),
+            expected
+        );
+    }
+
+    #[test]
+    fn traverse_ctl_skip() {
+        let tree = parse!(expr, "[[1, 2], [3, [4]], 5]");
+        let visited = std::cell::Cell::new(0);
+
+        // The depth of the node.
+        tree.traverse_ctl(
+            0,
+            &|node, depth| {
+                visited.set(visited.get() + 1);
+                let walk = if depth == 1 {
+                    Walk::Skip
+                } else {
+                    Walk::Continue
+                };
+                (node, depth + 1, walk)
+            },
+            &|node, depth| (node, depth),
+        );
+
+        assert_eq!(visited.get(), 4);
+    }
+
+    #[test]
+    fn traverse_ctl_break() {
+        let tree = parse!(expr, "[[1, 2], [3, [4]], 5]");
+        let (visited, finished) = (std::cell::Cell::new(0), std::cell::Cell::new(0));
+
+        let (walked, _) = tree.traverse_ctl(
+            (),
+            &|node, ctx| {
+                visited.set(visited.get() + 1);
+                let walk = match node {
+                    Ast::TypeNumber(_) => Walk::Break,
+                    _ => Walk::Continue,
+                };
+                (node, ctx, walk)
+            },
+            &|node, ctx| {
+                finished.set(finished.get() + 1);
+                (node, ctx)
+            },
+        );
+
+        assert_eq!((visited.get(), finished.get()), (3, 0));
+        assert_eq!(walked, tree);
+
+        assert_eq!(
+            tree.find_map(|node| match node {
+                Ast::TypeNumber(number) => Some(number.ty.clone()),
+                _ => None,
+            }),
+            Some("1".to_string())
+        );
+        assert!(tree.any(|node| matches!(node, Ast::TypeNumber(number) if number.ty == "4")));
+        assert!(!tree.any(|node| matches!(node, Ast::TypeString(_))));
+    }
+
+    #[rstest]
+    #[case::duplicate_literals(r#""a" | "b" | "a" | "b""#, r#""a" | "b""#)]
+    #[case::subsumed_by_primitive(r#""a" | 1 | string | true | boolean"#, "1 | string | boolean")]
+    #[case::subsumed_by_unknown(r#""a" | {a: 1} | unknown"#, "unknown")]
+    #[case::top_types("unknown | 1 | any | unknown", "any")]
+    #[case::any_and_unknown("any | unknown | any", "any")]
+    #[case::nested(r#""a" | (1 | ("a" | 2))"#, r#""a" | 1 | 2"#)]
+    #[case::never_members(r#"never | 1 | never"#, "1")]
+    #[case::only_never("never | never", "never")]
+    #[case::unique_symbol("unique symbol | symbol | unique symbol", "symbol")]
+    #[case::names(r#"T | "a" | string | T | [T] | {a: T}"#, "T | string | [T] | {a: T}")]
+    #[case::names_are_not_subsumed(
+        r#"keyof(T) | string | [T] | unknown"#,
+        "keyof(T) | [T] | unknown"
+    )]
+    fn normalize_union(#[case] source: &str, #[case] expected: &str) {
+        use crate::dsl::Pretty as _;
+
+        let Ast::UnionType(union) = parse!(expr, source) else {
+            panic!("expected a union");
+        };
+
+        assert_eq!(
+            super::normalize_union(union.types)
+                .iter()
+                .map(|member| member.render_pretty_dsl(80))
+                .join(" | "),
+            expected
+        );
+    }
 }
 
 #[derive(Derivative, Clone, Eq, Serialize)]
@@ -1893,9 +3359,10 @@ impl typescript::Pretty for ImportClause {
                         D::nil()
                             .append(D::line())
                             .append(specifiers)
-                            .append(D::line())
-                            .nest(4),
+                            .append(trailing_comma())
+                            .nest(indent()),
                     )
+                    .append(D::line())
                     .append(D::text("}"))
                     .group()
             }
@@ -1933,16 +3400,25 @@ impl typescript::Pretty for ImportSpecifier {
 #[serde(rename_all = "kebab-case")]
 pub enum BuiltinKeyword {
     Keyof,
+    /// `readonly T[]`, an array or tuple type whose items can't be assigned.
+    Readonly,
 }
 
-impl typescript::Pretty for BuiltinKeyword {
-    fn to_ts(&self) -> D<()> {
+impl BuiltinKeyword {
+    pub fn name(&self) -> &'static str {
         match self {
-            BuiltinKeyword::Keyof => D::text("keyof"),
+            BuiltinKeyword::Keyof => "keyof",
+            BuiltinKeyword::Readonly => "readonly",
         }
     }
 }
 
+impl typescript::Pretty for BuiltinKeyword {
+    fn to_ts(&self) -> D<()> {
+        D::text(self.name())
+    }
+}
+
 #[derive(Derivative, Clone, Eq, Serialize)]
 #[derivative(PartialEq)]
 #[derivative(Debug)]
@@ -1953,6 +3429,9 @@ pub enum PrimitiveType {
     String,
     Object,
     Symbol,
+    /// The type of a single symbol. TypeScript only accepts it as the type of a `const` or of a
+    /// readonly property, such as the tag of a brand.
+    UniqueSymbol,
     BigInt,
     Void,
     Undefined,
@@ -1967,6 +3446,7 @@ impl Display for PrimitiveType {
             PrimitiveType::String => write!(f, "string"),
             PrimitiveType::Object => write!(f, "object"),
             PrimitiveType::Symbol => write!(f, "symbol"),
+            PrimitiveType::UniqueSymbol => write!(f, "unique symbol"),
             PrimitiveType::BigInt => write!(f, "bigint"),
             PrimitiveType::Void => write!(f, "void"),
             PrimitiveType::Undefined => write!(f, "undefined"),
@@ -2045,6 +3525,11 @@ impl typescript::Pretty for ObjectPropertyKey {
     fn to_ts(&self) -> D<()> {
         match self {
             ObjectPropertyKey::Index(index) => surround(index.to_ts(), "[", "]").group(),
+            ObjectPropertyKey::Key(key)
+                if typescript::is_reserved_word(key) || !typescript::is_identifier(key) =>
+            {
+                string_literal(key)
+            }
             ObjectPropertyKey::Key(key) => D::text(key.clone()),
             ObjectPropertyKey::Computed(id) => surround(id.to_ts(), "[", "]").group(),
         }
@@ -2143,29 +3628,76 @@ impl typescript::Pretty for ObjectProperty {
     }
 }
 
+/// A name and the span it is written at. Names are compared, ordered and hashed without their
+/// span, so an identifier can key the bindings of the name it declares.
 #[ast_node(transparent)]
 pub struct Ident {
     pub name: String,
+    /// Whether the name isn't an identifier and is written as a string, as the name a module
+    /// exports can be, `import {"a-b" as AB} from "m"`. It is emitted quoted.
+    #[derivative(PartialEq = "ignore")]
+    pub raw: bool,
+}
+
+impl std::hash::Hash for Ident {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+impl PartialOrd for Ident {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ident {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.cmp(&other.name)
+    }
+}
+
+impl std::borrow::Borrow<str> for Ident {
+    fn borrow(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for Ident {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
 }
 
 impl Ident {
     fn pretty(&self) -> D<()> {
-        D::text(&self.name)
+        if self.raw {
+            string_literal(&self.name)
+        } else {
+            D::text(&self.name)
+        }
     }
 }
 
 impl typescript::Pretty for Ident {
     fn to_ts(&self) -> D<()> {
-        D::text(self.name.clone())
+        self.pretty()
     }
 }
 
 #[ast_node]
 pub struct TypeParameter {
-    pub name: String,
+    pub name: Ident,
     pub constraint: Option<Ast>,
     pub default: Option<Ast>,
     pub rest: bool,
+    /// `const T`, inferring the narrowest type of the arguments, TypeScript 5.0.
+    #[serde(rename = "const", skip_serializing_if = "std::ops::Not::not")]
+    pub is_const: bool,
+    /// Spans of the name in the `where` and `defaults` clauses of the declaration.
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore", Debug = "ignore")]
+    pub clause_spans: Vec<Span>,
 }
 
 impl TypeParameter {
@@ -2188,10 +3720,16 @@ impl TypeParameter {
         span: Span,
     ) -> Self {
         Self {
-            name,
+            name: Ident {
+                name,
+                span,
+                raw: false,
+            },
             constraint,
             default,
             rest,
+            is_const: false,
+            clause_spans: vec![],
             span,
         }
     }
@@ -2200,6 +3738,11 @@ impl TypeParameter {
 impl typescript::Pretty for TypeParameter {
     fn to_ts(&self) -> D<()> {
         let rest = if self.rest { D::text("...") } else { D::nil() };
+        let modifier = if self.is_const {
+            D::text("const ")
+        } else {
+            D::nil()
+        };
 
         let constraint = match &self.constraint {
             Some(constraint) => D::space()
@@ -2218,17 +3761,38 @@ impl typescript::Pretty for TypeParameter {
         };
 
         D::nil()
+            .append(modifier)
             .append(rest)
-            .append(self.name.clone())
+            .append(D::text(&self.name.name))
             .append(constraint)
             .append(default_value)
     }
 }
 
-pub(crate) mod if_expr;
+pub(crate) mod attribute;
+
+pub(crate) mod access;
+
+pub mod if_expr;
 
 pub(crate) mod match_expr;
 
 pub(crate) mod cond_expr;
 
 pub(crate) mod let_expr;
+
+pub(crate) mod union_declaration;
+
+pub mod brand_declaration;
+
+pub(crate) mod pattern_declaration;
+
+pub(crate) mod syntax_error;
+
+pub(crate) mod structural;
+
+pub(crate) mod consed;
+
+pub mod origin;
+
+pub mod schema;
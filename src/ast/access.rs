@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::{
+    diagnostic::{codes, Diagnostic},
+    rewrite,
+    runtime::Helpers,
+};
+
+/// The types the names of a program stand for: the bodies of its aliases without type
+/// parameters, by the position of each name referring to one. Names are resolved by
+/// [`rewrite::declarations`], a name shadowed by a type parameter or a binding of a `let` stands
+/// for none.
+#[derive(Debug, Default)]
+pub struct Aliases(HashMap<(usize, usize), Ast>);
+
+impl Aliases {
+    pub fn new(program: &Ast) -> Self {
+        let Ast::Program(Program { statements, .. }) = program else {
+            return Self::default();
+        };
+
+        let bodies: HashMap<_, _> = statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Ast::Statement(statement) => match statement.inner.as_ref() {
+                    Ast::TypeAlias(TypeAlias {
+                        name, params, body, ..
+                    }) if params.is_empty() => Some(((name.span.start, name.span.end), body)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+
+        Self(
+            rewrite::declarations(program)
+                .into_iter()
+                .filter_map(|(name, declaration)| {
+                    Some((name, bodies.get(&declaration)?.as_ref().clone()))
+                })
+                .collect(),
+        )
+    }
+
+    /// The body of the alias `name` refers to.
+    pub fn body(&self, name: &Ident) -> Option<&Ast> {
+        self.0.get(&(name.span.start, name.span.end))
+    }
+
+    /// The object literal `name` stands for, if it refers to an alias of one.
+    pub fn object(&self, name: &Ident) -> Option<&TypeLiteral> {
+        match self.body(name)? {
+            Ast::TypeLiteral(literal) => Some(literal),
+            _ => None,
+        }
+    }
+}
+
+impl Access {
+    /// `lhs.key` as `lhs["key"]`, the only form TypeScript has, folded to the value of the
+    /// property when `lhs` is an object literal, or the name of an alias of one. A missing property,
+    /// as any property of a literal that isn't an object such as `{a: 1}.a.b`, is an error rather
+    /// than an access TypeScript rejects, and stands for `never`.
+    ///
+    /// An object with index signatures or computed keys isn't folded, either could be the key.
+    pub(crate) fn simplify(self, helpers: &Helpers) -> Ast {
+        let key = match (self.kind, self.rhs.as_ref()) {
+            (AccessKind::Dot, Ast::Ident(ident)) => ident.name.clone(),
+            (AccessKind::Index, Ast::TypeString(string)) => string.ty.clone(),
+            (AccessKind::Dot, _) => unreachable!("rhs of dot access should be an ident"),
+            _ => return Ast::Access(self),
+        };
+
+        if is_scalar(&self.lhs) {
+            return self.missing(
+                &key,
+                "only object literals have properties".to_string(),
+                helpers,
+            );
+        }
+
+        let literal = match self.lhs.as_ref() {
+            Ast::TypeLiteral(literal) => Some((literal.clone(), false)),
+            Ast::Ident(name) => helpers.object(name).map(|literal| (literal, true)),
+            _ => None,
+        };
+
+        match literal {
+            Some((literal, declared)) if is_foldable(&literal) => {
+                self.fold(&literal, &key, helpers, declared)
+            }
+            _ => self.unfolded(&key),
+        }
+    }
+
+    /// `lhs["key"]`, left to TypeScript.
+    fn unfolded(&self, key: &str) -> Ast {
+        Ast::Access(Access {
+            rhs: Ast::TypeString(TypeString {
+                ty: key.to_string(),
+                span: self.rhs.as_span(),
+            })
+            .into(),
+            kind: AccessKind::Index,
+            ..self.clone()
+        })
+    }
+
+    /// The value of the property `key` of `literal`, simplified first when it is the body of an
+    /// alias. An access within the body of the alias being simplified, `type B as {b: B.b}`, is
+    /// left to TypeScript.
+    fn fold(&self, literal: &TypeLiteral, key: &str, helpers: &Helpers, declared: bool) -> Ast {
+        let property = literal
+            .iter()
+            .find(|property| matches!(&property.key, ObjectPropertyKey::Key(name) if name == key));
+
+        let Some(property) = property else {
+            let keys = literal
+                .iter()
+                .filter_map(|property| match &property.key {
+                    ObjectPropertyKey::Key(name) => Some(format!("`{name}`")),
+                    _ => None,
+                })
+                .join(", ");
+
+            let note = if keys.is_empty() {
+                "it has no properties".to_string()
+            } else {
+                format!("its properties are {keys}")
+            };
+
+            return self.missing(key, note, helpers);
+        };
+
+        let value = match self.lhs.as_ref() {
+            Ast::Ident(name) if declared => {
+                match helpers.expanding(name, || helpers.simplify(&property.value)) {
+                    Some(value) => value,
+                    None => return self.unfolded(key),
+                }
+            }
+            _ => property.value.clone(),
+        };
+
+        // An optional property may be missing.
+        if property.optional {
+            Ast::UnionType(UnionType {
+                types: vec![
+                    value,
+                    Ast::Primitive(PrimitiveType::Undefined, Span::default()),
+                ],
+                span: self.span,
+            })
+        } else {
+            value
+        }
+    }
+}
+
+impl Access {
+    /// `never`, reporting that `lhs` has no property `key`.
+    fn missing(&self, key: &str, note: String, helpers: &Helpers) -> Ast {
+        helpers.report(
+            Diagnostic::error(
+                format!("`{}` has no property `{key}`", self.lhs),
+                self.rhs.as_span(),
+            )
+            .with_note(note)
+            .with_code(codes::MISSING_PROPERTY),
+        );
+
+        Ast::NeverKeyword(self.span)
+    }
+}
+
+/// A literal that isn't an object, such as the value a property access folds to.
+fn is_scalar(node: &Ast) -> bool {
+    matches!(
+        node,
+        Ast::TypeNumber(_)
+            | Ast::TypeString(_)
+            | Ast::TrueKeyword(_)
+            | Ast::FalseKeyword(_)
+            | Ast::Primitive(PrimitiveType::Null | PrimitiveType::Undefined, _)
+    )
+}
+
+/// Only literals keyed by names can be folded, other keys may overlap.
+fn is_foldable(literal: &TypeLiteral) -> bool {
+    literal
+        .iter()
+        .all(|property| matches!(property.key, ObjectPropertyKey::Key(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::{compile, compile_streaming, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use textwrap_macros::dedent;
+
+    fn compiled(source: &str) -> String {
+        compile(source, &CompileOptions::default())
+            .unwrap()
+            .typescript
+    }
+
+    fn errors(source: &str) -> Vec<(String, Vec<String>, &str)> {
+        compile(source, &CompileOptions::default())
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message,
+                    diagnostic.notes,
+                    &source[diagnostic.span.start..diagnostic.span.end],
+                )
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case("type A as {a: string, b: number}.a", "type A = string;\n\n")]
+    #[case(
+        r#"type A as {a: string, "b-c": number}["b-c"]"#,
+        "type A = number;\n\n"
+    )]
+    #[case("type A as {a: {b: 1}}.a.b", "type A = 1;\n\n")]
+    #[case("type A as {?a: string}.a", "type A = string | undefined;\n\n")]
+    fn literals_are_folded(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(compiled(source), expected);
+    }
+
+    #[test]
+    fn missing_property() {
+        assert_eq!(
+            errors("type A as {a: string, c: number}.b"),
+            vec![(
+                "`{a: string, c: number}` has no property `b`".to_string(),
+                vec!["its properties are `a`, `c`".to_string()],
+                "b"
+            )]
+        );
+        assert_eq!(
+            errors("type Options as {}\ntype A as Options[:b]"),
+            vec![(
+                "`Options` has no property `b`".to_string(),
+                vec!["it has no properties".to_string()],
+                ":b"
+            )]
+        );
+        assert_eq!(
+            errors("type A as {a: 1}.a.b"),
+            vec![(
+                "`1` has no property `b`".to_string(),
+                vec!["only object literals have properties".to_string()],
+                "b"
+            )]
+        );
+        assert_eq!(
+            errors("type Options as {a: {b: :c}}\ntype A as Options.a.b.d"),
+            vec![(
+                "`\"c\"` has no property `d`".to_string(),
+                vec!["only object literals have properties".to_string()],
+                "d"
+            )]
+        );
+    }
+
+    #[test]
+    fn aliases_are_folded() {
+        let source = dedent!(
+            r#"
+            type Options as {retries: number, mode: :fast | :slow}
+            type Mode as Options.mode
+            type Nested as {options: Options}.options.retries
+            "#
+        );
+
+        assert_eq!(
+            compiled(source),
+            dedent!(
+                "
+                type Options = {retries: number, mode: 'fast' | 'slow'};
+
+                type Mode = 'fast' | 'slow';
+
+                type Nested = number;
+
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    /// Only an alias of a literal is folded, one referring to another alias is left to TypeScript.
+    #[test]
+    fn one_level_of_aliases() {
+        let source = "type Options as {mode: :fast}\ntype Alias as Options\ntype A as Alias.mode";
+
+        assert!(compiled(source).contains("type A = Alias['mode'];"));
+    }
+
+    #[rstest]
+    #[case(
+        "type A(Options) as Options.mode",
+        "type A<Options> = Options['mode'];"
+    )]
+    #[case(
+        "type A as let Options = {mode: 1} in Options.mode",
+        "type A = {mode: 1}['mode'];"
+    )]
+    fn shadowed_aliases_are_not_folded(#[case] use_: &str, #[case] expected: &str) {
+        let source = format!("type Options as {{mode: :fast}}\n{use_}");
+
+        assert_eq!(compiled(&source).lines().nth(2), Some(expected));
+    }
+
+    #[test]
+    fn recursive_aliases() {
+        assert_eq!(compiled("type B as {b: B.b}"), "type B = {b: B['b']};\n\n");
+        assert_eq!(
+            compiled("type B as {b: C.c}\ntype C as {c: B.b}\ntype A as B.b"),
+            "type B = {b: C['c']};\n\ntype C = {c: B['b']};\n\ntype A = B['b'];\n\n"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "type A as {[K in string]: number}.a",
+        "type A = {[K in string]: number}['a'];\n\n"
+    )]
+    #[case("type A as {a: 1, [key]: 2}.a", "type A = {a: 1, [key]: 2}['a'];\n\n")]
+    fn index_signatures_are_not_folded(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(compiled(source), expected);
+    }
+
+    #[test]
+    fn streaming() {
+        let source = "type Options as {mode: :fast}\ntype A as {mode: :slow}.mode";
+        let mut typescript = String::new();
+
+        compile_streaming(source, &CompileOptions::default(), |ts| {
+            typescript.push_str(ts)
+        })
+        .unwrap();
+
+        assert_eq!(typescript, compiled(source));
+    }
+}
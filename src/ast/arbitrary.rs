@@ -0,0 +1,459 @@
+/// Random programs for property tests, built as the parser builds them: a chain of unions or of
+/// intersections as a single node, an else branch for every conditional, and only the nodes the
+/// grammar can express, so that a program printed as source parses back to itself.
+///
+/// ```ignore
+/// #[quickcheck]
+/// fn property(program: Ast) -> bool { ... }
+/// ```
+use quickcheck::{Arbitrary, Gen};
+
+use super::{
+    attribute::{AttrValue, Attribute},
+    brand_declaration::BrandDeclaration,
+    build, cond_expr,
+    if_expr::IfExpr,
+    let_expr::{Binding, LetExpr},
+    match_expr,
+    pattern_declaration::PatternDeclaration,
+    union_declaration::{UnionDeclaration, Variant, DEFAULT_DISCRIMINANT},
+    *,
+};
+
+/// Depth of the expressions of a statement, beyond which only leaves are generated.
+const DEPTH: usize = 4;
+
+const NAMES: &[&str] = &["A", "T", "value", "$x", "_y", "Ünï"];
+
+impl Arbitrary for Ast {
+    /// A program of a few statements.
+    fn arbitrary(g: &mut Gen) -> Self {
+        let count = 1 + below(g, 4);
+
+        build::program((0..count).map(|_| statement(g)))
+    }
+}
+
+/// A number in `0..n`.
+fn below(g: &mut Gen, n: usize) -> usize {
+    usize::arbitrary(g) % n
+}
+
+fn pick<T: Clone>(g: &mut Gen, items: &[T]) -> T {
+    g.choose(items).unwrap().clone()
+}
+
+fn name(g: &mut Gen) -> String {
+    pick(g, NAMES).to_string()
+}
+
+fn ident(g: &mut Gen) -> Ident {
+    Ident::from(pick(g, NAMES))
+}
+
+fn some<T>(g: &mut Gen, f: impl FnOnce(&mut Gen) -> T) -> Option<T> {
+    bool::arbitrary(g).then(|| f(g))
+}
+
+fn modifier(g: &mut Gen) -> MappingModifier {
+    match bool::arbitrary(g) {
+        true => MappingModifier::Add,
+        false => MappingModifier::Remove,
+    }
+}
+
+fn many<T>(g: &mut Gen, min: usize, mut f: impl FnMut(&mut Gen) -> T) -> Vec<T> {
+    let count = min + below(g, 3);
+
+    (0..count).map(|_| f(g)).collect()
+}
+
+fn doc(g: &mut Gen) -> Option<String> {
+    some(g, |g| {
+        pick(g, &["A", "First\nsecond", "`code` and @tag"]).to_string()
+    })
+}
+
+fn statement(g: &mut Gen) -> Ast {
+    let declaration = match below(g, 9) {
+        0 => type_alias(g),
+        1 => Ast::Interface(Interface {
+            doc: doc(g),
+            export: bool::arbitrary(g),
+            global: false,
+            default: false,
+            name: name(g),
+            extends: some(g, name),
+            params: params(g),
+            definition: many(g, 0, |g| property(g, DEPTH)),
+            span: Span::default(),
+        }),
+        2 => Ast::UnionDeclaration(UnionDeclaration {
+            doc: doc(g),
+            export: bool::arbitrary(g),
+            name: ident(g),
+            discriminant: pick(g, &[DEFAULT_DISCRIMINANT, "type", "the kind"]).to_string(),
+            variants: many(g, 1, |g| Variant {
+                name: ident(g),
+                body: TypeLiteral {
+                    properties: many(g, 0, |g| property(g, DEPTH)),
+                    span: Span::default(),
+                },
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        }),
+        3 => Ast::BrandDeclaration(BrandDeclaration {
+            doc: doc(g),
+            export: bool::arbitrary(g),
+            name: ident(g),
+            base: expr(g, DEPTH).into(),
+            span: Span::default(),
+        }),
+        4 => Ast::PatternDeclaration(PatternDeclaration {
+            doc: doc(g),
+            name: ident(g),
+            params: many(g, 0, ident),
+            body: expr(g, DEPTH).into(),
+            span: Span::default(),
+        }),
+        5 => Ast::ImportStatement(ImportStatement {
+            import_clause: if bool::arbitrary(g) {
+                ImportClause::Named(many(g, 0, |g| ImportSpecifier {
+                    module_export_name: ident(g),
+                    alias: some(g, ident),
+                    span: Span::default(),
+                }))
+            } else {
+                ImportClause::Namespace { alias: ident(g) }
+            },
+            module: pick(g, &["./a", "b", "it's"]).to_string(),
+            span: Span::default(),
+        }),
+        6 => Ast::ExportDefault(ExportDefault {
+            name: ident(g),
+            span: Span::default(),
+        }),
+        7 => Ast::AmbientModule(AmbientModule {
+            name: pick(g, &["express", "./a", "it's"]).to_string(),
+            // Blocks don't nest.
+            body: many(g, 0, |g| Ast::Statement(type_alias(g).into())),
+            span: Span::default(),
+        }),
+        // The expressions of a test are not separated, the grammar reads an expression starting
+        // with `(` or `[` as an application or an index of the one before it.
+        _ => Ast::UnitTest(UnitTest {
+            name: pick(g, &["\"a\"", "'b c'", ":d"]).to_string(),
+            body: many(g, 0, |g| {
+                build::apply(Ast::Ident(ident(g)), [expr(g, DEPTH - 1)])
+            }),
+            span: Span::default(),
+        }),
+    };
+
+    Ast::Statement(Statement {
+        attributes: many(g, 0, attribute),
+        inner: declaration.into(),
+    })
+}
+
+fn type_alias(g: &mut Gen) -> Ast {
+    let modifier = below(g, 4);
+
+    Ast::TypeAlias(TypeAlias {
+        doc: doc(g),
+        export: modifier == 1,
+        global: modifier == 2,
+        default: modifier == 3,
+        name: ident(g),
+        params: params(g),
+        body: expr(g, DEPTH).into(),
+        span: Span::default(),
+    })
+}
+
+/// Parameters of distinct names, the `defaults` and `where` clauses referring to them by name.
+fn params(g: &mut Gen) -> Vec<TypeParameter> {
+    let first = below(g, NAMES.len());
+
+    (0..below(g, 3))
+        .map(|i| {
+            TypeParameter::new(
+                NAMES[(first + i) % NAMES.len()].to_string(),
+                some(g, |g| expr(g, 1)),
+                some(g, |g| expr(g, 1)),
+                false,
+                Span::default(),
+            )
+        })
+        .collect()
+}
+
+fn attribute(g: &mut Gen) -> Attribute {
+    Attribute {
+        name: ident(g),
+        args: many(g, 0, |g| match below(g, 3) {
+            0 => AttrValue::String(pick(g, &["client", "say \"hi\""]).to_string()),
+            1 => AttrValue::Number(pick(g, &["2", "-1.5"]).to_string()),
+            _ => AttrValue::Ident(name(g)),
+        }),
+        span: Span::default(),
+    }
+}
+
+fn leaf(g: &mut Gen) -> Ast {
+    match below(g, 10) {
+        0 => Ast::Primitive(
+            pick(
+                g,
+                &[
+                    PrimitiveType::Boolean,
+                    PrimitiveType::Number,
+                    PrimitiveType::String,
+                    PrimitiveType::Object,
+                    PrimitiveType::Symbol,
+                    PrimitiveType::UniqueSymbol,
+                    PrimitiveType::BigInt,
+                    PrimitiveType::Void,
+                    PrimitiveType::Undefined,
+                    PrimitiveType::Null,
+                ],
+            ),
+            Span::default(),
+        ),
+        1 => build::string_literal(pick(g, &["a", "b c", "it's", "say \"hi\"", "a\\b"])),
+        2 => build::number_literal(pick(g, &["0", "-2.5", "1_000"])),
+        3 => Ast::TemplateString(TemplateString {
+            ty: pick(g, &["`a`", "`a${T}b`"]).to_string(),
+            span: Span::default(),
+        }),
+        4 => pick(
+            g,
+            &[
+                Ast::TrueKeyword(Span::default()),
+                Ast::FalseKeyword(Span::default()),
+                Ast::NeverKeyword(Span::default()),
+                Ast::UnknownKeyword(Span::default()),
+                Ast::AnyKeyword(Span::default()),
+                Ast::ThisType(Span::default()),
+            ],
+        ),
+        5 => Ast::ImportType(ImportType {
+            module: pick(g, &["./a", "b"]).to_string(),
+            span: Span::default(),
+        }),
+        _ => Ast::Ident(ident(g)),
+    }
+}
+
+/// A type expression, nested at most `depth` deep.
+fn expr(g: &mut Gen, depth: usize) -> Ast {
+    if depth == 0 {
+        return leaf(g);
+    }
+
+    let depth = depth - 1;
+
+    match below(g, 22) {
+        0 => match (expr(g, depth), expr(g, depth)) {
+            (Ast::UnionType(UnionType { mut types, .. }), rhs) => {
+                types.push(rhs);
+                build::union(types)
+            }
+            (lhs, rhs) => build::union([lhs, rhs]),
+        },
+        1 => match (expr(g, depth), expr(g, depth)) {
+            (Ast::IntersectionType(IntersectionType { mut types, .. }), rhs) => {
+                types.push(rhs);
+                build::intersection(types)
+            }
+            (lhs, rhs) => build::intersection([lhs, rhs]),
+        },
+        2 => build::array(expr(g, depth)),
+        3 => build::tuple(many(g, 0, |g| expr(g, depth))),
+        4 => build::apply(expr(g, depth), many(g, 0, |g| expr(g, depth))),
+        5 => build::index(expr(g, depth), expr(g, depth)),
+        6 => Ast::Access(Access {
+            lhs: expr(g, depth).into(),
+            rhs: expr(g, depth).into(),
+            kind: AccessKind::Dot,
+            span: Span::default(),
+        }),
+        // The parser only accepts names around `::`, or a module on the left.
+        7 => Ast::Access(Access {
+            lhs: if bool::arbitrary(g) {
+                Ast::Ident(ident(g))
+            } else {
+                build::ident("NS")
+            }
+            .into(),
+            rhs: Ast::Ident(ident(g)).into(),
+            kind: AccessKind::Namespace,
+            span: Span::default(),
+        }),
+        8 => Ast::Builtin(Builtin {
+            name: pick(g, &[BuiltinKeyword::Keyof, BuiltinKeyword::Readonly]),
+            argument: expr(g, depth).into(),
+            span: Span::default(),
+        }),
+        9 => Ast::Infer(expr(g, depth).into()),
+        10 => Ast::TypeLiteral(TypeLiteral {
+            properties: many(g, 0, |g| property(g, depth)),
+            span: Span::default(),
+        }),
+        11 => {
+            let predicate = some(g, |g| {
+                let asserts = bool::arbitrary(g);
+
+                TypePredicate {
+                    asserts,
+                    param: ident(g),
+                    target: if asserts {
+                        some(g, |g| expr(g, depth).into())
+                    } else {
+                        Some(expr(g, depth).into())
+                    },
+                    span: Span::default(),
+                }
+            });
+
+            // The return type the parser gives a predicate.
+            let return_type = match &predicate {
+                Some(TypePredicate { asserts: true, .. }) => {
+                    Ast::Primitive(PrimitiveType::Void, Span::default())
+                }
+                Some(_) => Ast::Primitive(PrimitiveType::Boolean, Span::default()),
+                None => expr(g, depth),
+            };
+
+            Ast::FunctionType(FunctionType {
+                params: many(g, 0, |g| Parameter {
+                    ellipsis: bool::arbitrary(g),
+                    name: name(g),
+                    kind: expr(g, depth),
+                    span: Span::default(),
+                }),
+                return_type: return_type.into(),
+                predicate,
+                span: Span::default(),
+            })
+        }
+        12 => Ast::MappedType(MappedType {
+            index: name(g),
+            iterable: expr(g, depth).into(),
+            remapped_as: some(g, |g| expr(g, depth).into()),
+            readonly_mod: some(g, modifier),
+            optional_mod: some(g, modifier),
+            body: expr(g, depth).into(),
+            span: Span::default(),
+        }),
+        13 => Ast::IfExpr(IfExpr {
+            condition: condition(g, depth).into(),
+            then_branch: expr(g, depth).into(),
+            else_branch: Some(expr(g, depth).into()),
+            pass: false,
+            span: Span::default(),
+        }),
+        14 => Ast::MatchExpr(match_expr::MatchExpr {
+            value: expr(g, depth).into(),
+            arms: many(g, 1, |g| match_expr::Arm {
+                pattern: expr(g, depth),
+                body: expr(g, depth),
+                span: Span::default(),
+            }),
+            else_arm: expr(g, depth).into(),
+            span: Span::default(),
+        }),
+        15 => Ast::CondExpr(cond_expr::CondExpr {
+            arms: many(g, 1, |g| cond_expr::Arm {
+                condition: condition(g, depth),
+                body: expr(g, depth),
+                span: Span::default(),
+            }),
+            else_arm: Some(expr(g, depth).into()),
+            span: Span::default(),
+        }),
+        16 => Ast::LetExpr(LetExpr {
+            bindings: many(g, 1, |g| Binding {
+                name: ident(g),
+                value: expr(g, depth),
+                span: Span::default(),
+            }),
+            body: expr(g, depth).into(),
+            span: Span::default(),
+        }),
+        _ => leaf(g),
+    }
+}
+
+fn property(g: &mut Gen, depth: usize) -> ObjectProperty {
+    let key = match below(g, 3) {
+        0 => ObjectPropertyKey::Key(pick(g, &["a", "b c", "type", "$x"]).to_string()),
+        1 => ObjectPropertyKey::Computed(ident(g)),
+        _ => ObjectPropertyKey::Index(PropertyKeyIndex {
+            key: name(g),
+            iterable: expr(g, depth),
+            remapped_as: some(g, |g| expr(g, depth)),
+            span: Span::default(),
+        }),
+    };
+
+    ObjectProperty {
+        readonly: bool::arbitrary(g),
+        optional: bool::arbitrary(g),
+        key,
+        value: expr(g, depth),
+        span: Span::default(),
+    }
+}
+
+/// The test of an `if` or a `cond` arm. `not` only applies to a comparison or a connective. The
+/// grammar reads `==` and `!==` as `=` and `!=` followed by a stray `=`, they are left out.
+fn condition(g: &mut Gen, depth: usize) -> Ast {
+    let comparison = |g: &mut Gen| {
+        let op = pick(
+            g,
+            &[
+                InfixOp::Extends,
+                InfixOp::NotExtends,
+                InfixOp::Equals,
+                InfixOp::NotEquals,
+            ],
+        );
+
+        Ast::ExtendsInfixOp(ExtendsInfixOp {
+            lhs: expr(g, depth).into(),
+            non_distributive: matches!(op, InfixOp::Extends | InfixOp::NotExtends)
+                && bool::arbitrary(g),
+            op,
+            rhs: expr(g, depth).into(),
+            span: Span::default(),
+        })
+    };
+
+    if depth == 0 {
+        return comparison(g);
+    }
+
+    match below(g, 5) {
+        0 => Ast::ExtendsInfixOp(ExtendsInfixOp {
+            lhs: condition(g, depth - 1).into(),
+            op: pick(g, &[InfixOp::And, InfixOp::Or]),
+            rhs: condition(g, depth - 1).into(),
+            non_distributive: false,
+            span: Span::default(),
+        }),
+        1 => Ast::ExtendsPrefixOp(ExtendsPrefixOp {
+            op: PrefixOp::Not,
+            value: comparison(g).into(),
+            span: Span::default(),
+        }),
+        // `all!` and `any!` test a list of conditions.
+        2 => Ast::MacroCall(MacroCall {
+            name: pick(g, &["all!", "any!"]).to_string(),
+            args: vec![build::tuple(many(g, 0, |g| condition(g, depth - 1)))],
+            span: Span::default(),
+        }),
+        _ => comparison(g),
+    }
+}
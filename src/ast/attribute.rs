@@ -0,0 +1,193 @@
+use super::*;
+
+/// `@emit("client")`, the outputs a declaration is emitted to, see [`crate::compile::tags`].
+pub const EMIT: &str = "emit";
+/// `@deprecated("use B")`, emitted as a `@deprecated` tag of the doc comment of the declaration.
+pub const DEPRECATED: &str = "deprecated";
+
+/// `@companion`, a constant holding the literal object an alias stands for, see
+/// [`crate::companion`].
+pub const COMPANION: &str = "companion";
+
+/// The statements of an `#if flag("name") do ... end` block, which the parser tags with this
+/// attribute holding the name of the flag, see [`crate::flags`]. It can't be written as `@` one.
+pub const IF_FLAG: &str = "#if";
+/// The statements after the `#else` of an `#if` block, tagged as [`IF_FLAG`] ones are.
+pub const ELSE_FLAG: &str = "#else";
+
+/// Attributes claimed by a pass. The others are reported as unknown, nothing would read them; a
+/// pass reading a new attribute adds its name here.
+pub const ATTRIBUTES: &[&str] = &[EMIT, DEPRECATED, COMPANION];
+
+/// `@name(args...)`, written on the lines before a statement for the passes to read, see
+/// [`Statement::attributes`]. The parentheses can be left out when there are no arguments.
+#[ast_node]
+pub struct Attribute {
+    pub name: Ident,
+    pub args: Vec<AttrValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttrValue {
+    String(String),
+    Number(String),
+    Ident(String),
+}
+
+impl Attribute {
+    /// The arguments that are strings, such as the tags of `@emit("client", "server")`.
+    pub fn strings(&self) -> impl Iterator<Item = &str> {
+        self.args.iter().filter_map(|arg| match arg {
+            AttrValue::String(string) => Some(string.as_str()),
+            _ => None,
+        })
+    }
+}
+
+impl Statement {
+    /// The first attribute named `name`.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.name == name)
+    }
+}
+
+/// A `@deprecated` statement with the tag of its attribute added to the doc comment of its
+/// declaration, for TypeScript editors to strike through its uses. The attribute is dropped, the
+/// tag takes its place.
+pub(crate) fn deprecate(statement: Ast) -> Ast {
+    let Ast::Statement(mut statement) = statement else {
+        return statement;
+    };
+
+    let Some(position) = statement
+        .attributes
+        .iter()
+        .position(|attribute| attribute.name.name == DEPRECATED)
+    else {
+        return Ast::Statement(statement);
+    };
+
+    let attribute = statement.attributes.remove(position);
+
+    let tag = match attribute.strings().next() {
+        Some(message) => format!("@deprecated {message}"),
+        None => "@deprecated".to_string(),
+    };
+
+    if let Some(doc) = Rc::make_mut(&mut statement.inner).doc_mut() {
+        *doc = Some(match doc.take() {
+            Some(doc) => format!("{doc}\n{tag}"),
+            None => tag,
+        });
+    }
+
+    Ast::Statement(statement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compile::{compile, CompileOptions},
+        test_support::parse,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    #[test]
+    fn parsed() {
+        let program = parse!(dedent!(
+            r#"
+            @deprecated("use B")
+            @emit("client", "server")
+            @since(2, next)
+            @internal
+            type A as 1
+            "#
+        ));
+
+        let Ast::Program(Program { statements, .. }) = program else {
+            unreachable!()
+        };
+
+        let attributes: Vec<_> = statements[0]
+            .attributes()
+            .iter()
+            .map(|attribute| (attribute.name.name.as_str(), attribute.args.clone()))
+            .collect();
+
+        assert_eq!(
+            attributes,
+            vec![
+                ("deprecated", vec![AttrValue::String("use B".to_string())]),
+                (
+                    "emit",
+                    vec![
+                        AttrValue::String("client".to_string()),
+                        AttrValue::String("server".to_string())
+                    ]
+                ),
+                (
+                    "since",
+                    vec![
+                        AttrValue::Number("2".to_string()),
+                        AttrValue::Ident("next".to_string())
+                    ]
+                ),
+                ("internal", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn serialized() {
+        assert_eq!(
+            parse!("@deprecated(\"use B\")\ntype A as 1")
+                .to_sexp()
+                .unwrap()
+                .to_string(),
+            "(program (statements (statement (attributes ((name . \"deprecated\") (args (string . \
+             \"use B\")))) (inner type (export . #f) (name . \"A\") (params) (body number . \
+             \"1\")))))"
+        );
+    }
+
+    #[test]
+    fn deprecated() {
+        let source = dedent!(
+            r#"
+            /** A pair */
+            @deprecated("use `Tuple`")
+            export type Pair as [1, 2]
+
+            @deprecated
+            interface Box {value: 1}
+            "#
+        );
+
+        assert_eq!(
+            compile(source, &CompileOptions::default())
+                .unwrap()
+                .typescript,
+            dedent!(
+                r#"
+                /**
+                 * A pair
+                 * @deprecated use `Tuple`
+                 */
+                export type Pair = [1, 2];
+
+                /** @deprecated */
+                interface Box {
+                    value: 1;
+                }
+
+                "#
+            )
+            .trim_start()
+        );
+    }
+}
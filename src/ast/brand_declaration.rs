@@ -0,0 +1,166 @@
+use super::*;
+
+/// Key of the property a branded type is tagged with.
+pub const BRAND_KEY: &str = "__brand";
+
+/// A nominal type, `brand UserId = string`, that is its base type but doesn't accept the base
+/// type, nor another brand of it.
+#[ast_node]
+pub struct BrandDeclaration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    pub export: bool,
+    pub name: Ident,
+    pub base: Rc<Ast>,
+}
+
+/// How the tag of a branded type is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrandEncoding {
+    /// `string & {readonly __brand: unique symbol}`, each brand being a symbol of its own.
+    #[default]
+    Symbol,
+    /// `string & {readonly __brand: "UserId"}`, the name of the brand, which shows in the errors
+    /// of the type checker.
+    String,
+}
+
+impl BrandDeclaration {
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&Ast) -> Ast,
+    {
+        Self {
+            base: f(&self.base).into(),
+            ..self.clone()
+        }
+    }
+
+    /// The statement declaring the brand as an alias of its base type tagged with a readonly
+    /// property.
+    pub fn simplify(&self, encoding: BrandEncoding) -> Ast {
+        // The symbol is spanned by the name of the brand, which tells the symbols of two brands
+        // apart, see [`Ast::is_subtype`].
+        let tag = match encoding {
+            BrandEncoding::Symbol => Ast::Primitive(PrimitiveType::UniqueSymbol, self.name.span),
+            BrandEncoding::String => Ast::TypeString(TypeString {
+                ty: self.name.name.clone(),
+                span: self.name.span,
+            }),
+        };
+
+        let tag = Ast::TypeLiteral(TypeLiteral {
+            properties: vec![ObjectProperty {
+                readonly: true,
+                optional: false,
+                key: ObjectPropertyKey::Key(BRAND_KEY.to_string()),
+                value: tag,
+                span: self.name.span,
+            }],
+            span: self.name.span,
+        });
+
+        Ast::Statement(
+            Ast::TypeAlias(TypeAlias {
+                doc: self.doc.clone(),
+                export: self.export,
+                global: false,
+                default: false,
+                name: self.name.clone(),
+                params: vec![],
+                body: Ast::IntersectionType(IntersectionType {
+                    types: vec![(*self.base).clone(), tag],
+                    span: self.span,
+                })
+                .into(),
+                span: self.span,
+            })
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compile::{compile, CompileOptions},
+        extends_result::ExtendsResult,
+        test_support::parse,
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use textwrap_macros::dedent;
+
+    /// The body of each alias the brands of `source` are simplified to.
+    fn brands(source: &str, encoding: BrandEncoding) -> Vec<Ast> {
+        let macros = crate::runtime::MacroRegistry::default();
+        let (program, _) = parse!(source)
+            .simplify_with_macros(&macros, encoding)
+            .unwrap();
+
+        let Ast::Program(Program { statements, .. }) = program else {
+            unreachable!()
+        };
+
+        statements
+            .into_iter()
+            .map(|statement| match statement {
+                Ast::Statement(inner) => match inner.as_ref() {
+                    Ast::TypeAlias(alias) => alias.body.as_ref().clone(),
+                    node => panic!("expected an alias, got {node:?}"),
+                },
+                node => panic!("expected a statement, got {node:?}"),
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case::symbol(BrandEncoding::Symbol)]
+    #[case::string(BrandEncoding::String)]
+    fn distinct_brands(#[case] encoding: BrandEncoding) {
+        let [user_id, order_id] = brands("brand UserId = string\nbrand OrderId = string", encoding)
+            .try_into()
+            .unwrap();
+
+        let string = Ast::Primitive(PrimitiveType::String, Span::default());
+
+        assert_eq!(user_id.is_subtype(&order_id), ExtendsResult::False);
+        assert_eq!(order_id.is_subtype(&user_id), ExtendsResult::False);
+        assert_eq!(user_id.is_subtype(&user_id), ExtendsResult::True);
+        assert_eq!(user_id.is_subtype(&string), ExtendsResult::True);
+        assert_eq!(order_id.is_subtype(&string), ExtendsResult::True);
+        assert_eq!(string.is_subtype(&user_id), ExtendsResult::False);
+    }
+
+    #[rstest]
+    #[case::symbol(
+        BrandEncoding::Symbol,
+        "export type UserId = string & {readonly __brand: unique symbol};"
+    )]
+    #[case::string(
+        BrandEncoding::String,
+        "export type UserId = string & {readonly __brand: 'UserId'};"
+    )]
+    fn encoding(#[case] brand_encoding: BrandEncoding, #[case] expected: &str) {
+        let source = dedent!(
+            r#"
+            /** Identifies a user */
+            export brand UserId = string
+            "#
+        );
+
+        let options = CompileOptions {
+            brand_encoding,
+            ..Default::default()
+        };
+
+        let output = compile(source, &options).unwrap();
+
+        assert_eq!(
+            output.typescript.trim(),
+            format!("/** Identifies a user */\n{expected}")
+        );
+    }
+}
@@ -0,0 +1,381 @@
+/// Functions building trees in Rust code instead of parsing them, for types generated from other
+/// definitions. The nodes have no span, so they are equal to the parsed nodes of the same type.
+///
+/// ```
+/// use newtype::prelude::{build::*, *};
+///
+/// let pair = program([alias("Pair")
+///     .export()
+///     .param("T", Some(string()))
+///     .body(tuple([ident("T"), ident("T")]))]);
+///
+/// assert_eq!(pair, parse_source("export type Pair(T) where T <: string as [T, T]").unwrap());
+/// ```
+use super::*;
+
+pub fn program(statements: impl IntoIterator<Item = Ast>) -> Ast {
+    Ast::Program(Program {
+        statements: statements.into_iter().collect(),
+        span: Span::default(),
+    })
+}
+
+pub fn ident(name: &str) -> Ast {
+    Ident::from(name).into()
+}
+
+/// A string literal type.
+pub fn string_literal(value: &str) -> Ast {
+    TypeString::from(value).into()
+}
+
+/// A number literal type.
+pub fn number_literal(value: impl Display) -> Ast {
+    Ast::TypeNumber(TypeNumber {
+        ty: value.to_string(),
+        span: Span::default(),
+    })
+}
+
+fn primitive(primitive: PrimitiveType) -> Ast {
+    Ast::Primitive(primitive, Span::default())
+}
+
+pub fn string() -> Ast {
+    primitive(PrimitiveType::String)
+}
+
+pub fn number() -> Ast {
+    primitive(PrimitiveType::Number)
+}
+
+pub fn boolean() -> Ast {
+    primitive(PrimitiveType::Boolean)
+}
+
+pub fn null() -> Ast {
+    primitive(PrimitiveType::Null)
+}
+
+pub fn undefined() -> Ast {
+    primitive(PrimitiveType::Undefined)
+}
+
+pub fn never() -> Ast {
+    Ast::NeverKeyword(Span::default())
+}
+
+pub fn unknown() -> Ast {
+    Ast::UnknownKeyword(Span::default())
+}
+
+/// An object type, from properties or `(key, value)` pairs.
+pub fn object(properties: impl IntoIterator<Item = impl Into<ObjectProperty>>) -> Ast {
+    Ast::TypeLiteral(TypeLiteral {
+        properties: properties.into_iter().map(Into::into).collect(),
+        span: Span::default(),
+    })
+}
+
+/// A property of an object type, to be made [`ObjectProperty::optional`] or
+/// [`ObjectProperty::readonly`].
+pub fn property(key: &str, value: Ast) -> ObjectProperty {
+    ObjectProperty {
+        readonly: false,
+        optional: false,
+        key: ObjectPropertyKey::Key(key.to_string()),
+        value,
+        span: Span::default(),
+    }
+}
+
+/// `[key in iterable]: value`, a property for every key of `iterable`, such as an index signature
+/// when it is `string`.
+pub fn index_property(key: &str, iterable: Ast, value: Ast) -> ObjectProperty {
+    ObjectProperty {
+        readonly: false,
+        optional: false,
+        key: ObjectPropertyKey::Index(PropertyKeyIndex {
+            key: key.to_string(),
+            iterable,
+            remapped_as: None,
+            span: Span::default(),
+        }),
+        value,
+        span: Span::default(),
+    }
+}
+
+impl ObjectProperty {
+    pub fn optional(self) -> Self {
+        Self {
+            optional: true,
+            ..self
+        }
+    }
+
+    pub fn readonly(self) -> Self {
+        Self {
+            readonly: true,
+            ..self
+        }
+    }
+}
+
+impl From<(&str, Ast)> for ObjectProperty {
+    fn from((key, value): (&str, Ast)) -> Self {
+        property(key, value)
+    }
+}
+
+pub fn tuple(items: impl IntoIterator<Item = Ast>) -> Ast {
+    Ast::Tuple(Tuple {
+        items: items.into_iter().collect(),
+        span: Span::default(),
+    })
+}
+
+pub fn array(item: Ast) -> Ast {
+    Ast::Array(item.into())
+}
+
+pub fn union(types: impl IntoIterator<Item = Ast>) -> Ast {
+    Ast::UnionType(UnionType {
+        types: types.into_iter().collect(),
+        span: Span::default(),
+    })
+}
+
+/// A union of `types` without the members that add nothing to it, see [`normalize_union`]. A
+/// single member is the type itself.
+pub fn normalized_union(types: impl IntoIterator<Item = Ast>) -> Ast {
+    let mut types = normalize_union(types.into_iter().collect());
+
+    match types.len() {
+        0 => never(),
+        1 => types.remove(0),
+        _ => union(types),
+    }
+}
+
+pub fn intersection(types: impl IntoIterator<Item = Ast>) -> Ast {
+    Ast::IntersectionType(IntersectionType {
+        types: types.into_iter().collect(),
+        span: Span::default(),
+    })
+}
+
+/// `receiver(args...)`, a generic type applied to arguments.
+pub fn apply(receiver: Ast, args: impl IntoIterator<Item = Ast>) -> Ast {
+    Ast::ApplyGeneric(ApplyGeneric {
+        receiver: receiver.into(),
+        args: args.into_iter().collect(),
+        span: Span::default(),
+    })
+}
+
+/// `lhs[index]`, an indexed access.
+pub fn index(lhs: Ast, index: Ast) -> Ast {
+    Ast::Access(Access {
+        lhs: lhs.into(),
+        rhs: index.into(),
+        kind: AccessKind::Index,
+        span: Span::default(),
+    })
+}
+
+pub fn keyof(argument: Ast) -> Ast {
+    Ast::Builtin(Builtin {
+        name: BuiltinKeyword::Keyof,
+        argument: argument.into(),
+        span: Span::default(),
+    })
+}
+
+/// `?name`, a type inferred in the pattern of a conditional type.
+pub fn infer(name: &str) -> Ast {
+    Ast::Infer(ident(name).into())
+}
+
+/// `if lhs <: rhs then then_branch else else_branch end`.
+pub fn conditional(lhs: Ast, rhs: Ast, then_branch: Ast, else_branch: Ast) -> Ast {
+    Ast::ExtendsExpr(ExtendsExpr {
+        lhs: lhs.into(),
+        rhs: rhs.into(),
+        then_branch: then_branch.into(),
+        else_branch: else_branch.into(),
+        non_distributive: false,
+        span: Span::default(),
+    })
+}
+
+/// Starts the declaration of a type alias, finished by [`AliasBuilder::body`].
+pub fn alias(name: &str) -> AliasBuilder {
+    AliasBuilder {
+        doc: None,
+        export: false,
+        name: name.into(),
+        params: vec![],
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AliasBuilder {
+    doc: Option<String>,
+    export: bool,
+    name: Ident,
+    params: Vec<TypeParameter>,
+}
+
+impl AliasBuilder {
+    pub fn export(self) -> Self {
+        Self {
+            export: true,
+            ..self
+        }
+    }
+
+    /// Documents the alias, `doc` being the whole comment, e.g. `/** A pair */`.
+    pub fn doc(self, doc: &str) -> Self {
+        Self {
+            doc: Some(doc.to_string()),
+            ..self
+        }
+    }
+
+    /// Adds a type parameter, which extends `constraint` if there is one.
+    pub fn param(mut self, name: &str, constraint: Option<Ast>) -> Self {
+        self.params.push(TypeParameter::new(
+            name.to_string(),
+            constraint,
+            None,
+            false,
+            Span::default(),
+        ));
+
+        self
+    }
+
+    /// The statement declaring the alias as `body`.
+    pub fn body(self, body: Ast) -> Ast {
+        Ast::Statement(
+            Ast::TypeAlias(TypeAlias {
+                doc: self.doc,
+                export: self.export,
+                global: false,
+                default: false,
+                name: self.name,
+                params: self.params,
+                body: body.into(),
+                span: Span::default(),
+            })
+            .into(),
+        )
+    }
+}
+
+impl From<&str> for Ident {
+    fn from(name: &str) -> Self {
+        Ident {
+            name: name.to_string(),
+            span: Span::default(),
+            raw: false,
+        }
+    }
+}
+
+impl From<&str> for TypeString {
+    fn from(value: &str) -> Self {
+        TypeString {
+            ty: value.to_string(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl From<Ident> for Ast {
+    fn from(ident: Ident) -> Self {
+        Ast::Ident(ident)
+    }
+}
+
+impl From<TypeString> for Ast {
+    fn from(string: TypeString) -> Self {
+        Ast::TypeString(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::parse, typescript::Pretty as _};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::object(
+        alias("User").export().body(object([
+            ("id", number()),
+            ("name", string()),
+            ("tags", array(string())),
+        ])),
+        "export type User as {id: number, name: string, tags: string[]}"
+    )]
+    #[case::modifiers(
+        alias("Options").body(object([
+            property("width", number()).optional(),
+            property("kind", union([string_literal("a"), string_literal("b")])).readonly(),
+            property("nested", object([("ok", boolean())])),
+        ])),
+        "type Options as {?width: number, readonly kind: \"a\" | \"b\", nested: {ok: boolean}}"
+    )]
+    #[case::generic(
+        alias("Pair")
+            .param("K", Some(string()))
+            .param("V", None)
+            .body(tuple([ident("K"), apply(ident("Record"), [ident("K"), ident("V")])])),
+        "type Pair(K, V) where K <: string as [K, Record(K, V)]"
+    )]
+    #[case::conditional(
+        alias("Unwrap").param("T", None).body(conditional(
+            ident("T"),
+            apply(ident("Promise"), [infer("U")]),
+            ident("U"),
+            ident("T"),
+        )),
+        "type Unwrap(T) as if T <: Promise(?U) then U else T end"
+    )]
+    #[case::access(
+        alias("Value").param("T", None).body(intersection([
+            index(ident("T"), string_literal("value")),
+            keyof(union([ident("T"), null(), undefined(), number_literal(1)])),
+        ])),
+        "type Value(T) as T[\"value\"] & keyof(T | null | undefined | 1)"
+    )]
+    fn built_as_parsed(#[case] built: Ast, #[case] source: &str) {
+        let built = program([built]).simplify();
+        let parsed = parse!(source).simplify();
+
+        assert_eq!(built, parsed);
+        assert_eq!(built.render_pretty_ts(80), parsed.render_pretty_ts(80));
+    }
+
+    #[test]
+    fn normalized() {
+        assert_eq!(
+            normalized_union([string_literal("a"), string(), string_literal("a")]),
+            string()
+        );
+        assert_eq!(
+            normalized_union([never(), number_literal(1), ident("T")]),
+            union([number_literal(1), ident("T")])
+        );
+        assert_eq!(normalized_union([]), never());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Ast::from(Ident::from("T")), ident("T"));
+        assert_eq!(Ast::from(TypeString::from("a")), string_literal("a"));
+    }
+}
@@ -1,10 +1,10 @@
 use super::*;
+use if_expr::MissingElse;
 
 #[ast_node]
 pub struct CondExpr {
     pub arms: Vec<Arm>,
-    /// Unlike match and if expressions, the else arm is *not* optional
-    pub else_arm: Rc<Ast>,
+    pub else_arm: Option<Rc<Ast>>,
 }
 
 impl CondExpr {
@@ -22,24 +22,50 @@ impl CondExpr {
                 body: f(&arm.body),
             })
             .collect();
-        expr.else_arm = f(&self.else_arm).into();
+        expr.else_arm = self.else_arm.as_ref().map(|else_arm| f(else_arm).into());
         expr
     }
 
-    pub(crate) fn simplify(&self) -> Ast {
+    /// `T`, when every arm checks `T <: U` for the same `T`.
+    pub(crate) fn scrutinee(&self) -> Option<&Ast> {
+        let mut scrutinees = self
+            .arms
+            .iter()
+            .map(|arm| if_expr::scrutinee(&arm.condition));
+
+        let first = scrutinees.next()??;
+
+        scrutinees
+            .all(|scrutinee| scrutinee == Some(first))
+            .then_some(first)
+    }
+
+    /// Nested conditional types, a missing else arm standing for what `missing_else` says.
+    pub(crate) fn simplify(&self, missing_else: MissingElse) -> Ast {
         // Convert a CondExpr to a series of nested ternary expressions
         let CondExpr { arms, else_arm, .. } = self;
 
-        let init_else: Ast = else_arm.into();
+        let init_else: Ast = match else_arm {
+            Some(else_arm) => (**else_arm).clone(),
+            None => missing_else.fallback(self.scrutinee(), self.span),
+        };
 
         let acc: Ast = arms.iter().rev().fold(init_else, |else_arm, arm| {
             let Arm {
                 condition,
                 body: then,
-                ..
+                span,
             } = arm;
 
-            if_expr::expand_to_extends(condition, then, &else_arm)
+            // The `extends` expression testing the condition stands for the whole arm, as that
+            // of an arm of a `match` does.
+            match if_expr::expand_to_extends(condition, then, &else_arm) {
+                Ast::ExtendsExpr(extends) => Ast::ExtendsExpr(ExtendsExpr {
+                    span: *span,
+                    ..extends
+                }),
+                tree => tree,
+            }
         });
 
         acc
@@ -0,0 +1,271 @@
+/// Hash-consing of nodes, for the passes handling many copies of the same subtree, such as the
+/// unions of a generated program repeating one object type in each of their members.
+///
+/// A [`Consed`] table hands out a single `Rc` for every node of the same structure, so that
+/// comparing two interned nodes is comparing pointers: `Rc<Ast>` is `Eq`, and `==` on two `Rc`s
+/// checks whether they point to the same node before walking them, which makes equal shared
+/// children cheap to compare in any node holding them.
+///
+/// [`normalize_union`] interns the members of a union, so that its duplicates are dropped by
+/// address before every member is compared with every other, and the evaluation of conditional
+/// types checks each pair of interned types for [subtyping](Consed::is_subtype) once.
+///
+/// Interning is opt-in and shallow: a node is shared whole, its children aren't interned on their
+/// own. Passes rewriting a tree build new nodes rather than change them in place, so a shared node
+/// is never changed under another occurrence; one that does change it goes through
+/// [`Rc::make_mut`], which copies it first.
+///
+/// Nodes mentioning a `unique symbol` are never shared, each is a different type told apart by
+/// where it is written, which structural equality ignores.
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+use crate::extends_result::ExtendsResult;
+
+#[derive(Debug, Default)]
+pub struct Consed {
+    /// Interned nodes by their [content hash](Ast::content_hash), with those colliding.
+    nodes: HashMap<u64, Vec<Rc<Ast>>>,
+    /// Results of [`Consed::is_subtype`] by the addresses of the interned nodes compared, which
+    /// the table keeps alive.
+    subtypes: HashMap<(*const Ast, *const Ast), ExtendsResult>,
+}
+
+impl Consed {
+    /// The shared node equal to `node`, `node` itself the first time it is seen.
+    pub fn intern(&mut self, node: impl Into<Rc<Ast>>) -> Rc<Ast> {
+        let node = node.into();
+
+        if node.has_unique_symbol() {
+            return node;
+        }
+
+        let bucket = self.nodes.entry(node.content_hash()).or_default();
+
+        match bucket.iter().find(|shared| **shared == node) {
+            Some(shared) => shared.clone(),
+            None => {
+                bucket.push(node.clone());
+                node
+            }
+        }
+    }
+
+    /// Number of distinct nodes interned.
+    pub fn len(&self) -> usize {
+        self.nodes.values().map(Vec::len).sum()
+    }
+
+    /// [`Ast::is_subtype`] of `lhs` and `rhs`, computed once for each pair of interned nodes. A
+    /// node is a subtype of itself, which is told by its address.
+    pub fn is_subtype(
+        &mut self,
+        lhs: impl Into<Rc<Ast>>,
+        rhs: impl Into<Rc<Ast>>,
+    ) -> ExtendsResult {
+        let (lhs, rhs) = (self.intern(lhs), self.intern(rhs));
+
+        // `never` distributes over the conditional testing it, even against itself.
+        if Rc::ptr_eq(&lhs, &rhs) && !matches!(*lhs, Ast::NeverKeyword(_)) {
+            return ExtendsResult::True;
+        }
+
+        // Nodes that aren't interned have addresses of their own, which are freed on return.
+        if lhs.has_unique_symbol() || rhs.has_unique_symbol() {
+            return lhs.is_subtype(&rhs);
+        }
+
+        *self
+            .subtypes
+            .entry((Rc::as_ptr(&lhs), Rc::as_ptr(&rhs)))
+            .or_insert_with(|| lhs.is_subtype(&rhs))
+    }
+
+    /// `members` without the duplicates of the earlier ones, in order.
+    pub fn distinct(&mut self, members: Vec<Ast>) -> Vec<Ast> {
+        // Held until every member is interned, so that no address is reused for another.
+        let shared: Vec<_> = members
+            .into_iter()
+            .map(|member| self.intern(member))
+            .collect();
+        let mut seen = HashSet::new();
+
+        shared
+            .iter()
+            .filter(|member| seen.insert(Rc::as_ptr(member)))
+            .map(|member| (**member).clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ast::build::{number, string, string_literal},
+        dsl::Pretty as _,
+        parser::Rule,
+        test_support::parse,
+    };
+    use pretty_assertions::assert_eq;
+    use std::time::Instant;
+
+    fn object() -> Ast {
+        parse!(Rule::expr, "{kind: :user, id: number, tags: string[]}")
+    }
+
+    #[test]
+    fn equal_nodes_are_shared() {
+        let mut consed = Consed::default();
+        let a = consed.intern(object());
+        let b = consed.intern(parse!(
+            Rule::expr,
+            "{ kind: :user, id: number, tags: string[] }"
+        ));
+        let c = consed.intern(parse!(Rule::expr, "{kind: :admin}"));
+
+        assert!(Rc::ptr_eq(&a, &b));
+        assert!(!Rc::ptr_eq(&a, &c));
+        assert_eq!(consed.len(), 2);
+    }
+
+    #[test]
+    fn unique_symbols_are_not_shared() {
+        let mut consed = Consed::default();
+        let a = consed.intern(parse!(Rule::expr, "[unique symbol]"));
+        let b = consed.intern(parse!(Rule::expr, "[unique symbol]"));
+
+        assert!(!Rc::ptr_eq(&a, &b));
+        assert_eq!(consed.len(), 0);
+    }
+
+    #[test]
+    fn rewriting_one_occurrence_leaves_the_others() {
+        let mut consed = Consed::default();
+        let mut a = consed.intern(object());
+        let b = consed.intern(object());
+        assert!(Rc::ptr_eq(&a, &b));
+
+        *Rc::make_mut(&mut a) = string();
+
+        assert_eq!(*a, string());
+        assert_eq!(*b, object());
+        assert!(Rc::ptr_eq(&consed.intern(object()), &b));
+
+        // A rewrite through a pass builds new nodes.
+        let union = union_of([(*b).clone(), (*b).clone()]);
+        let (rewritten, _) = union.prewalk((), &|node, ctx| match node {
+            Ast::TypeLiteral(_) => (number(), ctx),
+            node => (node, ctx),
+        });
+
+        assert_eq!(rewritten.render_pretty_dsl(80), "number | number");
+        assert_eq!(*b, object());
+    }
+
+    fn union_of(types: impl IntoIterator<Item = Ast>) -> Ast {
+        Ast::UnionType(UnionType {
+            types: types.into_iter().collect(),
+            span: Span::default(),
+        })
+    }
+
+    #[test]
+    fn subtyping_is_memoized() {
+        let mut consed = Consed::default();
+
+        assert_eq!(
+            consed.is_subtype(string_literal("a"), string()),
+            ExtendsResult::True
+        );
+        assert_eq!(
+            consed.is_subtype(string(), string_literal("a")),
+            ExtendsResult::False
+        );
+        assert_eq!(
+            consed.is_subtype(string_literal("a"), string()),
+            ExtendsResult::True
+        );
+        assert_eq!(consed.subtypes.len(), 2);
+
+        // The same node is a subtype of itself without being compared.
+        assert_eq!(consed.is_subtype(object(), object()), ExtendsResult::True);
+        assert_eq!(consed.subtypes.len(), 2);
+
+        let never = parse!(Rule::expr, "never");
+        assert_eq!(
+            consed.is_subtype(never.clone(), never),
+            ExtendsResult::Never
+        );
+
+        let unique = parse!(Rule::expr, "unique symbol");
+        assert_eq!(
+            consed.is_subtype(unique.clone(), unique),
+            ExtendsResult::True
+        );
+        assert_eq!(consed.subtypes.len(), 3);
+    }
+
+    #[test]
+    fn distinct_members() {
+        let admin = parse!(Rule::expr, "{kind: :admin, id: number}");
+        let members = (0..30)
+            .map(|i| match i % 3 {
+                2 => admin.clone(),
+                _ => object(),
+            })
+            .chain([string(), string_literal("a"), string()])
+            .collect();
+
+        assert_eq!(
+            Consed::default().distinct(members),
+            vec![object(), admin, string(), string_literal("a")]
+        );
+
+        let unique = parse!(Rule::expr, "unique symbol");
+        assert_eq!(
+            Consed::default().distinct(vec![unique.clone(), unique.clone()]),
+            vec![unique.clone(), unique]
+        );
+    }
+
+    /// The normalization before interning: each member compared with those kept, as deep as they
+    /// are equal.
+    fn compared(members: Vec<Ast>) -> Vec<Ast> {
+        let mut kept: Vec<Ast> = vec![];
+
+        for member in members {
+            if !kept.contains(&member) {
+                kept.push(member);
+            }
+        }
+
+        kept.iter()
+            .filter(|member| !kept.iter().any(|other| other.subsumes(member)))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `--release -- --ignored --nocapture`"]
+    fn normalize_duplicate_members() {
+        // 50 object types, each repeated 20 times.
+        let members: Vec<_> = (0..1000)
+            .map(|i| {
+                let source = format!("{{kind: :k{}, id: number, tags: string[]}}", i % 50);
+                parse!(Rule::expr, &source)
+            })
+            .collect();
+
+        let start = Instant::now();
+        let before = compared(members.clone());
+        let before_time = start.elapsed();
+
+        let start = Instant::now();
+        let after = normalize_union(members);
+        let after_time = start.elapsed();
+
+        assert_eq!(before, after);
+        println!("1000 members: {before_time:?} compared, {after_time:?} interned");
+    }
+}
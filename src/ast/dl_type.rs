@@ -0,0 +1,228 @@
+/// The types of the DSL that Rust types are serialized as, for emitting TypeScript that mirrors
+/// the data a Rust program sends.
+///
+/// The implementations follow the JSON representation of `serde_json`: numbers of any width are
+/// `number`, `Option<T>` is `T | null`, maps keyed by strings are index signatures, and
+/// tuples are tuples.
+///
+/// Structs and enums implement the trait with the [`build`] functions, mirroring their serde
+/// attributes. A struct is an object of its fields, and an enum tagged with `#[serde(tag = "kind")]`
+/// the union of an object per variant, its tag first:
+///
+/// ```ignore
+/// #[derive(Serialize)]
+/// #[serde(tag = "kind")]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Square { size: f64 },
+/// }
+///
+/// impl ToDlType for Shape {
+///     fn dl_type() -> Ast {
+///         union([
+///             object([("kind", string_literal("Circle")), ("radius", f64::dl_type())]),
+///             object([("kind", string_literal("Square")), ("size", f64::dl_type())]),
+///         ])
+///     }
+/// }
+/// ```
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use super::{
+    build::{self, array, index_property, object, tuple, union},
+    Ast,
+};
+
+pub trait ToDlType {
+    fn dl_type() -> Ast;
+}
+
+macro_rules! primitive {
+    ($ty:expr => $($rust:ty),+) => {
+        $(
+            impl ToDlType for $rust {
+                fn dl_type() -> Ast {
+                    $ty
+                }
+            }
+        )+
+    };
+}
+
+primitive!(build::boolean() => bool);
+primitive!(build::string() => String, str, char);
+primitive!(build::number() => u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+primitive!(build::null() => ());
+
+impl<T: ToDlType> ToDlType for Option<T> {
+    fn dl_type() -> Ast {
+        union([T::dl_type(), build::null()])
+    }
+}
+
+impl<T: ToDlType> ToDlType for Vec<T> {
+    fn dl_type() -> Ast {
+        array(T::dl_type())
+    }
+}
+
+impl<T: ToDlType + ?Sized> ToDlType for Box<T> {
+    fn dl_type() -> Ast {
+        T::dl_type()
+    }
+}
+
+impl<T: ToDlType + ?Sized> ToDlType for Rc<T> {
+    fn dl_type() -> Ast {
+        T::dl_type()
+    }
+}
+
+impl<T: ToDlType + ?Sized> ToDlType for &T {
+    fn dl_type() -> Ast {
+        T::dl_type()
+    }
+}
+
+impl<T: ToDlType, S> ToDlType for HashMap<String, T, S> {
+    fn dl_type() -> Ast {
+        object([index_property("key", build::string(), T::dl_type())])
+    }
+}
+
+impl<T: ToDlType> ToDlType for BTreeMap<String, T> {
+    fn dl_type() -> Ast {
+        object([index_property("key", build::string(), T::dl_type())])
+    }
+}
+
+macro_rules! tuple {
+    ($($name:ident),+) => {
+        impl<$($name: ToDlType),+> ToDlType for ($($name,)+) {
+            fn dl_type() -> Ast {
+                tuple([$($name::dl_type()),+])
+            }
+        }
+    };
+}
+
+tuple!(A);
+tuple!(A, B);
+tuple!(A, B, C);
+tuple!(A, B, C, D);
+tuple!(A, B, C, D, E);
+tuple!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ast::build::{alias, program, property, string_literal},
+        typescript::Pretty as _,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    #[allow(unused)]
+    struct User {
+        id: u64,
+        name: String,
+        email: Option<String>,
+        roles: Vec<Role>,
+        settings: HashMap<String, (bool, i32)>,
+    }
+
+    impl ToDlType for User {
+        fn dl_type() -> Ast {
+            object([
+                ("id", u64::dl_type()),
+                ("name", String::dl_type()),
+                ("email", Option::<String>::dl_type()),
+                ("roles", Vec::<Role>::dl_type()),
+                ("settings", HashMap::<String, (bool, i32)>::dl_type()),
+            ])
+        }
+    }
+
+    /// `#[serde(tag = "kind")]`
+    #[allow(unused)]
+    enum Role {
+        Admin,
+        Member { team: String, since: Option<u32> },
+    }
+
+    impl ToDlType for Role {
+        fn dl_type() -> Ast {
+            union([
+                object([("kind", string_literal("Admin"))]),
+                object([
+                    ("kind", string_literal("Member")),
+                    ("team", String::dl_type()),
+                    ("since", Option::<u32>::dl_type()),
+                ]),
+            ])
+        }
+    }
+
+    fn emitted(declarations: impl IntoIterator<Item = (&'static str, Ast)>) -> String {
+        program(
+            declarations
+                .into_iter()
+                .map(|(name, body)| alias(name).export().body(body)),
+        )
+        .simplify()
+        .render_pretty_ts(80)
+        .trim_end()
+        .to_string()
+    }
+
+    #[test]
+    fn nested_struct() {
+        assert_eq!(
+            emitted([("User", User::dl_type())]),
+            dedent!(
+                "
+                export type User =
+                    {
+                        id: number,
+                        name: string,
+                        email: string | null,
+                        roles: ({kind: 'Admin'}
+                            | {kind: 'Member', team: string, since: number | null})[],
+                        settings: {[key in string]: [boolean, number]},
+                    };
+                "
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn tagged_enum() {
+        assert_eq!(
+            emitted([("Role", Role::dl_type())]),
+            dedent!(
+                "
+                export type Role =
+                    {kind: 'Admin'} | {kind: 'Member', team: string, since: number | null};
+                "
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn optional_properties() {
+        // `#[serde(skip_serializing_if = "Option::is_none")]` leaves the key out instead.
+        assert_eq!(
+            emitted([(
+                "A",
+                object([property("a", Option::<Box<str>>::dl_type()).optional()])
+            )]),
+            "export type A = {a?: string | null};"
+        );
+    }
+}
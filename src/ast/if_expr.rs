@@ -1,12 +1,45 @@
-use newtype::compose;
+use crate::compose;
 
 use super::*;
+use crate::{
+    ast::visit::{walk_cond_expr, walk_if_expr, Visit},
+    diagnostic::codes,
+};
 
 #[ast_node]
 pub struct IfExpr {
     pub condition: Rc<Ast>,
     pub then_branch: Rc<Ast>,
     pub else_branch: Option<Rc<Ast>>,
+    /// Written `else pass`, the else branch is the type the condition checks, see
+    /// [`MissingElse::Scrutinee`].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pass: bool,
+}
+
+/// What an `if` without an `else` branch, or a `cond` without an `else` arm, stands for when no
+/// condition holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingElse {
+    #[default]
+    Never,
+    Unknown,
+    /// The type the condition checks, `T` in `if T <: U then ... end`, leaving it unchanged. Only
+    /// a condition of that form has one, and each arm of a `cond` must check the same type.
+    Scrutinee,
+}
+
+impl MissingElse {
+    /// The else branch of an expression spanning `span` whose conditions check `scrutinee`.
+    /// Without one, [`check_fallbacks`] reports the expression and it falls back to `never`.
+    pub(crate) fn fallback(self, scrutinee: Option<&Ast>, span: Span) -> Ast {
+        match (self, scrutinee) {
+            (MissingElse::Unknown, _) => Ast::UnknownKeyword(span),
+            (MissingElse::Scrutinee, Some(scrutinee)) => scrutinee.clone(),
+            _ => Ast::NeverKeyword(span),
+        }
+    }
 }
 
 impl IfExpr {
@@ -19,20 +52,156 @@ impl IfExpr {
             condition: f(&self.condition).into(),
             then_branch: f(&self.then_branch).into(),
             else_branch: self.else_branch.as_ref().map(compose!(f, Into::into)),
+            pass: self.pass,
         }
     }
 
-    pub fn simplify(&self) -> Ast {
-        let else_branch = self
-            .else_branch
-            .as_ref()
-            .map_or_else(|| Ast::NeverKeyword(self.span), |v| (**v).clone());
+    /// The conditional type of the expression, a missing else branch standing for what
+    /// `missing_else` says, or for the scrutinee with `else pass`.
+    pub fn simplify(&self, missing_else: MissingElse) -> Ast {
+        let missing_else = if self.pass {
+            MissingElse::Scrutinee
+        } else {
+            missing_else
+        };
+
+        let else_branch = match &self.else_branch {
+            Some(else_branch) => (**else_branch).clone(),
+            None => missing_else.fallback(scrutinee(&self.condition), self.span),
+        };
 
         expand_to_extends(&self.condition, &self.then_branch, &else_branch)
     }
 }
 
-/// Expands an if expression into a series of nested ternary expressions
+/// `T` of a condition `T <: U`.
+pub(crate) fn scrutinee(condition: &Ast) -> Option<&Ast> {
+    match condition {
+        Ast::ExtendsInfixOp(ExtendsInfixOp {
+            lhs,
+            op: InfixOp::Extends,
+            ..
+        }) => Some(lhs),
+        _ => None,
+    }
+}
+
+/// The errors of the `if` and `cond` expressions of `program` falling back to their scrutinee
+/// without having one, with `else pass` or when `missing_else` is
+/// [`MissingElse::Scrutinee`].
+pub fn check_fallbacks(program: &Ast, missing_else: MissingElse) -> Vec<Diagnostic> {
+    struct Fallbacks {
+        missing_else: MissingElse,
+        diagnostics: Vec<Diagnostic>,
+    }
+
+    impl<'a> Visit<'a> for Fallbacks {
+        fn visit_if_expr(&mut self, expr: &'a IfExpr) {
+            if expr.pass && scrutinee(&expr.condition).is_none() {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        "`else pass` needs a condition of the form `T <: U`",
+                        expr.condition.as_span(),
+                    )
+                    .with_note("`pass` stands for `T`, the type the condition checks")
+                    .with_code(codes::INVALID_ELSE_FALLBACK),
+                );
+            } else if expr.else_branch.is_none()
+                && !expr.pass
+                && self.missing_else == MissingElse::Scrutinee
+                && scrutinee(&expr.condition).is_none()
+            {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        "`if` without `else` has no scrutinee to fall back to",
+                        expr.condition.as_span(),
+                    )
+                    .with_note(
+                        "`missing_else` is `scrutinee`, which needs a condition of the form \
+                         `T <: U`",
+                    )
+                    .with_note("add an `else` branch")
+                    .with_code(codes::INVALID_ELSE_FALLBACK),
+                );
+            }
+
+            walk_if_expr(self, expr);
+        }
+
+        fn visit_cond_expr(&mut self, cond: &'a CondExpr) {
+            if cond.else_arm.is_none()
+                && self.missing_else == MissingElse::Scrutinee
+                && cond.scrutinee().is_none()
+            {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        "`cond` without `else` has no scrutinee to fall back to",
+                        cond.span,
+                    )
+                    .with_note(
+                        "`missing_else` is `scrutinee`, which needs every arm to check the same \
+                         type, `T <: U`",
+                    )
+                    .with_note("add an `else` arm")
+                    .with_code(codes::INVALID_ELSE_FALLBACK),
+                );
+            }
+
+            walk_cond_expr(self, cond);
+        }
+    }
+
+    let mut fallbacks = Fallbacks {
+        missing_else,
+        diagnostics: vec![],
+    };
+    fallbacks.visit_ast(program);
+    fallbacks.diagnostics
+}
+
+/// Whether `condition`, its macros expanded, is a condition, reporting the part of it that isn't
+/// otherwise, such as `T` in `if T then ... end`.
+pub(crate) fn check_condition(condition: &Ast, helpers: &runtime::Helpers) -> bool {
+    let Some(node) = not_a_condition(condition) else {
+        return true;
+    };
+
+    let mut diagnostic = Diagnostic::error(format!("`{node}` is not a condition"), node.as_span())
+        .with_note("a condition is `A <: B`, or conditions joined by `and`, `or` and `not`");
+
+    if matches!(node, Ast::Ident(Ident { name, .. }) if name == "_") {
+        diagnostic = diagnostic.with_note("the arm taken when no condition holds is `else -> ...`");
+    }
+
+    helpers.report(diagnostic.with_code(codes::EXPECTED_CONDITION));
+
+    false
+}
+
+/// The first part of `condition` [`expand_to_extends`] can't expand.
+fn not_a_condition(condition: &Ast) -> Option<&Ast> {
+    match condition {
+        Ast::ExtendsInfixOp(ExtendsInfixOp {
+            lhs,
+            op: InfixOp::And | InfixOp::Or,
+            rhs,
+            ..
+        }) => not_a_condition(lhs).or_else(|| not_a_condition(rhs)),
+        Ast::ExtendsInfixOp(_) => None,
+        Ast::ExtendsPrefixOp(ExtendsPrefixOp {
+            op: PrefixOp::Not,
+            value,
+            ..
+        }) if value.is_compatible_with_not_prefix_op() => not_a_condition(value),
+        // The conditions of `all!` and `any!` when there are none, and a macro that failed to
+        // expand, whose error is reported.
+        Ast::TrueKeyword(_) | Ast::FalseKeyword(_) | Ast::NeverKeyword(_) => None,
+        node => Some(node),
+    }
+}
+
+/// Expands an if expression into a series of nested ternary expressions. The condition must have
+/// passed [`check_condition`].
 pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) -> Ast {
     // Recursive operations
     let out: Option<Ast> = match condition {
@@ -43,10 +212,7 @@ pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) ->
                 PrefixOp::Not if value.is_compatible_with_not_prefix_op() => {
                     Some(expand_to_extends(value, else_arm, then))
                 }
-                PrefixOp::Infer => todo!(),
-                _ => {
-                    unreachable!("Expected `not` or `infer` prefix operator, found {condition:#?}")
-                }
+                _ => unreachable!("Expected a `not` of a condition, found {condition:#?}"),
             }
         }
         Ast::ExtendsInfixOp(ExtendsInfixOp { lhs, op, rhs, .. }) => match op {
@@ -60,7 +226,12 @@ pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) ->
             }
             _ => None,
         },
-        _ => panic!("Expected extends operator, found {condition:#?}"),
+        // The conditions of `all!` and `any!` when there are none.
+        Ast::TrueKeyword(_) => Some(then.clone()),
+        Ast::FalseKeyword(_) => Some(else_arm.clone()),
+        // A macro that failed to expand, its error is reported.
+        Ast::NeverKeyword(span) => Some(Ast::NeverKeyword(*span)),
+        _ => unreachable!("Expected extends operator, found {condition:#?}"),
     };
 
     if let Some(v) = out {
@@ -70,7 +241,13 @@ pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) ->
     // Terminal nodes
     match condition {
         // Binary operators
-        Ast::ExtendsInfixOp(ExtendsInfixOp { lhs, op, rhs, .. }) => {
+        Ast::ExtendsInfixOp(ExtendsInfixOp {
+            lhs,
+            op,
+            rhs,
+            non_distributive,
+            ..
+        }) => {
             let span = lhs.as_span().merge(&rhs.as_span());
 
             // TODO report a syntax error here
@@ -92,6 +269,7 @@ pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) ->
                     rhs.clone(),
                     Rc::new(then.clone()),
                     Rc::new(else_arm.clone()),
+                    *non_distributive,
                 )),
                 InfixOp::NotExtends => Ast::from(ExtendsExpr::new(
                     span,
@@ -99,6 +277,7 @@ pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) ->
                     rhs.clone(),
                     Rc::new(else_arm.clone()),
                     Rc::new(then.clone()),
+                    *non_distributive,
                 )),
                 InfixOp::Equals => todo!("equals"),
                 InfixOp::NotEquals => todo!("not equals"),
@@ -107,6 +286,154 @@ pub(crate) fn expand_to_extends(condition: &Ast, then: &Ast, else_arm: &Ast) ->
                 _ => unreachable!(),
             }
         }
-        _ => panic!("Expected extends operator, found {condition:#?}"),
+        _ => unreachable!("Expected extends operator, found {condition:#?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn typescript(source: &str, missing_else: MissingElse) -> String {
+        let options = CompileOptions {
+            missing_else,
+            ..Default::default()
+        };
+
+        compile(source, &options).unwrap().typescript
+    }
+
+    fn errors(source: &str, missing_else: MissingElse) -> Vec<(String, String, Vec<String>)> {
+        let options = CompileOptions {
+            missing_else,
+            ..Default::default()
+        };
+
+        compile(source, &options)
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message,
+                    source[diagnostic.span.start..diagnostic.span.end].to_string(),
+                    diagnostic.notes,
+                )
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(MissingElse::Never, "type A<T> = T extends string ? 1 : never;\n\n")]
+    #[case(
+        MissingElse::Unknown,
+        "type A<T> = T extends string ? 1 : unknown;\n\n"
+    )]
+    #[case(MissingElse::Scrutinee, "type A<T> = T extends string ? 1 : T;\n\n")]
+    fn if_without_else(#[case] missing_else: MissingElse, #[case] expected: &str) {
+        assert_eq!(
+            typescript("type A(T) as if T <: string then 1 end", missing_else),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(
+        MissingElse::Never,
+        "type A<T> = T extends string ? 1 : T extends number ? 2 : never;\n\n"
+    )]
+    #[case(
+        MissingElse::Unknown,
+        "type A<T> = T extends string ? 1 : T extends number ? 2 : unknown;\n\n"
+    )]
+    #[case(
+        MissingElse::Scrutinee,
+        "type A<T> = T extends string ? 1 : T extends number ? 2 : T;\n\n"
+    )]
+    fn cond_without_else(#[case] missing_else: MissingElse, #[case] expected: &str) {
+        assert_eq!(
+            typescript(
+                "type A(T) as cond do T <: string -> 1, T <: number -> 2 end",
+                missing_else
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn else_branches_are_kept() {
+        assert_eq!(
+            typescript(
+                "type A(T) as if T <: string then 1 else 2 end",
+                MissingElse::Unknown
+            ),
+            "type A<T> = T extends string ? 1 : 2;\n\n"
+        );
+    }
+
+    #[test]
+    fn else_pass() {
+        assert_eq!(
+            typescript(
+                "type A(T) as if T <: string then Uppercase(T) else pass end",
+                MissingElse::Never
+            ),
+            "type A<T> = T extends string ? Uppercase<T> : T;\n\n"
+        );
+    }
+
+    #[test]
+    fn invalid_scrutinee() {
+        assert_eq!(
+            errors(
+                "type A(T) as if T <: string or T <: number then 1 else pass end",
+                MissingElse::Never
+            ),
+            vec![(
+                "`else pass` needs a condition of the form `T <: U`".to_string(),
+                "T <: string or T <: number".to_string(),
+                vec!["`pass` stands for `T`, the type the condition checks".to_string()]
+            )]
+        );
+        assert_eq!(
+            errors(
+                "type A(T) as if T <: string and T <: :a then 1 end",
+                MissingElse::Scrutinee
+            ),
+            vec![(
+                "`if` without `else` has no scrutinee to fall back to".to_string(),
+                "T <: string and T <: :a".to_string(),
+                vec![
+                    "`missing_else` is `scrutinee`, which needs a condition of the form `T <: U`"
+                        .to_string(),
+                    "add an `else` branch".to_string()
+                ]
+            )]
+        );
+        assert_eq!(
+            errors(
+                "type A(T, U) as cond do T <: string -> 1, U <: number -> 2 end",
+                MissingElse::Scrutinee
+            ),
+            vec![(
+                "`cond` without `else` has no scrutinee to fall back to".to_string(),
+                "cond do T <: string -> 1, U <: number -> 2 end".to_string(),
+                vec![
+                    "`missing_else` is `scrutinee`, which needs every arm to check the same type, \
+                     `T <: U`"
+                        .to_string(),
+                    "add an `else` arm".to_string()
+                ]
+            )]
+        );
+
+        // Only falling back to the scrutinee needs one.
+        assert!(compile(
+            "type A(T) as if T <: string and T <: :a then 1 end",
+            &CompileOptions::default()
+        )
+        .is_ok());
     }
 }
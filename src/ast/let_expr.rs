@@ -1,16 +1,33 @@
 use pretty::RcDoc as D;
 use serde::Serialize;
+use serde_lexpr::Value;
 
-use super::*;
+use super::{
+    origin::{Origin, OriginId},
+    visit::Visit,
+    *,
+};
 
 use std::collections::HashMap;
 
 #[ast_node]
 pub struct LetExpr {
-    pub bindings: Bindings,
+    /// In the order they are written.
+    pub bindings: Vec<Binding>,
     pub body: Rc<Ast>,
 }
 
+/// `name = value`, a binding of a `let`. Unlike other nodes, its span is serialized, the names
+/// being otherwise only told apart by their order, but for the canonical form of nodes, see
+/// [`structural`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Binding {
+    pub name: Ident,
+    pub value: Ast,
+    #[serde(skip_serializing_if = "structural::hides")]
+    pub span: Span,
+}
+
 impl LetExpr {
     pub fn map<F>(&self, f: F) -> Self
     where
@@ -23,21 +40,152 @@ impl LetExpr {
     /// Replace all identifiers in the body of the let expression with their corresponding
     /// values
     pub fn simplify(&self) -> Ast {
-        let mut bindings = self.bindings.clone();
         // simplifiy all bindings first
-        for (ident, value) in &self.bindings {
-            let new_value = value.simplify();
-            bindings.insert(ident.clone(), new_value);
+        let bindings: Bindings = self
+            .bindings
+            .iter()
+            .map(|binding| {
+                let value = binding.value.simplify();
+
+                let origin = OriginId::new(
+                    Origin::LetSubstitution {
+                        name: binding.name.name.clone(),
+                    },
+                    value.as_span().origin(),
+                );
+
+                (binding.name.clone(), value.with_origin(origin))
+            })
+            .collect();
+
+        substitute(&self.body, &bindings)
+    }
+}
+
+/// Replaces the names of `bindings` in `node`. The body has been simplified already, its
+/// conditionals are `extends` expressions, and the types inferred by the pattern of one, `?T`,
+/// shadow the bindings of the same name in its `then` branch.
+pub(crate) fn substitute(node: &Ast, bindings: &Bindings) -> Ast {
+    let (tree, _) = node.traverse_ctl(
+        (),
+        &|ast, ctx| match ast {
+            Ast::Ident(ref id) => (
+                bindings.get(id).unwrap_or(&ast).clone(),
+                ctx,
+                Walk::Continue,
+            ),
+            Ast::ExtendsExpr(expr) => {
+                let inferred = inferred(&expr.rhs);
+                let mut shadowed = bindings.clone();
+                shadowed.retain(|name, _| !inferred.contains(&name.name));
+
+                let expr = ExtendsExpr {
+                    lhs: substitute(&expr.lhs, bindings).into(),
+                    rhs: substitute(&expr.rhs, bindings).into(),
+                    then_branch: substitute(&expr.then_branch, &shadowed).into(),
+                    else_branch: substitute(&expr.else_branch, bindings).into(),
+                    ..expr
+                };
+
+                (Ast::ExtendsExpr(expr), ctx, Walk::Skip)
+            }
+            _ => (ast, ctx, Walk::Continue),
+        },
+        &|ast, ctx| (ast, ctx),
+    );
+
+    tree
+}
+
+/// The names of the types inferred in `pattern`.
+fn inferred(pattern: &Ast) -> Vec<String> {
+    #[derive(Default)]
+    struct Inferred(Vec<String>);
+
+    impl<'a> Visit<'a> for Inferred {
+        fn visit_infer(&mut self, binding: &'a Ast) {
+            if let Ast::Ident(ident) = binding {
+                self.0.push(ident.name.clone());
+            }
         }
+    }
+
+    let mut inferred = Inferred::default();
+    inferred.visit_ast(pattern);
+    inferred.0
+}
+
+/// Rewrites the `let` forms of `value`, a node serialized to an s-expression, as
+/// `(let ((a value) (b value)) body)`, the bindings in the order they are written and without
+/// their spans.
+pub(crate) fn let_forms(value: Value) -> Value {
+    match value {
+        Value::Cons(cons) => {
+            let (car, cdr) = cons.into_pair();
 
-        let (tree, _) = self.body.prewalk(bindings, &|ast, bindings| match ast {
-            Ast::Ident(ref id) => {
-                let new_value = bindings.get(&id.name).unwrap_or(&ast).clone();
-                (new_value, bindings)
+            match (car.as_symbol(), fields(&cdr)) {
+                (Some("let"), Some((bindings, body))) => Value::list([
+                    car,
+                    Value::list(bindings.iter().map(|binding| {
+                        Value::list([binding["name"].clone(), let_forms(binding["value"].clone())])
+                    })),
+                    let_forms(body),
+                ]),
+                _ => Value::cons(let_forms(car), let_forms(cdr)),
             }
-            _ => (ast, bindings),
-        });
+        }
+        Value::Vector(items) => Value::vector(items.into_vec().into_iter().map(let_forms)),
+        value => value,
+    }
+}
+
+/// The bindings and the body of a serialized `let`, `((bindings ...) (body . value))`.
+fn fields(value: &Value) -> Option<(Vec<Value>, Value)> {
+    let bindings = value.get("bindings")?.to_vec()?;
+    let body = value.get("body")?.clone();
+
+    Some((bindings, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ast, parse, sexpr};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn bindings_in_declaration_order() {
+        assert_eq!(
+            sexpr!("let b = 1, a = b in a").unwrap().to_string(),
+            r#"(let (("b" (number . "1")) ("a" (ident . "b"))) (ident . "a"))"#
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            ast!("let b = 1, a = b in a").to_json().unwrap(),
+            json!({
+                "let": {
+                    "bindings": [
+                        {"name": "b", "value": {"number": "1"}, "span": {"start": 4, "end": 9}},
+                        {"name": "a", "value": {"ident": "b"}, "span": {"start": 11, "end": 16}},
+                    ],
+                    "body": {"ident": "a"},
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn versioned_program() {
+        let program = parse!("type A as 1").to_json().unwrap();
 
-        tree
+        assert_eq!(program["schema_version"], json!(AST_VERSION));
+        assert_eq!(
+            program["program"]["statements"].as_array().unwrap().len(),
+            1
+        );
     }
 }
@@ -46,6 +46,12 @@ impl MatchExpr {
                 body,
             } = arm;
 
+            // `[A, B] extends [unknown, unknown]` always holds: the arm is the else branch, and
+            // the arms after it are never reached.
+            if matches_anything(value, pattern) {
+                return body.clone();
+            }
+
             let span = *span;
 
             Ast::from(ExtendsExpr {
@@ -54,13 +60,216 @@ impl MatchExpr {
                 rhs: pattern.clone().into(),
                 then_branch: body.clone().into(),
                 else_branch: acc.into(),
+                non_distributive: false,
             })
         })
     }
 }
 
+/// Whether `pattern` matches any value of `value`, a tuple of as many `unknown`, which a `_`
+/// matching one of the values of a `match` on several stands for.
+fn matches_anything(value: &Ast, pattern: &Ast) -> bool {
+    match (value, pattern) {
+        (Ast::Tuple(value), Ast::Tuple(pattern)) => {
+            value.items.len() == pattern.items.len()
+                && pattern
+                    .items
+                    .iter()
+                    .all(|item| matches!(item, Ast::UnknownKeyword(_)))
+        }
+        _ => false,
+    }
+}
+
 #[ast_node]
 pub struct Arm {
     pub pattern: Ast,
     pub body: Ast,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        compile::{compile, CompileOptions},
+        test_support::parse,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn typescript(source: &str) -> String {
+        compile(source, &CompileOptions::default())
+            .unwrap()
+            .typescript
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn diagnostic_in_an_arm() {
+        let diagnostics = compile(
+            "type A(T) as match T do string -> 1, number -> 2, \
+             boolean -> all!([T <: true, T <: false]), else -> 0 end",
+            &CompileOptions::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            diagnostics[0].notes[2..],
+            [
+                "expanded from macro `all!`",
+                "in the 3rd arm of this `match`"
+            ]
+        );
+    }
+
+    #[test]
+    fn verbose_sexpr() {
+        assert_eq!(
+            parse!("type A(T) as match T do 1 -> :a, else -> :b end")
+                .simplify()
+                .to_sexp_verbose()
+                .unwrap()
+                .to_string(),
+            concat!(
+                "(program (statements (statement type (export . #f) (name . \"A\") ",
+                "(params ((name . \"T\") (constraint) (default) (rest . #f))) ",
+                "(body extends-expr (lhs ident . \"T\") (rhs number . \"1\") ",
+                "(then-branch type-string . \"a\") (else-branch type-string . \"b\") ",
+                "(origin \"in the 1st arm of this `match`\")))))"
+            ),
+        );
+    }
+
+    #[test]
+    fn verbose_sexpr_after_a_compilation() {
+        let simplified = parse!("type A(T) as match T do 1 -> :a, else -> :b end").simplify();
+
+        compile(
+            "type B(T) as match T do 1 -> :a, 2 -> :b, else -> :c end",
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        assert!(simplified
+            .to_sexp_verbose()
+            .unwrap()
+            .to_string()
+            .contains("(origin \"in the 1st arm of this `match`\")"));
+    }
+
+    #[test]
+    fn if_let() {
+        assert_eq!(
+            typescript("type Head(L) as if let [?H, ?T] = L then H else never end"),
+            "type Head<L> = L extends [infer H, infer T] ? H : never;"
+        );
+    }
+
+    #[test]
+    fn if_let_chained() {
+        assert_eq!(
+            typescript(
+                "type Pair(A, B) as if let [?X] = A and let {value: ?Y} = B then [X, Y] \
+                 else :none end"
+            ),
+            "type Pair<A, B> = A extends [infer X] ? B extends {value: infer Y} ? [X, Y] : \
+             'none' : 'none';"
+        );
+    }
+
+    #[test]
+    fn if_let_is_a_match() {
+        assert_eq!(
+            parse!("type A(T) as if let [?H] = T and let 1 = H then :a else :b end"),
+            parse!(
+                "type A(T) as match T do [?H] -> (match H do 1 -> :a, else -> :b end), \
+                 else -> :b end"
+            )
+        );
+        assert_eq!(
+            parse!("type A(T) as if let [?H] = T then H end"),
+            parse!("type A(T) as match T do [?H] -> H end")
+        );
+    }
+
+    #[test]
+    fn several_values() {
+        assert_eq!(
+            typescript(
+                "type F(A, B) as match A, B do (string, number) -> 1, (number, ?N) -> N, \
+                 else -> 0 end"
+            ),
+            "type F<A, B> = [A, B] extends [string, number] ? 1 : [A, B] extends [number, infer N] \
+             ? N : 0;"
+        );
+    }
+
+    #[test]
+    fn application_patterns() {
+        assert_eq!(
+            typescript("type Unwrap(T) as match T do Promise(?U) -> U, else -> T end"),
+            "type Unwrap<T> = T extends Promise<infer U> ? U : T;"
+        );
+        assert_eq!(
+            typescript(
+                "type Flat(T) as match Array(T) do Array(Promise(Array(?U))) -> U, \
+                 else -> never end"
+            ),
+            "type Flat<T> = Array<T> extends Array<Promise<Array<infer U>>> ? U : never;"
+        );
+    }
+
+    #[test]
+    fn inferred_types_shadow_let_bindings() {
+        assert_eq!(
+            typescript(
+                "type Unwrap(T) as let U = string in match T do Promise(?U) -> U, \
+                 else -> U end"
+            ),
+            "type Unwrap<T> = T extends Promise<infer U> ? U : string;"
+        );
+        assert_eq!(
+            typescript("type Unwrap(T) as let U = string in if T <: Promise(?U) then U else U end"),
+            "type Unwrap<T> = T extends Promise<infer U> ? U : string;"
+        );
+    }
+
+    #[test]
+    fn wildcards_in_brackets() {
+        assert_eq!(
+            typescript("type F(A, B) as match A, B do [string, number] -> 1, [_, _] -> 2 end"),
+            "type F<A, B> = [A, B] extends [string, number] ? 1 : 2;"
+        );
+        assert_eq!(
+            typescript("type F(A, B) as match A, B do (string, _) -> 1, _ -> 2 end"),
+            "type F<A, B> = [A, B] extends [string, unknown] ? 1 : 2;"
+        );
+    }
+
+    #[test]
+    fn patterns_of_another_number_of_values() {
+        let source = "type A(T) as match T, T do (1) -> 1, (1, 2, 3) -> 2, (1, 2) -> 3 end";
+        let diagnostics = compile(source, &CompileOptions::default()).unwrap_err();
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    &source[diagnostic.span.start..diagnostic.span.end]
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("pattern of 1 value in a `match` on 2", "1"),
+                ("pattern of 3 values in a `match` on 2", "(1, 2, 3)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn wildcards() {
+        assert_eq!(
+            parse!("type F(A, B) as match A, B do (string, _) -> 1, (_, _) -> 2 end"),
+            parse!("type F(A, B) as match [A, B] do [string, unknown] -> 1, [unknown, unknown] -> 2 end")
+        );
+    }
+}
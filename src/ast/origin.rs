@@ -0,0 +1,197 @@
+/// Where the nodes the simplifier builds come from, told in the terms of the source: the `extends`
+/// expression of the 3rd arm of a `match` is "in the 3rd arm of this `match`", the tree a macro
+/// returns is "expanded from macro `fold!`".
+///
+/// The desugaring passes record an [`OriginId`] in the [`Span`] of the node they build, which
+/// keeps it as the tree is rewritten. Each origin knows the one it is nested in, the macro whose
+/// expansion called the macro, or the arm it is written in, so that the origins of a node read as
+/// a backtrace. Origins are interned per thread, like the [offset of spans](super::with_span_offset),
+/// so that a span stays `Copy`. They are never forgotten, as any node may still hold the id of
+/// one: the interner only grows with the origins that differ, not with every compilation of a
+/// long-lived process.
+use std::{cell::RefCell, collections::HashMap, fmt::Display};
+
+use serde::Serializer;
+
+use super::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Origin {
+    /// The arm of a `match`, counted from 0.
+    MatchArm { index: usize },
+    /// The arm of a `cond`, counted from 0.
+    CondArm { index: usize },
+    /// A value a `let` substituted for the name it binds.
+    LetSubstitution { name: String },
+    /// The tree a call of the macro `name`, `!` included, returned.
+    MacroExpansion { name: String },
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::MatchArm { index } => {
+                write!(f, "in the {} arm of this `match`", ordinal(index + 1))
+            }
+            Origin::CondArm { index } => {
+                write!(f, "in the {} arm of this `cond`", ordinal(index + 1))
+            }
+            Origin::LetSubstitution { name } => write!(f, "substituted for `{name}` by a `let`"),
+            Origin::MacroExpansion { name } => write!(f, "expanded from macro `{name}`"),
+        }
+    }
+}
+
+/// `1st`, `2nd`, `3rd`, `4th`, ..., `11th`, ..., `21st`.
+pub(crate) fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{n}{suffix}")
+}
+
+/// An [`Origin`] and the one it is nested in, interned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OriginId(u32);
+
+#[derive(Default)]
+struct Origins {
+    entries: Vec<(Origin, Option<OriginId>)>,
+    ids: HashMap<(Origin, Option<OriginId>), OriginId>,
+}
+
+thread_local! {
+    static ORIGINS: RefCell<Origins> = RefCell::new(Origins::default());
+    /// Whether spans serialize their origin, see [`super::Ast::to_sexp_verbose`].
+    static VERBOSE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+impl OriginId {
+    /// The id of `origin` nested in `parent`, the same for the same pair.
+    pub fn new(origin: Origin, parent: Option<OriginId>) -> Self {
+        ORIGINS.with_borrow_mut(|origins| {
+            let key = (origin, parent);
+
+            if let Some(id) = origins.ids.get(&key) {
+                return *id;
+            }
+
+            let id = OriginId(origins.entries.len() as u32);
+            origins.entries.push(key.clone());
+            origins.ids.insert(key, id);
+            id
+        })
+    }
+
+    pub fn origin(self) -> Origin {
+        ORIGINS.with_borrow(|origins| origins.entries[self.0 as usize].0.clone())
+    }
+
+    pub fn parent(self) -> Option<OriginId> {
+        ORIGINS.with_borrow(|origins| origins.entries[self.0 as usize].1)
+    }
+
+    /// Whether the origin is `other` or nested in it.
+    pub fn is_in(self, other: OriginId) -> bool {
+        std::iter::successors(Some(self), |id| id.parent()).any(|id| id == other)
+    }
+
+    /// The origin and the ones it is nested in, innermost first.
+    pub fn backtrace(self) -> Vec<Origin> {
+        std::iter::successors(Some(self), |id| id.parent())
+            .map(OriginId::origin)
+            .collect()
+    }
+}
+
+/// The innermost origin of the node at `span`, from the spans of the nodes it is in, outermost
+/// first, itself included. An arm only counts if its span contains `span`: the `extends`
+/// expression of the 1st arm of a `match` holds the following arms as its `else` branch.
+pub(crate) fn innermost(enclosing: &[Span], span: Span) -> Option<OriginId> {
+    enclosing.iter().rev().find_map(|node| {
+        let origin = node.origin()?;
+
+        let contains = node.start <= span.start && span.end <= node.end;
+
+        match origin.origin() {
+            Origin::MatchArm { .. } | Origin::CondArm { .. } if !contains => None,
+            _ => Some(origin),
+        }
+    })
+}
+
+/// Runs `f` with the origins of spans serialized, as the `origin` of the nodes that have one.
+pub(crate) fn verbose<T>(f: impl FnOnce() -> T) -> T {
+    let previous = VERBOSE.replace(true);
+    let result = f();
+    VERBOSE.set(previous);
+    result
+}
+
+/// Whether the span of a node is left out when it is serialized: always, unless it has an origin
+/// and [`verbose`] is on.
+pub(crate) fn hides(span: &Span) -> bool {
+    span.origin().is_none() || !VERBOSE.get()
+}
+
+/// Serializes the span of a node as the backtrace of its origin.
+pub(crate) fn serialize<S: Serializer>(span: &Span, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        span.origin()
+            .into_iter()
+            .flat_map(OriginId::backtrace)
+            .map(|origin| origin.to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(1, "1st")]
+    #[case(2, "2nd")]
+    #[case(3, "3rd")]
+    #[case(4, "4th")]
+    #[case(11, "11th")]
+    #[case(12, "12th")]
+    #[case(13, "13th")]
+    #[case(21, "21st")]
+    #[case(102, "102nd")]
+    fn ordinals(#[case] n: usize, #[case] expected: &str) {
+        assert_eq!(ordinal(n), expected);
+    }
+
+    #[test]
+    fn backtrace() {
+        let outer = OriginId::new(
+            Origin::MacroExpansion {
+                name: "fold!".to_string(),
+            },
+            None,
+        );
+        let arm = OriginId::new(Origin::MatchArm { index: 2 }, Some(outer));
+
+        assert_eq!(
+            arm.backtrace()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "in the 3rd arm of this `match`",
+                "expanded from macro `fold!`"
+            ]
+        );
+        assert_eq!(
+            OriginId::new(Origin::MatchArm { index: 2 }, Some(outer)),
+            arm
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use super::*;
+use crate::{
+    ast::visit::{walk_ast_mut, Visit, VisitMut},
+    diagnostic::{codes, Diagnostic},
+    names::Gensym,
+};
+
+/// A pattern synonym, `pattern Boxed(T) = {value: T}`, that the patterns of match arms use as
+/// `Boxed(?v)`. A use is replaced by the body of the synonym, its parameters by the arguments, so
+/// the caller names the types the pattern infers.
+#[ast_node]
+pub struct PatternDeclaration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    pub name: Ident,
+    pub params: Vec<Ident>,
+    pub body: Rc<Ast>,
+}
+
+impl PatternDeclaration {
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&Ast) -> Ast,
+    {
+        Self {
+            body: f(&self.body).into(),
+            ..self.clone()
+        }
+    }
+}
+
+/// The pattern synonyms declared so far, to expand the patterns of the match arms of a program.
+/// Synonyms are kept from one program to the next, for a program expanded one statement at a
+/// time.
+#[derive(Debug, Clone, Default)]
+pub struct Patterns {
+    declared: HashMap<String, PatternDeclaration>,
+}
+
+impl Patterns {
+    /// Declares the synonyms of `program` and expands their uses in the patterns of its match
+    /// arms, together with the errors of the uses that can't be expanded. The declarations are
+    /// left out of the expanded program, which the rest of the compiler sees as plain patterns.
+    pub fn expand(&mut self, program: &Ast) -> (Ast, Vec<Diagnostic>) {
+        let Ast::Program(Program { statements, span }) = program else {
+            return (program.clone(), vec![]);
+        };
+
+        let (declarations, statements): (Vec<_>, Vec<_>) =
+            statements.iter().cloned().partition(|statement| {
+                matches!(statement, Ast::Statement(inner) if matches!(inner.as_ref(), Ast::PatternDeclaration(_)))
+            });
+
+        for declaration in declarations {
+            if let Ast::Statement(inner) = declaration {
+                if let Ast::PatternDeclaration(pattern) = inner.as_ref() {
+                    self.declared
+                        .insert(pattern.name.name.clone(), pattern.clone());
+                }
+            }
+        }
+
+        let mut program = Ast::Program(Program {
+            statements,
+            span: *span,
+        });
+
+        if self.declared.is_empty() {
+            return (program, vec![]);
+        }
+
+        let mut expander = Expander {
+            declared: &self.declared,
+            gensym: Gensym::new(&program),
+            diagnostics: vec![],
+            expanding: vec![],
+            in_pattern: false,
+        };
+
+        expander.visit_ast_mut(&mut program);
+
+        (program, expander.diagnostics)
+    }
+}
+
+/// Expands the uses of synonyms in the patterns of match arms.
+struct Expander<'p> {
+    declared: &'p HashMap<String, PatternDeclaration>,
+    /// Names the types a synonym infers without the caller naming them, so that they can't
+    /// capture the names of the arm.
+    gensym: Gensym,
+    diagnostics: Vec<Diagnostic>,
+    /// Synonyms being expanded, innermost last, to reject a synonym using itself.
+    expanding: Vec<String>,
+    in_pattern: bool,
+}
+
+impl<'p> Expander<'p> {
+    /// The synonym `node` uses and its arguments, `Name(args...)` or `Name` without arguments.
+    fn use_of<'n>(&self, node: &'n Ast) -> Option<(&'p PatternDeclaration, &'n [Ast])> {
+        let declared = self.declared;
+
+        match node {
+            Ast::ApplyGeneric(ApplyGeneric { receiver, args, .. }) => match receiver.as_ref() {
+                Ast::Ident(name) => declared
+                    .get(&name.name)
+                    .map(|pattern| (pattern, args.as_slice())),
+                _ => None,
+            },
+            Ast::Ident(name) => declared.get(&name.name).map(|pattern| (pattern, &[][..])),
+            _ => None,
+        }
+    }
+
+    /// The body of `pattern` with its parameters replaced by `args` and the types it infers
+    /// renamed, itself expanded.
+    fn instantiate(&mut self, pattern: &PatternDeclaration, args: &[Ast], span: Span) -> Ast {
+        let name = &pattern.name.name;
+
+        if self.expanding.contains(name) {
+            self.diagnostics.push(
+                Diagnostic::error(format!("pattern `{name}` uses itself"), span)
+                    .with_code(codes::INVALID_PATTERN_SYNONYM),
+            );
+
+            return Ast::NeverKeyword(span);
+        }
+
+        if args.len() != pattern.params.len() {
+            let expected = pattern.params.len();
+
+            self.diagnostics.push(
+                Diagnostic::error(
+                    format!(
+                        "pattern `{name}` expects {expected} argument{}, found {}",
+                        if expected == 1 { "" } else { "s" },
+                        args.len()
+                    ),
+                    span,
+                )
+                .with_code(codes::INVALID_PATTERN_SYNONYM),
+            );
+
+            return Ast::NeverKeyword(span);
+        }
+
+        let mut args = args.to_vec();
+        for arg in &mut args {
+            self.visit_ast_mut(arg);
+        }
+
+        let mut inferred = Inferred::default();
+        inferred.visit_ast(&pattern.body);
+
+        let mut substitution = Substitution {
+            replacements: pattern
+                .params
+                .iter()
+                .map(|param| param.name.clone())
+                .zip(args)
+                .collect(),
+            renames: HashMap::new(),
+        };
+
+        for name in inferred.0 {
+            if !substitution.replacements.contains_key(&name) {
+                let fresh = self.gensym.fresh(&name);
+                substitution.renames.insert(name, fresh);
+            }
+        }
+
+        let mut body = (*pattern.body).clone();
+        substitution.visit_ast_mut(&mut body);
+
+        self.expanding.push(name.clone());
+        self.visit_ast_mut(&mut body);
+        self.expanding.pop();
+
+        body
+    }
+}
+
+impl VisitMut for Expander<'_> {
+    fn visit_ast_mut(&mut self, node: &mut Ast) {
+        if self.in_pattern {
+            if let Some((pattern, args)) = self.use_of(node) {
+                let (args, span) = (args.to_vec(), node.as_span());
+                *node = self.instantiate(pattern, &args, span);
+                return;
+            }
+        }
+
+        walk_ast_mut(self, node);
+    }
+
+    fn visit_match_expr_mut(&mut self, expr: &mut MatchExpr) {
+        let in_pattern = self.in_pattern;
+
+        self.visit_ast_mut(Rc::make_mut(&mut expr.value));
+
+        for arm in &mut expr.arms {
+            self.in_pattern = true;
+            self.visit_ast_mut(&mut arm.pattern);
+            self.in_pattern = in_pattern;
+
+            self.visit_ast_mut(&mut arm.body);
+        }
+
+        self.visit_ast_mut(Rc::make_mut(&mut expr.else_arm));
+    }
+}
+
+/// Names of the types a pattern infers.
+#[derive(Default)]
+struct Inferred(Vec<String>);
+
+impl<'a> Visit<'a> for Inferred {
+    fn visit_infer(&mut self, binding: &'a Ast) {
+        if let Ast::Ident(ident) = binding {
+            self.0.push(ident.name.clone());
+        }
+    }
+}
+
+/// Replaces the parameters of a synonym by its arguments and renames the types it infers.
+struct Substitution {
+    replacements: HashMap<String, Ast>,
+    renames: HashMap<String, Ident>,
+}
+
+impl VisitMut for Substitution {
+    fn visit_ast_mut(&mut self, node: &mut Ast) {
+        if let Ast::Ident(ident) = node {
+            if let Some(replacement) = self.replacements.get(&ident.name) {
+                *node = replacement.clone();
+                return;
+            }
+        }
+
+        walk_ast_mut(self, node);
+    }
+
+    fn visit_ident_mut(&mut self, ident: &mut Ident) {
+        if let Some(fresh) = self.renames.get(&ident.name) {
+            ident.name = fresh.name.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn typescript(source: &str) -> String {
+        compile(source, &CompileOptions::default())
+            .unwrap()
+            .typescript
+            .trim()
+            .to_string()
+    }
+
+    fn errors(source: &str) -> Vec<String> {
+        compile(source, &CompileOptions::default())
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn used_in_two_matches() {
+        let source = dedent!(
+            r#"
+            #feature(pattern_synonyms)
+            pattern Boxed(T) = {value: T, meta: ?M}
+            type Unbox(B) as match B do Boxed(?V) -> V, else -> never end
+            type Tag(B) as match B do Boxed(?M) -> [M], else -> never end
+            "#
+        );
+
+        assert_eq!(
+            typescript(source),
+            dedent!(
+                r#"
+                type Unbox<B> = B extends {value: infer V, meta: infer __dl_M_0} ? V : never;
+
+                type Tag<B> = B extends {value: infer M, meta: infer __dl_M_1} ? [M] : never;
+                "#
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let source = dedent!(
+            r#"
+            #feature(pattern_synonyms)
+            pattern Boxed(T) = {value: T}
+            pattern Pair(A, B) = [Boxed(A), Boxed(B)]
+            type Swap(P) as match P do Pair(?A, ?B) -> [B, A], else -> never end
+            "#
+        );
+
+        assert_eq!(
+            typescript(source),
+            "type Swap<P> = P extends [{value: infer A}, {value: infer B}] ? [B, A] : never;"
+        );
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let source = dedent!(
+            r#"
+            #feature(pattern_synonyms)
+            pattern Boxed(T) = {value: T}
+            type A(B) as match B do Boxed(?V, ?W) -> V, else -> never end
+            "#
+        );
+
+        assert_eq!(
+            errors(source),
+            vec!["pattern `Boxed` expects 1 argument, found 2"]
+        );
+    }
+
+    #[test]
+    fn recursive() {
+        let source = dedent!(
+            r#"
+            #feature(pattern_synonyms)
+            pattern List(T) = [T, List(T)]
+            type A(B) as match B do List(?V) -> V, else -> never end
+            "#
+        );
+
+        assert_eq!(errors(source), vec!["pattern `List` uses itself"]);
+    }
+}
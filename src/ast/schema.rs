@@ -0,0 +1,246 @@
+/// The JSON Schema of the serialization of a program, see [`Ast::to_json`], for the tools reading
+/// it. The schema is written by hand next to the nodes; the tests check it against the
+/// serialization of random programs, and the serialization of `tests/compat/program.dl` against
+/// the one recorded for the current [`AST_VERSION`], so that a change to the shape of a node
+/// doesn't go unnoticed.
+use serde_json::Value;
+
+#[cfg(doc)]
+use super::Ast;
+use super::AST_VERSION;
+
+/// The schema, printed by `newtype schema`.
+pub const SCHEMA: &str = include_str!("schema.json");
+
+/// The serialized program `json`, if it has the [`AST_VERSION`] of this compiler. The version is
+/// checked before anything else, a program of another version is an error saying so rather than
+/// one about the first node whose shape changed.
+pub fn read_json(json: &str) -> Result<Value, String> {
+    let value: Value = serde_json::from_str(json).map_err(|err| format!("invalid AST: {err}"))?;
+
+    // Version 1 kept it in the program.
+    let version = value
+        .get("schema_version")
+        .or_else(|| value.get("program")?.get("version"))
+        .ok_or("invalid AST: it has no `schema_version`")?;
+
+    match version.as_u64() {
+        Some(version) if version == u64::from(AST_VERSION) => Ok(value),
+        Some(version) => Err(format!(
+            "AST schema version {version} isn't supported, this compiler reads version \
+             {AST_VERSION}; serialize the program again"
+        )),
+        None => Err(format!("invalid AST: `schema_version` is `{version}`")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ast::Ast, test_support::parse};
+    use pretty_assertions::assert_eq;
+
+    const PROGRAM: &str = include_str!("../../tests/compat/program.dl");
+    const V1: &str = include_str!("../../tests/compat/program.v1.json");
+    const V2: &str = include_str!("../../tests/compat/program.v2.json");
+
+    fn schema() -> Value {
+        serde_json::from_str(SCHEMA).unwrap()
+    }
+
+    /// Whether `value` matches `schema`, for the keywords the schema uses. An unknown keyword is
+    /// an error, so that the schema can't say something the tests don't check.
+    fn validate(schema: &Value, root: &Value, value: &Value, path: &str) -> Result<(), String> {
+        let fail = |message: String| Err(format!("{path}: {message}"));
+        let Value::Object(keywords) = schema else {
+            return fail(format!("schema `{schema}` isn't an object"));
+        };
+
+        for (keyword, expected) in keywords {
+            match keyword.as_str() {
+                "$schema" | "title" | "description" | "$defs" => {}
+                "$ref" => {
+                    let name = expected
+                        .as_str()
+                        .and_then(|reference| reference.strip_prefix("#/$defs/"))
+                        .unwrap();
+                    validate(&root["$defs"][name], root, value, path)?;
+                }
+                "type" => {
+                    let types = match expected {
+                        Value::Array(types) => types.clone(),
+                        ty => vec![ty.clone()],
+                    };
+                    let matches = |ty: &Value| match ty.as_str().unwrap() {
+                        "object" => value.is_object(),
+                        "array" => value.is_array(),
+                        "string" => value.is_string(),
+                        "boolean" => value.is_boolean(),
+                        "integer" => value.is_u64() || value.is_i64(),
+                        "null" => value.is_null(),
+                        ty => panic!("unknown type `{ty}`"),
+                    };
+
+                    if !types.iter().any(matches) {
+                        return fail(format!("`{value}` isn't of type {expected}"));
+                    }
+                }
+                "const" if value != expected => {
+                    return fail(format!("`{value}` isn't `{expected}`"));
+                }
+                "enum" if !expected.as_array().unwrap().contains(value) => {
+                    return fail(format!("`{value}` isn't one of {expected}"));
+                }
+                "const" | "enum" => {}
+                "minimum" if value.as_f64() < expected.as_f64() => {
+                    return fail(format!("`{value}` is less than {expected}"));
+                }
+                "minimum" => {}
+                "properties" => {
+                    for (name, property) in expected.as_object().unwrap() {
+                        if let Some(value) = value.get(name) {
+                            validate(property, root, value, &format!("{path}.{name}"))?;
+                        }
+                    }
+                }
+                "required" => {
+                    for name in expected.as_array().unwrap() {
+                        if value.get(name.as_str().unwrap()).is_none() {
+                            return fail(format!("`{name}` is missing"));
+                        }
+                    }
+                }
+                "additionalProperties" => {
+                    assert_eq!(expected, &Value::Bool(false));
+                    let known = keywords.get("properties").and_then(Value::as_object);
+
+                    for name in value
+                        .as_object()
+                        .into_iter()
+                        .flat_map(|object| object.keys())
+                    {
+                        if !known.is_some_and(|known| known.contains_key(name)) {
+                            return fail(format!("`{name}` isn't a known property"));
+                        }
+                    }
+                }
+                "minProperties" | "maxProperties" => {
+                    let Some(count) = value.as_object().map(|object| object.len() as u64) else {
+                        continue;
+                    };
+                    let bound = expected.as_u64().unwrap();
+
+                    if (keyword == "minProperties" && count < bound)
+                        || (keyword == "maxProperties" && count > bound)
+                    {
+                        return fail(format!("{count} properties, {keyword} is {bound}"));
+                    }
+                }
+                "items" => {
+                    for (i, item) in value.as_array().into_iter().flatten().enumerate() {
+                        validate(expected, root, item, &format!("{path}[{i}]"))?;
+                    }
+                }
+                "minItems" | "maxItems" => {
+                    let Some(count) = value.as_array().map(|items| items.len() as u64) else {
+                        continue;
+                    };
+                    let bound = expected.as_u64().unwrap();
+
+                    if (keyword == "minItems" && count < bound)
+                        || (keyword == "maxItems" && count > bound)
+                    {
+                        return fail(format!("{count} items, {keyword} is {bound}"));
+                    }
+                }
+                "oneOf" => {
+                    let (matching, errors): (Vec<_>, Vec<_>) = expected
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|schema| validate(schema, root, value, path))
+                        .partition(Result::is_ok);
+
+                    match matching.len() {
+                        1 => {}
+                        // The error of the alternative matching the deepest.
+                        0 => {
+                            return errors
+                                .into_iter()
+                                .max_by_key(|error| {
+                                    error.as_ref().unwrap_err().split(": ").next().map(str::len)
+                                })
+                                .unwrap()
+                        }
+                        count => return fail(format!("`{value}` matches {count} of `oneOf`")),
+                    }
+                }
+                keyword => panic!("unknown keyword `{keyword}`"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn matches_schema(program: &Ast) -> Result<(), String> {
+        let schema = schema();
+
+        validate(&schema, &schema, &program.to_json().unwrap(), "")
+    }
+
+    #[test]
+    fn versioned_schema() {
+        assert_eq!(
+            schema()["properties"]["schema_version"]["const"],
+            AST_VERSION
+        );
+    }
+
+    #[test]
+    fn program_matches_the_schema() {
+        assert_eq!(matches_schema(&parse!(PROGRAM)), Ok(()));
+    }
+
+    #[quickcheck]
+    fn random_programs_match_the_schema(program: Ast) -> bool {
+        matches_schema(&program)
+            .map_err(|err| panic!("{err}\n{}", program.to_json().unwrap()))
+            .is_ok()
+    }
+
+    #[test]
+    fn mismatches_are_reported() {
+        let schema = schema();
+        let mut json = parse!("type A as 1").to_json().unwrap();
+        json["program"]["statements"][0]["statement"]["type"]["body"] = serde_json::json!(1);
+
+        assert_eq!(
+            validate(&schema, &schema, &json, ""),
+            Err(".program.statements[0].statement.type.body: `1` isn't of type \"object\"".into())
+        );
+    }
+
+    /// The serialization of the fixture is the one recorded for the current version; a change to
+    /// it needs a new version, and the recording of the previous one kept next to it.
+    #[test]
+    fn recorded_serialization() {
+        assert_eq!(read_json(V2), Ok(parse!(PROGRAM).to_json().unwrap()));
+    }
+
+    #[test]
+    fn previous_versions_are_rejected() {
+        assert_eq!(
+            read_json(V1),
+            Err(
+                "AST schema version 1 isn't supported, this compiler reads version 2; serialize \
+                 the program again"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            read_json(r#"{"program": {"statements": []}}"#),
+            Err("invalid AST: it has no `schema_version`".to_string())
+        );
+        assert!(read_json("{").unwrap_err().starts_with("invalid AST:"));
+    }
+}
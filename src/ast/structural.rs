@@ -0,0 +1,147 @@
+/// Structural identity of nodes, for the passes comparing, deduplicating or caching subtrees.
+///
+/// [`Ast::content_hash`] and [`Ast::structural_cmp`] both read the canonical form of a node, its
+/// serialization without any span, those of `let` bindings included, so they agree with `==`:
+/// equal nodes have the same hash and compare `Equal`, wherever they are written.
+///
+/// A hash is the same from one process to the next for a version of the crate, but may change
+/// with the shape of the nodes from one version to the next. A hash stored across versions must
+/// be stored with the version.
+use std::{cell::Cell, cmp::Ordering, hash::Hasher, io};
+
+use super::*;
+
+thread_local! {
+    /// Whether nodes are serialized in their canonical form.
+    static CANONICAL: Cell<bool> = const { Cell::new(false) };
+}
+
+impl Ast {
+    /// A hash of the structure of the node, ignoring spans, see the [module docs](self).
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        canonically(|| serde_json::to_writer(&mut hasher, self)).expect("nodes serialize");
+        hasher.finish()
+    }
+
+    /// A total order of nodes, ignoring spans, that is `Equal` exactly for equal nodes, to sort
+    /// union members or properties in the same order whatever order they are written in.
+    pub fn structural_cmp(&self, other: &Ast) -> Ordering {
+        canonical(self).cmp(&canonical(other))
+    }
+}
+
+fn canonical(node: &Ast) -> Vec<u8> {
+    canonically(|| serde_json::to_vec(node)).expect("nodes serialize")
+}
+
+/// Runs `f` with nodes serialized in their canonical form.
+fn canonically<T>(f: impl FnOnce() -> T) -> T {
+    let previous = CANONICAL.replace(true);
+    let result = f();
+    CANONICAL.set(previous);
+    result
+}
+
+/// Whether the span of a `let` binding is left out when it is serialized, in the canonical form.
+pub(crate) fn hides(_: &Span) -> bool {
+    CANONICAL.get()
+}
+
+/// The 64 bit FNV-1a hash, whose output only depends on its input, unlike the randomly seeded
+/// `DefaultHasher`.
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl io::Write for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        Hasher::write(self, bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::Rule, test_support::parse};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[test]
+    fn fnv1a() {
+        let mut hasher = Fnv1a::default();
+        Hasher::write(&mut hasher, b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    // A change of these hashes changes the hash of every tree, which is fine between versions of
+    // the crate but should be deliberate.
+    #[rstest]
+    #[case("string", 0x6f7d_8982_1f23_c94f)]
+    #[case("1 | :a", 0xd35a_9a4e_fe33_82d3)]
+    #[case("{a: number, readonly b?: T[]}", 0xe91f_2030_fbaa_7553)]
+    #[case("Box(T)", 0x8080_3627_76cb_5da6)]
+    #[case("if T <: string then 1 else never end", 0xeb23_dfd4_16c6_dac9)]
+    fn fixed_hashes(#[case] source: &str, #[case] hash: u64) {
+        assert_eq!(parse!(Rule::expr, source).content_hash(), hash);
+    }
+
+    #[test]
+    fn spans_are_ignored() {
+        for (a, b) in [
+            ("{a: 1, b: [T, U]}", "{ a : 1 ,  b : [ T , U ] }"),
+            ("let X = 1 in X", "let  X = 1 in X"),
+        ] {
+            let (a, b) = (parse!(Rule::expr, a), parse!(Rule::expr, b));
+
+            assert_eq!(a, b);
+            assert_eq!(a.content_hash(), b.content_hash());
+            assert_eq!(a.structural_cmp(&b), Ordering::Equal);
+        }
+    }
+
+    #[rstest]
+    #[case("1", "2")]
+    #[case("A", "B")]
+    #[case("{a: 1}", "{a: 2}")]
+    #[case("[A, B]", "[B, A]")]
+    #[case("A | B", "A & B")]
+    fn different_trees(#[case] a: &str, #[case] b: &str) {
+        let (a, b) = (parse!(Rule::expr, a), parse!(Rule::expr, b));
+
+        assert_ne!(a.content_hash(), b.content_hash());
+        assert_ne!(a.structural_cmp(&b), Ordering::Equal);
+        assert_eq!(a.structural_cmp(&b), b.structural_cmp(&a).reverse());
+    }
+
+    #[quickcheck]
+    fn ordering_agrees_with_equality(a: Ast, b: Ast) -> bool {
+        let copy = a.clone();
+
+        (a.structural_cmp(&b) == Ordering::Equal) == (a == b)
+            && a.structural_cmp(&copy) == Ordering::Equal
+            && a.content_hash() == copy.content_hash()
+    }
+}
@@ -0,0 +1,186 @@
+use super::*;
+use crate::{ast::visit::Visit, diagnostic::Diagnostic};
+
+/// Text that fails to parse where a property of an object, an element of a tuple or an arm of a
+/// `match` is expected. The grammar skips it up to the next `,` or closing bracket, so that the
+/// rest of the construct is still parsed and checked, and the node stands in its place with the
+/// error re-parsing it reports.
+#[ast_node]
+pub struct SyntaxError {
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore", Debug = "ignore")]
+    pub diagnostic: Box<Diagnostic>,
+}
+
+impl Ast {
+    /// The errors of the text skipped while parsing the node, in the order of the source.
+    pub fn syntax_errors(&self) -> Vec<Diagnostic> {
+        let mut errors = SyntaxErrors::default();
+        errors.visit_ast(self);
+        errors.0
+    }
+}
+
+#[derive(Default)]
+struct SyntaxErrors(Vec<Diagnostic>);
+
+impl<'a> Visit<'a> for SyntaxErrors {
+    fn visit_syntax_error(&mut self, error: &'a SyntaxError) {
+        self.0.push((*error.diagnostic).clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compile::{compile, CompileOptions},
+        parser::parse_program_with_errors,
+        query::completions_at,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    /// The operands pest expects after `|` besides an expression.
+    const OPERAND: &str = "`?`, a parameter, an object literal, or a string";
+
+    fn errors(source: &str) -> Vec<(String, usize)> {
+        compile(source, &CompileOptions::default())
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.message, diagnostic.span.start))
+            .collect()
+    }
+
+    #[test]
+    fn object_with_a_bad_property() {
+        let source = "type A as {a: 1, b: 2 3, c: string}";
+        let (program, errors) = parse_program_with_errors(source).unwrap();
+
+        let Ast::Program(Program { statements, .. }) = &program else {
+            unreachable!()
+        };
+        let Ast::Statement(statement) = &statements[0] else {
+            unreachable!()
+        };
+        let Ast::TypeAlias(TypeAlias { body, .. }) = statement.inner.as_ref() else {
+            unreachable!()
+        };
+        let Ast::TypeLiteral(TypeLiteral { properties, .. }) = body.as_ref() else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            properties
+                .iter()
+                .map(|property| (&property.key, property.value.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (&ObjectPropertyKey::Key("a".to_string()), "1".to_string()),
+                (
+                    &ObjectPropertyKey::Key(String::new()),
+                    "/* syntax error */".to_string()
+                ),
+                (
+                    &ObjectPropertyKey::Key("c".to_string()),
+                    "string".to_string()
+                )
+            ]
+        );
+        assert_eq!(
+            errors
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.span.start))
+                .collect::<Vec<_>>(),
+            vec![("expected `,`, `}`, or an operator, found `3`", 22)]
+        );
+    }
+
+    #[test]
+    fn match_with_a_bad_arm() {
+        let source = dedent!(
+            r#"
+            type A(T) as match T do
+                string -> 1,
+                number -> ,
+                [?U] -> U,
+                else -> never
+            end
+            "#
+        );
+        let (program, errors) = parse_program_with_errors(source).unwrap();
+
+        struct Arms(Vec<String>);
+
+        impl<'a> Visit<'a> for Arms {
+            fn visit_match_expr(&mut self, expr: &'a MatchExpr) {
+                for arm in &expr.arms {
+                    self.0.push(arm.pattern.to_string());
+                }
+            }
+        }
+
+        let mut arms = Arms(vec![]);
+        arms.visit_ast(&program);
+
+        assert_eq!(arms.0, vec!["string", "/* syntax error */", "[?U]"]);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|diagnostic| diagnostic.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["expected an expression, found `,`"]
+        );
+    }
+
+    #[test]
+    fn every_error_is_reported() {
+        let source = dedent!(
+            r#"
+            type A as {a: 1 |, b: 2, c: ;}
+            type B as [1, 2 2, 3, ;]
+            type C(T) as match T do 1 -> 2, 2 => 3, else -> never end
+            "#
+        );
+
+        assert_eq!(
+            errors(source),
+            vec![
+                (format!("expected an expression, {OPERAND}, found `,`"), 18),
+                ("expected an expression, found `;`".to_string(), 29),
+                (
+                    "expected `,`, `]`, or an operator, found `2`".to_string(),
+                    48
+                ),
+                ("expected an expression, found `;`".to_string(), 54),
+                ("expected `->` or an operator, found `=`".to_string(), 91),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_run_past_the_error() {
+        let source = "type A as {a: 1 |}\ntype Partial as 1";
+
+        assert_eq!(
+            errors(source),
+            vec![
+                (format!("expected an expression, {OPERAND}, found `}}`"), 17),
+                (
+                    "`Partial` shadows the global TypeScript type of the same name".to_string(),
+                    24
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn completions_around_the_error() {
+        let source = "type A(T) as {a: 1 |, b: T}";
+        let (program, _) = parse_program_with_errors(source).unwrap();
+
+        assert!(completions_at(&program, source.len() - 2)
+            .iter()
+            .any(|item| item.label == "T"));
+    }
+}
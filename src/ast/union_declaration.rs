@@ -0,0 +1,109 @@
+use super::*;
+
+/// Discriminant key of a union declared without `by`.
+pub const DEFAULT_DISCRIMINANT: &str = "kind";
+
+/// A discriminated union, `union Shape = Circle {radius: number} | Square {size: number}`.
+#[ast_node]
+pub struct UnionDeclaration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    pub export: bool,
+    pub name: Ident,
+    /// Key of the property holding the name of the variant.
+    pub discriminant: String,
+    pub variants: Vec<Variant>,
+}
+
+#[ast_node]
+pub struct Variant {
+    pub name: Ident,
+    pub body: TypeLiteral,
+}
+
+impl UnionDeclaration {
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&Ast) -> Ast,
+    {
+        Self {
+            variants: self
+                .variants
+                .iter()
+                .map(|variant| Variant {
+                    body: variant.body.map(&f),
+                    ..variant.clone()
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Names of the variants, in order of declaration.
+    pub fn variant_names(&self) -> impl Iterator<Item = &str> {
+        self.variants
+            .iter()
+            .map(|variant| variant.name.name.as_str())
+    }
+
+    /// Statements declaring an alias per variant, its properties preceded by the discriminant,
+    /// followed by the alias of the union of the variants.
+    pub fn simplify(&self) -> Vec<Ast> {
+        let alias = |doc: Option<String>, name: &Ident, body: Ast| {
+            Ast::Statement(
+                Ast::TypeAlias(TypeAlias {
+                    doc,
+                    export: self.export,
+                    global: false,
+                    default: false,
+                    name: name.clone(),
+                    params: vec![],
+                    body: body.into(),
+                    span: self.span,
+                })
+                .into(),
+            )
+        };
+
+        let mut statements: Vec<_> = self
+            .variants
+            .iter()
+            .map(|variant| {
+                let discriminant = ObjectProperty {
+                    readonly: false,
+                    optional: false,
+                    key: ObjectPropertyKey::Key(self.discriminant.clone()),
+                    value: Ast::TypeString(TypeString {
+                        ty: variant.name.name.clone(),
+                        span: variant.name.span,
+                    }),
+                    span: variant.span,
+                };
+
+                let properties = std::iter::once(discriminant)
+                    .chain(variant.body.properties.iter().cloned())
+                    .collect();
+
+                let body = Ast::TypeLiteral(TypeLiteral {
+                    properties,
+                    span: variant.body.span,
+                });
+
+                alias(None, &variant.name, body)
+            })
+            .collect();
+
+        let union = Ast::UnionType(UnionType {
+            types: self
+                .variants
+                .iter()
+                .map(|variant| Ast::Ident(variant.name.clone()))
+                .collect(),
+            span: self.span,
+        });
+
+        statements.push(alias(self.doc.clone(), &self.name, union));
+
+        statements
+    }
+}
@@ -0,0 +1,1051 @@
+/// Visitors over the tree, for passes that need a hook per kind of node and state of their own.
+///
+/// Every method of [`Visit`] defaults to the `walk_*` function of the same node, which visits its
+/// children. A pass overrides the methods of the nodes it is interested in, and calls the `walk_*`
+/// function from them to keep going down:
+///
+/// ```
+/// use newtype::{
+///     ast::{visit::walk_type_alias, TypeAlias},
+///     prelude::*,
+/// };
+///
+/// struct Aliases(usize);
+///
+/// impl<'a> Visit<'a> for Aliases {
+///     fn visit_type_alias(&mut self, alias: &'a TypeAlias) {
+///         self.0 += 1;
+///         walk_type_alias(self, alias);
+///     }
+/// }
+///
+/// let mut aliases = Aliases(0);
+/// aliases.visit_ast(&parse_source("type A as 1\ninterface B {}\ntype C as 2").unwrap());
+///
+/// assert_eq!(aliases.0, 2);
+/// ```
+///
+/// Unlike [`Ast::traverse`], the walk enters every child of every node: interfaces, function
+/// types, inferred types and the arguments of macro calls included. [`VisitMut`] is the same over
+/// a mutable tree.
+use super::*;
+
+pub trait Visit<'a> {
+    /// Any node, dispatched by [`walk_ast`] to the method of its kind.
+    fn visit_ast(&mut self, node: &'a Ast) {
+        walk_ast(self, node)
+    }
+
+    fn visit_program(&mut self, program: &'a Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_statement(&mut self, statement: &'a Ast) {
+        self.visit_ast(statement)
+    }
+
+    fn visit_type_alias(&mut self, alias: &'a TypeAlias) {
+        walk_type_alias(self, alias)
+    }
+
+    fn visit_interface(&mut self, interface: &'a Interface) {
+        walk_interface(self, interface)
+    }
+
+    fn visit_union_declaration(&mut self, union: &'a UnionDeclaration) {
+        walk_union_declaration(self, union)
+    }
+
+    fn visit_brand_declaration(&mut self, brand: &'a BrandDeclaration) {
+        walk_brand_declaration(self, brand)
+    }
+
+    fn visit_pattern_declaration(&mut self, pattern: &'a PatternDeclaration) {
+        walk_pattern_declaration(self, pattern)
+    }
+
+    fn visit_export_default(&mut self, export: &'a ExportDefault) {
+        walk_export_default(self, export)
+    }
+
+    fn visit_import_statement(&mut self, import: &'a ImportStatement) {
+        walk_import_statement(self, import)
+    }
+
+    fn visit_unit_test(&mut self, test: &'a UnitTest) {
+        walk_unit_test(self, test)
+    }
+
+    fn visit_ambient_module(&mut self, module: &'a AmbientModule) {
+        walk_ambient_module(self, module)
+    }
+
+    fn visit_type_parameter(&mut self, param: &'a TypeParameter) {
+        walk_type_parameter(self, param)
+    }
+
+    fn visit_object_property(&mut self, property: &'a ObjectProperty) {
+        walk_object_property(self, property)
+    }
+
+    fn visit_access(&mut self, access: &'a Access) {
+        walk_access(self, access)
+    }
+
+    fn visit_apply_generic(&mut self, apply: &'a ApplyGeneric) {
+        walk_apply_generic(self, apply)
+    }
+
+    fn visit_array(&mut self, item: &'a Ast) {
+        self.visit_ast(item)
+    }
+
+    fn visit_union_type(&mut self, union: &'a UnionType) {
+        walk_union_type(self, union)
+    }
+
+    fn visit_intersection_type(&mut self, intersection: &'a IntersectionType) {
+        walk_intersection_type(self, intersection)
+    }
+
+    fn visit_builtin(&mut self, builtin: &'a Builtin) {
+        walk_builtin(self, builtin)
+    }
+
+    fn visit_cond_expr(&mut self, cond: &'a CondExpr) {
+        walk_cond_expr(self, cond)
+    }
+
+    fn visit_extends_infix_op(&mut self, op: &'a ExtendsInfixOp) {
+        walk_extends_infix_op(self, op)
+    }
+
+    fn visit_extends_prefix_op(&mut self, op: &'a ExtendsPrefixOp) {
+        walk_extends_prefix_op(self, op)
+    }
+
+    fn visit_extends_expr(&mut self, expr: &'a ExtendsExpr) {
+        walk_extends_expr(self, expr)
+    }
+
+    /// An inferred type, `binding` being the name it declares.
+    fn visit_infer(&mut self, binding: &'a Ast) {
+        self.visit_ast(binding)
+    }
+
+    fn visit_if_expr(&mut self, expr: &'a IfExpr) {
+        walk_if_expr(self, expr)
+    }
+
+    fn visit_let_expr(&mut self, expr: &'a LetExpr) {
+        walk_let_expr(self, expr)
+    }
+
+    fn visit_match_expr(&mut self, expr: &'a MatchExpr) {
+        walk_match_expr(self, expr)
+    }
+
+    fn visit_mapped_type(&mut self, mapped: &'a MappedType) {
+        walk_mapped_type(self, mapped)
+    }
+
+    fn visit_type_literal(&mut self, literal: &'a TypeLiteral) {
+        walk_type_literal(self, literal)
+    }
+
+    fn visit_tuple(&mut self, tuple: &'a Tuple) {
+        walk_tuple(self, tuple)
+    }
+
+    fn visit_function_type(&mut self, function: &'a FunctionType) {
+        walk_function_type(self, function)
+    }
+
+    fn visit_macro_call(&mut self, call: &'a MacroCall) {
+        walk_macro_call(self, call)
+    }
+
+    /// A name, referenced or declared.
+    fn visit_ident(&mut self, _ident: &'a Ident) {}
+
+    fn visit_type_number(&mut self, _number: &'a TypeNumber) {}
+
+    fn visit_type_string(&mut self, _string: &'a TypeString) {}
+
+    fn visit_template_string(&mut self, _string: &'a TemplateString) {}
+
+    fn visit_import_type(&mut self, _import: &'a ImportType) {}
+
+    fn visit_primitive(&mut self, _primitive: &'a PrimitiveType) {}
+
+    /// `never`, `unknown`, `any`, `this`, `true`, `false` or a no-op, the nodes that are only a
+    /// span.
+    fn visit_keyword(&mut self, _keyword: &'a Ast) {}
+
+    fn visit_syntax_error(&mut self, _error: &'a SyntaxError) {}
+}
+
+pub fn walk_ast<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, node: &'a Ast) {
+    match node {
+        Ast::Access(access) => visitor.visit_access(access),
+        Ast::MacroCall(call) => visitor.visit_macro_call(call),
+        Ast::ApplyGeneric(apply) => visitor.visit_apply_generic(apply),
+        Ast::Array(item) => visitor.visit_array(item),
+        Ast::UnionType(union) => visitor.visit_union_type(union),
+        Ast::IntersectionType(intersection) => visitor.visit_intersection_type(intersection),
+        Ast::Builtin(builtin) => visitor.visit_builtin(builtin),
+        Ast::CondExpr(cond) => visitor.visit_cond_expr(cond),
+        Ast::ExtendsInfixOp(op) => visitor.visit_extends_infix_op(op),
+        Ast::ExtendsExpr(expr) => visitor.visit_extends_expr(expr),
+        Ast::Infer(binding) => visitor.visit_infer(binding),
+        Ast::ExtendsPrefixOp(op) => visitor.visit_extends_prefix_op(op),
+        Ast::Ident(ident) => visitor.visit_ident(ident),
+        Ast::IfExpr(expr) => visitor.visit_if_expr(expr),
+        Ast::ImportStatement(import) => visitor.visit_import_statement(import),
+        Ast::ImportType(import) => visitor.visit_import_type(import),
+        Ast::LetExpr(expr) => visitor.visit_let_expr(expr),
+        Ast::MappedType(mapped) => visitor.visit_mapped_type(mapped),
+        Ast::MatchExpr(expr) => visitor.visit_match_expr(expr),
+        Ast::TypeNumber(number) => visitor.visit_type_number(number),
+        Ast::TypeLiteral(literal) => visitor.visit_type_literal(literal),
+        Ast::Primitive(primitive, _) => visitor.visit_primitive(primitive),
+        Ast::Program(program) => visitor.visit_program(program),
+        Ast::Statement(statement) => visitor.visit_statement(statement),
+        Ast::UnitTest(test) => visitor.visit_unit_test(test),
+        Ast::AmbientModule(module) => visitor.visit_ambient_module(module),
+        Ast::TypeString(string) => visitor.visit_type_string(string),
+        Ast::TemplateString(string) => visitor.visit_template_string(string),
+        Ast::Tuple(tuple) => visitor.visit_tuple(tuple),
+        Ast::TypeAlias(alias) => visitor.visit_type_alias(alias),
+        Ast::UnionDeclaration(union) => visitor.visit_union_declaration(union),
+        Ast::BrandDeclaration(brand) => visitor.visit_brand_declaration(brand),
+        Ast::PatternDeclaration(pattern) => visitor.visit_pattern_declaration(pattern),
+        Ast::ExportDefault(export) => visitor.visit_export_default(export),
+        Ast::Interface(interface) => visitor.visit_interface(interface),
+        Ast::FunctionType(function) => visitor.visit_function_type(function),
+        Ast::NeverKeyword(_)
+        | Ast::TrueKeyword(_)
+        | Ast::FalseKeyword(_)
+        | Ast::UnknownKeyword(_)
+        | Ast::AnyKeyword(_)
+        | Ast::ThisType(_)
+        | Ast::NoOp(_) => visitor.visit_keyword(node),
+        Ast::SyntaxError(error) => visitor.visit_syntax_error(error),
+    }
+}
+
+pub fn walk_program<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, program: &'a Program) {
+    for statement in &program.statements {
+        visitor.visit_ast(statement);
+    }
+}
+
+pub fn walk_type_alias<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, alias: &'a TypeAlias) {
+    visitor.visit_ident(&alias.name);
+
+    for param in &alias.params {
+        visitor.visit_type_parameter(param);
+    }
+
+    visitor.visit_ast(&alias.body);
+}
+
+pub fn walk_interface<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, interface: &'a Interface) {
+    for param in &interface.params {
+        visitor.visit_type_parameter(param);
+    }
+
+    for property in &interface.definition {
+        visitor.visit_object_property(property);
+    }
+}
+
+pub fn walk_union_declaration<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    union: &'a UnionDeclaration,
+) {
+    visitor.visit_ident(&union.name);
+
+    for variant in &union.variants {
+        visitor.visit_ident(&variant.name);
+        visitor.visit_type_literal(&variant.body);
+    }
+}
+
+pub fn walk_brand_declaration<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    brand: &'a BrandDeclaration,
+) {
+    visitor.visit_ident(&brand.name);
+    visitor.visit_ast(&brand.base);
+}
+
+pub fn walk_pattern_declaration<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    pattern: &'a PatternDeclaration,
+) {
+    visitor.visit_ident(&pattern.name);
+
+    for param in &pattern.params {
+        visitor.visit_ident(param);
+    }
+
+    visitor.visit_ast(&pattern.body);
+}
+
+pub fn walk_export_default<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, export: &'a ExportDefault) {
+    visitor.visit_ident(&export.name);
+}
+
+pub fn walk_import_statement<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    import: &'a ImportStatement,
+) {
+    match &import.import_clause {
+        ImportClause::Named(specifiers) => {
+            for specifier in specifiers {
+                visitor.visit_ident(&specifier.module_export_name);
+
+                if let Some(alias) = &specifier.alias {
+                    visitor.visit_ident(alias);
+                }
+            }
+        }
+        ImportClause::Namespace { alias } => visitor.visit_ident(alias),
+    }
+}
+
+pub fn walk_unit_test<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, test: &'a UnitTest) {
+    for node in &test.body {
+        visitor.visit_ast(node);
+    }
+}
+
+pub fn walk_ambient_module<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, module: &'a AmbientModule) {
+    for statement in &module.body {
+        visitor.visit_ast(statement);
+    }
+}
+
+pub fn walk_type_parameter<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, param: &'a TypeParameter) {
+    visitor.visit_ident(&param.name);
+
+    for node in param.constraint.iter().chain(&param.default) {
+        visitor.visit_ast(node);
+    }
+}
+
+pub fn walk_object_property<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    property: &'a ObjectProperty,
+) {
+    match &property.key {
+        ObjectPropertyKey::Index(index) => {
+            visitor.visit_ast(&index.iterable);
+
+            if let Some(remapped_as) = &index.remapped_as {
+                visitor.visit_ast(remapped_as);
+            }
+        }
+        ObjectPropertyKey::Key(_) => {}
+        ObjectPropertyKey::Computed(ident) => visitor.visit_ident(ident),
+    }
+
+    visitor.visit_ast(&property.value);
+}
+
+pub fn walk_access<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, access: &'a Access) {
+    visitor.visit_ast(&access.lhs);
+
+    // The names after a dot or `::` are members of the left hand side, not references.
+    if access.kind == AccessKind::Index {
+        visitor.visit_ast(&access.rhs);
+    }
+}
+
+pub fn walk_apply_generic<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, apply: &'a ApplyGeneric) {
+    visitor.visit_ast(&apply.receiver);
+
+    for arg in &apply.args {
+        visitor.visit_ast(arg);
+    }
+}
+
+pub fn walk_union_type<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, union: &'a UnionType) {
+    for ty in &union.types {
+        visitor.visit_ast(ty);
+    }
+}
+
+pub fn walk_intersection_type<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    intersection: &'a IntersectionType,
+) {
+    for ty in &intersection.types {
+        visitor.visit_ast(ty);
+    }
+}
+
+pub fn walk_builtin<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, builtin: &'a Builtin) {
+    visitor.visit_ast(&builtin.argument);
+}
+
+pub fn walk_cond_expr<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, cond: &'a CondExpr) {
+    for arm in &cond.arms {
+        visitor.visit_ast(&arm.condition);
+        visitor.visit_ast(&arm.body);
+    }
+
+    if let Some(else_arm) = &cond.else_arm {
+        visitor.visit_ast(else_arm);
+    }
+}
+
+pub fn walk_extends_infix_op<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, op: &'a ExtendsInfixOp) {
+    visitor.visit_ast(&op.lhs);
+    visitor.visit_ast(&op.rhs);
+}
+
+pub fn walk_extends_prefix_op<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, op: &'a ExtendsPrefixOp) {
+    visitor.visit_ast(&op.value);
+}
+
+pub fn walk_extends_expr<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, expr: &'a ExtendsExpr) {
+    visitor.visit_ast(&expr.lhs);
+    visitor.visit_ast(&expr.rhs);
+    visitor.visit_ast(&expr.then_branch);
+    visitor.visit_ast(&expr.else_branch);
+}
+
+pub fn walk_if_expr<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, expr: &'a IfExpr) {
+    visitor.visit_ast(&expr.condition);
+    visitor.visit_ast(&expr.then_branch);
+
+    if let Some(else_branch) = &expr.else_branch {
+        visitor.visit_ast(else_branch);
+    }
+}
+
+/// Visits the bindings in the order they are written, then the body.
+pub fn walk_let_expr<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, expr: &'a LetExpr) {
+    for binding in &expr.bindings {
+        visitor.visit_ident(&binding.name);
+        visitor.visit_ast(&binding.value);
+    }
+
+    visitor.visit_ast(&expr.body);
+}
+
+pub fn walk_match_expr<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, expr: &'a MatchExpr) {
+    visitor.visit_ast(&expr.value);
+
+    for arm in &expr.arms {
+        visitor.visit_ast(&arm.pattern);
+        visitor.visit_ast(&arm.body);
+    }
+
+    visitor.visit_ast(&expr.else_arm);
+}
+
+pub fn walk_mapped_type<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, mapped: &'a MappedType) {
+    visitor.visit_ast(&mapped.iterable);
+
+    if let Some(remapped_as) = &mapped.remapped_as {
+        visitor.visit_ast(remapped_as);
+    }
+
+    visitor.visit_ast(&mapped.body);
+}
+
+pub fn walk_type_literal<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, literal: &'a TypeLiteral) {
+    for property in &literal.properties {
+        visitor.visit_object_property(property);
+    }
+}
+
+pub fn walk_tuple<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, tuple: &'a Tuple) {
+    for item in &tuple.items {
+        visitor.visit_ast(item);
+    }
+}
+
+pub fn walk_function_type<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, function: &'a FunctionType) {
+    for param in &function.params {
+        visitor.visit_ast(&param.kind);
+    }
+
+    visitor.visit_ast(&function.return_type);
+
+    if let Some(target) = function.predicate_target() {
+        visitor.visit_ast(target);
+    }
+}
+
+pub fn walk_macro_call<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, call: &'a MacroCall) {
+    for arg in &call.args {
+        visitor.visit_ast(arg);
+    }
+}
+
+/// [`Visit`] over a tree that can be changed in place. Nodes behind an [`Rc`] are cloned when
+/// they are shared, see [`Rc::make_mut`].
+///
+/// The names bound by a `let` are not visited.
+pub trait VisitMut {
+    /// Any node, dispatched by [`walk_ast_mut`] to the method of its kind.
+    fn visit_ast_mut(&mut self, node: &mut Ast) {
+        walk_ast_mut(self, node)
+    }
+
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program)
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Ast) {
+        self.visit_ast_mut(statement)
+    }
+
+    fn visit_type_alias_mut(&mut self, alias: &mut TypeAlias) {
+        walk_type_alias_mut(self, alias)
+    }
+
+    fn visit_interface_mut(&mut self, interface: &mut Interface) {
+        walk_interface_mut(self, interface)
+    }
+
+    fn visit_union_declaration_mut(&mut self, union: &mut UnionDeclaration) {
+        walk_union_declaration_mut(self, union)
+    }
+
+    fn visit_brand_declaration_mut(&mut self, brand: &mut BrandDeclaration) {
+        walk_brand_declaration_mut(self, brand)
+    }
+
+    fn visit_pattern_declaration_mut(&mut self, pattern: &mut PatternDeclaration) {
+        walk_pattern_declaration_mut(self, pattern)
+    }
+
+    fn visit_export_default_mut(&mut self, export: &mut ExportDefault) {
+        walk_export_default_mut(self, export)
+    }
+
+    fn visit_import_statement_mut(&mut self, import: &mut ImportStatement) {
+        walk_import_statement_mut(self, import)
+    }
+
+    fn visit_unit_test_mut(&mut self, test: &mut UnitTest) {
+        walk_unit_test_mut(self, test)
+    }
+
+    fn visit_ambient_module_mut(&mut self, module: &mut AmbientModule) {
+        walk_ambient_module_mut(self, module)
+    }
+
+    fn visit_type_parameter_mut(&mut self, param: &mut TypeParameter) {
+        walk_type_parameter_mut(self, param)
+    }
+
+    fn visit_object_property_mut(&mut self, property: &mut ObjectProperty) {
+        walk_object_property_mut(self, property)
+    }
+
+    fn visit_access_mut(&mut self, access: &mut Access) {
+        walk_access_mut(self, access)
+    }
+
+    fn visit_apply_generic_mut(&mut self, apply: &mut ApplyGeneric) {
+        walk_apply_generic_mut(self, apply)
+    }
+
+    fn visit_array_mut(&mut self, item: &mut Ast) {
+        self.visit_ast_mut(item)
+    }
+
+    fn visit_union_type_mut(&mut self, union: &mut UnionType) {
+        walk_union_type_mut(self, union)
+    }
+
+    fn visit_intersection_type_mut(&mut self, intersection: &mut IntersectionType) {
+        walk_intersection_type_mut(self, intersection)
+    }
+
+    fn visit_builtin_mut(&mut self, builtin: &mut Builtin) {
+        walk_builtin_mut(self, builtin)
+    }
+
+    fn visit_cond_expr_mut(&mut self, cond: &mut CondExpr) {
+        walk_cond_expr_mut(self, cond)
+    }
+
+    fn visit_extends_infix_op_mut(&mut self, op: &mut ExtendsInfixOp) {
+        walk_extends_infix_op_mut(self, op)
+    }
+
+    fn visit_extends_prefix_op_mut(&mut self, op: &mut ExtendsPrefixOp) {
+        walk_extends_prefix_op_mut(self, op)
+    }
+
+    fn visit_extends_expr_mut(&mut self, expr: &mut ExtendsExpr) {
+        walk_extends_expr_mut(self, expr)
+    }
+
+    /// An inferred type, `binding` being the name it declares.
+    fn visit_infer_mut(&mut self, binding: &mut Ast) {
+        self.visit_ast_mut(binding)
+    }
+
+    fn visit_if_expr_mut(&mut self, expr: &mut IfExpr) {
+        walk_if_expr_mut(self, expr)
+    }
+
+    fn visit_let_expr_mut(&mut self, expr: &mut LetExpr) {
+        walk_let_expr_mut(self, expr)
+    }
+
+    fn visit_match_expr_mut(&mut self, expr: &mut MatchExpr) {
+        walk_match_expr_mut(self, expr)
+    }
+
+    fn visit_mapped_type_mut(&mut self, mapped: &mut MappedType) {
+        walk_mapped_type_mut(self, mapped)
+    }
+
+    fn visit_type_literal_mut(&mut self, literal: &mut TypeLiteral) {
+        walk_type_literal_mut(self, literal)
+    }
+
+    fn visit_tuple_mut(&mut self, tuple: &mut Tuple) {
+        walk_tuple_mut(self, tuple)
+    }
+
+    fn visit_function_type_mut(&mut self, function: &mut FunctionType) {
+        walk_function_type_mut(self, function)
+    }
+
+    fn visit_macro_call_mut(&mut self, call: &mut MacroCall) {
+        walk_macro_call_mut(self, call)
+    }
+
+    /// A name, referenced or declared.
+    fn visit_ident_mut(&mut self, _ident: &mut Ident) {}
+
+    fn visit_type_number_mut(&mut self, _number: &mut TypeNumber) {}
+
+    fn visit_type_string_mut(&mut self, _string: &mut TypeString) {}
+
+    fn visit_template_string_mut(&mut self, _string: &mut TemplateString) {}
+
+    fn visit_import_type_mut(&mut self, _import: &mut ImportType) {}
+
+    fn visit_primitive_mut(&mut self, _primitive: &mut PrimitiveType) {}
+
+    /// `never`, `unknown`, `any`, `this`, `true`, `false` or a no-op, the nodes that are only a
+    /// span.
+    fn visit_keyword_mut(&mut self, _keyword: &mut Ast) {}
+}
+
+pub fn walk_ast_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut Ast) {
+    match node {
+        Ast::Access(access) => visitor.visit_access_mut(access),
+        Ast::MacroCall(call) => visitor.visit_macro_call_mut(call),
+        Ast::ApplyGeneric(apply) => visitor.visit_apply_generic_mut(apply),
+        Ast::Array(item) => visitor.visit_array_mut(Rc::make_mut(item)),
+        Ast::UnionType(union) => visitor.visit_union_type_mut(union),
+        Ast::IntersectionType(intersection) => visitor.visit_intersection_type_mut(intersection),
+        Ast::Builtin(builtin) => visitor.visit_builtin_mut(builtin),
+        Ast::CondExpr(cond) => visitor.visit_cond_expr_mut(cond),
+        Ast::ExtendsInfixOp(op) => visitor.visit_extends_infix_op_mut(op),
+        Ast::ExtendsExpr(expr) => visitor.visit_extends_expr_mut(expr),
+        Ast::Infer(binding) => visitor.visit_infer_mut(Rc::make_mut(binding)),
+        Ast::ExtendsPrefixOp(op) => visitor.visit_extends_prefix_op_mut(op),
+        Ast::Ident(ident) => visitor.visit_ident_mut(ident),
+        Ast::IfExpr(expr) => visitor.visit_if_expr_mut(expr),
+        Ast::ImportStatement(import) => visitor.visit_import_statement_mut(import),
+        Ast::ImportType(import) => visitor.visit_import_type_mut(import),
+        Ast::LetExpr(expr) => visitor.visit_let_expr_mut(expr),
+        Ast::MappedType(mapped) => visitor.visit_mapped_type_mut(mapped),
+        Ast::MatchExpr(expr) => visitor.visit_match_expr_mut(expr),
+        Ast::TypeNumber(number) => visitor.visit_type_number_mut(number),
+        Ast::TypeLiteral(literal) => visitor.visit_type_literal_mut(literal),
+        Ast::Primitive(primitive, _) => visitor.visit_primitive_mut(primitive),
+        Ast::Program(program) => visitor.visit_program_mut(program),
+        Ast::Statement(statement) => {
+            visitor.visit_statement_mut(Rc::make_mut(&mut statement.inner))
+        }
+        Ast::UnitTest(test) => visitor.visit_unit_test_mut(test),
+        Ast::AmbientModule(module) => visitor.visit_ambient_module_mut(module),
+        Ast::TypeString(string) => visitor.visit_type_string_mut(string),
+        Ast::TemplateString(string) => visitor.visit_template_string_mut(string),
+        Ast::Tuple(tuple) => visitor.visit_tuple_mut(tuple),
+        Ast::TypeAlias(alias) => visitor.visit_type_alias_mut(alias),
+        Ast::UnionDeclaration(union) => visitor.visit_union_declaration_mut(union),
+        Ast::BrandDeclaration(brand) => visitor.visit_brand_declaration_mut(brand),
+        Ast::PatternDeclaration(pattern) => visitor.visit_pattern_declaration_mut(pattern),
+        Ast::ExportDefault(export) => visitor.visit_export_default_mut(export),
+        Ast::Interface(interface) => visitor.visit_interface_mut(interface),
+        Ast::FunctionType(function) => visitor.visit_function_type_mut(function),
+        Ast::NeverKeyword(_)
+        | Ast::TrueKeyword(_)
+        | Ast::FalseKeyword(_)
+        | Ast::UnknownKeyword(_)
+        | Ast::AnyKeyword(_)
+        | Ast::ThisType(_)
+        | Ast::NoOp(_)
+        | Ast::SyntaxError(_) => visitor.visit_keyword_mut(node),
+    }
+}
+
+pub fn walk_program_mut<V: VisitMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for statement in &mut program.statements {
+        visitor.visit_ast_mut(statement);
+    }
+}
+
+pub fn walk_type_alias_mut<V: VisitMut + ?Sized>(visitor: &mut V, alias: &mut TypeAlias) {
+    visitor.visit_ident_mut(&mut alias.name);
+
+    for param in &mut alias.params {
+        visitor.visit_type_parameter_mut(param);
+    }
+
+    visitor.visit_ast_mut(Rc::make_mut(&mut alias.body));
+}
+
+pub fn walk_interface_mut<V: VisitMut + ?Sized>(visitor: &mut V, interface: &mut Interface) {
+    for param in &mut interface.params {
+        visitor.visit_type_parameter_mut(param);
+    }
+
+    for property in &mut interface.definition {
+        visitor.visit_object_property_mut(property);
+    }
+}
+
+pub fn walk_union_declaration_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    union: &mut UnionDeclaration,
+) {
+    visitor.visit_ident_mut(&mut union.name);
+
+    for variant in &mut union.variants {
+        visitor.visit_ident_mut(&mut variant.name);
+        visitor.visit_type_literal_mut(&mut variant.body);
+    }
+}
+
+pub fn walk_brand_declaration_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    brand: &mut BrandDeclaration,
+) {
+    visitor.visit_ident_mut(&mut brand.name);
+    visitor.visit_ast_mut(Rc::make_mut(&mut brand.base));
+}
+
+pub fn walk_pattern_declaration_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    pattern: &mut PatternDeclaration,
+) {
+    visitor.visit_ident_mut(&mut pattern.name);
+
+    for param in &mut pattern.params {
+        visitor.visit_ident_mut(param);
+    }
+
+    visitor.visit_ast_mut(Rc::make_mut(&mut pattern.body));
+}
+
+pub fn walk_export_default_mut<V: VisitMut + ?Sized>(visitor: &mut V, export: &mut ExportDefault) {
+    visitor.visit_ident_mut(&mut export.name);
+}
+
+pub fn walk_import_statement_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    import: &mut ImportStatement,
+) {
+    match &mut import.import_clause {
+        ImportClause::Named(specifiers) => {
+            for specifier in specifiers {
+                visitor.visit_ident_mut(&mut specifier.module_export_name);
+
+                if let Some(alias) = &mut specifier.alias {
+                    visitor.visit_ident_mut(alias);
+                }
+            }
+        }
+        ImportClause::Namespace { alias } => visitor.visit_ident_mut(alias),
+    }
+}
+
+pub fn walk_unit_test_mut<V: VisitMut + ?Sized>(visitor: &mut V, test: &mut UnitTest) {
+    for node in &mut test.body {
+        visitor.visit_ast_mut(node);
+    }
+}
+
+pub fn walk_ambient_module_mut<V: VisitMut + ?Sized>(visitor: &mut V, module: &mut AmbientModule) {
+    for statement in &mut module.body {
+        visitor.visit_ast_mut(statement);
+    }
+}
+
+pub fn walk_type_parameter_mut<V: VisitMut + ?Sized>(visitor: &mut V, param: &mut TypeParameter) {
+    visitor.visit_ident_mut(&mut param.name);
+
+    for node in param.constraint.iter_mut().chain(&mut param.default) {
+        visitor.visit_ast_mut(node);
+    }
+}
+
+pub fn walk_object_property_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    property: &mut ObjectProperty,
+) {
+    match &mut property.key {
+        ObjectPropertyKey::Index(index) => {
+            visitor.visit_ast_mut(&mut index.iterable);
+
+            if let Some(remapped_as) = &mut index.remapped_as {
+                visitor.visit_ast_mut(remapped_as);
+            }
+        }
+        ObjectPropertyKey::Key(_) => {}
+        ObjectPropertyKey::Computed(ident) => visitor.visit_ident_mut(ident),
+    }
+
+    visitor.visit_ast_mut(&mut property.value);
+}
+
+pub fn walk_access_mut<V: VisitMut + ?Sized>(visitor: &mut V, access: &mut Access) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut access.lhs));
+
+    // The names after a dot or `::` are members of the left hand side, not references.
+    if access.kind == AccessKind::Index {
+        visitor.visit_ast_mut(Rc::make_mut(&mut access.rhs));
+    }
+}
+
+pub fn walk_apply_generic_mut<V: VisitMut + ?Sized>(visitor: &mut V, apply: &mut ApplyGeneric) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut apply.receiver));
+
+    for arg in &mut apply.args {
+        visitor.visit_ast_mut(arg);
+    }
+}
+
+pub fn walk_union_type_mut<V: VisitMut + ?Sized>(visitor: &mut V, union: &mut UnionType) {
+    for ty in &mut union.types {
+        visitor.visit_ast_mut(ty);
+    }
+}
+
+pub fn walk_intersection_type_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    intersection: &mut IntersectionType,
+) {
+    for ty in &mut intersection.types {
+        visitor.visit_ast_mut(ty);
+    }
+}
+
+pub fn walk_builtin_mut<V: VisitMut + ?Sized>(visitor: &mut V, builtin: &mut Builtin) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut builtin.argument));
+}
+
+pub fn walk_cond_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, cond: &mut CondExpr) {
+    for arm in &mut cond.arms {
+        visitor.visit_ast_mut(&mut arm.condition);
+        visitor.visit_ast_mut(&mut arm.body);
+    }
+
+    if let Some(else_arm) = &mut cond.else_arm {
+        visitor.visit_ast_mut(Rc::make_mut(else_arm));
+    }
+}
+
+pub fn walk_extends_infix_op_mut<V: VisitMut + ?Sized>(visitor: &mut V, op: &mut ExtendsInfixOp) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut op.lhs));
+    visitor.visit_ast_mut(Rc::make_mut(&mut op.rhs));
+}
+
+pub fn walk_extends_prefix_op_mut<V: VisitMut + ?Sized>(visitor: &mut V, op: &mut ExtendsPrefixOp) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut op.value));
+}
+
+pub fn walk_extends_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut ExtendsExpr) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.lhs));
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.rhs));
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.then_branch));
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.else_branch));
+}
+
+pub fn walk_if_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut IfExpr) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.condition));
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.then_branch));
+
+    if let Some(else_branch) = &mut expr.else_branch {
+        visitor.visit_ast_mut(Rc::make_mut(else_branch));
+    }
+}
+
+/// Visits the values of the bindings in the order they are written, then the body.
+pub fn walk_let_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut LetExpr) {
+    for binding in &mut expr.bindings {
+        visitor.visit_ast_mut(&mut binding.value);
+    }
+
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.body));
+}
+
+pub fn walk_match_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut MatchExpr) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.value));
+
+    for arm in &mut expr.arms {
+        visitor.visit_ast_mut(&mut arm.pattern);
+        visitor.visit_ast_mut(&mut arm.body);
+    }
+
+    visitor.visit_ast_mut(Rc::make_mut(&mut expr.else_arm));
+}
+
+pub fn walk_mapped_type_mut<V: VisitMut + ?Sized>(visitor: &mut V, mapped: &mut MappedType) {
+    visitor.visit_ast_mut(Rc::make_mut(&mut mapped.iterable));
+
+    if let Some(remapped_as) = &mut mapped.remapped_as {
+        visitor.visit_ast_mut(Rc::make_mut(remapped_as));
+    }
+
+    visitor.visit_ast_mut(Rc::make_mut(&mut mapped.body));
+}
+
+pub fn walk_type_literal_mut<V: VisitMut + ?Sized>(visitor: &mut V, literal: &mut TypeLiteral) {
+    for property in &mut literal.properties {
+        visitor.visit_object_property_mut(property);
+    }
+}
+
+pub fn walk_tuple_mut<V: VisitMut + ?Sized>(visitor: &mut V, tuple: &mut Tuple) {
+    for item in &mut tuple.items {
+        visitor.visit_ast_mut(item);
+    }
+}
+
+pub fn walk_function_type_mut<V: VisitMut + ?Sized>(visitor: &mut V, function: &mut FunctionType) {
+    for param in &mut function.params {
+        visitor.visit_ast_mut(&mut param.kind);
+    }
+
+    visitor.visit_ast_mut(Rc::make_mut(&mut function.return_type));
+
+    if let Some(target) = function
+        .predicate
+        .as_mut()
+        .and_then(|predicate| predicate.target.as_mut())
+    {
+        visitor.visit_ast_mut(Rc::make_mut(target));
+    }
+}
+
+pub fn walk_macro_call_mut<V: VisitMut + ?Sized>(visitor: &mut V, call: &mut MacroCall) {
+    for arg in &mut call.args {
+        visitor.visit_ast_mut(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{test_support::parse, typescript::Pretty as _};
+    use pretty_assertions::assert_eq;
+
+    /// Number of nodes of each kind, by the name of the variant.
+    #[derive(Default)]
+    struct Counts(BTreeMap<String, usize>);
+
+    impl<'a> Visit<'a> for Counts {
+        fn visit_ast(&mut self, node: &'a Ast) {
+            let debug = format!("{node:?}");
+            let variant = debug.split(['(', ' ']).next().unwrap().to_string();
+
+            *self.0.entry(variant).or_default() += 1;
+
+            walk_ast(self, node);
+        }
+    }
+
+    #[test]
+    fn counts_nodes_per_variant() {
+        let program = parse!(
+            r#"
+            import { Box } from "./box.dl"
+            interface I(T) { a: T, b: (x: T) => [T, 1] }
+            type A(T) where T <: string as if T <: Box(?U) then {[K in keyof(U)]: U[K]} else :a | never end
+            union Shape = Circle {radius: number} | Square {size: number}
+            "#
+        );
+
+        let mut counts = Counts::default();
+        counts.visit_ast(&program);
+
+        let expected = [
+            ("Access", 1),
+            ("ApplyGeneric", 1),
+            ("Builtin", 1),
+            ("ExtendsInfixOp", 1),
+            ("FunctionType", 1),
+            ("Ident", 9),
+            ("IfExpr", 1),
+            ("ImportStatement", 1),
+            ("Infer", 1),
+            ("Interface", 1),
+            ("NeverKeyword", 1),
+            ("Primitive", 3),
+            ("Program", 1),
+            ("Statement", 4),
+            ("Tuple", 1),
+            ("TypeAlias", 1),
+            ("TypeLiteral", 1),
+            ("TypeNumber", 1),
+            ("TypeString", 1),
+            ("UnionDeclaration", 1),
+            ("UnionType", 1),
+        ];
+
+        assert_eq!(
+            counts.0,
+            expected
+                .into_iter()
+                .map(|(variant, count)| (variant.to_string(), count))
+                .collect()
+        );
+    }
+
+    struct Rename<'a>(&'a str, &'a str);
+
+    impl VisitMut for Rename<'_> {
+        fn visit_ident_mut(&mut self, ident: &mut Ident) {
+            if ident.name == self.0 {
+                ident.name = self.1.to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn renames_in_place() {
+        let mut program = parse!("type A(T) as {a: T, b: (x: T) => T[]}").simplify();
+        let original = program.clone();
+
+        Rename("T", "Item").visit_ast_mut(&mut program);
+
+        assert_eq!(
+            program.render_pretty_ts(80).trim_end(),
+            "type A<Item> = {a: Item, b: (x: Item) => Item[]};"
+        );
+        // Shared nodes are copied rather than changed.
+        assert_eq!(
+            original.render_pretty_ts(80).trim_end(),
+            "type A<T> = {a: T, b: (x: T) => T[]};"
+        );
+    }
+}
@@ -0,0 +1,457 @@
+/// Value companions of the aliases of literal objects, for `--emit-const-companions`. An alias
+/// marked `@companion` is emitted as a constant holding the object, and the type of its values:
+///
+/// ```text
+/// @companion
+/// export type Color as {red: :red, green: :green}
+/// ```
+///
+/// ```ts
+/// export const Color = {red: 'red', green: 'green'} as const;
+///
+/// export type Color = (typeof Color)[keyof typeof Color];
+/// ```
+///
+/// The object must be made of literals only: strings, numbers, booleans, `null`, `undefined`, and
+/// objects and tuples of them, which is checked whether companions are emitted or not. Without the
+/// option the alias is emitted as it is written.
+use pretty::RcDoc as D;
+
+use crate::{
+    ast::{
+        attribute::COMPANION, Ast, ObjectProperty, ObjectPropertyKey, PrimitiveType, Program, Span,
+        Statement, Tuple, TypeAlias, TypeLiteral, TypeNumber, TypeString,
+    },
+    diagnostic::{codes, Diagnostic},
+    pretty::{indent, jsdoc, string_literal, style, trailing_comma},
+    typescript::{is_identifier, is_reserved_word, Pretty},
+};
+
+/// The errors of the statements of `program` marked `@companion` that can't have one.
+pub fn check(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(statement) => statement
+                .attribute(COMPANION)
+                .map(|attribute| (statement, attribute.span)),
+            _ => None,
+        })
+        .filter_map(|(statement, span)| check_statement(statement, span).err())
+        .map(|diagnostic| diagnostic.with_code(codes::COMPANION))
+        .collect()
+}
+
+fn check_statement(statement: &Statement, span: Span) -> Result<(), Diagnostic> {
+    let Ast::TypeAlias(alias) = statement.inner.as_ref() else {
+        return Err(Diagnostic::error(
+            "`@companion` needs an alias of an object literal",
+            span,
+        ));
+    };
+
+    if !alias.params.is_empty() {
+        return Err(Diagnostic::error(
+            format!(
+                "`{}` can't have a companion, it is generic",
+                alias.name.name
+            ),
+            alias.name.span,
+        )
+        .with_note("a companion is a value, which has no type parameters"));
+    }
+
+    if alias.global || alias.default {
+        let declared = if alias.global {
+            "global"
+        } else {
+            "a default export"
+        };
+
+        return Err(Diagnostic::error(
+            format!(
+                "`{}` can't have a companion, it is {declared}",
+                alias.name.name
+            ),
+            alias.name.span,
+        ));
+    }
+
+    if !matches!(alias.body.as_ref(), Ast::TypeLiteral(_)) {
+        return Err(Diagnostic::error(
+            format!(
+                "`{}` can't have a companion, it isn't an object literal",
+                alias.name.name
+            ),
+            alias.body.as_span(),
+        ));
+    }
+
+    literal(&alias.body).map_err(|span| {
+        Diagnostic::error(
+            format!(
+                "`{}` can't have a companion, its values aren't all literals",
+                alias.name.name
+            ),
+            span,
+        )
+        .with_note(
+            "a companion holds strings, numbers, booleans, `null`, `undefined`, and objects and \
+             tuples of them",
+        )
+    })
+}
+
+/// Whether `node` has a value written the same way, or the span of the first part that hasn't.
+fn literal(node: &Ast) -> Result<(), Span> {
+    match node {
+        Ast::TypeString(_)
+        | Ast::TypeNumber(_)
+        | Ast::TrueKeyword(_)
+        | Ast::FalseKeyword(_)
+        | Ast::Primitive(PrimitiveType::Null | PrimitiveType::Undefined, _) => Ok(()),
+        Ast::TypeLiteral(TypeLiteral { properties, .. }) => {
+            properties.iter().try_for_each(|property| match property {
+                ObjectProperty {
+                    key: ObjectPropertyKey::Key(_),
+                    optional: false,
+                    value,
+                    ..
+                } => literal(value),
+                ObjectProperty { span, .. } => Err(*span),
+            })
+        }
+        Ast::Tuple(Tuple { items, .. }) => items.iter().try_for_each(literal),
+        node => Err(node.as_span()),
+    }
+}
+
+/// Drops the `@companion` attributes of `program`, whose aliases are then emitted as they are
+/// written.
+pub fn strip(program: &Ast) -> Ast {
+    let Ast::Program(Program { statements, span }) = program else {
+        return program.clone();
+    };
+
+    let statements = statements
+        .iter()
+        .map(|statement| match statement {
+            Ast::Statement(statement) => {
+                let mut statement = statement.clone();
+                statement
+                    .attributes
+                    .retain(|attribute| attribute.name.name != COMPANION);
+                Ast::Statement(statement)
+            }
+            statement => statement.clone(),
+        })
+        .collect();
+
+    Ast::Program(Program {
+        statements,
+        span: *span,
+    })
+}
+
+/// The constant and the alias of a statement marked `@companion`, without the final `;`. A
+/// statement that can't have one, which [`check`] reports, is emitted as it is.
+pub(crate) fn to_ts(statement: &Statement) -> D<()> {
+    let Ast::TypeAlias(TypeAlias {
+        doc,
+        export,
+        name,
+        body,
+        ..
+    }) = statement.inner.as_ref()
+    else {
+        return statement.inner.to_ts();
+    };
+
+    if check_statement(statement, Span::default()).is_err() {
+        return statement.inner.to_ts();
+    }
+
+    let comment = || match doc {
+        Some(doc) => jsdoc(doc),
+        None => D::nil(),
+    };
+
+    let export = || {
+        if *export {
+            D::text("export ")
+        } else {
+            D::nil()
+        }
+    };
+
+    let name = || name.to_ts();
+
+    comment()
+        .append(export())
+        .append("const ")
+        .append(name())
+        .append(" = ")
+        .append(value(body))
+        .append(" as const;")
+        .append(D::hardline())
+        .append(D::hardline())
+        .append(comment())
+        .append(export())
+        .append("type ")
+        .append(name())
+        .append(" = (typeof ")
+        .append(name())
+        .append(")[keyof typeof ")
+        .append(name())
+        .append("]")
+}
+
+/// The value of a literal type, see [`literal`].
+fn value(node: &Ast) -> D<()> {
+    match node {
+        Ast::TypeString(TypeString { ty, .. }) => string_literal(ty),
+        Ast::TypeNumber(TypeNumber { ty, .. }) => D::text(ty.clone()),
+        Ast::TypeLiteral(TypeLiteral { properties, .. }) => object(properties),
+        Ast::Tuple(Tuple { items, .. }) => D::text("[")
+            .append(
+                D::line_()
+                    .append(D::intersperse(
+                        items.iter().map(value),
+                        D::text(",").append(D::line()),
+                    ))
+                    .append(trailing_comma())
+                    .nest(indent()),
+            )
+            .append(D::line_())
+            .append("]")
+            .group(),
+        node => node.to_ts(),
+    }
+}
+
+fn object(properties: &[ObjectProperty]) -> D<()> {
+    if properties.is_empty() {
+        return D::text("{}");
+    }
+
+    let members = properties.iter().map(|property| {
+        let key = match &property.key {
+            ObjectPropertyKey::Key(key) if is_identifier(key) && !is_reserved_word(key) => {
+                D::text(key.clone())
+            }
+            ObjectPropertyKey::Key(key) => string_literal(key),
+            _ => unreachable!("checked by `literal`"),
+        };
+
+        key.append(": ").append(value(&property.value))
+    });
+
+    let line = if style().bracket_spacing {
+        D::line()
+    } else {
+        D::line_()
+    };
+
+    D::text("{")
+        .append(
+            line.clone()
+                .append(D::intersperse(members, D::text(",").append(D::line())))
+                .append(trailing_comma())
+                .nest(indent())
+                .append(line),
+        )
+        .append("}")
+        .group()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, compile_streaming, CompileOptions, OutputKind};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn companions() -> CompileOptions {
+        CompileOptions {
+            const_companions: true,
+            ..Default::default()
+        }
+    }
+
+    fn errors(source: &str) -> Vec<(String, String)> {
+        compile(source, &companions())
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message,
+                    source[diagnostic.span.start..diagnostic.span.end].to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn string_literal_map() {
+        let source = dedent!(
+            r#"
+            /** The colors of a theme. */
+            @companion
+            export type Color as {red: :red, green: :green, "dark-blue": "dark blue"}
+
+            type Palette as {primary: Color}
+            "#
+        );
+
+        assert_eq!(
+            compile(source, &companions()).unwrap().typescript,
+            dedent!(
+                "
+                /** The colors of a theme. */
+                export const Color = {red: 'red', green: 'green', 'dark-blue': 'dark blue'} as const;
+
+                /** The colors of a theme. */
+                export type Color = (typeof Color)[keyof typeof Color];
+
+                type Palette = {primary: Color};
+
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn nested_literals() {
+        let source = dedent!(
+            r#"
+            @companion
+            type Limits as {
+                retries: 3,
+                verbose: false,
+                fallback: null,
+                sizes: [1, 2, -4],
+                http: {timeout: 1.5, methods: [:get, :post]},
+            }
+            "#
+        );
+        let options = CompileOptions {
+            width: 40,
+            ..companions()
+        };
+
+        assert_eq!(
+            compile(source, &options).unwrap().typescript,
+            dedent!(
+                "
+                const Limits = {
+                    retries: 3,
+                    verbose: false,
+                    fallback: null,
+                    sizes: [1, 2, -4],
+                    http: {
+                        timeout: 1.5,
+                        methods: ['get', 'post'],
+                    },
+                } as const;
+
+                type Limits = (typeof Limits)[keyof typeof Limits];
+
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn emitted_as_written_without_the_option() {
+        let source = "@companion\nexport type Color as {red: :red}";
+
+        assert_eq!(
+            compile(source, &CompileOptions::default())
+                .unwrap()
+                .typescript,
+            "export type Color = {red: 'red'};\n\n"
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let source = "@companion\nexport type Color as {red: :red}\ntype A as Color";
+        let mut typescript = String::new();
+
+        compile_streaming(source, &companions(), |ts| typescript.push_str(ts)).unwrap();
+
+        assert_eq!(
+            typescript,
+            compile(source, &companions()).unwrap().typescript
+        );
+    }
+
+    #[test]
+    fn non_literals() {
+        assert_eq!(
+            errors("@companion\ntype Color as {red: :red, other: string}"),
+            vec![(
+                "`Color` can't have a companion, its values aren't all literals".to_string(),
+                "string".to_string()
+            )]
+        );
+        assert_eq!(
+            errors("@companion\ntype Color as {red: :red, ?other: :other}"),
+            vec![(
+                "`Color` can't have a companion, its values aren't all literals".to_string(),
+                "?other: :other".to_string()
+            )]
+        );
+        assert_eq!(
+            errors("@companion\ntype Color as :red | :green"),
+            vec![(
+                "`Color` can't have a companion, it isn't an object literal".to_string(),
+                ":red | :green".to_string()
+            )]
+        );
+        assert_eq!(
+            errors("@companion\ntype Color(T) as {red: T}"),
+            vec![(
+                "`Color` can't have a companion, it is generic".to_string(),
+                "Color".to_string()
+            )]
+        );
+        assert_eq!(
+            errors("@companion\ninterface Color {red: :red}"),
+            vec![(
+                "`@companion` needs an alias of an object literal".to_string(),
+                "@companion".to_string()
+            )]
+        );
+
+        // Whether companions are emitted or not.
+        assert!(compile(
+            "@companion\ntype Color as {red: string}",
+            &CompileOptions::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn declaration_files_have_no_companions() {
+        let options = CompileOptions {
+            output_kind: OutputKind::Dts,
+            ..companions()
+        };
+
+        assert_eq!(
+            compile("@companion\ntype Color as {red: :red}", &options)
+                .unwrap_err()
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect::<Vec<_>>(),
+            vec!["const companions can't be emitted to a declaration file"]
+        );
+    }
+}
@@ -0,0 +1,1364 @@
+/// The compiler pipeline: source text in, TypeScript out.
+use std::{collections::BTreeMap, rc::Rc};
+
+use crate::{
+    ast::{
+        brand_declaration::BrandEncoding,
+        if_expr::{self, MissingElse},
+        pattern_declaration::Patterns,
+        Ast, ExportDefault, Ident, Interface, Program, TypeAlias,
+    },
+    companion, conditions, config,
+    diagnostic::{Diagnostic, Severity},
+    error::humanize_pest,
+    features::{self, FeatureSet},
+    flags, hoist,
+    lint::{self, Level},
+    names, parser,
+    pretty::{self, Quote, Style},
+    runtime::{self, MacroRegistry},
+    source::{malformed_pragmas, SourceFile},
+    strict,
+    target::{self, TsVersion},
+    timings::{pass_scope, PassTimings},
+    typescript::Pretty,
+    zod,
+};
+use size::AliasSize;
+
+pub use hoist::MIN_THRESHOLD as MIN_HOIST_THRESHOLD;
+
+pub mod incremental;
+pub mod project;
+pub mod size;
+pub mod tags;
+
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// Maximum line width of the emitted TypeScript.
+    pub width: usize,
+    /// Record the duration and output size of every pass.
+    pub timings: bool,
+    /// Kind of file the TypeScript is written for.
+    pub output_kind: OutputKind,
+    /// Level of each lint, by name. Lints not listed are reported as warnings.
+    pub lints: BTreeMap<String, Level>,
+    /// Merge adjacent arms of conditional types that have the same body, see [`Ast::merge_arms`].
+    pub merge_arms: bool,
+    /// Write the conditional types filtering a type parameter as `Extract` and `Exclude`, see
+    /// [`Ast::extract_exclude`].
+    pub extract_exclude: bool,
+    /// Hoist the subexpressions repeated at least this many times in an alias, see
+    /// [`hoist::hoist_common`]. At least [`MIN_HOIST_THRESHOLD`].
+    pub hoist: Option<usize>,
+    /// Macros the program can call, the builtin ones by default.
+    pub macros: Rc<MacroRegistry>,
+    /// How `brand` declarations tag their base type.
+    pub brand_encoding: BrandEncoding,
+    /// Extension relative imports of `.dl` modules are rewritten to, when the module is compiled
+    /// as part of a project, see [`project::rewrite_imports`].
+    pub import_extension: Option<&'static str>,
+    /// Emit only the declarations for this `@emit` tag and those they use, see
+    /// [`tags::only_tag`].
+    pub only_tag: Option<String>,
+    /// Unstable features enabled in addition to those the source enables, see [`features`].
+    pub features: FeatureSet,
+    /// Flags set, which the `#if` blocks of the source test, see [`flags`].
+    pub flags: Vec<String>,
+    /// Version of TypeScript the output is written for, see [`target`].
+    pub target: TsVersion,
+    /// Report the warnings of lints as errors, and reject what the compiler can't lower yet or
+    /// leaves to a wildcard, see [`strict`].
+    pub strict: bool,
+    /// Lay the TypeScript out as Prettier does by default, see [`Style::PRETTIER`].
+    pub prettier_compat: bool,
+    /// Quote of string literals, by default double with `prettier_compat` and single otherwise.
+    pub quote: Option<Quote>,
+    /// Emit the aliases marked `@companion` with a constant holding their value, see
+    /// [`companion`].
+    pub const_companions: bool,
+    /// What an `if` or `cond` without `else` falls back to when no condition holds, see
+    /// [`MissingElse`].
+    pub missing_else: MissingElse,
+    /// Lower the conditions outside an `if` or a `cond` to the `true` or `false` they test rather
+    /// than reject them, see [`conditions`].
+    pub lower_conditions: bool,
+    /// Reject the exported aliases emitted as more bytes than this, see [`size`].
+    pub max_emit_bytes_per_alias: Option<usize>,
+    /// Record the size of the TypeScript of every exported alias.
+    pub measure_sizes: bool,
+    /// Most expressions an expression can be nested in, a deeper one is a syntax error, see
+    /// [`parser::DEFAULT_MAX_NESTING`].
+    pub max_nesting: usize,
+}
+
+/// Kind of file the TypeScript is written for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputKind {
+    /// A module.
+    #[default]
+    Ts,
+    /// An ambient declaration file.
+    Dts,
+    /// A module, and a file of Zod schemas mirroring its declarations, written next to it.
+    /// Experimental.
+    Zod,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            width: 120,
+            timings: false,
+            output_kind: OutputKind::default(),
+            lints: BTreeMap::new(),
+            merge_arms: false,
+            extract_exclude: false,
+            hoist: None,
+            macros: Rc::new(MacroRegistry::default()),
+            brand_encoding: BrandEncoding::default(),
+            import_extension: None,
+            only_tag: None,
+            features: FeatureSet::default(),
+            flags: vec![],
+            target: TsVersion::default(),
+            strict: false,
+            prettier_compat: false,
+            quote: None,
+            const_companions: false,
+            missing_else: MissingElse::default(),
+            lower_conditions: false,
+            max_emit_bytes_per_alias: None,
+            measure_sizes: false,
+            max_nesting: parser::DEFAULT_MAX_NESTING,
+        }
+    }
+}
+
+impl CompileOptions {
+    /// The options a module is compiled with: those of `self`, with the ones the `#options` pragma
+    /// of its `source` overrides, see [`config::module_options`]. Returns the problems of the
+    /// pragma as well.
+    pub fn for_module(&self, source: &str) -> (Self, Vec<Diagnostic>) {
+        let (overrides, diagnostics) = config::module_options(source);
+
+        let mut lints = self.lints.clone();
+        lints.extend(overrides.lints);
+
+        let options = Self {
+            strict: overrides.strict.unwrap_or(self.strict),
+            target: overrides.target.unwrap_or(self.target),
+            lints,
+            ..self.clone()
+        };
+
+        (options, diagnostics)
+    }
+
+    /// Layout of the emitted TypeScript.
+    pub fn style(&self) -> Style {
+        let style = if self.prettier_compat {
+            Style::PRETTIER
+        } else {
+            Style::DEFAULT
+        };
+
+        Style {
+            quote: self.quote.unwrap_or(style.quote),
+            ..style
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompileOutput {
+    pub typescript: String,
+    /// Names of the types the module exports, at their declaration.
+    pub exports: Vec<Ident>,
+    /// The schema file, when the output kind is [`OutputKind::Zod`].
+    pub zod: Option<String>,
+    /// Warnings reported while compiling.
+    pub diagnostics: Vec<Diagnostic>,
+    pub timings: PassTimings,
+    /// Sizes of the exported aliases, the largest first, when they are measured.
+    pub sizes: Vec<AliasSize>,
+}
+
+/// Compiles `source`, failing with every diagnostic reported if any of them is an error.
+pub fn compile(source: &str, options: &CompileOptions) -> Result<CompileOutput, Vec<Diagnostic>> {
+    compile_measuring(source, options, &mut vec![])
+}
+
+/// [`compile`], with the sizes of the exported aliases when they are measured, even if an alias
+/// larger than [`CompileOptions::max_emit_bytes_per_alias`] fails the compilation.
+pub fn compile_measured(
+    source: &str,
+    options: &CompileOptions,
+) -> (Result<CompileOutput, Vec<Diagnostic>>, Vec<AliasSize>) {
+    let mut sizes = vec![];
+    let result = compile_measuring(source, options, &mut sizes);
+
+    (result, sizes)
+}
+
+/// [`compile`], recording the sizes of the aliases in `measured` once they are measured.
+fn compile_measuring(
+    source: &str,
+    options: &CompileOptions,
+    measured: &mut Vec<AliasSize>,
+) -> Result<CompileOutput, Vec<Diagnostic>> {
+    let mut timings = PassTimings::new(options.timings);
+
+    if options.const_companions && options.output_kind == OutputKind::Dts {
+        return Err(vec![companions_in_declaration_file()]);
+    }
+
+    // The checks still run on a program with syntax errors recovered from, to report their
+    // diagnostics as well.
+    let (program, syntax_errors) = pass_scope!(
+        "parse",
+        timings,
+        parser::with_max_nesting(options.max_nesting, || {
+            parser::parse_program_with_errors(source)
+        })
+    )
+    .map_err(|err| vec![humanize_pest(*err, source)])?;
+
+    let (program, flag_notes) =
+        pass_scope!("flags", timings, flags::prune(&program, &options.flags));
+
+    let file = SourceFile::new("", source);
+    let (options, mut diagnostics) = options.for_module(source);
+    diagnostics.extend(flag_notes);
+    let options = &options;
+    let (features, feature_diagnostics) = options.features.with_pragmas(source);
+    diagnostics.extend(feature_diagnostics);
+    diagnostics.extend(malformed_pragmas(source));
+    diagnostics.extend(syntax_errors);
+
+    diagnostics.extend(pass_scope!(
+        "features",
+        timings,
+        features::check(&program, &features)
+    ));
+
+    diagnostics.extend(pass_scope!(
+        "target",
+        timings,
+        target::check(&program, options.target)
+    ));
+
+    diagnostics.extend(pass_scope!(
+        "fallbacks",
+        timings,
+        if_expr::check_fallbacks(&program, options.missing_else)
+    ));
+
+    if options.strict {
+        diagnostics.extend(pass_scope!("strict", timings, strict::check(&program)));
+    }
+
+    // The checks see the plain patterns the synonyms stand for.
+    let (program, expansion_diagnostics) = pass_scope!(
+        "expand patterns",
+        timings,
+        Patterns::default().expand(&program)
+    );
+    diagnostics.extend(expansion_diagnostics);
+
+    let found = checks(
+        lint::STATEMENT_CHECKS.iter().chain(lint::MODULE_CHECKS),
+        &program,
+        &mut timings,
+    );
+    diagnostics.extend(pass_scope!(
+        "lint levels",
+        timings,
+        lints(found, &program, &file, options)
+    ));
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return Err(diagnostics);
+    }
+
+    let (simplified, expansion_diagnostics) = pass_scope!(
+        "simplify",
+        timings,
+        program.simplify_with_helpers(
+            &runtime::Helpers::new(&program, &options.macros)
+                .with_brands(options.brand_encoding)
+                .with_missing_else(options.missing_else)
+        )
+    )
+    .map_err(|errors| {
+        diagnostics
+            .iter()
+            .cloned()
+            .chain(errors)
+            .collect::<Vec<_>>()
+    })?;
+
+    diagnostics.extend(expansion_diagnostics);
+
+    let simplified = if options.lower_conditions {
+        pass_scope!("conditions", timings, conditions::lower(&simplified))
+    } else {
+        let errors = pass_scope!("conditions", timings, conditions::check(&simplified));
+
+        if !errors.is_empty() {
+            return Err(diagnostics.into_iter().chain(errors).collect());
+        }
+
+        simplified
+    };
+
+    let simplified = pass_scope!("cleanup", timings, simplified.remove_noops());
+
+    diagnostics.extend(pass_scope!(
+        "check simplified",
+        timings,
+        lints(
+            lint::check_simplified(&simplified),
+            &program,
+            &file,
+            options
+        )
+    ));
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return Err(diagnostics);
+    }
+
+    let simplified = match &options.only_tag {
+        Some(tag) => pass_scope!("only tag", timings, tags::only_tag(&simplified, tag)).map_err(
+            |errors| {
+                diagnostics
+                    .iter()
+                    .cloned()
+                    .chain(errors)
+                    .collect::<Vec<_>>()
+            },
+        )?,
+        None => simplified,
+    };
+
+    let simplified = if options.merge_arms {
+        pass_scope!("merge arms", timings, simplified.merge_arms())
+    } else {
+        simplified
+    };
+
+    let simplified = if options.extract_exclude {
+        pass_scope!("extract exclude", timings, simplified.extract_exclude())
+    } else {
+        simplified
+    };
+
+    let simplified = match options.hoist {
+        Some(threshold) => pass_scope!(
+            "hoist",
+            timings,
+            hoist::hoist_common(&simplified, threshold)
+        ),
+        None => simplified,
+    };
+
+    let simplified = match options.import_extension {
+        Some(extension) => pass_scope!(
+            "rewrite imports",
+            timings,
+            project::rewrite_imports(&simplified, extension)
+        ),
+        None => simplified,
+    };
+
+    let simplified = if options.const_companions {
+        simplified
+    } else {
+        pass_scope!("strip companions", timings, companion::strip(&simplified))
+    };
+
+    let simplified = pass_scope!(
+        "rename keys",
+        timings,
+        names::rename_reserved_keys(&simplified)
+    );
+
+    let typescript = pass_scope!(
+        "emit",
+        timings,
+        pretty::with_style(options.style(), || emit(
+            &simplified,
+            options.output_kind,
+            options.width
+        ))
+    );
+
+    // Measured on their own, the aliases are laid out as they are in the file.
+    let sizes = if options.measure_sizes || options.max_emit_bytes_per_alias.is_some() {
+        pass_scope!(
+            "measure",
+            timings,
+            pretty::with_style(options.style(), || size::measure(
+                &simplified,
+                options.width
+            ))
+        )
+    } else {
+        vec![]
+    };
+    measured.clone_from(&sizes);
+
+    if let Some(limit) = options.max_emit_bytes_per_alias {
+        let errors = size::check(&sizes, limit);
+
+        if !errors.is_empty() {
+            return Err(diagnostics.into_iter().chain(errors).collect());
+        }
+    }
+
+    let zod = match options.output_kind {
+        OutputKind::Zod => {
+            let (zod, zod_diagnostics) = pass_scope!(
+                "emit zod",
+                timings,
+                pretty::with_style(options.style(), || {
+                    zod::emit(&simplified, options.width)
+                })
+            );
+            diagnostics.extend(zod_diagnostics);
+            Some(zod)
+        }
+        _ => None,
+    };
+
+    Ok(CompileOutput {
+        typescript,
+        exports: exports(&simplified),
+        zod,
+        diagnostics,
+        timings,
+        sizes,
+    })
+}
+
+/// A declaration file can't hold the value of a companion, only its type.
+fn companions_in_declaration_file() -> Diagnostic {
+    Diagnostic::error(
+        "const companions can't be emitted to a declaration file",
+        Default::default(),
+    )
+    .with_note("emit `ts`, or compile without `--emit-const-companions`")
+}
+
+/// Names of the aliases and interfaces `program` exports, `default` for its default export.
+fn exports(program: &Ast) -> Vec<Ident> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(TypeAlias {
+                    default: true,
+                    name,
+                    ..
+                })
+                | Ast::ExportDefault(ExportDefault { name, .. }) => Some(Ident {
+                    name: "default".to_string(),
+                    span: name.span,
+                    raw: false,
+                }),
+                Ast::Interface(Interface {
+                    default: true,
+                    span,
+                    ..
+                }) => Some(Ident {
+                    name: "default".to_string(),
+                    span: *span,
+                    raw: false,
+                }),
+                Ast::TypeAlias(TypeAlias {
+                    export: true, name, ..
+                }) => Some(name.clone()),
+                Ast::Interface(Interface {
+                    export: true,
+                    name,
+                    span,
+                    ..
+                }) => Some(Ident {
+                    name: name.clone(),
+                    span: *span,
+                    raw: false,
+                }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Renders a simplified program as the contents of a file of the given kind.
+///
+/// Statements marked `global` are gathered in a `declare global` block, which is only allowed in
+/// a module. A declaration file ends with `export {}`: without any import or export it would
+/// declare its types globally, and with some but no export list every declaration is exported.
+pub fn emit(program: &Ast, kind: OutputKind, width: usize) -> String {
+    let mut typescript = program.render_pretty_ts(width);
+
+    let statements = match program {
+        Ast::Program(Program { statements, .. }) => statements.as_slice(),
+        _ => &[],
+    };
+
+    let is_module = statements.iter().any(makes_module);
+    let has_globals = statements.iter().any(Ast::is_global);
+
+    if kind == OutputKind::Dts || (has_globals && !is_module) {
+        typescript.push_str("export {};\n");
+    }
+
+    typescript
+}
+
+/// Whether `statement` makes the file it is emitted to a module, an import or an export.
+fn makes_module(statement: &Ast) -> bool {
+    match statement {
+        Ast::Statement(inner) => match &**inner {
+            Ast::ImportStatement(_) => true,
+            Ast::TypeAlias(TypeAlias {
+                export, default, ..
+            })
+            | Ast::Interface(Interface {
+                export, default, ..
+            }) => *export || *default,
+            Ast::ExportDefault(_) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Runs each of `checks` over `program` as a pass of its own.
+fn checks<'a>(
+    checks: impl IntoIterator<Item = &'a (&'static str, lint::Check)>,
+    program: &Ast,
+    timings: &mut PassTimings,
+) -> Vec<Diagnostic> {
+    checks
+        .into_iter()
+        .flat_map(|(name, check)| pass_scope!(name, timings, check(program)))
+        .collect()
+}
+
+/// The diagnostics of the lints, once their pragmas and levels are applied.
+fn lints(
+    diagnostics: Vec<Diagnostic>,
+    program: &Ast,
+    file: &SourceFile,
+    options: &CompileOptions,
+) -> Vec<Diagnostic> {
+    let diagnostics = lint::apply_levels(
+        lint::apply_pragmas(diagnostics, file, program),
+        &options.lints,
+    );
+
+    if options.strict {
+        strict::deny_warnings(diagnostics, &options.lints)
+    } else {
+        diagnostics
+    }
+}
+
+/// Compiles `source` one statement at a time, passing the TypeScript of each statement to
+/// `write` as soon as it is emitted, so that the program is never held as a whole. Returns the
+/// warnings reported, or every diagnostic reported up to the first error.
+///
+/// Each statement is checked on its own, see [`lint::STATEMENT_CHECKS`]. The helpers of macros
+/// are declared after the first statement using them, and global statements are gathered in a
+/// `declare global` block at the end, as in [`compile`].
+pub fn compile_streaming(
+    source: &str,
+    options: &CompileOptions,
+    write: impl FnMut(&str),
+) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    compile_streaming_timed(source, options, write).0
+}
+
+/// [`compile_streaming`], with the durations of its passes, each summed over the statements, if
+/// [`CompileOptions::timings`] is set.
+pub fn compile_streaming_timed(
+    source: &str,
+    options: &CompileOptions,
+    write: impl FnMut(&str),
+) -> (Result<Vec<Diagnostic>, Vec<Diagnostic>>, PassTimings) {
+    let mut timings = PassTimings::new(options.timings);
+    let result = stream(source, options, write, &mut timings);
+
+    (result, timings)
+}
+
+/// [`compile_streaming`], recording the durations of the passes in `timings`.
+fn stream(
+    source: &str,
+    options: &CompileOptions,
+    mut write: impl FnMut(&str),
+    timings: &mut PassTimings,
+) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    // The schemas are ordered by their dependencies, which aren't known before the end.
+    if options.output_kind == OutputKind::Zod {
+        return Err(vec![Diagnostic::error(
+            "Zod schemas can't be emitted while streaming",
+            Default::default(),
+        )
+        .with_note("compile without `--streaming`, or emit `ts`")]);
+    }
+
+    if options.const_companions && options.output_kind == OutputKind::Dts {
+        return Err(vec![companions_in_declaration_file()]);
+    }
+
+    let empty = Ast::Program(Program {
+        statements: vec![],
+        span: Default::default(),
+    });
+    let helpers = runtime::Helpers::new(&empty, &options.macros)
+        .with_brands(options.brand_encoding)
+        .with_missing_else(options.missing_else);
+
+    // Helpers are named before the statements declaring the names they could take are parsed,
+    // hence the names are reserved from the text.
+    helpers.reserve(
+        source
+            .split(|c: char| c != '$' && !unicode_ident::is_xid_continue(c))
+            .filter(|word| word.starts_with(names::GENERATED_PREFIX)),
+    );
+
+    let file = SourceFile::new("", source);
+    let (options, mut diagnostics) = options.for_module(source);
+    let options = &options;
+    let (features, feature_diagnostics) = options.features.with_pragmas(source);
+    diagnostics.extend(feature_diagnostics);
+    diagnostics.extend(malformed_pragmas(source));
+    let mut patterns = Patterns::default();
+    let mut globals = vec![];
+    let mut is_module = false;
+
+    let mut statements = parser::statements(source, options.max_nesting);
+
+    while let Some(statement) = pass_scope!("parse", timings, statements.next()) {
+        let statement = statement.map_err(|diagnostic| {
+            diagnostics.push(diagnostic);
+            diagnostics.clone()
+        })?;
+
+        let program = Ast::Program(Program {
+            span: statement.as_span(),
+            statements: vec![statement],
+        });
+
+        let (program, flag_notes) =
+            pass_scope!("flags", timings, flags::prune(&program, &options.flags));
+        diagnostics.extend(flag_notes);
+
+        let Ast::Program(Program { statements, .. }) = &program else {
+            unreachable!("pruning keeps the program");
+        };
+
+        if statements.is_empty() {
+            continue;
+        }
+
+        diagnostics.extend(pass_scope!(
+            "features",
+            timings,
+            features::check(&program, &features)
+        ));
+        diagnostics.extend(pass_scope!(
+            "target",
+            timings,
+            target::check(&program, options.target)
+        ));
+        diagnostics.extend(pass_scope!(
+            "fallbacks",
+            timings,
+            if_expr::check_fallbacks(&program, options.missing_else)
+        ));
+
+        let (program, expansion_diagnostics) =
+            pass_scope!("expand patterns", timings, patterns.expand(&program));
+        diagnostics.extend(expansion_diagnostics);
+
+        let found = checks(lint::STATEMENT_CHECKS, &program, timings);
+        diagnostics.extend(pass_scope!(
+            "lint levels",
+            timings,
+            lints(found, &program, &file, options)
+        ));
+
+        // What strict mode rejects would make the simplifier fail, hence it stops before it.
+        if options.strict {
+            diagnostics.extend(pass_scope!("strict", timings, strict::check(&program)));
+
+            if diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.severity == Severity::Error)
+            {
+                return Err(diagnostics);
+            }
+        }
+
+        let simplified = pass_scope!("simplify", timings, program.simplify_with(&helpers));
+        diagnostics.extend(helpers.take_diagnostics());
+
+        let simplified = if options.lower_conditions {
+            pass_scope!("conditions", timings, conditions::lower(&simplified))
+        } else {
+            diagnostics.extend(pass_scope!(
+                "conditions",
+                timings,
+                conditions::check(&simplified)
+            ));
+            simplified
+        };
+
+        diagnostics.extend(pass_scope!(
+            "check simplified",
+            timings,
+            lints(
+                lint::check_simplified(&simplified),
+                &program,
+                &file,
+                options
+            )
+        ));
+
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            return Err(diagnostics);
+        }
+
+        let mut simplified = pass_scope!("cleanup", timings, simplified.remove_noops());
+
+        if options.merge_arms {
+            simplified = pass_scope!("merge arms", timings, simplified.merge_arms());
+        }
+
+        if options.extract_exclude {
+            simplified = pass_scope!("extract exclude", timings, simplified.extract_exclude());
+        }
+
+        if let Some(threshold) = options.hoist {
+            simplified = pass_scope!(
+                "hoist",
+                timings,
+                hoist::hoist_common(&simplified, threshold)
+            );
+        }
+
+        if let Some(extension) = options.import_extension {
+            simplified = pass_scope!(
+                "rewrite imports",
+                timings,
+                project::rewrite_imports(&simplified, extension)
+            );
+        }
+
+        if !options.const_companions {
+            simplified = pass_scope!("strip companions", timings, companion::strip(&simplified));
+        }
+
+        simplified = pass_scope!(
+            "rename keys",
+            timings,
+            names::rename_reserved_keys(&simplified)
+        );
+
+        let Ast::Program(Program { statements, span }) = simplified else {
+            unreachable!()
+        };
+
+        is_module |= statements.iter().any(makes_module);
+
+        let (global, statements): (Vec<_>, Vec<_>) =
+            statements.into_iter().partition(Ast::is_global);
+        globals.extend(global);
+
+        write(&pass_scope!(
+            "emit",
+            timings,
+            pretty::with_style(options.style(), || {
+                Ast::Program(Program { statements, span }).render_pretty_ts(options.width)
+            })
+        ));
+    }
+
+    let has_globals = !globals.is_empty();
+
+    write(&pass_scope!(
+        "emit",
+        timings,
+        pretty::with_style(options.style(), || {
+            Ast::Program(Program {
+                statements: globals,
+                span: Default::default(),
+            })
+            .render_pretty_ts(options.width)
+        })
+    ));
+
+    if options.output_kind == OutputKind::Dts || (has_globals && !is_module) {
+        write("export {};\n");
+    }
+
+    Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use super::*;
+    use crate::test_support::{parse, tsc};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use textwrap_macros::dedent;
+
+    // The expected files are laid out as Prettier lays out TypeScript, `;` after the members of
+    // object types included, so that formatting the output with Prettier leaves it unchanged.
+    // They are written by hand: check them with `prettier --parser typescript` when the layout
+    // changes.
+    #[rstest]
+    #[case::objects(
+        include_str!("../tests/prettier/objects.dl"),
+        include_str!("../tests/prettier/objects.ts")
+    )]
+    #[case::interfaces(
+        include_str!("../tests/prettier/interfaces.dl"),
+        include_str!("../tests/prettier/interfaces.ts")
+    )]
+    #[case::unions(
+        include_str!("../tests/prettier/unions.dl"),
+        include_str!("../tests/prettier/unions.ts")
+    )]
+    fn prettier_compat(#[case] source: &str, #[case] expected: &str) {
+        let options = CompileOptions {
+            prettier_compat: true,
+            width: 80,
+            ..Default::default()
+        };
+
+        assert_eq!(compile(source, &options).unwrap().typescript, expected);
+    }
+
+    #[test]
+    fn quote_is_configurable() {
+        let source = "type A as {it: \"it's\", \"a-b\": :c}";
+        let typescript = |prettier_compat, quote| {
+            let options = CompileOptions {
+                prettier_compat,
+                quote,
+                ..Default::default()
+            };
+
+            compile(source, &options).unwrap().typescript
+        };
+
+        assert_eq!(
+            typescript(false, None),
+            "type A = {it: 'it\\'s', 'a-b': 'c'};\n\n"
+        );
+        assert_eq!(
+            typescript(true, None),
+            "type A = { it: \"it's\"; \"a-b\": \"c\" };\n\n"
+        );
+        assert_eq!(
+            typescript(true, Some(Quote::Single)),
+            "type A = { it: 'it\\'s'; 'a-b': 'c' };\n\n"
+        );
+        assert_eq!(
+            typescript(false, Some(Quote::Double)),
+            "type A = {it: \"it's\", \"a-b\": \"c\"};\n\n"
+        );
+    }
+
+    #[test]
+    fn timings_record_every_pass() {
+        let options = CompileOptions {
+            timings: true,
+            ..Default::default()
+        };
+
+        let output = compile("type A as if 1 <: number then 1 else 0 end", &options).unwrap();
+
+        let names: Vec<_> = output.timings.iter().map(|pass| pass.name).collect();
+        let checks = lint::STATEMENT_CHECKS
+            .iter()
+            .chain(lint::MODULE_CHECKS)
+            .map(|(name, _)| *name);
+
+        assert_eq!(
+            names,
+            [
+                "parse",
+                "flags",
+                "features",
+                "target",
+                "fallbacks",
+                "expand patterns",
+            ]
+            .into_iter()
+            .chain(checks)
+            .chain([
+                "lint levels",
+                "simplify",
+                "conditions",
+                "cleanup",
+                "check simplified",
+                "strip companions",
+                "rename keys",
+                "emit"
+            ])
+            .collect::<Vec<_>>()
+        );
+
+        for pass in output.timings.iter() {
+            assert!(pass.duration >= Duration::ZERO);
+        }
+
+        let nodes: Vec<_> = output.timings.iter().map(|pass| pass.nodes).collect();
+
+        let (before, rest) = nodes.split_at(6);
+        let (checks, after) = rest.split_at(rest.len() - 7);
+
+        assert!(matches!(
+            before,
+            [Some(_), Some(_), None, None, None, Some(_)]
+        ));
+        assert!(checks.iter().all(Option::is_none));
+        assert!(matches!(
+            after,
+            [Some(_), None, Some(_), None, Some(_), Some(_), None]
+        ));
+    }
+
+    #[test]
+    fn timings_while_streaming() {
+        let options = CompileOptions {
+            timings: true,
+            ..Default::default()
+        };
+
+        let (result, timings) = compile_streaming_timed(
+            "type A as if 1 <: number then 1 else 0 end\ntype B as {a: A}",
+            &options,
+            |_| {},
+        );
+        result.unwrap();
+
+        let names: Vec<_> = timings.iter().map(|pass| pass.name).collect();
+
+        assert_eq!(names.iter().collect::<HashSet<_>>().len(), names.len());
+        assert!(names.starts_with(&["parse", "flags", "features"]));
+        assert!(names.contains(&"confusable names"));
+        assert!(names.ends_with(&["strip companions", "rename keys", "emit"]));
+
+        let (_, timings) = compile_streaming_timed("type A as 1", &Default::default(), |_| {});
+
+        assert_eq!(timings.iter().count(), 0);
+    }
+
+    #[test]
+    fn errors_fail_compilation() {
+        let diagnostics = compile("type new as 1", &CompileOptions::default()).unwrap_err();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn warnings_are_returned_with_the_output() {
+        let output = compile("type declare as 1", &CompileOptions::default()).unwrap();
+
+        assert_eq!(output.typescript.trim(), "type declare = 1;");
+        assert_eq!(output.diagnostics.len(), 1);
+        assert_eq!(output.diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn malformed_source_pragmas_are_reported() {
+        let output = compile(
+            "#source templates/a.dl line 1\ntype A as 1",
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            output
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["malformed `#source` pragma"]
+        );
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let source = "type A as [[[1]]]";
+        let options = |max_nesting| CompileOptions {
+            max_nesting,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile(source, &options(2))
+                .unwrap_err()
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect::<Vec<_>>(),
+            vec!["expression nesting exceeds limit of 2"]
+        );
+        assert_eq!(
+            compile(source, &options(3)).unwrap().typescript,
+            "type A = [[[1]]];\n\n"
+        );
+    }
+
+    #[test]
+    fn timings_disabled() {
+        let output = compile("type A as 1", &CompileOptions::default()).unwrap();
+
+        assert_eq!(output.timings.iter().count(), 0);
+    }
+
+    fn emit_as(kind: OutputKind, source: &str) -> String {
+        let options = CompileOptions {
+            output_kind: kind,
+            ..Default::default()
+        };
+
+        compile(source, &options).unwrap().typescript
+    }
+
+    const MODULE: &str = r#"
+        import { Foo } from "./foo"
+
+        type Local as Foo
+
+        export type A as Local[]
+    "#;
+
+    const GLOBALS: &str = r#"
+        type Id as string
+
+        global interface Window {
+            id: Id
+        }
+
+        global type Ids as Id[]
+    "#;
+
+    #[test]
+    fn declaration_file_of_a_module() {
+        assert_eq!(
+            emit_as(OutputKind::Dts, MODULE),
+            dedent!(
+                "
+                import type { Foo } from './foo';
+
+                type Local = Foo;
+
+                export type A = Local[];
+
+                export {};
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn global_augmentation() {
+        let expected = dedent!(
+            "
+            type Id = string;
+
+            declare global {
+                interface Window {
+                    id: Id;
+                }
+
+                type Ids = Id[];
+            }
+
+            export {};
+            "
+        )
+        .trim_start();
+
+        assert_eq!(emit_as(OutputKind::Dts, GLOBALS), expected);
+        assert_eq!(emit_as(OutputKind::Ts, GLOBALS), expected);
+    }
+
+    #[test]
+    fn modules_are_not_marked() {
+        assert!(!emit_as(OutputKind::Ts, MODULE).contains("export {}"));
+    }
+
+    #[test]
+    fn removed_statements_leave_no_blank_lines() {
+        let program = parse!("type A as 1\ntype B as 2\ntype C as 3").simplify();
+
+        let Ast::Program(Program { statements, span }) = program else {
+            unreachable!()
+        };
+
+        let shaken = Ast::Program(Program {
+            statements: vec![statements[0].clone(), Ast::noop(), statements[2].clone()],
+            span,
+        });
+
+        let cleaned = Ast::Program(Program {
+            statements: vec![statements[0].clone(), statements[2].clone()],
+            span,
+        });
+
+        let expected = "type A = 1;\n\ntype C = 3;\n\n";
+
+        assert_eq!(emit(&shaken, OutputKind::Ts, 80), expected);
+        assert_eq!(shaken.remove_noops(), cleaned);
+        assert_eq!(emit(&cleaned, OutputKind::Ts, 80), expected);
+    }
+
+    #[rstest]
+    #[case::inline_alias(
+        "export default type Config as {width: number}",
+        "type Config = {width: number};\nexport default Config;\n\n"
+    )]
+    #[case::inline_interface(
+        "export default interface Config {width: number}",
+        "export default interface Config {\n    width: number;\n}\n\n"
+    )]
+    #[case::reference(
+        "type Config as {width: number}\nexport default Config",
+        "type Config = {width: number};\n\nexport default Config;\n\n"
+    )]
+    fn default_export(#[case] source: &str, #[case] expected: &str) {
+        let output = compile(source, &CompileOptions::default()).unwrap();
+
+        assert_eq!(output.typescript, expected);
+        assert_eq!(
+            output
+                .exports
+                .iter()
+                .map(|name| name.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["default"]
+        );
+        assert_eq!(
+            emit_as(OutputKind::Dts, source),
+            format!("{expected}export {{}};\n")
+        );
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::blank("\n  \n")]
+    #[case::unit_tests_only("unittest \"a\" do 1 end")]
+    fn empty_programs(#[case] source: &str) {
+        assert_eq!(emit_as(OutputKind::Ts, source), "");
+        assert_eq!(emit_as(OutputKind::Dts, source), "export {};\n");
+    }
+
+    mod streaming {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn streamed(source: &str, options: &CompileOptions) -> String {
+            let mut typescript = String::new();
+
+            compile_streaming(source, options, |chunk| typescript.push_str(chunk)).unwrap();
+
+            typescript
+        }
+
+        #[rstest]
+        fn as_compiled(
+            #[values(
+                MODULE,
+                GLOBALS,
+                AUGMENTATION,
+                "",
+                "/** Documented */\ntype A as 1\nunion B = C | D\nbrand E = string",
+                "type Config as {width: number}\nexport default Config"
+            )]
+            source: &str,
+            #[values(OutputKind::Ts, OutputKind::Dts)] output_kind: OutputKind,
+        ) {
+            let options = CompileOptions {
+                output_kind,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                streamed(source, &options),
+                compile(source, &options).unwrap().typescript
+            );
+        }
+
+        #[test]
+        fn helpers_are_declared_once() {
+            let source = dedent!(
+                "
+                type A(T) as deep_partial!(T)
+                type __dl_deep_partial_0 as 1
+                type B(T) as deep_partial!(T)
+                "
+            );
+
+            let typescript = streamed(source, &CompileOptions::default());
+
+            assert_eq!(
+                typescript.matches("type __dl_deep_partial_1<T> =").count(),
+                1,
+                "{typescript}"
+            );
+            assert!(
+                typescript.contains("type B<T> = __dl_deep_partial_1<T>;"),
+                "{typescript}"
+            );
+        }
+
+        #[test]
+        fn errors_stop_compilation() {
+            let mut typescript = String::new();
+
+            let diagnostics = compile_streaming(
+                "type A as 1\ntype new as 2\ntype B as 3",
+                &CompileOptions::default(),
+                |chunk| typescript.push_str(chunk),
+            )
+            .unwrap_err();
+
+            assert_eq!(typescript, "type A = 1;\n\n");
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].severity, Severity::Error);
+        }
+    }
+
+    const AUGMENTATION: &str = r#"
+        import { User } from "./user"
+
+        declare module :express do
+            /** The signed in user. */
+            interface Request {user: User}
+        end
+    "#;
+
+    #[test]
+    fn augmentations() {
+        assert_eq!(
+            emit_as(OutputKind::Dts, AUGMENTATION),
+            dedent!(
+                "
+                import type { User } from './user';
+
+                declare module 'express' {
+                    /** The signed in user. */
+                    interface Request {
+                        user: User;
+                    }
+                }
+
+                export {};
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn augmentations_type_check() {
+        let files = [
+            (
+                "express.d.ts",
+                "declare module 'express' {\n    export interface Request {\n        url: string;\n    }\n}\n"
+                    .to_string(),
+            ),
+            ("user.d.ts", "export interface User {\n    name: string;\n}\n".to_string()),
+            ("augmentation.d.ts", emit_as(OutputKind::Dts, AUGMENTATION)),
+            (
+                "use.ts",
+                "import type { Request } from 'express';\n\n\
+                 export const name = (request: Request): string => request.url + request.user.name;\n"
+                    .to_string(),
+            ),
+        ];
+
+        let files: Vec<_> = files
+            .iter()
+            .map(|(name, source)| (*name, source.as_str()))
+            .collect();
+
+        match tsc(&files) {
+            Some(result) => result.unwrap(),
+            None => eprintln!("skipped, tsc is not installed"),
+        }
+    }
+
+    #[test]
+    fn declaration_files_type_check() {
+        let files = [
+            ("foo.d.ts", "export type Foo = 1;\n".to_string()),
+            ("module.d.ts", emit_as(OutputKind::Dts, MODULE)),
+            ("globals.d.ts", emit_as(OutputKind::Dts, GLOBALS)),
+            (
+                "use.ts",
+                "const ids: Ids = [window.id];\nexport {};\n".to_string(),
+            ),
+        ];
+
+        let files: Vec<_> = files
+            .iter()
+            .map(|(name, source)| (*name, source.as_str()))
+            .collect();
+
+        match tsc(&files) {
+            Some(result) => result.unwrap(),
+            None => eprintln!("skipped, tsc is not installed"),
+        }
+    }
+
+    /// Keys, strings and templates whose text needs escaping are emitted as TypeScript reading
+    /// them as the same text.
+    #[test]
+    fn escaped_text_type_checks() {
+        let source = "export type A as {\"it's\": 'say \"hi\"', lines: \"a\nb\\\"}\n\
+                      export type B(T) as `$${T}: ${T} ${ \\`";
+
+        let typescript = emit_as(OutputKind::Ts, source);
+
+        assert_eq!(
+            typescript,
+            "export type A = {'it\\'s': 'say \"hi\"', lines: 'a\\nb\\\\'};\n\n\
+             export type B<T> = `$${T}: ${T} \\${ \\\\`;\n\n"
+        );
+
+        match tsc(&[("escaped.ts", &typescript)]) {
+            Some(result) => result.unwrap(),
+            None => eprintln!("skipped, tsc is not installed"),
+        }
+    }
+}
@@ -0,0 +1,523 @@
+/// Incremental checking of the modules of a project, for an editor: the modules are inputs set one
+/// at a time as they change, and the results of the queries over them are memoized, so that
+/// editing a module only runs again the queries whose inputs changed.
+///
+/// A query is memoized with the fingerprint of its inputs, the hash of the source of its module
+/// for [`Database::parse`], [`Database::resolve`] and [`Database::symbols`]. The diagnostics of a
+/// module also depend on the exports of the modules it imports, as resolved, and are checked again
+/// when one of them changes. [`Database::diagnostics`] gathers those of every module.
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use super::{
+    compile,
+    project::{self, ModuleDiagnostic},
+    CompileOptions,
+};
+use crate::{
+    ast::{visit::Visit, Ast, ImportStatement, ImportType, Program},
+    diagnostic::Diagnostic,
+    flags, parser,
+    rewrite::{self, Symbol},
+    source::{SourceFile, SourceMap},
+};
+
+/// A memoized query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Query {
+    Parse,
+    Resolve,
+    Symbols,
+    /// The diagnostics of a module.
+    Check,
+    /// The diagnostics of every module.
+    Diagnostics,
+}
+
+impl Query {
+    const ALL: [Query; 5] = [
+        Query::Parse,
+        Query::Resolve,
+        Query::Symbols,
+        Query::Check,
+        Query::Diagnostics,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Query::Parse => "parse",
+            Query::Resolve => "resolve",
+            Query::Symbols => "symbols",
+            Query::Check => "check",
+            Query::Diagnostics => "diagnostics",
+        }
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How often the queries were answered from the cache, and how often they ran, for each module.
+#[derive(Debug, Clone, Default)]
+pub struct CacheStats {
+    hits: BTreeMap<Query, usize>,
+    /// The runs of the queries by module, the project's being the empty path.
+    runs: BTreeMap<(Query, PathBuf), usize>,
+}
+
+impl CacheStats {
+    pub fn hits(&self, query: Query) -> usize {
+        self.hits.get(&query).copied().unwrap_or_default()
+    }
+
+    /// How many times `query` ran, for every module.
+    pub fn misses(&self, query: Query) -> usize {
+        self.runs
+            .iter()
+            .filter(|((ran, _), _)| *ran == query)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// How many times `query` ran for the module at `path`.
+    pub fn runs(&self, query: Query, path: impl AsRef<Path>) -> usize {
+        self.runs
+            .get(&(query, path.as_ref().to_path_buf()))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<11}  {:>8}  {:>8}", "query", "hits", "misses")?;
+
+        for query in Query::ALL {
+            writeln!(
+                f,
+                "{:<11}  {:>8}  {:>8}",
+                query.name(),
+                self.hits(query),
+                self.misses(query)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The modules a module imports, and the declaration of each name declared in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolved {
+    /// The paths of the modules it imports from by a relative specifier, sorted, whether they
+    /// are in the project or not.
+    pub imports: Vec<PathBuf>,
+    /// See [`rewrite::declarations`].
+    pub declarations: HashMap<(usize, usize), (usize, usize)>,
+}
+
+/// A result and the fingerprint of the inputs it was computed from.
+struct Memo<T> {
+    inputs: u64,
+    value: T,
+}
+
+type Cache<T> = RefCell<HashMap<PathBuf, Memo<T>>>;
+
+pub struct Database {
+    options: CompileOptions,
+    /// The source of each module, by its path relative to the root of the project, and its hash.
+    files: BTreeMap<PathBuf, (Rc<str>, u64)>,
+    parsed: Cache<Option<Rc<Ast>>>,
+    resolved: Cache<Rc<Resolved>>,
+    symbols: Cache<Rc<BTreeMap<String, Symbol>>>,
+    checked: Cache<Rc<Vec<Diagnostic>>>,
+    diagnostics: Cache<Rc<Vec<ModuleDiagnostic>>>,
+    stats: RefCell<CacheStats>,
+}
+
+impl Database {
+    pub fn new(options: CompileOptions) -> Self {
+        Self {
+            options,
+            files: BTreeMap::new(),
+            parsed: Default::default(),
+            resolved: Default::default(),
+            symbols: Default::default(),
+            checked: Default::default(),
+            diagnostics: Default::default(),
+            stats: Default::default(),
+        }
+    }
+
+    /// Sets the source of the module at `path`, adding it to the project if it isn't in it.
+    pub fn set_file(&mut self, path: impl Into<PathBuf>, source: &str) {
+        self.files
+            .insert(path.into(), (source.into(), fingerprint(source)));
+    }
+
+    /// Removes the module at `path` from the project, and what was computed from it.
+    pub fn remove_file(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+
+        self.files.remove(path);
+        self.parsed.borrow_mut().remove(path);
+        self.resolved.borrow_mut().remove(path);
+        self.symbols.borrow_mut().remove(path);
+        self.checked.borrow_mut().remove(path);
+    }
+
+    /// The paths of the modules, sorted.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// The source files of the modules, the id of each being its index in [`Database::files`], as
+    /// the diagnostics of [`Database::diagnostics`] refer to them.
+    pub fn sources(&self) -> SourceMap {
+        let mut sources = SourceMap::default();
+
+        for (path, (source, _)) in &self.files {
+            sources.add(SourceFile::new(path.display().to_string(), &**source));
+        }
+
+        sources
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.borrow().clone()
+    }
+
+    /// The program of the module at `path`, without the statements its flags leave out. `None` for
+    /// a module that isn't in the project or doesn't parse.
+    pub fn parse(&self, path: &Path) -> Option<Rc<Ast>> {
+        let (source, hash) = self.files.get(path)?;
+
+        self.memoized(Query::Parse, path, &self.parsed, *hash, || {
+            let program = parser::parse_newtype_program(source).ok()?;
+            let (program, _) = flags::prune(&program, &self.options.flags);
+            Some(program.into())
+        })
+    }
+
+    /// The imports and the declarations of the module at `path`, see [`Resolved`].
+    pub fn resolve(&self, path: &Path) -> Rc<Resolved> {
+        let Some((_, hash)) = self.files.get(path) else {
+            return Default::default();
+        };
+
+        self.memoized(Query::Resolve, path, &self.resolved, *hash, || {
+            let Some(program) = self.parse(path) else {
+                return Default::default();
+            };
+
+            let mut specifiers = Specifiers::default();
+            specifiers.visit_ast(&program);
+
+            let mut imports: Vec<_> = specifiers
+                .0
+                .into_iter()
+                .filter_map(|specifier| project::resolve(path, specifier))
+                .collect();
+            imports.sort();
+            imports.dedup();
+
+            Resolved {
+                imports,
+                declarations: rewrite::declarations(&program),
+            }
+            .into()
+        })
+    }
+
+    /// The names declared at the top of the module at `path`, its imports included.
+    pub fn symbols(&self, path: &Path) -> Rc<BTreeMap<String, Symbol>> {
+        let Some((_, hash)) = self.files.get(path) else {
+            return Default::default();
+        };
+
+        self.memoized(Query::Symbols, path, &self.symbols, *hash, || {
+            let Some(program) = self.parse(path) else {
+                return Default::default();
+            };
+
+            // The names in scope at the start of a statement are those of the module.
+            let Ast::Program(Program { statements, .. }) = program.as_ref() else {
+                return Default::default();
+            };
+
+            match statements.first() {
+                Some(statement) => {
+                    rewrite::names_in_scope(&program, statement.as_span().start).into()
+                }
+                None => Default::default(),
+            }
+        })
+    }
+
+    /// The diagnostics of the module at `path`, checked with the exports of the modules it
+    /// imports, as [`project::compile_project`] reports them.
+    pub fn check(&self, path: &Path) -> Rc<Vec<Diagnostic>> {
+        let Some((source, hash)) = self.files.get(path) else {
+            return Default::default();
+        };
+
+        let resolved = self.resolve(path);
+
+        let mut hasher = DefaultHasher::new();
+        hash.hash(&mut hasher);
+        for imported in &resolved.imports {
+            (imported, self.files.get(imported).map(|(_, hash)| hash)).hash(&mut hasher);
+        }
+
+        self.memoized(Query::Check, path, &self.checked, hasher.finish(), || {
+            let mut output = match compile(source, &self.options) {
+                Ok(output) => output.diagnostics,
+                Err(errors) => return errors.into(),
+            };
+
+            if let Some(program) = self.parse(path) {
+                let programs: Vec<_> = resolved
+                    .imports
+                    .iter()
+                    .filter_map(|imported| {
+                        Some((imported.as_path(), self.parse(imported)?.as_ref().clone()))
+                    })
+                    .collect();
+
+                let mut errors = project::imported_applications(path, &program, &programs);
+                errors.append(&mut output);
+                output = errors;
+            }
+
+            output.into()
+        })
+    }
+
+    /// The diagnostics of every module, their file set to the module's in
+    /// [`Database::sources`].
+    pub fn diagnostics(&self) -> Rc<Vec<ModuleDiagnostic>> {
+        let mut hasher = DefaultHasher::new();
+        for (path, (_, hash)) in &self.files {
+            (path, hash).hash(&mut hasher);
+        }
+
+        self.memoized(
+            Query::Diagnostics,
+            Path::new(""),
+            &self.diagnostics,
+            hasher.finish(),
+            || {
+                self.files
+                    .keys()
+                    .zip(self.sources().ids())
+                    .flat_map(|(path, file)| {
+                        self.check(path)
+                            .iter()
+                            .map(|diagnostic| ModuleDiagnostic {
+                                path: path.clone(),
+                                diagnostic: diagnostic.clone().with_file(file),
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+                    .into()
+            },
+        )
+    }
+
+    /// The value of `query` for the module at `path` in `cache`, computed again only if the
+    /// fingerprint of its inputs isn't the one it was computed from.
+    fn memoized<T: Clone>(
+        &self,
+        query: Query,
+        path: &Path,
+        cache: &Cache<T>,
+        inputs: u64,
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        let cached = cache
+            .borrow()
+            .get(path)
+            .filter(|memo| memo.inputs == inputs)
+            .map(|memo| memo.value.clone());
+
+        if let Some(value) = cached {
+            *self.stats.borrow_mut().hits.entry(query).or_default() += 1;
+            return value;
+        }
+
+        *self
+            .stats
+            .borrow_mut()
+            .runs
+            .entry((query, path.to_path_buf()))
+            .or_default() += 1;
+
+        let value = compute();
+
+        cache.borrow_mut().insert(
+            path.to_path_buf(),
+            Memo {
+                inputs,
+                value: value.clone(),
+            },
+        );
+
+        value
+    }
+}
+
+fn fingerprint(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The specifiers of the modules a program imports from, by an import statement or type.
+#[derive(Default)]
+struct Specifiers<'a>(Vec<&'a str>);
+
+impl<'a> Visit<'a> for Specifiers<'a> {
+    fn visit_import_statement(&mut self, import: &'a ImportStatement) {
+        self.0.push(&import.module);
+    }
+
+    fn visit_import_type(&mut self, import: &'a ImportType) {
+        self.0.push(&import.module);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::SymbolKind;
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn project() -> Database {
+        let mut db = Database::new(CompileOptions::default());
+
+        db.set_file("shapes.dl", "export type Box(T) as {value: T}");
+        db.set_file(
+            "main.dl",
+            dedent!(
+                r#"
+                import * as Shapes from "./shapes.dl"
+                export type A as Shapes::Box(1)
+                "#
+            ),
+        );
+        db.set_file("other.dl", "export type C as 1");
+
+        db
+    }
+
+    fn messages(db: &Database) -> Vec<(String, String)> {
+        db.diagnostics()
+            .iter()
+            .map(|ModuleDiagnostic { path, diagnostic }| {
+                (path.display().to_string(), diagnostic.message.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn editing_a_module_runs_only_its_queries() {
+        let mut db = project();
+
+        assert_eq!(messages(&db), vec![]);
+
+        db.set_file("shapes.dl", "export type Box(T, U) as {value: T, other: U}");
+
+        assert_eq!(
+            messages(&db),
+            vec![(
+                "main.dl".to_string(),
+                "`Shapes::Box` expects 2 type arguments, found 1".to_string()
+            )]
+        );
+
+        let stats = db.stats();
+
+        for query in [Query::Parse, Query::Resolve] {
+            assert_eq!(stats.runs(query, "shapes.dl"), 2, "{query}");
+            assert_eq!(stats.runs(query, "main.dl"), 1, "{query}");
+            assert_eq!(stats.runs(query, "other.dl"), 1, "{query}");
+        }
+
+        // `main.dl` imports the edited module, `other.dl` doesn't.
+        assert_eq!(stats.runs(Query::Check, "main.dl"), 2);
+        assert_eq!(stats.runs(Query::Check, "other.dl"), 1);
+        assert_eq!(stats.runs(Query::Diagnostics, ""), 2);
+
+        messages(&db);
+
+        assert_eq!(db.stats().runs(Query::Diagnostics, ""), 2);
+        assert_eq!(db.stats().hits(Query::Diagnostics), 1);
+    }
+
+    #[test]
+    fn imports_are_resolved() {
+        let db = project();
+
+        assert_eq!(
+            db.resolve(Path::new("main.dl")).imports,
+            vec![PathBuf::from("shapes.dl")]
+        );
+        assert!(db.resolve(Path::new("other.dl")).imports.is_empty());
+        assert_eq!(
+            db.symbols(Path::new("main.dl"))
+                .iter()
+                .map(|(name, symbol)| (name.as_str(), symbol.kind))
+                .collect::<Vec<_>>(),
+            vec![("A", SymbolKind::Alias), ("Shapes", SymbolKind::Namespace)]
+        );
+    }
+
+    #[test]
+    fn removed_modules() {
+        let mut db = project();
+
+        db.remove_file("shapes.dl");
+
+        assert_eq!(db.parse(Path::new("shapes.dl")), None);
+        assert_eq!(messages(&db), vec![]);
+        assert_eq!(
+            db.files().collect::<Vec<_>>(),
+            vec![Path::new("main.dl"), Path::new("other.dl")]
+        );
+    }
+
+    #[test]
+    fn stats_table() {
+        let db = project();
+
+        db.diagnostics();
+        db.diagnostics();
+
+        assert_eq!(
+            db.stats().to_string(),
+            dedent!(
+                "
+                query            hits    misses
+                parse               4         3
+                resolve             0         3
+                symbols             0         0
+                check               0         3
+                diagnostics         1         1
+                "
+            )
+            .trim_start()
+        );
+    }
+}
@@ -0,0 +1,645 @@
+/// Compilation of a directory of modules to a directory of TypeScript files, with an optional
+/// `index.ts` re-exporting every exported type.
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+
+use super::{compile, CompileOptions, OutputKind};
+use crate::{
+    ast::{visit::Visit, Ast, Ident, ImportClause, ImportStatement, ImportType},
+    diagnostic::Diagnostic,
+    flags,
+    lint::{self, Signatures},
+    parser,
+    source::{FileId, Origin, SourceFile, SourceMap},
+    zod,
+};
+
+/// Extension of the source files.
+pub const SOURCE_EXTENSION: &str = "dl";
+
+/// A source file of a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    /// Path relative to the root of the project.
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// A diagnostic reported for one module of a project, its file set to the module's in
+/// [`sources`].
+#[derive(Debug, Clone)]
+pub struct ModuleDiagnostic {
+    pub path: PathBuf,
+    pub diagnostic: Diagnostic,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectOutput {
+    /// Emitted files, paths being relative to the output directory.
+    pub files: Vec<(PathBuf, String)>,
+    /// Warnings reported while compiling.
+    pub diagnostics: Vec<ModuleDiagnostic>,
+}
+
+/// Every `.dl` file under `root`, sorted by path.
+pub fn modules(root: &Path) -> io::Result<Vec<Module>> {
+    let mut modules = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == SOURCE_EXTENSION) {
+                modules.push(Module {
+                    source: std::fs::read_to_string(&path)?,
+                    path: path.strip_prefix(root).unwrap().to_path_buf(),
+                });
+            }
+        }
+    }
+
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(modules)
+}
+
+/// The source files of `modules`, the id of each being its index.
+pub fn sources(modules: &[Module]) -> SourceMap {
+    let mut sources = SourceMap::default();
+
+    for module in modules {
+        sources.add(SourceFile::new(
+            module.path.display().to_string(),
+            module.source.as_str(),
+        ));
+    }
+
+    sources
+}
+
+/// Compiles every module to a file at the same relative path, failing with every diagnostic
+/// reported if any module has an error. With `barrel`, an `index.ts` re-exports the exported types
+/// of every module, and two modules exporting the same name is an error.
+pub fn compile_project(
+    modules: &[Module],
+    options: &CompileOptions,
+    barrel: bool,
+) -> Result<ProjectOutput, Vec<ModuleDiagnostic>> {
+    let (output_extension, import_extension) = match options.output_kind {
+        OutputKind::Ts | OutputKind::Zod => ("ts", "ts"),
+        OutputKind::Dts => ("d.ts", "js"),
+    };
+
+    let options = CompileOptions {
+        import_extension: Some(import_extension),
+        ..options.clone()
+    };
+
+    let sources = sources(modules);
+    let mut files = vec![];
+    let mut diagnostics = vec![];
+    let mut failed = false;
+    let mut exports = vec![];
+
+    // Modules that don't parse fail to compile below.
+    let programs = modules
+        .iter()
+        .filter_map(|module| {
+            let program = parser::parse_newtype_program(&module.source).ok()?;
+            let (program, _) = flags::prune(&program, &options.flags);
+            Some((module.path.as_path(), program))
+        })
+        .collect_vec();
+
+    for (module, file) in modules.iter().zip(sources.ids()) {
+        let in_module = |diagnostic: Diagnostic| ModuleDiagnostic {
+            path: module.path.clone(),
+            diagnostic: diagnostic.with_file(file),
+        };
+
+        match compile(&module.source, &options) {
+            Ok(output) => {
+                let program = programs.iter().find(|(path, _)| *path == module.path);
+
+                if let Some((_, program)) = program {
+                    let errors = imported_applications(&module.path, program, &programs);
+
+                    failed |= !errors.is_empty();
+                    diagnostics.extend(errors.into_iter().map(in_module));
+                }
+
+                diagnostics.extend(output.diagnostics.into_iter().map(in_module));
+                files.push((
+                    module.path.with_extension(output_extension),
+                    output.typescript,
+                ));
+
+                if let Some(zod) = output.zod.filter(|zod| !zod.is_empty()) {
+                    files.push((module.path.with_extension(zod::EXTENSION), zod));
+                }
+                exports.push((module, file, output.exports));
+            }
+            Err(errors) => {
+                failed = true;
+                diagnostics.extend(errors.into_iter().map(in_module));
+            }
+        }
+    }
+
+    if barrel && !failed {
+        match barrel_file(&exports, &sources, import_extension) {
+            Ok(index) => files.push((
+                PathBuf::from("index").with_extension(output_extension),
+                index,
+            )),
+            Err(collisions) => {
+                failed = true;
+                diagnostics.extend(collisions);
+            }
+        }
+    }
+
+    if failed {
+        Err(diagnostics)
+    } else {
+        Ok(ProjectOutput { files, diagnostics })
+    }
+}
+
+/// Checks the applications of the types other modules of the project export that `program`, the
+/// module at `path`, names through a namespace import or an import type, which checking the module
+/// on its own can't resolve.
+pub(super) fn imported_applications(
+    path: &Path,
+    program: &Ast,
+    programs: &[(&Path, Ast)],
+) -> Vec<Diagnostic> {
+    let mut imports = Imports::default();
+    imports.visit_ast(program);
+
+    let exports = |specifier: &str| {
+        let imported = resolve(path, specifier)?;
+
+        programs
+            .iter()
+            .find(|(path, _)| *path == imported)
+            .map(|(_, program)| Signatures::exports(program))
+    };
+
+    let mut signatures = Signatures::default();
+
+    for (namespace, specifier) in imports.namespaces {
+        if let Some(exports) = exports(specifier) {
+            signatures = signatures.with_namespace(namespace, exports);
+        }
+    }
+
+    for specifier in imports.import_types {
+        if let Some(exports) = exports(specifier) {
+            signatures = signatures.with_module(specifier, exports);
+        }
+    }
+
+    lint::applications(program, &signatures)
+}
+
+/// Modules a program imports as a namespace or references with an import type.
+#[derive(Default)]
+struct Imports<'a> {
+    /// Namespaces and the specifiers of their modules.
+    namespaces: Vec<(&'a str, &'a str)>,
+    import_types: Vec<&'a str>,
+}
+
+impl<'a> Visit<'a> for Imports<'a> {
+    fn visit_import_statement(&mut self, import: &'a ImportStatement) {
+        if let ImportClause::Namespace { alias } = &import.import_clause {
+            self.namespaces.push((&alias.name, &import.module));
+        }
+    }
+
+    fn visit_import_type(&mut self, import: &'a ImportType) {
+        self.import_types.push(&import.module);
+    }
+}
+
+/// Whether `specifier` imports a module of the project rather than a package.
+pub(crate) fn is_relative(specifier: &str) -> bool {
+    specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+/// The path of the module a relative `specifier` imports from the module at `from`, both relative
+/// to the root of the project. A specifier without an extension imports a `.dl` module, `"./a"`
+/// being `a.dl`.
+pub(crate) fn resolve(from: &Path, specifier: &str) -> Option<PathBuf> {
+    if !is_relative(specifier) {
+        return None;
+    }
+
+    let mut path = from.parent().unwrap_or(Path::new("")).to_path_buf();
+
+    for component in specifier.split('/') {
+        match component {
+            "." | "" => {}
+            ".." => {
+                if !path.pop() {
+                    return None;
+                }
+            }
+            component => path.push(component),
+        }
+    }
+
+    if path.extension().is_none() {
+        path.set_extension(SOURCE_EXTENSION);
+    }
+
+    Some(path)
+}
+
+/// An `index` module re-exporting the exports of every module, or an error for every name exported
+/// by two modules.
+fn barrel_file(
+    exports: &[(&Module, FileId, Vec<Ident>)],
+    sources: &SourceMap,
+    import_extension: &str,
+) -> Result<String, Vec<ModuleDiagnostic>> {
+    let mut declared: BTreeMap<&str, (FileId, &Ident)> = BTreeMap::new();
+    let mut collisions = vec![];
+
+    for (module, file, names) in exports {
+        for name in names {
+            match declared.get(name.name.as_str()) {
+                Some((first_file, first)) => {
+                    let first_file = sources.get(*first_file);
+                    let Origin {
+                        file: first_path,
+                        line,
+                        column,
+                    } = first_file.origin(first.span.start);
+
+                    collisions.push(ModuleDiagnostic {
+                        path: module.path.clone(),
+                        diagnostic: Diagnostic::error(
+                            format!("`{}` is exported by two modules", name.name),
+                            name.span,
+                        )
+                        .with_note(format!("first exported at {first_path}:{line}:{column}"))
+                        .with_file(*file),
+                    });
+                }
+                None => {
+                    declared.insert(&name.name, (*file, name));
+                }
+            }
+        }
+    }
+
+    if !collisions.is_empty() {
+        return Err(collisions);
+    }
+
+    let mut index = String::new();
+
+    for (module, _, names) in exports {
+        if names.is_empty() {
+            continue;
+        }
+
+        index += &format!(
+            "export type {{ {} }} from './{}';\n",
+            names.iter().map(|name| &name.name).join(", "),
+            specifier(&module.path.with_extension(import_extension))
+        );
+    }
+
+    Ok(index)
+}
+
+/// A path as a module specifier, separated by `/` on every platform.
+fn specifier(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .join("/")
+}
+
+/// Rewrites the relative imports and import types of `.dl` modules in `program` to import the
+/// emitted files, `"./a.dl"` becoming `"./a.ts"` for the extension `ts`.
+pub fn rewrite_imports(program: &Ast, extension: &str) -> Ast {
+    let (program, _) = program.prewalk((), &|node, ctx| {
+        let node = match node {
+            Ast::ImportStatement(import) => Ast::ImportStatement(ImportStatement {
+                module: rewrite_specifier(&import.module, extension),
+                ..import
+            }),
+            Ast::ImportType(import) => Ast::ImportType(ImportType {
+                module: rewrite_specifier(&import.module, extension),
+                ..import
+            }),
+            node => node,
+        };
+
+        (node, ctx)
+    });
+
+    program
+}
+
+fn rewrite_specifier(module: &str, extension: &str) -> String {
+    match module.strip_suffix(&format!(".{SOURCE_EXTENSION}")) {
+        Some(stem) if is_relative(module) => format!("{stem}.{extension}"),
+        _ => module.to_string(),
+    }
+}
+
+/// Writes `files` under `out_dir`, creating the directories they are in.
+pub fn write(out_dir: &Path, files: &[(PathBuf, String)]) -> io::Result<()> {
+    for (path, contents) in files {
+        let path = out_dir.join(path);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, contents)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        diagnostic::Severity,
+        test_support::{parse, temp_dir},
+        typescript::Pretty as _,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn module(path: &str, source: &str) -> Module {
+        Module {
+            path: PathBuf::from(path),
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn three_modules() {
+        let src = temp_dir();
+        let dist = temp_dir();
+
+        std::fs::create_dir_all(src.join("nested")).unwrap();
+        std::fs::write(src.join("a.dl"), "export type A as 1").unwrap();
+        std::fs::write(
+            src.join("b.dl"),
+            "import { A } from \"./a.dl\"\nexport type B as [A]\ntype Private as 2",
+        )
+        .unwrap();
+        std::fs::write(
+            src.join("nested/c.dl"),
+            "import { B } from \"../b.dl\"\nexport interface C { b: B }",
+        )
+        .unwrap();
+        std::fs::write(src.join("notes.txt"), "not a module").unwrap();
+
+        let modules = modules(&src).unwrap();
+
+        assert_eq!(
+            modules.iter().map(|module| &module.path).collect_vec(),
+            vec![
+                Path::new("a.dl"),
+                Path::new("b.dl"),
+                Path::new("nested/c.dl")
+            ]
+        );
+
+        let output = compile_project(&modules, &CompileOptions::default(), true).unwrap();
+
+        write(&dist, &output.files).unwrap();
+
+        let read = |path: &str| std::fs::read_to_string(dist.join(path)).unwrap();
+
+        assert_eq!(read("a.ts"), "export type A = 1;\n\n");
+        assert_eq!(
+            read("b.ts"),
+            dedent!(
+                "
+                import type { A } from './a.ts';
+
+                export type B = [A];
+
+                type Private = 2;
+
+                "
+            )
+            .trim_start()
+        );
+        assert!(
+            read("nested/c.ts").starts_with("import type { B } from '../b.ts';\n"),
+            "{}",
+            read("nested/c.ts")
+        );
+        assert_eq!(
+            read("index.ts"),
+            dedent!(
+                "
+                export type { A } from './a.ts';
+                export type { B } from './b.ts';
+                export type { C } from './nested/c.ts';
+                "
+            )
+            .trim_start()
+        );
+
+        std::fs::remove_dir_all(src).unwrap();
+        std::fs::remove_dir_all(dist).unwrap();
+    }
+
+    #[test]
+    fn modules_override_options() {
+        let modules = [
+            module("new.dl", "#options(strict = true)\ntype Record as 1"),
+            module("legacy.dl", "type Record as 1"),
+        ];
+
+        let errors = compile_project(&modules, &CompileOptions::default(), false).unwrap_err();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.path.to_str().unwrap(), error.diagnostic.severity))
+                .collect_vec(),
+            vec![
+                ("new.dl", Severity::Error),
+                ("legacy.dl", Severity::Warning)
+            ]
+        );
+        assert!(errors[0]
+            .diagnostic
+            .notes
+            .contains(&"`--strict` denies `shadowed_global`".to_string()));
+
+        let modules = [module(
+            "new.dl",
+            "#options(strict = true, emit = \"dts\")\nexport type A as 1",
+        )];
+
+        let errors = compile_project(&modules, &CompileOptions::default(), false).unwrap_err();
+
+        assert_eq!(
+            errors[0].diagnostic.message,
+            "`emit` can't be set by a module"
+        );
+        assert_eq!(
+            errors[0].diagnostic.code,
+            Some(crate::diagnostic::codes::MODULE_OPTION.id)
+        );
+    }
+
+    #[test]
+    fn declaration_files_import_javascript() {
+        let modules = [
+            module("a.dl", "export type A as 1"),
+            module("b.dl", "import { A } from \"./a.dl\"\nexport type B as A"),
+        ];
+
+        let options = CompileOptions {
+            output_kind: OutputKind::Dts,
+            ..Default::default()
+        };
+
+        let output = compile_project(&modules, &options, true).unwrap();
+
+        let paths = output.files.iter().map(|(path, _)| path).collect_vec();
+
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("a.d.ts"),
+                Path::new("b.d.ts"),
+                Path::new("index.d.ts")
+            ]
+        );
+        assert!(output.files[1].1.contains("from './a.js'"));
+        assert!(output.files[2]
+            .1
+            .contains("export type { B } from './b.js';"));
+    }
+
+    #[test]
+    fn type_arguments_through_namespaces() {
+        let modules = [
+            module("shapes/box.dl", "export type Box(T) as {value: T}"),
+            module(
+                "main.dl",
+                dedent!(
+                    r#"
+                    import * as Shapes from "./shapes/box.dl"
+                    type A as Shapes::Box(1)
+                    type B as Shapes::Box(1, 2)
+                    type C as import("./shapes/box.dl")::Box()
+                    "#
+                ),
+            ),
+        ];
+
+        let errors = compile_project(&modules, &CompileOptions::default(), false).unwrap_err();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.path.as_path(), error.diagnostic.message.as_str()))
+                .collect_vec(),
+            vec![
+                (
+                    Path::new("main.dl"),
+                    "`Shapes::Box` expects 1 type argument, found 2"
+                ),
+                (
+                    Path::new("main.dl"),
+                    "`import(\"./shapes/box.dl\")::Box` expects 1 type argument, found 0"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_types_are_rewritten() {
+        let program = parse!("type A as import(\"./a.dl\")::A");
+
+        assert_eq!(
+            rewrite_imports(&program, "ts").render_pretty_ts(80).trim(),
+            "type A = import('./a.ts').A;"
+        );
+    }
+
+    #[test]
+    fn non_relative_imports_are_kept() {
+        let program = parse!("import { A } from \"pkg.dl\"\nimport { B } from \"./b.ts\"");
+
+        assert_eq!(rewrite_imports(&program, "ts"), program);
+    }
+
+    #[test]
+    fn barrel_default_export() {
+        let modules = [
+            module("a.dl", "export type A as 1\nexport default type Main as A"),
+            module("b.dl", "export type B as 2"),
+        ];
+
+        let output = compile_project(&modules, &CompileOptions::default(), true).unwrap();
+
+        assert_eq!(
+            output.files[2],
+            (
+                PathBuf::from("index.ts"),
+                "export type { A, default } from './a.ts';\nexport type { B } from './b.ts';\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn barrel_collision() {
+        let modules = [
+            module("a.dl", "export type Shared as 1"),
+            module("b.dl", "type Private as 1\nexport type Shared as 2"),
+        ];
+
+        let diagnostics = compile_project(&modules, &CompileOptions::default(), true)
+            .unwrap_err()
+            .into_iter()
+            .map(|ModuleDiagnostic { path, diagnostic }| {
+                (
+                    path,
+                    diagnostic.message,
+                    &modules[1].source[diagnostic.span.start..diagnostic.span.end],
+                    diagnostic.notes,
+                )
+            })
+            .collect_vec();
+
+        assert_eq!(
+            diagnostics,
+            vec![(
+                PathBuf::from("b.dl"),
+                "`Shared` is exported by two modules".to_string(),
+                "Shared",
+                vec!["first exported at a.dl:1:13".to_string()]
+            )]
+        );
+
+        // Without a barrel the names don't collide.
+        assert!(compile_project(&modules, &CompileOptions::default(), false).is_ok());
+    }
+}
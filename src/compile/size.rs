@@ -0,0 +1,200 @@
+/// Sizes of the TypeScript emitted for the exported aliases, for the bundlers failing on a single
+/// type too large. `--max-emit-bytes-per-alias` rejects the aliases emitted as more bytes than its
+/// limit, and `--emit-size-report` lists the size of every alias, the largest first.
+use std::fmt::Write;
+
+use serde_derive::Serialize;
+
+use crate::{
+    ast::{Ast, Program, Span, TypeAlias},
+    diagnostic::{codes, Diagnostic},
+    typescript::Pretty,
+};
+
+/// Output format of the size report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SizeReportFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AliasSize {
+    pub name: String,
+    /// Length of the TypeScript of the alias, its doc comment and final `;` included.
+    pub bytes: usize,
+    /// The declaration of the alias.
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// The sizes of the exported aliases of the simplified `program`, rendered `width` columns wide,
+/// the largest first and those of the same size in the order they are declared.
+pub fn measure(program: &Ast, width: usize) -> Vec<AliasSize> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    let mut sizes: Vec<_> = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.inner.as_ref() {
+                Ast::TypeAlias(TypeAlias {
+                    export,
+                    default,
+                    name,
+                    ..
+                }) if *export || *default => Some(AliasSize {
+                    name: name.name.clone(),
+                    bytes: statement.render_pretty_ts(width).len(),
+                    span: statement.as_span(),
+                }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+    sizes
+}
+
+/// The errors of the aliases emitted as more than `limit` bytes, in the order they are declared.
+pub fn check(sizes: &[AliasSize], limit: usize) -> Vec<Diagnostic> {
+    let mut oversized: Vec<_> = sizes.iter().filter(|size| size.bytes > limit).collect();
+    oversized.sort_by_key(|size| size.span.start);
+
+    oversized
+        .into_iter()
+        .map(|size| {
+            Diagnostic::error(
+                format!(
+                    "`{}` is emitted as {} bytes, more than the limit of {limit}",
+                    size.name, size.bytes
+                ),
+                size.span,
+            )
+            .with_note(
+                "split it into smaller aliases, which it refers to instead of inlining them, or \
+                 pass `--hoist` to bind its repeated parts once",
+            )
+            .with_code(codes::EMIT_SIZE_LIMIT)
+        })
+        .collect()
+}
+
+/// The report of `sizes`, a table of the aliases and their sizes or a JSON array of them.
+pub fn render(sizes: &[AliasSize], format: SizeReportFormat) -> String {
+    match format {
+        SizeReportFormat::Table => {
+            let width = sizes
+                .iter()
+                .map(|size| size.bytes.to_string().len())
+                .max()
+                .unwrap_or(0);
+
+            sizes.iter().fold(String::new(), |mut out, size| {
+                let _ = writeln!(out, "{:>width$}  {}", size.bytes, size.name);
+                out
+            })
+        }
+        SizeReportFormat::Json => {
+            serde_json::to_string_pretty(sizes).expect("sizes serialize") + "\n"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, compile_measured, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    const SOURCE: &str = dedent!(
+        r#"
+        export type Small as 1
+        type Private as {a: string, b: number, c: boolean}
+        export type Large as {a: string, b: number, c: boolean}
+        export default type Medium as [string]
+        "#
+    );
+
+    fn limited(limit: Option<usize>) -> CompileOptions {
+        CompileOptions {
+            max_emit_bytes_per_alias: limit,
+            measure_sizes: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn oversized_aliases_fail() {
+        let diagnostics = compile(SOURCE, &limited(Some(30))).unwrap_err();
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    SOURCE[diagnostic.span.start..diagnostic.span.end].trim_end()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "`Large` is emitted as 55 bytes, more than the limit of 30",
+                    "export type Large as {a: string, b: number, c: boolean}"
+                ),
+                (
+                    "`Medium` is emitted as 46 bytes, more than the limit of 30",
+                    "export default type Medium as [string]"
+                ),
+            ]
+        );
+        assert!(compile(SOURCE, &limited(Some(55))).is_ok());
+    }
+
+    #[test]
+    fn report() {
+        let sizes = compile(SOURCE, &limited(None)).unwrap().sizes;
+
+        assert_eq!(
+            render(&sizes, SizeReportFormat::Table),
+            "55  Large\n46  Medium\n22  Small\n"
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&render(&sizes, SizeReportFormat::Json))
+                .unwrap(),
+            serde_json::json!([
+                {"name": "Large", "bytes": 55},
+                {"name": "Medium", "bytes": 46},
+                {"name": "Small", "bytes": 22},
+            ])
+        );
+    }
+
+    /// The sizes are reported whether or not an alias is too large.
+    #[test]
+    fn report_of_oversized_aliases() {
+        let (result, sizes) = compile_measured(SOURCE, &limited(Some(30)));
+
+        assert_eq!(result.unwrap_err().len(), 2);
+        assert_eq!(
+            render(&sizes, SizeReportFormat::Table),
+            "55  Large\n46  Medium\n22  Small\n"
+        );
+
+        let (result, sizes) = compile_measured(SOURCE, &limited(None));
+
+        assert_eq!(result.unwrap().sizes, sizes);
+    }
+
+    #[test]
+    fn unlimited() {
+        let output = compile(SOURCE, &CompileOptions::default()).unwrap();
+
+        assert!(output.diagnostics.is_empty());
+        assert!(output.sizes.is_empty());
+    }
+}
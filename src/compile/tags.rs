@@ -0,0 +1,321 @@
+/// Outputs of a program split by the `@emit` tags of its declarations, to emit the types of a
+/// client and of a server from the same program.
+///
+/// ```text
+/// @emit("server")
+/// type Secret as string
+///
+/// @emit("client")
+/// type Button as {label: string}
+///
+/// type Id as string
+/// ```
+///
+/// A declaration without tags is emitted to every output, unless only the declarations left out
+/// of an output use it.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{
+        attribute::{Attribute, EMIT},
+        visit::{walk_type_parameter, Visit},
+        Ast, ExportDefault, Ident, Interface, Program, Span, TypeAlias,
+    },
+    diagnostic::{codes, Diagnostic},
+};
+
+/// The statements of `program`, once simplified, emitted to the output `tag`. Fails when a
+/// declaration emitted to it uses one that isn't.
+pub fn only_tag(program: &Ast, tag: &str) -> Result<Ast, Vec<Diagnostic>> {
+    let Ast::Program(Program { statements, span }) = program else {
+        return Ok(program.clone());
+    };
+
+    let declarations: Vec<Declaration> = statements.iter().map(Declaration::new).collect();
+
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, declaration) in declarations.iter().enumerate() {
+        if let Some(name) = declaration.name {
+            by_name.entry(name).or_default().push(i);
+        }
+    }
+
+    // The statements each statement uses, and those using it.
+    let uses: Vec<Vec<usize>> = declarations
+        .iter()
+        .enumerate()
+        .map(|(i, declaration)| {
+            let mut uses: Vec<usize> = declaration
+                .references
+                .iter()
+                .flat_map(|name| by_name.get(name.as_str()).into_iter().flatten().copied())
+                .filter(|&j| j != i)
+                .collect();
+            uses.sort_unstable();
+            uses.dedup();
+            uses
+        })
+        .collect();
+
+    let mut used_by = vec![vec![]; statements.len()];
+    for (i, uses) in uses.iter().enumerate() {
+        for &j in uses {
+            used_by[j].push(i);
+        }
+    }
+
+    let mut dropped: HashSet<usize> = declarations
+        .iter()
+        .enumerate()
+        .filter(|(_, declaration)| !declaration.tags.is_empty() && !declaration.tags.contains(&tag))
+        .map(|(i, _)| i)
+        .collect();
+
+    // An untagged declaration only the dropped declarations use is dropped along with them.
+    loop {
+        let unused: Vec<usize> = (0..statements.len())
+            .filter(|i| {
+                !dropped.contains(i)
+                    && declarations[*i].tags.is_empty()
+                    && !used_by[*i].is_empty()
+                    && used_by[*i].iter().all(|j| dropped.contains(j))
+            })
+            .collect();
+
+        if unused.is_empty() {
+            break;
+        }
+
+        dropped.extend(unused);
+    }
+
+    let diagnostics: Vec<Diagnostic> = (0..statements.len())
+        .filter(|i| !dropped.contains(i))
+        .flat_map(|i| uses[i].iter().map(move |&j| (i, j)))
+        .filter(|(_, j)| dropped.contains(j))
+        .map(|(i, j)| {
+            let (user, used) = (&declarations[i], &declarations[j]);
+
+            Diagnostic::error(
+                format!(
+                    "`{}` is emitted to `{tag}` but uses `{}`, which isn't",
+                    user.name.unwrap_or("export default"),
+                    used.name.unwrap_or_default(),
+                ),
+                user.span,
+            )
+            .with_note(format!(
+                "`{}` is only emitted to {}",
+                used.name.unwrap_or_default(),
+                used.tags
+                    .iter()
+                    .map(|tag| format!("`{tag}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .with_code(codes::EMIT_TAG_CONFLICT)
+        })
+        .collect();
+
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    Ok(Ast::Program(Program {
+        statements: statements
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !dropped.contains(i))
+            .map(|(_, statement)| statement.clone())
+            .collect(),
+        span: *span,
+    }))
+}
+
+/// What a statement declares and uses, also read by [`crate::analysis::dependency_graph`].
+pub(crate) struct Declaration<'a> {
+    pub name: Option<&'a str>,
+    /// Arguments of the `@emit` attributes of the statement.
+    pub tags: Vec<&'a str>,
+    /// Names the statement refers to, its own only when it refers to itself.
+    pub references: HashSet<String>,
+    pub span: Span,
+}
+
+impl<'a> Declaration<'a> {
+    pub fn new(statement: &'a Ast) -> Self {
+        let inner = match statement {
+            Ast::Statement(inner) => inner.as_ref(),
+            other => other,
+        };
+
+        let (name, span) = match inner {
+            Ast::TypeAlias(TypeAlias { name, .. }) => (Some(name.name.as_str()), name.span),
+            Ast::Interface(Interface { name, span, .. }) => (Some(name.as_str()), *span),
+            Ast::ExportDefault(ExportDefault { name, .. }) => (None, name.span),
+            other => (None, other.as_span()),
+        };
+
+        let tags = statement
+            .attributes()
+            .iter()
+            .filter(|attribute| attribute.name.name == EMIT)
+            .flat_map(Attribute::strings)
+            .collect();
+
+        let mut references = References::default();
+        references.visit_ast(statement);
+
+        if let Ast::Interface(Interface {
+            extends: Some(extends),
+            ..
+        }) = inner
+        {
+            references.0.insert(extends.clone());
+        }
+
+        Self {
+            name,
+            tags,
+            references: references.0,
+            span,
+        }
+    }
+}
+
+#[derive(Default)]
+struct References(HashSet<String>);
+
+impl<'a> Visit<'a> for References {
+    fn visit_ident(&mut self, ident: &'a Ident) {
+        self.0.insert(ident.name.clone());
+    }
+
+    fn visit_type_alias(&mut self, alias: &'a TypeAlias) {
+        for param in &alias.params {
+            walk_type_parameter(self, param);
+        }
+
+        self.visit_ast(&alias.body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn compile_for(source: &str, tag: &str) -> Result<String, Vec<String>> {
+        let options = CompileOptions {
+            only_tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+
+        compile(source, &options)
+            .map(|output| output.typescript)
+            .map_err(|diagnostics| {
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| diagnostic.message)
+                    .collect()
+            })
+    }
+
+    const SOURCE: &str = dedent!(
+        r#"
+        type Id as string
+        type Token as string
+
+        @emit("server")
+        export type Session as {id: Id, token: Token}
+
+        @emit("client")
+        export type Button as {id: Id, label: string}
+
+        export type User as {id: Id}
+        "#
+    );
+
+    #[test]
+    fn keeps_what_the_tagged_declarations_use() {
+        assert_eq!(
+            compile_for(SOURCE, "client").unwrap(),
+            dedent!(
+                r#"
+                type Id = string;
+
+                export type Button = {id: Id, label: string};
+
+                export type User = {id: Id};
+
+                "#
+            )
+            .trim_start()
+        );
+
+        assert_eq!(
+            compile_for(SOURCE, "server").unwrap(),
+            dedent!(
+                r#"
+                type Id = string;
+
+                type Token = string;
+
+                export type Session = {id: Id, token: Token};
+
+                export type User = {id: Id};
+
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn untagged_declarations_are_emitted_everywhere() {
+        let source = "type Id as string\nexport type User as {id: Id}";
+
+        for tag in ["client", "server"] {
+            assert_eq!(
+                compile_for(source, tag).unwrap(),
+                "type Id = string;\n\nexport type User = {id: Id};\n\n"
+            );
+        }
+    }
+
+    #[test]
+    fn conflict() {
+        let source = dedent!(
+            r#"
+            @emit("server")
+            type Secret as string
+
+            @emit("client", "server")
+            export type Form as {secret: Secret}
+            "#
+        );
+
+        assert_eq!(
+            compile_for(source, "client"),
+            Err(vec![
+                "`Form` is emitted to `client` but uses `Secret`, which isn't".to_string()
+            ])
+        );
+        assert!(compile_for(source, "server").is_ok());
+    }
+
+    #[test]
+    fn variants_keep_the_tags_of_their_union() {
+        let source = dedent!(
+            r#"
+            @emit("server")
+            union Event = Login {user: string} | Logout
+
+            type A as 1
+            "#
+        );
+
+        assert_eq!(compile_for(source, "client").unwrap(), "type A = 1;\n\n");
+    }
+}
@@ -0,0 +1,216 @@
+/// Conditions left outside the condition of an `if` or a `cond`. The parser only reads `A <: B`,
+/// `and`, `or` and `not` in a condition, but a macro such as `all!` expands to one wherever it is
+/// called, and `type A(T) as all!([T <: string, T <: :a])` has no TypeScript of its own.
+///
+/// Such a condition is an error, unless `--lower-conditions` is passed, which lowers it to the
+/// conditional type of the boolean it stands for:
+///
+/// ```text
+/// type A(T) as all!([T <: string, T <: :a])
+/// ```
+///
+/// ```ts
+/// type A<T> = T extends string ? T extends 'a' ? true : false : false;
+/// ```
+///
+/// Conditionals are simplified away first, a condition still in the tree is one that no `if` or
+/// `cond` consumed.
+use crate::{
+    ast::{
+        if_expr::expand_to_extends,
+        origin,
+        visit::{walk_ast, Visit},
+        Ast, ExtendsPrefixOp, PrefixOp, Span,
+    },
+    diagnostic::{codes, Diagnostic},
+};
+
+/// `A <: B`, `A and B`, `A or B` or `not (A)`, not the `?T` of a pattern.
+fn is_condition(node: &Ast) -> bool {
+    matches!(
+        node,
+        Ast::ExtendsInfixOp(_)
+            | Ast::ExtendsPrefixOp(ExtendsPrefixOp {
+                op: PrefixOp::Not,
+                ..
+            })
+    )
+}
+
+/// The errors of the conditions of the simplified `program`, each outside an `if` or a `cond`.
+pub fn check(program: &Ast) -> Vec<Diagnostic> {
+    #[derive(Default)]
+    struct Conditions {
+        diagnostics: Vec<Diagnostic>,
+        /// Spans of the nodes the visited one is in, for the origin of a condition.
+        enclosing: Vec<Span>,
+    }
+
+    impl<'a> Visit<'a> for Conditions {
+        fn visit_ast(&mut self, node: &'a Ast) {
+            if !is_condition(node) {
+                self.enclosing.push(node.as_span());
+                walk_ast(self, node);
+                self.enclosing.pop();
+                return;
+            }
+
+            let mut enclosing = self.enclosing.clone();
+            enclosing.push(node.as_span());
+
+            self.diagnostics.push(
+                Diagnostic::error(
+                    format!("`{node}` is a condition, which only an `if` or a `cond` can test"),
+                    node.as_span(),
+                )
+                .with_note(format!("wrap it as `if {node} then true else false end`"))
+                .with_note("or pass `--lower-conditions` to have the compiler do it")
+                .with_origin(origin::innermost(&enclosing, node.as_span()))
+                .with_code(codes::MISPLACED_CONDITION),
+            );
+        }
+    }
+
+    let mut conditions = Conditions::default();
+    conditions.visit_ast(program);
+    conditions.diagnostics
+}
+
+/// Replaces each condition of the simplified `program` by the conditional type it tests,
+/// `true` when it holds and `false` otherwise.
+pub fn lower(program: &Ast) -> Ast {
+    let (lowered, _) = program.prewalk((), &|node, ctx| {
+        if !is_condition(&node) {
+            return (node, ctx);
+        }
+
+        let span = node.as_span();
+
+        (
+            expand_to_extends(&node, &Ast::TrueKeyword(span), &Ast::FalseKeyword(span)),
+            ctx,
+        )
+    });
+
+    lowered
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+
+    fn lowered() -> CompileOptions {
+        CompileOptions {
+            lower_conditions: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn conditions_outside_an_if_are_errors() {
+        let source = "type A(T) as all!([T <: string, T <: :a])";
+        let diagnostics = compile(source, &CompileOptions::default()).unwrap_err();
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    &source[diagnostic.span.start..diagnostic.span.end]
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`T <: string and T <: \"a\"` is a condition, which only an `if` or a `cond` can test",
+                "T <: string, T <: :a"
+            )]
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            vec![
+                "wrap it as `if T <: string and T <: \"a\" then true else false end`",
+                "or pass `--lower-conditions` to have the compiler do it",
+                "expanded from macro `all!`"
+            ]
+        );
+    }
+
+    #[test]
+    fn conditions_are_lowered() {
+        assert_eq!(
+            compile("type A(T) as all!([T <: string, T <: :a])", &lowered())
+                .unwrap()
+                .typescript,
+            "type A<T> = T extends string ? T extends 'a' ? true : false : false;\n\n"
+        );
+        assert_eq!(
+            compile(
+                "type A(T) as {ok: any!([T <: 1, not (T <: 2)])}",
+                &lowered()
+            )
+            .unwrap()
+            .typescript,
+            "type A<T> = {ok: T extends 1 ? true : T extends 2 ? false : true};\n\n"
+        );
+    }
+
+    /// The parameters, return types and predicates of function types are simplified like any other
+    /// type.
+    #[test]
+    fn conditionals_in_function_types() {
+        let source = concat!(
+            "type G(T) as (x: unknown) => if T <: 1 then 1 else 2 end\n",
+            "type P(T) as (x: unknown) => x is if T <: 1 then :a else :b end\n",
+            "type Q(T) as (x: cond do T <: 1 -> :a, else -> :b end) => void",
+        );
+        let expected = concat!(
+            "type G<T> = (x: unknown) => T extends 1 ? 1 : 2;\n\n",
+            "type P<T> = (x: unknown) => x is T extends 1 ? 'a' : 'b';\n\n",
+            "type Q<T> = (x: T extends 1 ? 'a' : 'b') => void;\n\n",
+        );
+
+        assert_eq!(
+            compile(source, &CompileOptions::default())
+                .unwrap()
+                .typescript,
+            expected
+        );
+        assert_eq!(compile(source, &lowered()).unwrap().typescript, expected);
+    }
+
+    #[test]
+    fn conditions_in_function_types_are_lowered() {
+        let source = "type G(T) as (x: unknown) => all!([T <: 1])\n\
+                      type P(T) as (x: unknown) => x is any!([T <: 1])";
+
+        assert_eq!(
+            compile(source, &CompileOptions::default())
+                .unwrap_err()
+                .len(),
+            2
+        );
+        assert_eq!(
+            compile(source, &lowered()).unwrap().typescript,
+            "type G<T> = (x: unknown) => T extends 1 ? true : false;\n\n\
+             type P<T> = (x: unknown) => x is T extends 1 ? true : false;\n\n"
+        );
+    }
+
+    #[test]
+    fn conditions_of_conditionals_are_left() {
+        let source = "type A(T) as if all!([T <: string, T <: :a]) then 1 else 2 end";
+
+        assert_eq!(
+            compile(source, &CompileOptions::default())
+                .unwrap()
+                .typescript,
+            compile(source, &lowered()).unwrap().typescript
+        );
+        assert_eq!(
+            compile(source, &CompileOptions::default())
+                .unwrap()
+                .typescript,
+            "type A<T> = T extends string ? T extends 'a' ? 1 : 2 : 2;\n\n"
+        );
+    }
+}
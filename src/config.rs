@@ -0,0 +1,755 @@
+/// Project configuration, read from a `definitelylisp.toml` file.
+///
+/// ```toml
+/// width = 100
+/// emit = "dts"
+///
+/// [lints]
+/// contextual_keyword = "deny"
+/// ```
+///
+/// A module can override some of the options for itself with an `#options` pragma, see
+/// [`module_options`].
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    ast::{brand_declaration::BrandEncoding, if_expr::MissingElse, Span},
+    compile::{CompileOptions, OutputKind, MIN_HOIST_THRESHOLD},
+    diagnostic::{codes, Diagnostic},
+    features::{self, FeatureSet},
+    lint::{self, Level},
+    parser::{self, trivia},
+    pretty::Quote,
+    target::TsVersion,
+};
+
+pub const FILE_NAME: &str = "definitelylisp.toml";
+
+/// Compile options that may each be left unset, so that options from several places can be merged
+/// field by field.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Config {
+    /// Maximum line width of the emitted TypeScript.
+    pub width: Option<usize>,
+    /// Kind of TypeScript file to write, `"ts"` or `"dts"`.
+    pub emit: Option<OutputKind>,
+    /// Record the duration and output size of every pass.
+    pub timings: Option<bool>,
+    /// Merge adjacent arms of conditional types that have the same body.
+    pub merge_arms: Option<bool>,
+    /// Write the conditional types filtering a type parameter as `Extract` and `Exclude`.
+    pub extract_exclude: Option<bool>,
+    /// Hoist the subexpressions repeated at least this many times in an alias.
+    pub hoist: Option<usize>,
+    /// How `brand` declarations tag their base type, `"symbol"` or `"string"`.
+    pub brand: Option<BrandEncoding>,
+    /// Emit only the declarations for this `@emit` tag and those they use.
+    pub only_tag: Option<String>,
+    /// Unstable features to enable, by name.
+    pub features: Option<Vec<String>>,
+    /// Flags to set, which `#if flag("name")` blocks test.
+    pub flags: Option<Vec<String>>,
+    /// Version of TypeScript the output is written for, such as `"4.7"`.
+    pub target: Option<TsVersion>,
+    /// Report the warnings of lints as errors, and reject what the compiler can't lower yet.
+    pub strict: Option<bool>,
+    /// Lay the TypeScript out as Prettier does by default, 80 columns wide unless `width` is set.
+    pub prettier_compat: Option<bool>,
+    /// Quote of string literals, `"single"` or `"double"`.
+    pub quote: Option<Quote>,
+    /// Emit the aliases marked `@companion` with a constant holding their value.
+    pub const_companions: Option<bool>,
+    /// What an `if` or `cond` without `else` falls back to, `"never"`, `"unknown"` or
+    /// `"scrutinee"`.
+    pub missing_else: Option<MissingElse>,
+    /// Lower the conditions outside an `if` or a `cond` to `true` or `false` rather than reject
+    /// them.
+    pub lower_conditions: Option<bool>,
+    /// Reject the exported aliases emitted as more bytes than this.
+    pub max_emit_bytes_per_alias: Option<usize>,
+    /// Most expressions an expression can be nested in.
+    pub max_nesting: Option<usize>,
+    /// Level of each lint, by name.
+    #[serde(default)]
+    pub lints: BTreeMap<String, Level>,
+    /// Problems found while reading the file: unknown keys and lints, or the error that made the
+    /// file unreadable.
+    #[serde(skip)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Config {
+    /// Combines two configs field by field, taking the fields set in `over` over those of `self`.
+    /// Lint levels are combined per lint.
+    pub fn merge(mut self, over: Config) -> Config {
+        self.lints.extend(over.lints);
+        self.diagnostics.extend(over.diagnostics);
+
+        Config {
+            width: over.width.or(self.width),
+            emit: over.emit.or(self.emit),
+            timings: over.timings.or(self.timings),
+            merge_arms: over.merge_arms.or(self.merge_arms),
+            extract_exclude: over.extract_exclude.or(self.extract_exclude),
+            hoist: over.hoist.or(self.hoist),
+            brand: over.brand.or(self.brand),
+            only_tag: over.only_tag.or(self.only_tag),
+            features: over.features.or(self.features),
+            flags: over.flags.or(self.flags),
+            target: over.target.or(self.target),
+            strict: over.strict.or(self.strict),
+            prettier_compat: over.prettier_compat.or(self.prettier_compat),
+            quote: over.quote.or(self.quote),
+            const_companions: over.const_companions.or(self.const_companions),
+            missing_else: over.missing_else.or(self.missing_else),
+            lower_conditions: over.lower_conditions.or(self.lower_conditions),
+            max_emit_bytes_per_alias: over
+                .max_emit_bytes_per_alias
+                .or(self.max_emit_bytes_per_alias),
+            max_nesting: over.max_nesting.or(self.max_nesting),
+            lints: self.lints,
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    /// Options to compile with, using the defaults for anything left unset.
+    pub fn compile_options(&self) -> CompileOptions {
+        let defaults = CompileOptions::default();
+        let prettier_compat = self.prettier_compat.unwrap_or(defaults.prettier_compat);
+
+        CompileOptions {
+            width: self
+                .width
+                .unwrap_or(if prettier_compat { 80 } else { defaults.width }),
+            timings: self.timings.unwrap_or(defaults.timings),
+            output_kind: self.emit.unwrap_or(defaults.output_kind),
+            lints: self.lints.clone(),
+            merge_arms: self.merge_arms.unwrap_or(defaults.merge_arms),
+            extract_exclude: self.extract_exclude.unwrap_or(defaults.extract_exclude),
+            hoist: self.hoist.or(defaults.hoist),
+            macros: defaults.macros,
+            brand_encoding: self.brand.unwrap_or(defaults.brand_encoding),
+            import_extension: defaults.import_extension,
+            only_tag: self.only_tag.clone(),
+            features: FeatureSet::new(self.features.iter().flatten().cloned()),
+            flags: self.flags.clone().unwrap_or_default(),
+            target: self.target.unwrap_or(defaults.target),
+            strict: self.strict.unwrap_or(defaults.strict),
+            prettier_compat,
+            quote: self.quote,
+            const_companions: self.const_companions.unwrap_or(defaults.const_companions),
+            missing_else: self.missing_else.unwrap_or(defaults.missing_else),
+            lower_conditions: self.lower_conditions.unwrap_or(defaults.lower_conditions),
+            max_emit_bytes_per_alias: self
+                .max_emit_bytes_per_alias
+                .or(defaults.max_emit_bytes_per_alias),
+            measure_sizes: defaults.measure_sizes,
+            max_nesting: self.max_nesting.unwrap_or(defaults.max_nesting),
+        }
+    }
+}
+
+/// Finds the config file nearest to `start_dir`, looking in it and then in each of its ancestors.
+/// A file that can't be read or parsed gives a config with no options, and the error in
+/// [`Config::diagnostics`].
+pub fn load(start_dir: &Path) -> Option<(PathBuf, Config)> {
+    let start_dir = start_dir.canonicalize().ok()?;
+
+    let path = start_dir
+        .ancestors()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|path| path.is_file())?;
+
+    let config = std::fs::read_to_string(&path)
+        .map_err(|err| {
+            Diagnostic::error(
+                format!("can't read the config file: {err}"),
+                Span::new(0, 0),
+            )
+            .with_code(codes::INVALID_CONFIG)
+        })
+        .and_then(|source| parse(&source))
+        .unwrap_or_else(|diagnostic| Config {
+            diagnostics: vec![diagnostic],
+            ..Default::default()
+        });
+
+    Some((path, config))
+}
+
+/// Reads a config from the contents of a config file. Unknown keys and lints are not an error, but
+/// are reported as warnings in [`Config::diagnostics`].
+pub fn parse(source: &str) -> Result<Config, Diagnostic> {
+    let mut unknown = vec![];
+
+    let mut config: Config = serde_ignored::deserialize(toml::Deserializer::new(source), |path| {
+        unknown.push(keys(&path))
+    })
+    .map_err(|err| {
+        let span = err.span().unwrap_or(0..0);
+        Diagnostic::error(err.message(), Span::new(span.start, span.end))
+            .with_code(codes::INVALID_CONFIG)
+    })?;
+
+    let document = toml_edit::ImDocument::parse(source).unwrap();
+
+    for path in unknown {
+        config.diagnostics.push(
+            Diagnostic::warning(
+                format!("unknown key `{}`", path.join(".")),
+                key_span(&document, &path),
+            )
+            .with_code(codes::UNKNOWN_CONFIG_KEY),
+        );
+    }
+
+    if let Some(threshold) = config
+        .hoist
+        .filter(|threshold| *threshold < MIN_HOIST_THRESHOLD)
+    {
+        return Err(Diagnostic::error(
+            format!("`hoist` is {threshold}, it must be at least {MIN_HOIST_THRESHOLD}"),
+            key_span(&document, &["hoist".to_string()]),
+        )
+        .with_note("a subexpression is hoisted when it is repeated")
+        .with_code(codes::INVALID_CONFIG));
+    }
+
+    for name in config.lints.keys() {
+        if !lint::LINTS.contains(&name.as_str()) {
+            let path = ["lints".to_string(), name.clone()];
+
+            config.diagnostics.push(
+                Diagnostic::warning(format!("unknown lint `{name}`"), key_span(&document, &path))
+                    .with_note(format!("the known lints are {}", lint::LINTS.join(", ")))
+                    .with_code(codes::UNKNOWN_LINT),
+            );
+        }
+    }
+
+    for name in config.features.iter().flatten() {
+        if !features::FEATURES
+            .iter()
+            .any(|feature| feature.name == name)
+        {
+            config.diagnostics.push(
+                Diagnostic::warning(
+                    format!("unknown feature `{name}`"),
+                    key_span(&document, &["features".to_string()]),
+                )
+                .with_code(codes::UNKNOWN_FEATURE),
+            );
+        }
+    }
+
+    Ok(config)
+}
+
+/// Options a module can set for itself with an `#options` pragma, see [`module_options`]. The
+/// others apply to the whole build.
+pub const MODULE_OPTIONS: &[&str] = &["strict", "target", "lints"];
+
+/// Start of the TOML document the list of an `#options` pragma is read as, an inline table.
+const PRAGMA_TABLE: &str = "options = {";
+
+/// The options the `#options(key = value, ...)` pragmas at the top of `source` set for its module,
+/// such as `#options(strict = true, target = "5.0")`. Each list holds the entries of a TOML inline
+/// table; the grammar skips the pragmas as trivia. Returns the problems of the lists as well:
+/// setting an option that isn't one of [`MODULE_OPTIONS`] is an error, an unknown key a warning,
+/// and a pragma after the first statement an error.
+pub fn module_options(source: &str) -> (Config, Vec<Diagnostic>) {
+    let mut config = Config::default();
+    let mut diagnostics = vec![];
+
+    for (list, offset) in option_pragmas(source, &mut diagnostics) {
+        let document = format!("{PRAGMA_TABLE}{list}}}");
+
+        // Spans in the document to spans in the source.
+        let shift = |span: std::ops::Range<usize>| {
+            let at = |position: usize| {
+                (offset + position.saturating_sub(PRAGMA_TABLE.len())).min(offset + list.len())
+            };
+
+            Span::new(at(span.start), at(span.end))
+        };
+
+        let parsed = match toml_edit::ImDocument::parse(document.as_str()) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "invalid `#options` pragma: {}",
+                            err.message().trim_end().replace('\n', "; ")
+                        ),
+                        shift(err.span().unwrap_or(0..0)),
+                    )
+                    .with_code(codes::INVALID_CONFIG),
+                );
+                continue;
+            }
+        };
+
+        let Some(table) = parsed
+            .get("options")
+            .and_then(|item| item.as_inline_table())
+        else {
+            continue;
+        };
+
+        for (key, value) in table.iter() {
+            let key_span = table
+                .key(key)
+                .and_then(|key| key.span())
+                .map_or(Span::new(offset, offset), shift);
+            let value_span = value.span().map_or(key_span, shift);
+
+            let entry = parse(&format!(
+                "{} = {}",
+                toml_edit::Key::new(key),
+                value.to_string().trim()
+            ));
+
+            if !MODULE_OPTIONS.contains(&key) {
+                let unknown = entry.as_ref().is_ok_and(|entry| {
+                    entry
+                        .diagnostics
+                        .iter()
+                        .any(|diagnostic| diagnostic.code == Some(codes::UNKNOWN_CONFIG_KEY.id))
+                });
+
+                let diagnostic = if unknown {
+                    Diagnostic::warning(format!("unknown option `{key}`"), key_span)
+                        .with_code(codes::UNKNOWN_CONFIG_KEY)
+                } else {
+                    Diagnostic::error(format!("`{key}` can't be set by a module"), key_span)
+                        .with_code(codes::MODULE_OPTION)
+                };
+
+                diagnostics
+                    .push(diagnostic.with_note("a module can set `strict`, `target` and `lints`"));
+                continue;
+            }
+
+            match entry {
+                Ok(mut entry) => {
+                    diagnostics.extend(std::mem::take(&mut entry.diagnostics).into_iter().map(
+                        |diagnostic| Diagnostic {
+                            span: key_span,
+                            ..diagnostic
+                        },
+                    ));
+                    config = config.merge(entry);
+                }
+                Err(diagnostic) => diagnostics.push(Diagnostic {
+                    span: value_span,
+                    ..diagnostic
+                }),
+            }
+        }
+    }
+
+    (config, diagnostics)
+}
+
+/// The lists of the `#options(...)` pragmas at the top of `source`, with their offsets, reporting
+/// those after its first statement, which are ignored.
+fn option_pragmas<'a>(source: &'a str, diagnostics: &mut Vec<Diagnostic>) -> Vec<(&'a str, usize)> {
+    let (pragmas, header_end) = parser::header_pragmas(source);
+
+    for (offset, line) in trivia::pragma_lines(source, "#options") {
+        if offset >= header_end {
+            diagnostics.push(
+                Diagnostic::error(
+                    "`#options` pragma after a statement",
+                    Span::new(offset, offset + line.trim_end().len()),
+                )
+                .with_note("a module sets its options at its top, before its first statement")
+                .with_code(codes::INVALID_CONFIG),
+            );
+        }
+    }
+
+    pragmas
+        .into_iter()
+        .filter_map(|(offset, pragma)| {
+            let (list, _) = pragma
+                .strip_prefix("#options")?
+                .trim_start()
+                .strip_prefix('(')?
+                .rsplit_once(')')?;
+
+            Some((list, offset + pragma.find('(')? + 1))
+        })
+        .collect()
+}
+
+/// The keys leading to an ignored value.
+fn keys(path: &serde_ignored::Path) -> Vec<String> {
+    use serde_ignored::Path;
+
+    match path {
+        Path::Root => vec![],
+        Path::Seq { parent, index } => {
+            let mut keys = keys(parent);
+            keys.push(index.to_string());
+            keys
+        }
+        Path::Map { parent, key } => {
+            let mut keys = keys(parent);
+            keys.push(key.clone());
+            keys
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => keys(parent),
+    }
+}
+
+/// Span of the last key of `path`, or an empty span if it can't be found.
+fn key_span(document: &toml_edit::ImDocument<&str>, path: &[String]) -> Span {
+    let mut table: &dyn toml_edit::TableLike = document.as_table();
+    let mut span = None;
+
+    for key in path {
+        let Some((key, item)) = table.get_key_value(key) else {
+            break;
+        };
+
+        span = key.span();
+
+        match item.as_table_like() {
+            Some(inner) => table = inner,
+            None => break,
+        }
+    }
+
+    span.map_or(Span::new(0, 0), |span| Span::new(span.start, span.end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compile::compile, diagnostic::Severity, source::SourceFile, test_support::temp_dir,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    #[test]
+    fn discovered_in_an_ancestor() {
+        let root = temp_dir();
+        let nested = root.join("a").join("b");
+
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(FILE_NAME), "width = 40\n").unwrap();
+
+        let (path, config) = load(&nested).unwrap();
+
+        assert_eq!(path, root.canonicalize().unwrap().join(FILE_NAME));
+        assert_eq!(config.width, Some(40));
+
+        std::fs::write(nested.join(FILE_NAME), "width = 60\n").unwrap();
+
+        let (path, config) = load(&nested).unwrap();
+
+        assert_eq!(path, nested.canonicalize().unwrap().join(FILE_NAME));
+        assert_eq!(config.width, Some(60));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn unreadable_file_is_a_diagnostic() {
+        let root = temp_dir();
+
+        std::fs::write(root.join(FILE_NAME), [0xff, 0xfe]).unwrap();
+
+        let (_, config) = load(&root).unwrap();
+
+        assert_eq!(config.width, None);
+        assert_eq!(
+            config.diagnostics[0].message,
+            "can't read the config file: stream did not contain valid UTF-8"
+        );
+        assert_eq!(config.diagnostics[0].severity, Severity::Error);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn flags_take_precedence() {
+        let file = parse(dedent!(
+            r#"
+            width = 40
+            emit = "dts"
+            merge_arms = true
+            brand = "string"
+            target = "4.7"
+            strict = true
+
+            [lints]
+            contextual_keyword = "deny"
+            confusable_names = "allow"
+            "#
+        ))
+        .unwrap();
+
+        let flags = Config {
+            emit: Some(OutputKind::Ts),
+            lints: [("confusable_names".to_string(), Level::Warn)].into(),
+            ..Default::default()
+        };
+
+        let options = file.merge(flags).compile_options();
+
+        assert_eq!(options.width, 40);
+        assert_eq!(options.output_kind, OutputKind::Ts);
+        assert!(!options.timings);
+        assert!(options.merge_arms);
+        assert_eq!(options.brand_encoding, BrandEncoding::String);
+        assert_eq!(options.target, TsVersion::new(4, 7));
+        assert!(options.strict);
+        assert_eq!(
+            options.lints,
+            [
+                ("confusable_names".to_string(), Level::Warn),
+                ("contextual_keyword".to_string(), Level::Deny),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn prettier_compat_is_80_columns_wide() {
+        let options = parse("prettier_compat = true\nquote = \"single\"\n")
+            .unwrap()
+            .compile_options();
+
+        assert_eq!(options.width, 80);
+        assert_eq!(options.style().indent, 2);
+        assert_eq!(options.style().quote, Quote::Single);
+
+        let options = parse("prettier_compat = true\nwidth = 100\n")
+            .unwrap()
+            .compile_options();
+
+        assert_eq!(options.width, 100);
+    }
+
+    #[test]
+    fn lint_levels() {
+        let compile_with = |config: &str| {
+            let options = parse(config).unwrap().compile_options();
+            compile("type A(declare) as declare", &options)
+        };
+
+        let diagnostics = compile_with("[lints]\ncontextual_keyword = \"deny\"").unwrap_err();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+
+        let output = compile_with("[lints]\ncontextual_keyword = \"allow\"").unwrap();
+
+        assert_eq!(output.diagnostics, vec![]);
+
+        let output = compile_with("").unwrap();
+
+        assert_eq!(output.diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn unknown_keys_are_warnings() {
+        let source = "width = 80\ncolour = true\n\n[lints]\nunused_binding = \"deny\"\n";
+
+        let config = parse(source).unwrap();
+
+        let rendered: Vec<_> = config
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(&SourceFile::new("", source)))
+            .collect();
+
+        assert_eq!(
+            rendered,
+            vec![
+                dedent!(
+                    "
+                    warning[DL0005]: unknown key `colour`
+                     --> 2:1
+                      |
+                    2 | colour = true
+                      | ^^^^^^
+                      = help: run `newtype explain DL0005` for more information"
+                )
+                .trim_start(),
+                dedent!(
+                    r#"
+                    warning[DL0006]: unknown lint `unused_binding`
+                     --> 5:1
+                      |
+                    5 | unused_binding = "deny"
+                      | ^^^^^^^^^^^^^^
+                      = note: the known lints are contextual_keyword, confusable_names, non_exhaustive_match, shadowed_global, constant_condition, explicit_any, single_use_alias, inconsistent_optionality
+                      = help: run `newtype explain DL0006` for more information"#
+                )
+                .trim_start(),
+            ]
+        );
+        assert_eq!(config.width, Some(80));
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let diagnostic = parse("width = \"wide\"").unwrap_err();
+
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!((diagnostic.span.start, diagnostic.span.end), (8, 14));
+    }
+
+    #[test]
+    fn module_options_override_a_subset() {
+        let source = dedent!(
+            r#"
+            #options(strict = true, target = "5.0", lints = {explicit_any = "deny"})
+            type A as 1
+            "#
+        );
+
+        let (config, diagnostics) = module_options(source);
+
+        assert_eq!(diagnostics, vec![]);
+        assert_eq!(
+            config,
+            Config {
+                strict: Some(true),
+                target: Some(TsVersion::new(5, 0)),
+                lints: [("explicit_any".to_string(), Level::Deny)].into(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn module_options_that_apply_to_the_build_are_errors() {
+        let source = "#options(strict = true, width = 80, colour = true)\ntype A as 1\n";
+
+        let (config, diagnostics) = module_options(source);
+
+        let rendered: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(&SourceFile::new("", source)))
+            .collect();
+
+        assert_eq!(
+            rendered,
+            vec![
+                dedent!(
+                    "
+                    error[DL0038]: `width` can't be set by a module
+                     --> 1:25
+                      |
+                    1 | #options(strict = true, width = 80, colour = true)
+                      |                         ^^^^^
+                      = note: a module can set `strict`, `target` and `lints`
+                      = help: run `newtype explain DL0038` for more information"
+                )
+                .trim_start(),
+                dedent!(
+                    "
+                    warning[DL0005]: unknown option `colour`
+                     --> 1:37
+                      |
+                    1 | #options(strict = true, width = 80, colour = true)
+                      |                                     ^^^^^^
+                      = note: a module can set `strict`, `target` and `lints`
+                      = help: run `newtype explain DL0005` for more information"
+                )
+                .trim_start(),
+            ]
+        );
+        assert_eq!(config.strict, Some(true));
+        assert_eq!(config.width, None);
+    }
+
+    #[test]
+    fn invalid_module_options() {
+        let source = "#options(target = \"five\")\n#options(strict = )\n";
+
+        let (_, diagnostics) = module_options(source);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    &source[diagnostic.span.start..diagnostic.span.end]
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "`five` is not a TypeScript version, such as `5.0`",
+                    "\"five\""
+                ),
+                (
+                    "invalid `#options` pragma: invalid string; expected `\"`, `'`",
+                    ""
+                ),
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code == Some(codes::INVALID_CONFIG.id)));
+    }
+
+    #[test]
+    fn module_options_only_at_the_top() {
+        let source = dedent!(
+            "
+            /** A module. */
+            #options(target = \"4.7\")
+            /*
+            #options(strict = true)
+            */
+            // #options(strict = true)
+            type A as '
+            #options(strict = true)'
+            #options(strict = true)
+            "
+        );
+
+        let (config, diagnostics) = module_options(source);
+
+        assert_eq!(config.target, Some(TsVersion::new(4, 7)));
+        assert_eq!(config.strict, None);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    &source[diagnostic.span.start..diagnostic.span.end]
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`#options` pragma after a statement",
+                "#options(strict = true)"
+            )]
+        );
+    }
+
+    #[test]
+    fn hoist_threshold_below_two() {
+        let source = "width = 80\nhoist = 1\n";
+
+        let diagnostic = parse(source).unwrap_err();
+
+        assert_eq!(diagnostic.message, "`hoist` is 1, it must be at least 2");
+        assert_eq!(&source[diagnostic.span.start..diagnostic.span.end], "hoist");
+        assert_eq!(parse("hoist = 2").unwrap().hoist, Some(2));
+    }
+}
@@ -0,0 +1,414 @@
+/// Compiler diagnostics and their terminal rendering.
+use std::fmt::Display;
+
+use crate::{
+    ast::{origin::OriginId, Span},
+    source::{FileId, Origin, SourceFile},
+};
+
+use codes::Code;
+
+pub mod baseline;
+pub mod codes;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Byte range of the source the diagnostic points at. May be empty, in which case a single
+    /// caret is drawn at its start.
+    pub span: Span,
+    pub notes: Vec<String>,
+    /// Name of the lint that reported the diagnostic, whose level can be configured.
+    pub lint: Option<&'static str>,
+    /// Id of the [`Code`] explaining the diagnostic.
+    pub code: Option<&'static str>,
+    /// A fix that can be applied without looking, by `newtype fix`. Boxed, few diagnostics have
+    /// one.
+    pub suggestion: Option<Box<Suggestion>>,
+    /// The file of a project the span is in, in the project's
+    /// [`SourceMap`](crate::source::SourceMap). `None` for the one file being compiled.
+    pub file: Option<FileId>,
+}
+
+/// A mechanical fix of the problem a diagnostic reports: `replacement` replaces the text at
+/// `span`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+    /// Whether `span` is the name of a declaration that is renamed along with every reference to
+    /// it, rather than text replaced on its own.
+    pub rename: bool,
+}
+
+impl Suggestion {
+    pub fn replace(span: Span, replacement: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            replacement: replacement.into(),
+            message: message.into(),
+            rename: false,
+        }
+    }
+
+    /// Renames the declaration named at `span` to `new_name`.
+    pub fn rename(span: Span, new_name: impl Into<String>) -> Self {
+        let new_name = new_name.into();
+
+        Self {
+            span,
+            message: format!("rename it to `{new_name}`"),
+            replacement: new_name,
+            rename: true,
+        }
+    }
+}
+
+/// How diagnostics are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// An excerpt of the source pointing at the problem.
+    #[default]
+    Human,
+    /// A JSON object per line.
+    Json,
+}
+
+impl Diagnostic {
+    /// A diagnostic at `span`, without its origin, which it tells in its notes instead, see
+    /// [`Diagnostic::with_origin`].
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            span: Span::new(span.start, span.end),
+            notes: vec![],
+            lint: None,
+            code: None,
+            suggestion: None,
+            file: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, message, span)
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Notes telling where the node the diagnostic points at comes from, the backtrace of
+    /// `origin` if the simplifier built it.
+    pub fn with_origin(mut self, origin: Option<OriginId>) -> Self {
+        self.notes.extend(
+            origin
+                .into_iter()
+                .flat_map(OriginId::backtrace)
+                .map(|origin| origin.to_string()),
+        );
+        self
+    }
+
+    pub fn with_lint(mut self, lint: &'static str) -> Self {
+        self.lint = Some(lint);
+        self
+    }
+
+    pub fn with_code(mut self, code: Code) -> Self {
+        self.code = Some(code.id);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// The diagnostic as a JSON object, with the file, line and column its span starts at, see
+    /// [`SourceFile::origin`].
+    pub fn to_json(&self, file: &SourceFile) -> serde_json::Value {
+        let Origin { file, line, column } = file.origin(self.span.start);
+
+        serde_json::json!({
+            "file": file,
+            "severity": self.severity.to_string(),
+            "message": self.message,
+            "code": self.code,
+            "has_explanation": self.code.is_some_and(codes::has_explanation),
+            "span": {"start": self.span.start, "end": self.span.end},
+            "line": line,
+            "column": column,
+            "notes": self.notes,
+            "suggestion": self.suggestion.as_ref().map(|suggestion| serde_json::json!({
+                "span": {"start": suggestion.span.start, "end": suggestion.span.end},
+                "replacement": suggestion.replacement,
+                "message": suggestion.message,
+                "rename": suggestion.rename,
+            })),
+        })
+    }
+
+    /// Renders the diagnostic with an excerpt of the line it points at, e.g.
+    ///
+    /// ```text
+    /// error[DL0001]: expected `then` or an operator, found `1`
+    ///  --> 1:21
+    ///   |
+    /// 1 | type A as if a <: b 1 end
+    ///   |                     ^
+    ///   = help: run `newtype explain DL0001` for more information
+    /// ```
+    ///
+    /// The position is the one the span comes from, see [`SourceFile::origin`], with the text of
+    /// the line of `file` it is on.
+    pub fn render(&self, file: &SourceFile) -> String {
+        let (line, _) = file.line_col(self.span.start);
+        let text = file.line(line);
+
+        let Origin {
+            file: name,
+            line,
+            column,
+        } = file.origin(self.span.start);
+
+        // Spans covering several lines are underlined up to the end of their first line.
+        let width = file
+            .snippet(self.span.start..self.span.end)
+            .lines()
+            .next()
+            .map_or(0, |s| s.chars().count())
+            .max(1);
+
+        let gutter = " ".repeat(line.to_string().len());
+
+        let mut out = match self.code {
+            Some(code) => format!("{}[{code}]: {}\n", self.severity, self.message),
+            None => format!("{}: {}\n", self.severity, self.message),
+        };
+
+        out += &match name {
+            "" => format!("{gutter}--> {line}:{column}\n"),
+            name => format!("{gutter}--> {name}:{line}:{column}\n"),
+        };
+        out += &format!("{gutter} |\n");
+        out += &format!("{line} | {text}\n");
+        out += &format!("{gutter} | {}{}", " ".repeat(column - 1), "^".repeat(width));
+
+        for note in &self.notes {
+            out += &format!("\n{gutter} = note: {note}");
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            out += &format!("\n{gutter} = help: {}", suggestion.message);
+        }
+
+        if let Some(code) = self.code.filter(|code| codes::has_explanation(code)) {
+            out += &format!("\n{gutter} = help: run `newtype explain {code}` for more information");
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    #[test]
+    fn render_points_at_span() {
+        let source = "type A as 1\ntype B as if a then b end\n";
+
+        let diagnostic = Diagnostic::error("expected `<:`", Span::new(27, 31))
+            .with_note("conditions compare two types");
+
+        assert_eq!(
+            diagnostic.render(&SourceFile::new("", source)),
+            dedent!(
+                "
+                error: expected `<:`
+                 --> 2:16
+                  |
+                2 | type B as if a then b end
+                  |                ^^^^
+                  = note: conditions compare two types"
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn render_refers_to_the_explanation() {
+        let diagnostic = Diagnostic::warning("`l` is easily confused with `I`", Span::new(9, 10))
+            .with_code(codes::CONFUSABLE_NAMES);
+
+        assert_eq!(
+            diagnostic.render(&SourceFile::new("", "type A(I, l) as l")),
+            dedent!(
+                "
+                warning[DL0004]: `l` is easily confused with `I`
+                 --> 1:10
+                  |
+                1 | type A(I, l) as l
+                  |          ^
+                  = help: run `newtype explain DL0004` for more information"
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn render_names_the_file() {
+        let diagnostic = Diagnostic::error("`new` is a reserved word", Span::new(17, 20));
+
+        assert_eq!(
+            diagnostic.render(&SourceFile::new("src/a.dl", "type A as 1\ntype new as 1")),
+            dedent!(
+                "
+                error: `new` is a reserved word
+                 --> src/a.dl:2:6
+                  |
+                2 | type new as 1
+                  |      ^^^"
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn render_names_the_template() {
+        let source = dedent!(
+            r#"
+            #source "templates/user.tmpl" line 42
+            type User as {
+                id: frob!(number),
+            #source "templates/fields.tmpl" line 3
+                name: frob!(string),
+            }
+            "#
+        );
+        let file = SourceFile::new("generated.dl", source);
+
+        // The alias starts in one template and ends in another, each of its parts is reported in
+        // the template it comes from.
+        let excerpts: Vec<_> = compile(source, &CompileOptions::default())
+            .unwrap_err()
+            .iter()
+            .map(|diagnostic| {
+                diagnostic
+                    .render(&file)
+                    .lines()
+                    .skip(1)
+                    .take(4)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect();
+
+        assert_eq!(
+            excerpts,
+            vec![
+                dedent!(
+                    "
+                      --> templates/user.tmpl:43:9
+                       |
+                    43 |     id: frob!(number),
+                       |         ^^^^^^^^^^^^^"
+                )
+                .trim_start_matches('\n'),
+                dedent!(
+                    "
+                     --> templates/fields.tmpl:3:11
+                      |
+                    3 |     name: frob!(string),
+                      |           ^^^^^^^^^^^^^"
+                )
+                .trim_start_matches('\n'),
+            ]
+        );
+        assert_eq!(
+            compile(source, &CompileOptions::default()).unwrap_err()[1].to_json(&file)["file"],
+            "templates/fields.tmpl"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let diagnostic = Diagnostic::error("`new` is a reserved word", Span::new(5, 8))
+            .with_code(codes::RESERVED_NAME);
+
+        assert_eq!(
+            diagnostic.to_json(&SourceFile::new("", "type new as 1")),
+            serde_json::json!({
+                "file": "",
+                "severity": "error",
+                "message": "`new` is a reserved word",
+                "code": "DL0002",
+                "has_explanation": true,
+                "span": {"start": 5, "end": 8},
+                "line": 1,
+                "column": 6,
+                "notes": [],
+                "suggestion": null,
+            })
+        );
+
+        let diagnostic = Diagnostic::error("unexpected", Span::new(0, 0));
+
+        assert_eq!(
+            diagnostic.to_json(&SourceFile::new("", ""))["has_explanation"],
+            false
+        );
+    }
+
+    #[test]
+    fn suggestion() {
+        let diagnostic = Diagnostic::error("`new` is a reserved word", Span::new(5, 8))
+            .with_suggestion(Suggestion::rename(Span::new(5, 8), "new_"));
+
+        assert_eq!(
+            diagnostic.to_json(&SourceFile::new("", "type new as 1"))["suggestion"],
+            serde_json::json!({
+                "span": {"start": 5, "end": 8},
+                "replacement": "new_",
+                "message": "rename it to `new_`",
+                "rename": true,
+            })
+        );
+        assert!(diagnostic
+            .render(&SourceFile::new("", "type new as 1"))
+            .ends_with("= help: rename it to `new_`"));
+    }
+}
@@ -0,0 +1,240 @@
+/// Baselines of the diagnostics a codebase already has, so that `newtype check --baseline` only
+/// fails on new ones while the old ones are fixed at their own pace.
+///
+/// An entry identifies a diagnostic by its code, its file, and a hash of the source it points at,
+/// not by its line, so that editing another part of the file doesn't make it stale. Two
+/// diagnostics with the same entry are told apart by their number: an entry recorded twice
+/// suppresses two of them.
+use std::{collections::HashMap, hash::Hasher};
+
+use serde_derive::{Deserialize, Serialize};
+
+use super::Diagnostic;
+use crate::{ast::structural::Fnv1a, source::SourceFile};
+
+/// Version of the format, written to the file and checked when reading it.
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Entry {
+    pub file: String,
+    /// Code of the diagnostic, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// FNV-1a hash of the text the diagnostic points at, in hexadecimal.
+    pub hash: String,
+    /// Message of the diagnostic when it was recorded, for the reader of the file only.
+    pub message: String,
+}
+
+impl Entry {
+    pub fn new(file: &SourceFile, diagnostic: &Diagnostic) -> Self {
+        let mut hasher = Fnv1a::default();
+        hasher.write(
+            file.snippet(diagnostic.span.start..diagnostic.span.end)
+                .as_bytes(),
+        );
+
+        Self {
+            file: file.name.clone(),
+            code: diagnostic.code.map(str::to_string),
+            hash: format!("{:016x}", hasher.finish()),
+            message: diagnostic.message.clone(),
+        }
+    }
+
+    /// What a diagnostic is matched on, everything but the message.
+    fn key(&self) -> (String, Option<String>, String) {
+        (self.file.clone(), self.code.clone(), self.hash.clone())
+    }
+}
+
+impl Baseline {
+    /// A baseline of every diagnostic, with the file it is in.
+    pub fn new<'a>(
+        diagnostics: impl IntoIterator<Item = (&'a SourceFile, &'a Diagnostic)>,
+    ) -> Self {
+        let mut entries: Vec<Entry> = diagnostics
+            .into_iter()
+            .map(|(file, diagnostic)| Entry::new(file, diagnostic))
+            .collect();
+
+        // Sorted, the file changes as little as the diagnostics do.
+        entries.sort();
+
+        Self {
+            version: VERSION,
+            entries,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let baseline: Self =
+            serde_json::from_str(json).map_err(|err| format!("invalid baseline: {err}"))?;
+
+        if baseline.version != VERSION {
+            return Err(format!(
+                "baseline version {} isn't supported, record it again with `--write-baseline`",
+                baseline.version
+            ));
+        }
+
+        Ok(baseline)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("baselines serialize") + "\n"
+    }
+
+    /// Removes the diagnostics of the baseline from `diagnostics`, returning the entries that no
+    /// longer match one, whose problem was fixed.
+    pub fn apply<'a, T>(
+        &self,
+        diagnostics: &mut Vec<T>,
+        diagnostic: impl Fn(&T) -> (&'a SourceFile, &Diagnostic),
+    ) -> Vec<Entry> {
+        let mut remaining: HashMap<_, usize> = HashMap::new();
+
+        for entry in &self.entries {
+            *remaining.entry(entry.key()).or_default() += 1;
+        }
+
+        diagnostics.retain(|item| {
+            let (file, diagnostic) = diagnostic(item);
+            let entry = Entry::new(file, diagnostic);
+
+            match remaining.get_mut(&entry.key()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+
+        self.entries
+            .iter()
+            .filter(|entry| match remaining.get_mut(&entry.key()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+
+    fn check(name: &str, source: &str) -> (SourceFile, Vec<Diagnostic>) {
+        let diagnostics = compile(source, &CompileOptions::default())
+            .unwrap()
+            .diagnostics;
+
+        (SourceFile::new(name, source), diagnostics)
+    }
+
+    fn baseline(file: &SourceFile, diagnostics: &[Diagnostic]) -> Baseline {
+        Baseline::new(diagnostics.iter().map(|diagnostic| (file, diagnostic)))
+    }
+
+    fn unmatched(
+        baseline: &Baseline,
+        file: &SourceFile,
+        diagnostics: Vec<Diagnostic>,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut diagnostics = diagnostics;
+        let stale = baseline.apply(&mut diagnostics, |diagnostic| (file, diagnostic));
+
+        (
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect(),
+            stale.into_iter().map(|entry| entry.message).collect(),
+        )
+    }
+
+    #[test]
+    fn old_warnings_are_suppressed() {
+        let (file, diagnostics) = check("a.dl", "type A(l, I) as l");
+        let recorded = baseline(&file, &diagnostics);
+
+        assert_eq!(recorded.entries.len(), 1);
+
+        // Lines added above don't move the entry.
+        let (file, diagnostics) = check("a.dl", "type B as 1\n\ntype A(l, I) as l");
+
+        assert_eq!(unmatched(&recorded, &file, diagnostics), (vec![], vec![]));
+    }
+
+    #[test]
+    fn new_warnings_are_reported() {
+        let (file, diagnostics) = check("a.dl", "type A(l, I) as l");
+        let recorded = baseline(&file, &diagnostics);
+
+        // The entry suppresses one of the two diagnostics it matches.
+        let (file, diagnostics) = check("a.dl", "type A(l, I) as l\ntype B(l, I) as l");
+
+        assert_eq!(
+            unmatched(&recorded, &file, diagnostics),
+            (vec!["`I` is easily confused with `l`".to_string()], vec![])
+        );
+
+        // The same problem in another file isn't the recorded one.
+        let (file, diagnostics) = check("b.dl", "type A(l, I) as l");
+
+        assert_eq!(
+            unmatched(&recorded, &file, diagnostics),
+            (
+                vec!["`I` is easily confused with `l`".to_string()],
+                vec!["`I` is easily confused with `l`".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn fixed_problems_leave_stale_entries() {
+        let (file, diagnostics) = check("a.dl", "type A(l, I) as l\ntype B(l, I) as I");
+        let recorded = baseline(&file, &diagnostics);
+
+        assert_eq!(recorded.entries.len(), 2);
+
+        let (file, diagnostics) = check("a.dl", "type A(l, I) as l\ntype B(T) as T");
+
+        assert_eq!(
+            unmatched(&recorded, &file, diagnostics),
+            (vec![], vec!["`I` is easily confused with `l`".to_string()])
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let (file, diagnostics) = check("a.dl", "type A(l, I) as l");
+        let recorded = baseline(&file, &diagnostics);
+
+        assert_eq!(Baseline::from_json(&recorded.to_json()), Ok(recorded));
+        assert_eq!(
+            Baseline::from_json(r#"{"version": 0, "entries": []}"#),
+            Err(
+                "baseline version 0 isn't supported, record it again with `--write-baseline`"
+                    .to_string()
+            )
+        );
+        assert!(Baseline::from_json("[]")
+            .unwrap_err()
+            .starts_with("invalid baseline:"));
+    }
+}
@@ -0,0 +1,1173 @@
+/// A diagnostic code and its long-form explanation, printed by `newtype explain <code>`. Every
+/// code is declared here together with its explanation and listed in [`CODES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Code {
+    pub id: &'static str,
+    pub explanation: &'static str,
+}
+
+pub const SYNTAX_ERROR: Code = Code {
+    id: "DL0001",
+    explanation: r#"The source does not follow the grammar of the language.
+
+The message lists what was expected where the parser stopped.
+
+Erroneous code example:
+
+    type A as if a <: b 1 end
+
+A conditional needs a `then` branch:
+
+    type A as if a <: b then 1 end
+
+TypeScript syntax written out of habit gets a message of its own, with the
+rewrite to the language's syntax where it is mechanical: `=` declaring an alias
+rather than `as`, `extends` testing a condition rather than `<:`, a `? :`
+conditional rather than `if ... then ... else ... end`, and `;` separating
+properties rather than `,`.
+"#,
+};
+
+pub const RESERVED_NAME: Code = Code {
+    id: "DL0002",
+    explanation: r#"A declaration is named after a word TypeScript reserves.
+
+Reserved words can't name a type alias, an interface or a type parameter in
+TypeScript, so the output would not compile.
+
+Erroneous code example:
+
+    type new as 1
+
+Rename the declaration:
+
+    type New as 1
+"#,
+};
+
+pub const CONTEXTUAL_KEYWORD: Code = Code {
+    id: "DL0003",
+    explanation: r#"A declaration is named after a TypeScript contextual keyword.
+
+Contextual keywords such as `declare`, `infer` or `type` are only keywords in
+some positions, so they are valid names. They are easily misread in the
+emitted TypeScript though.
+
+Example:
+
+    type A(declare) as declare
+
+Consider a name that is not a keyword:
+
+    type A(Declaration) as Declaration
+
+The `contextual_keyword` lint reports this diagnostic, its level can be set in
+the `[lints]` table of `definitelylisp.toml`.
+"#,
+};
+
+pub const CONFUSABLE_NAMES: Code = Code {
+    id: "DL0004",
+    explanation: r#"Two names declared in the same scope look alike.
+
+Names are compared by their confusable skeletons as defined by Unicode TR39,
+e.g. a Latin "a" and a Cyrillic "а", or `l` and `I`, have the same skeleton.
+Readers can't tell such names apart.
+
+Example:
+
+    type A(l, I) as l
+
+Use names that are distinct at a glance:
+
+    type A(Left, Item) as Left
+
+The `confusable_names` lint reports this diagnostic, its level can be set in
+the `[lints]` table of `definitelylisp.toml`.
+"#,
+};
+
+pub const UNKNOWN_CONFIG_KEY: Code = Code {
+    id: "DL0005",
+    explanation: r#"`definitelylisp.toml` contains a key that is not an option.
+
+The key is ignored. This is usually a misspelling.
+
+Erroneous config example:
+
+    colour = true
+
+The options are `width`, `emit`, `timings`, `merge_arms`, `extract_exclude`,
+`hoist`, `brand`, `only_tag`, `features`, `flags`, `target`, `strict`,
+`prettier_compat`, `quote` and the `[lints]` table:
+
+    width = 100
+    emit = "dts"
+"#,
+};
+
+pub const UNKNOWN_LINT: Code = Code {
+    id: "DL0006",
+    explanation: r#"The `[lints]` table of `definitelylisp.toml` names a lint that does not
+exist.
+
+The level is ignored.
+
+Erroneous config example:
+
+    [lints]
+    contextual_keywords = "deny"
+
+Use the name of a lint, the note lists all of them:
+
+    [lints]
+    contextual_keyword = "deny"
+"#,
+};
+
+pub const INVALID_CONFIG: Code = Code {
+    id: "DL0007",
+    explanation: r#"`definitelylisp.toml` is not valid TOML, or an option has a value of the
+wrong type.
+
+Nothing is compiled until the config is fixed.
+
+Erroneous config example:
+
+    width = "wide"
+
+The width is a number of columns:
+
+    width = 100
+"#,
+};
+
+pub const IMPORT_SYNTAX_ERROR: Code = Code {
+    id: "DL0008",
+    explanation: r#"A file given to `newtype import` is not valid in its own language.
+
+The importers have parsers of their own, which stop at the first error.
+
+Erroneous TypeScript example, for `newtype import dts`:
+
+    type A = { a: string;
+
+Close the object type:
+
+    type A = { a: string };
+"#,
+};
+
+pub const UNSUPPORTED_CONSTRUCT: Code = Code {
+    id: "DL0009",
+    explanation: r#"A file given to `newtype import` uses a construct the importer doesn't
+translate.
+
+The importers only cover the type definitions of their language. Values,
+namespaces, classes or GraphQL operations have no counterpart.
+
+Erroneous TypeScript example, for `newtype import dts`:
+
+    declare const a: string;
+
+Import a file containing only the type aliases and interfaces:
+
+    type A = string;
+"#,
+};
+
+pub const UNMAPPED_SCALAR: Code = Code {
+    id: "DL0010",
+    explanation: r#"A GraphQL schema declares a custom scalar without a type for it.
+
+Custom scalars are opaque to the schema, so the importer needs to be told the
+type they are serialized as.
+
+Erroneous schema example:
+
+    scalar Date
+
+Map the scalar to a type with `--scalar`:
+
+    newtype import graphql schema.graphql --scalar Date=string
+"#,
+};
+
+pub const COMPLEXITY_LIMIT: Code = Code {
+    id: "DL0011",
+    explanation: r#"An exported alias goes over a limit given to `newtype stats --limit`.
+
+Deeply nested conditional types slow down the type checker of every project
+using them. `newtype stats` measures the simplified body of each exported alias:
+
+- `nodes`, the size of the type,
+- `depth`, the deepest nesting of conditional types, every arm of a `match`
+  or `cond` nests one level deeper,
+- `infer`, the number of inferred types,
+- `instantiation`, the deepest nesting of conditional types and generic
+  applications.
+
+Erroneous code example, with `--limit depth=2`:
+
+    export type A(T) as match T do 1 -> :a, 2 -> :b, else -> :c end
+
+Split the alias, or look up the result instead of testing each case:
+
+    type Names as {1: :a, 2: :b}
+    export type A(T) as if T <: keyof(Names) then Names[T] else :c end
+"#,
+};
+
+pub const INVALID_MACRO_ARGUMENT: Code = Code {
+    id: "DL0012",
+    explanation: r#"A macro is called with arguments it can't be applied to.
+
+`merge!` takes two object types and merges their properties, the properties of
+the second one taking precedence. Other literals have no properties to merge.
+
+Erroneous code example:
+
+    type A as merge!({a: 1}, 1)
+
+Pass object types:
+
+    type A as merge!({a: 1}, {b: 2})
+"#,
+};
+
+pub const NON_EXHAUSTIVE_MATCH: Code = Code {
+    id: "DL0013",
+    explanation: r#"A `match` over the variants of a union doesn't cover all of them.
+
+A `match` without an `else` arm is `never` for the types none of its arms
+match, so the variants left out silently become `never`.
+
+Example:
+
+    union Shape = Circle {radius: number} | Square {size: number}
+    type Sides(S) as match S do Circle -> 0 end
+
+Match every variant, or add an `else` arm:
+
+    union Shape = Circle {radius: number} | Square {size: number}
+    type Sides(S) as match S do Circle -> 0, Square -> 4 end
+
+A type parameter constrained to a union of literals is matched against each
+of them in the same way:
+
+    type Size(S) where S <: :small | :large as match S do :small -> 1 end
+
+A match on several values compares them as a tuple, which doesn't distribute
+over unions: `Pair(:a | :b, 1)` only matches an arm covering both `:a` and
+`:b`. Unless one arm covers every combination of the constraints, their unions
+fall through:
+
+    type Pair(T, U) where T <: :a | :b, U <: 1 | 2 as
+      match T, U do (:a, _) -> 1, (:b, _) -> 2 end
+
+A pattern of more or fewer values than the match is on never matches, and is
+reported as well.
+
+The `non_exhaustive_match` lint reports this diagnostic, its level can be set in
+the `[lints]` table of `definitelylisp.toml`.
+
+With `--strict`, a match over a type parameter constrained to a union of
+literals must cover it with its patterns. Leaving members to an `else` or `_`
+arm is an error, so that a member added to the constraint is reported:
+
+    type Size(S) where S <: :small | :large as match S do :small -> 1, else -> 2 end
+"#,
+};
+
+pub const UNKNOWN_MACRO: Code = Code {
+    id: "DL0014",
+    explanation: r#"A macro is called that is not registered.
+
+The builtin macros are `all!`, `any!`, `assert_equal!`, `dbg!`,
+`deep_partial!`, `deep_readonly!`, `index_by!`, `keys_of!`, `merge!`,
+`mutable!`, `unquote!` and `values_of!`. Programs embedding the compiler can
+register macros of their own, the note lists every macro that is registered.
+
+Erroneous code example:
+
+    type A as deep_required!({a: 1})
+
+Call a registered macro:
+
+    type A as deep_partial!({a: 1})
+"#,
+};
+
+pub const UNSATISFIED_CONSTRAINT: Code = Code {
+    id: "DL0015",
+    explanation: r#"An argument doesn't satisfy the constraint of its type parameter.
+
+Only literal keys of object types are checked. The constraint of a parameter
+can mention the parameters before it, which stand for their arguments.
+
+The default of a parameter is checked against its constraint in the same
+way, when both are literals or primitives:
+
+    type Size(S) defaults S = :medium where S <: :small | :large as S
+
+Erroneous code example:
+
+    type Get(T, K) where K <: keyof(T) as T[K]
+    type A as Get({a: 1}, :b)
+
+Pass a key of the object:
+
+    type Get(T, K) where K <: keyof(T) as T[K]
+    type A as Get({a: 1}, :a)
+"#,
+};
+
+pub const SHADOWED_GLOBAL: Code = Code {
+    id: "DL0016",
+    explanation: r#"A declaration is named after a type of the TypeScript standard library.
+
+The emitted declaration shadows the global type in its module, so any other
+type of the module meaning the global, e.g. `Record` or `Partial`, refers to the
+declaration instead.
+
+Example:
+
+    type Partial as 1
+
+Rename the declaration:
+
+    type PartialResult as 1
+
+Or, when the global is not needed in the module, allow the shadowing with a
+comment directly above the declaration:
+
+    // allow(shadowed_global)
+    type Partial as 1
+
+The `shadowed_global` lint reports this diagnostic, its level can be set in
+the `[lints]` table of `definitelylisp.toml`.
+"#,
+};
+
+pub const MISPLACED_THIS: Code = Code {
+    id: "DL0017",
+    explanation: r#"A `this` type is used outside of a method.
+
+`this` is the type of the object a method is called on, so it is only valid in
+the methods of an interface or an object type, in their parameters or return
+type.
+
+Erroneous code example:
+
+    type A as this
+
+Use it in a method:
+
+    interface Builder {add: (x: number) => this}
+"#,
+};
+
+pub const INVALID_DEFAULT_EXPORT: Code = Code {
+    id: "DL0018",
+    explanation: r#"A module has more than one default export, or exports by default a name
+it doesn't declare.
+
+A default export is declared with `export default type`, `export default
+interface`, or `export default` followed by the name of a declaration or an
+import of the module.
+
+Erroneous code example:
+
+    export default type A as 1
+    export default type B as 2
+
+Export one of them by name:
+
+    export default type A as 1
+    export type B as 2
+"#,
+};
+
+pub const INVALID_TYPE_ARGUMENTS: Code = Code {
+    id: "DL0019",
+    explanation: r#"A generic type is applied to too few or too many type arguments.
+
+Every parameter without a default takes an argument, a rest parameter takes
+any number of them. Types imported as a namespace are checked too when the
+module is part of the project.
+
+Erroneous code example:
+
+    type Pair(A, B) as [A, B]
+    type P as Pair(1)
+
+Pass an argument for every parameter:
+
+    type Pair(A, B) as [A, B]
+    type P as Pair(1, 2)
+"#,
+};
+
+pub const INVALID_PATTERN_SYNONYM: Code = Code {
+    id: "DL0020",
+    explanation: r#"A pattern synonym is used with too few or too many arguments, or is
+declared in terms of itself.
+
+A use of a synonym is replaced by its body, each parameter by the argument
+in its position, so it takes an argument for every parameter.
+
+Erroneous code example:
+
+    #feature(pattern_synonyms)
+    pattern Boxed(T) = {value: T}
+    type A(B) as match B do Boxed(?V, ?W) -> V, else -> never end
+
+Pass an argument for every parameter:
+
+    #feature(pattern_synonyms)
+    pattern Boxed(T) = {value: T}
+    type A(B) as match B do Boxed(?V) -> V, else -> never end
+"#,
+};
+
+pub const EMIT_TAG_CONFLICT: Code = Code {
+    id: "DL0021",
+    explanation: r#"A declaration emitted to an output uses a declaration that isn't.
+
+With `--only-tag`, only the declarations tagged with `@emit` for that output
+are emitted, together with the untagged ones. A declaration can't use one
+tagged for other outputs only, the output would refer to a missing type.
+
+Erroneous code example:
+
+    @emit("server")
+    type Secret as string
+    @emit("client")
+    type Form as {secret: Secret}
+
+Emit the declaration it uses to the same output:
+
+    @emit("client", "server")
+    type Secret as string
+    @emit("client")
+    type Form as {secret: Secret}
+"#,
+};
+
+pub const UNKNOWN_ATTRIBUTE: Code = Code {
+    id: "DL0022",
+    explanation: r#"An attribute is not read by any pass of the compiler.
+
+Attributes are written on the lines before a statement, for the passes of
+the compiler claiming their name to read. An attribute with another name,
+usually misspelled, would be ignored.
+
+Erroneous code example:
+
+    @deprecate("use B")
+    type A as 1
+
+Use the name of a known attribute, such as `@deprecated` or `@emit`:
+
+    @deprecated("use B")
+    type A as 1
+"#,
+};
+
+pub const FEATURE_NOT_ENABLED: Code = Code {
+    id: "DL0023",
+    explanation: r#"An unstable construct is used without enabling its feature.
+
+Constructs whose design may still change are only available once their
+feature is enabled, by a `#feature(name)` line in the source or by the
+`features` option of `definitelylisp.toml`.
+
+Erroneous code example:
+
+    type A(T) as if T <:! string then 1 else 2 end
+
+Enable the feature:
+
+    #feature(non_distributive)
+    type A(T) as if T <:! string then 1 else 2 end
+"#,
+};
+
+pub const UNKNOWN_FEATURE: Code = Code {
+    id: "DL0024",
+    explanation: r#"A `#feature` line or the `features` option names a feature that doesn't exist.
+
+The name is ignored. The features are `pattern_synonyms` and
+`non_distributive`.
+
+Erroneous code example:
+
+    #feature(variadics)
+    type A as 1
+"#,
+};
+
+pub const CONSTANT_CONDITION: Code = Code {
+    id: "DL0025",
+    explanation: r#"A condition compares literals or primitives, so it always holds or never
+does.
+
+The branch the condition can't take is never taken, which is usually a typo,
+such as a literal written in place of a type parameter.
+
+Example:
+
+    type A(T) as if :a <: string then T else never end
+
+Compare the type parameter:
+
+    type A(T) as if T <: string then T else never end
+
+Conditions mentioning a name or inferring a type are not reported, they
+depend on what they are applied to.
+
+The `constant_condition` lint reports this diagnostic, its level can be set in
+the `[lints]` table of `definitelylisp.toml`.
+"#,
+};
+
+pub const UNSUPPORTED_BY_TARGET: Code = Code {
+    id: "DL0026",
+    explanation: r#"A construct needs a newer version of TypeScript than the target.
+
+The version the output is written for is set by the `target` option of
+`definitelylisp.toml` or by `--target`, and is the latest by default. A
+construct the target can't read is an error rather than output it would
+reject.
+
+Erroneous code example, with `target = "4.7"`:
+
+    type Tuple(const T) as T
+
+`const` type parameters need TypeScript 5.0. Raise the target, or remove the
+modifier:
+
+    type Tuple(T) as T
+"#,
+};
+
+pub const INCOMPLETE_LOWERING: Code = Code {
+    id: "DL0027",
+    explanation: r#"A construct the compiler can't lower to TypeScript yet, rejected by `--strict`.
+
+Without `--strict` the compiler fails on these constructs as it meets them.
+Strict mode reports each of them up front instead.
+
+Erroneous code example, with `strict = true`:
+
+    type A(T) as if T = 1 then 1 else 2 end
+
+Write the condition with `<:` instead:
+
+    type A(T) as if T <: 1 then 1 else 2 end
+"#,
+};
+
+pub const INVALID_TYPE_PARAMETERS: Code = Code {
+    id: "DL0028",
+    explanation: r#"The type parameters of a declaration are in an order TypeScript rejects.
+
+A parameter with a default can only be followed by parameters with a default,
+as an argument can only be left out at the end. A rest parameter is the last
+parameter and has no default.
+
+Erroneous code example:
+
+    type Pair(A, B) defaults A = string as [A, B]
+
+Give the parameters after it a default too, or move it last:
+
+    type Pair(B, A) defaults A = string as [A, B]
+"#,
+};
+
+pub const EXPLICIT_ANY: Code = Code {
+    id: "DL0029",
+    explanation: r#"An exported declaration has an `any` in it.
+
+`any` turns off the checks of the type checker wherever the type is used, and
+spreads to the types built from it. The `any` may be written in the
+declaration, or come from an alias of the module that isn't exported:
+
+    type Loose as any
+    export type Options as {extra: Loose}
+
+Use `unknown`, which accepts the same values but must be narrowed before use:
+
+    export type Options as {extra: unknown}
+
+Or allow it with a comment directly above the declaration:
+
+    // allow(explicit_any)
+    export type Options as {extra: any}
+
+The `explicit_any` lint reports this diagnostic. It is allowed by default, set
+`explicit_any = "warn"` or `"deny"` in the `[lints]` table of
+`definitelylisp.toml` to enable it.
+"#,
+};
+
+pub const NO_ZOD_SCHEMA: Code = Code {
+    id: "DL0030",
+    explanation: r#"A declaration gets no schema in the file of `--emit zod`.
+
+Zod schemas are emitted for the structural subset of the language: primitives,
+literals, object types, arrays, tuples and unions of them, and references to
+the other declarations of the module. A declaration using anything else, or
+referring to a declaration without a schema, is left out of the schema file,
+and its type is still emitted.
+
+Example:
+
+    export type Flags as map K in :a | :b do boolean end
+
+Mapped types have no schema. Write the schema of the declaration by hand, or
+declare a type the schema can express:
+
+    export type Flags as {a: boolean, b: boolean}
+"#,
+};
+
+pub const COMPANION: Code = Code {
+    id: "DL0031",
+    explanation: r#"A declaration marked `@companion` can't have one.
+
+A companion is a constant holding the object an alias stands for, emitted with
+`--emit-const-companions` next to the type of its values. The alias must not be
+generic, global or a default export, and its body must be an object of literals:
+strings, numbers, booleans, `null`, `undefined`, and objects and tuples of them.
+
+Example:
+
+    @companion
+    type Color as {red: :red, other: string}
+
+`string` has no value to put in the constant. Write the value instead:
+
+    @companion
+    type Color as {red: :red, other: :other}
+"#,
+};
+
+pub const INVALID_ELSE_FALLBACK: Code = Code {
+    id: "DL0032",
+    explanation: r#"An `if` or `cond` falls back to a scrutinee it doesn't have.
+
+Without `else`, an expression stands for `never` when no condition holds, or
+for what `missing_else` says. `else pass`, and `missing_else = "scrutinee"`,
+stand for the type the condition checks, `T` in `T <: U`, which only a
+condition of that form has. Each arm of a `cond` must check the same type.
+
+Example:
+
+    type A(T) as if not (T <: string) then 1 else pass end
+
+`not (...)` has no scrutinee. Write the else branch instead:
+
+    type A(T) as if not (T <: string) then 1 else T end
+"#,
+};
+
+pub const MISPLACED_CONDITION: Code = Code {
+    id: "DL0033",
+    explanation: r#"A condition is used as a type.
+
+`A <: B`, `and`, `or` and `not` are tested by an `if` or a `cond`, they have no
+type of their own. A macro such as `all!` or `any!` expands to a condition
+wherever it is called, which is an error outside a condition.
+
+Example:
+
+    type A(T) as all!([T <: string, T <: :a])
+
+Test the condition, with the types it stands for:
+
+    type A(T) as if all!([T <: string, T <: :a]) then true else false end
+
+`--lower-conditions`, or the `lower_conditions` option, has the compiler do so.
+"#,
+};
+
+pub const EMIT_SIZE_LIMIT: Code = Code {
+    id: "DL0034",
+    explanation: r#"An exported alias is emitted as more bytes than the limit.
+
+`--max-emit-bytes-per-alias`, or the `max_emit_bytes_per_alias` option, limits
+the size of the TypeScript of each exported alias, its doc comment included,
+for the tools failing on a single type too large.
+
+Example, with a limit of 40 bytes:
+
+    export type Point as {x: number, y: number, z: number}
+
+Declare parts of the alias on their own, it refers to them instead of holding
+them:
+
+    type XY as {x: number, y: number}
+    export type Point as XY & {z: number}
+
+An alias repeating a large subexpression is smaller with `--hoist`, which binds
+it once.
+"#,
+};
+
+pub const INVALID_TYPE_PREDICATE: Code = Code {
+    id: "DL0035",
+    explanation: r#"A type predicate names a parameter the function doesn't have.
+
+`x is T` and `asserts x is T`, in place of the return type of a function type,
+narrow the argument of its parameter `x`. The name must be one of the
+parameters of that function.
+
+Erroneous code example:
+
+    type Guard as (value: unknown) => x is string
+
+Name the parameter:
+
+    type Guard as (value: unknown) => value is string
+"#,
+};
+
+pub const MISSING_PROPERTY: Code = Code {
+    id: "DL0036",
+    explanation: r#"A property of an object literal that it doesn't have is accessed.
+
+`{a: A}.a`, `{a: A}[:a]`, and the same through the name of an alias of an
+object literal, are folded to the value of the property. An object without the
+property has no value to fold to, and TypeScript would reject the access. Nor
+has a literal that isn't an object, such as the value of `{a: 1}.a`.
+
+Erroneous code example:
+
+    type A as {a: string, c: number}.b
+
+Access one of the properties of the object:
+
+    type A as {a: string, c: number}.c
+"#,
+};
+
+pub const SINGLE_USE_ALIAS: Code = Code {
+    id: "DL0037",
+    explanation: r#"A generic alias that isn't exported is applied only once.
+
+An alias used in a single place is an indirection the reader has to follow, and
+the application can be written out instead: the body of the alias with the
+arguments in place of its parameters.
+
+Example:
+
+    type Wrap(T) as {value: T}
+    export type Boxed as Wrap(string)
+
+Inlined:
+
+    export type Boxed as {value: string}
+
+`newtype fix` inlines the application and leaves the alias, which can then be
+removed. Exported aliases are never reported, other modules may apply them.
+
+The `single_use_alias` lint reports this diagnostic. It is allowed by default,
+set `single_use_alias = "warn"` or `"deny"` in the `[lints]` table of
+`definitelylisp.toml` to enable it.
+"#,
+};
+
+pub const MODULE_OPTION: Code = Code {
+    id: "DL0038",
+    explanation: r#"An `#options` pragma sets an option that applies to the whole build.
+
+A module can override `strict`, `target` and `lints` for itself, so that new
+modules can be held to stricter rules than old or generated ones. The other
+options, such as the width or the kind of file emitted, are the same for every
+module of a build, and are set in `definitelylisp.toml` or with flags.
+
+Erroneous code example:
+
+    #options(strict = true, emit = "dts")
+    export type A as 1
+
+Set only the options a module can override:
+
+    #options(strict = true)
+    export type A as 1
+"#,
+};
+
+pub const INCONSISTENT_OPTIONALITY: Code = Code {
+    id: "DL0039",
+    explanation: r#"Object literals of a union with the same keys disagree on one of them.
+
+A key that is optional in some members of a union and required in the others,
+or whose value is the same in each but for `undefined`, is most often the mark
+of a generator that writes the members one by one. Narrowing on such a union
+behaves in surprising ways, since none of the keys tells the members apart.
+
+Example:
+
+    export type Event as {name: string, at: number} | {name: string, ?at: number}
+
+Mark the key the same in each member, or merge the members:
+
+    export type Event as {name: string, ?at: number}
+
+Discriminated unions are not reported: a key every member has, with a
+different literal in each, tells them apart.
+
+The `inconsistent_optionality` lint reports this diagnostic, over the types of
+the program once simplified. It is allowed by default, set
+`inconsistent_optionality = "warn"` or `"deny"` in the `[lints]` table of
+`definitelylisp.toml` to enable it.
+"#,
+};
+
+pub const ASSERTION_FAILED: Code = Code {
+    id: "DL0040",
+    explanation: r#"The two arguments of `assert_equal!` are not the same type.
+
+`assert_equal!(A, B)` checks, while the program is simplified, that `A` and `B`
+are written the same. Spacing and the position in the source don't matter. It expands to nothing when they are the
+same, and fails the compilation otherwise.
+
+Erroneous code example:
+
+    export type A as assert_equal!(1, 2)
+
+Make the two sides the same:
+
+    export type A as assert_equal!(1, 1)
+"#,
+};
+
+pub const EXPECTED_CONDITION: Code = Code {
+    id: "DL0041",
+    explanation: r#"An `if` or a `cond` tests something that isn't a condition.
+
+A condition is `A <: B`, or conditions joined by `and`, `or` and `not`. A macro
+such as `all!` may stand for one, as long as it expands to a condition. A type
+on its own, or the `?T` of a pattern, has nothing to test.
+
+Erroneous code example:
+
+    type A(T) as cond do T <: 1 -> 1, _ -> 2 end
+
+The arm taken when no condition holds is the `else` arm:
+
+    type A(T) as cond do T <: 1 -> 1, else -> 2 end
+"#,
+};
+
+/// Every diagnostic code.
+pub const CODES: &[Code] = &[
+    SYNTAX_ERROR,
+    RESERVED_NAME,
+    CONTEXTUAL_KEYWORD,
+    CONFUSABLE_NAMES,
+    UNKNOWN_CONFIG_KEY,
+    UNKNOWN_LINT,
+    INVALID_CONFIG,
+    IMPORT_SYNTAX_ERROR,
+    UNSUPPORTED_CONSTRUCT,
+    UNMAPPED_SCALAR,
+    COMPLEXITY_LIMIT,
+    INVALID_MACRO_ARGUMENT,
+    NON_EXHAUSTIVE_MATCH,
+    UNKNOWN_MACRO,
+    UNSATISFIED_CONSTRAINT,
+    SHADOWED_GLOBAL,
+    MISPLACED_THIS,
+    INVALID_DEFAULT_EXPORT,
+    INVALID_TYPE_ARGUMENTS,
+    INVALID_PATTERN_SYNONYM,
+    EMIT_TAG_CONFLICT,
+    UNKNOWN_ATTRIBUTE,
+    FEATURE_NOT_ENABLED,
+    UNKNOWN_FEATURE,
+    CONSTANT_CONDITION,
+    UNSUPPORTED_BY_TARGET,
+    INCOMPLETE_LOWERING,
+    INVALID_TYPE_PARAMETERS,
+    EXPLICIT_ANY,
+    NO_ZOD_SCHEMA,
+    COMPANION,
+    INVALID_ELSE_FALLBACK,
+    MISPLACED_CONDITION,
+    EMIT_SIZE_LIMIT,
+    INVALID_TYPE_PREDICATE,
+    MISSING_PROPERTY,
+    SINGLE_USE_ALIAS,
+    MODULE_OPTION,
+    INCONSISTENT_OPTIONALITY,
+    ASSERTION_FAILED,
+    EXPECTED_CONDITION,
+];
+
+/// The explanation of the code `id`, which is not case sensitive.
+pub fn explain(id: &str) -> Result<&'static str, String> {
+    CODES
+        .iter()
+        .find(|code| code.id.eq_ignore_ascii_case(id.trim()))
+        .map(|code| code.explanation)
+        .ok_or_else(|| {
+            let ids: Vec<_> = CODES.iter().map(|code| code.id).collect();
+
+            format!(
+                "`{id}` is not a diagnostic code, codes range from {} to {}",
+                ids[0],
+                ids[ids.len() - 1]
+            )
+        })
+}
+
+/// Whether the code `id` has an explanation.
+pub fn has_explanation(id: &str) -> bool {
+    explain(id).is_ok_and(|explanation| !explanation.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compile::{compile, CompileOptions, OutputKind},
+        lint::{self, Level},
+        target::TsVersion,
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[test]
+    fn every_code_is_explained() {
+        for (i, code) in CODES.iter().enumerate() {
+            assert_eq!(
+                code.id,
+                format!("DL{:04}", i + 1),
+                "codes are numbered in order"
+            );
+            assert!(has_explanation(code.id), "{} has no explanation", code.id);
+        }
+    }
+
+    #[rstest]
+    #[case::syntax_error(SYNTAX_ERROR, "type A as if a <: b 1 end")]
+    #[case::reserved_name(RESERVED_NAME, "type new as 1")]
+    #[case::contextual_keyword(CONTEXTUAL_KEYWORD, "type A(declare) as declare")]
+    #[case::confusable_names(CONFUSABLE_NAMES, "type A(l, I) as l")]
+    #[case::invalid_macro_argument(INVALID_MACRO_ARGUMENT, "type A as merge!({a: 1}, 1)")]
+    #[case::non_exhaustive_match(
+        NON_EXHAUSTIVE_MATCH,
+        "union Shape = Circle {radius: number} | Square {size: number}\n    \
+         type Sides(S) as match S do Circle -> 0 end"
+    )]
+    #[case::unknown_macro(UNKNOWN_MACRO, "type A as deep_required!({a: 1})")]
+    #[case::unsatisfied_constraint(
+        UNSATISFIED_CONSTRAINT,
+        "type Get(T, K) where K <: keyof(T) as T[K]\n    type A as Get({a: 1}, :b)"
+    )]
+    #[case::shadowed_global(SHADOWED_GLOBAL, "type Partial as 1")]
+    #[case::misplaced_this(MISPLACED_THIS, "type A as this")]
+    #[case::invalid_default_export(
+        INVALID_DEFAULT_EXPORT,
+        "export default type A as 1\n    export default type B as 2"
+    )]
+    #[case::invalid_type_arguments(
+        INVALID_TYPE_ARGUMENTS,
+        "type Pair(A, B) as [A, B]\n    type P as Pair(1)"
+    )]
+    #[case::invalid_pattern_synonym(
+        INVALID_PATTERN_SYNONYM,
+        "#feature(pattern_synonyms)\n    pattern Boxed(T) = {value: T}\n    \
+         type A(B) as match B do Boxed(?V, ?W) -> V, else -> never end"
+    )]
+    #[case::feature_not_enabled(
+        FEATURE_NOT_ENABLED,
+        "type A(T) as if T <:! string then 1 else 2 end"
+    )]
+    #[case::unknown_feature(UNKNOWN_FEATURE, "#feature(variadics)\n    type A as 1")]
+    #[case::unknown_attribute(UNKNOWN_ATTRIBUTE, "@deprecate(\"use B\")\n    type A as 1")]
+    #[case::invalid_type_parameters(
+        INVALID_TYPE_PARAMETERS,
+        "type Pair(A, B) defaults A = string as [A, B]"
+    )]
+    #[case::companion(COMPANION, "@companion\n    type Color as {red: :red, other: string}")]
+    #[case::invalid_type_predicate(
+        INVALID_TYPE_PREDICATE,
+        "type Guard as (value: unknown) => x is string"
+    )]
+    #[case::missing_property(MISSING_PROPERTY, "type A as {a: string, c: number}.b")]
+    #[case::misplaced_condition(MISPLACED_CONDITION, "type A(T) as all!([T <: string, T <: :a])")]
+    #[case::invalid_else_fallback(
+        INVALID_ELSE_FALLBACK,
+        "type A(T) as if not (T <: string) then 1 else pass end"
+    )]
+    #[case::assertion_failed(ASSERTION_FAILED, "export type A as assert_equal!(1, 2)")]
+    #[case::expected_condition(EXPECTED_CONDITION, "type A(T) as cond do T <: 1 -> 1, _ -> 2 end")]
+    #[case::constant_condition(
+        CONSTANT_CONDITION,
+        "type A(T) as if :a <: string then T else never end"
+    )]
+    fn compiler_codes_are_emitted(#[case] code: Code, #[case] source: &str) {
+        let diagnostics = match compile(source, &CompileOptions::default()) {
+            Ok(output) => output.diagnostics,
+            Err(diagnostics) => diagnostics,
+        };
+
+        let codes: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+
+        assert_eq!(codes, vec![Some(code.id)]);
+        assert!(code.explanation.contains(source));
+    }
+
+    #[test]
+    fn emit_tag_conflict_is_emitted() {
+        let source = "@emit(\"server\")\n    type Secret as string\n    \
+                      @emit(\"client\")\n    type Form as {secret: Secret}";
+
+        let options = CompileOptions {
+            only_tag: Some("client".to_string()),
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some(EMIT_TAG_CONFLICT.id));
+        assert!(EMIT_TAG_CONFLICT.explanation.contains(source));
+    }
+
+    #[test]
+    fn emit_size_limit_is_emitted() {
+        let source = "export type Point as {x: number, y: number, z: number}";
+
+        let options = CompileOptions {
+            max_emit_bytes_per_alias: Some(40),
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some(EMIT_SIZE_LIMIT.id));
+        assert!(EMIT_SIZE_LIMIT.explanation.contains(source));
+    }
+
+    #[test]
+    fn unsupported_by_target_is_emitted() {
+        let source = "type Tuple(const T) as T";
+
+        let options = CompileOptions {
+            target: TsVersion::new(4, 7),
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some(UNSUPPORTED_BY_TARGET.id));
+        assert!(UNSUPPORTED_BY_TARGET.explanation.contains(source));
+    }
+
+    #[test]
+    fn incomplete_lowering_is_emitted() {
+        let source = "type A(T) as if T = 1 then 1 else 2 end";
+
+        let options = CompileOptions {
+            strict: true,
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some(INCOMPLETE_LOWERING.id));
+        assert!(INCOMPLETE_LOWERING.explanation.contains(source));
+    }
+
+    #[test]
+    fn explicit_any_is_emitted() {
+        let source = "type Loose as any\n    export type Options as {extra: Loose}";
+
+        let options = CompileOptions {
+            lints: [(lint::EXPLICIT_ANY.to_string(), Level::Warn)].into(),
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap().diagnostics;
+
+        assert_eq!(diagnostics[0].code, Some(EXPLICIT_ANY.id));
+        assert!(EXPLICIT_ANY.explanation.contains(source));
+    }
+
+    #[test]
+    fn single_use_alias_is_emitted() {
+        let source = "type Wrap(T) as {value: T}\n    export type Boxed as Wrap(string)";
+
+        let options = CompileOptions {
+            lints: [(lint::SINGLE_USE_ALIAS.to_string(), Level::Warn)].into(),
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap().diagnostics;
+
+        assert_eq!(diagnostics[0].code, Some(SINGLE_USE_ALIAS.id));
+        assert!(SINGLE_USE_ALIAS.explanation.contains(source));
+    }
+
+    #[test]
+    fn module_option_is_emitted() {
+        let source = "#options(strict = true, emit = \"dts\")\n    export type A as 1";
+
+        let diagnostics = compile(source, &CompileOptions::default()).unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some(MODULE_OPTION.id));
+        assert!(MODULE_OPTION.explanation.contains(source));
+    }
+
+    #[test]
+    fn inconsistent_optionality_is_emitted() {
+        let source =
+            "export type Event as {name: string, at: number} | {name: string, ?at: number}";
+
+        let options = CompileOptions {
+            lints: [(lint::INCONSISTENT_OPTIONALITY.to_string(), Level::Warn)].into(),
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap().diagnostics;
+
+        assert_eq!(diagnostics[0].code, Some(INCONSISTENT_OPTIONALITY.id));
+        assert!(INCONSISTENT_OPTIONALITY.explanation.contains(source));
+    }
+
+    #[test]
+    fn no_zod_schema_is_emitted() {
+        let source = "export type Flags as map K in :a | :b do boolean end";
+
+        let options = CompileOptions {
+            output_kind: OutputKind::Zod,
+            ..Default::default()
+        };
+
+        let diagnostics = compile(source, &options).unwrap().diagnostics;
+
+        assert_eq!(diagnostics[0].code, Some(NO_ZOD_SCHEMA.id));
+        assert!(NO_ZOD_SCHEMA.explanation.contains(source));
+    }
+
+    #[test]
+    fn codes_are_case_insensitive() {
+        assert_eq!(explain("dl0004"), Ok(CONFUSABLE_NAMES.explanation));
+    }
+
+    #[test]
+    fn unknown_code() {
+        assert_eq!(
+            explain("E0308"),
+            Err("`E0308` is not a diagnostic code, codes range from DL0001 to DL0041".to_string())
+        );
+    }
+}
@@ -0,0 +1,531 @@
+/// Semantic differences between the exported declarations of two programs, for reviewing changes
+/// to generated types.
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        Ast, FunctionType, Interface, ObjectProperty, Program, TypeAlias, TypeLiteral,
+        TypeParameter,
+    },
+    lint::always_extends,
+    typescript::Pretty,
+};
+
+/// Whether a change keeps the values of the old type valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    /// Every value of the old type is a value of the new one.
+    Compatible,
+    /// Some values of the old type are no longer valid.
+    Breaking,
+}
+
+impl Display for Compatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compatibility::Compatible => f.write_str("compatible"),
+            Compatibility::Breaking => f.write_str("breaking"),
+        }
+    }
+}
+
+/// Where a type is in the exported one: where its values are handed out, as the type of a
+/// property is, or taken in, as the type of the parameter of a function is. A value of a function
+/// type that takes more takes the old values too, so widening is compatible in the first and
+/// narrowing in the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Output,
+    Input,
+}
+
+impl Position {
+    /// The position of a parameter of a function type at `self`.
+    fn flip(self) -> Self {
+        match self {
+            Position::Output => Position::Input,
+            Position::Input => Position::Output,
+        }
+    }
+
+    /// How compatible widening the type at the position is, narrowing it being the opposite.
+    fn widening(self) -> Compatibility {
+        match self {
+            Position::Output => Compatibility::Compatible,
+            Position::Input => Compatibility::Breaking,
+        }
+    }
+
+    fn narrowing(self) -> Compatibility {
+        match self {
+            Position::Output => Compatibility::Breaking,
+            Position::Input => Compatibility::Compatible,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiChange {
+    /// Name of the exported declaration.
+    pub name: String,
+    pub compatibility: Compatibility,
+    /// What changed, e.g. "`User.email` removed".
+    pub description: String,
+}
+
+/// An exported declaration: its type parameters and the type it declares, interfaces being the
+/// object of their properties.
+struct Export<'a> {
+    name: &'a str,
+    params: &'a [TypeParameter],
+    extends: Option<&'a str>,
+    ty: Ast,
+}
+
+fn exports(program: &Ast) -> Vec<Export<'_>> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(alias @ TypeAlias { export: true, .. }) => Some(Export {
+                    name: &alias.name.name,
+                    params: &alias.params,
+                    extends: None,
+                    ty: alias.body.as_ref().clone(),
+                }),
+                Ast::Interface(interface @ Interface { export: true, .. }) => Some(Export {
+                    name: &interface.name,
+                    params: &interface.params,
+                    extends: interface.extends.as_deref(),
+                    ty: Ast::TypeLiteral(TypeLiteral {
+                        properties: interface.definition.clone(),
+                        span: interface.span,
+                    }),
+                }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Changes to the exported declarations from the simplified program `old` to `new`: the
+/// declarations added, removed, and every difference between the types of the others.
+///
+/// Objects are taken as the exact shape of the values they describe, so a new property is only
+/// compatible when it is optional. Removing a property, making one required, removing a member of
+/// a union or narrowing a literal to a literal, `number` to `1`, is breaking; making a property
+/// optional, adding a member to a union or widening a literal to its primitive isn't. It is the
+/// other way around for the parameters of function types, see [`Position`]. Any other change is
+/// taken to be breaking.
+pub fn compare(old: &Ast, new: &Ast) -> Vec<ApiChange> {
+    let old_exports = exports(old);
+    let new_exports = exports(new);
+
+    let mut changes = vec![];
+
+    let mut change = |name: &str, compatibility, description: String| {
+        changes.push(ApiChange {
+            name: name.to_string(),
+            compatibility,
+            description,
+        })
+    };
+
+    for old in &old_exports {
+        let Some(new) = new_exports.iter().find(|new| new.name == old.name) else {
+            change(
+                old.name,
+                Compatibility::Breaking,
+                format!("`{}` removed", old.name),
+            );
+            continue;
+        };
+
+        if old.params != new.params || old.extends != new.extends {
+            change(
+                old.name,
+                Compatibility::Breaking,
+                format!("`{}` has different type parameters or bases", old.name),
+            );
+        }
+
+        let mut differences = vec![];
+        compare_types(
+            &old.ty,
+            &new.ty,
+            old.name,
+            Position::Output,
+            &mut differences,
+        );
+
+        for (compatibility, description) in differences {
+            change(old.name, compatibility, description);
+        }
+    }
+
+    for new in &new_exports {
+        if !old_exports.iter().any(|old| old.name == new.name) {
+            change(
+                new.name,
+                Compatibility::Compatible,
+                format!("`{}` added", new.name),
+            );
+        }
+    }
+
+    changes
+}
+
+fn compare_types(
+    old: &Ast,
+    new: &Ast,
+    path: &str,
+    position: Position,
+    out: &mut Vec<(Compatibility, String)>,
+) {
+    if old == new {
+        return;
+    }
+
+    // Whether `member` is one of `members`, or a literal of one of them.
+    let included = |member: &Ast, members: &[Ast]| {
+        members
+            .iter()
+            .any(|other| other == member || always_extends(member, other) == Some(true))
+    };
+
+    match (old, new) {
+        (Ast::TypeLiteral(old), Ast::TypeLiteral(new)) => {
+            compare_objects(old, new, path, position, out)
+        }
+        (Ast::Array(old), Ast::Array(new)) => {
+            compare_types(old, new, &format!("{path}[]"), position, out)
+        }
+        (Ast::FunctionType(old), Ast::FunctionType(new))
+            if old.params.len() == new.params.len() && old.predicate == new.predicate =>
+        {
+            compare_functions(old, new, path, position, out)
+        }
+        (Ast::UnionType(_), _) | (_, Ast::UnionType(_)) => {
+            let old_members = members(old);
+            let new_members = members(new);
+
+            for member in &old_members {
+                if !included(member, &new_members) {
+                    out.push((
+                        position.narrowing(),
+                        format!("`{path}` no longer includes `{}`", render(member)),
+                    ));
+                }
+            }
+
+            for member in &new_members {
+                if !included(member, &old_members) {
+                    out.push((
+                        position.widening(),
+                        format!("`{path}` now includes `{}`", render(member)),
+                    ));
+                }
+            }
+        }
+        _ if always_extends(old, new) == Some(true) => out.push((
+            position.widening(),
+            format!(
+                "`{path}` widened from `{}` to `{}`",
+                render(old),
+                render(new)
+            ),
+        )),
+        _ if always_extends(new, old) == Some(true) => out.push((
+            position.narrowing(),
+            format!(
+                "`{path}` narrowed from `{}` to `{}`",
+                render(old),
+                render(new)
+            ),
+        )),
+        _ => out.push((
+            Compatibility::Breaking,
+            format!(
+                "`{path}` changed from `{}` to `{}`",
+                render(old),
+                render(new)
+            ),
+        )),
+    }
+}
+
+/// The parameters of two function types of as many, at the position opposite to `position`, and
+/// their return types.
+fn compare_functions(
+    old: &FunctionType,
+    new: &FunctionType,
+    path: &str,
+    position: Position,
+    out: &mut Vec<(Compatibility, String)>,
+) {
+    for (old_param, new_param) in old.params.iter().zip(&new.params) {
+        if old_param.ellipsis != new_param.ellipsis {
+            out.push((
+                Compatibility::Breaking,
+                format!(
+                    "`{path}({})` changed to a rest parameter or back",
+                    new_param.name
+                ),
+            ));
+            continue;
+        }
+
+        compare_types(
+            &old_param.kind,
+            &new_param.kind,
+            &format!("{path}({})", new_param.name),
+            position.flip(),
+            out,
+        );
+    }
+
+    compare_types(
+        &old.return_type,
+        &new.return_type,
+        &format!("{path}()"),
+        position,
+        out,
+    );
+}
+
+fn compare_objects(
+    old: &TypeLiteral,
+    new: &TypeLiteral,
+    path: &str,
+    position: Position,
+    out: &mut Vec<(Compatibility, String)>,
+) {
+    let find = |literal: &'_ TypeLiteral, property: &ObjectProperty| {
+        literal
+            .properties
+            .iter()
+            .find(|other| other.key == property.key)
+            .cloned()
+    };
+
+    for old_property in &old.properties {
+        let key = render(&old_property.key);
+        let path = format!("{path}.{key}");
+
+        let Some(new_property) = find(new, old_property) else {
+            out.push((Compatibility::Breaking, format!("`{path}` removed")));
+            continue;
+        };
+
+        match (old_property.optional, new_property.optional) {
+            (false, true) => out.push((position.widening(), format!("`{path}` became optional"))),
+            (true, false) => out.push((position.narrowing(), format!("`{path}` became required"))),
+            _ => {}
+        }
+
+        compare_types(
+            &old_property.value,
+            &new_property.value,
+            &path,
+            position,
+            out,
+        );
+    }
+
+    for new_property in &new.properties {
+        if find(old, new_property).is_none() {
+            let path = format!("{path}.{}", render(&new_property.key));
+
+            if new_property.optional {
+                out.push((position.widening(), format!("`{path}` added as optional")));
+            } else {
+                out.push((Compatibility::Breaking, format!("`{path}` added")));
+            }
+        }
+    }
+}
+
+fn members(ast: &Ast) -> Vec<Ast> {
+    match ast {
+        Ast::UnionType(union) => union.types.iter().flat_map(members).collect(),
+        ast => vec![ast.clone()],
+    }
+}
+
+fn render(node: &impl Pretty) -> String {
+    node.render_pretty_ts(usize::MAX).trim_end().to_string()
+}
+
+/// Renders changes grouped by compatibility, the breaking ones first.
+pub fn report(changes: &[ApiChange]) -> String {
+    let mut out = String::new();
+
+    for compatibility in [Compatibility::Breaking, Compatibility::Compatible] {
+        let group: Vec<_> = changes
+            .iter()
+            .filter(|change| change.compatibility == compatibility)
+            .collect();
+
+        if group.is_empty() {
+            continue;
+        }
+
+        out += &format!("{compatibility} changes:\n");
+
+        for change in group {
+            out += &format!("  {}\n", change.description);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn diff(old: &str, new: &str) -> Vec<(Compatibility, String)> {
+        compare(&parse!(old).simplify(), &parse!(new).simplify())
+            .into_iter()
+            .map(|change| (change.compatibility, change.description))
+            .collect()
+    }
+
+    #[test]
+    fn identical_programs() {
+        let source = "export type A as {a: 1 | 2}\ntype Private as 1";
+
+        assert_eq!(diff(source, source), vec![]);
+        assert_eq!(
+            diff(source, "export type A as {a: 1 | 2}\ntype Private as 2"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn added_alias() {
+        assert_eq!(
+            diff(
+                "export type A as 1",
+                "export type A as 1\nexport type B as 2"
+            ),
+            vec![(Compatibility::Compatible, "`B` added".to_string())]
+        );
+    }
+
+    #[test]
+    fn removed_property() {
+        assert_eq!(
+            diff(
+                "export type User as {id: number, email: string}",
+                "export type User as {id: number}"
+            ),
+            vec![(Compatibility::Breaking, "`User.email` removed".to_string())]
+        );
+    }
+
+    #[test]
+    fn widened_union() {
+        assert_eq!(
+            diff(
+                "export type A as {status: :ok | :error, ?b: 1}",
+                "export type A as {status: :ok | :error | :pending, ?b: 1}"
+            ),
+            vec![(
+                Compatibility::Compatible,
+                "`A.status` now includes `'pending'`".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn widened_and_narrowed_literals() {
+        assert_eq!(
+            diff(
+                "export type A as {a: 1, b: number, c: 1 | 2, d: :x}",
+                "export type A as {a: number, b: 1, c: number, d: 1}"
+            ),
+            vec![
+                (
+                    Compatibility::Compatible,
+                    "`A.a` widened from `1` to `number`".to_string()
+                ),
+                (
+                    Compatibility::Breaking,
+                    "`A.b` narrowed from `number` to `1`".to_string()
+                ),
+                (
+                    Compatibility::Compatible,
+                    "`A.c` now includes `number`".to_string()
+                ),
+                (
+                    Compatibility::Breaking,
+                    "`A.d` changed from `'x'` to `1`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parameters_are_compared_the_other_way() {
+        assert_eq!(
+            diff(
+                "export type F as (a: 1, b: number, c: {x: 1}) => 1",
+                "export type F as (a: number, b: 1, c: {?x: 1}) => number"
+            ),
+            vec![
+                (
+                    Compatibility::Breaking,
+                    "`F(a)` widened from `1` to `number`".to_string()
+                ),
+                (
+                    Compatibility::Compatible,
+                    "`F(b)` narrowed from `number` to `1`".to_string()
+                ),
+                (
+                    Compatibility::Breaking,
+                    "`F(c).x` became optional".to_string()
+                ),
+                (
+                    Compatibility::Compatible,
+                    "`F()` widened from `1` to `number`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_groups_by_compatibility() {
+        let changes = compare(
+            &parse!("export type A as {a: 1, b: 2}\nexport type Gone as 1").simplify(),
+            &parse!("export interface A {?a: 1, c: 3}").simplify(),
+        );
+
+        assert_eq!(
+            report(&changes),
+            dedent!(
+                "
+                breaking changes:
+                  `A.b` removed
+                  `A.c` added
+                  `Gone` removed
+                compatible changes:
+                  `A.a` became optional
+                "
+            )
+            .trim_start()
+        );
+    }
+}
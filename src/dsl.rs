@@ -0,0 +1,1046 @@
+/// Printing of the tree back to DSL source, used by the importers to write out the definitions
+/// they build, see [`Ast::to_dl`].
+use std::fmt::{self, Display};
+
+use pest::Parser;
+use pretty::RcDoc as D;
+
+use crate::{
+    ast::{
+        attribute::AttrValue,
+        brand_declaration::BrandDeclaration,
+        cond_expr::CondExpr,
+        if_expr::IfExpr,
+        let_expr::LetExpr,
+        match_expr::MatchExpr,
+        pattern_declaration::PatternDeclaration,
+        union_declaration::{UnionDeclaration, DEFAULT_DISCRIMINANT},
+        Access, AccessKind, AmbientModule, ApplyGeneric, Ast, Builtin, BuiltinKeyword, ExtendsExpr,
+        ExtendsInfixOp, ExtendsPrefixOp, FunctionType, ImportClause, ImportStatement, ImportType,
+        InfixOp, Interface, IntersectionType, MacroCall, MappedType, MappingModifier,
+        ObjectProperty, ObjectPropertyKey, PrefixOp, Program, PropertyKeyIndex, Statement, Tuple,
+        TypeAlias, TypeLiteral, TypeParameter, TypePredicate, UnionType, UnitTest,
+    },
+    parser::{NewtypeParser, Rule},
+    pretty::jsdoc,
+};
+
+pub trait Pretty {
+    fn render_pretty_dsl(&self, width: usize) -> String {
+        let mut w = Vec::new();
+        self.to_dsl().render(width, &mut w).unwrap();
+        String::from_utf8(w).unwrap()
+    }
+
+    fn to_dsl(&self) -> D<()>;
+}
+
+/// Layout of the source printed by [`Ast::to_dl_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DlOptions {
+    /// Columns a line fits in before it is broken.
+    pub width: usize,
+}
+
+impl Default for DlOptions {
+    fn default() -> Self {
+        Self { width: 80 }
+    }
+}
+
+impl Ast {
+    /// DSL source of the node, which parses back to the node, spans aside. Nodes built in Rust
+    /// code are printed as well as parsed ones, e.g. for the importers and for the fixes
+    /// suggested by diagnostics.
+    ///
+    /// The nodes the simplifier lowers to are printed as the source they are lowered from, a
+    /// conditional type as an `if`, which parses to the node before simplification.
+    pub fn to_dl(&self) -> String {
+        self.to_dl_with(&DlOptions::default())
+    }
+
+    pub fn to_dl_with(&self, options: &DlOptions) -> String {
+        self.render_pretty_dsl(options.width)
+    }
+
+    /// DSL source of the node on a single line of at most `max_len` characters, for diagnostics
+    /// to quote. A longer node is cut short with `…`, an object literal after the last property
+    /// that fits, as in `{a: number, …}`.
+    pub fn summary(&self, max_len: usize) -> String {
+        let line = single_line(self);
+
+        if line.chars().count() <= max_len {
+            return line;
+        }
+
+        if let Ast::TypeLiteral(TypeLiteral { properties, span }) = self {
+            let shown = (0..properties.len())
+                .map(|count| {
+                    let literal = Ast::TypeLiteral(TypeLiteral {
+                        properties: properties[..count].to_vec(),
+                        span: *span,
+                    });
+                    let line = single_line(&literal);
+                    let open = line.strip_suffix('}').unwrap_or(&line).trim_end();
+
+                    if count == 0 {
+                        format!("{open}…}}")
+                    } else {
+                        format!("{open}, …}}")
+                    }
+                })
+                .take_while(|shown| shown.chars().count() <= max_len)
+                .last();
+
+            if let Some(shown) = shown {
+                return shown;
+            }
+        }
+
+        let mut cut: String = line.chars().take(max_len.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+/// Longest rendering of a node by `Display`, see [`Ast::summary`].
+pub const SUMMARY_LEN: usize = 60;
+
+/// The node as [`Ast::summary`] renders it, at most [`SUMMARY_LEN`] characters long.
+impl Display for Ast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary(SUMMARY_LEN))
+    }
+}
+
+/// The source of `node` on a single line, to be written in place of `replaced` in a program,
+/// parenthesized if it binds less strongly.
+pub(crate) fn replacing(node: &Ast, replaced: &Ast) -> String {
+    let line = single_line(node);
+
+    if precedence(node) < precedence(replaced) {
+        format!("({line})")
+    } else {
+        line
+    }
+}
+
+/// The source of `node` with its lines joined.
+fn single_line(node: &Ast) -> String {
+    node.render_pretty_dsl(usize::MAX)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Binding strength of the operators, used to decide where parentheses are needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    /// Function types and `let` extend as far right as possible.
+    Function,
+    Union,
+    Intersection,
+    /// Applications and `[]`.
+    Postfix,
+    Infer,
+    /// `.` and `::`.
+    Access,
+    Index,
+    Primary,
+}
+
+fn precedence(node: &Ast) -> Precedence {
+    match node {
+        Ast::FunctionType(_) | Ast::LetExpr(_) => Precedence::Function,
+        Ast::UnionType(_) => Precedence::Union,
+        Ast::IntersectionType(_) => Precedence::Intersection,
+        Ast::ApplyGeneric(_) | Ast::Array(_) => Precedence::Postfix,
+        Ast::Infer(_) => Precedence::Infer,
+        Ast::Access(Access {
+            kind: AccessKind::Index,
+            ..
+        }) => Precedence::Index,
+        Ast::Access(_) => Precedence::Access,
+        _ => Precedence::Primary,
+    }
+}
+
+/// Prints `node` as an operand of an operator binding as strongly as `parent`. Operators are left
+/// associative, so only the first operand may have the same precedence without parentheses.
+fn operand(node: &Ast, parent: Precedence, first: bool) -> D<()> {
+    let child = precedence(node);
+
+    if child < parent || (child == parent && !first) {
+        D::text("(").append(node.to_dsl()).append(")")
+    } else {
+        node.to_dsl()
+    }
+}
+
+/// Prints `node` as an operand of `.` or `::`. A dot following a number would be read as its
+/// decimal point, numbers are parenthesized.
+fn access_operand(node: &Ast, first: bool) -> D<()> {
+    match node {
+        Ast::TypeNumber(_) => D::text("(").append(node.to_dsl()).append(")"),
+        node => operand(node, Precedence::Access, first),
+    }
+}
+
+fn infix<'a>(types: &'a [Ast], op: &'static str, parent: Precedence) -> D<'a, ()> {
+    let operands = types
+        .iter()
+        .enumerate()
+        .map(|(i, node)| operand(node, parent, i == 0));
+
+    D::intersperse(operands, D::line().append(op).append(D::space())).group()
+}
+
+/// Binding strength of the operators of conditions, the `if` and `cond` tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ConditionPrecedence {
+    Connective,
+    Comparison,
+    /// `not`, and the types compared.
+    Primary,
+}
+
+fn condition_precedence(node: &Ast) -> ConditionPrecedence {
+    match node {
+        Ast::ExtendsInfixOp(ExtendsInfixOp {
+            op: InfixOp::And | InfixOp::Or,
+            ..
+        }) => ConditionPrecedence::Connective,
+        Ast::ExtendsInfixOp(_) => ConditionPrecedence::Comparison,
+        _ => ConditionPrecedence::Primary,
+    }
+}
+
+/// Prints `node` as an operand of a condition operator, as [`operand`] does for types.
+fn condition_operand(node: &Ast, parent: ConditionPrecedence, first: bool) -> D<()> {
+    let child = condition_precedence(node);
+
+    if child < parent || (child == parent && !first) {
+        D::text("(").append(node.to_dsl()).append(")")
+    } else {
+        node.to_dsl()
+    }
+}
+
+fn infix_op(op: &InfixOp, non_distributive: bool) -> &'static str {
+    match (op, non_distributive) {
+        (InfixOp::Extends, false) => "<:",
+        (InfixOp::Extends, true) => "<:!",
+        (InfixOp::NotExtends, false) => "</:",
+        (InfixOp::NotExtends, true) => "</:!",
+        (InfixOp::Equals, _) => "=",
+        (InfixOp::NotEquals, _) => "!=",
+        (InfixOp::StrictEquals, _) => "==",
+        (InfixOp::StrictNotEquals, _) => "!==",
+        (InfixOp::And, _) => "and",
+        (InfixOp::Or, _) => "or",
+    }
+}
+
+/// `if condition then then_branch else else_branch end`, the else branch left out when there is
+/// none.
+fn if_then_else<'a>(
+    condition: D<'a, ()>,
+    then_branch: &'a Ast,
+    else_branch: Option<D<'a, ()>>,
+) -> D<'a, ()> {
+    let else_branch = match else_branch {
+        Some(else_branch) => D::text("else")
+            .append(D::line().append(else_branch).nest(4))
+            .append(D::line()),
+        None => D::nil(),
+    };
+
+    D::text("if ")
+        .append(condition)
+        .append(" then")
+        .append(D::line().append(then_branch.to_dsl()).nest(4))
+        .append(D::line())
+        .append(else_branch)
+        .append("end")
+        .group()
+}
+
+/// The arms of a `match` or `cond` between `head` and `end`, the else arm last when there is one.
+fn arms<'a>(
+    head: D<'a, ()>,
+    arms: impl Iterator<Item = (D<'a, ()>, &'a Ast)>,
+    else_arm: Option<&'a Ast>,
+) -> D<'a, ()> {
+    let arms = arms
+        .map(|(test, body)| test.append(" -> ").append(body.to_dsl()))
+        .chain(else_arm.map(|else_arm| D::text("else -> ").append(else_arm.to_dsl())));
+
+    head.append(" do")
+        .append(
+            D::line()
+                .append(D::intersperse(arms, D::text(",").append(D::line())))
+                .nest(4),
+        )
+        .append(D::line())
+        .append("end")
+        .group()
+}
+
+/// Comma separated list between `open` and `close`, laid out one item per line when it doesn't
+/// fit.
+fn list<'a>(
+    items: impl Iterator<Item = D<'a, ()>>,
+    open: &'static str,
+    close: &'static str,
+) -> D<'a, ()> {
+    let items: Vec<_> = items.collect();
+
+    if items.is_empty() {
+        return D::text(open).append(close);
+    }
+
+    let body = D::intersperse(items, D::text(",").append(D::line()));
+
+    D::text(open)
+        .append(
+            D::line_()
+                .append(body)
+                .append(D::text(",").flat_alt(D::nil()))
+                .nest(4),
+        )
+        .append(D::line_())
+        .append(close)
+        .group()
+}
+
+/// Whether `name` can be written as a bare identifier.
+pub fn is_ident(name: &str) -> bool {
+    NewtypeParser::parse(Rule::ident, name)
+        .is_ok_and(|mut pairs| pairs.next().is_some_and(|pair| pair.as_str() == name))
+}
+
+/// A string literal, quoted with whichever quote it doesn't contain as the grammar has no escapes.
+fn string_literal<'a>(string: &str) -> D<'a, ()> {
+    if string.contains('"') {
+        D::text(format!("'{string}'"))
+    } else {
+        D::text(format!("\"{string}\""))
+    }
+}
+
+fn name<'a>(name: &str) -> D<'a, ()> {
+    if is_ident(name) {
+        D::text(name.to_string())
+    } else {
+        string_literal(name)
+    }
+}
+
+/// The parameter list and the `defaults` and `where` clauses of a declaration.
+fn definition_options<'a>(params: &'a [TypeParameter]) -> D<'a, ()> {
+    if params.is_empty() {
+        return D::nil();
+    }
+
+    let names = list(
+        params.iter().map(|param| {
+            let modifier = if param.is_const { "const " } else { "" };
+            D::text(format!("{modifier}{}", param.name.name))
+        }),
+        "(",
+        ")",
+    );
+
+    let clause = |keyword: &'static str, items: Vec<D<'a, ()>>| {
+        if items.is_empty() {
+            return D::nil();
+        }
+
+        D::line()
+            .append(keyword)
+            .append(D::space())
+            .append(D::intersperse(items, D::text(",").append(D::space())))
+    };
+
+    let defaults = params
+        .iter()
+        .filter_map(|param| {
+            let default = param.default.as_ref()?;
+            Some(
+                D::text(param.name.name.clone())
+                    .append(" = ")
+                    .append(default.to_dsl()),
+            )
+        })
+        .collect();
+
+    let constraints = params
+        .iter()
+        .filter_map(|param| {
+            let constraint = param.constraint.as_ref()?;
+            Some(
+                D::text(param.name.name.clone())
+                    .append(" <: ")
+                    .append(constraint.to_dsl()),
+            )
+        })
+        .collect();
+
+    names
+        .append(clause("defaults", defaults).nest(4))
+        .append(clause("where", constraints).nest(4))
+}
+
+/// The doc comment of a declaration. Block comments nest in the DSL, so openers are escaped as well
+/// as closers.
+fn doc(doc: Option<&str>) -> D<()> {
+    match doc {
+        Some(doc) => jsdoc(&doc.replace("/*", "/\\*")),
+        None => D::nil(),
+    }
+}
+
+/// A statement preceded by the doc comment of its declaration, then its attributes, each on a line
+/// of its own. The parser reads the comment from the text before the attributes.
+fn statement(statement: &Statement) -> D<()> {
+    let attributes = statement.attributes.iter().map(|attribute| {
+        let args = if attribute.args.is_empty() {
+            D::nil()
+        } else {
+            list(
+                attribute.args.iter().map(|arg| match arg {
+                    AttrValue::String(string) => string_literal(string),
+                    AttrValue::Number(text) | AttrValue::Ident(text) => D::text(text.clone()),
+                }),
+                "(",
+                ")",
+            )
+        };
+
+        D::text(format!("@{}", attribute.name.name))
+            .append(args)
+            .append(D::hardline())
+    });
+
+    doc(statement.doc())
+        .append(D::concat(attributes))
+        .append(statement.inner.to_dsl())
+}
+
+/// The `export default`, `export` or `global` modifier of a declaration.
+fn modifier<'a>(default: bool, export: bool, global: bool) -> D<'a, ()> {
+    if default {
+        D::text("export default ")
+    } else if export {
+        D::text("export ")
+    } else if global {
+        D::text("global ")
+    } else {
+        D::nil()
+    }
+}
+
+impl Pretty for Ast {
+    fn to_dsl(&self) -> D<()> {
+        match self {
+            Ast::Program(Program { statements, .. }) => D::intersperse(
+                statements.iter().map(|statement| statement.to_dsl()),
+                D::hardline().append(D::hardline()),
+            )
+            .append(D::hardline()),
+            Ast::Statement(s) => statement(s),
+            Ast::TypeAlias(alias) => alias.to_dsl(),
+            Ast::Interface(interface) => interface.to_dsl(),
+            Ast::UnionDeclaration(union) => union.to_dsl(),
+            Ast::BrandDeclaration(brand) => brand.to_dsl(),
+            Ast::PatternDeclaration(pattern) => pattern.to_dsl(),
+            Ast::ExportDefault(export) => {
+                D::text("export default ").append(export.name.name.clone())
+            }
+            Ast::Ident(ident) => D::text(ident.name.clone()),
+            Ast::Primitive(primitive, _) => D::text(primitive.to_string()),
+            Ast::TypeString(string) => string_literal(&string.ty),
+            Ast::TypeNumber(number) => D::text(number.ty.clone()),
+            Ast::TemplateString(template) => D::text(template.ty.clone()),
+            Ast::TrueKeyword(_) => D::text("true"),
+            Ast::FalseKeyword(_) => D::text("false"),
+            Ast::NeverKeyword(_) => D::text("never"),
+            Ast::UnknownKeyword(_) => D::text("unknown"),
+            Ast::AnyKeyword(_) => D::text("any"),
+            Ast::ThisType(_) => D::text("this"),
+            Ast::ImportType(ImportType { module, .. }) => D::text("import(")
+                .append(string_literal(module))
+                .append(")"),
+            Ast::UnionType(UnionType { types, .. }) => infix(types, "|", Precedence::Union),
+            Ast::IntersectionType(IntersectionType { types, .. }) => {
+                infix(types, "&", Precedence::Intersection)
+            }
+            Ast::Array(inner) => operand(inner, Precedence::Postfix, true).append("[]"),
+            Ast::Tuple(Tuple { items, .. }) => list(items.iter().map(Ast::to_dsl), "[", "]"),
+            Ast::TypeLiteral(literal) => literal.to_dsl(),
+            Ast::ApplyGeneric(ApplyGeneric { receiver, args, .. }) => operand(
+                receiver,
+                Precedence::Postfix,
+                true,
+            )
+            .append(list(args.iter().map(Ast::to_dsl), "(", ")")),
+            Ast::Access(Access { lhs, rhs, kind, .. }) => {
+                let op = match kind {
+                    AccessKind::Index => {
+                        return operand(lhs, Precedence::Index, true)
+                            .append("[")
+                            .append(rhs.to_dsl())
+                            .append("]")
+                    }
+                    AccessKind::Dot => ".",
+                    AccessKind::Namespace => "::",
+                };
+
+                access_operand(lhs, true)
+                    .append(op)
+                    .append(access_operand(rhs, false))
+            }
+            Ast::Builtin(Builtin { name, argument, .. }) => D::text(name.name())
+                .append("(")
+                .append(argument.to_dsl())
+                .append(")"),
+            Ast::Infer(inner) => D::text("?").append(operand(inner, Precedence::Infer, true)),
+            Ast::ExtendsExpr(ExtendsExpr {
+                lhs,
+                rhs,
+                then_branch,
+                else_branch,
+                non_distributive,
+                ..
+            }) => if_then_else(
+                lhs.to_dsl()
+                    .append(D::space())
+                    .append(infix_op(&InfixOp::Extends, *non_distributive))
+                    .append(D::space())
+                    .append(rhs.to_dsl()),
+                then_branch,
+                Some(else_branch.to_dsl()),
+            ),
+            Ast::IfExpr(IfExpr {
+                condition,
+                then_branch,
+                else_branch,
+                pass,
+                ..
+            }) => if_then_else(
+                condition.to_dsl(),
+                then_branch,
+                if *pass {
+                    Some(D::text("pass"))
+                } else {
+                    else_branch.as_deref().map(Pretty::to_dsl)
+                },
+            ),
+            Ast::ExtendsInfixOp(ExtendsInfixOp {
+                lhs,
+                op,
+                rhs,
+                non_distributive,
+                ..
+            }) => {
+                let parent = condition_precedence(self);
+
+                condition_operand(lhs, parent, true)
+                    .append(D::space())
+                    .append(infix_op(op, *non_distributive))
+                    .append(D::space())
+                    .append(condition_operand(rhs, parent, false))
+            }
+            // The parser only accepts a parenthesized test after `not`.
+            Ast::ExtendsPrefixOp(ExtendsPrefixOp { op, value, .. }) => match op {
+                PrefixOp::Not => D::text("not (").append(value.to_dsl()).append(")"),
+                PrefixOp::Infer => D::text("?").append(value.to_dsl()),
+            },
+            Ast::MatchExpr(MatchExpr {
+                value,
+                arms: match_arms,
+                else_arm,
+                ..
+            }) => arms(
+                D::text("match ").append(value.to_dsl()),
+                match_arms
+                    .iter()
+                    .map(|arm| (arm.pattern.to_dsl(), &arm.body)),
+                Some(else_arm),
+            ),
+            Ast::CondExpr(CondExpr {
+                arms: cond_arms,
+                else_arm,
+                ..
+            }) => arms(
+                D::text("cond"),
+                cond_arms
+                    .iter()
+                    .map(|arm| (arm.condition.to_dsl(), &arm.body)),
+                else_arm.as_deref(),
+            ),
+            Ast::LetExpr(LetExpr { bindings, body, .. }) => {
+                let bindings = bindings.iter().map(|binding| {
+                    D::text(binding.name.name.clone())
+                        .append(" = ")
+                        .append(binding.value.to_dsl())
+                });
+
+                D::text("let ")
+                    .append(D::intersperse(bindings, D::text(",").append(D::line())).nest(4))
+                    .append(" in")
+                    .append(D::line().append(body.to_dsl()).nest(4))
+                    .group()
+            }
+            Ast::MacroCall(MacroCall { name, args, .. }) => {
+                D::text(name.clone()).append(list(args.iter().map(Ast::to_dsl), "(", ")"))
+            }
+            Ast::ImportStatement(ImportStatement {
+                import_clause,
+                module,
+                ..
+            }) => {
+                let clause = match import_clause {
+                    ImportClause::Named(specifiers) => list(
+                        specifiers.iter().map(|specifier| {
+                            let alias = match &specifier.alias {
+                                Some(alias) => D::text(" as ").append(alias.name.clone()),
+                                None => D::nil(),
+                            };
+
+                            let name = &specifier.module_export_name;
+
+                            match name.raw {
+                                true => string_literal(&name.name),
+                                false => D::text(name.name.clone()),
+                            }
+                            .append(alias)
+                        }),
+                        "{",
+                        "}",
+                    ),
+                    ImportClause::Namespace { alias } => D::text(format!("* as {}", alias.name)),
+                };
+
+                D::text("import ")
+                    .append(clause)
+                    .append(" from ")
+                    .append(string_literal(module))
+            }
+            // Each expression on a line of its own. The grammar can't tell an expression starting
+            // with `(` or `[` from an application or an index of the one before it.
+            Ast::UnitTest(UnitTest { name, body, .. }) => D::text("unittest ")
+                .append(name.clone())
+                .append(" do")
+                .append(
+                    D::concat(body.iter().map(|expr| D::hardline().append(expr.to_dsl()))).nest(4),
+                )
+                .append(D::hardline())
+                .append("end"),
+            Ast::AmbientModule(AmbientModule { name, body, .. }) => D::text("declare module ")
+                .append(string_literal(name))
+                .append(" do")
+                .append(
+                    D::concat(
+                        body.iter()
+                            .map(|statement| D::hardline().append(statement.to_dsl())),
+                    )
+                    .nest(4),
+                )
+                .append(D::hardline())
+                .append("end"),
+            Ast::MappedType(MappedType {
+                index,
+                iterable,
+                remapped_as,
+                readonly_mod,
+                optional_mod,
+                body,
+                ..
+            }) => {
+                let modifier =
+                    |modifier: &Option<MappingModifier>, text: &'static str| match modifier {
+                        Some(MappingModifier::Add) => D::text(text),
+                        Some(MappingModifier::Remove) => D::text("-").append(text),
+                        None => D::nil(),
+                    };
+
+                let remapped_as = match remapped_as {
+                    Some(remapped_as) => D::text(" as ").append(remapped_as.to_dsl()),
+                    None => D::nil(),
+                };
+
+                D::text("map ")
+                    .append(modifier(readonly_mod, "readonly "))
+                    .append(modifier(optional_mod, "?"))
+                    .append(index.clone())
+                    .append(" in ")
+                    .append(iterable.to_dsl())
+                    .append(remapped_as)
+                    .append(" do")
+                    .append(D::line().append(body.to_dsl()).nest(4))
+                    .append(D::line())
+                    .append("end")
+                    .group()
+            }
+            Ast::FunctionType(FunctionType {
+                params,
+                return_type,
+                predicate,
+                ..
+            }) => {
+                let params = params.iter().map(|param| {
+                    let ellipsis = if param.ellipsis { "..." } else { "" };
+
+                    D::text(format!("{ellipsis}{}: ", param.name)).append(param.kind.to_dsl())
+                });
+
+                let return_type = match predicate {
+                    Some(TypePredicate {
+                        asserts,
+                        param,
+                        target,
+                        ..
+                    }) => {
+                        let asserts = if *asserts { "asserts " } else { "" };
+                        let predicate = D::text(format!("{asserts}{}", param.name));
+
+                        match target {
+                            Some(target) => predicate.append(" is ").append(target.to_dsl()),
+                            None => predicate,
+                        }
+                    }
+                    None => return_type.to_dsl(),
+                };
+
+                list(params, "(", ")").append(" => ").append(return_type)
+            }
+            // The marker of a node removed by a pass, which prints as nothing.
+            Ast::NoOp(_) => D::nil(),
+            Ast::SyntaxError(_) => D::text("/* syntax error */"),
+        }
+    }
+}
+
+impl Pretty for TypeAlias {
+    fn to_dsl(&self) -> D<()> {
+        let declaration = modifier(self.default, self.export, self.global)
+            .append("type ")
+            .append(self.name.name.clone())
+            .append(definition_options(&self.params))
+            .append(" as")
+            .append(D::line().append(self.body.to_dsl()).nest(4))
+            .group();
+
+        declaration
+    }
+}
+
+impl Pretty for Interface {
+    fn to_dsl(&self) -> D<()> {
+        let extends = match &self.extends {
+            Some(extends) => D::text(" extends ").append(extends.clone()),
+            None => D::nil(),
+        };
+
+        let body = list(self.definition.iter().map(Pretty::to_dsl), "{", "}");
+
+        let declaration = modifier(self.default, self.export, self.global)
+            .append("interface ")
+            .append(self.name.clone())
+            .append(definition_options(&self.params))
+            .append(extends)
+            .append(" ")
+            .append(body)
+            .group();
+
+        declaration
+    }
+}
+
+impl Pretty for UnionDeclaration {
+    fn to_dsl(&self) -> D<()> {
+        let discriminant = if self.discriminant == DEFAULT_DISCRIMINANT {
+            D::nil()
+        } else {
+            D::text(" by ").append(string_literal(&self.discriminant))
+        };
+
+        let variants = self.variants.iter().map(|variant| {
+            let body = if variant.body.is_empty() {
+                D::nil()
+            } else {
+                D::text(" ").append(variant.body.to_dsl())
+            };
+
+            D::text(variant.name.name.clone()).append(body)
+        });
+
+        let declaration = modifier(false, self.export, false)
+            .append("union ")
+            .append(self.name.name.clone())
+            .append(discriminant)
+            .append(" =")
+            .append(
+                D::line()
+                    .append(D::text("| ").flat_alt(D::nil()))
+                    .append(D::intersperse(variants, D::line().append("| ")))
+                    .nest(4),
+            )
+            .group();
+
+        declaration
+    }
+}
+
+impl Pretty for BrandDeclaration {
+    fn to_dsl(&self) -> D<()> {
+        let declaration = modifier(false, self.export, false)
+            .append("brand ")
+            .append(self.name.name.clone())
+            .append(" =")
+            .append(D::line().append(self.base.to_dsl()).nest(4))
+            .group();
+
+        declaration
+    }
+}
+
+impl Pretty for PatternDeclaration {
+    fn to_dsl(&self) -> D<()> {
+        let params = if self.params.is_empty() {
+            D::nil()
+        } else {
+            list(
+                self.params.iter().map(|param| D::text(param.name.clone())),
+                "(",
+                ")",
+            )
+        };
+
+        let declaration = D::text("pattern ")
+            .append(self.name.name.clone())
+            .append(params)
+            .append(" =")
+            .append(D::line().append(self.body.to_dsl()).nest(4))
+            .group();
+
+        declaration
+    }
+}
+
+impl Pretty for TypeLiteral {
+    fn to_dsl(&self) -> D<()> {
+        list(self.properties.iter().map(Pretty::to_dsl), "{", "}")
+    }
+}
+
+impl Pretty for ObjectProperty {
+    fn to_dsl(&self) -> D<()> {
+        let readonly = if self.readonly {
+            D::text("readonly ")
+        } else {
+            D::nil()
+        };
+
+        let optional = if self.optional {
+            D::text("?")
+        } else {
+            D::nil()
+        };
+
+        let key = match &self.key {
+            ObjectPropertyKey::Key(key) => name(key),
+            ObjectPropertyKey::Computed(ident) => D::text(format!("[{}]", ident.name)),
+            ObjectPropertyKey::Index(PropertyKeyIndex {
+                key,
+                iterable,
+                remapped_as,
+                ..
+            }) => {
+                let remapped_as = match remapped_as {
+                    Some(remapped_as) => D::text(" as ").append(remapped_as.to_dsl()),
+                    None => D::nil(),
+                };
+
+                D::text(format!("[{key} in "))
+                    .append(iterable.to_dsl())
+                    .append(remapped_as)
+                    .append("]")
+            }
+        };
+
+        readonly
+            .append(optional)
+            .append(key)
+            .append(": ")
+            .append(self.value.to_dsl())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::parse_newtype_program, test_support::parse};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::primitive("string", "string")]
+    #[case::union("1 | :a | ?T[]", "1 | \"a\" | ?T[]")]
+    #[case::application("Box(T, {value: T})", "Box(T, {value: T})")]
+    #[case::object("{a: number, b: string[]}", "{a: number, b: string[]}")]
+    #[case::condition(
+        "if T <: string then :a else match T do 1 -> :b end end",
+        "if T <: string then \"a\" else match T do 1 -> \"b\", else -> n…"
+    )]
+    fn display(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(parse!(Rule::expr, source).to_string(), expected);
+    }
+
+    #[test]
+    fn summary_is_cut_short() {
+        let union = parse!(Rule::expr, "Alpha | Beta | Gamma | Delta");
+        let object = parse!(Rule::expr, "{alpha: number, beta: string, gamma: boolean}");
+
+        assert_eq!(union.summary(16), "Alpha | Beta | …");
+        assert_eq!(object.summary(36), "{alpha: number, beta: string, …}");
+        assert_eq!(object.summary(20), "{alpha: number, …}");
+        assert_eq!(object.summary(3), "{…}");
+        assert_eq!(object.summary(2), "{…");
+        assert_eq!(object.summary(100).chars().count(), 45);
+        let tuple = format!("[{}]", vec!["Value"; 20].join(", "));
+        assert_eq!(
+            parse!(Rule::expr, &tuple).to_string().chars().count(),
+            SUMMARY_LEN
+        );
+    }
+
+    #[rstest]
+    #[case::alias("type A as 1")]
+    #[case::export("export type A as \"a\"")]
+    #[case::params("type A(T, U) defaults U = 1 where T <: string as T | U")]
+    #[case::const_params("type A(const T, U) where T <: string[] as T")]
+    #[case::union_of_intersections("type A as a & b | c & d")]
+    #[case::nested_union("type A as a | (b | c)")]
+    #[case::intersection_of_unions("type A as (a | b) & c")]
+    #[case::array("type A as (a | b)[]")]
+    #[case::function_operand("type A as ((a: string) => 1) | 2")]
+    #[case::map("type A(T) as map readonly ?K in keyof(T) as K do T[K] end")]
+    #[case::map_removing("type A(T) as map -readonly -?K in keyof(T) do T[K] end")]
+    #[case::object("type A as {a: 1, ?b: string[], readonly c: [1, 2], \"d-e\": 3}")]
+    #[case::application("type A as Record(string, a::b.c)")]
+    #[case::access("type A(T) as [T[\"k\"], T.k, NS::Inner::A]")]
+    #[case::namespaced_application("type A(T) as NS::Inner::Box(T)")]
+    #[case::import_type("type A(T) as import(\"./box.dl\")::Box(T)")]
+    #[case::parenthesized_callee("type A(T) as (B | C)(T)")]
+    #[case::interface("interface I(T) extends J {a: T}")]
+    #[case::global("global interface Window {a: 1}")]
+    #[case::union("export union Shape = Circle {radius: number} | Point")]
+    #[case::union_by("union Event by \"type\" =\n    | Click {x: number, y: number}\n    | KeyPress {code: string}")]
+    #[case::brand("/** Identifies a user */\nexport brand UserId = string")]
+    #[case::pattern("/** A boxed value */\npattern Boxed(T) = {value: T, meta: ?M}")]
+    #[case::pattern_without_params("pattern Empty = []")]
+    #[case::emit_tags("/** A */\n@emit(\"client\", \"server\")\nexport type A as 1")]
+    #[case::emit_tags_union("@emit(\"server\")\nunion Shape = Circle {radius: number} | Point")]
+    #[case::attributes("@deprecated\n@since(2, next)\ntype A as 1")]
+    #[case::doc("/**\n * A\n * b\n */\nexport type A as 1")]
+    #[case::conditions(
+        "type A(T) as if T <: 1 and not (T <: 2) or all!([T <: 3, T </:! 4]) then 1 end"
+    )]
+    #[case::match_let("type A(T) as match T do [?U] -> let V = U[] in V | 1, else -> never end")]
+    #[case::cond("type A(T) as cond do T <: 1 -> :a, T <: 2 -> (() => 1) | 2, else -> :b end")]
+    #[case::postfix_operands(
+        "type A(T) as [(T[])[0], ?(T[]), (?T)[0], (T.a)[0], T.(a | b), (0).a]"
+    )]
+    #[case::imports("import {A, B as C} from \"./m\"\n\nimport * as NS from :n")]
+    #[case::string_imports("import {\"a-b\" as AB} from \"./m\"")]
+    #[case::ambient_module(
+        "declare module :express do\n    /** A */\n    export type A as 1\n    type B as A\nend"
+    )]
+    #[case::unittest("unittest \"a\" do\n    assert!(1)\n    assert!(2)\nend")]
+    fn round_trip(#[case] source: &str) {
+        let ast = parse!(source);
+
+        let printed = ast.render_pretty_dsl(80);
+
+        assert_eq!(parse!(printed.trim()), ast, "{printed}");
+    }
+
+    /// The helpers macros declare print as source parsing back to them, `mutable!` removing
+    /// `readonly`.
+    #[test]
+    fn expanded_macros() {
+        use crate::{
+            ast::{Program, Span},
+            runtime::{Deep, Helpers, MacroRegistry},
+        };
+
+        let program = parse!("type A as 1");
+        let macros = MacroRegistry::default();
+        let helpers = Helpers::new(&program, &macros);
+        helpers.deep(Deep::Mutable);
+        let helper = Ast::Program(Program {
+            statements: helpers.take_declarations(),
+            span: Span::default(),
+        });
+
+        let printed = helper.to_dl();
+
+        assert!(printed.contains("map -readonly K in"), "{printed}");
+        assert_eq!(parse!(printed.trim()).simplify(), helper, "{printed}");
+    }
+
+    #[test]
+    fn built_nodes() {
+        use crate::ast::build::*;
+
+        assert_eq!(
+            index(union([ident("A"), ident("B")]), string_literal("k")).to_dl(),
+            "(A | B)[\"k\"]"
+        );
+
+        let pair = program([alias("Pair")
+            .param("T", None)
+            .body(tuple([ident("T"), ident("T")]))]);
+
+        assert_eq!(pair.to_dl(), "type Pair(T) as [T, T]\n");
+        assert_eq!(
+            pair.to_dl_with(&DlOptions { width: 12 }),
+            "type Pair(\n    T,\n) as\n    [T, T]\n"
+        );
+    }
+
+    /// Every program the parser can build prints as source parsing back to it.
+    #[quickcheck]
+    fn parses_back(program: Ast) -> bool {
+        let source = program.to_dl();
+
+        match parse_newtype_program(&source) {
+            Ok(parsed) if parsed == program => true,
+            parsed => panic!("{source}\n{parsed:#?}"),
+        }
+    }
+
+    #[test]
+    fn non_distributive_conditional() {
+        let ast = parse!("type A(T) as if T <:! string then 1 else 2 end").simplify();
+
+        assert_eq!(
+            ast.render_pretty_dsl(80),
+            "type A(T) as if T <:! string then 1 else 2 end\n"
+        );
+    }
+
+    #[test]
+    fn long_literals_break() {
+        let ast = parse!("type A as {first: 1, second: 2, third: 3}");
+
+        assert_eq!(
+            ast.render_pretty_dsl(20),
+            "type A as\n    {\n        first: 1,\n        second: 2,\n        third: 3,\n    }\n"
+        );
+    }
+}
@@ -0,0 +1,609 @@
+/// Translation of pest errors into user facing diagnostics.
+use pest::error::{Error, ErrorVariant, InputLocation};
+
+use crate::{
+    ast::Span,
+    diagnostic::{codes, Diagnostic, Suggestion},
+    parser::Rule,
+};
+
+/// Most suggestions listed in a single "expected ..." message.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Literal tokens worth suggesting when pest reports them as expected, with the description that
+/// already covers the token where it also starts a larger construct (`:` starts an atom string).
+/// Whitespace, comment openers and the first characters of identifiers or numbers are reported by
+/// pest as well, but only add noise.
+const SUGGESTED_TOKENS: &[(&str, Option<&str>)] = &[
+    ("then", None),
+    ("else", None),
+    ("end", None),
+    ("do", None),
+    ("in", None),
+    ("as", None),
+    ("from", None),
+    ("->", None),
+    ("=>", None),
+    ("=", Some(OPERATOR)),
+    (":", Some(EXPRESSION)),
+    (",", None),
+    (")", None),
+    ("]", None),
+    ("}", None),
+];
+
+/// Converts a pest error into a [`Diagnostic`], describing the expected input in terms of the
+/// language rather than grammar rule names.
+pub fn humanize_pest(err: Error<Rule>, source: &str) -> Diagnostic {
+    let (mut span, positives) = match err.location {
+        InputLocation::Pos(pos) => (Span::new(pos, pos), positives(&err.variant)),
+        InputLocation::Span((start, end)) => (Span::new(start, end), positives(&err.variant)),
+    };
+
+    if let ErrorVariant::CustomError { message } = err.variant {
+        return Diagnostic::error(message, span).with_code(codes::SYNTAX_ERROR);
+    }
+
+    // Recovery skips text wherever a property, element or arm fails, which isn't worth suggesting.
+    let mut rules: Vec<_> = positives
+        .into_iter()
+        .filter(|rule| *rule != Rule::skipped)
+        .collect();
+    let mut tokens = vec![];
+    let mut expected = vec![];
+
+    if let Some(attempts) = err.parse_attempts() {
+        expected = attempts
+            .expected_tokens()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        tokens = SUGGESTED_TOKENS
+            .iter()
+            .filter(|(token, _)| expected.iter().any(|expected| expected == token))
+            .collect();
+
+        // Pest only tracks rules in the error itself, so a failure after a literal token (e.g. a
+        // missing `)`) is reported where the enclosing rule started. Lookaheads also advance the
+        // attempts, hence they are only trusted when they expect a token worth suggesting.
+        if attempts.max_position > span.start && !tokens.is_empty() {
+            span = Span::new(attempts.max_position, attempts.max_position);
+            rules = vec![];
+        } else if attempts.max_position != span.start {
+            tokens = vec![];
+        }
+    }
+
+    if let Some(diagnostic) = confusion(source, span.start, &expected) {
+        return diagnostic;
+    }
+
+    let mut descriptions: Vec<_> = rules.into_iter().map(describe).collect();
+
+    // Operators can continue almost any expression, so they are the least interesting suggestion.
+    descriptions.sort_by_key(|description| *description == OPERATOR);
+
+    let mut suggestions: Vec<_> = tokens
+        .into_iter()
+        .filter(|(_, covered_by)| !covered_by.is_some_and(|d| descriptions.contains(&d)))
+        .map(|(token, _)| format!("`{token}`"))
+        .collect();
+
+    for description in descriptions {
+        if !suggestions.iter().any(|s| s == description) {
+            suggestions.push(description.to_string());
+        }
+    }
+
+    suggestions.truncate(MAX_SUGGESTIONS);
+
+    let found = found(source, span.start);
+
+    let message = if suggestions.is_empty() {
+        format!("unexpected {found}")
+    } else {
+        format!(
+            "expected {}, found {found}",
+            join_alternatives(&suggestions)
+        )
+    };
+
+    Diagnostic::error(message, span).with_code(codes::SYNTAX_ERROR)
+}
+
+/// A diagnostic of its own for the TypeScript syntax the parser stopped at, if it is one that
+/// users coming from TypeScript write out of habit: `=` declaring an alias, `extends` testing a
+/// condition or constraining a type parameter, a `? :` conditional and `;` separating properties. `expected` are the literal tokens
+/// the parser expected at `offset`.
+fn confusion(source: &str, offset: usize, expected: &[String]) -> Option<Diagnostic> {
+    let rest = source.get(offset..)?.trim_start();
+    let start = source.len() - rest.len();
+    let line_end = start + rest.find('\n').unwrap_or(rest.len());
+    let expects = |token: &str| expected.iter().any(|expected| expected == token);
+    let token = |len: usize| Span::new(start, start + len);
+
+    let is_operator = rest.starts_with('?') || rest.starts_with("<:") || word(rest) == "extends";
+
+    if let Some(question) = is_operator
+        .then(|| top_level(source, start, line_end, '?'))
+        .flatten()
+    {
+        let diagnostic = Diagnostic::error(
+            "use `if ... then ... else ... end` instead of `? :`",
+            Span::new(question, question + 1),
+        )
+        .with_note("a conditional type is written `if T <: U then A else B end`")
+        .with_code(codes::SYNTAX_ERROR);
+
+        return Some(match ternary(source, start, question, line_end) {
+            Some(suggestion) => diagnostic.with_suggestion(suggestion),
+            None => diagnostic,
+        });
+    }
+
+    let diagnostic = if rest.starts_with('=') && !rest.starts_with("=>") && expects("as") {
+        Diagnostic::error("use `as` instead of `=` to declare an alias", token(1))
+            .with_note("an alias is declared as `type Name as Type`")
+            .with_suggestion(Suggestion::replace(token(1), "as", "replace `=` with `as`"))
+    } else if word(rest) == "extends" && expects("<:") {
+        let span = token("extends".len());
+
+        Diagnostic::error("use `<:` instead of `extends`", span)
+            .with_note("a condition is written `T <: U`")
+            .with_suggestion(Suggestion::replace(
+                span,
+                "<:",
+                "replace `extends` with `<:`",
+            ))
+    } else if word(rest) == "extends" && expects(")") && expects(",") {
+        let diagnostic = Diagnostic::error(
+            "use a `where` clause to constrain a type parameter",
+            token("extends".len()),
+        )
+        .with_note("a constraint is written `type A(T) where T <: U as ...`");
+
+        match where_clause(source, start) {
+            Some(suggestion) => diagnostic.with_suggestion(suggestion),
+            None => diagnostic,
+        }
+    } else if rest.starts_with(';') && expects("}") {
+        Diagnostic::error("use `,` instead of `;` to separate properties", token(1))
+            .with_note("object types and interface bodies are written `{a: A, b: B}`")
+            .with_suggestion(Suggestion::replace(token(1), ",", "replace `;` with `,`"))
+    } else {
+        return None;
+    };
+
+    Some(diagnostic.with_code(codes::SYNTAX_ERROR))
+}
+
+/// The parameter list whose parameter is constrained by the `extends` at `extends`, rewritten with
+/// the constraint in a `where` clause, when the list is followed by the body of the declaration.
+fn where_clause(source: &str, extends: usize) -> Option<Suggestion> {
+    let before = source[..extends].trim_end();
+    let name = &before[before
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .map_or(0, |at| at + 1)..];
+    let start = before.len() - name.len();
+
+    let constraint_start = extends + "extends".len();
+    let constraint_end = parameter_end(source, constraint_start)?;
+    let mut close = constraint_end;
+
+    while !source[close..].starts_with(')') {
+        close = parameter_end(source, close + 1)?;
+    }
+
+    let after = source[close + 1..].trim_start();
+
+    if name.is_empty() || !(word(after) == "as" || after.starts_with('{')) {
+        return None;
+    }
+
+    Some(Suggestion::replace(
+        Span::new(start, close + 1),
+        format!(
+            "{name}{}) where {name} <: {}",
+            &source[constraint_end..close],
+            source[constraint_start..constraint_end].trim()
+        ),
+        "move the constraint to a `where` clause",
+    ))
+}
+
+/// Offset of the first `,` or `)` from `start` that isn't nested in brackets or quoted.
+fn parameter_end(source: &str, start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+
+    for (i, c) in source[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ',' | ')') if depth == 0 => return Some(start + i),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// The `if` a `C ? A : B` conditional whose `?` is at `question` is rewritten to, when the
+/// conditional is the rest of its line and starts with its line's `if` or after its alias's `as`.
+/// `operator` is where the parser stopped in it.
+fn ternary(source: &str, operator: usize, question: usize, line_end: usize) -> Option<Suggestion> {
+    let colon = top_level(source, question + 1, line_end, ':')?;
+    let line_start = source[..operator]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let before = &source[line_start..operator];
+
+    let (start, condition) = match before.rfind("if ") {
+        Some(at) if word(&before[at..]) == "if" => (
+            line_start + at,
+            &source[line_start + at + "if".len()..question],
+        ),
+        _ => {
+            let at = before.rfind(" as ")?;
+            let start = line_start + at + " as ".len();
+            (start, &source[start..question])
+        }
+    };
+
+    let then_branch = source[question + 1..colon].trim();
+    let else_branch = source[colon + 1..line_end].trim();
+    let else_branch = else_branch
+        .strip_suffix(" end")
+        .unwrap_or(else_branch)
+        .trim();
+    let condition = condition.trim().replace(" extends ", " <: ");
+
+    Some(Suggestion::replace(
+        Span::new(start, source[..line_end].trim_end().len()),
+        format!("if {condition} then {then_branch} else {else_branch} end"),
+        "rewrite it as an `if`",
+    ))
+}
+
+/// Offset of the first `token` between `start` and `end` with whitespace on both sides that isn't
+/// nested in brackets or quoted.
+fn top_level(source: &str, start: usize, end: usize, token: char) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (i, c) in source[start..end].char_indices() {
+        let at = start + i;
+
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            (None, c)
+                if c == token
+                    && depth == 0
+                    && previous.is_whitespace()
+                    && source[at + 1..].starts_with(char::is_whitespace) =>
+            {
+                return Some(at);
+            }
+            _ => {}
+        }
+
+        previous = c;
+    }
+
+    None
+}
+
+/// The word `text` starts with.
+fn word(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(text.len());
+
+    &text[..end]
+}
+
+fn positives(variant: &ErrorVariant<Rule>) -> Vec<Rule> {
+    match variant {
+        ErrorVariant::ParsingError { positives, .. } => positives.clone(),
+        ErrorVariant::CustomError { .. } => vec![],
+    }
+}
+
+const OPERATOR: &str = "an operator";
+
+const EXPRESSION: &str = "an expression";
+
+/// Human readable description of what the rule matches.
+fn describe(rule: Rule) -> &'static str {
+    use Rule::*;
+
+    match rule {
+        EOI => "end of input",
+        COMMENT | BLOCK_COMMENT | LINE_COMMENT | FEATURE_PRAGMA | OPTIONS_PRAGMA
+        | SOURCE_PRAGMA | WHITESPACE => "whitespace",
+        keyword => "a keyword",
+        neg => "`-`",
+        program | test_expr | next_statement | statement => "a declaration",
+        module_header | options_pragma => "an `#options` pragma",
+        import_statement => "an import",
+        import_clause | namespace_import | named_import => "an import clause",
+        from_clause => "`from`",
+        import_specifier => "an imported name",
+        unittest => "a unittest",
+        ambient_module => "a `declare module` block",
+        conditional_block => "an `#if` block",
+        conditional_body => "a declaration",
+        type_alias => "a type alias",
+        interface => "an interface",
+        union_declaration => "a union declaration",
+        brand_declaration => "a brand declaration",
+        pattern_declaration => "a pattern declaration",
+        attribute => "an attribute",
+        attribute_arg => "an attribute argument",
+        variant => "a variant",
+        extends_clause => "an `extends` clause",
+        defaults_caluse => "a `defaults` clause",
+        where_clause => "a `where` clause",
+        type_parameter_default => "a type parameter default",
+        type_constraint => "a type parameter constraint",
+        type_parameters => "a type parameter",
+        type_parameter => "a type parameter",
+        const_keyword => "`const`",
+        definition_options => "type parameters",
+        expr | expr1 | expr_primary | term | top_type | bottom_type | builtin | builtin_keyword
+        | keyof | builtin_readonly | macro_call | function_type | boolean | tuple | let_expr
+        | map_expr | if_expr | if_let_expr | match_expr | cond_expr | any | unknown | never
+        | primitive | primitive_null | primitive_undefined | primitive_void | primitive_string
+        | primitive_boolean | primitive_number | primitive_object | primitive_bigint
+        | primitive_symbol | literal_true | literal_false => EXPRESSION,
+        primitive_unique_symbol => EXPRESSION,
+        tuple_element | element_recovery => EXPRESSION,
+        this_type => EXPRESSION,
+        import_type => EXPRESSION,
+        expr_prefix | builtin_prefix | expr_infix | expr_postfix | extends_prefix
+        | extends_infix | colon2 | dot_op | array_modifier | indexed_access | application
+        | pipe | union | intersection | extends | not_extends | equals | not_equals
+        | strict_equals | strict_not_equals | and | or | not => OPERATOR,
+        argument_list | macro_argument_list | macro_argument => "an argument",
+        condition_list => "a list of conditions",
+        parameters | unnamed_parameters | named_parameters | named_parameter
+        | unnamed_parameter => "a parameter",
+        ellipsis_token => "`...`",
+        type_predicate => "a type predicate",
+        is_keyword => "`is`",
+        asserts_keyword => "`asserts`",
+        infer | optional_modifier => "`?`",
+        object_literal => "an object literal",
+        object_property | object_member | property_recovery => "a property",
+        property_key
+        | property_key_prefix
+        | property_key_inner
+        | computed_property_key
+        | index_property_key => "a property key",
+        let_binding => "a binding",
+        let_pattern => "a `let` pattern",
+        let_keyword => "`let`",
+        end_keyword => "`end`",
+        else_keyword => "`else`",
+        pass_keyword => "`pass`",
+        skipped | skipped_end | skipped_token | skipped_nested => "skipped text",
+        match_arm | match_member | arm_recovery => "a match arm",
+        pattern_tuple => "a pattern",
+        cond_arm => "a cond arm",
+        else_arm => "an `else` arm",
+        extends_expr | extends_primary => "a condition",
+        readonly_modifier => "`readonly`",
+        mapping_modifiers => "a modifier",
+        mapping_readonly => "`readonly` or `-readonly`",
+        mapping_optional => "`?` or `-?`",
+        export => "`export`",
+        default_export => "`export default`",
+        export_default => "a default export",
+        global => "`global`",
+        string | atom_string | double_quote_string | single_quote_string => "a string",
+        template_string => "a template string",
+        macro_ident => "a macro name",
+        ident | ident_start | ident_chars => "an identifier",
+        number => "a number",
+    }
+}
+
+/// Describes the token at `offset`.
+fn found(source: &str, offset: usize) -> String {
+    let rest = source.get(offset..).unwrap_or_default().trim_start();
+
+    let Some(first) = rest.chars().next() else {
+        return "end of input".to_string();
+    };
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+    let token = if is_word(first) {
+        rest.split(|c| !is_word(c)).next().unwrap_or_default()
+    } else {
+        &rest[..first.len_utf8()]
+    };
+
+    format!("`{token}`")
+}
+
+/// Joins alternatives as "a", "a or b" and "a, b, or c".
+fn join_alternatives(alternatives: &[String]) -> String {
+    match alternatives {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{first} or {second}"),
+        [init @ .., last] => format!("{}, or {last}", init.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::{parse_newtype_program, parse_program_with_errors},
+        source::SourceFile,
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use textwrap_macros::dedent;
+
+    fn humanize(source: &str) -> Diagnostic {
+        let err = parse_newtype_program(source).unwrap_err();
+        humanize_pest(*err, source)
+    }
+
+    #[rstest]
+    #[case::missing_body("type A as", "expected an expression, found end of input")]
+    #[case::missing_then(
+        "type A as if a <: b 1 end",
+        "expected `then` or an operator, found `1`"
+    )]
+    #[case::missing_name("type as 1", "expected an identifier, found `as`")]
+    #[case::missing_as(
+        "type A 1",
+        "expected `as`, a `defaults` clause, or a `where` clause, found `1`"
+    )]
+    #[case::missing_interface_body(
+        "interface I",
+        "expected an `extends` clause, a `defaults` clause, a `where` clause, or an object literal, found end of input"
+    )]
+    #[case::missing_colon("type A as { a 1 }", "expected `:`, found `1`")]
+    #[case::unclosed_tuple(
+        "type A as [1, 2",
+        "expected `,`, `]`, or an operator, found end of input"
+    )]
+    #[case::missing_arrow(
+        "type A as match a do 1 end",
+        "expected `->` or an operator, found `end`"
+    )]
+    #[case::empty_cond("type A as cond do end", "expected a condition, found `end`")]
+    #[case::unclosed_import("import { a from :b", "expected `as`, `,`, or `}`, found `from`")]
+    #[case::unclosed_parameters("type A(x as 1", "expected `,` or `)`, found `as`")]
+    #[case::missing_binding("type A as let in 1", "expected an identifier, found `in`")]
+    #[case::not_a_declaration("foo", "expected a declaration, found `foo`")]
+    #[case::condition_in_tuple(
+        "type A(T) as [T <: string]",
+        "expected `,`, `]`, or an operator, found `<`"
+    )]
+    fn test_humanize_pest(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(humanize(source).message, expected);
+    }
+
+    #[rstest]
+    #[case::equals_for_as(
+        "type A(T) = [T]",
+        "use `as` instead of `=` to declare an alias",
+        "type A(T) as [T]"
+    )]
+    #[case::extends_for_subtype(
+        "type A(T) as if T extends string then 1 else 2 end",
+        "use `<:` instead of `extends`",
+        "type A(T) as if T <: string then 1 else 2 end"
+    )]
+    #[case::extends_in_parameters(
+        "type S(T extends string) as T",
+        "use a `where` clause to constrain a type parameter",
+        "type S(T) where T <: string as T"
+    )]
+    #[case::extends_in_several_parameters(
+        "interface I(T, U extends {a: [1, 2]}, V) {a: U}",
+        "use a `where` clause to constrain a type parameter",
+        "interface I(T, U, V) where U <: {a: [1, 2]} {a: U}"
+    )]
+    #[case::ternary(
+        "type A(T) as T extends string ? {a: 1} : [2, 3]",
+        "use `if ... then ... else ... end` instead of `? :`",
+        "type A(T) as if T <: string then {a: 1} else [2, 3] end"
+    )]
+    #[case::ternary_in_if(
+        "type A(T) as if T <: string ? 1 : 2 end",
+        "use `if ... then ... else ... end` instead of `? :`",
+        "type A(T) as if T <: string then 1 else 2 end"
+    )]
+    #[case::semicolons_in_interface(
+        "interface A { a: string; b: number }",
+        "use `,` instead of `;` to separate properties",
+        "interface A { a: string, b: number }"
+    )]
+    fn typescript_habits(#[case] source: &str, #[case] message: &str, #[case] fixed: &str) {
+        let diagnostic = match parse_program_with_errors(source) {
+            Err(err) => humanize_pest(*err, source),
+            Ok((_, errors)) => errors.into_iter().next().unwrap(),
+        };
+
+        assert_eq!(diagnostic.message, message);
+        assert_eq!(diagnostic.code, Some(codes::SYNTAX_ERROR.id));
+
+        let suggestion = diagnostic.suggestion.unwrap();
+        let mut applied = source.to_string();
+        applied.replace_range(
+            suggestion.span.start..suggestion.span.end,
+            &suggestion.replacement,
+        );
+
+        assert_eq!(applied, fixed);
+        assert!(parse_newtype_program(&applied).is_ok(), "{applied}");
+    }
+
+    #[test]
+    fn ternary_without_a_mechanical_rewrite() {
+        let diagnostic = humanize("type A as [T ? 1 : 2]");
+
+        assert_eq!(
+            diagnostic.message,
+            "use `if ... then ... else ... end` instead of `? :`"
+        );
+        assert_eq!((diagnostic.span.start, diagnostic.span.end), (13, 14));
+        assert_eq!(diagnostic.suggestion, None);
+    }
+
+    #[test]
+    fn suggestions_are_capped() {
+        let source = "type A as 1 2";
+
+        let message = humanize(source).message;
+
+        let suggestions = message.trim_start_matches("expected ").split(", ").count();
+
+        assert!(suggestions <= MAX_SUGGESTIONS, "{message}");
+    }
+
+    #[test]
+    fn custom_errors_keep_their_message() {
+        let source = "type A as 1 /* a";
+
+        let diagnostic = humanize(source);
+
+        assert_eq!(diagnostic.message, "unterminated block comment");
+        assert_eq!((diagnostic.span.start, diagnostic.span.end), (12, 14));
+    }
+
+    #[test]
+    fn excerpt_points_at_failure() {
+        let source = "type A as if a <: b 1 end";
+
+        assert_eq!(
+            humanize(source).render(&SourceFile::new("", source)),
+            dedent!(
+                "
+                error[DL0001]: expected `then` or an operator, found `1`
+                 --> 1:21
+                  |
+                1 | type A as if a <: b 1 end
+                  |                     ^
+                  = help: run `newtype explain DL0001` for more information"
+            )
+            .trim_start()
+        );
+    }
+}
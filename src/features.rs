@@ -0,0 +1,274 @@
+/// Unstable constructs, which a program only uses once their feature is enabled, by a
+/// `#feature(name, ...)` line in the source or by the `features` option.
+///
+/// ```text
+/// #feature(pattern_synonyms)
+///
+/// pattern Boxed(T) = {value: T}
+/// ```
+use std::collections::BTreeSet;
+
+use crate::{
+    ast::{
+        pattern_declaration::PatternDeclaration,
+        visit::{walk_ast, Visit},
+        Ast, ExtendsExpr, ExtendsInfixOp, Span,
+    },
+    config,
+    diagnostic::{codes, Diagnostic},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    pub name: &'static str,
+    /// What the feature allows, completing "... are unstable".
+    pub description: &'static str,
+}
+
+pub const PATTERN_SYNONYMS: Feature = Feature {
+    name: "pattern_synonyms",
+    description: "pattern synonyms",
+};
+
+pub const NON_DISTRIBUTIVE: Feature = Feature {
+    name: "non_distributive",
+    description: "non-distributive conditions, `<:!`,",
+};
+
+/// Every feature that can be enabled.
+pub const FEATURES: &[Feature] = &[PATTERN_SYNONYMS, NON_DISTRIBUTIVE];
+
+/// Names of the enabled features.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureSet(BTreeSet<String>);
+
+impl FeatureSet {
+    pub fn new<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+        Self(names.into_iter().map(Into::into).collect())
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.0.contains(feature.name)
+    }
+
+    /// The features of `self` and those `source` enables with `#feature` lines, together with the
+    /// warnings for the names in them that are not features.
+    pub fn with_pragmas(&self, source: &str) -> (Self, Vec<Diagnostic>) {
+        let mut features = self.clone();
+        let mut diagnostics = vec![];
+
+        for (name, span) in pragmas(source) {
+            if !FEATURES.iter().any(|feature| feature.name == name) {
+                diagnostics.push(
+                    Diagnostic::warning(format!("unknown feature `{name}`"), span)
+                        .with_note(format!(
+                            "the known features are {}",
+                            FEATURES
+                                .iter()
+                                .map(|feature| feature.name)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ))
+                        .with_code(codes::UNKNOWN_FEATURE),
+                );
+            }
+
+            features.0.insert(name.to_string());
+        }
+
+        (features, diagnostics)
+    }
+}
+
+/// The names listed by the `#feature(name, ...)` lines of `source`, which the grammar skips as
+/// trivia, with their spans.
+fn pragmas(source: &str) -> Vec<(&str, Span)> {
+    let mut names = vec![];
+    let mut offset = 0;
+
+    for line in source.split_inclusive('\n') {
+        let start = offset + line.len() - line.trim_start().len();
+        offset += line.len();
+
+        let Some(list) = line.trim().strip_prefix("#feature") else {
+            continue;
+        };
+
+        let Some(list) = list
+            .trim_start()
+            .strip_prefix('(')
+            .and_then(|list| list.split_once(')'))
+            .map(|(list, _)| list)
+        else {
+            continue;
+        };
+
+        // Offset of the list in the source.
+        let mut position = start + line.trim().find('(').unwrap() + 1;
+
+        for name in list.split(',') {
+            let leading = name.len() - name.trim_start().len();
+            let trimmed = name.trim();
+
+            if !trimmed.is_empty() {
+                let start = position + leading;
+                names.push((trimmed, Span::new(start, start + trimmed.len())));
+            }
+
+            position += name.len() + 1;
+        }
+    }
+
+    names
+}
+
+/// Errors for the unstable constructs `program` uses without their feature enabled.
+pub fn check(program: &Ast, features: &FeatureSet) -> Vec<Diagnostic> {
+    let mut gates = Gates {
+        features,
+        diagnostics: vec![],
+    };
+    gates.visit_ast(program);
+    gates.diagnostics
+}
+
+struct Gates<'f> {
+    features: &'f FeatureSet,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Gates<'_> {
+    fn require(&mut self, feature: Feature, span: Span) {
+        if self.features.is_enabled(feature) {
+            return;
+        }
+
+        self.diagnostics.push(
+            Diagnostic::error(format!("{} are unstable", feature.description), span)
+                .with_note(format!(
+                    "add `#feature({})` to the source, or `features = [\"{}\"]` to {}",
+                    feature.name,
+                    feature.name,
+                    config::FILE_NAME
+                ))
+                .with_code(codes::FEATURE_NOT_ENABLED),
+        );
+    }
+}
+
+impl<'a> Visit<'a> for Gates<'_> {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        match node {
+            Ast::ExtendsInfixOp(ExtendsInfixOp {
+                non_distributive: true,
+                span,
+                ..
+            })
+            | Ast::ExtendsExpr(ExtendsExpr {
+                non_distributive: true,
+                span,
+                ..
+            }) => self.require(NON_DISTRIBUTIVE, *span),
+            _ => {}
+        }
+
+        walk_ast(self, node)
+    }
+
+    fn visit_pattern_declaration(&mut self, pattern: &'a PatternDeclaration) {
+        self.require(PATTERN_SYNONYMS, pattern.name.span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn messages(source: &str, options: &CompileOptions) -> Vec<String> {
+        let diagnostics = match compile(source, options) {
+            Ok(output) => output.diagnostics,
+            Err(diagnostics) => diagnostics,
+        };
+
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    const SOURCE: &str = dedent!(
+        r#"
+        pattern Boxed(T) = {value: T}
+        type A(T) as match T do Boxed(?V) -> V, else -> never end
+        type B(T) as if T <:! string then 1 else 2 end
+        "#
+    );
+
+    #[test]
+    fn gated() {
+        assert_eq!(
+            messages(SOURCE, &CompileOptions::default()),
+            vec![
+                "pattern synonyms are unstable",
+                "non-distributive conditions, `<:!`, are unstable"
+            ]
+        );
+    }
+
+    #[test]
+    fn enabled_by_pragma() {
+        let source = format!("#feature(pattern_synonyms, non_distributive)\n{SOURCE}");
+
+        assert_eq!(
+            compile(&source, &CompileOptions::default())
+                .unwrap()
+                .typescript
+                .trim(),
+            "type A<T> = T extends {value: infer V} ? V : never;\n\n\
+             type B<T> = [T] extends [string] ? 1 : 2;"
+        );
+    }
+
+    #[test]
+    fn enabled_by_options() {
+        let options = CompileOptions {
+            features: FeatureSet::new(["pattern_synonyms"]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(&format!("#feature(non_distributive)\n{SOURCE}"), &options),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            messages(SOURCE, &options),
+            vec!["non-distributive conditions, `<:!`, are unstable"]
+        );
+    }
+
+    #[test]
+    fn unknown_feature() {
+        let source = "#feature(non_distributive, variadics)\ntype A as 1";
+        let (features, diagnostics) = FeatureSet::default().with_pragmas(source);
+
+        assert!(features.is_enabled(NON_DISTRIBUTIVE));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    diagnostic.span.start,
+                    diagnostic.span.end
+                ))
+                .collect::<Vec<_>>(),
+            vec![("unknown feature `variadics`", 27, 36)]
+        );
+        assert_eq!(
+            messages(source, &CompileOptions::default()),
+            vec!["unknown feature `variadics`"]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+/// Conditional compilation: the statements of an `#if flag("name") do ... end` block are compiled
+/// only when the flag is set, by `--flag name` or the `flags` option, and those after its `#else`
+/// only when it isn't.
+///
+/// ```text
+/// #if flag("experimental") do
+///   type Api as {v2: true}
+/// #else
+///   type Api as {v1: true}
+/// end
+/// ```
+///
+/// Unlike the tags of `@emit`, which filter the simplified program, the statements left out are
+/// pruned right after parsing, so they aren't resolved nor linted and can name types that only
+/// exist when the flag is set. They still have to parse. The parser tags each statement of a block
+/// with the flag it depends on, see [`attribute::IF_FLAG`], pruning only removes statements, the
+/// others keep their spans.
+use std::collections::HashSet;
+
+use crate::{
+    ast::{attribute, Ast, Program},
+    diagnostic::{Diagnostic, Severity},
+};
+
+/// `program` without the statements of the `#if` blocks whose flag, among `flags`, says they are
+/// left out, and with the tags of those kept removed. A flag not set is false, with a note the
+/// first time a block tests it.
+pub fn prune(program: &Ast, flags: &[String]) -> (Ast, Vec<Diagnostic>) {
+    let Ast::Program(Program { statements, span }) = program else {
+        return (program.clone(), vec![]);
+    };
+
+    let mut diagnostics = vec![];
+    let mut noted = HashSet::new();
+
+    let statements = statements
+        .iter()
+        .filter_map(|statement| {
+            let Ast::Statement(statement) = statement else {
+                return Some(statement.clone());
+            };
+
+            let mut statement = statement.clone();
+
+            let (tags, attributes) =
+                statement
+                    .attributes
+                    .into_iter()
+                    .partition::<Vec<_>, _>(|attribute| {
+                        [attribute::IF_FLAG, attribute::ELSE_FLAG]
+                            .contains(&attribute.name.name.as_str())
+                    });
+            statement.attributes = attributes;
+
+            // The blocks the statement is in, outermost last, are tested outermost first: the
+            // flag of a block left out isn't tested.
+            let mut kept = true;
+
+            for tag in tags.iter().rev() {
+                let flag = tag.strings().next().unwrap_or_default();
+                let set = flags.iter().any(|set| set == flag);
+
+                if !set && noted.insert(flag.to_string()) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Note,
+                            format!("flag `{flag}` isn't set, it is false"),
+                            tag.span,
+                        )
+                        .with_note(format!("set it with `--flag {flag}` or the `flags` option")),
+                    );
+                }
+
+                kept = set == (tag.name.name == attribute::IF_FLAG);
+
+                if !kept {
+                    break;
+                }
+            }
+
+            kept.then_some(Ast::Statement(statement))
+        })
+        .collect();
+
+    let program = Ast::Program(Program {
+        statements,
+        span: *span,
+    });
+
+    (program, diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        compile::{compile, compile_streaming, CompileOptions},
+        diagnostic::Severity,
+    };
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn flagged(flags: &[&str]) -> CompileOptions {
+        CompileOptions {
+            flags: flags.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        }
+    }
+
+    const SOURCE: &str = dedent!(
+        r#"
+        type A as 1
+        #if flag("experimental") do
+          type B as 2
+          #if flag("beta") do
+            type C as 3
+          end
+        #else
+          type B as 4
+        end
+        type D as B
+        "#
+    );
+
+    #[test]
+    fn included_and_excluded_branches() {
+        assert_eq!(
+            compile(SOURCE, &flagged(&["experimental", "beta"]))
+                .unwrap()
+                .typescript,
+            "type A = 1;\n\ntype B = 2;\n\ntype C = 3;\n\ntype D = B;\n\n"
+        );
+        assert_eq!(
+            compile(SOURCE, &flagged(&["experimental"]))
+                .unwrap()
+                .typescript,
+            "type A = 1;\n\ntype B = 2;\n\ntype D = B;\n\n"
+        );
+    }
+
+    #[test]
+    fn else_clause() {
+        let output = compile(SOURCE, &flagged(&[])).unwrap();
+
+        assert_eq!(
+            output.typescript,
+            "type A = 1;\n\ntype B = 4;\n\ntype D = B;\n\n"
+        );
+        assert_eq!(
+            output
+                .diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.severity, diagnostic.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(Severity::Note, "flag `experimental` isn't set, it is false")]
+        );
+        assert_eq!(
+            &SOURCE[output.diagnostics[0].span.start..output.diagnostics[0].span.end],
+            r#"#if flag("experimental")"#
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let mut typescript = String::new();
+
+        compile_streaming(SOURCE, &flagged(&["experimental"]), |chunk| {
+            typescript.push_str(chunk)
+        })
+        .unwrap();
+
+        assert_eq!(
+            typescript,
+            compile(SOURCE, &flagged(&["experimental"]))
+                .unwrap()
+                .typescript
+        );
+    }
+
+    #[test]
+    fn excluded_code_is_not_checked() {
+        let source = dedent!(
+            r#"
+            #if flag("v2") do
+              export default Api
+            end
+            type Version as :one
+            "#
+        );
+
+        assert_eq!(
+            compile(source, &flagged(&[])).unwrap().typescript,
+            "type Version = 'one';\n\n"
+        );
+        assert!(compile(source, &flagged(&["v2"])).is_err());
+    }
+
+    #[test]
+    fn spans_of_kept_code() {
+        let source = "type A as 1\n#if flag(\"x\") do\n  export default Missing\nend\n";
+        let diagnostics = compile(source, &flagged(&["x"])).unwrap_err();
+
+        assert_eq!(
+            &source[diagnostics[0].span.start..diagnostics[0].span.end],
+            "Missing"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+/// Hoisting of the subexpressions repeated in an alias, which are bound once by a conditional
+/// type inferring them instead of being emitted, and instantiated, at every use.
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    rc::Rc,
+};
+
+use crate::{
+    ast::{consed::Consed, Ast, ExtendsExpr, Ident, MappedType, Program, Span, TypeAlias, Walk},
+    names::Gensym,
+};
+
+/// The smallest threshold: the hoisted expression itself is left once in the alias, any
+/// expression is repeated at least as much as that.
+pub const MIN_THRESHOLD: usize = 2;
+
+/// Rewrites each type alias of a simplified program whose body repeats a subexpression at least
+/// `threshold` times, `T["a"]["b"]` becoming `__dl_tmp_0` in
+/// `[T["a"]["b"]] extends [infer __dl_tmp_0] ? ... : never`. The test is wrapped in tuples so that
+/// it doesn't distribute over a union, which the expression it replaces wouldn't do.
+///
+/// The largest subexpression is hoisted first, until none is repeated enough. An expression that
+/// mentions a name inferred or iterated over somewhere in the body isn't hoisted, as it would be
+/// moved out of the scope of that name. Nothing is hoisted below [`MIN_THRESHOLD`].
+pub fn hoist_common(program: &Ast, threshold: usize) -> Ast {
+    let Ast::Program(Program { statements, span }) = program else {
+        return program.clone();
+    };
+
+    if threshold < MIN_THRESHOLD {
+        return program.clone();
+    }
+
+    let mut gensym = Gensym::new(program);
+
+    let statements = statements
+        .iter()
+        .map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(alias) => Ast::Statement(
+                    Ast::TypeAlias(TypeAlias {
+                        body: hoist_alias_body(&alias.body, threshold, &mut gensym).into(),
+                        ..alias.clone()
+                    })
+                    .into(),
+                ),
+                _ => statement.clone(),
+            },
+            _ => statement.clone(),
+        })
+        .collect();
+
+    Ast::Program(Program {
+        statements,
+        span: *span,
+    })
+}
+
+fn hoist_alias_body(body: &Ast, threshold: usize, gensym: &mut Gensym) -> Ast {
+    let scoped = scoped_names(body);
+
+    let mut body = body.clone();
+
+    while let Some(common) = most_repeated(&body, threshold, &scoped) {
+        let name = gensym.fresh("tmp");
+
+        let (rewritten, _) = body.prewalk((), &|node, ctx| {
+            if node == common {
+                (Ast::Ident(name.clone()), ctx)
+            } else {
+                (node, ctx)
+            }
+        });
+
+        body = Ast::ExtendsExpr(ExtendsExpr {
+            lhs: common.into(),
+            rhs: Ast::Infer(Rc::new(Ast::Ident(name))).into(),
+            then_branch: rewritten.into(),
+            else_branch: Ast::NeverKeyword(Span::default()).into(),
+            non_distributive: true,
+            span: body.as_span(),
+        });
+    }
+
+    body
+}
+
+/// The largest subexpression of `body` that appears at least `threshold` times and can be
+/// hoisted, the first one found if several are as large.
+fn most_repeated(body: &Ast, threshold: usize, scoped: &HashSet<String>) -> Option<Ast> {
+    // Occurrences of each subexpression, interned so that equal ones, whatever their spans, are
+    // counted together.
+    let consed = RefCell::new(Consed::default());
+    let counts: RefCell<Vec<(Rc<Ast>, usize)>> = RefCell::new(vec![]);
+    let indices: RefCell<HashMap<*const Ast, usize>> = RefCell::new(HashMap::new());
+
+    body.prewalk((), &|node, ctx| {
+        if is_hoistable(&node, scoped) {
+            let shared = consed.borrow_mut().intern(node.clone());
+            let mut counts = counts.borrow_mut();
+
+            match indices.borrow_mut().entry(Rc::as_ptr(&shared)) {
+                Entry::Occupied(index) => counts[*index.get()].1 += 1,
+                Entry::Vacant(index) => {
+                    index.insert(counts.len());
+                    counts.push((shared, 1));
+                }
+            }
+        }
+
+        (node, ctx)
+    });
+
+    counts
+        .into_inner()
+        .into_iter()
+        .filter(|(_, count)| *count >= threshold)
+        .map(|(node, _)| (node.node_count(), node))
+        .rev()
+        .max_by_key(|(size, _)| *size)
+        .map(|(_, node)| Rc::unwrap_or_clone(node))
+}
+
+/// Whether `node` is worth binding to a name and can be moved to the top of the alias: it is not
+/// a name or a literal, and it doesn't depend on a name bound inside the alias.
+fn is_hoistable(node: &Ast, scoped: &HashSet<String>) -> bool {
+    if node.node_count() < 2 {
+        return false;
+    }
+
+    !node.any(|node| match node {
+        Ast::Ident(Ident { name, .. }) => scoped.contains(name),
+        // The names an infer or a mapped type binds are only in scope of part of the alias.
+        Ast::Infer(_) | Ast::MappedType(_) => true,
+        _ => false,
+    })
+}
+
+/// Names bound by an `infer` or iterated over by a mapped type anywhere in `body`.
+fn scoped_names(body: &Ast) -> HashSet<String> {
+    let names = RefCell::new(HashSet::new());
+
+    body.traverse_ctl(
+        (),
+        &|node, ctx| {
+            match &node {
+                Ast::Infer(inner) => {
+                    if let Some(ident) = inner.as_ident() {
+                        names.borrow_mut().insert(ident.name.clone());
+                    }
+                }
+                Ast::MappedType(MappedType { index, .. }) => {
+                    names.borrow_mut().insert(index.clone());
+                }
+                _ => {}
+            }
+
+            (node, ctx, Walk::Continue)
+        },
+        &|node, ctx| (node, ctx),
+    );
+
+    names.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::parse, typescript::Pretty as _};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn hoisted(source: &str, threshold: usize) -> String {
+        hoist_common(&parse!(source).simplify(), threshold)
+            .render_pretty_ts(80)
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn repeated_access_chain() {
+        assert_eq!(
+            hoisted(
+                r#"type A(T) as match T["a"]["b"] do 1 -> T["a"]["b"][], else -> [T["a"]["b"]] end"#,
+                3
+            ),
+            dedent!(
+                "
+                type A<T> =
+                    [T['a']['b']] extends [infer __dl_tmp_0]
+                        ? __dl_tmp_0 extends 1
+                            ? __dl_tmp_0[]
+                            : [__dl_tmp_0]
+                        : never;
+                "
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn below_the_threshold() {
+        let source = r#"type A(T) as [T["a"], T["a"]]"#;
+
+        assert_eq!(hoisted(source, 3), "type A<T> = [T['a'], T['a']];");
+    }
+
+    /// With `T` a union, `T["a"]` is a union too: the tuple of the original keeps it whole, where a
+    /// distributive `infer` would make a union of tuples.
+    #[test]
+    fn union_arguments_are_not_distributed() {
+        assert_eq!(
+            hoisted(r#"type A(T) as [T["a"], T["a"]]"#, 2),
+            dedent!(
+                "
+                type A<T> =
+                    [T['a']] extends [infer __dl_tmp_0]
+                        ? [__dl_tmp_0, __dl_tmp_0]
+                        : never;
+                "
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn thresholds_below_two() {
+        let source = r#"type A(T) as [T["a"], T["a"]]"#;
+
+        assert_eq!(hoisted(source, 1), "type A<T> = [T['a'], T['a']];");
+        assert_eq!(hoisted(source, 0), "type A<T> = [T['a'], T['a']];");
+    }
+
+    #[test]
+    fn scoped_expressions_stay() {
+        // `U` is only in scope of the then branch, `K` of the mapped type.
+        assert_eq!(
+            hoisted(
+                r#"type A(T) as if T <: [?U] then [U["a"], U["a"], U["a"]] else never end"#,
+                3
+            ),
+            "type A<T> = T extends [infer U] ? [U['a'], U['a'], U['a']] : never;"
+        );
+        assert_eq!(
+            hoisted(
+                r#"type A(T) as map K in keyof(T) do [T[K], T[K], T[K]] end"#,
+                3
+            ),
+            "type A<T> = { [K in keyof T]: [T[K], T[K], T[K]] };"
+        );
+    }
+
+    #[test]
+    fn emitted_size_is_reduced() {
+        let source = r#"
+            type Kind(T) as match T["payload"]["data"]["kind"] do
+                :a -> [T["payload"]["data"]["kind"], :first],
+                :b -> [T["payload"]["data"]["kind"], :second],
+                :c -> [T["payload"]["data"]["kind"], :third],
+                else -> T["payload"]["data"]["kind"]
+            end
+        "#;
+
+        let plain = parse!(source).simplify().render_pretty_ts(80);
+        let hoisted = hoisted(source, 3);
+
+        assert!(
+            hoisted.len() < plain.len(),
+            "{} bytes hoisted, {} bytes plain:\n{hoisted}",
+            hoisted.len(),
+            plain.len(),
+        );
+    }
+}
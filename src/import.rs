@@ -0,0 +1,103 @@
+/// Importers translating other type definition formats into the tree, so that they can be written
+/// out as DSL source or compiled straight to TypeScript.
+pub mod dts;
+pub mod graphql;
+pub mod json_schema;
+
+use crate::{
+    ast::{Ast, Ident, IntersectionType, Span, UnionType},
+    diagnostic::{codes, Diagnostic},
+    dsl::is_ident,
+    typescript::is_reserved_word,
+};
+
+/// Turns a name from an imported definition into an identifier that can name an alias.
+pub(crate) fn alias_name(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c == '$' || unicode_ident::is_xid_continue(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if !ident.starts_with(|c: char| c == '$' || c == '_' || unicode_ident::is_xid_start(c)) {
+        ident.insert(0, '_');
+    }
+
+    if !is_ident(&ident) || is_reserved_word(&ident) {
+        ident.push('_');
+    }
+
+    ident
+}
+
+/// A reference to `name`, which is a valid identifier.
+pub(crate) fn ident(name: String) -> Ast {
+    Ast::Ident(Ident {
+        name,
+        span: Span::default(),
+        raw: false,
+    })
+}
+
+/// A union of `types`, one node for all of them as the parser produces for a chain of `|`.
+pub(crate) fn union(types: Vec<Ast>) -> Ast {
+    fold(types, Ast::NeverKeyword(Span::default()), |lhs, rhs| {
+        let types = match lhs {
+            Ast::UnionType(union) => [union.types, vec![rhs]].concat(),
+            lhs => vec![lhs, rhs],
+        };
+
+        Ast::UnionType(UnionType {
+            types,
+            span: Span::default(),
+        })
+    })
+}
+
+/// An intersection of `types`, one node for all of them as the parser produces for a chain of
+/// `&`.
+pub(crate) fn intersection(types: Vec<Ast>) -> Ast {
+    fold(types, Ast::UnknownKeyword(Span::default()), |lhs, rhs| {
+        let types = match lhs {
+            Ast::IntersectionType(intersection) => [intersection.types, vec![rhs]].concat(),
+            lhs => vec![lhs, rhs],
+        };
+
+        Ast::IntersectionType(IntersectionType {
+            types,
+            span: Span::default(),
+        })
+    })
+}
+
+fn fold(types: Vec<Ast>, empty: Ast, f: impl Fn(Ast, Ast) -> Ast) -> Ast {
+    let mut types = types.into_iter();
+
+    match types.next() {
+        Some(first) => types.fold(first, f),
+        None => empty,
+    }
+}
+
+/// A syntax error for the token at `span` of `source`, which isn't `what` the importer expected.
+/// Only the end of input is an empty token.
+pub(crate) fn expected(what: &str, span: Span, source: &str) -> Diagnostic {
+    let found = match &source[span.start..span.end] {
+        "" => "end of input".to_string(),
+        token => format!("`{token}`"),
+    };
+
+    Diagnostic::error(format!("expected {what}, found {found}"), span)
+        .with_code(codes::IMPORT_SYNTAX_ERROR)
+}
+
+/// An error for the construct at `span`, which the importer doesn't translate.
+pub(crate) fn unsupported(what: &str, span: Span) -> Diagnostic {
+    Diagnostic::error(format!("unsupported construct: {what}"), span)
+        .with_code(codes::UNSUPPORTED_CONSTRUCT)
+}
@@ -0,0 +1,864 @@
+/// Translation of TypeScript declaration files into the tree.
+///
+/// Only a pragmatic subset of TypeScript is understood: type aliases and interfaces built from
+/// unions, intersections, conditional types, mapped types, literals and type references. Anything
+/// else is reported as an unsupported construct pointing at the offending TypeScript source.
+use std::rc::Rc;
+
+use crate::{
+    ast::{
+        Access, AccessKind, ApplyGeneric, Ast, Builtin, BuiltinKeyword, ExtendsExpr, FunctionType,
+        Ident, ImportType, Interface, MappedType, MappingModifier, ObjectProperty,
+        ObjectPropertyKey, Parameter, PrimitiveType, Program, Span, TemplateString, Tuple,
+        TypeAlias, TypeLiteral, TypeNumber, TypeParameter, TypeString,
+    },
+    diagnostic::{codes, Diagnostic},
+    import::{expected, ident, intersection, union, unsupported},
+    parser::trivia,
+};
+
+use lexer::{Token, TokenKind};
+
+#[cfg(test)]
+mod contract;
+mod lexer;
+
+type Result<T> = std::result::Result<T, Diagnostic>;
+
+/// Parses the declarations in `source` into a program of type aliases and interfaces.
+pub fn from_dts(source: &str) -> Result<Ast> {
+    let tokens = lexer::tokenize(source)
+        .map_err(|diagnostic| diagnostic.with_code(codes::IMPORT_SYNTAX_ERROR))?;
+
+    let mut parser = Parser {
+        source,
+        tokens,
+        pos: 0,
+    };
+
+    parser.program()
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn program(&mut self) -> Result<Ast> {
+        let mut statements = vec![];
+        let mut previous_end = 0;
+
+        loop {
+            while self.eat(";") {}
+
+            if self.peek().kind == TokenKind::Eof {
+                break;
+            }
+
+            let start = self.peek().span.start;
+            let doc = trivia::doc_comment(&self.source[previous_end..start]);
+
+            statements.push(Ast::Statement(self.statement(doc)?.into()));
+
+            previous_end = self.tokens[self.pos - 1].span.end;
+        }
+
+        Ok(Ast::Program(Program {
+            statements,
+            span: Span::default(),
+        }))
+    }
+
+    fn statement(&mut self, doc: Option<String>) -> Result<Ast> {
+        let export = self.eat_keyword("export");
+
+        let token = self.peek().clone();
+
+        match self.ident_of(&token) {
+            Some("type") => {
+                self.next();
+                self.type_alias(doc, export)
+            }
+            Some("interface") => {
+                self.next();
+                self.interface(doc, export)
+            }
+            Some("declare") => Err(unsupported("`declare` modifiers", token.span)),
+            Some("default") => Err(unsupported("default exports", token.span)),
+            Some(
+                keyword @ ("import" | "namespace" | "module" | "function" | "const" | "let" | "var"
+                | "class" | "abstract" | "enum"),
+            ) => Err(unsupported(
+                &format!("`{keyword}` declarations"),
+                token.span,
+            )),
+            _ => Err(self.expected("a type alias or an interface")),
+        }
+    }
+
+    fn type_alias(&mut self, doc: Option<String>, export: bool) -> Result<Ast> {
+        let name = self.name()?;
+        let params = self.type_parameters()?;
+
+        self.expect("=")?;
+
+        let body = self.ty()?;
+
+        Ok(Ast::TypeAlias(TypeAlias {
+            doc,
+            export,
+            global: false,
+            default: false,
+            name: Ident {
+                name,
+                span: Span::default(),
+                raw: false,
+            },
+            params,
+            body: body.into(),
+            span: Span::default(),
+        }))
+    }
+
+    fn interface(&mut self, doc: Option<String>, export: bool) -> Result<Ast> {
+        let name = self.name()?;
+        let params = self.type_parameters()?;
+
+        let extends = if self.eat_keyword("extends") {
+            let extends = self.name()?;
+
+            if matches!(self.peek().kind, TokenKind::Punct("," | "<" | ".")) {
+                return Err(unsupported(
+                    "interfaces extending anything but a single interface name",
+                    self.peek().span,
+                ));
+            }
+
+            Some(extends)
+        } else {
+            None
+        };
+
+        let definition = self.members()?;
+
+        Ok(Ast::Interface(Interface {
+            doc,
+            export,
+            global: false,
+            default: false,
+            name,
+            extends,
+            params,
+            definition,
+            span: Span::default(),
+        }))
+    }
+
+    /// Optional `<T extends C = D, ...>` list of a declaration.
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter>> {
+        let mut params = vec![];
+
+        if !self.eat("<") {
+            return Ok(params);
+        }
+
+        while !self.eat(">") {
+            let token = self.peek().clone();
+
+            if let (Some("in" | "out"), TokenKind::Ident(_)) =
+                (self.ident_of(&token), &self.peek_at(1).kind)
+            {
+                return Err(unsupported("variance annotations", token.span));
+            }
+
+            let is_const = self.eat_keyword("const");
+            let name = self.name()?;
+
+            let constraint = if self.eat_keyword("extends") {
+                Some(self.ty()?)
+            } else {
+                None
+            };
+
+            let default = if self.eat("=") {
+                Some(self.ty()?)
+            } else {
+                None
+            };
+
+            params.push(TypeParameter {
+                is_const,
+                ..TypeParameter::new(name, constraint, default, false, Span::default())
+            });
+
+            if !self.eat(",") {
+                self.expect(">")?;
+                break;
+            }
+        }
+
+        Ok(params)
+    }
+
+    /// The members of an interface or object type, between braces.
+    fn members(&mut self) -> Result<Vec<ObjectProperty>> {
+        self.expect("{")?;
+
+        let mut properties = vec![];
+
+        while !self.eat("}") {
+            properties.push(self.member()?);
+
+            if !self.eat(";") && !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+
+        Ok(properties)
+    }
+
+    fn member(&mut self) -> Result<ObjectProperty> {
+        let readonly = matches!(
+            self.peek_at(1).kind,
+            TokenKind::Ident(_) | TokenKind::String(_)
+        ) && self.eat_keyword("readonly");
+
+        let token = self.next();
+
+        let key = match &token.kind {
+            TokenKind::Ident(key) | TokenKind::String(key) | TokenKind::Number(key) => key.clone(),
+            TokenKind::Punct("[") => return Err(unsupported("index signatures", token.span)),
+            TokenKind::Punct("(" | "<") => return Err(unsupported("call signatures", token.span)),
+            _ => return Err(expected("a property", token.span, self.source)),
+        };
+
+        if let Some(accessor @ ("get" | "set")) = self.ident_of(&token) {
+            if matches!(self.peek().kind, TokenKind::Ident(_) | TokenKind::String(_)) {
+                return Err(unsupported(&format!("`{accessor}` accessors"), token.span));
+            }
+        }
+
+        let optional = self.eat("?");
+
+        if matches!(self.peek().kind, TokenKind::Punct("(" | "<")) {
+            return Err(unsupported("method signatures", token.span));
+        }
+
+        self.expect(":")?;
+
+        let value = self.ty()?;
+
+        Ok(ObjectProperty {
+            readonly,
+            optional,
+            key: ObjectPropertyKey::Key(key),
+            value,
+            span: Span::default(),
+        })
+    }
+
+    /// A type, conditional types included.
+    fn ty(&mut self) -> Result<Ast> {
+        if self.at_function_type() {
+            return self.function_type();
+        }
+
+        let check = self.union()?;
+
+        if !self.eat_keyword("extends") {
+            return Ok(check);
+        }
+
+        // The extends type of a conditional can't itself be a conditional type without parens.
+        let extends = self.union()?;
+
+        self.expect("?")?;
+        let then_branch = self.ty()?;
+
+        self.expect(":")?;
+        let else_branch = self.ty()?;
+
+        Ok(Ast::ExtendsExpr(ExtendsExpr {
+            lhs: check.into(),
+            rhs: extends.into(),
+            then_branch: then_branch.into(),
+            else_branch: else_branch.into(),
+            non_distributive: false,
+            span: Span::default(),
+        }))
+    }
+
+    fn union(&mut self) -> Result<Ast> {
+        self.eat("|");
+
+        let mut types = vec![self.intersection()?];
+
+        while self.eat("|") {
+            types.push(self.intersection()?);
+        }
+
+        Ok(union(types))
+    }
+
+    fn intersection(&mut self) -> Result<Ast> {
+        self.eat("&");
+
+        let mut types = vec![self.type_operator()?];
+
+        while self.eat("&") {
+            types.push(self.type_operator()?);
+        }
+
+        Ok(intersection(types))
+    }
+
+    /// Prefix operators, which bind looser than the postfix ones.
+    fn type_operator(&mut self) -> Result<Ast> {
+        let token = self.peek().clone();
+
+        match self.ident_of(&token) {
+            Some("keyof") if !self.at_end_of_type(1) => {
+                self.next();
+
+                Ok(Ast::Builtin(Builtin {
+                    name: BuiltinKeyword::Keyof,
+                    argument: self.type_operator()?.into(),
+                    span: Span::default(),
+                }))
+            }
+            Some("infer") if !self.at_end_of_type(1) => {
+                self.next();
+
+                let name = self.name()?;
+
+                Ok(Ast::Infer(ident(name).into()))
+            }
+            // `readonly [1]` is a readonly tuple, not an index into a type named `readonly`.
+            Some("readonly")
+                if !self.at_end_of_type(1) || self.peek_at(1).kind == TokenKind::Punct("[") =>
+            {
+                self.next();
+
+                Ok(Ast::Builtin(Builtin {
+                    name: BuiltinKeyword::Readonly,
+                    argument: self.type_operator()?.into(),
+                    span: Span::default(),
+                }))
+            }
+            Some("unique") if self.ident_of(self.peek_at(1)) == Some("symbol") => {
+                self.next();
+                self.next();
+
+                Ok(primitive(PrimitiveType::UniqueSymbol))
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Ast> {
+        let mut ty = self.primary()?;
+
+        while self.eat("[") {
+            if self.eat("]") {
+                ty = Ast::Array(ty.into());
+            } else {
+                let index = self.ty()?;
+                self.expect("]")?;
+
+                ty = Ast::Access(Access {
+                    lhs: ty.into(),
+                    rhs: index.into(),
+                    kind: AccessKind::Index,
+                    span: Span::default(),
+                });
+            }
+        }
+
+        Ok(ty)
+    }
+
+    fn primary(&mut self) -> Result<Ast> {
+        if self.peek().kind == TokenKind::Punct("{") {
+            return self.object();
+        }
+
+        let token = self.next();
+
+        let ty = match &token.kind {
+            TokenKind::Punct("(") => {
+                let ty = self.ty()?;
+                self.expect(")")?;
+                ty
+            }
+            TokenKind::Punct("[") => self.tuple()?,
+            TokenKind::Punct("<") => return Err(unsupported("generic function types", token.span)),
+            TokenKind::Punct("-") => match self.next().kind {
+                TokenKind::Number(number) => number_literal(format!("-{number}")),
+                _ => return Err(expected("a number", token.span, self.source)),
+            },
+            TokenKind::String(string) => Ast::TypeString(TypeString {
+                ty: string.clone(),
+                span: Span::default(),
+            }),
+            TokenKind::Number(number) => number_literal(number.clone()),
+            TokenKind::Template(template) => Ast::TemplateString(TemplateString {
+                ty: template.clone(),
+                span: Span::default(),
+            }),
+            TokenKind::Ident(name) => match name.as_str() {
+                "any" => Ast::AnyKeyword(Span::default()),
+                "unknown" => Ast::UnknownKeyword(Span::default()),
+                "never" => Ast::NeverKeyword(Span::default()),
+                "true" => Ast::TrueKeyword(Span::default()),
+                "false" => Ast::FalseKeyword(Span::default()),
+                "string" => primitive(PrimitiveType::String),
+                "number" => primitive(PrimitiveType::Number),
+                "boolean" => primitive(PrimitiveType::Boolean),
+                "object" => primitive(PrimitiveType::Object),
+                "symbol" => primitive(PrimitiveType::Symbol),
+                "bigint" => primitive(PrimitiveType::BigInt),
+                "void" => primitive(PrimitiveType::Void),
+                "undefined" => primitive(PrimitiveType::Undefined),
+                "null" => primitive(PrimitiveType::Null),
+                "this" => Ast::ThisType(Span::default()),
+                "typeof" => return Err(unsupported("`typeof` type queries", token.span)),
+                "new" | "abstract" => return Err(unsupported("constructor types", token.span)),
+                "import" => {
+                    self.expect("(")?;
+
+                    let name = self.next();
+
+                    let module = match &name.kind {
+                        TokenKind::String(module) => module.clone(),
+                        _ => return Err(expected("a module name", name.span, self.source)),
+                    };
+
+                    self.expect(")")?;
+
+                    let module = Ast::ImportType(ImportType {
+                        module,
+                        span: Span::default(),
+                    });
+
+                    self.type_reference(module)?
+                }
+                _ => self.type_reference(ident(name.clone()))?,
+            },
+            _ => return Err(expected("a type", token.span, self.source)),
+        };
+
+        Ok(ty)
+    }
+
+    /// A possibly qualified name, applied to type arguments if any follow, qualifying `receiver`.
+    fn type_reference(&mut self, mut receiver: Ast) -> Result<Ast> {
+        while self.eat(".") {
+            receiver = Ast::Access(Access {
+                lhs: receiver.into(),
+                rhs: ident(self.name()?).into(),
+                kind: AccessKind::Namespace,
+                span: Span::default(),
+            });
+        }
+
+        if !self.eat("<") {
+            return Ok(receiver);
+        }
+
+        let args = self.list(">", Self::ty)?;
+
+        Ok(Ast::ApplyGeneric(ApplyGeneric {
+            receiver: receiver.into(),
+            args,
+            span: Span::default(),
+        }))
+    }
+
+    fn tuple(&mut self) -> Result<Ast> {
+        let items = self.list("]", |parser| {
+            let token = parser.peek().clone();
+
+            if token.kind == TokenKind::Punct("...") {
+                return Err(unsupported("rest elements in tuple types", token.span));
+            }
+
+            if matches!(token.kind, TokenKind::Ident(_))
+                && matches!(parser.peek_at(1).kind, TokenKind::Punct(":" | "?"))
+            {
+                return Err(unsupported("named tuple members", token.span));
+            }
+
+            let item = parser.ty()?;
+
+            if parser.peek().kind == TokenKind::Punct("?") {
+                return Err(unsupported("optional tuple elements", parser.peek().span));
+            }
+
+            Ok(item)
+        })?;
+
+        Ok(Ast::Tuple(Tuple {
+            items,
+            span: Span::default(),
+        }))
+    }
+
+    /// An object type, or a mapped type when its only member is `[K in T]`.
+    fn object(&mut self) -> Result<Ast> {
+        let is_mapped = {
+            let mut n = 1;
+
+            if matches!(self.peek_at(n).kind, TokenKind::Punct("+" | "-")) {
+                n += 1;
+            }
+
+            if self.ident_of(self.peek_at(n)) == Some("readonly") {
+                n += 1;
+            }
+
+            self.peek_at(n).kind == TokenKind::Punct("[")
+                && matches!(self.peek_at(n + 1).kind, TokenKind::Ident(_))
+                && self.ident_of(self.peek_at(n + 2)) == Some("in")
+        };
+
+        if is_mapped {
+            return self.mapped_type();
+        }
+
+        Ok(Ast::TypeLiteral(TypeLiteral {
+            properties: self.members()?,
+            span: Span::default(),
+        }))
+    }
+
+    fn mapped_type(&mut self) -> Result<Ast> {
+        self.expect("{")?;
+
+        let readonly_mod = self.mapping_modifier("readonly")?;
+
+        self.expect("[")?;
+
+        let index = self.name()?;
+
+        // `in`, already checked by `object`.
+        self.next();
+
+        let iterable = self.ty()?;
+
+        let remapped_as = if self.eat_keyword("as") {
+            Some(Rc::new(self.ty()?))
+        } else {
+            None
+        };
+
+        self.expect("]")?;
+
+        let optional_mod = self.mapping_modifier("?")?;
+
+        self.expect(":")?;
+
+        let body = self.ty()?;
+
+        self.eat(";");
+        self.eat(",");
+        self.expect("}")?;
+
+        Ok(Ast::MappedType(MappedType {
+            index,
+            iterable: iterable.into(),
+            remapped_as,
+            readonly_mod,
+            optional_mod,
+            body: body.into(),
+            span: Span::default(),
+        }))
+    }
+
+    /// A `readonly` or `?` modifier of a mapped type, optionally prefixed with `+`, or with `-`
+    /// to remove it.
+    fn mapping_modifier(&mut self, modifier: &'static str) -> Result<Option<MappingModifier>> {
+        let kind = if self.eat("-") {
+            MappingModifier::Remove
+        } else {
+            MappingModifier::Add
+        };
+        let signed = kind == MappingModifier::Remove || self.eat("+");
+
+        let present = match modifier {
+            "?" => self.eat("?"),
+            keyword => self.eat_keyword(keyword),
+        };
+
+        if signed && !present {
+            return Err(self.expected(&format!("`{modifier}`")));
+        }
+
+        Ok(present.then_some(kind))
+    }
+
+    fn at_function_type(&self) -> bool {
+        if self.peek().kind != TokenKind::Punct("(") {
+            return false;
+        }
+
+        match (&self.peek_at(1).kind, &self.peek_at(2).kind) {
+            (TokenKind::Punct(")"), _) | (TokenKind::Punct("..."), _) => true,
+            (TokenKind::Ident(_), TokenKind::Punct(":" | "?" | ",")) => true,
+            (TokenKind::Ident(_), TokenKind::Punct(")")) => {
+                self.peek_at(3).kind == TokenKind::Punct("=>")
+            }
+            _ => false,
+        }
+    }
+
+    fn function_type(&mut self) -> Result<Ast> {
+        self.expect("(")?;
+
+        let params = self.list(")", |parser| {
+            let ellipsis = parser.eat("...");
+            let name = parser.name()?;
+
+            if parser.peek().kind == TokenKind::Punct("?") {
+                return Err(unsupported("optional parameters", parser.peek().span));
+            }
+
+            parser.expect(":")?;
+
+            Ok(Parameter {
+                ellipsis,
+                name,
+                kind: parser.ty()?,
+                span: Span::default(),
+            })
+        })?;
+
+        self.expect("=>")?;
+
+        let return_type = self.ty()?;
+
+        Ok(Ast::FunctionType(FunctionType {
+            params,
+            return_type: return_type.into(),
+            predicate: None,
+            span: Span::default(),
+        }))
+    }
+
+    /// Comma separated items up to `close`, which is consumed.
+    fn list<T>(
+        &mut self,
+        close: &'static str,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut items = vec![];
+
+        while !self.eat(close) {
+            items.push(item(self)?);
+
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Whether the token `n` ahead ends a type, meaning a keyword like `keyof` is used as a name.
+    fn at_end_of_type(&self, n: usize) -> bool {
+        matches!(
+            self.peek_at(n).kind,
+            TokenKind::Eof
+                | TokenKind::Punct(
+                    ")" | "]" | "}" | ">" | "," | ";" | "|" | "&" | "?" | ":" | "=" | "." | "["
+                )
+        )
+    }
+
+    fn name(&mut self) -> Result<String> {
+        match self.peek().kind.clone() {
+            TokenKind::Ident(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => Err(self.expected("an identifier")),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, n: usize) -> &Token {
+        let last = self.tokens.len() - 1;
+        &self.tokens[(self.pos + n).min(last)]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        self.pos = (self.pos + 1).min(self.tokens.len() - 1);
+        token
+    }
+
+    fn ident_of<'t>(&self, token: &'t Token) -> Option<&'t str> {
+        match &token.kind {
+            TokenKind::Ident(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, punct: &'static str) -> bool {
+        if self.peek().kind == TokenKind::Punct(punct) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.ident_of(self.peek()) == Some(keyword) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &'static str) -> Result<()> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{punct}`")))
+        }
+    }
+
+    fn expected(&self, what: &str) -> Diagnostic {
+        expected(what, self.peek().span, self.source)
+    }
+}
+
+fn primitive(primitive: PrimitiveType) -> Ast {
+    Ast::Primitive(primitive, Span::default())
+}
+
+fn number_literal(number: String) -> Ast {
+    Ast::TypeNumber(TypeNumber {
+        ty: number,
+        span: Span::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::Pretty as _;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[test]
+    fn prints_dsl() {
+        let source = "export interface I<T> { a: T[]; b?: string | number }\ntype K = keyof I<1>;";
+
+        assert_eq!(
+            from_dts(source).unwrap().render_pretty_dsl(80),
+            "export interface I(T) {a: T[], ?b: string | number}\n\ntype K as keyof(I(1))\n"
+        );
+    }
+
+    #[test]
+    fn chains_are_single_nodes() {
+        use crate::test_support::parse;
+
+        assert_eq!(
+            from_dts("type A = a | b | c;\ntype B = a & b & c | d & e;").unwrap(),
+            parse!("type A as a | b | c\ntype B as a & b & c | d & e")
+        );
+    }
+
+    #[test]
+    fn unique_symbol() {
+        let source = "type S = symbol | unique symbol;";
+
+        assert_eq!(
+            from_dts(source).unwrap().render_pretty_dsl(80),
+            "type S as symbol | unique symbol\n"
+        );
+    }
+
+    #[test]
+    fn modifiers_of_mapped_types() {
+        let source = "type M<T> = { +readonly [K in keyof T]+?: T[K] };";
+
+        assert_eq!(
+            from_dts(source).unwrap().render_pretty_dsl(80),
+            "type M(T) as map readonly ?K in keyof(T) do T[K] end\n"
+        );
+
+        let source = "type M<T> = { -readonly [K in keyof T]-?: T[K] };";
+
+        assert_eq!(
+            from_dts(source).unwrap().render_pretty_dsl(80),
+            "type M(T) as map -readonly -?K in keyof(T) do T[K] end\n"
+        );
+    }
+
+    #[test]
+    fn const_type_parameters() {
+        let source = "type T<const A extends string[]> = A;";
+
+        assert_eq!(
+            from_dts(source).unwrap().render_pretty_dsl(80),
+            "type T(const A) where A <: string[] as A\n"
+        );
+    }
+
+    #[test]
+    fn import_types() {
+        let source = "type U = import('./user').User<string>;";
+
+        assert_eq!(
+            from_dts(source).unwrap().render_pretty_dsl(80),
+            "type U as import(\"./user\")::User(string)\n"
+        );
+    }
+
+    #[rstest]
+    #[case::declare("declare const a: 1;", "unsupported construct: `declare` modifiers", 0)]
+    #[case::namespace("namespace N {}", "unsupported construct: `namespace` declarations", 0)]
+    #[case::index_signature(
+        "interface I { [key: string]: number }",
+        "unsupported construct: index signatures",
+        14
+    )]
+    #[case::method(
+        "interface I { m(): void }",
+        "unsupported construct: method signatures",
+        14
+    )]
+    #[case::typeof_query(
+        "type A = typeof a;",
+        "unsupported construct: `typeof` type queries",
+        9
+    )]
+    #[case::named_tuple("type A = [a: 1];", "unsupported construct: named tuple members", 10)]
+    #[case::sign_without_modifier(
+        "type A<T> = { - [K in keyof T]: T[K] };",
+        "expected `readonly`, found `[`",
+        16
+    )]
+    #[case::variance("type A<in T> = T;", "unsupported construct: variance annotations", 7)]
+    #[case::syntax_error("type A = ;", "expected a type, found `;`", 9)]
+    #[case::missing_equals("type A 1", "expected `=`, found `1`", 7)]
+    fn errors(#[case] source: &str, #[case] message: &str, #[case] start: usize) {
+        let diagnostic = from_dts(source).unwrap_err();
+
+        assert_eq!(
+            (diagnostic.message.as_str(), diagnostic.span.start),
+            (message, start)
+        );
+    }
+}
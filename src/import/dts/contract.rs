@@ -0,0 +1,167 @@
+/// Contract between the TypeScript emitter and the declaration importer: a simplified program
+/// emitted as TypeScript and imported back is the program it started from, spans and layout
+/// aside.
+///
+/// Constructs the guarantee doesn't cover are listed at the end as ignored tests, each with the
+/// reason it is excluded.
+use quickcheck::{Arbitrary, Gen, TestResult};
+
+use super::from_dts;
+use crate::{
+    ast::{build, Ast, Span, TemplateString},
+    source::SourceFile,
+    test_support::parse,
+    typescript::Pretty as _,
+};
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+
+fn assert_round_trip(source: &str, width: usize) {
+    assert_program_round_trip(parse!(source).simplify(), width);
+}
+
+fn assert_program_round_trip(program: Ast, width: usize) {
+    let typescript = program.render_pretty_ts(width);
+
+    let imported = from_dts(&typescript)
+        .unwrap_or_else(|d| panic!("{}", d.render(&SourceFile::new("", typescript.as_str()))));
+
+    assert_eq!(imported, program, "{typescript}");
+}
+
+/// Every fixture is emitted on a single line where it fits and broken over many lines at the
+/// narrow width.
+#[rstest]
+// literals
+#[case::literals("type A as 1 | -2.5 | \"a\" | true | false | null | undefined")]
+#[case::keywords("type A as [any, unknown, never, void, object, symbol, bigint, boolean]")]
+#[case::atom("type A as :a-b")]
+#[case::single_quote("type A as 'say \"hi\"'")]
+#[case::apostrophe("type A as \"it's\"")]
+#[case::backslash("type A as \"a\\b\\n\"")]
+#[case::template("type A(T) as `a${T}b${1 | 2}`")]
+#[case::template_text("type A(T) as `$${T}\\n{${T}}`")]
+#[case::quoted_key("type A as {\"it's\": 1, 'say \"hi\"': 2}")]
+#[case::line_break("type A as \"a\nb\"")]
+// declarations
+#[case::generics(
+    "export type Pair(A, B) defaults B = A where A <: string as [A, B[], Record(A, B)]"
+)]
+#[case::interface("interface Point(T) extends Base {x: T, ?y: number, readonly \"z-z\": string}")]
+#[case::empty_interface("export interface I {}")]
+#[case::doc_and_path("/** Documented */\nexport type A as a & b | ns::Inner(string) | c & d")]
+#[case::multi_line_doc("/** First\n second */\ninterface I {}")]
+// conditionals
+#[case::conditional(
+    "type Kind(T) as if T <: string then :s else if T <: number then :n else never end end"
+)]
+#[case::cond_chain("type A(T) as cond do T <: 1 -> :a, T <: 2 -> :b, T <: 3 -> :c, else -> :d end")]
+#[case::match_arms("type A(T) as match T do 1 -> :a, 2 -> :b, else -> :c end")]
+#[case::negated("type A(T) as if not (T <: 1) then 2 else 3 end")]
+#[case::conjunction("type A(T) as if T <: 1 and T <: 2 then 2 else 3 end")]
+#[case::disjunction("type A(T) as if T <: 1 or T <: 2 then 2 else 3 end")]
+#[case::infer("type Unwrap(T) as if T <: Promise(?U)[] then U else T end")]
+#[case::infer_in_function("type R(T) as if T <: (...args: any) => ?R then R else never end")]
+// mapped types
+#[case::mapped("type Values(T) as map ?K in keyof(T) do T[K] end")]
+#[case::readonly("type A(T) as map readonly K in keyof(T) do T[K] end")]
+#[case::remapped(
+    "type Getters(T) as map readonly K in keyof(T) as `get_${K & string}` do () => T[K] end"
+)]
+// objects and functions
+#[case::object("type A as {a: 1 | 2, \"b c\": 2, readonly ?d: 3, new: 4}")]
+#[case::empty_object_and_tuple("type A as [{}, []]")]
+#[case::function("type F as (a: string, ...rest: number[]) => {ok: true} | {ok: false}")]
+#[case::curried("type A as (a: (b: 2) => 3) => (c: 4) => 5")]
+// access
+#[case::indexed_access("type A(T) as T[\"a\"][number]")]
+#[case::dot_access("type A(T) as T.a.b")]
+#[case::keyof_access("type A(T) as keyof(T[1])")]
+#[case::keyof_prefix("type A(T) as keyof keyof T[1]")]
+#[case::readonly_array("type A(T) as readonly T[] | readonly [T]")]
+// parenthesized operands
+#[case::function_in_union("type A as (() => 1) | 2")]
+#[case::function_in_array("type A as (() => 1)[]")]
+#[case::union_in_array("type A as (1 | 2)[]")]
+#[case::union_in_intersection("type A as (1 | 2) & 3")]
+#[case::union_in_keyof("type A(T) as keyof(T | 1)")]
+#[case::keyof_in_array("type A(T) as keyof(T)[]")]
+#[case::infer_in_array("type A(T) as if T <: (?U)[] then U else never end")]
+#[case::conditional_in_union("type A(T) as (if T <: 1 then 2 else 3 end) | 4")]
+#[case::conditional_in_array("type A(T) as (if T <: 1 then 2 else 3 end)[]")]
+#[case::conditional_in_condition(
+    "type A(T) as if (if T <: 1 then 2 else 3 end) <: (if T <: 4 then 5 else 6 end) then 7 end"
+)]
+#[case::mapped_in_union("type A(T) as (map K in keyof(T) do 1 end) | 2")]
+fn corpus(#[case] source: &str, #[values(20, 80)] width: usize) {
+    assert_round_trip(source, width);
+}
+
+/// Text of the characters that are escaped in strings, keys and templates.
+#[derive(Debug, Clone)]
+struct Text(String);
+
+impl Arbitrary for Text {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let chars = [
+            'a', ' ', '\'', '"', '\\', '\n', '\r', '\t', '`', '$', '{', '}', '\u{2028}',
+        ];
+        let len = usize::arbitrary(g) % 8;
+
+        Self((0..len).map(|_| *g.choose(&chars).unwrap()).collect())
+    }
+}
+
+#[quickcheck]
+fn escaped_strings_and_keys(key: Text, value: Text) {
+    let program = build::program([build::alias("A").body(build::object([build::property(
+        &key.0,
+        build::string_literal(&value.0),
+    )]))]);
+
+    assert_program_round_trip(program, 80);
+}
+
+/// Text that reads as a placeholder or the end of the template has no value the importer can
+/// give it, see [`template_literal`](crate::pretty::template_literal).
+#[quickcheck]
+fn escaped_templates(text: Text) -> TestResult {
+    if text.0.contains('`') || text.0.contains("${") {
+        return TestResult::discard();
+    }
+
+    let template = Ast::TemplateString(TemplateString {
+        ty: format!("`{0}${{T}}{0}`", text.0),
+        span: Span::default(),
+    });
+    let program = build::program([build::alias("A").param("T", None).body(template)]);
+
+    assert_program_round_trip(program, 80);
+    TestResult::passed()
+}
+
+#[test]
+#[ignore = "globals are emitted in a `declare global` block, the importer rejects `declare`"]
+fn global_declarations() {
+    assert_round_trip("global type A as 1", 80);
+}
+
+#[test]
+#[ignore = "imports are emitted as `import type`, the importer rejects `import` declarations"]
+fn import_statements() {
+    assert_round_trip("import { A } from :a\ntype B as A", 80);
+}
+
+#[test]
+#[ignore = "unit tests are checked by the compiler and not emitted"]
+fn unit_tests() {
+    assert_round_trip("unittest \"a\" do 1 end\ntype A as 1", 80);
+}
+
+#[test]
+#[ignore = "the importer rejects `-readonly` and `-?`, which the language has no syntax for"]
+fn removed_mapping_modifiers() {
+    let typescript = "type A<T> = { -readonly [K in keyof T]-?: T[K] };";
+
+    from_dts(typescript).unwrap();
+}
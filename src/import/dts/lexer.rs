@@ -0,0 +1,336 @@
+/// Tokenizer for the subset of TypeScript understood by the declaration importer.
+use crate::{ast::Span, diagnostic::Diagnostic};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// Identifiers and keywords alike, the parser tells them apart.
+    Ident(String),
+    /// A string literal, with its escapes resolved.
+    String(String),
+    Number(String),
+    /// A template literal including the backticks, with the escapes of its text resolved and its
+    /// placeholders as written.
+    Template(String),
+    Punct(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Punctuation, longest first so that `=>` is not read as `=` followed by `>`.
+const PUNCTUATION: &[&str] = &[
+    "=>", "...", "{", "}", "(", ")", "[", "]", "<", ">", ",", ";", ":", "?", "|", "&", "=", ".",
+    "-", "+", "*", "!", "@", "#",
+];
+
+/// Splits `source` into tokens, skipping whitespace and comments. The last token is always
+/// [`TokenKind::Eof`].
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token>, Diagnostic> {
+    let mut tokens = vec![];
+    let mut offset = 0;
+
+    while offset < source.len() {
+        let rest = &source[offset..];
+        let c = rest.chars().next().unwrap();
+
+        if c.is_whitespace() {
+            offset += c.len_utf8();
+            continue;
+        }
+
+        if rest.starts_with("//") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let len = comment.find("*/").ok_or_else(|| {
+                Diagnostic::error("unterminated block comment", Span::new(offset, offset + 2))
+            })?;
+
+            offset += len + 4;
+            continue;
+        }
+
+        let (kind, len) = if is_ident_start(c) {
+            let len = rest
+                .find(|c: char| !is_ident_continue(c))
+                .unwrap_or(rest.len());
+
+            (TokenKind::Ident(rest[..len].to_string()), len)
+        } else if c.is_ascii_digit()
+            || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let len = number_len(rest);
+
+            (TokenKind::Number(rest[..len].to_string()), len)
+        } else if c == '"' || c == '\'' {
+            let (value, len) = string(rest, c).ok_or_else(|| {
+                Diagnostic::error("unterminated string literal", Span::new(offset, offset + 1))
+            })?;
+
+            (TokenKind::String(value), len)
+        } else if c == '`' {
+            let (value, len) = template(rest)
+                .map_err(|message| Diagnostic::error(message, Span::new(offset, offset + 1)))?;
+
+            (TokenKind::Template(value), len)
+        } else if let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) {
+            (TokenKind::Punct(punct), punct.len())
+        } else {
+            return Err(Diagnostic::error(
+                format!("unexpected character `{c}`"),
+                Span::new(offset, offset + c.len_utf8()),
+            ));
+        };
+
+        tokens.push(Token {
+            kind,
+            span: Span::new(offset, offset + len),
+        });
+
+        offset += len;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span::new(source.len(), source.len()),
+    });
+
+    Ok(tokens)
+}
+
+fn is_ident_start(c: char) -> bool {
+    c == '$' || c == '_' || unicode_ident::is_xid_start(c)
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c == '$' || c == '\u{200C}' || c == '\u{200D}' || unicode_ident::is_xid_continue(c)
+}
+
+/// Length of the numeric literal at the start of `rest`, in any radix and with separators,
+/// fractions and exponents.
+fn number_len(rest: &str) -> usize {
+    let mut len = 0;
+    let mut previous = '\0';
+
+    for c in rest.chars() {
+        let exponent_sign = (c == '+' || c == '-') && matches!(previous, 'e' | 'E');
+
+        if !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || exponent_sign) {
+            break;
+        }
+
+        len += c.len_utf8();
+        previous = c;
+    }
+
+    len
+}
+
+/// Value and length of the string literal at the start of `rest`, delimited by `quote`.
+fn string(rest: &str, quote: char) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = rest.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Some((value, i + 1)),
+            '\n' => return None,
+            '\\' => value.extend(unescape(&mut chars)?),
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// The character of the escape sequence following a backslash, none for an escaped line break.
+/// `None` for a malformed sequence.
+fn unescape<I>(chars: &mut I) -> Option<Option<char>>
+where
+    I: Iterator<Item = (usize, char)> + Clone,
+{
+    let (_, escaped) = chars.next()?;
+
+    let c = match escaped {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'v' => '\u{b}',
+        '0' => '\0',
+        '\n' => return Some(None),
+        'x' => {
+            let digits: String = (0..2)
+                .filter_map(|_| chars.next())
+                .map(|(_, c)| c)
+                .collect();
+            char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?
+        }
+        'u' => {
+            let digits: String = match chars.clone().next() {
+                Some((_, '{')) => {
+                    chars.next();
+                    chars
+                        .by_ref()
+                        .map(|(_, c)| c)
+                        .take_while(|c| *c != '}')
+                        .collect()
+                }
+                _ => (0..4)
+                    .filter_map(|_| chars.next())
+                    .map(|(_, c)| c)
+                    .collect(),
+            };
+
+            char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?
+        }
+        c => c,
+    };
+
+    Some(Some(c))
+}
+
+/// Value and length of the template literal at the start of `rest`: its text with the escapes
+/// resolved, and its placeholders as written. Text that would read as a placeholder or the end of
+/// the template once its escapes are resolved, `` \` `` or `\${`, has no value.
+fn template(rest: &str) -> Result<(String, usize), &'static str> {
+    const UNTERMINATED: &str = "unterminated template literal";
+
+    let mut value = String::from("`");
+    let mut text = String::new();
+    let mut depth = 0;
+    let mut chars = rest.char_indices().skip(1).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if depth > 0 => {
+                value.push(c);
+                value.extend(chars.next().map(|(_, c)| c));
+            }
+            '\\' => text.extend(unescape(&mut chars).ok_or(UNTERMINATED)?),
+            '$' if depth == 0 && chars.peek().is_some_and(|(_, c)| *c == '{') => {
+                chars.next();
+                value += cooked(&text)?;
+                text.clear();
+                value += "${";
+                depth += 1;
+            }
+            '{' if depth > 0 => {
+                value.push(c);
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                value.push(c);
+                depth -= 1;
+            }
+            '`' if depth == 0 => {
+                value += cooked(&text)?;
+                value.push('`');
+                return Ok((value, i + 1));
+            }
+            c if depth > 0 => value.push(c),
+            c => text.push(c),
+        }
+    }
+
+    Err(UNTERMINATED)
+}
+
+/// The text of a template between its placeholders, unless it can't be told apart from them.
+fn cooked(text: &str) -> Result<&str, &'static str> {
+    if text.contains('`') || text.contains("${") {
+        Err("a template literal with `` ` `` or `${` in its text isn't supported")
+    } else {
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn tokens() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("type A<T> = (a: T) => 'it\\'s' | 1_000 | `x${T}` // trailing\n/* block */;"),
+            vec![
+                Ident("type".to_string()),
+                Ident("A".to_string()),
+                Punct("<"),
+                Ident("T".to_string()),
+                Punct(">"),
+                Punct("="),
+                Punct("("),
+                Ident("a".to_string()),
+                Punct(":"),
+                Ident("T".to_string()),
+                Punct(")"),
+                Punct("=>"),
+                String("it's".to_string()),
+                Punct("|"),
+                Number("1_000".to_string()),
+                Punct("|"),
+                Template("`x${T}`".to_string()),
+                Punct(";"),
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(
+            kinds(r#""a\"bA\u{1F600}\x41\n""#),
+            vec![
+                TokenKind::String("a\"bA\u{1F600}A\n".to_string()),
+                TokenKind::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn template_escapes() {
+        assert_eq!(
+            kinds(r"`a\\b\n${T | '\''}\$c`"),
+            vec![
+                TokenKind::Template("`a\\b\n${T | '\\''}$c`".to_string()),
+                TokenKind::Eof
+            ]
+        );
+        assert_eq!(
+            tokenize(r"type A = `\${T}`").unwrap_err().message,
+            "a template literal with `` ` `` or `${` in its text isn't supported"
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        let err = tokenize("type A = 'a").unwrap_err();
+
+        assert_eq!(err.message, "unterminated string literal");
+        assert_eq!((err.span.start, err.span.end), (9, 10));
+
+        let err = tokenize("type A = ~1").unwrap_err();
+
+        assert_eq!(err.message, "unexpected character `~`");
+        assert_eq!((err.span.start, err.span.end), (9, 10));
+    }
+}
@@ -0,0 +1,608 @@
+/// Translation of GraphQL schema definitions into type aliases.
+///
+/// Object, interface and input types become object literals, enums and unions become unions, and
+/// every type is nullable unless marked with `!`. Fields of input types that are nullable can
+/// also be left out, hence they are optional as well. Arguments, directives and default values
+/// have no bearing on the types and are skipped.
+use std::collections::HashMap;
+
+use crate::{
+    ast::{
+        Ast, Ident, ObjectProperty, ObjectPropertyKey, PrimitiveType, Program, Span, TypeAlias,
+        TypeLiteral, TypeString,
+    },
+    diagnostic::{codes, Diagnostic},
+    import::{alias_name, expected, ident, union, unsupported},
+};
+
+use lexer::{Token, TokenKind};
+
+mod lexer;
+
+type Result<T> = std::result::Result<T, Diagnostic>;
+
+/// Parses the schema in `source` into a program of type aliases. Custom scalars are declared as
+/// the type given for them in `scalars`, which can also override the built-in scalars.
+pub fn from_sdl(source: &str, scalars: &HashMap<String, Ast>) -> Result<Ast> {
+    let tokens = lexer::tokenize(source)
+        .map_err(|diagnostic| diagnostic.with_code(codes::IMPORT_SYNTAX_ERROR))?;
+
+    let mut parser = Parser {
+        source,
+        tokens,
+        pos: 0,
+        scalars,
+    };
+
+    parser.document()
+}
+
+/// A type reference of a field.
+enum Type {
+    Named(String),
+    List(Box<Type>),
+    NonNull(Box<Type>),
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    scalars: &'a HashMap<String, Ast>,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> Result<Ast> {
+        let mut statements = vec![];
+
+        while self.peek().kind != TokenKind::Eof {
+            if let Some(alias) = self.definition()? {
+                statements.push(Ast::Statement(alias.into()));
+            }
+        }
+
+        Ok(Ast::Program(Program {
+            statements,
+            span: Span::default(),
+        }))
+    }
+
+    /// A definition, or `None` for the ones that don't declare a type.
+    fn definition(&mut self) -> Result<Option<Ast>> {
+        let doc = self.description();
+
+        let token = self.next();
+
+        let body = match self.name_of(&token) {
+            Some("schema") => {
+                self.directives()?;
+                self.skip_braces()?;
+                return Ok(None);
+            }
+            Some("directive") => {
+                self.directive_definition()?;
+                return Ok(None);
+            }
+            Some("scalar") => {
+                let name = self.peek().clone();
+                let scalar = self.name()?;
+                self.directives()?;
+
+                let ty = self.scalars.get(&scalar).cloned().ok_or_else(|| {
+                    Diagnostic::error(
+                        format!("no type is given for the custom scalar `{scalar}`"),
+                        name.span,
+                    )
+                    .with_note(format!("map it to a type with `--scalar {scalar}=<type>`"))
+                    .with_code(codes::UNMAPPED_SCALAR)
+                })?;
+
+                return Ok(Some(alias(doc, &scalar, ty)));
+            }
+            Some(keyword @ ("type" | "interface" | "input")) => {
+                let input = keyword == "input";
+                let name = self.name()?;
+
+                self.implements()?;
+                self.directives()?;
+
+                let properties = self.fields(input)?;
+
+                (
+                    name,
+                    Ast::TypeLiteral(TypeLiteral {
+                        properties,
+                        span: Span::default(),
+                    }),
+                )
+            }
+            Some("enum") => {
+                let name = self.name()?;
+                self.directives()?;
+                self.expect("{")?;
+
+                let mut values = vec![];
+
+                while !self.eat("}") {
+                    self.description();
+
+                    values.push(Ast::TypeString(TypeString {
+                        ty: self.name()?,
+                        span: Span::default(),
+                    }));
+
+                    self.directives()?;
+                }
+
+                (name, union(values))
+            }
+            Some("union") => {
+                let name = self.name()?;
+                self.directives()?;
+
+                let mut members = vec![];
+
+                if self.eat("=") {
+                    self.eat("|");
+
+                    loop {
+                        members.push(reference(&self.name()?));
+
+                        if !self.eat("|") {
+                            break;
+                        }
+                    }
+                }
+
+                (name, union(members))
+            }
+            Some("extend") => return Err(unsupported("type extensions", token.span)),
+            Some("query" | "mutation" | "subscription" | "fragment") => {
+                return Err(unsupported("operations", token.span)
+                    .with_note("only schema definitions can be imported"))
+            }
+            _ => return Err(expected("a type definition", token.span, self.source)),
+        };
+
+        let (name, ty) = body;
+
+        Ok(Some(alias(doc, &name, ty)))
+    }
+
+    /// The fields of an object, interface or input type, between braces.
+    fn fields(&mut self, input: bool) -> Result<Vec<ObjectProperty>> {
+        let mut properties = vec![];
+
+        if !self.eat("{") {
+            return Ok(properties);
+        }
+
+        while !self.eat("}") {
+            self.description();
+
+            let key = self.name()?;
+
+            if !input && self.eat("(") {
+                self.input_values(")")?;
+            }
+
+            self.expect(":")?;
+
+            let ty = self.ty()?;
+
+            if input && self.eat("=") {
+                self.value()?;
+            }
+
+            self.directives()?;
+
+            properties.push(ObjectProperty {
+                readonly: false,
+                optional: input && !matches!(ty, Type::NonNull(_)),
+                key: ObjectPropertyKey::Key(key),
+                value: self.convert(&ty),
+                span: Span::default(),
+            });
+        }
+
+        Ok(properties)
+    }
+
+    fn convert(&self, ty: &Type) -> Ast {
+        match ty {
+            Type::NonNull(ty) => self.convert_non_null(ty),
+            ty => union(vec![
+                self.convert_non_null(ty),
+                Ast::Primitive(PrimitiveType::Null, Span::default()),
+            ]),
+        }
+    }
+
+    fn convert_non_null(&self, ty: &Type) -> Ast {
+        match ty {
+            // Custom scalars are declared as aliases, so only the built-in ones are looked up.
+            Type::Named(name) => match builtin_scalar(name) {
+                Some(builtin) => self.scalars.get(name).cloned().unwrap_or(builtin),
+                None => reference(name),
+            },
+            Type::List(item) => Ast::Array(self.convert(item).into()),
+            Type::NonNull(ty) => self.convert_non_null(ty),
+        }
+    }
+
+    fn ty(&mut self) -> Result<Type> {
+        let ty = if self.eat("[") {
+            let item = self.ty()?;
+            self.expect("]")?;
+            Type::List(item.into())
+        } else {
+            Type::Named(self.name()?)
+        };
+
+        if self.eat("!") {
+            Ok(Type::NonNull(ty.into()))
+        } else {
+            Ok(ty)
+        }
+    }
+
+    fn implements(&mut self) -> Result<()> {
+        if self.eat_name("implements") {
+            self.eat("&");
+            self.name()?;
+
+            while self.eat("&") {
+                self.name()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn directive_definition(&mut self) -> Result<()> {
+        self.expect("@")?;
+        self.name()?;
+
+        if self.eat("(") {
+            self.input_values(")")?;
+        }
+
+        self.eat_name("repeatable");
+
+        if !self.eat_name("on") {
+            return Err(self.expected("`on`"));
+        }
+
+        self.eat("|");
+        self.name()?;
+
+        while self.eat("|") {
+            self.name()?;
+        }
+
+        Ok(())
+    }
+
+    /// Argument definitions up to `close`.
+    fn input_values(&mut self, close: &'static str) -> Result<()> {
+        while !self.eat(close) {
+            self.description();
+            self.name()?;
+            self.expect(":")?;
+            self.ty()?;
+
+            if self.eat("=") {
+                self.value()?;
+            }
+
+            self.directives()?;
+        }
+
+        Ok(())
+    }
+
+    fn directives(&mut self) -> Result<()> {
+        while self.eat("@") {
+            self.name()?;
+
+            if self.eat("(") {
+                while !self.eat(")") {
+                    self.name()?;
+                    self.expect(":")?;
+                    self.value()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Skips a constant value.
+    fn value(&mut self) -> Result<()> {
+        let token = self.next();
+
+        match token.kind {
+            TokenKind::Name(_) | TokenKind::String(_) | TokenKind::Number(_) => {}
+            TokenKind::Punct("$") => {
+                self.name()?;
+            }
+            TokenKind::Punct("[") => {
+                while !self.eat("]") {
+                    self.value()?;
+                }
+            }
+            TokenKind::Punct("{") => {
+                while !self.eat("}") {
+                    self.name()?;
+                    self.expect(":")?;
+                    self.value()?;
+                }
+            }
+            _ => return Err(expected("a value", token.span, self.source)),
+        }
+
+        Ok(())
+    }
+
+    fn skip_braces(&mut self) -> Result<()> {
+        self.expect("{")?;
+
+        let mut depth = 1;
+
+        while depth > 0 {
+            match self.next().kind {
+                TokenKind::Punct("{") => depth += 1,
+                TokenKind::Punct("}") => depth -= 1,
+                TokenKind::Eof => return Err(self.expected("`}`")),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn description(&mut self) -> Option<String> {
+        match self.peek().kind.clone() {
+            TokenKind::String(description) => {
+                self.next();
+                Some(description)
+            }
+            _ => None,
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        match self.peek().kind.clone() {
+            TokenKind::Name(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => Err(self.expected("a name")),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        self.pos = (self.pos + 1).min(self.tokens.len() - 1);
+        token
+    }
+
+    fn name_of<'t>(&self, token: &'t Token) -> Option<&'t str> {
+        match &token.kind {
+            TokenKind::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, punct: &'static str) -> bool {
+        if self.peek().kind == TokenKind::Punct(punct) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_name(&mut self, name: &str) -> bool {
+        if self.name_of(self.peek()) == Some(name) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &'static str) -> Result<()> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{punct}`")))
+        }
+    }
+
+    fn expected(&self, what: &str) -> Diagnostic {
+        expected(what, self.peek().span, self.source)
+    }
+}
+
+fn builtin_scalar(name: &str) -> Option<Ast> {
+    let primitive = match name {
+        "Int" | "Float" => PrimitiveType::Number,
+        "String" | "ID" => PrimitiveType::String,
+        "Boolean" => PrimitiveType::Boolean,
+        _ => return None,
+    };
+
+    Some(Ast::Primitive(primitive, Span::default()))
+}
+
+fn reference(name: &str) -> Ast {
+    ident(alias_name(name))
+}
+
+fn alias(doc: Option<String>, name: &str, body: Ast) -> Ast {
+    Ast::TypeAlias(TypeAlias {
+        doc,
+        export: true,
+        global: false,
+        default: false,
+        name: Ident {
+            name: alias_name(name),
+            span: Span::default(),
+            raw: false,
+        },
+        params: vec![],
+        body: body.into(),
+        span: Span::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl::Pretty as _, source::SourceFile, test_support::parse};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn import(source: &str, scalars: &[(&str, &str)]) -> Result<String> {
+        let scalars = scalars
+            .iter()
+            .map(|(name, ty)| {
+                (
+                    name.to_string(),
+                    crate::parser::parse_newtype_expr(ty).unwrap(),
+                )
+            })
+            .collect();
+
+        Ok(from_sdl(source, &scalars)?.render_pretty_dsl(80))
+    }
+
+    #[test]
+    fn every_construct() {
+        let source = r#"
+            schema { query: Query }
+
+            directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+
+            "Something with an id."
+            interface Node {
+              id: ID!
+            }
+
+            type User implements Node & Named @key(fields: "id") {
+              id: ID!
+              name: String
+              age: Int!
+              score: Float
+              friends(first: Int = 10, after: String): [User!]!
+              tags: [String]
+              status: Status!
+              result: SearchResult @deprecated(reason: "use `results`")
+            }
+
+            """
+            How far a user got.
+            """
+            enum Status {
+              ACTIVE
+              "No longer around."
+              BANNED @deprecated
+            }
+
+            union SearchResult = | User | Node
+
+            input Filter {
+              name: String
+              limit: Int! = 20
+              statuses: [Status!] = [ACTIVE]
+            }
+        "#;
+
+        assert_eq!(
+            import(source, &[]).unwrap(),
+            dedent!(
+                r#"
+                /** Something with an id. */
+                export type Node as {id: string}
+
+                export type User as
+                    {
+                        id: string,
+                        name: string | null,
+                        age: number,
+                        score: number | null,
+                        friends: User[],
+                        tags: (string | null)[] | null,
+                        status: Status,
+                        result: SearchResult | null,
+                    }
+
+                /** How far a user got. */
+                export type Status as "ACTIVE" | "BANNED"
+
+                export type SearchResult as User | Node
+
+                export type Filter as
+                    {?name: string | null, limit: number, ?statuses: Status[] | null}
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn custom_scalars_are_mapped() {
+        let source = "scalar DateTime\ntype Event { at: DateTime!, id: ID! }";
+
+        assert_eq!(
+            import(source, &[("DateTime", "string"), ("ID", "number")]).unwrap(),
+            "export type DateTime as string\n\nexport type Event as {at: DateTime, id: number}\n"
+        );
+    }
+
+    #[test]
+    fn unknown_scalar() {
+        let source = "scalar DateTime\ntype Event { at: DateTime! }";
+
+        let diagnostic = import(source, &[]).unwrap_err();
+
+        assert_eq!(
+            diagnostic.render(&SourceFile::new("", source)),
+            dedent!(
+                "
+                error[DL0010]: no type is given for the custom scalar `DateTime`
+                 --> 1:8
+                  |
+                1 | scalar DateTime
+                  |        ^^^^^^^^
+                  = note: map it to a type with `--scalar DateTime=<type>`
+                  = help: run `newtype explain DL0010` for more information"
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn output_reparses() {
+        let source = "type A { a: [[B!]], b: String! }\nunion B = A";
+
+        let program = from_sdl(source, &HashMap::new()).unwrap();
+
+        let source = program.render_pretty_dsl(80);
+
+        assert_eq!(parse!(source.trim()), program);
+    }
+
+    #[test]
+    fn operations_are_unsupported() {
+        let diagnostic = import("type A { a: Int }\nquery { a }", &[]).unwrap_err();
+
+        assert_eq!(diagnostic.message, "unsupported construct: operations");
+        assert_eq!(diagnostic.span.start, 18);
+    }
+}
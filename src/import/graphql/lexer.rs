@@ -0,0 +1,241 @@
+/// Tokenizer for GraphQL schema definitions.
+use crate::{ast::Span, diagnostic::Diagnostic};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Name(String),
+    /// A string or block string, with its escapes resolved and its indentation removed.
+    String(String),
+    Number(String),
+    Punct(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+const PUNCTUATION: &[&str] = &[
+    "...", "!", "$", "&", "(", ")", ":", "=", "@", "[", "]", "{", "|", "}",
+];
+
+/// Splits `source` into tokens, skipping whitespace, commas and comments. The last token is
+/// always [`TokenKind::Eof`].
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token>, Diagnostic> {
+    let mut tokens = vec![];
+    let mut offset = 0;
+
+    while offset < source.len() {
+        let rest = &source[offset..];
+        let c = rest.chars().next().unwrap();
+
+        if c.is_whitespace() || c == ',' || c == '\u{FEFF}' {
+            offset += c.len_utf8();
+            continue;
+        }
+
+        if c == '#' {
+            offset += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+
+        let (kind, len) = if c == '_' || c.is_ascii_alphabetic() {
+            let len = rest
+                .find(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+                .unwrap_or(rest.len());
+
+            (TokenKind::Name(rest[..len].to_string()), len)
+        } else if c == '-' || c.is_ascii_digit() {
+            let len = rest
+                .char_indices()
+                .skip(1)
+                .find(|(i, c)| {
+                    let sign = (*c == '+' || *c == '-') && rest[..*i].ends_with(['e', 'E']);
+                    !(c.is_ascii_alphanumeric() || *c == '.' || sign)
+                })
+                .map_or(rest.len(), |(i, _)| i);
+
+            (TokenKind::Number(rest[..len].to_string()), len)
+        } else if let Some(block) = rest.strip_prefix("\"\"\"") {
+            let len = block_string_len(block).ok_or_else(|| {
+                Diagnostic::error("unterminated block string", Span::new(offset, offset + 3))
+            })?;
+
+            let raw = &block[..len].replace("\\\"\"\"", "\"\"\"");
+
+            (TokenKind::String(block_string_value(raw)), len + 6)
+        } else if c == '"' {
+            let (value, len) = string(rest).ok_or_else(|| {
+                Diagnostic::error("unterminated string", Span::new(offset, offset + 1))
+            })?;
+
+            (TokenKind::String(value), len)
+        } else if let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) {
+            (TokenKind::Punct(punct), punct.len())
+        } else {
+            return Err(Diagnostic::error(
+                format!("unexpected character `{c}`"),
+                Span::new(offset, offset + c.len_utf8()),
+            ));
+        };
+
+        tokens.push(Token {
+            kind,
+            span: Span::new(offset, offset + len),
+        });
+
+        offset += len;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span::new(source.len(), source.len()),
+    });
+
+    Ok(tokens)
+}
+
+/// Length of the contents of the block string starting `block`, without its closing quotes.
+fn block_string_len(block: &str) -> Option<usize> {
+    let mut offset = 0;
+
+    loop {
+        let end = offset + block[offset..].find("\"\"\"")?;
+
+        if block[..end].ends_with('\\') {
+            offset = end + 3;
+        } else {
+            return Some(end);
+        }
+    }
+}
+
+/// The value of a block string: the common indentation of all but the first line and the blank
+/// leading and trailing lines are removed.
+fn block_string_value(raw: &str) -> String {
+    let lines: Vec<_> = raw.lines().collect();
+
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let lines: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line
+            } else {
+                line.get(indent..).unwrap_or("")
+            }
+        })
+        .collect();
+
+    let start = lines
+        .iter()
+        .take_while(|line| line.trim().is_empty())
+        .count();
+    let end = lines.len()
+        - lines
+            .iter()
+            .rev()
+            .take_while(|line| line.trim().is_empty())
+            .count();
+
+    lines[start..end.max(start)].join("\n")
+}
+
+/// Value and length of the string literal at the start of `rest`.
+fn string(rest: &str) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = rest.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, i + 1)),
+            '\n' => return None,
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'u' => {
+                    let digits: String = (0..4)
+                        .filter_map(|_| chars.next())
+                        .map(|(_, c)| c)
+                        .collect();
+                    value.push(char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?);
+                }
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn tokens() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("type A { # comment\n a(x: Int = -1.5e+3, y: \"\\u0041\"): [A!]! }"),
+            vec![
+                Name("type".to_string()),
+                Name("A".to_string()),
+                Punct("{"),
+                Name("a".to_string()),
+                Punct("("),
+                Name("x".to_string()),
+                Punct(":"),
+                Name("Int".to_string()),
+                Punct("="),
+                Number("-1.5e+3".to_string()),
+                Name("y".to_string()),
+                Punct(":"),
+                String("A".to_string()),
+                Punct(")"),
+                Punct(":"),
+                Punct("["),
+                Name("A".to_string()),
+                Punct("!"),
+                Punct("]"),
+                Punct("!"),
+                Punct("}"),
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn block_strings_are_dedented() {
+        assert_eq!(
+            kinds("\"\"\"\n    First\n      second \\\"\"\"\n    \"\"\""),
+            vec![
+                TokenKind::String("First\n  second \"\"\"".to_string()),
+                TokenKind::Eof
+            ]
+        );
+    }
+}
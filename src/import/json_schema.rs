@@ -0,0 +1,765 @@
+/// Translation of JSON Schema documents into type aliases.
+///
+/// The root schema and every definition reached through a local `$ref` become a type alias, and
+/// references are emitted as the name of the referenced alias, which makes recursive schemas work
+/// without any special handling.
+use serde_json::{Map, Value};
+
+use crate::{
+    ast::{
+        ApplyGeneric, Ast, Ident, ObjectProperty, ObjectPropertyKey, PrimitiveType, Program, Span,
+        Tuple, TypeAlias, TypeLiteral, TypeNumber, TypeString,
+    },
+    diagnostic::{codes, Diagnostic},
+    import::{alias_name, ident, intersection, union},
+};
+
+type Result<T> = std::result::Result<T, Diagnostic>;
+
+/// Builds a program declaring the schema in `source` as `root_name`, followed by the definitions
+/// it references.
+pub fn from_schema(source: &str, root_name: &str) -> Result<Ast> {
+    let schema: Value = serde_json::from_str(source).map_err(|err| {
+        let offset = offset(source, err.line(), err.column());
+
+        Diagnostic::error(err.to_string(), Span::new(offset, offset))
+            .with_code(codes::IMPORT_SYNTAX_ERROR)
+    })?;
+
+    let mut importer = Importer {
+        source,
+        root: &schema,
+        aliases: vec![("#".to_string(), alias_name(root_name))],
+    };
+
+    let mut statements = vec![];
+
+    // Converting a schema can reference new definitions, which are appended to the aliases.
+    let mut i = 0;
+
+    while let Some((reference, name)) = importer.aliases.get(i).cloned() {
+        let pointer = &reference[1..];
+        let schema = importer.root.pointer(pointer).unwrap_or(&Value::Null);
+
+        let doc = schema
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        let body = importer.convert(schema, pointer)?;
+
+        let alias = Ast::TypeAlias(TypeAlias {
+            doc,
+            export: true,
+            global: false,
+            default: false,
+            name: Ident {
+                name,
+                span: Span::default(),
+                raw: false,
+            },
+            params: vec![],
+            body: body.into(),
+            span: Span::default(),
+        });
+
+        statements.push(Ast::Statement(alias.into()));
+
+        i += 1;
+    }
+
+    Ok(Ast::Program(Program {
+        statements,
+        span: Span::default(),
+    }))
+}
+
+struct Importer<'a> {
+    source: &'a str,
+    root: &'a Value,
+    /// Local references (`#/definitions/Name`) of the schemas to declare, with the name of their
+    /// alias, in declaration order.
+    aliases: Vec<(String, String)>,
+}
+
+impl<'a> Importer<'a> {
+    /// An error pointing at the value at `pointer` in the source.
+    fn error(&self, message: String, pointer: &str) -> Diagnostic {
+        Diagnostic::error(message, locate(self.source, pointer))
+    }
+
+    /// Name of the alias declared for the schema `reference` points at, queueing it if it is new.
+    /// `at` is the pointer of the `$ref` itself.
+    fn reference(&mut self, reference: &str, at: &str) -> Result<Ast> {
+        if let Some((_, name)) = self.aliases.iter().find(|(r, _)| r == reference) {
+            return Ok(ident(name.clone()));
+        }
+
+        let Some(pointer) = reference.strip_prefix('#') else {
+            return Err(self
+                .error(format!("can't import the schema at `{reference}`"), at)
+                .with_note("only references within the schema are supported")
+                .with_code(codes::UNSUPPORTED_CONSTRUCT));
+        };
+
+        if self.root.pointer(pointer).is_none() {
+            return Err(self
+                .error(format!("`{reference}` doesn't point at a schema"), at)
+                .with_code(codes::IMPORT_SYNTAX_ERROR));
+        }
+
+        // The last segment of the pointer, with JSON pointer escapes undone.
+        let segment = reference.rsplit('/').next().unwrap_or_default();
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+
+        let base = alias_name(&segment);
+        let mut name = base.clone();
+        let mut n = 1;
+
+        while self.aliases.iter().any(|(_, other)| *other == name) {
+            n += 1;
+            name = format!("{base}{n}");
+        }
+
+        self.aliases.push((reference.to_string(), name.clone()));
+
+        Ok(ident(name))
+    }
+
+    /// The type of `schema`, which is at `at` in the document.
+    fn convert(&mut self, schema: &Value, at: &str) -> Result<Ast> {
+        let schema = match schema {
+            Value::Bool(true) => return Ok(Ast::UnknownKeyword(Span::default())),
+            Value::Bool(false) => return Ok(Ast::NeverKeyword(Span::default())),
+            Value::Object(schema) => schema,
+            schema => {
+                return Err(self
+                    .error(format!("expected a schema, found `{schema}`"), at)
+                    .with_code(codes::IMPORT_SYNTAX_ERROR))
+            }
+        };
+
+        if let Some(Value::String(reference)) = schema.get("$ref") {
+            return self.reference(reference, &format!("{at}/$ref"));
+        }
+
+        if let Some(value) = schema.get("const") {
+            return Ok(literal(value));
+        }
+
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            return Ok(union(values.iter().map(literal).collect()));
+        }
+
+        let mut parts = vec![];
+
+        match schema.get("type") {
+            Some(Value::String(ty)) => {
+                parts.push(self.convert_type(ty, schema, at, &format!("{at}/type"))?)
+            }
+            Some(Value::Array(types)) => {
+                let types = types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| {
+                        let type_at = format!("{at}/type/{i}");
+
+                        match ty {
+                            Value::String(ty) => self.convert_type(ty, schema, at, &type_at),
+                            ty => Err(self
+                                .error(
+                                    format!("expected the name of a type, found `{ty}`"),
+                                    &type_at,
+                                )
+                                .with_code(codes::IMPORT_SYNTAX_ERROR)),
+                        }
+                    })
+                    .collect::<Result<_>>()?;
+
+                parts.push(union(types));
+            }
+            Some(ty) => {
+                return Err(self
+                    .error(
+                        format!("expected the name of a type, found `{ty}`"),
+                        &format!("{at}/type"),
+                    )
+                    .with_code(codes::IMPORT_SYNTAX_ERROR))
+            }
+            None if schema.contains_key("properties") => {
+                parts.push(self.convert_object(schema, at)?)
+            }
+            None if schema.contains_key("items") || schema.contains_key("prefixItems") => {
+                parts.push(self.convert_array(schema, at)?)
+            }
+            None => {}
+        }
+
+        for (keyword, combine) in [
+            ("oneOf", union as fn(Vec<Ast>) -> Ast),
+            ("anyOf", union),
+            ("allOf", intersection),
+        ] {
+            if let Some(Value::Array(schemas)) = schema.get(keyword) {
+                let types = schemas
+                    .iter()
+                    .enumerate()
+                    .map(|(i, schema)| self.convert(schema, &format!("{at}/{keyword}/{i}")))
+                    .collect::<Result<_>>()?;
+                parts.push(combine(types));
+            }
+        }
+
+        if parts.is_empty() {
+            Ok(Ast::UnknownKeyword(Span::default()))
+        } else {
+            Ok(intersection(parts))
+        }
+    }
+
+    /// The type of `schema`, at `at`, of the type named `ty`, at `type_at`.
+    fn convert_type(
+        &mut self,
+        ty: &str,
+        schema: &Map<String, Value>,
+        at: &str,
+        type_at: &str,
+    ) -> Result<Ast> {
+        match ty {
+            "string" => Ok(primitive(PrimitiveType::String)),
+            "number" | "integer" => Ok(primitive(PrimitiveType::Number)),
+            "boolean" => Ok(primitive(PrimitiveType::Boolean)),
+            "null" => Ok(primitive(PrimitiveType::Null)),
+            "array" => self.convert_array(schema, at),
+            "object" => self.convert_object(schema, at),
+            ty => Err(self
+                .error(format!("unknown type `{ty}`"), type_at)
+                .with_note(
+                    "the types are `string`, `number`, `integer`, `boolean`, `null`, `array` and \
+                     `object`",
+                )
+                .with_code(codes::IMPORT_SYNTAX_ERROR)),
+        }
+    }
+
+    fn convert_array(&mut self, schema: &Map<String, Value>, at: &str) -> Result<Ast> {
+        // Tuples are `prefixItems` since draft 2020-12, and an array of `items` before.
+        let items = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(items)), _) | (None, Some(Value::Array(items))) => {
+                let keyword = if schema.contains_key("prefixItems") {
+                    "prefixItems"
+                } else {
+                    "items"
+                };
+
+                return Ok(Ast::Tuple(Tuple {
+                    items: items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| self.convert(item, &format!("{at}/{keyword}/{i}")))
+                        .collect::<Result<_>>()?,
+                    span: Span::default(),
+                }));
+            }
+            (_, Some(items)) => self.convert(items, &format!("{at}/items"))?,
+            (_, None) => Ast::UnknownKeyword(Span::default()),
+        };
+
+        Ok(Ast::Array(items.into()))
+    }
+
+    fn convert_object(&mut self, schema: &Map<String, Value>, at: &str) -> Result<Ast> {
+        let required: Vec<_> = match schema.get("required") {
+            Some(Value::Array(required)) => required.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+
+        let properties: Vec<_> = match schema.get("properties") {
+            Some(Value::Object(properties)) => properties
+                .iter()
+                .map(|(key, value)| {
+                    let key_at = format!("{at}/properties/{}", escape(key));
+
+                    Ok(ObjectProperty {
+                        readonly: value.get("readOnly") == Some(&Value::Bool(true)),
+                        optional: !required.contains(&key.as_str()),
+                        key: ObjectPropertyKey::Key(key.clone()),
+                        value: self.convert(value, &key_at)?,
+                        span: Span::default(),
+                    })
+                })
+                .collect::<Result<_>>()?,
+            _ => vec![],
+        };
+
+        // A mapped type can't have other members, so extra properties are typed as a record
+        // intersected with the declared ones.
+        let additional = match schema.get("additionalProperties") {
+            None | Some(Value::Bool(false)) => None,
+            Some(value) => Some(record(
+                self.convert(value, &format!("{at}/additionalProperties"))?,
+            )),
+        };
+
+        Ok(match (properties.is_empty(), additional) {
+            (true, None) => primitive(PrimitiveType::Object),
+            (true, Some(additional)) => additional,
+            (false, additional) => {
+                let literal = Ast::TypeLiteral(TypeLiteral {
+                    properties,
+                    span: Span::default(),
+                });
+
+                intersection([literal].into_iter().chain(additional).collect())
+            }
+        })
+    }
+}
+
+/// `key` as a segment of a JSON pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// The byte offset of the 1-based `line` and `column` of `source`.
+fn offset(source: &str, line: usize, column: usize) -> usize {
+    let start: usize = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+
+    (start + column.saturating_sub(1)).min(source.len())
+}
+
+/// The span of the value at the JSON pointer `pointer` in the JSON text `source`, an empty span at
+/// the start if there is none.
+fn locate(source: &str, pointer: &str) -> Span {
+    let bytes = source.as_bytes();
+    let mut pos = skip_whitespace(bytes, 0);
+
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+
+        let found = match bytes.get(pos) {
+            Some(b'{') => member(source, pos, &segment),
+            Some(b'[') => segment
+                .parse()
+                .ok()
+                .and_then(|index| element(bytes, pos, index)),
+            _ => None,
+        };
+
+        match found {
+            Some(found) => pos = found,
+            None => return Span::default(),
+        }
+    }
+
+    Span::new(pos, skip_value(bytes, pos))
+}
+
+/// The start of the value of the member `key` of the object starting at `pos`.
+fn member(source: &str, mut pos: usize, key: &str) -> Option<usize> {
+    let bytes = source.as_bytes();
+
+    loop {
+        pos = skip_whitespace(bytes, pos + 1);
+
+        if bytes.get(pos) != Some(&b'"') {
+            return None;
+        }
+
+        let end = skip_value(bytes, pos);
+        let name: String = serde_json::from_str(&source[pos..end]).ok()?;
+
+        pos = skip_whitespace(bytes, end);
+        pos = skip_whitespace(bytes, pos + 1);
+
+        if name == key {
+            return Some(pos);
+        }
+
+        pos = skip_whitespace(bytes, skip_value(bytes, pos));
+
+        if bytes.get(pos) != Some(&b',') {
+            return None;
+        }
+    }
+}
+
+/// The start of the element `index` of the array starting at `pos`.
+fn element(bytes: &[u8], mut pos: usize, index: usize) -> Option<usize> {
+    for _ in 0..index {
+        pos = skip_whitespace(bytes, skip_value(bytes, skip_whitespace(bytes, pos + 1)));
+
+        if bytes.get(pos) != Some(&b',') {
+            return None;
+        }
+    }
+
+    let pos = skip_whitespace(bytes, pos + 1);
+
+    (bytes.get(pos) != Some(&b']')).then_some(pos)
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+
+    pos
+}
+
+/// The end of the value starting at `pos`.
+fn skip_value(bytes: &[u8], mut pos: usize) -> usize {
+    let mut depth = 0;
+
+    while let Some(&byte) = bytes.get(pos) {
+        match byte {
+            b'"' => {
+                pos += 1;
+
+                while let Some(&byte) = bytes.get(pos) {
+                    pos += if byte == b'\\' { 2 } else { 1 };
+
+                    if byte == b'"' {
+                        break;
+                    }
+                }
+
+                if depth == 0 {
+                    return pos;
+                }
+
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth > 0 => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return pos + 1;
+                }
+            }
+            b',' | b'}' | b']' if depth == 0 => return pos,
+            byte if depth == 0 && byte.is_ascii_whitespace() => return pos,
+            _ => {}
+        }
+
+        pos += 1;
+    }
+
+    pos
+}
+
+fn primitive(primitive: PrimitiveType) -> Ast {
+    Ast::Primitive(primitive, Span::default())
+}
+
+/// The type of a `const` or `enum` value.
+fn literal(value: &Value) -> Ast {
+    match value {
+        Value::Null => primitive(PrimitiveType::Null),
+        Value::Bool(true) => Ast::TrueKeyword(Span::default()),
+        Value::Bool(false) => Ast::FalseKeyword(Span::default()),
+        Value::Number(number) => Ast::TypeNumber(TypeNumber {
+            ty: number.to_string(),
+            span: Span::default(),
+        }),
+        Value::String(string) => Ast::TypeString(TypeString {
+            ty: string.clone(),
+            span: Span::default(),
+        }),
+        Value::Array(items) => Ast::Tuple(Tuple {
+            items: items.iter().map(literal).collect(),
+            span: Span::default(),
+        }),
+        Value::Object(properties) => Ast::TypeLiteral(TypeLiteral {
+            properties: properties
+                .iter()
+                .map(|(key, value)| ObjectProperty {
+                    readonly: false,
+                    optional: false,
+                    key: ObjectPropertyKey::Key(key.clone()),
+                    value: literal(value),
+                    span: Span::default(),
+                })
+                .collect(),
+            span: Span::default(),
+        }),
+    }
+}
+
+fn record(value: Ast) -> Ast {
+    Ast::ApplyGeneric(ApplyGeneric {
+        receiver: ident("Record".to_string()).into(),
+        args: vec![primitive(PrimitiveType::String), value],
+        span: Span::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl::Pretty as _, test_support::parse, typescript::Pretty as _};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use textwrap_macros::dedent;
+
+    fn import(schema: Value) -> String {
+        from_schema(&schema.to_string(), "Root")
+            .unwrap()
+            .render_pretty_dsl(80)
+    }
+
+    #[test]
+    fn every_construct() {
+        let schema = json!({
+            "description": "A user.",
+            "type": "object",
+            "required": ["id", "name", "tags"],
+            "properties": {
+                "id": { "type": "integer", "readOnly": true },
+                "name": { "type": "string" },
+                "email": { "type": ["string", "null"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "point": { "type": "array", "prefixItems": [{ "type": "number" }, { "type": "number" }] },
+                "role": { "enum": ["admin", "user", 1, true, null] },
+                "kind": { "const": "user" },
+                "address": { "$ref": "#/definitions/Address" },
+                "contact": { "oneOf": [{ "$ref": "#/definitions/Address" }, { "type": "boolean" }] },
+                "named": { "allOf": [{ "$ref": "#/$defs/Named" }, { "type": "object", "additionalProperties": true }] },
+                "extra": { "type": "object", "additionalProperties": { "type": "number" } },
+                "meta": {},
+                "never": false,
+                "first-name": { "anyOf": [{ "type": "string" }, { "type": "array", "items": { "type": ["string", "number"] } }] }
+            },
+            "definitions": {
+                "Address": {
+                    "type": "object",
+                    "properties": { "street": { "type": "string" } },
+                    "additionalProperties": { "type": "string" }
+                }
+            },
+            "$defs": {
+                "Named": { "type": "object" }
+            }
+        });
+
+        assert_eq!(
+            import(schema),
+            dedent!(
+                r#"
+                /** A user. */
+                export type Root as
+                    {
+                        ?address: Address,
+                        ?contact: Address | boolean,
+                        ?email: string | null,
+                        ?extra: Record(string, number),
+                        ?"first-name": string | (string | number)[],
+                        readonly id: number,
+                        ?kind: "user",
+                        ?meta: unknown,
+                        name: string,
+                        ?named: Named & Record(string, unknown),
+                        ?"never": never,
+                        ?point: [number, number],
+                        ?role: "admin" | "user" | 1 | true | null,
+                        tags: string[],
+                    }
+
+                export type Address as {?street: string} & Record(string, string)
+
+                export type Named as object
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn optional_properties() {
+        let schema = json!({
+            "type": "object",
+            "required": ["a"],
+            "properties": { "a": { "type": "string" }, "b": { "type": "string" } }
+        });
+
+        assert_eq!(
+            import(schema),
+            "export type Root as {a: string, ?b: string}\n"
+        );
+    }
+
+    #[test]
+    fn recursive_references() {
+        let schema = json!({
+            "$ref": "#/definitions/Tree",
+            "definitions": {
+                "Tree": {
+                    "type": "object",
+                    "required": ["children"],
+                    "properties": {
+                        "children": { "type": "array", "items": { "$ref": "#/definitions/Tree" } },
+                        "parent": { "$ref": "#" }
+                    }
+                }
+            }
+        });
+
+        assert_eq!(
+            import(schema),
+            dedent!(
+                "
+                export type Root as Tree
+
+                export type Tree as {children: Tree[], ?parent: Root}
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn names_are_sanitized() {
+        let schema = json!({
+            "oneOf": [
+                { "$ref": "#/definitions/a~1b" },
+                { "$ref": "#/definitions/1st" },
+                { "$ref": "#/definitions/new" },
+                { "$ref": "#/$defs/new" }
+            ],
+            "definitions": { "a/b": {}, "1st": {}, "new": {} },
+            "$defs": { "new": {} }
+        });
+
+        assert_eq!(
+            import(schema).lines().next().unwrap(),
+            "export type Root as a_b | _1st | new_ | new_2"
+        );
+    }
+
+    #[test]
+    fn output_reparses() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "a": { "oneOf": [{ "type": "string" }, { "$ref": "#/definitions/B" }] },
+                "b c": { "type": "array", "items": { "anyOf": [{ "type": "number" }, { "type": "null" }] } }
+            },
+            "definitions": { "B": { "enum": ["it's", 2] } }
+        });
+
+        let program = from_schema(&schema.to_string(), "Root").unwrap();
+
+        let source = program.render_pretty_dsl(80);
+
+        assert_eq!(parse!(source.trim()), program);
+    }
+
+    #[test]
+    fn compiles_to_typescript() {
+        let schema = json!({
+            "type": "object",
+            "required": ["a"],
+            "properties": { "a": { "type": "string" } },
+            "additionalProperties": { "type": "number" }
+        });
+
+        assert_eq!(
+            from_schema(&schema.to_string(), "Root")
+                .unwrap()
+                .simplify()
+                .render_pretty_ts(80)
+                .trim_end(),
+            "export type Root = {a: string} & Record<string, number>;"
+        );
+    }
+
+    /// The message of the error importing `source` and the text it points at.
+    fn error(source: &str) -> (String, &str, Option<&'static str>) {
+        let diagnostic = from_schema(source, "Root").unwrap_err();
+
+        (
+            diagnostic.message,
+            &source[diagnostic.span.start..diagnostic.span.end],
+            diagnostic.code,
+        )
+    }
+
+    #[test]
+    fn external_references() {
+        let source = r#"{"properties": {"a": {"$ref": "other.json#/definitions/A"}}}"#;
+
+        assert_eq!(
+            error(source),
+            (
+                "can't import the schema at `other.json#/definitions/A`".to_string(),
+                r#""other.json#/definitions/A""#,
+                Some(codes::UNSUPPORTED_CONSTRUCT.id)
+            )
+        );
+    }
+
+    #[test]
+    fn dangling_references() {
+        let source = r##"{"items": [{"type": "string"}, {"$ref": "#/definitions/B"}]}"##;
+
+        assert_eq!(error(source).1, r##""#/definitions/B""##);
+    }
+
+    #[test]
+    fn unknown_types() {
+        let source = dedent!(
+            r#"
+            {
+              "type": "object",
+              "properties": {
+                "a~b": { "type": ["string", "text"] },
+                "c": { "type": "date" }
+              }
+            }
+            "#
+        );
+
+        assert_eq!(
+            error(source),
+            (
+                "unknown type `text`".to_string(),
+                r#""text""#,
+                Some(codes::IMPORT_SYNTAX_ERROR.id)
+            )
+        );
+        assert_eq!(
+            error(r#"{"properties": {"c": {"type": "date"}}}"#).1,
+            r#""date""#
+        );
+    }
+
+    #[test]
+    fn values_that_are_not_schemas() {
+        assert_eq!(
+            error(r#"{"oneOf": [true, 1]}"#),
+            (
+                "expected a schema, found `1`".to_string(),
+                "1",
+                Some(codes::IMPORT_SYNTAX_ERROR.id)
+            )
+        );
+        assert_eq!(
+            error(r#"{"type": 1}"#).0,
+            "expected the name of a type, found `1`"
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        let source = "{\n  \"type\": \"object\",\n}";
+        let diagnostic = from_schema(source, "Root").unwrap_err();
+
+        assert_eq!(diagnostic.code, Some(codes::IMPORT_SYNTAX_ERROR.id));
+        assert_eq!(&source[diagnostic.span.start..], "}");
+    }
+}
@@ -1,3 +1,64 @@
+//! The DefinitelyLisp compiler, which the `newtype` binary wraps. Programs embedding it import
+//! from [`prelude`], whose items only change with the version of the crate; the other public
+//! modules are the binary's and can change at any time. `tests/public-api.txt` lists the whole
+//! public surface, so that neither changes by accident.
+#![feature(assert_matches)]
+#![feature(box_patterns)]
+#![feature(macro_metavar_expr)]
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_macros)]
+
+extern crate alloc;
+extern crate pest;
+
+#[macro_use]
+extern crate pest_derive;
+
+#[cfg(test)]
+extern crate quickcheck;
+
+#[cfg(test)]
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+pub mod analysis;
+pub mod ast;
+pub(crate) mod companion;
+pub mod compile;
+pub(crate) mod conditions;
+pub mod config;
+pub mod diagnostic;
+pub mod diff;
+pub mod dsl;
+pub(crate) mod error;
+pub(crate) mod extends_result;
+pub(crate) mod features;
+pub mod flags;
+pub(crate) mod hoist;
+pub mod import;
+pub(crate) mod lint;
+pub(crate) mod names;
+#[cfg(feature = "parser-internals")]
+pub mod parser;
+#[cfg(not(feature = "parser-internals"))]
+pub(crate) mod parser;
+pub mod prelude;
+pub mod pretty;
+pub mod query;
+pub mod repl;
+pub mod rewrite;
+pub(crate) mod runtime;
+pub mod source;
+pub(crate) mod strict;
+pub mod target;
+pub mod timings;
+pub mod typescript;
+pub mod zod;
+
+#[cfg(test)]
+mod test_support;
+
 /// Function composition macro
 #[macro_export]
 macro_rules! compose {
@@ -0,0 +1,3396 @@
+/// Checks over the parsed program that report diagnostics without changing the tree.
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use itertools::{Either, Itertools};
+use unicode_security::skeleton;
+
+use crate::{
+    ast::{
+        attribute, build,
+        cond_expr::CondExpr,
+        if_expr::IfExpr,
+        let_expr::LetExpr,
+        match_expr::MatchExpr,
+        visit::{
+            walk_ast, walk_cond_expr, walk_function_type, walk_if_expr, walk_interface,
+            walk_let_expr, walk_match_expr, walk_object_property, walk_type_alias, Visit,
+        },
+        Access, AccessKind, AmbientModule, ApplyGeneric, Ast, Bindings, Builtin, BuiltinKeyword,
+        ExportDefault, ExtendsInfixOp, FunctionType, ImportClause, InfixOp, Interface, MacroCall,
+        ObjectProperty, ObjectPropertyKey, PrimitiveType, Program, Span, Tuple, TypeAlias,
+        TypeLiteral, TypeParameter, UnionType,
+    },
+    companion,
+    diagnostic::{codes, Diagnostic, Severity, Suggestion},
+    dsl,
+    extends_result::ExtendsResult,
+    names::Gensym,
+    rewrite,
+    source::SourceFile,
+    typescript::{is_contextual_keyword, is_reserved_word},
+};
+
+mod lib_globals;
+
+/// Declarations named after a TypeScript contextual keyword.
+pub const CONTEXTUAL_KEYWORD: &str = "contextual_keyword";
+/// Names in the same scope that look alike.
+pub const CONFUSABLE_NAMES: &str = "confusable_names";
+
+/// Matches over the variants of a union that miss some of them.
+pub const NON_EXHAUSTIVE_MATCH: &str = "non_exhaustive_match";
+/// Declarations named after a type of the TypeScript standard library.
+pub const SHADOWED_GLOBAL: &str = "shadowed_global";
+/// Conditions of literals that always hold or never do.
+pub const CONSTANT_CONDITION: &str = "constant_condition";
+/// `any` in an exported declaration.
+pub const EXPLICIT_ANY: &str = "explicit_any";
+/// Generic aliases of a module applied once, which can be inlined.
+pub const SINGLE_USE_ALIAS: &str = "single_use_alias";
+/// Keys of the object literals of a union optional in some of them and required in others.
+pub const INCONSISTENT_OPTIONALITY: &str = "inconsistent_optionality";
+
+/// Lints whose level can be configured. Reserved words are always an error, the output would not
+/// compile.
+pub const LINTS: &[&str] = &[
+    CONTEXTUAL_KEYWORD,
+    CONFUSABLE_NAMES,
+    NON_EXHAUSTIVE_MATCH,
+    SHADOWED_GLOBAL,
+    CONSTANT_CONDITION,
+    EXPLICIT_ANY,
+    SINGLE_USE_ALIAS,
+    INCONSISTENT_OPTIONALITY,
+];
+
+/// Lints that only report when their level is set, as a matter of style rather than a likely
+/// mistake.
+pub const ALLOWED_BY_DEFAULT: &[&str] = &[EXPLICIT_ANY, SINGLE_USE_ALIAS, INCONSISTENT_OPTIONALITY];
+
+/// How the diagnostics of a lint are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// A check over a program, see [`STATEMENT_CHECKS`].
+pub type Check = fn(&Ast) -> Vec<Diagnostic>;
+
+/// The checks that hold for part of a program, run on each statement of a program compiled one
+/// statement at a time, by the name of the pass their duration is recorded as. The default export
+/// is left out, it needs the declarations of the whole module.
+pub const STATEMENT_CHECKS: &[(&str, Check)] = &[
+    ("reserved names", reserved_names),
+    ("confusable names", confusable_names),
+    ("macro arguments", macro_arguments),
+    ("non-exhaustive matches", non_exhaustive_matches),
+    ("constant conditions", constant_conditions),
+    ("applications", |program| {
+        applications(program, &Signatures::new(program))
+    }),
+    ("shadowed globals", shadowed_globals),
+    ("misplaced this", misplaced_this),
+    ("type predicates", type_predicates),
+    ("unknown attributes", unknown_attributes),
+    ("companions", companion::check),
+    ("type parameter lists", type_parameter_lists),
+    ("explicit any", explicit_any),
+    ("single use aliases", single_use_aliases),
+];
+
+/// The checks that need the whole module, run after [`STATEMENT_CHECKS`].
+pub const MODULE_CHECKS: &[(&str, Check)] = &[("default exports", default_exports)];
+
+/// Runs every check over `program`.
+pub fn check(program: &Ast) -> Vec<Diagnostic> {
+    STATEMENT_CHECKS
+        .iter()
+        .chain(MODULE_CHECKS)
+        .flat_map(|(_, check)| check(program))
+        .collect()
+}
+
+/// Runs the checks over the simplified program, which see the types the macros and the
+/// desugaring build as well as those written out.
+pub fn check_simplified(program: &Ast) -> Vec<Diagnostic> {
+    inconsistent_optionality(program)
+}
+
+/// Drops the diagnostics of a lint allowed by a `// allow(lint, ...)` comment on the lines
+/// directly above the line they point at, or above the statement of `program` they are in.
+pub fn apply_pragmas(
+    diagnostics: Vec<Diagnostic>,
+    file: &SourceFile,
+    program: &Ast,
+) -> Vec<Diagnostic> {
+    let statements = match program {
+        Ast::Program(Program { statements, .. }) => statements.as_slice(),
+        _ => &[],
+    };
+
+    let allows = |offset: usize, lint: &str| {
+        let (line, _) = file.line_col(offset);
+
+        (1..line)
+            .rev()
+            .map(|line| file.line(line).trim())
+            .take_while(|line| line.starts_with("//"))
+            .filter_map(|line| {
+                line.trim_start_matches('/')
+                    .trim()
+                    .strip_prefix("allow(")?
+                    .strip_suffix(')')
+            })
+            .any(|lints| lints.split(',').any(|allowed| allowed.trim() == lint))
+    };
+
+    diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            let Some(lint) = diagnostic.lint else {
+                return true;
+            };
+
+            let start = diagnostic.span.start;
+            let statement = statements
+                .iter()
+                .map(Ast::as_span)
+                .find(|span| span.start <= start && start < span.end);
+
+            !(allows(start, lint) || statement.is_some_and(|span| allows(span.start, lint)))
+        })
+        .collect()
+}
+
+/// Drops the diagnostics of allowed lints and turns those of denied lints into errors. Lints
+/// without a level keep the severity they were reported with, but for those
+/// [allowed by default](ALLOWED_BY_DEFAULT).
+pub fn apply_levels(
+    diagnostics: Vec<Diagnostic>,
+    levels: &BTreeMap<String, Level>,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+            let Some(lint) = diagnostic.lint else {
+                return Some(diagnostic);
+            };
+
+            match levels.get(lint) {
+                Some(Level::Allow) => None,
+                Some(Level::Warn) => Some(Diagnostic {
+                    severity: Severity::Warning,
+                    ..diagnostic
+                }),
+                Some(Level::Deny) => Some(
+                    Diagnostic {
+                        severity: Severity::Error,
+                        ..diagnostic
+                    }
+                    .with_note(format!("`{lint}` is set to \"deny\"")),
+                ),
+                None if ALLOWED_BY_DEFAULT.contains(&lint) => None,
+                None => Some(diagnostic),
+            }
+        })
+        .collect()
+}
+
+/// Flags declarations named after TypeScript reserved words (an error, the output would not
+/// compile) or contextual keywords (a warning).
+pub fn reserved_names(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+    let gensym = RefCell::new(Gensym::new(program));
+
+    // Interfaces are not renamed, their name has no span of its own.
+    let check_name = |name: &str, kind: &str, span: Span, rename: Rename| {
+        let reserved = is_reserved_word(name);
+
+        let diagnostic = if reserved {
+            Diagnostic::error(
+                format!("`{name}` is a reserved word in TypeScript and cannot name a {kind}"),
+                span,
+            )
+            .with_code(codes::RESERVED_NAME)
+        } else if is_contextual_keyword(name) {
+            Diagnostic::warning(
+                format!("`{name}` is a contextual keyword in TypeScript"),
+                span,
+            )
+            .with_note(format!("consider renaming this {kind}"))
+            .with_lint(CONTEXTUAL_KEYWORD)
+            .with_code(codes::CONTEXTUAL_KEYWORD)
+        } else {
+            return;
+        };
+
+        let renamable = match rename {
+            Rename::Always => true,
+            Rename::IfReserved => reserved,
+            Rename::Never => false,
+        };
+
+        let diagnostic = if renamable {
+            diagnostic.with_suggestion(Suggestion::rename(
+                span,
+                gensym.borrow_mut().alternative(name),
+            ))
+        } else {
+            diagnostic
+        };
+
+        diagnostics.borrow_mut().push(diagnostic);
+    };
+
+    let check_params = |params: &[TypeParameter]| {
+        for param in params {
+            check_name(
+                &param.name.name,
+                "type parameter",
+                param.name.span,
+                Rename::Always,
+            );
+        }
+    };
+
+    program.prewalk((), &|node, ctx| {
+        match &node {
+            Ast::TypeAlias(TypeAlias {
+                name,
+                params,
+                export,
+                ..
+            }) => {
+                check_name(
+                    &name.name,
+                    "type alias",
+                    name.span,
+                    Rename::unless_exported(*export),
+                );
+                check_params(params);
+            }
+            Ast::Interface(Interface {
+                name, params, span, ..
+            }) => {
+                check_name(name, "interface", *span, Rename::Never);
+                check_params(params);
+            }
+            Ast::UnionDeclaration(union) => {
+                let renamable = Rename::unless_exported(union.export);
+
+                check_name(&union.name.name, "union", union.name.span, renamable);
+
+                for variant in &union.variants {
+                    check_name(&variant.name.name, "variant", variant.name.span, renamable);
+                }
+            }
+            Ast::BrandDeclaration(brand) => check_name(
+                &brand.name.name,
+                "brand",
+                brand.name.span,
+                Rename::unless_exported(brand.export),
+            ),
+            // Without an alias, the name is the one the module exports, which can't be renamed.
+            Ast::ImportStatement(import) => match &import.import_clause {
+                ImportClause::Named(specifiers) => {
+                    for specifier in specifiers {
+                        match &specifier.alias {
+                            Some(alias) => {
+                                check_name(&alias.name, "named import", alias.span, Rename::Always)
+                            }
+                            None => check_name(
+                                &specifier.module_export_name.name,
+                                "named import",
+                                specifier.module_export_name.span,
+                                Rename::Never,
+                            ),
+                        }
+                    }
+                }
+                ImportClause::Namespace { alias } => {
+                    check_name(&alias.name, "namespace import", alias.span, Rename::Always)
+                }
+            },
+            _ => {}
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// When a suggestion renames a declaration named after a keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rename {
+    Always,
+    /// Only if the name is reserved. Renaming an exported declaration changes the exports of the
+    /// module, which is only safe when they would not compile anyway.
+    IfReserved,
+    Never,
+}
+
+impl Rename {
+    fn unless_exported(export: bool) -> Self {
+        if export {
+            Rename::IfReserved
+        } else {
+            Rename::Always
+        }
+    }
+}
+
+/// Warns about two names declared in the same scope that look alike, that is whose confusable
+/// skeletons (as defined by Unicode TR39) are equal. Names are NFC normalized by the parser, so
+/// different encodings of the same name are the same name rather than a confusable pair.
+pub fn confusable_names(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    let check_scope = |names: Vec<(&str, Span)>| {
+        let mut seen: Vec<(&str, String)> = vec![];
+
+        for (name, span) in names {
+            let name_skeleton: String = skeleton(name).collect();
+
+            if let Some((other, _)) = seen
+                .iter()
+                .find(|(other, other_skeleton)| *other != name && *other_skeleton == name_skeleton)
+            {
+                diagnostics.borrow_mut().push(
+                    Diagnostic::warning(
+                        format!("`{name}` is easily confused with `{other}`"),
+                        span,
+                    )
+                    .with_note(format!("`{other}` is declared in the same scope"))
+                    .with_lint(CONFUSABLE_NAMES)
+                    .with_code(codes::CONFUSABLE_NAMES),
+                );
+            }
+
+            seen.push((name, name_skeleton));
+        }
+    };
+
+    program.prewalk((), &|node, ctx| {
+        match &node {
+            Ast::Program(Program { statements, .. }) => {
+                let declarations = statements.iter().filter_map(|statement| {
+                    let declaration = match statement {
+                        Ast::Statement(inner) => inner.as_ref(),
+                        other => other,
+                    };
+
+                    match declaration {
+                        Ast::TypeAlias(alias) => {
+                            Some(vec![(alias.name.name.as_str(), alias.name.span)])
+                        }
+                        Ast::Interface(interface) => {
+                            Some(vec![(interface.name.as_str(), interface.span)])
+                        }
+                        // Every variant declares an alias of its own.
+                        Ast::UnionDeclaration(union) => Some(
+                            std::iter::once(&union.name)
+                                .chain(union.variants.iter().map(|variant| &variant.name))
+                                .map(|name| (name.name.as_str(), name.span))
+                                .collect(),
+                        ),
+                        Ast::BrandDeclaration(brand) => {
+                            Some(vec![(brand.name.name.as_str(), brand.name.span)])
+                        }
+                        _ => None,
+                    }
+                });
+
+                check_scope(declarations.flatten().collect());
+            }
+            Ast::TypeAlias(alias) => check_scope(param_names(&alias.params)),
+            Ast::Interface(interface) => check_scope(param_names(&interface.params)),
+            Ast::LetExpr(LetExpr { bindings, .. }) => check_scope(
+                bindings
+                    .iter()
+                    .map(|binding| (binding.name.name.as_str(), binding.name.span))
+                    .collect(),
+            ),
+            _ => {}
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Rejects calls to the builtin macros with the wrong number of arguments, and calls to `merge!`
+/// that pass a literal other than an object.
+pub fn macro_arguments(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        if let Ast::MacroCall(MacroCall { name, args, span }) = &node {
+            let mut diagnostics = diagnostics.borrow_mut();
+
+            let arity = match name.as_str() {
+                "merge!" | "index_by!" => Some(2),
+                "deep_readonly!" | "deep_partial!" | "mutable!" | "keys_of!" | "values_of!"
+                | "all!" | "any!" => Some(1),
+                _ => None,
+            };
+
+            if let Some(arity) = arity.filter(|arity| *arity != args.len()) {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "`{name}` expects {arity} argument{}, found {}",
+                            if arity == 1 { "" } else { "s" },
+                            args.len()
+                        ),
+                        *span,
+                    )
+                    .with_code(codes::INVALID_MACRO_ARGUMENT),
+                );
+            }
+
+            if name == "merge!" {
+                for arg in args {
+                    if let Some(kind) = non_object_literal(arg) {
+                        diagnostics.push(
+                            Diagnostic::error(
+                                format!("`merge!` expects object types, found {kind}"),
+                                arg.as_span(),
+                            )
+                            .with_code(codes::INVALID_MACRO_ARGUMENT),
+                        );
+                    }
+                }
+            }
+
+            // The tree walk doesn't enter the arguments of macros.
+            for arg in args {
+                diagnostics.extend(macro_arguments(arg));
+            }
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Warns about a `match` without an `else` arm whose patterns are variants of a union declared in
+/// the program, but not all of them, or that matches a type parameter constrained to a union of
+/// literals against some of them only. The match is `never` for the types left out.
+pub fn non_exhaustive_matches(program: &Ast) -> Vec<Diagnostic> {
+    let mut diagnostics = union_matches(program);
+    diagnostics.extend(constrained_matches(program));
+    diagnostics.extend(pattern_arities(program));
+    diagnostics
+}
+
+fn union_matches(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    let unions: Vec<_> = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::UnionDeclaration(union) => Some(union),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    let diagnostics = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        if let Ast::MatchExpr(MatchExpr {
+            arms,
+            else_arm,
+            span,
+            ..
+        }) = &node
+        {
+            let patterns: Vec<_> = arms
+                .iter()
+                .filter_map(|arm| arm.pattern.as_ident().map(|ident| ident.name.as_str()))
+                .collect();
+
+            let union = unions.iter().find(|union| {
+                union
+                    .variant_names()
+                    .any(|variant| patterns.contains(&variant))
+            });
+
+            if let (Some(union), Ast::NeverKeyword(_)) = (union, else_arm.as_ref()) {
+                let missing: Vec<_> = union
+                    .variant_names()
+                    .filter(|variant| !patterns.contains(variant))
+                    .map(|variant| format!("`{variant}`"))
+                    .collect();
+
+                if !missing.is_empty() && !patterns.contains(&union.name.name.as_str()) {
+                    diagnostics.borrow_mut().push(
+                        Diagnostic::warning(
+                            format!(
+                                "`match` doesn't cover every variant of `{}`",
+                                union.name.name
+                            ),
+                            *span,
+                        )
+                        .with_note(format!("{} not matched", missing.join(", ")))
+                        .with_lint(NON_EXHAUSTIVE_MATCH)
+                        .with_code(codes::NON_EXHAUSTIVE_MATCH),
+                    );
+                }
+            }
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Most combinations of the members of the values of a `match` on several values checked, past
+/// which the match isn't checked.
+const MAX_COMBINATIONS: usize = 256;
+
+/// Matches without an `else` arm over a type parameter constrained to a union of literals, or
+/// over several of them, whose patterns leave some of the members, or of their combinations, out.
+///
+/// A match on a parameter distributes over the union it is instantiated with, one member at a
+/// time, but a match on several is a tuple, `[T, U] extends [P, Q]`, which doesn't: a union
+/// argument only matches an arm covering all of its members. So unless one arm covers every
+/// combination, instantiating the parameters with their whole constraints falls through.
+fn constrained_matches(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    closed_matches(program, &|expr, subject, domain| {
+        let Ast::NeverKeyword(_) = expr.else_arm.as_ref() else {
+            return;
+        };
+
+        let patterns: Vec<_> = expr.arms.iter().map(|arm| &arm.pattern).collect();
+
+        let Some(missing) = uncovered(&patterns, domain) else {
+            return;
+        };
+
+        let covers_all = |pattern: &&Ast| {
+            domain
+                .iter()
+                .all(|member| covers(pattern, member) == Some(true))
+        };
+
+        if !missing.is_empty() {
+            diagnostics.borrow_mut().push(
+                Diagnostic::warning(format!("`match` doesn't cover {subject}"), expr.span)
+                    .with_note(format!("{} not matched", missing.join(", ")))
+                    .with_lint(NON_EXHAUSTIVE_MATCH)
+                    .with_code(codes::NON_EXHAUSTIVE_MATCH),
+            );
+        } else if let Ast::Tuple(Tuple { items, span }) = expr.value.as_ref() {
+            if patterns.iter().any(covers_all) {
+                return;
+            }
+
+            let unions = (0..items.len())
+                .map(|i| {
+                    let mut members: Vec<Ast> = vec![];
+
+                    for combination in domain {
+                        let Ast::Tuple(combination) = combination else {
+                            continue;
+                        };
+
+                        if !members.contains(&combination.items[i]) {
+                            members.push(combination.items[i].clone());
+                        }
+                    }
+
+                    build::union(members)
+                })
+                .collect();
+
+            diagnostics.borrow_mut().push(
+                Diagnostic::warning(
+                    format!(
+                        "`match` doesn't cover the unions of {}",
+                        subject.trim_start_matches("every combination of ")
+                    ),
+                    expr.span,
+                )
+                .with_note(format!(
+                    "`{}` not matched",
+                    Ast::Tuple(Tuple {
+                        items: unions,
+                        span: *span
+                    })
+                ))
+                .with_note(
+                    "a match on several values doesn't distribute over unions, no arm covers \
+                     every combination",
+                )
+                .with_lint(NON_EXHAUSTIVE_MATCH)
+                .with_code(codes::NON_EXHAUSTIVE_MATCH),
+            );
+        }
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Arms of a match on a tuple whose pattern has as many values as the tuple doesn't, which never
+/// match: `[A, B]` only extends tuples of 2. Those of a match on several values, `match A, B`, are
+/// syntax errors.
+fn pattern_arities(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        if let Ast::MatchExpr(MatchExpr { value, arms, .. }) = &node {
+            if let Ast::Tuple(Tuple { items: values, .. }) = value.as_ref() {
+                for arm in arms {
+                    let Ast::Tuple(Tuple { items, span }) = &arm.pattern else {
+                        continue;
+                    };
+
+                    if items.len() != values.len() {
+                        diagnostics.borrow_mut().push(
+                            Diagnostic::warning(
+                                format!(
+                                    "pattern of {} value{} in a `match` on {}",
+                                    items.len(),
+                                    if items.len() == 1 { "" } else { "s" },
+                                    values.len()
+                                ),
+                                *span,
+                            )
+                            .with_note("the arm never matches")
+                            .with_lint(NON_EXHAUSTIVE_MATCH)
+                            .with_code(codes::NON_EXHAUSTIVE_MATCH),
+                        );
+                    }
+                }
+            }
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Errors for the matches over closed domains, as checked by [`constrained_matches`], that leave
+/// members to an `else` or `_` arm, for `--strict`. The patterns must name every member, so that
+/// a member added to the constraint is reported rather than silently falling to the wildcard. A
+/// `_` within a tuple pattern is allowed, it stands for one of the values only.
+pub fn wildcard_matches(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    closed_matches(program, &|expr, subject, domain| {
+        let patterns: Vec<_> = expr
+            .arms
+            .iter()
+            .map(|arm| &arm.pattern)
+            .filter(|pattern| !matches!(pattern, Ast::UnknownKeyword(_)))
+            .collect();
+
+        let has_wildcard = patterns.len() < expr.arms.len()
+            || !matches!(expr.else_arm.as_ref(), Ast::NeverKeyword(_));
+
+        if !has_wildcard {
+            return;
+        }
+
+        let Some(missing) = uncovered(&patterns, domain) else {
+            return;
+        };
+
+        if !missing.is_empty() {
+            diagnostics.borrow_mut().push(
+                Diagnostic::error(
+                    format!("`match` leaves a wildcard to cover {subject}"),
+                    expr.span,
+                )
+                .with_note(format!("{} not matched by a pattern", missing.join(", ")))
+                .with_note(
+                    "`--strict` requires the patterns of a match over a closed domain to cover it",
+                )
+                .with_code(codes::NON_EXHAUSTIVE_MATCH),
+            );
+        }
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Calls `f` with each match of `program` over a constrained type parameter, or several, with
+/// what it is checked to cover and the members or their combinations, see [`matched_domain`].
+fn closed_matches(program: &Ast, f: &dyn Fn(&MatchExpr, &str, &[Ast])) {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return;
+    };
+
+    for statement in statements {
+        let Ast::Statement(inner) = statement else {
+            continue;
+        };
+
+        let domains = closed_domains(inner);
+
+        if domains.is_empty() {
+            continue;
+        }
+
+        inner.prewalk((), &|node, ctx| {
+            if let Ast::MatchExpr(expr) = &node {
+                if let Some((subject, domain)) = matched_domain(&expr.value, &domains) {
+                    f(expr, &subject, &domain);
+                }
+            }
+
+            (node, ctx)
+        });
+    }
+}
+
+/// The members of `domain` none of `patterns` covers, `None` when a pattern that isn't a literal
+/// or a primitive may match anything.
+fn uncovered(patterns: &[&Ast], domain: &[Ast]) -> Option<Vec<String>> {
+    let coverage: Vec<Vec<bool>> = patterns
+        .iter()
+        .map(|pattern| {
+            domain
+                .iter()
+                .map(|member| covers(pattern, member))
+                .collect()
+        })
+        .collect::<Option<_>>()?;
+
+    Some(
+        domain
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !coverage.iter().any(|covered| covered[*i]))
+            .map(|(_, member)| format!("`{member}`"))
+            .collect(),
+    )
+}
+
+/// What a match on `value` is checked to cover, for a constrained type parameter or a tuple of
+/// them, `match A, B do`, and the members of their constraints or their combinations.
+fn matched_domain(value: &Ast, domains: &HashMap<String, Vec<Ast>>) -> Option<(String, Vec<Ast>)> {
+    match value {
+        Ast::Ident(param) => {
+            let domain = domains.get(param.name.as_str())?;
+            Some((
+                format!("every member of the constraint of `{}`", param.name),
+                domain.clone(),
+            ))
+        }
+        Ast::Tuple(Tuple { items, span }) => {
+            let mut names = vec![];
+            let mut combinations: Vec<Vec<Ast>> = vec![vec![]];
+
+            for item in items {
+                let domain = domains.get(item.as_ident()?.name.as_str())?;
+
+                if combinations.len() * domain.len() > MAX_COMBINATIONS {
+                    return None;
+                }
+
+                names.push(format!("`{item}`"));
+                combinations = combinations
+                    .into_iter()
+                    .flat_map(|combination| {
+                        domain.iter().map(move |member| {
+                            let mut combination = combination.clone();
+                            combination.push(member.clone());
+                            combination
+                        })
+                    })
+                    .collect();
+            }
+
+            Some((
+                format!(
+                    "every combination of the constraints of {}",
+                    names.join(", ")
+                ),
+                combinations
+                    .into_iter()
+                    .map(|items| Ast::Tuple(Tuple { items, span: *span }))
+                    .collect(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `pattern` always matches `member`, a literal or a tuple of them, `None` when it
+/// depends on more than the literals.
+fn covers(pattern: &Ast, member: &Ast) -> Option<bool> {
+    match (pattern, member) {
+        (Ast::UnknownKeyword(_), _) => Some(true),
+        (Ast::Tuple(patterns), Ast::Tuple(members)) => {
+            if patterns.items.len() != members.items.len() {
+                return Some(false);
+            }
+
+            patterns
+                .items
+                .iter()
+                .zip(&members.items)
+                .try_fold(true, |all, (pattern, member)| {
+                    Some(covers(pattern, member)? && all)
+                })
+        }
+        _ => always_extends(member, pattern),
+    }
+}
+
+/// The literals each type parameter of an alias or interface constrained to a union of them can
+/// be, e.g. `"a"` and `"b"` for `T <: "a" | "b"`, by name. Other parameters are open, as are
+/// those bound again in the declaration.
+fn closed_domains(declaration: &Ast) -> HashMap<String, Vec<Ast>> {
+    fn members(node: &Ast, out: &mut Vec<Ast>) -> bool {
+        match node {
+            Ast::UnionType(union) => union.types.iter().all(|ty| members(ty, out)),
+            Ast::TypeString(_)
+            | Ast::TypeNumber(_)
+            | Ast::TrueKeyword(_)
+            | Ast::FalseKeyword(_) => {
+                out.push(node.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[derive(Default)]
+    struct Rebound(Vec<String>);
+
+    impl<'a> Visit<'a> for Rebound {
+        fn visit_infer(&mut self, binding: &'a Ast) {
+            if let Ast::Ident(ident) = binding {
+                self.0.push(ident.name.clone());
+            }
+        }
+
+        fn visit_let_expr(&mut self, expr: &'a LetExpr) {
+            self.0.extend(
+                expr.bindings
+                    .iter()
+                    .map(|binding| binding.name.name.clone()),
+            );
+            walk_let_expr(self, expr);
+        }
+    }
+
+    let params = match declaration {
+        Ast::TypeAlias(alias) => &alias.params,
+        Ast::Interface(interface) => &interface.params,
+        _ => return HashMap::new(),
+    };
+
+    let mut rebound = Rebound::default();
+    rebound.visit_ast(declaration);
+
+    params
+        .iter()
+        .filter(|param| !rebound.0.contains(&param.name.name))
+        .filter_map(|param| {
+            let mut domain = vec![];
+            members(param.constraint.as_ref()?, &mut domain)
+                .then(|| (param.name.name.clone(), domain))
+        })
+        .collect()
+}
+
+/// Warns about the conditions written over literals and primitives only, such as `:a <: string`,
+/// that always hold or never do, leaving a branch that is never taken. Conditions mentioning a
+/// name or inferring a type depend on what they are applied to and are left alone.
+///
+/// A pattern matched against a type parameter constrained to a union of literals is checked
+/// against each of them, it never matches when none of them extends it.
+pub fn constant_conditions(program: &Ast) -> Vec<Diagnostic> {
+    let mut conditions = ConstantConditions::default();
+    conditions.visit_ast(program);
+    conditions.diagnostics
+}
+
+#[derive(Default)]
+struct ConstantConditions {
+    diagnostics: Vec<Diagnostic>,
+    /// See [`closed_domains`], for the declaration being visited.
+    domains: HashMap<String, Vec<Ast>>,
+}
+
+impl ConstantConditions {
+    fn report(&mut self, message: String, dead: &str, span: Span) {
+        self.diagnostics.push(
+            Diagnostic::warning(message, span)
+                .with_note(format!("{dead} never taken"))
+                .with_lint(CONSTANT_CONDITION)
+                .with_code(codes::CONSTANT_CONDITION),
+        );
+    }
+}
+
+/// Whether a written condition always holds, when it is a single comparison of closed types.
+fn constant_condition(condition: &Ast) -> Option<bool> {
+    let Ast::ExtendsInfixOp(ExtendsInfixOp { lhs, op, rhs, .. }) = condition else {
+        return None;
+    };
+
+    match op {
+        InfixOp::Extends => always_extends(lhs, rhs),
+        InfixOp::NotExtends => always_extends(lhs, rhs).map(|holds| !holds),
+        _ => None,
+    }
+}
+
+/// Whether `lhs` always extends `rhs`, when both are literals or primitives that [`Ast::is_subtype`]
+/// decides. `null`, `undefined` and `void` are left out, they are assignable to one another.
+pub(crate) fn always_extends(lhs: &Ast, rhs: &Ast) -> Option<bool> {
+    let closed = |node: &Ast| match node {
+        Ast::TypeString(_) | Ast::TypeNumber(_) | Ast::TrueKeyword(_) | Ast::FalseKeyword(_) => {
+            true
+        }
+        Ast::Primitive(primitive, _) => !matches!(
+            primitive,
+            PrimitiveType::Null
+                | PrimitiveType::Undefined
+                | PrimitiveType::Void
+                | PrimitiveType::UniqueSymbol
+        ),
+        _ => false,
+    };
+
+    if !closed(lhs) || !closed(rhs) {
+        return None;
+    }
+
+    match lhs.is_subtype(rhs) {
+        ExtendsResult::True => Some(true),
+        ExtendsResult::False => Some(false),
+        ExtendsResult::Never | ExtendsResult::Both => None,
+    }
+}
+
+impl<'a> Visit<'a> for ConstantConditions {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        if let Ast::TypeAlias(_) | Ast::Interface(_) = node {
+            self.domains = closed_domains(node);
+            walk_ast(self, node);
+            self.domains.clear();
+        } else {
+            walk_ast(self, node);
+        }
+    }
+
+    fn visit_if_expr(&mut self, expr: &'a IfExpr) {
+        match constant_condition(&expr.condition) {
+            Some(true) if expr.else_branch.is_some() || expr.pass => self.report(
+                format!("`{}` is always true", expr.condition),
+                "the `else` branch is",
+                expr.condition.as_span(),
+            ),
+            Some(false) => self.report(
+                format!("`{}` is always false", expr.condition),
+                "the `then` branch is",
+                expr.condition.as_span(),
+            ),
+            _ => {}
+        }
+
+        walk_if_expr(self, expr);
+    }
+
+    fn visit_cond_expr(&mut self, cond: &'a CondExpr) {
+        for arm in &cond.arms {
+            match constant_condition(&arm.condition) {
+                Some(true) => self.report(
+                    format!("`{}` is always true", arm.condition),
+                    "the arms after it are",
+                    arm.condition.as_span(),
+                ),
+                Some(false) => self.report(
+                    format!("`{}` is always false", arm.condition),
+                    "its arm is",
+                    arm.condition.as_span(),
+                ),
+                None => {}
+            }
+        }
+
+        walk_cond_expr(self, cond);
+    }
+
+    fn visit_match_expr(&mut self, expr: &'a MatchExpr) {
+        let domain = match expr.value.as_ref() {
+            Ast::Ident(param) => self.domains.get(&param.name).cloned(),
+            _ => None,
+        };
+
+        if let Some(domain) = domain {
+            for arm in &expr.arms {
+                let never = domain
+                    .iter()
+                    .all(|member| always_extends(member, &arm.pattern) == Some(false));
+
+                if never {
+                    self.diagnostics.push(
+                        Diagnostic::warning(
+                            format!("`{}` never matches `{}`", expr.value, arm.pattern),
+                            arm.pattern.as_span(),
+                        )
+                        .with_note(format!(
+                            "`{}` is constrained to `{}`",
+                            expr.value,
+                            domain.iter().join(" | ")
+                        ))
+                        .with_note("its arm is never taken")
+                        .with_lint(CONSTANT_CONDITION)
+                        .with_code(codes::CONSTANT_CONDITION),
+                    );
+                }
+            }
+        }
+
+        for arm in &expr.arms {
+            match always_extends(&expr.value, &arm.pattern) {
+                Some(true) => self.report(
+                    format!("`{}` always matches `{}`", expr.value, arm.pattern),
+                    "the arms after it are",
+                    arm.pattern.as_span(),
+                ),
+                Some(false) => self.report(
+                    format!("`{}` never matches `{}`", expr.value, arm.pattern),
+                    "its arm is",
+                    arm.pattern.as_span(),
+                ),
+                None => {}
+            }
+        }
+
+        walk_match_expr(self, expr);
+    }
+}
+
+/// Type parameters of the aliases and interfaces in scope of a program, to check the applications
+/// of them. Names qualified by a namespace import or an import type resolve to the exports of
+/// the imported module when it is known, which is only the case for the modules of a project.
+#[derive(Debug, Clone, Default)]
+pub struct Signatures<'a> {
+    declared: HashMap<&'a str, &'a [TypeParameter]>,
+    /// Exports of the modules imported with `import * as NS`, by namespace.
+    namespaces: HashMap<&'a str, Signatures<'a>>,
+    /// Exports of the modules of `import("./m")` types, by specifier.
+    modules: HashMap<&'a str, Signatures<'a>>,
+}
+
+impl<'a> Signatures<'a> {
+    /// The declarations of `program`.
+    pub fn new(program: &'a Ast) -> Self {
+        Self::declared_by(program, false)
+    }
+
+    /// The exported declarations of `program`, seen from the modules importing it.
+    pub fn exports(program: &'a Ast) -> Self {
+        Self::declared_by(program, true)
+    }
+
+    fn declared_by(program: &'a Ast, exported_only: bool) -> Self {
+        let Ast::Program(Program { statements, .. }) = program else {
+            return Self::default();
+        };
+
+        Self {
+            declared: Self::declarations(statements, exported_only).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// The declarations in scope of a `declare module` block, its own shadowing those of the
+    /// program.
+    pub fn within(&self, module: &'a AmbientModule) -> Self {
+        let mut signatures = self.clone();
+        signatures
+            .declared
+            .extend(Self::declarations(&module.body, false));
+        signatures
+    }
+
+    fn declarations(
+        statements: &'a [Ast],
+        exported_only: bool,
+    ) -> impl Iterator<Item = (&'a str, &'a [TypeParameter])> {
+        statements
+            .iter()
+            .filter_map(move |statement| match statement {
+                Ast::Statement(inner) => match inner.as_ref() {
+                    Ast::TypeAlias(TypeAlias {
+                        name,
+                        params,
+                        export,
+                        ..
+                    }) if *export || !exported_only => {
+                        Some((name.name.as_str(), params.as_slice()))
+                    }
+                    Ast::Interface(Interface {
+                        name,
+                        params,
+                        export,
+                        ..
+                    }) if *export || !exported_only => Some((name.as_str(), params.as_slice())),
+                    _ => None,
+                },
+                _ => None,
+            })
+    }
+
+    /// Resolves `NS::Name` to the exports of `module`.
+    pub fn with_namespace(mut self, namespace: &'a str, module: Signatures<'a>) -> Self {
+        self.namespaces.insert(namespace, module);
+        self
+    }
+
+    /// Resolves `import(specifier)::Name` to the exports of `module`.
+    pub fn with_module(mut self, specifier: &'a str, module: Signatures<'a>) -> Self {
+        self.modules.insert(specifier, module);
+        self
+    }
+
+    /// The type parameters of the declaration `callee` names, if it is known.
+    pub fn resolve(&self, callee: &Ast) -> Option<&'a [TypeParameter]> {
+        match callee {
+            Ast::Ident(ident) => self.declared.get(ident.name.as_str()).copied(),
+            Ast::Access(Access {
+                lhs,
+                rhs,
+                kind: AccessKind::Namespace,
+                ..
+            }) => {
+                let module = match lhs.as_ref() {
+                    Ast::Ident(namespace) => self.namespaces.get(namespace.name.as_str())?,
+                    Ast::ImportType(import) => self.modules.get(import.module.as_str())?,
+                    _ => return None,
+                };
+
+                let name = rhs.as_ident()?;
+
+                module.declared.get(name.name.as_str()).copied()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Runs the checks of the applications of generic types in `program` whose callee resolves
+/// through `signatures`. The statements of a `declare module` block are checked in the scope of
+/// the block, see [`Signatures::within`].
+pub fn applications<'a>(program: &'a Ast, signatures: &Signatures<'a>) -> Vec<Diagnostic> {
+    let check = |node: &Ast, signatures: &Signatures| {
+        let mut diagnostics = type_argument_counts(node, signatures);
+        diagnostics.extend(unsatisfied_constraints(node, signatures));
+        diagnostics
+    };
+
+    let Ast::Program(Program { statements, span }) = program else {
+        return check(program, signatures);
+    };
+
+    let (modules, statements): (Vec<_>, Vec<_>) =
+        statements
+            .iter()
+            .partition_map(|statement| match statement {
+                Ast::Statement(inner) => match inner.as_ref() {
+                    Ast::AmbientModule(module) => Either::Left(module),
+                    _ => Either::Right(statement.clone()),
+                },
+                _ => Either::Right(statement.clone()),
+            });
+
+    let mut diagnostics = check(
+        &Ast::Program(Program {
+            statements,
+            span: *span,
+        }),
+        signatures,
+    );
+
+    for module in modules {
+        let body = Ast::Program(Program {
+            statements: module.body.clone(),
+            span: module.span,
+        });
+
+        diagnostics.extend(check(&body, &signatures.within(module)));
+    }
+
+    diagnostics
+}
+
+/// Rejects applications of an alias or interface to fewer arguments than it has parameters without
+/// a default, or to more than it has parameters.
+pub fn type_argument_counts(program: &Ast, signatures: &Signatures) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        let Ast::ApplyGeneric(ApplyGeneric {
+            receiver,
+            args,
+            span,
+        }) = &node
+        else {
+            return (node, ctx);
+        };
+
+        let Some(params) = signatures.resolve(receiver) else {
+            return (node, ctx);
+        };
+
+        let min = params
+            .iter()
+            .filter(|param| param.default.is_none() && !param.rest)
+            .count();
+        let max = if params.iter().any(|param| param.rest) {
+            usize::MAX
+        } else {
+            params.len()
+        };
+
+        if !(min..=max).contains(&args.len()) {
+            let expected = match (min, max) {
+                (min, usize::MAX) => format!("at least {min}"),
+                (min, max) if min == max => min.to_string(),
+                (min, max) => format!("{min} to {max}"),
+            };
+
+            diagnostics.borrow_mut().push(
+                Diagnostic::error(
+                    format!(
+                        "`{}` expects {expected} type argument{}, found {}",
+                        receiver,
+                        if max == 1 || (min, max) == (1, usize::MAX) {
+                            ""
+                        } else {
+                            "s"
+                        },
+                        args.len()
+                    ),
+                    *span,
+                )
+                .with_code(codes::INVALID_TYPE_ARGUMENTS),
+            );
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Rejects literal arguments of an alias or interface that are not keys of the object their
+/// parameter is constrained to, `Get({a: 1}, :b)` with `K <: keyof(T)`.
+///
+/// A constraint can mention the parameters before its own, which stand for their arguments.
+pub fn unsatisfied_constraints(program: &Ast, signatures: &Signatures) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        let Ast::ApplyGeneric(ApplyGeneric { receiver, args, .. }) = &node else {
+            return (node, ctx);
+        };
+
+        let Some(params) = signatures.resolve(receiver) else {
+            return (node, ctx);
+        };
+
+        for (i, (param, arg)) in params.iter().zip(args).enumerate() {
+            let Some(constraint) = &param.constraint else {
+                continue;
+            };
+
+            if !matches!(arg, Ast::TypeString(_) | Ast::TypeNumber(_)) {
+                continue;
+            }
+
+            // The parameters before this one are in scope of its constraint.
+            let preceding: Bindings = params[..i]
+                .iter()
+                .zip(args)
+                .map(|(param, arg)| (param.name.clone(), arg.clone()))
+                .collect();
+
+            let (constraint, _) = constraint.prewalk((), &|node, ctx| match &node {
+                Ast::Ident(ident) => (preceding.get(ident).unwrap_or(&node).clone(), ctx),
+                _ => (node, ctx),
+            });
+
+            let Ast::Builtin(Builtin {
+                name: BuiltinKeyword::Keyof,
+                argument,
+                ..
+            }) = &constraint
+            else {
+                continue;
+            };
+
+            let Some(keys) = argument.literal_keys() else {
+                continue;
+            };
+
+            if arg.is_subtype(&constraint) == ExtendsResult::False {
+                diagnostics.borrow_mut().push(
+                    Diagnostic::error(
+                        format!(
+                            "`{}` doesn't satisfy the constraint `{}` of `{}`",
+                            arg, constraint, param.name
+                        ),
+                        arg.as_span(),
+                    )
+                    .with_note(if keys.is_empty() {
+                        format!("`{argument}` has no keys")
+                    } else {
+                        format!(
+                            "the keys are {}",
+                            keys.iter().map(|key| format!("`{key:?}`")).join(", ")
+                        )
+                    })
+                    .with_code(codes::UNSATISFIED_CONSTRAINT),
+                );
+            }
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+/// Warns about declarations named after a type of the TypeScript standard library, such as
+/// `Record`, which the emitted declaration shadows in its module. Those declared `global` don't
+/// shadow it, they are in its scope: `global interface Array(T)` augments the global type.
+pub fn shadowed_globals(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    let mut diagnostics = vec![];
+    let mut gensym = Gensym::new(program);
+
+    // Only declarations that are not exported are renamed, the exports of the module are kept.
+    let mut check_name = |name: &str, kind: &str, span: Span, renamable: bool| {
+        if lib_globals::LIB_GLOBALS.binary_search(&name).is_ok() {
+            let diagnostic = Diagnostic::warning(
+                format!("`{name}` shadows the global TypeScript type of the same name"),
+                span,
+            )
+            .with_note(format!(
+                "consider renaming this {kind}, or add `// allow({SHADOWED_GLOBAL})` above \
+                     it if the global is not needed in this module"
+            ))
+            .with_lint(SHADOWED_GLOBAL)
+            .with_code(codes::SHADOWED_GLOBAL);
+
+            diagnostics.push(if renamable {
+                diagnostic.with_suggestion(Suggestion::rename(span, gensym.alternative(name)))
+            } else {
+                diagnostic
+            });
+        }
+    };
+
+    for statement in statements {
+        let Ast::Statement(inner) = statement else {
+            continue;
+        };
+
+        match inner.as_ref() {
+            Ast::TypeAlias(TypeAlias {
+                name,
+                export,
+                global: false,
+                ..
+            }) => check_name(&name.name, "type alias", name.span, !export),
+            Ast::Interface(Interface {
+                name,
+                span,
+                global: false,
+                ..
+            }) => check_name(name, "interface", *span, false),
+            Ast::UnionDeclaration(union) => {
+                check_name(&union.name.name, "union", union.name.span, !union.export);
+
+                for variant in &union.variants {
+                    check_name(
+                        &variant.name.name,
+                        "variant",
+                        variant.name.span,
+                        !union.export,
+                    );
+                }
+            }
+            Ast::BrandDeclaration(brand) => {
+                check_name(&brand.name.name, "brand", brand.name.span, !brand.export)
+            }
+            _ => {}
+        }
+    }
+
+    diagnostics
+}
+
+/// Rejects a second default export in a module, and `export default` of a name the module doesn't
+/// declare or import.
+pub fn default_exports(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    let statements: Vec<&Ast> = statements
+        .iter()
+        .map(|statement| match statement {
+            Ast::Statement(inner) => inner.as_ref(),
+            other => other,
+        })
+        .collect();
+
+    let declared: Vec<&str> = statements
+        .iter()
+        .flat_map(|statement| match statement {
+            Ast::TypeAlias(alias) => vec![alias.name.name.as_str()],
+            Ast::Interface(interface) => vec![interface.name.as_str()],
+            Ast::UnionDeclaration(union) => std::iter::once(&union.name)
+                .chain(union.variants.iter().map(|variant| &variant.name))
+                .map(|name| name.name.as_str())
+                .collect(),
+            Ast::BrandDeclaration(brand) => vec![brand.name.name.as_str()],
+            Ast::ImportStatement(import) => match &import.import_clause {
+                ImportClause::Named(specifiers) => specifiers
+                    .iter()
+                    .map(|specifier| {
+                        specifier
+                            .alias
+                            .as_ref()
+                            .unwrap_or(&specifier.module_export_name)
+                            .name
+                            .as_str()
+                    })
+                    .collect(),
+                ImportClause::Namespace { alias } => vec![alias.name.as_str()],
+            },
+            _ => vec![],
+        })
+        .collect();
+
+    let mut diagnostics = vec![];
+    let mut first: Option<&str> = None;
+
+    for statement in statements {
+        let (name, span) = match statement {
+            Ast::TypeAlias(TypeAlias {
+                default: true,
+                name,
+                ..
+            }) => (name.name.as_str(), name.span),
+            Ast::Interface(Interface {
+                default: true,
+                name,
+                span,
+                ..
+            }) => (name.as_str(), *span),
+            Ast::ExportDefault(ExportDefault { name, span }) => {
+                if !declared.contains(&name.name.as_str()) {
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!("`{}` is not declared in this module", name.name),
+                            name.span,
+                        )
+                        .with_code(codes::INVALID_DEFAULT_EXPORT),
+                    );
+                }
+
+                (name.name.as_str(), *span)
+            }
+            _ => continue,
+        };
+
+        match first {
+            Some(first) => diagnostics.push(
+                Diagnostic::error("a module has only one default export", span)
+                    .with_note(format!("`{first}` is already the default export"))
+                    .with_code(codes::INVALID_DEFAULT_EXPORT),
+            ),
+            None => first = Some(name),
+        }
+    }
+
+    diagnostics
+}
+
+/// Flags `this` types outside of the methods of an interface or an object type (an error,
+/// TypeScript has no object for them to stand for).
+pub fn misplaced_this(program: &Ast) -> Vec<Diagnostic> {
+    let mut visitor = ThisTypes::default();
+    visitor.visit_ast(program);
+    visitor.diagnostics
+}
+
+/// Flags the type predicates of function types naming none of their parameters (an error, there
+/// is nothing for them to narrow).
+pub fn type_predicates(program: &Ast) -> Vec<Diagnostic> {
+    #[derive(Default)]
+    struct Predicates(Vec<Diagnostic>);
+
+    impl<'a> Visit<'a> for Predicates {
+        fn visit_function_type(&mut self, function: &'a FunctionType) {
+            if let Some(predicate) = &function.predicate {
+                let name = &predicate.param.name;
+
+                if !function.params.iter().any(|param| &param.name == name) {
+                    let note = if function.params.is_empty() {
+                        "the function has no parameters".to_string()
+                    } else {
+                        format!(
+                            "its parameters are {}",
+                            function
+                                .params
+                                .iter()
+                                .map(|param| format!("`{}`", param.name))
+                                .join(", ")
+                        )
+                    };
+
+                    self.0.push(
+                        Diagnostic::error(
+                            format!("`{name}` is not a parameter of the function"),
+                            predicate.param.span,
+                        )
+                        .with_note(note)
+                        .with_code(codes::INVALID_TYPE_PREDICATE),
+                    );
+                }
+            }
+
+            walk_function_type(self, function);
+        }
+    }
+
+    let mut predicates = Predicates::default();
+    predicates.visit_ast(program);
+    predicates.0
+}
+
+/// Flags the attributes no pass claims, see [`attribute::ATTRIBUTES`], which would be ignored.
+pub fn unknown_attributes(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .flat_map(Ast::attributes)
+        .filter(|attribute| !attribute::ATTRIBUTES.contains(&attribute.name.name.as_str()))
+        .map(|attribute| {
+            Diagnostic::warning(
+                format!("unknown attribute `@{}`", attribute.name.name),
+                attribute.span,
+            )
+            .with_note(format!(
+                "the known attributes are {}",
+                attribute::ATTRIBUTES
+                    .iter()
+                    .map(|name| format!("`@{name}`"))
+                    .join(", ")
+            ))
+            .with_code(codes::UNKNOWN_ATTRIBUTE)
+        })
+        .collect()
+}
+
+/// Runs [`check_type_parameters`] over the parameters of every alias and interface.
+pub fn type_parameter_lists(program: &Ast) -> Vec<Diagnostic> {
+    #[derive(Default)]
+    struct Lists(Vec<Diagnostic>);
+
+    impl<'a> Visit<'a> for Lists {
+        fn visit_type_alias(&mut self, alias: &'a TypeAlias) {
+            self.0.extend(check_type_parameters(&alias.params));
+            walk_type_alias(self, alias);
+        }
+
+        fn visit_interface(&mut self, interface: &'a Interface) {
+            self.0.extend(check_type_parameters(&interface.params));
+            walk_interface(self, interface);
+        }
+    }
+
+    let mut lists = Lists::default();
+    lists.visit_ast(program);
+    lists.0
+}
+
+/// Reports the `any` of exported declarations, written in them or through an alias of the module
+/// that isn't exported and has an `any` in its body, as `Loose` in
+///
+/// ```text
+/// type Loose as any
+/// export type Options as {extra: Loose}
+/// ```
+///
+/// Aliases are followed one level deep, and only within `program`: checked one statement at a
+/// time, the aliases of the other statements aren't known. Allowed by default.
+pub fn explicit_any(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    let declarations = statements.iter().filter_map(|statement| match statement {
+        Ast::Statement(inner) => Some(inner.as_ref()),
+        _ => None,
+    });
+
+    let loose = declarations
+        .clone()
+        .filter_map(|declaration| match declaration {
+            Ast::TypeAlias(alias)
+                if !(alias.export || alias.default)
+                    && alias.body.any(|node| matches!(node, Ast::AnyKeyword(_))) =>
+            {
+                Some(alias.name.name.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut anys = ExplicitAnys {
+        loose,
+        declaration: "",
+        params: vec![],
+        diagnostics: vec![],
+    };
+
+    for declaration in declarations {
+        match declaration {
+            Ast::TypeAlias(alias) if alias.export || alias.default => {
+                anys.enter(&alias.name.name, &alias.params);
+                walk_type_alias(&mut anys, alias);
+            }
+            Ast::Interface(interface) if interface.export || interface.default => {
+                anys.enter(&interface.name, &interface.params);
+                walk_interface(&mut anys, interface);
+            }
+            Ast::UnionDeclaration(union) if union.export => {
+                anys.enter(&union.name.name, &[]);
+                anys.visit_ast(declaration);
+            }
+            Ast::BrandDeclaration(brand) if brand.export => {
+                anys.enter(&brand.name.name, &[]);
+                anys.visit_ast(declaration);
+            }
+            _ => {}
+        }
+    }
+
+    anys.diagnostics
+}
+
+struct ExplicitAnys<'a> {
+    /// The aliases of `any`, see [`explicit_any`].
+    loose: HashSet<&'a str>,
+    /// The exported declaration being visited.
+    declaration: &'a str,
+    /// Its type parameters, which shadow the aliases.
+    params: Vec<&'a str>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> ExplicitAnys<'a> {
+    fn enter(&mut self, declaration: &'a str, params: &'a [TypeParameter]) {
+        self.declaration = declaration;
+        self.params = params
+            .iter()
+            .map(|param| param.name.name.as_str())
+            .collect();
+    }
+}
+
+impl<'a> Visit<'a> for ExplicitAnys<'a> {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        let declaration = self.declaration;
+
+        match node {
+            Ast::AnyKeyword(span) => self.diagnostics.push(
+                Diagnostic::warning(format!("`any` in the exported type `{declaration}`"), *span)
+                    .with_lint(EXPLICIT_ANY)
+                    .with_code(codes::EXPLICIT_ANY),
+            ),
+            Ast::Ident(ident)
+                if self.loose.contains(ident.name.as_str())
+                    && !self.params.contains(&ident.name.as_str()) =>
+            {
+                let name = &ident.name;
+
+                self.diagnostics.push(
+                    Diagnostic::warning(
+                        format!("`{name}` brings `any` into the exported type `{declaration}`"),
+                        ident.span,
+                    )
+                    .with_note(format!("`{name}` is declared with `any`"))
+                    .with_lint(EXPLICIT_ANY)
+                    .with_code(codes::EXPLICIT_ANY),
+                )
+            }
+            _ => walk_ast(self, node),
+        }
+    }
+}
+
+/// Generic aliases applied once in `program`, an indirection the application could do without.
+/// The suggestion inlines the application: the body of the alias with the arguments, or the
+/// defaults of the parameters left out, in place of the parameters.
+///
+/// Exported aliases may be used by other modules and are left alone, as are recursive ones.
+/// Checked one statement at a time, the uses in the other statements aren't known and nothing is
+/// reported. Allowed by default.
+pub fn single_use_aliases(program: &Ast) -> Vec<Diagnostic> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    let declarations = rewrite::declarations(program);
+    let mut references: HashMap<_, Vec<_>> = HashMap::new();
+
+    for (&reference, &declaration) in &declarations {
+        if reference != declaration {
+            references.entry(declaration).or_default().push(reference);
+        }
+    }
+
+    statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(alias)
+                    if !(alias.export || alias.default || alias.params.is_empty()) =>
+                {
+                    Some((statement.as_span(), alias))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter_map(|(statement, alias)| {
+            let name = &alias.name;
+            let [reference] = references
+                .get(&(name.span.start, name.span.end))?
+                .as_slice()
+            else {
+                return None;
+            };
+
+            if statement.start <= reference.0 && reference.1 <= statement.end {
+                return None;
+            }
+
+            let application = program.find_map(|node| match &node {
+                Ast::ApplyGeneric(ApplyGeneric { receiver, .. })
+                    if receiver.as_span().start == reference.0
+                        && receiver.as_span().end == reference.1 =>
+                {
+                    Some(node.clone())
+                }
+                _ => None,
+            })?;
+
+            let mut diagnostic =
+                Diagnostic::warning(format!("`{}` is only used once", name.name), name.span)
+                    .with_note(format!("it is applied once, as `{application}`"))
+                    .with_lint(SINGLE_USE_ALIAS)
+                    .with_code(codes::SINGLE_USE_ALIAS);
+
+            if let Some(inlined) = inline(program, alias, &application, &declarations) {
+                diagnostic = diagnostic.with_suggestion(Suggestion::replace(
+                    application.as_span(),
+                    dsl::replacing(&inlined, &application),
+                    format!("inline `{}`", name.name),
+                ));
+            }
+
+            Some(diagnostic)
+        })
+        .collect()
+}
+
+/// The body of `alias` with the arguments of `application` in place of its parameters, its names
+/// resolved by `declarations` of `program`. `None` for an application TypeScript would reject, or one of a rest
+/// parameter, when a name of an argument would be shadowed by a declaration of the body, and when
+/// a name the body refers to stands for another declaration at the application.
+fn inline(
+    program: &Ast,
+    alias: &TypeAlias,
+    application: &Ast,
+    declarations: &HashMap<(usize, usize), (usize, usize)>,
+) -> Option<Ast> {
+    let Ast::ApplyGeneric(ApplyGeneric { args, .. }) = application else {
+        return None;
+    };
+
+    if args.len() > alias.params.len() || alias.params.iter().any(|param| param.rest) {
+        return None;
+    }
+
+    let names = RefCell::new(HashSet::new());
+    for arg in args {
+        arg.prewalk((), &|node, ctx| {
+            if let Ast::Ident(ident) = &node {
+                names.borrow_mut().insert(ident.name.clone());
+            }
+            (node, ctx)
+        });
+    }
+    let names = names.into_inner();
+
+    // A name of an argument must stand for the same declaration where its parameter is used.
+    let body = alias.body.as_span();
+    let params: HashSet<_> = alias
+        .params
+        .iter()
+        .map(|param| (param.name.span.start, param.name.span.end))
+        .collect();
+    let captured = declarations
+        .iter()
+        .filter(|(_, declaration)| params.contains(declaration))
+        .filter(|(reference, _)| body.start <= reference.0 && reference.1 <= body.end)
+        .any(|(reference, _)| {
+            rewrite::names_in_scope(program, reference.0)
+                .iter()
+                .any(|(name, symbol)| {
+                    names.contains(name)
+                        && symbol
+                            .declaration
+                            .is_some_and(|(start, end)| body.start <= start && end <= body.end)
+                })
+        });
+
+    if captured {
+        return None;
+    }
+
+    // A name of the body declared outside of the alias must stand for the same declaration at the
+    // application, `type Other as string`, `type Wrap(T) as {o: Other}`, `type X(Other) as Wrap(1)`.
+    let outside = |declaration: Option<(usize, usize)>| {
+        declaration.map_or(true, |(start, end)| {
+            end <= alias.span.start || alias.span.end <= start
+        })
+    };
+    let free = RefCell::new(vec![]);
+    alias.body.prewalk((), &|node, ctx| {
+        if let Ast::Ident(ident) = &node {
+            let declaration = declarations
+                .get(&(ident.span.start, ident.span.end))
+                .copied();
+
+            if outside(declaration) {
+                free.borrow_mut().push((ident.name.clone(), declaration));
+            }
+        }
+        (node, ctx)
+    });
+    let free = free.into_inner();
+
+    if !free.is_empty() {
+        let in_scope = rewrite::names_in_scope(program, application.as_span().start);
+
+        if free.iter().any(|(name, declaration)| {
+            in_scope.get(name).and_then(|symbol| symbol.declaration) != *declaration
+        }) {
+            return None;
+        }
+    }
+
+    // The defaults may refer to the parameters before them, substituted in order.
+    let mut values: HashMap<(usize, usize), Ast> = HashMap::new();
+
+    for (i, param) in alias.params.iter().enumerate() {
+        let value = match (args.get(i), &param.default) {
+            (Some(arg), _) => arg.clone(),
+            (None, Some(default)) => substitute(default, &values, declarations),
+            (None, None) => return None,
+        };
+
+        values.insert((param.name.span.start, param.name.span.end), value);
+    }
+
+    Some(substitute(&alias.body, &values, declarations))
+}
+
+/// `node` with the names declared at the positions of `values` replaced by their values.
+fn substitute(
+    node: &Ast,
+    values: &HashMap<(usize, usize), Ast>,
+    declarations: &HashMap<(usize, usize), (usize, usize)>,
+) -> Ast {
+    let (node, _) = node.prewalk((), &|node, ctx| match &node {
+        Ast::Ident(ident) => {
+            let value = declarations
+                .get(&(ident.span.start, ident.span.end))
+                .and_then(|declaration| values.get(declaration));
+
+            (value.cloned().unwrap_or(node), ctx)
+        }
+        _ => (node, ctx),
+    });
+
+    node
+}
+
+/// Object literals of a union with the same keys that disagree on a key: it is optional in some of
+/// them and required in others, or its value is the same but for `undefined`, as in
+///
+/// ```text
+/// type Event as {name: string, at: number} | {name: string, ?at: number}
+/// ```
+///
+/// A union like this usually comes from a bug of the generator that wrote it, and narrows in
+/// confusing ways. Each property at fault is reported. Discriminated unions are left alone: a key
+/// every member has, with a different literal in each, tells them apart whatever the other keys.
+///
+/// Run over the simplified program, see [`check_simplified`]. Allowed by default.
+pub fn inconsistent_optionality(program: &Ast) -> Vec<Diagnostic> {
+    let diagnostics = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        if let Ast::UnionType(union) = &node {
+            diagnostics.borrow_mut().extend(union_optionality(union));
+        }
+
+        (node, ctx)
+    });
+
+    diagnostics.into_inner()
+}
+
+fn union_optionality(union: &UnionType) -> Vec<Diagnostic> {
+    let objects: Vec<_> = union
+        .types
+        .iter()
+        .filter_map(|member| match member {
+            Ast::TypeLiteral(literal) => Some((literal, literal.literal_keys()?)),
+            _ => None,
+        })
+        .collect();
+
+    if is_discriminated(&objects) {
+        return vec![];
+    }
+
+    let mut groups: BTreeMap<Vec<&str>, Vec<&TypeLiteral>> = BTreeMap::new();
+
+    for (literal, keys) in &objects {
+        let keys = keys.iter().copied().sorted().dedup().collect();
+        groups.entry(keys).or_default().push(literal);
+    }
+
+    let mut diagnostics = vec![];
+
+    for (keys, members) in groups.iter().filter(|(_, members)| members.len() > 1) {
+        for key in keys {
+            let properties: Vec<_> = members
+                .iter()
+                .filter_map(|literal| property(literal, key))
+                .collect();
+
+            if !properties
+                .iter()
+                .map(|property| property.optional)
+                .all_equal()
+            {
+                diagnostics.extend(properties.iter().map(|property| {
+                    let (here, elsewhere) = if property.optional {
+                        ("optional", "required")
+                    } else {
+                        ("required", "optional")
+                    };
+
+                    Diagnostic::warning(
+                        format!(
+                            "`{key}` is {here} here but {elsewhere} in another member of the union"
+                        ),
+                        property.span,
+                    )
+                    .with_note("the members have the same keys, mark the key the same in each")
+                    .with_lint(INCONSISTENT_OPTIONALITY)
+                    .with_code(codes::INCONSISTENT_OPTIONALITY)
+                }));
+            } else if !properties
+                .iter()
+                .map(|property| &property.value)
+                .all_equal()
+                && properties
+                    .iter()
+                    .map(|property| defined(&property.value))
+                    .all_equal()
+            {
+                diagnostics.extend(properties.iter().map(|property| {
+                    Diagnostic::warning(
+                        format!(
+                            "`{key}` is `{}` here, it differs from another member of the union \
+                             only by `undefined`",
+                            property.value
+                        ),
+                        property.span,
+                    )
+                    .with_note(
+                        "the members have the same keys, give the key the same value in each",
+                    )
+                    .with_lint(INCONSISTENT_OPTIONALITY)
+                    .with_code(codes::INCONSISTENT_OPTIONALITY)
+                }));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Whether a key of every one of `objects` is required and a different literal in each.
+fn is_discriminated(objects: &[(&TypeLiteral, Vec<&str>)]) -> bool {
+    let Some((first, _)) = objects.first() else {
+        return false;
+    };
+
+    first.iter().any(|discriminant| {
+        let ObjectPropertyKey::Key(key) = &discriminant.key else {
+            return false;
+        };
+
+        let values: Option<Vec<_>> = objects
+            .iter()
+            .map(|(literal, _)| {
+                let property = property(literal, key)?;
+
+                let literal = matches!(
+                    property.value,
+                    Ast::TypeString(_)
+                        | Ast::TypeNumber(_)
+                        | Ast::TrueKeyword(_)
+                        | Ast::FalseKeyword(_)
+                );
+
+                (literal && !property.optional).then_some(&property.value)
+            })
+            .collect();
+
+        values.is_some_and(|values| {
+            values
+                .iter()
+                .enumerate()
+                .all(|(i, value)| !values[..i].contains(value))
+        })
+    })
+}
+
+/// The property named `key` of `literal`.
+fn property<'a>(literal: &'a TypeLiteral, key: &str) -> Option<&'a ObjectProperty> {
+    literal
+        .iter()
+        .find(|property| matches!(&property.key, ObjectPropertyKey::Key(name) if name == key))
+}
+
+/// The members of `value` but `undefined`.
+fn defined(value: &Ast) -> Vec<&Ast> {
+    let members = match value {
+        Ast::UnionType(union) => union.types.iter().collect(),
+        value => vec![value],
+    };
+
+    members
+        .into_iter()
+        .filter(|member| !matches!(member, Ast::Primitive(PrimitiveType::Undefined, _)))
+        .collect()
+}
+
+/// Errors for a list of type parameters TypeScript rejects, reported on the parameter at fault
+/// rather than left to `tsc`: a parameter without a default after one with a default, a rest
+/// parameter before the last or with a default, and a default that isn't assignable to the
+/// constraint of its parameter when both are literals or primitives.
+pub fn check_type_parameters(params: &[TypeParameter]) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut defaulted: Option<&TypeParameter> = None;
+
+    for (i, param) in params.iter().enumerate() {
+        let name = &param.name.name;
+
+        if param.rest {
+            if i + 1 < params.len() {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!("the rest parameter `{name}` isn't the last parameter"),
+                        param.span,
+                    )
+                    .with_code(codes::INVALID_TYPE_PARAMETERS),
+                );
+            }
+
+            if param.default.is_some() {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!("the rest parameter `{name}` has a default"),
+                        param.span,
+                    )
+                    .with_note("a rest parameter takes no argument when none is left")
+                    .with_code(codes::INVALID_TYPE_PARAMETERS),
+                );
+            }
+        } else if param.default.is_none() {
+            if let Some(defaulted) = defaulted {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "`{name}` has no default but follows `{}`, which has one",
+                            defaulted.name.name
+                        ),
+                        param.span,
+                    )
+                    .with_note("the parameters with a default come after those without")
+                    .with_code(codes::INVALID_TYPE_PARAMETERS),
+                );
+            }
+        }
+
+        if param.default.is_some() && defaulted.is_none() {
+            defaulted = Some(param);
+        }
+
+        if let (Some(default), Some(constraint)) = (&param.default, &param.constraint) {
+            if let Some(outside) = outside_constraint(default, constraint) {
+                if !outside.is_empty() {
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!(
+                                "the default `{default}` of `{name}` doesn't satisfy its \
+                                 constraint `{constraint}`"
+                            ),
+                            param.span,
+                        )
+                        .with_note(format!(
+                            "{} {} not assignable to `{constraint}`",
+                            outside
+                                .iter()
+                                .map(|member| format!("`{member}`"))
+                                .join(", "),
+                            if outside.len() == 1 { "is" } else { "are" }
+                        ))
+                        .with_code(codes::UNSATISFIED_CONSTRAINT),
+                    );
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// The members of the union `default` none of the members of `constraint` takes, `None` unless
+/// every member of both is a literal or a primitive, see [`always_extends`].
+fn outside_constraint<'a>(default: &'a Ast, constraint: &Ast) -> Option<Vec<&'a Ast>> {
+    fn members(node: &Ast) -> Vec<&Ast> {
+        match node {
+            Ast::UnionType(union) => union.types.iter().flat_map(members).collect(),
+            node => vec![node],
+        }
+    }
+
+    let constraint = members(constraint);
+    let mut outside = vec![];
+
+    for member in members(default) {
+        let extends = constraint
+            .iter()
+            .map(|ty| always_extends(member, ty))
+            .collect::<Option<Vec<_>>>()?;
+
+        if !extends.contains(&true) {
+            outside.push(member);
+        }
+    }
+
+    Some(outside)
+}
+
+/// What the node visited is nested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// A property of an interface or an object type.
+    Member,
+    /// A function type that is the value of a property.
+    Method,
+    /// Any other function type.
+    Function,
+}
+
+#[derive(Default)]
+struct ThisTypes {
+    scopes: Vec<Scope>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Visit<'a> for ThisTypes {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        if let Ast::ThisType(span) = node {
+            if !self.scopes.contains(&Scope::Method) {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        "`this` is only a type in the methods of an interface or an object type",
+                        *span,
+                    )
+                    .with_code(codes::MISPLACED_THIS),
+                );
+            }
+        }
+
+        walk_ast(self, node)
+    }
+
+    fn visit_object_property(&mut self, property: &'a ObjectProperty) {
+        self.scopes.push(Scope::Member);
+        walk_object_property(self, property);
+        self.scopes.pop();
+    }
+
+    fn visit_function_type(&mut self, function: &'a FunctionType) {
+        let scope = match self.scopes.last() {
+            Some(Scope::Member) => Scope::Method,
+            _ => Scope::Function,
+        };
+
+        self.scopes.push(scope);
+        walk_function_type(self, function);
+        self.scopes.pop();
+    }
+}
+
+/// What a literal that is not an object is, for use in a message.
+fn non_object_literal(node: &Ast) -> Option<&'static str> {
+    let kind = match node {
+        Ast::TypeNumber(_) => "a number",
+        Ast::TypeString(_) | Ast::TemplateString(_) => "a string",
+        Ast::TrueKeyword(_) | Ast::FalseKeyword(_) => "a boolean",
+        Ast::Tuple(_) => "a tuple",
+        Ast::Array(_) => "an array",
+        Ast::Primitive(..) => "a primitive type",
+        Ast::FunctionType(_) => "a function type",
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+fn param_names(params: &[TypeParameter]) -> Vec<(&str, Span)> {
+    params
+        .iter()
+        .map(|param| (param.name.name.as_str(), param.name.span))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{diagnostic::Severity, parser::Rule, test_support::parse};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    /// The diagnostics reported at the default levels.
+    fn messages(source: &str) -> Vec<(Severity, String)> {
+        apply_levels(check(&parse!(source)), &BTreeMap::new())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn reserved_alias_name_is_an_error() {
+        assert_eq!(
+            messages("type new as 1"),
+            vec![(
+                Severity::Error,
+                "`new` is a reserved word in TypeScript and cannot name a type alias".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn reserved_type_parameter_is_an_error() {
+        assert_eq!(
+            messages("interface I(default) { x: default }"),
+            vec![(
+                Severity::Error,
+                "`default` is a reserved word in TypeScript and cannot name a type parameter"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn reserved_import_name_is_an_error() {
+        assert_eq!(
+            messages("import { default as new } from :n\nimport * as default from :m"),
+            vec![
+                (
+                    Severity::Error,
+                    "`new` is a reserved word in TypeScript and cannot name a named import"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "`default` is a reserved word in TypeScript and cannot name a namespace import"
+                        .to_string()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn contextual_keyword_is_a_warning() {
+        assert_eq!(
+            messages("type A(declare) as declare"),
+            vec![(
+                Severity::Warning,
+                "`declare` is a contextual keyword in TypeScript".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn confusable_names_are_a_warning() {
+        // The second alias is spelled with a Cyrillic "а".
+        assert_eq!(
+            messages("type Data as 1\ntype D\u{0430}ta as 2"),
+            vec![(
+                Severity::Warning,
+                "`D\u{0430}ta` is easily confused with `Data`".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn confusable_type_parameters_are_a_warning() {
+        assert_eq!(
+            messages("type A(l, I) as l"),
+            vec![(
+                Severity::Warning,
+                "`I` is easily confused with `l`".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn names_in_different_scopes_are_not_confusable() {
+        assert_eq!(
+            messages("type Data(l) as l\ntype D\u{0430}ta(I) as I"),
+            vec![(
+                Severity::Warning,
+                "`D\u{0430}ta` is easily confused with `Data`".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn ordinary_names_pass() {
+        assert_eq!(messages("type A(T) as T\ninterface I { new: 1 }"), vec![]);
+    }
+
+    #[test]
+    fn merge_of_a_non_object_literal() {
+        assert_eq!(
+            messages("type A as merge!({a: 1}, merge!([1], \"b\"))"),
+            vec![
+                (
+                    Severity::Error,
+                    "`merge!` expects object types, found a tuple".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "`merge!` expects object types, found a string".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            messages("type A as merge!({a: 1})"),
+            vec![(
+                Severity::Error,
+                "`merge!` expects 2 arguments, found 1".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn deep_macro_arity() {
+        assert_eq!(
+            messages("type A(T) as deep_readonly!(T, T)"),
+            vec![(
+                Severity::Error,
+                "`deep_readonly!` expects 1 argument, found 2".to_string()
+            )]
+        );
+        assert_eq!(messages("type A(T) as deep_partial!([T])"), vec![]);
+    }
+
+    #[test]
+    fn match_missing_variants() {
+        let union = "union Shape = Circle {r: number} | Square {s: number} | Point\n";
+
+        assert_eq!(
+            messages(&format!(
+                "{union}type A(S) as match S do Circle -> 1, Point -> 2 end"
+            )),
+            vec![(
+                Severity::Warning,
+                "`match` doesn't cover every variant of `Shape`".to_string()
+            )]
+        );
+
+        for exhaustive in [
+            "type A(S) as match S do Circle -> 1, Square -> 2, Point -> 3 end",
+            "type A(S) as match S do Circle -> 1, else -> 2 end",
+            "type A(S) as match S do Circle -> 1, Shape -> 2 end",
+        ] {
+            assert_eq!(messages(&format!("{union}{exhaustive}")), vec![]);
+        }
+    }
+
+    fn spans(source: &str) -> Vec<&str> {
+        check(&parse!(source))
+            .into_iter()
+            .map(|diagnostic| &source[diagnostic.span.start..diagnostic.span.end])
+            .collect()
+    }
+
+    #[test]
+    fn declarations_are_reported_at_their_name() {
+        assert_eq!(spans("type new as 1"), vec!["new"]);
+        assert_eq!(spans("type A(declare) as declare"), vec!["declare"]);
+        assert_eq!(spans("type A as let l = 1, I = 2 in l"), vec!["I"]);
+    }
+
+    #[test]
+    fn dependent_constraints() {
+        let get = "type Get(T, K) where K <: keyof(T) as T[K]\n";
+
+        for satisfied in [
+            "type A as Get({a: 1, b: 2}, :b)",
+            "type A(U) as Get(U, :b)",
+            "type A(U) as Get({a: 1}, U)",
+        ] {
+            assert_eq!(messages(&format!("{get}{satisfied}")), vec![]);
+        }
+
+        let source = format!("{get}type A as Get({{a: 1, b: 2}}, :c)");
+        let diagnostics = apply_levels(check(&parse!(&source)), &BTreeMap::new());
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    &source[diagnostic.span.start..diagnostic.span.end],
+                    diagnostic.notes.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`\"c\"` doesn't satisfy the constraint `keyof({a: 1, b: 2})` of `K`",
+                ":c",
+                vec!["the keys are `\"a\"`, `\"b\"`".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn type_argument_counts() {
+        let declarations = "type Pair(A, B) as [A, B]\ntype Opt(T, U) defaults U = 1 as T | U\n";
+
+        for valid in ["type P as Pair(1, 2)", "type O as Opt(1) | Opt(1, 2)"] {
+            assert_eq!(messages(&format!("{declarations}{valid}")), vec![]);
+        }
+
+        assert_eq!(
+            messages(&format!(
+                "{declarations}type P as Pair(1)\ntype O as Opt(1, 2, 3)\ntype Q as Opt()"
+            )),
+            vec![
+                (
+                    Severity::Error,
+                    "`Pair` expects 2 type arguments, found 1".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "`Opt` expects 1 to 2 type arguments, found 3".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "`Opt` expects 1 to 2 type arguments, found 0".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambient_module_scope() {
+        // The declarations of the program are in scope of the block, those of the block aren't
+        // outside of it.
+        assert_eq!(
+            messages(
+                "type Pair(A, B) as [A, B]\n\
+                 declare module :express do\n\
+                     type Inner(T) as T\n\
+                     type Handler as [Pair(1), Inner(1, 2)]\n\
+                 end\n\
+                 type Outer as Inner(1, 2)"
+            ),
+            vec![
+                (
+                    Severity::Error,
+                    "`Pair` expects 2 type arguments, found 1".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "`Inner` expects 1 type argument, found 2".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowed_global() {
+        assert_eq!(
+            messages("export type Record(K, V) as {}\ninterface Partial { a: 1 }"),
+            vec![
+                (
+                    Severity::Warning,
+                    "`Record` shadows the global TypeScript type of the same name".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "`Partial` shadows the global TypeScript type of the same name".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            messages("type Recorded as 1\ntype A(Record) as Record"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn global_declarations_do_not_shadow_globals() {
+        assert_eq!(
+            messages(
+                "global interface Array(T) { last: T }\n\
+                 global interface Window { id: string }\n\
+                 global type Awaited as 1"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn constrained_match_is_exhaustive() {
+        assert_eq!(
+            messages(
+                "type A(T) where T <: :a | :b as match T do :a -> 1, :b -> 2 end\n\
+                 type B(T) where T <: :a | 1 as match T do string -> 1, number -> 2 end"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn constrained_match_misses_a_member() {
+        let diagnostics = check(&parse!(
+            "type A(T) where T <: :a | :b | :c as match T do :a -> 1 end"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.notes.clone()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`match` doesn't cover every member of the constraint of `T`",
+                vec!["`\"b\"`, `\"c\"` not matched".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn match_on_several_values_misses_a_combination() {
+        let diagnostics = check(&parse!(
+            "type A(T, U) where T <: :a | :b, U <: 1 | 2 as\n\
+             match T, U do (:a, _) -> 1, (:b, 1) -> 2 end\n\
+             type B(T, U) where T <: :a | :b, U <: 1 | 2 as\n\
+             match T, U do (:a, _) -> 1, (_, number) -> 2 end"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.notes.clone()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`match` doesn't cover every combination of the constraints of `T`, `U`",
+                vec!["`[\"b\", 2]` not matched".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn match_on_several_values_misses_their_unions() {
+        let diagnostics = check(&parse!(
+            "type A(T, U) where T <: :a | :b, U <: 1 | 2 as\n\
+             match T, U do (:a, _) -> 1, (:b, _) -> 2 end\n\
+             type B(T, U) where T <: :a | :b, U <: 1 | 2 as\n\
+             match T, U do (:a, 1) -> 1, (_, _) -> 2 end"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.notes.clone()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`match` doesn't cover the unions of the constraints of `T`, `U`",
+                vec![
+                    "`[\"a\" | \"b\", 1 | 2]` not matched".to_string(),
+                    "a match on several values doesn't distribute over unions, no arm covers \
+                     every combination"
+                        .to_string()
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn patterns_of_another_number_of_values() {
+        // The parser rejects those of a `match A, B`, see `parse_patterns_of_values`.
+        let diagnostics = check(&parse!(
+            "type B(T, U) as match [T, U] do [string] -> 1, [string, _] -> 2, else -> 3 end"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.notes.clone()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "pattern of 1 value in a `match` on 2",
+                vec!["the arm never matches".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn pattern_outside_the_constraint_never_matches() {
+        let diagnostics = check(&parse!(
+            "type A(T) where T <: :a | :b as match T do :a -> 1, :c -> 2, else -> 3 end"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.notes.clone()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`T` never matches `\"c\"`",
+                vec![
+                    "`T` is constrained to `\"a\" | \"b\"`".to_string(),
+                    "its arm is never taken".to_string()
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn open_params_are_not_checked_against_their_constraint() {
+        assert_eq!(
+            messages(
+                "type A(T) as match T do :a -> 1 end\n\
+                 type B(T) where T <: string as match T do :a -> 1, 1 -> 2 end\n\
+                 type C(T) where T <: :a | :b as match [1] do [?T] -> match T do 1 -> 1 end end\n\
+                 type D(T) where T <: :a | :b as match T do :a -> 1, [?U] -> 2 end"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn always_true_condition() {
+        let diagnostics = check(&parse!(
+            "type A(T) as if :a <: string then T else never end"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    diagnostic.notes.clone(),
+                    diagnostic.lint
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`\"a\" <: string` is always true",
+                vec!["the `else` branch is never taken".to_string()],
+                Some(CONSTANT_CONDITION)
+            )]
+        );
+    }
+
+    #[test]
+    fn always_false_condition() {
+        assert_eq!(
+            messages(
+                "type A as if 1 <: string then 1 else 2 end\n\
+                 type B as cond do true </: boolean -> 1, :a <: :a -> 2, else -> 3 end\n\
+                 type C as match 1 do string -> 1, number -> 2, else -> 3 end"
+            ),
+            vec![
+                (
+                    Severity::Warning,
+                    "`1 <: string` is always false".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "`true </: boolean` is always false".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "`\"a\" <: \"a\"` is always true".to_string()
+                ),
+                (Severity::Warning, "`1` never matches `string`".to_string()),
+                (Severity::Warning, "`1` always matches `number`".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn conditions_of_names_are_not_constant() {
+        assert_eq!(
+            messages(
+                "type A(T) as if T <: string then 1 else 2 end\n\
+                 type B as if :a <: ?U then U else 2 end\n\
+                 type C(T) as match T do :a -> 1, [?H] -> H, else -> 3 end\n\
+                 type D as if :a <: string and 1 <: T then 1 else 2 end\n\
+                 type E as if null <: void then 1 else 2 end"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn constant_condition_points_at_the_source() {
+        use crate::compile::{compile, CompileOptions};
+
+        let source = "type A(T) as match T do 1 -> :a, 2 -> :a, \
+                      else -> if 1 <: string then :a else :b end end";
+        let options = CompileOptions {
+            merge_arms: true,
+            ..Default::default()
+        };
+
+        let output = compile(source, &options).unwrap();
+        let span = output.diagnostics[0].span;
+
+        assert_eq!(output.diagnostics.len(), 1);
+        assert_eq!(&source[span.start..span.end], "1 <: string");
+        assert_eq!(
+            output.typescript.trim(),
+            "type A<T> = T extends 1 | 2 ? 'a' : 1 extends string ? 'a' : 'b';"
+        );
+    }
+
+    #[test]
+    fn default_exports() {
+        assert_eq!(
+            messages("export default type A as 1\ninterface I {}\nexport default I"),
+            vec![(
+                Severity::Error,
+                "a module has only one default export".to_string()
+            )]
+        );
+        assert_eq!(
+            messages("import { B } from \"./b\"\nexport default C"),
+            vec![(
+                Severity::Error,
+                "`C` is not declared in this module".to_string()
+            )]
+        );
+        assert_eq!(
+            messages("import { B } from \"./b\"\nexport default B"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn misplaced_this() {
+        let misplaced = (
+            Severity::Error,
+            "`this` is only a type in the methods of an interface or an object type".to_string(),
+        );
+
+        assert_eq!(messages("type A as this"), vec![misplaced.clone()]);
+        assert_eq!(
+            messages("interface I {self: this}"),
+            vec![misplaced.clone()]
+        );
+        assert_eq!(messages("type F as (x: this) => 1"), vec![misplaced]);
+        assert_eq!(
+            messages("interface I {add: (x: number) => this, eq: (other: this) => boolean}"),
+            vec![]
+        );
+        assert_eq!(
+            messages("type A as {each: (f: (x: this) => 1) => this}"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn type_predicates() {
+        assert_eq!(
+            messages("type F as (value: unknown, key: string) => x is string"),
+            vec![(
+                Severity::Error,
+                "`x` is not a parameter of the function".to_string()
+            )]
+        );
+        assert_eq!(
+            check(&parse!("type F as () => asserts x"))[0].notes,
+            vec!["the function has no parameters"]
+        );
+        assert_eq!(
+            messages("type F as (value: unknown) => (x: unknown) => asserts value"),
+            vec![(
+                Severity::Error,
+                "`value` is not a parameter of the function".to_string()
+            )]
+        );
+        assert_eq!(
+            messages("type F as (value: unknown) => asserts value is string"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn required_parameter_after_a_default() {
+        let diagnostics = check(&parse!(
+            "type A(T, U, V) defaults U = 1 as [T, U, V]\ninterface I(T, U) defaults T = 1 {a: U}"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.span.start))
+                .collect::<Vec<_>>(),
+            vec![
+                ("`V` has no default but follows `U`, which has one", 13),
+                ("`U` has no default but follows `T`, which has one", 59),
+            ]
+        );
+    }
+
+    #[test]
+    fn rest_parameter_last_without_a_default() {
+        let param = |name: &str, default: Option<Ast>, rest: bool| {
+            TypeParameter::new(name.to_string(), None, default, rest, Span::default())
+        };
+
+        assert_eq!(
+            check_type_parameters(&[
+                param("T", None, true),
+                param("U", Some(parse!(Rule::expr, "[]")), true),
+            ])
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>(),
+            vec![
+                "the rest parameter `T` isn't the last parameter",
+                "the rest parameter `U` has a default",
+            ]
+        );
+        assert_eq!(
+            check_type_parameters(&[
+                param("T", Some(parse!(Rule::expr, "1")), false),
+                param("U", None, true)
+            ]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn default_outside_its_constraint() {
+        let diagnostics = check(&parse!(
+            "type A(S) defaults S = :medium | :small where S <: :small | :large as S\n\
+             type B(T) defaults T = 1 where T <: string as T"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.notes.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "the default `\"medium\" | \"small\"` of `S` doesn't satisfy its constraint \
+                     `\"small\" | \"large\"`",
+                    vec!["`\"medium\"` is not assignable to `\"small\" | \"large\"`".to_string()]
+                ),
+                (
+                    "the default `1` of `T` doesn't satisfy its constraint `string`",
+                    vec!["`1` is not assignable to `string`".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn valid_type_parameters() {
+        assert_eq!(
+            messages(
+                "type A(T, U, V) defaults U = :a, V = U where U <: :a | :b, V <: string as [T, U, V]\n\
+                 type B(K) defaults K = Key where K <: string as K"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn unknown_attributes() {
+        let diagnostics = check(&parse!(
+            "@deprecated\n@emit(\"client\")\n@internal\ntype A as 1"
+        ));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.severity,
+                    diagnostic.message.as_str(),
+                    diagnostic.notes.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                Severity::Warning,
+                "unknown attribute `@internal`",
+                vec!["the known attributes are `@emit`, `@deprecated`, `@companion`".to_string()]
+            )]
+        );
+        assert_eq!(
+            (diagnostics[0].span.start, diagnostics[0].span.end),
+            (28, 37)
+        );
+    }
+
+    #[test]
+    fn suggested_renames() {
+        fn suggestions(source: &str) -> Vec<(&str, Option<String>)> {
+            check(&parse!(source))
+                .into_iter()
+                .map(|diagnostic| {
+                    let span = diagnostic.span;
+                    let suggestion = diagnostic.suggestion.map(|suggestion| {
+                        assert!(suggestion.rename);
+                        suggestion.replacement
+                    });
+
+                    (&source[span.start..span.end], suggestion)
+                })
+                .collect()
+        }
+
+        assert_eq!(
+            suggestions("type new(infer, infer_) as [infer, infer_]"),
+            vec![
+                ("new", Some("new_".to_string())),
+                ("infer", Some("infer_2".to_string()))
+            ]
+        );
+
+        // Renaming an exported declaration changes the exports, the name is left to the author
+        // unless the output would not compile anyway.
+        assert_eq!(
+            suggestions(
+                "export type declare as 1
+export type new as 2
+type Record as 3"
+            ),
+            vec![
+                ("declare", None),
+                ("new", Some("new_".to_string())),
+                ("Record", Some("Record_".to_string()))
+            ]
+        );
+        assert_eq!(
+            suggestions("interface Map(declare) { a: declare }"),
+            vec![
+                ("declare", Some("declare_".to_string())),
+                ("interface Map(declare) { a: declare }", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn pragma_allows_a_lint() {
+        let source = "// allow(shadowed_global)\ntype Record as 1\ntype Array as 2";
+
+        let program = parse!(source);
+        let diagnostics = apply_pragmas(check(&program), &SourceFile::new("", source), &program);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| &source[diagnostic.span.start..diagnostic.span.end])
+                .collect::<Vec<_>>(),
+            vec!["Array"]
+        );
+
+        let source = "/** Doc */\n// allow(confusable_names, shadowed_global)\ntype Record as 1";
+        let program = parse!(source);
+
+        assert_eq!(
+            apply_pragmas(check(&program), &SourceFile::new("", source), &program),
+            vec![]
+        );
+    }
+
+    fn explicit_anys(source: &str) -> Vec<(String, &str)> {
+        let program = parse!(source);
+
+        apply_pragmas(
+            explicit_any(&program),
+            &SourceFile::new("", source),
+            &program,
+        )
+        .into_iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.message,
+                &source[diagnostic.span.start..diagnostic.span.end],
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn explicit_any_in_an_export() {
+        assert_eq!(
+            explicit_anys(
+                "export type A as {a: any, b: [1, any]}\nexport interface I {f: (x: any) => 1}"
+            ),
+            vec![
+                ("`any` in the exported type `A`".to_string(), "any"),
+                ("`any` in the exported type `A`".to_string(), "any"),
+                ("`any` in the exported type `I`".to_string(), "any"),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_any_through_an_alias() {
+        let source = dedent!(
+            r#"
+            type Loose as any
+            type Loosest(T) as {value: any | T}
+            type Strict as unknown
+            export type A(Strict) as {a: Loose, b: Loosest(1), c: Strict}
+            export type B(Loose) as Loose
+            "#
+        );
+
+        assert_eq!(
+            explicit_anys(source),
+            vec![
+                (
+                    "`Loose` brings `any` into the exported type `A`".to_string(),
+                    "Loose"
+                ),
+                (
+                    "`Loosest` brings `any` into the exported type `A`".to_string(),
+                    "Loosest"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_any_allowed_for_a_statement() {
+        let source = dedent!(
+            r#"
+            // allow(explicit_any)
+            export type A as {
+                a: any,
+                b: any
+            }
+            export type B as {
+                a: any
+            }
+            "#
+        );
+
+        assert_eq!(
+            explicit_anys(source),
+            vec![("`any` in the exported type `B`".to_string(), "any")]
+        );
+    }
+
+    #[test]
+    fn explicit_any_outside_exports() {
+        assert_eq!(
+            explicit_anys("type A as any\ninterface I {a: any}\nexport type B as I"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn explicit_any_is_allowed_by_default() {
+        let source = "export type A as any";
+
+        assert_eq!(
+            apply_levels(check(&parse!(source)), &BTreeMap::new()),
+            vec![]
+        );
+        assert_eq!(
+            apply_levels(
+                check(&parse!(source)),
+                &[(EXPLICIT_ANY.to_string(), Level::Deny)].into()
+            )
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message.as_str()))
+            .collect::<Vec<_>>(),
+            vec![(Severity::Error, "`any` in the exported type `A`")]
+        );
+    }
+
+    fn single_uses(source: &str) -> Vec<(String, &str, Option<String>)> {
+        single_use_aliases(&parse!(source))
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message,
+                    &source[diagnostic.span.start..diagnostic.span.end],
+                    diagnostic
+                        .suggestion
+                        .map(|suggestion| suggestion.replacement),
+                )
+            })
+            .collect()
+    }
+
+    fn inlined(source: &str) -> String {
+        let levels = [(SINGLE_USE_ALIAS.to_string(), Level::Warn)].into();
+
+        rewrite::fix(source, &levels).unwrap().0
+    }
+
+    #[test]
+    fn single_use_alias_is_inlined() {
+        let source = "type Wrap(T) as {value: T}\nexport type Boxed as Wrap(string)\n";
+
+        assert_eq!(
+            single_uses(source),
+            vec![(
+                "`Wrap` is only used once".to_string(),
+                "Wrap",
+                Some("{value: string}".to_string())
+            )]
+        );
+        assert_eq!(
+            inlined(source),
+            "type Wrap(T) as {value: T}\nexport type Boxed as {value: string}\n"
+        );
+    }
+
+    #[test]
+    fn single_use_alias_with_defaults() {
+        let source = dedent!(
+            r#"
+            type Pair(A, B) defaults B = [A] as A | B
+            export type List as Pair(1)[]
+            "#
+        );
+
+        assert_eq!(
+            inlined(source),
+            dedent!(
+                r#"
+                type Pair(A, B) defaults B = [A] as A | B
+                export type List as (1 | [1])[]
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn aliases_used_twice() {
+        let source = dedent!(
+            r#"
+            type Wrap(T) as {value: T}
+            type List(T) as [T, List(T)]
+            export type A as [Wrap(1), Wrap(2)]
+            export type B as List(1)
+            "#
+        );
+
+        assert_eq!(single_uses(source), vec![]);
+    }
+
+    #[test]
+    fn exported_aliases_are_not_reported() {
+        let source = "export type Wrap(T) as {value: T}\nexport type Boxed as Wrap(string)";
+
+        assert_eq!(single_uses(source), vec![]);
+    }
+
+    /// The argument `Name` would stand for the binding of the `let` once inlined.
+    #[test]
+    fn captured_arguments_are_not_inlined() {
+        let source = dedent!(
+            r#"
+            type Name as :outer
+            type Named(T) as let Name = :inner in {name: Name, value: T}
+            export type A as Named(Name)
+            "#
+        );
+
+        assert_eq!(
+            single_uses(source),
+            vec![("`Named` is only used once".to_string(), "Named", None)]
+        );
+    }
+
+    /// `Other` would stand for the parameter of `X` once inlined.
+    #[test]
+    fn captured_names_of_the_body_are_not_inlined() {
+        let source = dedent!(
+            r#"
+            type Other as string
+            type Wrap(T) as {v: T, o: Other}
+            export type X(Other) as Wrap(1)
+            "#
+        );
+
+        assert_eq!(
+            single_uses(source),
+            vec![("`Wrap` is only used once".to_string(), "Wrap", None)]
+        );
+    }
+
+    #[test]
+    fn single_use_alias_is_allowed_by_default() {
+        let source = "type Wrap(T) as {value: T}\nexport type Boxed as Wrap(string)";
+
+        assert_eq!(
+            apply_levels(check(&parse!(source)), &BTreeMap::new()),
+            vec![]
+        );
+    }
+
+    fn inconsistencies(source: &str) -> Vec<(String, &str)> {
+        inconsistent_optionality(&parse!(source).simplify())
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message,
+                    &source[diagnostic.span.start..diagnostic.span.end],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inconsistent_optionality_in_a_union() {
+        let source = dedent!(
+            r#"
+            export type Event as {name: string, at: number} | {name: string, ?at: number} | {id: number}
+            export type Total as {sum: number} | {sum: number | undefined}
+            "#
+        );
+
+        assert_eq!(
+            inconsistencies(source),
+            vec![
+                (
+                    "`at` is required here but optional in another member of the union".to_string(),
+                    "at: number"
+                ),
+                (
+                    "`at` is optional here but required in another member of the union".to_string(),
+                    "?at: number"
+                ),
+                (
+                    "`sum` is `number` here, it differs from another member of the union only by \
+                     `undefined`"
+                        .to_string(),
+                    "sum: number"
+                ),
+                (
+                    "`sum` is `number | undefined` here, it differs from another member of the \
+                     union only by `undefined`"
+                        .to_string(),
+                    "sum: number | undefined"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn discriminated_unions_are_not_reported() {
+        let source = dedent!(
+            r#"
+            export type Shape as {kind: :circle, size: number} | {kind: :square, ?size: number}
+            "#
+        );
+
+        assert_eq!(inconsistencies(source), vec![]);
+    }
+
+    #[test]
+    fn consistent_unions_are_not_reported() {
+        let source = dedent!(
+            r#"
+            export type A as {a: string, ?b: number} | {a: number, ?b: number} | {c: 1}
+            export type B as {a: 1} | {?a: 1, b: 2}
+            "#
+        );
+
+        assert_eq!(inconsistencies(source), vec![]);
+    }
+
+    #[test]
+    fn inconsistent_optionality_is_allowed_by_default() {
+        let source = "export type A as {a: 1} | {?a: 1}";
+        let simplified = parse!(source).simplify();
+
+        assert_eq!(
+            apply_levels(check_simplified(&simplified), &BTreeMap::new()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn lib_globals_are_sorted() {
+        assert!(lib_globals::LIB_GLOBALS
+            .windows(2)
+            .all(|pair| pair[0] < pair[1]));
+    }
+}
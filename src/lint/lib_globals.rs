@@ -0,0 +1,155 @@
+// @generated by scripts/lib_globals.sh, do not edit.
+
+/// Names of the types declared by the ECMAScript lib files of TypeScript, sorted.
+pub const LIB_GLOBALS: &[&str] = &[
+    "AggregateError",
+    "AggregateErrorConstructor",
+    "Array",
+    "ArrayBuffer",
+    "ArrayBufferConstructor",
+    "ArrayBufferLike",
+    "ArrayBufferTypes",
+    "ArrayBufferView",
+    "ArrayConstructor",
+    "ArrayLike",
+    "AsyncGenerator",
+    "AsyncGeneratorFunction",
+    "AsyncGeneratorFunctionConstructor",
+    "AsyncIterable",
+    "AsyncIterableIterator",
+    "AsyncIterator",
+    "Atomics",
+    "Awaited",
+    "BigInt",
+    "BigInt64Array",
+    "BigInt64ArrayConstructor",
+    "BigIntConstructor",
+    "BigUint64Array",
+    "BigUint64ArrayConstructor",
+    "Boolean",
+    "BooleanConstructor",
+    "CallableFunction",
+    "Capitalize",
+    "ClassDecorator",
+    "ConcatArray",
+    "ConstructorParameters",
+    "DataView",
+    "DataViewConstructor",
+    "Date",
+    "DateConstructor",
+    "Error",
+    "ErrorConstructor",
+    "EvalError",
+    "EvalErrorConstructor",
+    "Exclude",
+    "Extract",
+    "FinalizationRegistry",
+    "FinalizationRegistryConstructor",
+    "Float32Array",
+    "Float32ArrayConstructor",
+    "Float64Array",
+    "Float64ArrayConstructor",
+    "Function",
+    "FunctionConstructor",
+    "Generator",
+    "GeneratorFunction",
+    "GeneratorFunctionConstructor",
+    "IArguments",
+    "ImportMeta",
+    "InstanceType",
+    "Int16Array",
+    "Int16ArrayConstructor",
+    "Int32Array",
+    "Int32ArrayConstructor",
+    "Int8Array",
+    "Int8ArrayConstructor",
+    "Iterable",
+    "IterableIterator",
+    "Iterator",
+    "IteratorResult",
+    "IteratorReturnResult",
+    "IteratorYieldResult",
+    "JSON",
+    "Lowercase",
+    "Map",
+    "MapConstructor",
+    "Math",
+    "MethodDecorator",
+    "NewableFunction",
+    "NoInfer",
+    "NonNullable",
+    "Number",
+    "NumberConstructor",
+    "Object",
+    "ObjectConstructor",
+    "Omit",
+    "OmitThisParameter",
+    "ParameterDecorator",
+    "Parameters",
+    "Partial",
+    "Pick",
+    "Promise",
+    "PromiseConstructor",
+    "PromiseConstructorLike",
+    "PromiseFulfilledResult",
+    "PromiseLike",
+    "PromiseRejectedResult",
+    "PromiseSettledResult",
+    "PropertyDecorator",
+    "PropertyDescriptor",
+    "PropertyDescriptorMap",
+    "PropertyKey",
+    "ProxyConstructor",
+    "ProxyHandler",
+    "RangeError",
+    "RangeErrorConstructor",
+    "Readonly",
+    "ReadonlyArray",
+    "ReadonlyMap",
+    "ReadonlySet",
+    "Record",
+    "ReferenceError",
+    "ReferenceErrorConstructor",
+    "RegExp",
+    "RegExpConstructor",
+    "RegExpExecArray",
+    "RegExpMatchArray",
+    "Required",
+    "ReturnType",
+    "Set",
+    "SetConstructor",
+    "SharedArrayBuffer",
+    "SharedArrayBufferConstructor",
+    "String",
+    "StringConstructor",
+    "Symbol",
+    "SymbolConstructor",
+    "SyntaxError",
+    "SyntaxErrorConstructor",
+    "TemplateStringsArray",
+    "ThisParameterType",
+    "ThisType",
+    "TypeError",
+    "TypeErrorConstructor",
+    "TypedPropertyDescriptor",
+    "URIError",
+    "URIErrorConstructor",
+    "Uint16Array",
+    "Uint16ArrayConstructor",
+    "Uint32Array",
+    "Uint32ArrayConstructor",
+    "Uint8Array",
+    "Uint8ArrayConstructor",
+    "Uint8ClampedArray",
+    "Uint8ClampedArrayConstructor",
+    "Uncapitalize",
+    "Uppercase",
+    "WeakKey",
+    "WeakKeyTypes",
+    "WeakMap",
+    "WeakMapConstructor",
+    "WeakRef",
+    "WeakRefConstructor",
+    "WeakSet",
+    "WeakSetConstructor",
+];
@@ -1,37 +1,14 @@
-// #![feature(never_type)]
-#![feature(assert_matches)]
-#![feature(box_patterns)]
-#![feature(macro_metavar_expr)]
-#![allow(dead_code)]
-#![allow(unused_imports)]
-#![allow(unused_macros)]
-
-extern crate alloc;
-extern crate pest;
-
-#[macro_use]
-extern crate pest_derive;
-
-#[cfg(test)]
-extern crate quickcheck;
-
-#[cfg(test)]
-#[macro_use(quickcheck)]
-extern crate quickcheck_macros;
-
-mod ast;
-mod parser;
-mod pretty;
-mod runtime;
-mod typescript;
-
-mod extends_result;
-#[cfg(test)]
-mod test_support;
-
 use clap::Parser;
-use std::io::Read;
-use typescript::Pretty;
+use newtype::{
+    analysis, ast,
+    compile::{self, size::SizeReportFormat},
+    config, diagnostic, diff, dsl, import,
+    prelude::*,
+    pretty, repl, rewrite, target,
+    timings::TimingsFormat,
+    zod,
+};
+use std::{io::Read, path::Path};
 
 #[derive(Debug, Parser)]
 #[clap(name = "newtype compiler")]
@@ -40,37 +17,940 @@ struct Args {
     input: Option<String>,
     #[clap(short, long, value_name = "FILE")]
     output: Option<String>,
+    /// Compile every `.dl` file of the input directory to a file at the same path under DIR
+    #[clap(
+        long,
+        value_name = "DIR",
+        requires = "input",
+        conflicts_with = "output"
+    )]
+    out_dir: Option<String>,
+    /// With `--out-dir`, also write an `index.ts` re-exporting every exported type
+    #[clap(long, requires = "out_dir")]
+    barrel: bool,
+    /// Print the duration of each compiler pass to stderr
+    #[clap(long)]
+    timings: bool,
+    #[clap(long, value_enum, default_value_t)]
+    timings_format: TimingsFormat,
+    /// Kind of TypeScript file to write [default: ts]. `zod` also writes Zod schemas of the types
+    /// to `<OUTPUT>.zod.ts`
+    #[clap(long, value_enum)]
+    emit: Option<compile::OutputKind>,
+    /// Maximum line width of the emitted TypeScript [default: 120]
+    #[clap(long)]
+    width: Option<usize>,
+    /// Merge adjacent arms of conditional types that have the same body, `T extends A | B`
+    /// instead of testing `A` and `B` in turn
+    #[clap(long)]
+    merge_arms: bool,
+    /// Write conditional types filtering a type parameter, `T extends U ? T : never`, as
+    /// `Extract<T, U>` and `Exclude<T, U>`
+    #[clap(long)]
+    extract_exclude: bool,
+    /// Enable unstable features, as `#feature(NAME)` in the source does
+    #[clap(long, value_name = "NAME", value_delimiter = ',')]
+    features: Vec<String>,
+    /// Set a flag, compiling the `#if flag("NAME") do ... end` blocks testing it rather than
+    /// their `#else`
+    #[clap(long = "flag", value_name = "NAME")]
+    flags: Vec<String>,
+    /// Bind a subexpression repeated at least N times in an alias once, with an `infer`, instead
+    /// of repeating it
+    #[clap(long, value_name = "N", value_parser = hoist_threshold)]
+    hoist: Option<usize>,
+    /// Compile the input one statement at a time, writing each statement as soon as it is
+    /// compiled, for inputs too large to hold as a whole. The lints comparing statements are not
+    /// run
+    #[clap(long, conflicts_with_all = [
+        "out_dir",
+        "only_tag",
+        "max_emit_bytes_per_alias",
+        "emit_size_report",
+    ])]
+    streaming: bool,
+    /// How `brand` declarations tag their base type [default: symbol]
+    #[clap(long, value_enum)]
+    brand_encoding: Option<ast::brand_declaration::BrandEncoding>,
+    /// Emit only the declarations tagged `@emit("TAG")`, the untagged ones and those they use
+    #[clap(long, value_name = "TAG")]
+    only_tag: Option<String>,
+    /// Version of TypeScript to write for, such as `4.7`. Constructs it can't read are errors
+    /// [default: 5.0]
+    #[clap(long, value_name = "VERSION")]
+    target: Option<target::TsVersion>,
+    /// Report the warnings of lints as errors, and reject the constructs the compiler can't
+    /// lower yet and the matches over a closed set of types leaving members to a wildcard
+    #[clap(long)]
+    strict: bool,
+    /// Lay the TypeScript out as Prettier does by default: 2 columns of indentation, double
+    /// quotes and 80 columns unless `--width` is passed
+    #[clap(long)]
+    prettier_compat: bool,
+    /// Quote of string literals [default: single, double with `--prettier-compat`]
+    #[clap(long, value_enum)]
+    quote: Option<pretty::Quote>,
+    /// Emit the aliases marked `@companion` with a constant holding their value, `export const
+    /// Color = {...} as const`, and as the type of its values
+    #[clap(long)]
+    emit_const_companions: bool,
+    /// What an `if` or `cond` without `else` falls back to when no condition holds, `scrutinee`
+    /// being the type the condition checks [default: never]
+    #[clap(long, value_enum)]
+    missing_else: Option<ast::if_expr::MissingElse>,
+    /// Lower the conditions a macro such as `all!` leaves outside an `if` or a `cond` to the
+    /// `true` or `false` they test, rather than reject them
+    #[clap(long)]
+    lower_conditions: bool,
+    /// Reject the exported aliases whose TypeScript is longer than N bytes
+    #[clap(long, value_name = "N")]
+    max_emit_bytes_per_alias: Option<usize>,
+    /// Print the size of the TypeScript of every exported alias to stdout, the largest first,
+    /// even when one is over `--max-emit-bytes-per-alias`
+    #[clap(long, value_enum, value_name = "FORMAT", conflicts_with = "out_dir")]
+    emit_size_report: Option<SizeReportFormat>,
+    /// Reject the expressions nested in more than N others as syntax errors, rather than run out
+    /// of stack on them [default: 512]
+    #[clap(long, value_name = "N")]
+    max_nesting: Option<usize>,
+    /// How diagnostics are printed
+    #[clap(long, value_enum, default_value_t, global = true)]
+    error_format: ErrorFormat,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Translate type definitions from another format
+    #[clap(subcommand)]
+    Import(Import),
+    /// Print complexity metrics of the exported aliases
+    Stats {
+        #[clap(value_name = "FILE")]
+        file: String,
+        /// Fail if an alias goes over a limit, e.g. `--limit depth=50`. The metrics are `nodes`,
+        /// `depth`, `infer` and `instantiation`
+        #[clap(long = "limit", value_name = "METRIC=VALUE")]
+        limits: Vec<analysis::Limit>,
+    },
+    /// Print the graph of the references between the aliases and interfaces of a program
+    Graph {
+        #[clap(value_name = "FILE")]
+        file: String,
+        #[clap(long, value_enum, default_value_t)]
+        format: analysis::GraphFormat,
+    },
+    /// Print the signature, parameters, inferred types and TypeScript of an alias
+    Describe {
+        #[clap(value_name = "FILE")]
+        file: String,
+        #[clap(value_name = "ALIAS")]
+        name: String,
+        #[clap(long, value_enum, default_value_t)]
+        format: analysis::DescribeFormat,
+    },
+    /// Print the changes to the exported types between two versions of a program
+    Diff {
+        #[clap(value_name = "OLD")]
+        old: String,
+        #[clap(value_name = "NEW")]
+        new: String,
+        /// Fail if there is a change of this severity or a more severe one
+        #[clap(long, value_enum, value_name = "SEVERITY")]
+        fail_on: Option<diff::Compatibility>,
+    },
+    /// Rename a top-level declaration and its references in every module of a project, e.g.
+    /// `rename types.dl::User Member` or `rename --at types.dl:120 Member`
+    #[clap(allow_missing_positional = true)]
+    Rename {
+        /// The declaration, or a reference to it, at a byte offset of a module
+        #[clap(long, value_name = "FILE:OFFSET")]
+        at: Option<rewrite::rename::Target>,
+        /// The declaration of a name in a module
+        #[clap(
+            value_name = "FILE::NAME",
+            required_unless_present = "at",
+            conflicts_with = "at"
+        )]
+        old: Option<rewrite::rename::Target>,
+        #[clap(value_name = "NEW")]
+        new_name: String,
+        /// Directory of the project, which the paths of modules are relative to
+        #[clap(long, value_name = "DIR", default_value = ".")]
+        root: String,
+    },
+    /// Apply the fixes suggested by the lints to files in place
+    Fix {
+        #[clap(value_name = "FILE", required = true)]
+        files: Vec<String>,
+    },
+    /// Compile files without writing them and report their diagnostics, failing if there is one
+    /// that isn't in the baseline
+    Check {
+        #[clap(value_name = "FILE", required = true)]
+        files: Vec<String>,
+        /// JSON file of the diagnostics to ignore, and to list as stale once they are fixed
+        #[clap(long, value_name = "FILE")]
+        baseline: Option<String>,
+        /// Record every current diagnostic in the baseline instead of checking against it
+        #[clap(long, requires = "baseline")]
+        write_baseline: bool,
+    },
+    /// Print the explanation of a diagnostic code, e.g. `explain DL0001`
+    Explain {
+        #[clap(value_name = "CODE")]
+        code: String,
+    },
+    /// Print the syntax tree of a program as JSON, which `schema` describes
+    Ast {
+        #[clap(value_name = "FILE")]
+        file: String,
+    },
+    /// Print the JSON Schema of the syntax trees that `ast` prints
+    Schema,
+    /// Evaluate type expressions interactively, with the declarations entered before them in
+    /// scope. `:help` lists the commands
+    Repl,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Import {
+    /// Translate a JSON Schema document into type aliases
+    JsonSchema {
+        #[clap(value_name = "FILE")]
+        file: String,
+        /// Name of the alias declared for the root schema
+        #[clap(long, default_value = "Root")]
+        root_name: String,
+        #[clap(flatten)]
+        output: ImportOutput,
+    },
+    /// Translate the type aliases and interfaces of a TypeScript declaration file
+    Dts {
+        #[clap(value_name = "FILE")]
+        file: String,
+        #[clap(flatten)]
+        output: ImportOutput,
+    },
+    /// Translate the types of a GraphQL schema definition
+    Graphql {
+        #[clap(value_name = "FILE")]
+        file: String,
+        /// Type a custom scalar is translated to, written in the DSL, e.g. `--scalar Date=string`
+        #[clap(long = "scalar", value_name = "NAME=TYPE")]
+        scalars: Vec<String>,
+        #[clap(flatten)]
+        output: ImportOutput,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+struct ImportOutput {
+    #[clap(short, long, value_name = "FILE")]
+    output: Option<String>,
+    #[clap(long, value_enum, default_value_t)]
+    emit: Emit,
+}
+
+/// A threshold of `--hoist`, at least [`compile::MIN_HOIST_THRESHOLD`].
+fn hoist_threshold(value: &str) -> Result<usize, String> {
+    let threshold: usize = value.parse().map_err(|err| format!("{err}"))?;
+
+    if threshold < compile::MIN_HOIST_THRESHOLD {
+        return Err(format!(
+            "a subexpression is hoisted when it is repeated, the threshold must be at least {}",
+            compile::MIN_HOIST_THRESHOLD
+        ));
+    }
+
+    Ok(threshold)
+}
+
+/// Output language of an import.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum Emit {
+    #[default]
+    Dl,
+    Ts,
+    Dts,
+}
+
+/// Prints a diagnostic to stderr, human readable ones are followed by a blank line.
+fn report(diagnostic: &diagnostic::Diagnostic, file: &SourceFile, format: ErrorFormat) {
+    match format {
+        ErrorFormat::Human => eprintln!("{}\n", diagnostic.render(file)),
+        ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(file)),
+    }
+}
+
+/// Reads `file`, exiting if it can't be read.
+fn read(file: &str) -> SourceFile {
+    match std::fs::read_to_string(file) {
+        Ok(text) => SourceFile::new(file, text),
+        Err(err) => {
+            eprintln!("error: can't read `{file}`: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads the modules under `root`, exiting if they can't be read.
+fn read_modules(root: &Path) -> Vec<compile::project::Module> {
+    match compile::project::modules(root) {
+        Ok(modules) => modules,
+        Err(err) => {
+            eprintln!("error: can't read `{}`: {err}", root.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn import(import: Import, error_format: ErrorFormat) {
+    let (program, ImportOutput { output, emit }) = match import {
+        Import::JsonSchema {
+            file,
+            root_name,
+            output,
+        } => {
+            let source = read(&file);
+
+            match import::json_schema::from_schema(&source.text, &root_name) {
+                Ok(program) => (program, output),
+                Err(diagnostic) => {
+                    report(&diagnostic, &source, error_format);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Import::Dts { file, output } => {
+            let source = read(&file);
+
+            match import::dts::from_dts(&source.text) {
+                Ok(program) => (program, output),
+                Err(diagnostic) => {
+                    report(&diagnostic, &source, error_format);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Import::Graphql {
+            file,
+            scalars,
+            output,
+        } => {
+            let mut table = std::collections::HashMap::new();
+
+            for scalar in &scalars {
+                let Some((name, ty)) = scalar.split_once('=') else {
+                    eprintln!("expected `NAME=TYPE`, found `{scalar}`");
+                    std::process::exit(1);
+                };
+
+                match parse_type(ty) {
+                    Ok(ty) => {
+                        table.insert(name.trim().to_string(), ty);
+                    }
+                    Err(err) => {
+                        report(&err, &SourceFile::new("", ty), error_format);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            let source = read(&file);
+
+            match import::graphql::from_sdl(&source.text, &table) {
+                Ok(program) => (program, output),
+                Err(diagnostic) => {
+                    report(&diagnostic, &source, error_format);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    let width = compile::CompileOptions::default().width;
+
+    let out = match emit {
+        Emit::Dl => dsl::Pretty::render_pretty_dsl(&program, width),
+        Emit::Ts => compile::emit(&program.simplify(), compile::OutputKind::Ts, width),
+        Emit::Dts => compile::emit(&program.simplify(), compile::OutputKind::Dts, width),
+    };
+
+    if let Some(output_filename) = output {
+        std::fs::write(output_filename, out).unwrap();
+    } else {
+        print!("{}", out);
+    }
+}
+
+fn stats(file: &str, limits: &[analysis::Limit], error_format: ErrorFormat) {
+    let source = read(file);
+
+    let program = match parse_source(&source.text) {
+        Ok(program) => program.simplify(),
+        Err(err) => {
+            report(&err, &source, error_format);
+            std::process::exit(1);
+        }
+    };
+
+    print!("{}", analysis::table(&analysis::exported_aliases(&program)));
+
+    let diagnostics = analysis::check_limits(&program, limits);
+
+    for diagnostic in &diagnostics {
+        report(diagnostic, &source, error_format);
+    }
+
+    if !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn graph(file: &str, format: analysis::GraphFormat, error_format: ErrorFormat) {
+    let source = read(file);
+
+    let program = match parse_source(&source.text) {
+        Ok(program) => program.simplify(),
+        Err(err) => {
+            report(&err, &source, error_format);
+            std::process::exit(1);
+        }
+    };
+
+    print!("{}", analysis::dependency_graph(&program).render(format));
+}
+
+fn ast(file: &str, error_format: ErrorFormat) {
+    let source = read(file);
+
+    let program = match parse_source(&source.text) {
+        Ok(program) => program,
+        Err(err) => {
+            report(&err, &source, error_format);
+            std::process::exit(1);
+        }
+    };
+
+    let json = program.to_json().expect("programs serialize");
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn describe(file: &str, name: &str, format: analysis::DescribeFormat, error_format: ErrorFormat) {
+    let source = read(file);
+
+    let program = match parse_source(&source.text) {
+        Ok(program) => program,
+        Err(err) => {
+            report(&err, &source, error_format);
+            std::process::exit(1);
+        }
+    };
+
+    match analysis::describe(&program, name) {
+        Ok(description) => print!("{}", description.render(format)),
+        Err(message) => {
+            eprintln!("error: {message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn diff(old: &str, new: &str, fail_on: Option<diff::Compatibility>, error_format: ErrorFormat) {
+    let simplified = |file: &str| {
+        let source = read(file);
+
+        match parse_source(&source.text) {
+            Ok(program) => program.simplify(),
+            Err(err) => {
+                report(&err, &source, error_format);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let changes = diff::compare(&simplified(old), &simplified(new));
+
+    print!("{}", diff::report(&changes));
+
+    if let Some(fail_on) = fail_on {
+        if changes.iter().any(|change| change.compatibility >= fail_on) {
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs a session on the terminal, compiling with the options of the config of the working
+/// directory.
+fn repl() {
+    let options = config::load(Path::new("."))
+        .map(|(_, config)| config.compile_options())
+        .unwrap_or_default();
+
+    let mut session = repl::Session::new(options);
+
+    repl::run(std::io::stdin().lock(), std::io::stdout(), &mut session).unwrap();
+}
+
+/// Renames a declaration across the modules under `root`, writing the modules with edits, and
+/// prints how many edits each has.
+fn rename(
+    root: &Path,
+    target: &rewrite::rename::Target,
+    new_name: &str,
+    error_format: ErrorFormat,
+) {
+    use compile::project::{self, ModuleDiagnostic};
+    use rewrite::rename::RenameError;
+
+    let modules = read_modules(root);
+
+    let renamed = match rewrite::rename::rename(&modules, target, new_name) {
+        Ok(renamed) => renamed,
+        Err(RenameError::Diagnostics(diagnostics)) => {
+            let sources = project::sources(&modules);
+
+            for ModuleDiagnostic { diagnostic, .. } in &diagnostics {
+                report(
+                    diagnostic,
+                    sources.get(diagnostic.file.unwrap()),
+                    error_format,
+                );
+            }
+
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let plural = |count: usize| if count == 1 { "" } else { "s" };
+
+    for ((path, source), (_, edits)) in renamed.apply(&modules).iter().zip(&renamed.edits) {
+        std::fs::write(root.join(path), source).unwrap();
+        println!(
+            "{}: {} edit{}",
+            path.display(),
+            edits.len(),
+            plural(edits.len())
+        );
+    }
+
+    let files = renamed.edits.len();
+
+    println!(
+        "renamed `{}` to `{new_name}` in {files} file{}",
+        renamed.old_name,
+        plural(files)
+    );
+}
+
+/// Applies the suggested fixes to each file, with the lint levels of the config nearest to it, and
+/// prints the problems fixed.
+fn fix(files: &[String], error_format: ErrorFormat) {
+    let mut fixed = 0;
+    let mut failed = false;
+
+    for file in files {
+        let source = read(file).text;
+
+        let dir = Path::new(file)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let levels = config::load(dir)
+            .map(|(_, config)| config.lints)
+            .unwrap_or_default();
+
+        match rewrite::fix(&source, &levels) {
+            Ok((_, diagnostics)) if diagnostics.is_empty() => {}
+            Ok((fixed_source, diagnostics)) => {
+                std::fs::write(file, fixed_source).unwrap();
+
+                for diagnostic in &diagnostics {
+                    let suggestion = diagnostic.suggestion.as_ref().unwrap();
+                    println!("{file}: {}, {}", diagnostic.message, suggestion.message);
+                }
+
+                fixed += diagnostics.len();
+            }
+            Err(diagnostic) => {
+                failed = true;
+                report(&diagnostic, &SourceFile::new(file, source), error_format);
+            }
+        }
+    }
+
+    println!("fixed {fixed} problem{}", if fixed == 1 { "" } else { "s" });
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Compiles each file with the options of the config nearest to it and reports the diagnostics
+/// not in the baseline, or records them all in it with `write_baseline`.
+fn check(
+    files: &[String],
+    baseline: Option<&str>,
+    write_baseline: bool,
+    error_format: ErrorFormat,
+) {
+    use diagnostic::baseline::Baseline;
+
+    let sources: Vec<SourceFile> = files.iter().map(|file| read(file)).collect();
+
+    let mut diagnostics = vec![];
+
+    for source in &sources {
+        let dir = Path::new(&source.name)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let options = config::load(dir)
+            .map(|(_, config)| config.compile_options())
+            .unwrap_or_default();
+
+        let (Ok(compile::CompileOutput {
+            diagnostics: found, ..
+        })
+        | Err(found)) = compile::compile(&source.text, &options);
+
+        diagnostics.extend(found.into_iter().map(|diagnostic| (source, diagnostic)));
+    }
+
+    let stale = match baseline {
+        Some(path) if write_baseline => {
+            let baseline = Baseline::new(
+                diagnostics
+                    .iter()
+                    .map(|(file, diagnostic)| (*file, diagnostic)),
+            );
+            std::fs::write(path, baseline.to_json()).unwrap();
+
+            let count = baseline.entries.len();
+            println!(
+                "recorded {count} diagnostic{} in {path}",
+                if count == 1 { "" } else { "s" }
+            );
+            return;
+        }
+        Some(path) => {
+            let baseline = std::fs::read_to_string(path)
+                .map_err(|err| format!("can't read the baseline {path}: {err}"))
+                .and_then(|json| Baseline::from_json(&json));
+
+            match baseline {
+                Ok(baseline) => {
+                    baseline.apply(&mut diagnostics, |(file, diagnostic)| (*file, diagnostic))
+                }
+                Err(message) => {
+                    eprintln!("error: {message}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => vec![],
+    };
+
+    for (file, diagnostic) in &diagnostics {
+        report(diagnostic, file, error_format);
+    }
+
+    for entry in &stale {
+        println!(
+            "{}: fixed, remove it from the baseline: {}",
+            entry.file, entry.message
+        );
+    }
+
+    if !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Compiles the modules under `root` to `out_dir`, reporting the diagnostics of each module with
+/// its path.
+fn project(
+    root: &Path,
+    out_dir: &Path,
+    options: &compile::CompileOptions,
+    barrel: bool,
+    error_format: ErrorFormat,
+) {
+    use compile::project::{self, ModuleDiagnostic};
+
+    let modules = read_modules(root);
+    let sources = project::sources(&modules);
+
+    let report_all = |diagnostics: &[ModuleDiagnostic]| {
+        for ModuleDiagnostic { diagnostic, .. } in diagnostics {
+            let file = sources.get(diagnostic.file.unwrap());
+
+            report(diagnostic, file, error_format);
+        }
+    };
+
+    match project::compile_project(&modules, options, barrel) {
+        Ok(output) => {
+            report_all(&output.diagnostics);
+
+            if let Err(err) = project::write(out_dir, &output.files) {
+                eprintln!("error: can't write to `{}`: {err}", out_dir.display());
+                std::process::exit(1);
+            }
+        }
+        Err(diagnostics) => {
+            report_all(&diagnostics);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Compiles `input` statement by statement to `output`, or to stdout.
+fn streaming(
+    input: &SourceFile,
+    output: Option<&str>,
+    options: &compile::CompileOptions,
+    timings_format: TimingsFormat,
+    error_format: ErrorFormat,
+) {
+    use std::io::Write;
+
+    let mut out: Box<dyn Write> = match output {
+        Some(output_filename) => Box::new(std::fs::File::create(output_filename).unwrap()),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut out = std::io::BufWriter::new(&mut out);
+
+    let (result, timings) = compile::compile_streaming_timed(&input.text, options, |typescript| {
+        out.write_all(typescript.as_bytes()).unwrap()
+    });
+
+    out.flush().unwrap();
+
+    let diagnostics = match &result {
+        Ok(diagnostics) | Err(diagnostics) => diagnostics,
+    };
+
+    for diagnostic in diagnostics {
+        report(diagnostic, input, error_format);
+    }
+
+    if timings.is_enabled() {
+        eprintln!("{}", timings.render(timings_format));
+    }
+
+    if result.is_err() {
+        std::process::exit(1);
+    }
 }
 
 fn main() {
     let args = Args::parse();
 
-    let input_source = if let Some(input_filename) = args.input {
-        std::fs::read_to_string(input_filename).unwrap()
+    match args.command {
+        Some(Command::Import(command)) => return import(command, args.error_format),
+        Some(Command::Stats { file, limits }) => return stats(&file, &limits, args.error_format),
+        Some(Command::Graph { file, format }) => return graph(&file, format, args.error_format),
+        Some(Command::Describe { file, name, format }) => {
+            return describe(&file, &name, format, args.error_format)
+        }
+        Some(Command::Diff { old, new, fail_on }) => {
+            return diff(&old, &new, fail_on, args.error_format)
+        }
+        Some(Command::Rename {
+            at,
+            old,
+            new_name,
+            root,
+        }) => {
+            let target = at.or(old).expect("clap requires a target");
+
+            return rename(Path::new(&root), &target, &new_name, args.error_format);
+        }
+        Some(Command::Fix { files }) => return fix(&files, args.error_format),
+        Some(Command::Check {
+            files,
+            baseline,
+            write_baseline,
+        }) => {
+            return check(
+                &files,
+                baseline.as_deref(),
+                write_baseline,
+                args.error_format,
+            )
+        }
+        Some(Command::Explain { code }) => match diagnostic::codes::explain(&code) {
+            Ok(explanation) => return print!("{explanation}"),
+            Err(message) => {
+                eprintln!("error: {message}");
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Ast { file }) => return ast(&file, args.error_format),
+        Some(Command::Schema) => return print!("{}", ast::schema::SCHEMA),
+        Some(Command::Repl) => return repl(),
+        None => {}
+    }
+
+    let input_is_dir = args.out_dir.is_some();
+
+    let input_source = if input_is_dir {
+        String::new()
+    } else if let Some(input_filename) = &args.input {
+        read(input_filename).text
     } else {
         let mut input = String::new();
         std::io::stdin().read_to_string(&mut input).unwrap();
         input
     };
 
-    let input = input_source.as_str();
+    let input = SourceFile::new(args.input.as_deref().unwrap_or_default(), input_source);
 
-    let result = parser::parse_newtype_program(input);
+    let flags = config::Config {
+        width: args.width,
+        emit: args.emit,
+        timings: args.timings.then_some(true),
+        merge_arms: args.merge_arms.then_some(true),
+        extract_exclude: args.extract_exclude.then_some(true),
+        features: (!args.features.is_empty()).then_some(args.features),
+        flags: (!args.flags.is_empty()).then_some(args.flags),
+        hoist: args.hoist,
+        brand: args.brand_encoding,
+        only_tag: args.only_tag,
+        target: args.target,
+        strict: args.strict.then_some(true),
+        prettier_compat: args.prettier_compat.then_some(true),
+        quote: args.quote,
+        const_companions: args.emit_const_companions.then_some(true),
+        missing_else: args.missing_else,
+        lower_conditions: args.lower_conditions.then_some(true),
+        max_emit_bytes_per_alias: args.max_emit_bytes_per_alias,
+        max_nesting: args.max_nesting,
+        ..Default::default()
+    };
+
+    // The config file is looked up from the directory of the input, flags override its options.
+    let start_dir = args
+        .input
+        .as_deref()
+        .map(Path::new)
+        .and_then(|input| {
+            if input_is_dir {
+                Some(input)
+            } else {
+                input.parent()
+            }
+        })
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let config = match config::load(start_dir) {
+        Some((path, config)) => {
+            // A file that can't be read has its error in the diagnostics.
+            let source = SourceFile::new(
+                path.display().to_string(),
+                std::fs::read_to_string(&path).unwrap_or_default(),
+            );
+
+            for diagnostic in &config.diagnostics {
+                report(diagnostic, &source, args.error_format);
+            }
+
+            if config
+                .diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.severity == diagnostic::Severity::Error)
+            {
+                std::process::exit(1);
+            }
+
+            config.merge(flags)
+        }
+        None => flags,
+    };
+
+    let options = CompileOptions {
+        measure_sizes: args.emit_size_report.is_some(),
+        ..config.compile_options()
+    };
+
+    if let (Some(root), Some(out_dir)) = (&args.input, &args.out_dir) {
+        return project(
+            Path::new(root),
+            Path::new(out_dir),
+            &options,
+            args.barrel,
+            args.error_format,
+        );
+    }
+
+    if args.streaming {
+        return streaming(
+            &input,
+            args.output.as_deref(),
+            &options,
+            args.timings_format,
+            args.error_format,
+        );
+    }
+
+    let (result, sizes) = compile::compile_measured(&input.text, &options);
+
+    // The report follows the output, and lists the sizes of the aliases over the limit too.
+    let report_sizes = || {
+        if let Some(format) = args.emit_size_report {
+            print!("{}", compile::size::render(&sizes, format));
+        }
+    };
 
     match result {
         Ok(result) => {
-            let simplified = result.simplify();
+            for diagnostic in &result.diagnostics {
+                report(diagnostic, &input, args.error_format);
+            }
 
-            let out = simplified.render_pretty_ts(120);
+            let out = result.typescript;
 
+            // The schemas are written next to the output, or follow it on stdout.
             if let Some(output_filename) = args.output {
+                if let Some(zod) = result.zod {
+                    let path = Path::new(&output_filename).with_extension(zod::EXTENSION);
+                    std::fs::write(path, zod).unwrap();
+                }
+
                 std::fs::write(output_filename, out).unwrap();
             } else {
-                println!("{}", out);
+                print!("{}", out);
+
+                if let Some(zod) = result.zod {
+                    print!("\n{zod}");
+                }
             }
+
+            if result.timings.is_enabled() {
+                eprintln!("{}", result.timings.render(args.timings_format));
+            }
+
+            report_sizes();
         }
-        Err(error) => {
-            eprintln!("{}", error);
+        Err(diagnostics) => {
+            for diagnostic in &diagnostics {
+                report(diagnostic, &input, args.error_format);
+            }
+
+            report_sizes();
+
+            std::process::exit(1);
         }
     }
 }
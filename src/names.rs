@@ -0,0 +1,291 @@
+/// Fresh names for declarations generated by the compiler.
+use std::{
+    collections::{BTreeMap, HashSet},
+    rc::Rc,
+};
+
+use crate::{
+    ast::{
+        visit::{
+            walk_interface, walk_mapped_type, walk_mapped_type_mut, walk_object_property,
+            walk_object_property_mut, Visit, VisitMut,
+        },
+        Ast, Ident, Interface, MappedType, ObjectProperty, ObjectPropertyKey, Span,
+    },
+    typescript::is_reserved_word,
+};
+
+/// Prefix of every generated name.
+pub const GENERATED_PREFIX: &str = "__dl_";
+
+/// Generates names for one output file, like `__dl_helper_3`, that are distinct from every name of
+/// the program and from each other.
+///
+/// Each prefix is numbered on its own, so the names a pass generates only depend on its own
+/// requests and not on what the passes before it generated.
+#[derive(Debug, Clone, Default)]
+pub struct Gensym {
+    /// Names declared or referenced in the program and names already generated.
+    taken: HashSet<String>,
+    /// Next number of each prefix.
+    counters: BTreeMap<String, usize>,
+}
+
+impl Gensym {
+    /// A generator for names that don't collide with any name of `program`.
+    pub fn new(program: &Ast) -> Self {
+        Self {
+            taken: symbols(program),
+            counters: BTreeMap::new(),
+        }
+    }
+
+    /// A name starting with `__dl_<prefix>_` that is neither a name of the program nor a name
+    /// generated before.
+    pub fn fresh(&mut self, prefix: &str) -> Ident {
+        let counter = self.counters.entry(prefix.to_string()).or_default();
+
+        let name = loop {
+            let name = format!("{GENERATED_PREFIX}{prefix}_{counter}");
+            *counter += 1;
+
+            if !self.taken.contains(&name) {
+                break name;
+            }
+        };
+
+        self.taken.insert(name.clone());
+
+        Ident {
+            name,
+            span: Span::default(),
+            raw: false,
+        }
+    }
+
+    /// Keeps `names` from being generated, names of the program that are not in the tree it was
+    /// created from.
+    pub fn reserve<'n>(&mut self, names: impl IntoIterator<Item = &'n str>) {
+        self.taken.extend(names.into_iter().map(str::to_string));
+    }
+
+    /// A name for a declaration of the program named `name` to be renamed to, `name_` or else
+    /// `name_2`, `name_3`... that is neither a name of the program nor a name generated before.
+    pub fn alternative(&mut self, name: &str) -> String {
+        let name = std::iter::once(format!("{name}_"))
+            .chain((2..).map(|n| format!("{name}_{n}")))
+            .find(|candidate| !self.taken.contains(candidate))
+            .unwrap();
+
+        self.taken.insert(name.clone());
+
+        name
+    }
+}
+
+/// Renames the keys of mapped types named after a TypeScript reserved word, `new` in
+/// `{[new in K]: new}`, which TypeScript rejects. The key is only bound in its mapped type, so
+/// renaming it changes nothing else; the other names are checked by `lint::reserved_names`.
+pub fn rename_reserved_keys(program: &Ast) -> Ast {
+    let mut program = program.clone();
+
+    ReservedKeys {
+        gensym: Gensym::new(&program),
+    }
+    .visit_ast_mut(&mut program);
+
+    program
+}
+
+struct ReservedKeys {
+    gensym: Gensym,
+}
+
+impl ReservedKeys {
+    /// An alternative to `key` if it is reserved, with the references to it in `scope` renamed.
+    fn rename<'s>(&mut self, key: &mut String, scope: impl IntoIterator<Item = &'s mut Ast>) {
+        if !is_reserved_word(key) {
+            return;
+        }
+
+        let name = self.gensym.alternative(key);
+
+        let mut references = References {
+            from: key,
+            to: &name,
+        };
+
+        for node in scope {
+            references.visit_ast_mut(node);
+        }
+
+        *key = name;
+    }
+}
+
+impl VisitMut for ReservedKeys {
+    // The mapped types nested in one are renamed first, so that the references left are to its
+    // own key.
+    fn visit_mapped_type_mut(&mut self, mapped: &mut MappedType) {
+        walk_mapped_type_mut(self, mapped);
+
+        let scope = mapped
+            .remapped_as
+            .iter_mut()
+            .chain([&mut mapped.body])
+            .map(Rc::make_mut);
+
+        self.rename(&mut mapped.index, scope);
+    }
+
+    fn visit_object_property_mut(&mut self, property: &mut ObjectProperty) {
+        walk_object_property_mut(self, property);
+
+        if let ObjectPropertyKey::Index(index) = &mut property.key {
+            let scope = index.remapped_as.iter_mut().chain([&mut property.value]);
+
+            self.rename(&mut index.key, scope);
+        }
+    }
+}
+
+/// Renames the references to a name, members accessed with `.` or `::` aside.
+struct References<'n> {
+    from: &'n str,
+    to: &'n str,
+}
+
+impl VisitMut for References<'_> {
+    fn visit_ident_mut(&mut self, ident: &mut Ident) {
+        if ident.name == self.from {
+            ident.name = self.to.to_string();
+        }
+    }
+}
+
+/// Every name declared or referenced in `program`.
+fn symbols(program: &Ast) -> HashSet<String> {
+    let mut symbols = Symbols::default();
+    symbols.visit_ast(program);
+    symbols.0
+}
+
+#[derive(Default)]
+struct Symbols(HashSet<String>);
+
+impl<'a> Visit<'a> for Symbols {
+    fn visit_ident(&mut self, ident: &'a Ident) {
+        self.0.insert(ident.name.clone());
+    }
+
+    fn visit_interface(&mut self, interface: &'a Interface) {
+        self.0.insert(interface.name.clone());
+        self.0.extend(interface.extends.clone());
+
+        walk_interface(self, interface);
+    }
+
+    fn visit_mapped_type(&mut self, mapped: &'a MappedType) {
+        self.0.insert(mapped.index.clone());
+
+        walk_mapped_type(self, mapped);
+    }
+
+    fn visit_object_property(&mut self, property: &'a ObjectProperty) {
+        if let ObjectPropertyKey::Index(index) = &property.key {
+            self.0.insert(index.key.clone());
+        }
+
+        walk_object_property(self, property);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::parse, typescript::Pretty};
+    use pretty_assertions::assert_eq;
+
+    fn names(gensym: &mut Gensym, prefix: &str, n: usize) -> Vec<String> {
+        (0..n).map(|_| gensym.fresh(prefix).name).collect()
+    }
+
+    #[test]
+    fn skips_names_of_the_program() {
+        let program = parse!(
+            "type __dl_helper_0 as 1\n\
+             interface I { a: __dl_helper_2 }\n\
+             type A(__dl_helper_3) as __dl_helper_3"
+        );
+
+        let mut gensym = Gensym::new(&program);
+
+        assert_eq!(
+            names(&mut gensym, "helper", 3),
+            vec!["__dl_helper_1", "__dl_helper_4", "__dl_helper_5"]
+        );
+    }
+
+    #[test]
+    fn deterministic() {
+        let program = parse!("type __dl_block_0 as 1");
+
+        let run = || {
+            let mut gensym = Gensym::new(&program);
+            let mut names = names(&mut gensym, "block", 2);
+            names.extend(self::names(&mut gensym, "fold", 2));
+            names
+        };
+
+        assert_eq!(run(), run());
+        assert_eq!(
+            run(),
+            vec!["__dl_block_1", "__dl_block_2", "__dl_fold_0", "__dl_fold_1"]
+        );
+    }
+
+    #[test]
+    fn alternatives() {
+        let program = parse!("type new as new_\ntype A(new_2) as 1");
+
+        let mut gensym = Gensym::new(&program);
+
+        assert_eq!(gensym.alternative("new"), "new_3");
+        assert_eq!(gensym.alternative("new"), "new_4");
+        assert_eq!(gensym.alternative("infer"), "infer_");
+    }
+
+    #[test]
+    fn reserved_keys() {
+        let program = parse!(
+            "type new_ as 1\n\
+             type A(T) as map new in keyof(T) do [new, new_, T::new] end\n\
+             type B(K) as {[new in K]: map new in K do new end}"
+        );
+
+        assert_eq!(
+            rename_reserved_keys(&program.simplify())
+                .render_pretty_ts(80)
+                .trim_end(),
+            "type new_ = 1;\n\n\
+             type A<T> = { [new_2 in keyof T]: [new_2, new_, T.new] };\n\n\
+             type B<K> = {[new_4 in K]: { [new_3 in K]: new_3 }};"
+        );
+    }
+
+    #[test]
+    fn unique_across_passes() {
+        let program = parse!("type A as 1");
+
+        let mut gensym = Gensym::new(&program);
+
+        // A pass generating names doesn't change those of another prefix.
+        let first = names(&mut gensym, "helper", 2);
+        let second = names(&mut gensym, "assert", 2);
+        let third = names(&mut gensym, "helper", 2);
+
+        assert_eq!(first, vec!["__dl_helper_0", "__dl_helper_1"]);
+        assert_eq!(second, vec!["__dl_assert_0", "__dl_assert_1"]);
+        assert_eq!(third, vec!["__dl_helper_2", "__dl_helper_3"]);
+    }
+}
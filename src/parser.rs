@@ -1,14 +1,19 @@
 pub(crate) mod pratt;
+pub(crate) mod trivia;
 
 use std::{
+    cell::Cell,
     collections::{BTreeSet, HashMap},
     default,
     iter::FilterMap,
     rc::Rc,
+    sync::Mutex,
 };
 
 use crate::{
     ast::{macros::*, *},
+    diagnostic::{codes, Diagnostic},
+    error::humanize_pest,
     typescript,
 };
 
@@ -16,15 +21,21 @@ use cond_expr::CondExpr;
 use if_expr::IfExpr;
 use itertools::Itertools;
 
-use let_expr::LetExpr;
+use attribute::{AttrValue, Attribute};
+use brand_declaration::BrandDeclaration;
+use let_expr::{Binding, LetExpr};
 use match_expr::MatchExpr;
+use pattern_declaration::PatternDeclaration;
 use pest::{
     error::{Error, ErrorVariant},
     pratt_parser::PrattParser,
     Parser,
 };
+use syntax_error::SyntaxError;
+use union_declaration::UnionDeclaration;
 
-use pratt::{EXPR_PARSER, EXTENDS_PARSER};
+use pratt::{Fixity, CONDITION_OPERATORS, EXPR_OPERATORS, EXPR_PARSER, EXTENDS_PARSER};
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
@@ -35,6 +46,117 @@ pub type ParserError = Error<Rule>;
 pub type Pair<'i> = pest::iterators::Pair<'i, Rule>;
 pub type Pairs<'i> = pest::iterators::Pairs<'i, Rule>;
 
+/// Most expressions an expression can be nested in by default, see [`with_max_nesting`].
+pub const DEFAULT_MAX_NESTING: usize = 512;
+
+thread_local! {
+    /// Most expressions the parser accepts an expression nested in, and how many the one being
+    /// parsed is nested in.
+    static NESTING: Cell<(usize, usize)> = const { Cell::new((DEFAULT_MAX_NESTING, 0)) };
+}
+
+/// Runs `f` with the parser accepting an expression nested in at most `limit` others. Pest, the
+/// parser and the passes after it recurse into each, so that a program nesting thousands of them,
+/// as generated ones sometimes do, would overflow the stack; a deeper expression is a syntax error
+/// instead.
+pub(crate) fn with_max_nesting<T>(limit: usize, f: impl FnOnce() -> T) -> T {
+    let previous = NESTING.replace((limit, 0));
+    let result = f();
+    NESTING.set(previous);
+    result
+}
+
+/// Parses `source` as `rule`. On failure the source is parsed again, recording the literal tokens
+/// expected at the failure position for `error::humanize_pest`. Pest records them for every parse
+/// of the process once switched on, and recording them slows it down, so it is only on for the
+/// second parse.
+fn parse_rule(rule: Rule, source: &str) -> Result<Pairs<'_>, Box<Error<Rule>>> {
+    static ERROR_DETAIL: Mutex<()> = Mutex::new(());
+
+    NewtypeParser::parse(rule, source).or_else(|_| {
+        let _detail = ERROR_DETAIL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        pest::set_error_detail(true);
+        let parsed = NewtypeParser::parse(rule, source);
+        pest::set_error_detail(false);
+
+        parsed.map_err(Box::new)
+    })
+}
+
+fn nesting_error(limit: usize) -> String {
+    format!("expression nesting exceeds limit of {limit}")
+}
+
+/// The bracket of `source` past the nesting limit, as an error, checked before pest recurses into
+/// it.
+fn check_bracket_nesting(source: &str) -> Result<(), Box<Error<Rule>>> {
+    let (limit, _) = NESTING.get();
+
+    match trivia::bracket_nesting_over(source, limit) {
+        Some(offset) => Err(Box::new(
+            Span::new(offset, offset + 1).as_custom_error(source, nesting_error(limit)),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Parses the expression `pair` one level deeper than its parent, or, when it is nested in more
+/// expressions than [`with_max_nesting`] allows, stands a syntax error in for it without parsing
+/// what it nests. Each prefix and postfix operator nests its operand one level deeper,
+/// `keyof keyof T` and `T[][]` as much as `[[T]]`.
+fn parse_nested(pair: Pair) -> Ast {
+    struct Depth(usize, usize);
+
+    impl Drop for Depth {
+        fn drop(&mut self) {
+            NESTING.set((self.0, self.1));
+        }
+    }
+
+    let (limit, depth) = NESTING.get();
+
+    let too_deep = |span| {
+        Ast::SyntaxError(SyntaxError {
+            span,
+            diagnostic: Box::new(
+                Diagnostic::error(nesting_error(limit), span).with_code(codes::SYNTAX_ERROR),
+            ),
+        })
+    };
+
+    if depth > limit {
+        return too_deep(Span::from(&pair));
+    }
+
+    // The longest chain of operators applied to an operand, the infix operators between operands
+    // start a new one.
+    let mut chain = 0;
+    let mut longest = 0;
+
+    for operator in pair.clone().into_inner() {
+        match operator.as_rule() {
+            Rule::infer
+            | Rule::builtin_prefix
+            | Rule::indexed_access
+            | Rule::array_modifier
+            | Rule::application => chain += 1,
+            _ => chain = 0,
+        }
+
+        if depth + chain > limit {
+            return too_deep(Span::from(&operator));
+        }
+
+        longest = longest.max(chain);
+    }
+
+    let _depth = Depth(limit, depth);
+    NESTING.set((limit, depth + 1 + longest));
+
+    parse_expr(pair.into_inner())
+}
+
 pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
     use Rule::*;
 
@@ -42,8 +164,22 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
         .map_primary(parse)
         .map_prefix(|op, child| match op.as_rule() {
             infer => Ast::Infer(child.into()),
+            builtin_prefix => {
+                let span = Span::from(&op).merge(&child.as_span());
+                let name = op.into_inner().find(match_tag("name")).unwrap();
+
+                Ast::Builtin(Builtin {
+                    name: parse_builtin_keyword(name),
+                    argument: child.into(),
+                    span,
+                })
+            }
             rule => {
-                parse_error!(op, vec![infer], vec![rule]);
+                parse_error!(
+                    op,
+                    pratt::rules(EXPR_OPERATORS, Fixity::is_prefix),
+                    vec![rule]
+                );
             }
         })
         .map_postfix(|lhs, op| match op.as_rule() {
@@ -57,7 +193,7 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
                 Ast::Access(Access {
                     lhs,
                     rhs,
-                    is_dot: false,
+                    kind: AccessKind::Index,
                     span,
                 })
             }
@@ -74,7 +210,11 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
                 })
             }
             rule => {
-                parse_error!(op, vec![indexed_access, array_modifier], vec![rule]);
+                parse_error!(
+                    op,
+                    pratt::rules(EXPR_OPERATORS, Fixity::is_postfix),
+                    vec![rule]
+                );
             }
         })
         .map_infix(|lhs, op, rhs| {
@@ -85,22 +225,33 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
             }
 
             let ast = match op.as_rule() {
-                union => Ast::UnionType(UnionType {
-                    types: vec![lhs, rhs],
-                    span,
-                }),
+                // A chain of the operator is a single node, as one nested for each operand would
+                // be too deep to walk for a wide union.
+                union => match lhs {
+                    Ast::UnionType(UnionType { mut types, .. }) => {
+                        types.push(rhs);
+                        Ast::UnionType(UnionType { types, span })
+                    }
+                    lhs => Ast::UnionType(UnionType {
+                        types: vec![lhs, rhs],
+                        span,
+                    }),
+                },
 
-                intersection => Ast::IntersectionType(IntersectionType {
-                    types: vec![lhs, rhs],
-                    span,
-                }),
+                intersection => match lhs {
+                    Ast::IntersectionType(IntersectionType { mut types, .. }) => {
+                        types.push(rhs);
+                        Ast::IntersectionType(IntersectionType { types, span })
+                    }
+                    lhs => Ast::IntersectionType(IntersectionType {
+                        types: vec![lhs, rhs],
+                        span,
+                    }),
+                },
 
                 colon2 => {
-                    let mut acc = vec![];
-
-                    match lhs {
-                        Ast::Path(Path { segments, .. }) => acc.extend(segments.to_owned()),
-                        Ast::Ident(_) => acc.push(lhs),
+                    let rhs = match rhs {
+                        Ast::Ident(_) => rhs,
                         ast => {
                             let error = ast.as_span().as_parsing_error(
                                 op.get_input(),
@@ -111,9 +262,13 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
                         }
                     };
 
-                    match rhs {
-                        Ast::Path(Path { segments, .. }) => acc.extend(segments.to_owned()),
-                        Ast::Ident(_) => acc.push(rhs),
+                    match lhs {
+                        Ast::Ident(_)
+                        | Ast::ImportType(_)
+                        | Ast::Access(Access {
+                            kind: AccessKind::Namespace,
+                            ..
+                        }) => {}
                         ast => {
                             let error = ast.as_span().as_parsing_error(
                                 op.get_input(),
@@ -124,16 +279,18 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
                         }
                     };
 
-                    Ast::Path(Path {
+                    Ast::Access(Access {
+                        lhs: lhs.into(),
+                        rhs: rhs.into(),
+                        kind: AccessKind::Namespace,
                         span,
-                        segments: acc,
                     })
                 }
 
                 dot_op => Ast::Access(Access {
                     lhs: lhs.into(),
                     rhs: rhs.into(),
-                    is_dot: true,
+                    kind: AccessKind::Dot,
                     span,
                 }),
 
@@ -146,7 +303,7 @@ pub(crate) fn parse_expr(pairs: Pairs) -> Ast {
 }
 
 /// Replace the pipe operator macro with a type application
-/// ```
+/// ```text
 /// A |> B
 /// # B(A)
 /// ```
@@ -189,16 +346,238 @@ fn replace_pipe_with_type_application(rhs: Ast, lhs: Ast, op: Pair) -> Ast {
     }
 }
 
+/// Parses `source`, failing on the first syntax error, including those recovered from.
 pub(crate) fn parse_newtype_program(source: &str) -> Result<Ast, Box<Error<Rule>>> {
-    let pair = NewtypeParser::parse(Rule::program, source)?.next().unwrap();
+    let (program, errors) = parse_program_with_errors(source)?;
+
+    match errors.first() {
+        Some(error) => Err(Box::new(
+            error.span.as_custom_error(source, error.message.clone()),
+        )),
+        None => Ok(program),
+    }
+}
+
+/// Parses `source`, with the first syntax error as a diagnostic.
+pub fn parse_source(source: &str) -> Result<Ast, Diagnostic> {
+    parse_newtype_program(source).map_err(|err| humanize_pest(*err, source))
+}
+
+/// Parses `source`, together with the errors of the properties, tuple elements and match arms the
+/// grammar skipped to parse the rest of their construct, see [`SyntaxError`]. Fails on the
+/// syntax errors it can't recover from.
+pub(crate) fn parse_program_with_errors(
+    source: &str,
+) -> Result<(Ast, Vec<Diagnostic>), Box<Error<Rule>>> {
+    check_bracket_nesting(source)?;
+
+    let pair = parse_rule(Rule::program, source)
+        .map_err(|error| match trivia::unterminated_block_comment(source) {
+            Some(offset) => Box::new(
+                Span::new(offset, offset + 2)
+                    .as_custom_error(source, "unterminated block comment".to_string()),
+            ),
+            None => error,
+        })?
+        .next()
+        .unwrap();
+
+    let program = parse(pair);
+    let errors = program.syntax_errors();
+
+    Ok((program, errors))
+}
+
+/// The `#options` pragmas at the top of `source`, before its first statement, with their
+/// offsets, and the offset the first statement starts at, or the end of `source`. The pragmas in
+/// comments are comments.
+pub(crate) fn header_pragmas(source: &str) -> (Vec<(usize, &str)>, usize) {
+    let header = parse_rule(Rule::module_header, source)
+        .expect("any source starts with a header")
+        .next()
+        .unwrap();
+
+    let pragmas = header
+        .clone()
+        .into_inner()
+        .map(|pragma| (pragma.as_span().start(), pragma.as_str()))
+        .collect();
+
+    (pragmas, header.as_span().end())
+}
+
+/// Parses the statements of `source` one at a time, for a program too large to be held as a whole
+/// to be compiled statement by statement. Stops after a syntax error.
+pub(crate) fn statements(
+    source: &str,
+    max_nesting: usize,
+) -> impl Iterator<Item = Result<Ast, Diagnostic>> + '_ {
+    let mut offset = 0;
+    let mut previous_end = 0;
+    let mut failed = false;
+
+    std::iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+
+        let rest = &source[offset..];
+
+        // Pest indexes the lines of its whole input, hence the statement is parsed from a chunk
+        // of the source, grown until it holds the whole statement.
+        let mut end = next_statement_start(rest, rest.len() - rest.trim_start().len());
+
+        let parsed = loop {
+            let chunk = &rest[..end];
+            // A shorter chunk is expected to fail, only the error of the whole rest is reported.
+            let parsed = with_max_nesting(max_nesting, || {
+                check_bracket_nesting(chunk).map_err(|error| *error)?;
+
+                if end == rest.len() {
+                    parse_rule(Rule::next_statement, chunk).map_err(|error| *error)
+                } else {
+                    NewtypeParser::parse(Rule::next_statement, chunk)
+                }
+            });
+
+            match parsed {
+                Ok(mut pairs) => {
+                    let pair = pairs.next().unwrap();
+
+                    if matches!(pair.as_rule(), Rule::statement | Rule::conditional_block)
+                        || end == rest.len()
+                    {
+                        break Ok(pair);
+                    }
+                }
+                Err(error) if end == rest.len() => break Err(error),
+                Err(_) => {}
+            }
+
+            end = next_statement_start(rest, 2 * end);
+        };
+
+        let pair = match parsed {
+            Ok(pair) => pair,
+            Err(error) => {
+                failed = true;
+
+                let error = match trivia::unterminated_block_comment(rest) {
+                    Some(start) => Span::new(start, start + 2)
+                        .as_custom_error(rest, "unterminated block comment".to_string()),
+                    None => error,
+                };
+
+                let mut diagnostic = humanize_pest(error, rest);
+                diagnostic.span =
+                    Span::new(diagnostic.span.start + offset, diagnostic.span.end + offset);
+
+                return Some(vec![Err(diagnostic)]);
+            }
+        };
+
+        if pair.as_rule() == Rule::EOI {
+            return None;
+        }
+
+        // As in `parse_program`, the doc comment is recovered from the text preceding the
+        // statement.
+        let start = offset + pair.as_span().start();
+        let doc = trivia::doc_comment(&source[previous_end..start]);
+
+        previous_end = offset + last_token_end(&pair);
+        let end = offset + pair.as_span().end();
+
+        // An `#if` block is parsed whole, its statements yielded in turn.
+        let statements = with_max_nesting(max_nesting, || {
+            with_span_offset(offset, || match pair.as_rule() {
+                Rule::conditional_block => parse_conditional_block(pair),
+                _ => vec![with_doc(parse(pair), doc)],
+            })
+        });
+        offset = end;
+
+        // Statements are compiled as soon as they are parsed, so the errors recovered from stop
+        // the parsing as the others do.
+        if let Some(error) = statements
+            .iter()
+            .find_map(|statement| statement.syntax_errors().into_iter().next())
+        {
+            failed = true;
+            return Some(vec![Err(error)]);
+        }
+
+        Some(statements.into_iter().map(Ok).collect())
+    })
+    .flatten()
+}
+
+/// Keywords that start a statement, see [`next_statement_start`].
+pub(crate) const STATEMENT_KEYWORDS: &[&str] = &[
+    "type",
+    "interface",
+    "union",
+    "brand",
+    "pattern",
+    "import",
+    "export",
+    // `global` is only a keyword before a declaration.
+    "global type",
+    "global interface",
+    "unittest",
+];
+
+/// Start of the first line after `from` that can only start a statement, one starting with a
+/// keyword followed by a name, however it is indented, or the end of `source` if there is none. A
+/// statement is never continued by such a line, so it is parsed the same from the text before it
+/// as from the whole source. The line might be in a comment or in an `#if` block, in which case
+/// the text before it fails to parse.
+fn next_statement_start(source: &str, from: usize) -> usize {
+    let bytes = source.as_bytes();
+    let mut position = from.min(source.len());
+
+    while let Some(newline) = bytes[position..].iter().position(|&byte| byte == b'\n') {
+        position += newline + 1;
+
+        let line = source[position..].trim_start_matches([' ', '\t']);
+
+        let starts_statement = STATEMENT_KEYWORDS.iter().any(|keyword| {
+            line.strip_prefix(keyword)
+                .and_then(|line| line.strip_prefix([' ', '\t']))
+                .and_then(|line| line.chars().next())
+                .is_some_and(|c| {
+                    unicode_ident::is_xid_start(c) || matches!(c, '_' | '$' | '{' | '"' | '*')
+                })
+        });
+
+        if starts_statement {
+            return position;
+        }
+    }
+
+    source.len()
+}
+
+/// Parses a single type expression, such as a type given on the command line.
+pub(crate) fn parse_newtype_expr(source: &str) -> Result<Ast, Box<Error<Rule>>> {
+    check_bracket_nesting(source)?;
+
+    let pair = parse_rule(Rule::test_expr, source)?
+        .next()
+        .unwrap();
 
     Ok(parse(pair))
 }
 
+/// Parses a single type expression, with its syntax error as a diagnostic.
+pub fn parse_type(source: &str) -> Result<Ast, Diagnostic> {
+    parse_newtype_expr(source).map_err(|err| humanize_pest(*err, source))
+}
+
 pub(crate) fn parse_extends_expr(pairs: Pairs) -> Ast {
     EXTENDS_PARSER
         .map_primary(|pair| match pair.as_rule() {
-            Rule::expr => parse_expr(pair.into_inner()),
+            Rule::expr => parse_nested(pair),
             Rule::extends_expr => parse_extends_expr(pair.into_inner()),
             rule => parse_error!(pair, vec![Rule::expr, Rule::extends_expr], vec![rule]),
         })
@@ -208,28 +587,29 @@ pub(crate) fn parse_extends_expr(pairs: Pairs) -> Ast {
         .map_prefix(|op, primary_node| {
             let span: Span = primary_node.as_span().merge(&op.as_span().into());
 
-            if op.as_rule() == Rule::not && !primary_node.is_extends_infix_op() {
-                let error_not = Error::<Rule>::new_from_span(
-                    ErrorVariant::CustomError {
-                        message: "`not` may only be used with an extends expression".to_string(),
-                    },
-                    op.as_span(),
-                );
-
-                let error = span.as_parsing_error(
-                    op.get_input(),
-                    vec![Rule::extends_expr],
-                    vec![],
-                );
-
-                let error_expr = format!("{error}");
+            if op.as_rule() == Rule::not && !primary_node.is_compatible_with_not_prefix_op() {
+                let op_span = Span::from(&op);
 
-                panic!("{error_not}\n{error_expr}\nHint: You might have forgotten to wrap the expression in parentheses, `not` has higher precedence than other operators.");
+                return Ast::SyntaxError(SyntaxError {
+                    span,
+                    diagnostic: Box::new(
+                        Diagnostic::error("`not` may only be used with a condition", op_span)
+                            .with_note(
+                                "`not` has higher precedence than other operators, you might have \
+                                 forgotten to wrap the condition in parentheses",
+                            )
+                            .with_code(codes::SYNTAX_ERROR),
+                    ),
+                });
             }
 
             let op = match op.as_rule() {
                 Rule::not => PrefixOp::Not,
-                rule => parse_error!(op, vec![Rule::not], vec![rule]),
+                rule => parse_error!(
+                    op,
+                    pratt::rules(CONDITION_OPERATORS, Fixity::is_prefix),
+                    vec![rule]
+                ),
             };
 
            let value = primary_node.into();
@@ -242,6 +622,7 @@ pub(crate) fn parse_extends_expr(pairs: Pairs) -> Ast {
         })
         .map_infix(|lhs: Ast, op: Pair, rhs: Ast| {
             let span: Span = lhs.as_span().merge(&rhs.as_span());
+            let non_distributive = op.as_str().ends_with('!');
 
             let op = match op.as_rule() {
                 Rule::extends => InfixOp::Extends,
@@ -254,24 +635,20 @@ pub(crate) fn parse_extends_expr(pairs: Pairs) -> Ast {
                 Rule::or => InfixOp::Or,
                 rule => parse_error!(
                     op,
-                    vec![
-                        Rule::extends,
-                        Rule::not_extends,
-                        Rule::equals,
-                        Rule::not_equals,
-                        Rule::strict_equals,
-                        Rule::strict_not_equals,
-                        Rule::and,
-                        Rule::or
-                    ],
+                    pratt::rules(CONDITION_OPERATORS, Fixity::is_infix),
                     vec![rule]
                 ),
             };
 
             let lhs = lhs.into();
             let rhs = rhs.into();
-            let extends_infix_op = ExtendsInfixOp { lhs, op, rhs, span };
-            
+            let extends_infix_op = ExtendsInfixOp {
+                lhs,
+                op,
+                rhs,
+                non_distributive,
+                span,
+            };
 
             Ast::ExtendsInfixOp(extends_infix_op)
         })
@@ -287,10 +664,16 @@ pub(crate) fn parse(pair: Pair) -> Ast {
         Rule::program => parse_program(pair),
         Rule::statement => parse_statement(pair),
         Rule::type_alias => parse_type_alias(pair),
+        Rule::export_default => parse_export_default(pair),
         Rule::unittest => Ast::UnitTest(parse_unittest(pair)),
+        Rule::ambient_module => parse_ambient_module(pair),
         Rule::interface => parse_interface(pair),
+        Rule::union_declaration => parse_union_declaration(pair),
+        Rule::brand_declaration => parse_brand_declaration(pair),
+        Rule::pattern_declaration => parse_pattern_declaration(pair),
         Rule::import_statement => parse_import_statement(pair),
         Rule::if_expr => parse_if_expr(pair),
+        Rule::if_let_expr => parse_if_let_expr(pair),
         Rule::object_literal => Ast::TypeLiteral(parse_object_literal(pair)),
         Rule::primitive => {
             let value = pair.into_inner().next().unwrap();
@@ -301,6 +684,7 @@ pub(crate) fn parse(pair: Pair) -> Ast {
                 Rule::primitive_boolean => PrimitiveType::Boolean,
                 Rule::primitive_bigint => PrimitiveType::BigInt,
                 Rule::primitive_symbol => PrimitiveType::Symbol,
+                Rule::primitive_unique_symbol => PrimitiveType::UniqueSymbol,
                 Rule::primitive_object => PrimitiveType::Object,
                 Rule::primitive_null => PrimitiveType::Null,
                 Rule::primitive_void => PrimitiveType::Void,
@@ -322,6 +706,11 @@ pub(crate) fn parse(pair: Pair) -> Ast {
         Rule::never => Ast::NeverKeyword(span),
         Rule::any => Ast::AnyKeyword(span),
         Rule::unknown => Ast::UnknownKeyword(span),
+        Rule::this_type => Ast::ThisType(span),
+        Rule::import_type => Ast::ImportType(ImportType {
+            module: pair.into_inner().next().map(parse_string_literal).unwrap(),
+            span,
+        }),
         Rule::boolean => {
             let value = pair.into_inner().next().unwrap();
 
@@ -332,9 +721,10 @@ pub(crate) fn parse(pair: Pair) -> Ast {
             }
         }
         Rule::tuple => parse_tuple(pair),
+        Rule::condition_list => parse_condition_list(pair),
         Rule::macro_call => Ast::MacroCall(parse_macro_call(pair)),
         Rule::builtin => parse_builtin(pair),
-        Rule::expr => parse_expr(pair.into_inner()),
+        Rule::expr => parse_nested(pair),
         Rule::match_expr => Ast::MatchExpr(parse_match_expr(pair)),
         Rule::cond_expr => Ast::CondExpr(parse_cond_expr(pair)),
         Rule::map_expr => Ast::MappedType(parse_map_expr(pair)),
@@ -351,19 +741,21 @@ pub(crate) fn parse(pair: Pair) -> Ast {
     }
 }
 
+fn parse_builtin_keyword(pair: Pair) -> BuiltinKeyword {
+    match pair.as_rule() {
+        Rule::keyof => BuiltinKeyword::Keyof,
+        Rule::builtin_readonly => BuiltinKeyword::Readonly,
+        rule => {
+            parse_error!(pair, vec![Rule::keyof, Rule::builtin_readonly], vec![rule]);
+        }
+    }
+}
+
 fn parse_builtin(pair: Pair) -> Ast {
     let span: Span = (&pair).into();
     let mut inner = pair.into_inner();
 
-    let name = inner.find(match_tag("name")).unwrap();
-
-    let name = match name.as_rule() {
-        Rule::keyof => BuiltinKeyword::Keyof,
-        _ => unreachable!(
-            "unexpected rule while parsing builtin: {:?}",
-            name.as_rule()
-        ),
-    };
+    let name = parse_builtin_keyword(inner.find(match_tag("name")).unwrap());
 
     let argument = inner.find(match_tag("argument")).map(parse).unwrap().into();
 
@@ -381,15 +773,52 @@ fn parse_match_expr(pair: Pair) -> MatchExpr {
 
     let mut inner = pair.into_inner();
 
-    let value = inner.find(match_tag("value")).map(parse).unwrap().into();
+    // `match A, B do` matches the tuple of the values, `[A, B]`.
+    let values: Vec<_> = inner
+        .clone()
+        .filter(match_tag("value"))
+        .map(parse)
+        .collect();
+    let count = values.len();
+    let value = match <[Ast; 1]>::try_from(values) {
+        Ok([value]) => value,
+        Err(items) => Ast::Tuple(Tuple {
+            span: Span::new(
+                items[0].as_span().start,
+                items[items.len() - 1].as_span().end,
+            ),
+            items,
+        }),
+    }
+    .into();
 
     let arms: Vec<match_expr::Arm> = inner
         .clone()
-        .filter(match_tag("arm"))
+        .filter(|pair| pair.as_node_tag() == Some("arm") || pair.as_rule() == Rule::skipped)
         .map(|pair| {
             let span: Span = (&pair).into();
+
+            if pair.as_rule() == Rule::skipped {
+                return Arm {
+                    span,
+                    pattern: parse_skipped(pair, Rule::arm_recovery),
+                    body: Ast::NeverKeyword(span),
+                };
+            }
+
             let mut inner = pair.into_inner();
-            let pattern = inner.find(match_tag("pattern")).map(parse).unwrap();
+            let pattern = inner
+                .find(match_tag("pattern"))
+                .map(|pattern| match pattern.as_rule() {
+                    Rule::pattern_tuple => parse_pattern_tuple(pattern),
+                    _ => parse(pattern),
+                })
+                .unwrap();
+            let pattern = if count > 1 {
+                parse_patterns_of_values(pattern, count)
+            } else {
+                pattern
+            };
             let body = inner.find(match_tag("body")).map(parse).unwrap();
 
             Arm {
@@ -415,6 +844,117 @@ fn parse_match_expr(pair: Pair) -> MatchExpr {
     }
 }
 
+/// `(P, _)` as the tuple `[P, unknown]`, a `_` matching any value.
+fn parse_pattern_tuple(pair: Pair) -> Ast {
+    let span: Span = (&pair).into();
+    let items = pair
+        .into_inner()
+        .map(|pair| match parse(pair) {
+            Ast::Ident(ident) if ident.name == "_" => Ast::UnknownKeyword(ident.span),
+            pattern => pattern,
+        })
+        .collect();
+
+    Ast::Tuple(Tuple { span, items })
+}
+
+/// The pattern of an arm of a `match` on `count` values: a tuple of as many patterns, `(P, _)` or
+/// `[P, _]`, each `_` matching any value, or `_` matching any values. Any other pattern is a
+/// syntax error, as it never matches.
+fn parse_patterns_of_values(pattern: Ast, count: usize) -> Ast {
+    let is_wildcard = |pattern: &Ast| matches!(pattern, Ast::Ident(ident) if ident.name == "_");
+
+    let (items, span) = match pattern {
+        Ast::Tuple(Tuple { items, span }) if items.len() == count => (items, span),
+        pattern if is_wildcard(&pattern) => {
+            let span = pattern.as_span();
+            (vec![pattern; count], span)
+        }
+        pattern => {
+            let span = pattern.as_span();
+            let found = match &pattern {
+                Ast::Tuple(Tuple { items, .. }) => items.len(),
+                _ => 1,
+            };
+
+            return Ast::SyntaxError(SyntaxError {
+                span,
+                diagnostic: Box::new(
+                    Diagnostic::error(
+                        format!(
+                            "pattern of {found} value{} in a `match` on {count}",
+                            if found == 1 { "" } else { "s" }
+                        ),
+                        span,
+                    )
+                    .with_note(format!(
+                        "a `match` on several values has a pattern for each, `({})`",
+                        vec!["_"; count].join(", ")
+                    ))
+                    .with_code(codes::SYNTAX_ERROR),
+                ),
+            });
+        }
+    };
+
+    let items = items
+        .into_iter()
+        .map(|item| match item {
+            item if is_wildcard(&item) => Ast::UnknownKeyword(item.as_span()),
+            item => item,
+        })
+        .collect();
+
+    Ast::Tuple(Tuple { items, span })
+}
+
+/// `if let P = V and let Q = W then A else B end` as `match V do P -> (match W do Q -> A, else ->
+/// B end), else -> B end`.
+fn parse_if_let_expr(pair: Pair) -> Ast {
+    use match_expr::Arm;
+
+    let span: Span = (&pair).into();
+    let inner = pair.into_inner();
+
+    let bindings: Vec<(Span, Ast, Ast)> = inner
+        .clone()
+        .filter(match_tag("binding"))
+        .map(|pair| {
+            let span: Span = (&pair).into();
+            let mut inner = pair.into_inner();
+            let pattern = inner.find(match_tag("pattern")).map(parse).unwrap();
+            let value = inner.find(match_tag("value")).map(parse).unwrap();
+
+            (span, pattern, value)
+        })
+        .collect();
+
+    let then_branch = inner.clone().find(match_tag("then")).map(parse).unwrap();
+
+    let else_branch: Rc<Ast> = inner
+        .clone()
+        .find(match_tag("else"))
+        .map(parse)
+        .unwrap_or(Ast::NeverKeyword(span))
+        .into();
+
+    bindings
+        .into_iter()
+        .rev()
+        .fold(then_branch, |body, (arm_span, pattern, value)| {
+            Ast::MatchExpr(MatchExpr {
+                span,
+                value: value.into(),
+                arms: vec![Arm {
+                    span: arm_span,
+                    pattern,
+                    body,
+                }],
+                else_arm: else_branch.clone(),
+            })
+        })
+}
+
 fn parse_cond_expr(pair: Pair) -> CondExpr {
     let span = Span::from(&pair);
     let inner = pair.into_inner();
@@ -424,8 +964,7 @@ fn parse_cond_expr(pair: Pair) -> CondExpr {
         .find(match_tag("else"))
         .and_then(|p| p.into_inner().find(match_tag("body")))
         .map(parse)
-        .unwrap_or(Ast::NeverKeyword(span))
-        .into();
+        .map(Rc::new);
 
     let arms: Vec<cond_expr::Arm> = inner
         .clone()
@@ -461,38 +1000,43 @@ fn parse_cond_expr(pair: Pair) -> CondExpr {
 fn parse_map_expr(pair: Pair) -> MappedType {
     let span: Span = (&pair).into();
 
-    let mut inner = pair.into_inner();
+    let inner = pair.into_inner();
 
-    let ipk = next_pair!(inner, Rule::index_property_key);
+    let modifier = |tag| {
+        find_tag(inner.clone(), tag).map(|pair| match pair.as_str().starts_with('-') {
+            true => MappingModifier::Remove,
+            false => MappingModifier::Add,
+        })
+    };
 
-    let body = inner.find(match_tag("body")).map(parse).unwrap().into();
+    let readonly_mod = modifier("readonly");
+    let optional_mod = modifier("optional");
 
-    let mut inner = ipk.into_inner();
+    let ipk = inner
+        .clone()
+        .find(|pair| pair.as_rule() == Rule::index_property_key)
+        .unwrap();
 
-    let index = inner
-        .next()
-        .and_then(filter_rule(Rule::ident))
-        .unwrap()
-        .as_str()
-        .to_string();
+    let body = find_tag(inner.clone(), "body").map(parse).unwrap().into();
 
-    let iterable = inner.find(match_tag("iterable")).map(parse).unwrap().into();
+    let [index, iterable, remap_clause] =
+        take_tags!(ipk.into_inner(), ["index", "iterable", "remap_clause"]);
 
     MappedType {
         span,
-        index,
-        iterable,
+        index: ident_name(&index.unwrap()),
+        iterable: parse(iterable.unwrap()).into(),
         body,
-        remapped_as: None,
-        readonly_mod: None,
-        optional_mod: None,
+        remapped_as: remap_clause.map(|pair| parse(pair).into()),
+        readonly_mod,
+        optional_mod,
     }
 }
 
 fn parse_let_expr(pair: Pair) -> LetExpr {
     let span: Span = (&pair).into();
 
-    let bindings: HashMap<_, _> = pair
+    let bindings = pair
         .clone()
         .into_inner()
         .filter(match_tag("binding"))
@@ -501,12 +1045,16 @@ fn parse_let_expr(pair: Pair) -> LetExpr {
             let name = inner.next().unwrap();
             assert_eq!(name.as_rule(), Rule::ident);
 
-            let name = name.as_str().to_string();
+            let name = parse_ident(name);
             let value = inner.next().unwrap();
             assert_eq!(value.as_rule(), Rule::expr);
             let value = parse(value);
 
-            (name, value)
+            // From the name to the end of the value, the pair of the binding also holding the
+            // whitespace before `in`.
+            let span = Span::new(name.span.start, value.as_span().end);
+
+            Binding { name, value, span }
         })
         .collect();
 
@@ -584,7 +1132,7 @@ fn parse_function_type(pair: Pair) -> FunctionType {
                 Parameter {
                     span: pair.as_span().into(),
                     ellipsis,
-                    name: name.as_str().to_string(),
+                    name: ident_name(name),
                     kind: parse(kind.to_owned()),
                 }
             })
@@ -595,14 +1143,54 @@ fn parse_function_type(pair: Pair) -> FunctionType {
         None => vec![],
     };
 
-    let return_type = next_pair!(inner, Rule::expr);
+    let next = inner.next().unwrap();
+
+    let (return_type, predicate) = match next.as_rule() {
+        Rule::type_predicate => {
+            let predicate = parse_type_predicate(next);
+            let return_type = if predicate.asserts {
+                PrimitiveType::Void
+            } else {
+                PrimitiveType::Boolean
+            };
 
-    let return_type = parse(return_type).into();
+            (
+                Ast::Primitive(return_type, predicate.span).into(),
+                Some(predicate),
+            )
+        }
+        _ => (parse(next).into(), None),
+    };
 
     FunctionType {
         span,
         params,
         return_type,
+        predicate,
+    }
+}
+
+fn parse_type_predicate(pair: Pair) -> TypePredicate {
+    let span: Span = (&pair).into();
+
+    let mut asserts = false;
+    let mut param = None;
+    let mut target = None;
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::asserts_keyword => asserts = true,
+            Rule::ident => param = Some(parse_ident(pair)),
+            Rule::expr => target = Some(parse(pair).into()),
+            _ => {}
+        }
+    }
+
+    TypePredicate {
+        asserts,
+        param: param.expect("a type predicate names a parameter"),
+        target,
+        span,
     }
 }
 
@@ -610,8 +1198,8 @@ fn parse_macro_call(pair: Pair) -> MacroCall {
     let span: Span = (&pair).into();
     let mut inner = pair.into_inner();
     let name = next_pair!(inner, Rule::macro_ident);
-    let args = next_pair!(inner, Rule::argument_list);
-    let name = name.as_str().to_string();
+    let args = next_pair!(inner, Rule::macro_argument_list);
+    let name = ident_name(&name);
     let inner = args.into_inner();
     let args = inner.map(parse).collect_vec();
     MacroCall { span, name, args }
@@ -636,19 +1224,27 @@ fn parse_type_alias(pair: Pair) -> Ast {
         .map(|p| p.as_rule() == Rule::export)
         .unwrap_or(false);
 
-    let name = inner.clone().find(match_tag("name")).unwrap().as_str();
+    let global = inner
+        .peek()
+        .map(|p| p.as_rule() == Rule::global)
+        .unwrap_or(false);
+
+    let default = inner
+        .peek()
+        .map(|p| p.as_rule() == Rule::default_export)
+        .unwrap_or(false);
 
-    let name = Ident {
-        name: name.to_string(),
-        span,
-    };
+    let name = parse_ident(inner.clone().find(match_tag("name")).unwrap());
 
     let body = Rc::new(inner.clone().find(match_tag("body")).map(parse).unwrap());
 
     let params = parse_definition_options(inner);
 
     Ast::TypeAlias(TypeAlias {
+        doc: None,
         export,
+        global,
+        default,
         name,
         params,
         body,
@@ -657,24 +1253,163 @@ fn parse_type_alias(pair: Pair) -> Ast {
 }
 
 fn parse_statement(pair: Pair) -> Ast {
-    let inner = pair.into_inner().next().unwrap();
-    let inner = parse(inner);
-    Ast::Statement(inner.into())
+    let (attributes, declaration): (Vec<_>, Vec<_>) = pair
+        .into_inner()
+        .partition(|pair| pair.as_rule() == Rule::attribute);
+
+    Ast::Statement(Statement {
+        attributes: attributes.into_iter().map(parse_attribute).collect(),
+        inner: parse(declaration.into_iter().next().unwrap()).into(),
+    })
+}
+
+fn parse_attribute(pair: Pair) -> Attribute {
+    assert_ast!(pair, Rule::attribute);
+
+    // Without arguments the pair ends after the trivia following the name.
+    let span = Span::new(pair.as_span().start(), last_token_end(&pair));
+    let mut inner = pair.into_inner();
+
+    let name = parse_ident(inner.next().unwrap());
+
+    let args = inner
+        .map(|arg| match arg.as_rule() {
+            Rule::string => AttrValue::String(parse_string_literal(arg)),
+            Rule::number => AttrValue::Number(arg.as_str().to_string()),
+            Rule::ident => AttrValue::Ident(arg.as_str().to_string()),
+            _ => unreachable!("attribute arguments are strings, numbers or names"),
+        })
+        .collect();
+
+    Attribute { name, args, span }
 }
 
 fn parse_program(pair: Pair) -> Ast {
     let span: Span = (&pair).into();
+    let start = pair.as_span().start();
 
-    let statements: Vec<_> = pair
-        .clone()
-        .into_inner()
-        .filter(|pair| pair.as_rule() != Rule::EOI) // Remove the end of input token
-        .map(parse)
-        .collect();
+    let statements = parse_statements(pair, start);
 
     Ast::Program(Program { statements, span })
 }
 
+fn parse_ambient_module(pair: Pair) -> Ast {
+    let span: Span = (&pair).into();
+    let name = next_pair!(pair.clone().into_inner(), Rule::string);
+    let start = name.as_span().end();
+
+    Ast::AmbientModule(AmbientModule {
+        name: parse_string_literal(name),
+        body: parse_statements(pair, start),
+        span,
+    })
+}
+
+/// The statements among the children of `pair`, with the doc comments written between them, the
+/// first one from the text from `start` on.
+fn parse_statements(pair: Pair, start: usize) -> Vec<Ast> {
+    let input = pair.get_input();
+    let mut previous_end = start;
+
+    pair.into_inner()
+        .flat_map(|pair| match pair.as_rule() {
+            Rule::statement => {
+                // Comments are trivia to the grammar, recover doc comments from the text
+                // preceding the statement.
+                let gap = &input[previous_end..pair.as_span().start()];
+                previous_end = last_token_end(&pair);
+
+                let doc = trivia::doc_comment(gap);
+
+                vec![with_doc(parse(pair), doc)]
+            }
+            Rule::conditional_block => {
+                previous_end = last_token_end(&pair);
+                parse_conditional_block(pair)
+            }
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// The statements of an `#if` block, each tagged with the flag it depends on, see
+/// [`attribute::IF_FLAG`]. The block itself leaves no node, its statements keep their spans.
+fn parse_conditional_block(pair: Pair) -> Vec<Ast> {
+    assert_ast!(pair, Rule::conditional_block);
+
+    let mut inner = pair.clone().into_inner();
+    let flag = next_pair!(inner, Rule::string);
+    // From `#if` to the closing parenthesis of the flag.
+    let span = Span::new(pair.as_span().start(), flag.as_span().end() + 1);
+    let flag = parse_string_literal(flag);
+
+    inner
+        .filter(|pair| pair.as_rule() == Rule::conditional_body)
+        .zip([attribute::IF_FLAG, attribute::ELSE_FLAG])
+        .flat_map(|(body, name)| {
+            let start = body.as_span().start();
+
+            parse_statements(body, start)
+                .into_iter()
+                .map(|statement| {
+                    let Ast::Statement(mut statement) = statement else {
+                        return statement;
+                    };
+
+                    statement.attributes.push(Attribute {
+                        name: Ident {
+                            name: name.to_string(),
+                            span,
+                            raw: false,
+                        },
+                        args: vec![AttrValue::String(flag.clone())],
+                        span,
+                    });
+
+                    Ast::Statement(statement)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// End of the last token produced by `pair`. Unlike the end of the pair itself this excludes any
+/// trailing trivia.
+fn last_token_end(pair: &Pair) -> usize {
+    pair.clone()
+        .into_inner()
+        .flatten()
+        .filter(|pair| pair.clone().into_inner().next().is_none())
+        .map(|pair| pair.as_span().end())
+        .max()
+        .unwrap_or_else(|| pair.as_span().end())
+}
+
+/// Attaches a doc comment to the declaration wrapped by `statement`.
+fn with_doc(statement: Ast, doc: Option<String>) -> Ast {
+    let Some(doc) = doc else {
+        return statement;
+    };
+
+    let mut statement = statement;
+
+    if let Some(slot) = statement.doc_mut() {
+        *slot = Some(doc);
+    }
+
+    statement
+}
+
+fn parse_export_default(pair: Pair) -> Ast {
+    let span: Span = (&pair).into();
+    let name = pair.into_inner().find(match_tag("name")).unwrap();
+
+    Ast::ExportDefault(ExportDefault {
+        name: parse_ident(name),
+        span,
+    })
+}
+
 fn parse_interface(pair: Pair) -> Ast {
     let span: Span = (&pair).into();
     let inner = pair.clone().into_inner();
@@ -684,12 +1419,17 @@ fn parse_interface(pair: Pair) -> Ast {
         .map(|p| p.as_rule() == Rule::export)
         .unwrap_or(false);
 
-    let name = inner
-        .clone()
-        .find(match_tag("name"))
-        .unwrap()
-        .as_str()
-        .into();
+    let global = inner
+        .peek()
+        .map(|p| p.as_rule() == Rule::global)
+        .unwrap_or(false);
+
+    let default = inner
+        .peek()
+        .map(|p| p.as_rule() == Rule::default_export)
+        .unwrap_or(false);
+
+    let name = ident_name(&inner.clone().find(match_tag("name")).unwrap());
 
     let body = inner.clone().find(match_tag("body")).unwrap();
 
@@ -697,13 +1437,20 @@ fn parse_interface(pair: Pair) -> Ast {
 
     let definition = body.properties;
 
-    let params = parse_definition_options(inner);
+    let extends = inner
+        .clone()
+        .find(match_tag("extends"))
+        .and_then(|clause| clause.into_inner().find(match_tag("name")))
+        .map(|name| ident_name(&name));
 
-    let extends = None;
+    let params = parse_definition_options(inner);
 
     Ast::Interface(Interface {
+        doc: None,
         span,
         export,
+        global,
+        default,
         extends,
         name,
         params,
@@ -711,30 +1458,154 @@ fn parse_interface(pair: Pair) -> Ast {
     })
 }
 
+fn parse_union_declaration(pair: Pair) -> Ast {
+    use union_declaration::{Variant, DEFAULT_DISCRIMINANT};
+
+    let span: Span = (&pair).into();
+    let inner = pair.into_inner();
+
+    let export = inner
+        .peek()
+        .map(|p| p.as_rule() == Rule::export)
+        .unwrap_or(false);
+
+    let name = inner.clone().find(match_tag("name")).unwrap();
+
+    let discriminant = inner
+        .clone()
+        .find(match_tag("discriminant"))
+        .map_or(DEFAULT_DISCRIMINANT.to_string(), parse_string_literal);
+
+    let variants = inner
+        .filter(match_tag("variant"))
+        .map(|pair| {
+            let span: Span = (&pair).into();
+            let inner = pair.into_inner();
+
+            let name = inner.clone().find(match_tag("name")).unwrap();
+
+            let body = inner.clone().find(match_tag("body")).map_or_else(
+                || TypeLiteral {
+                    properties: vec![],
+                    span,
+                },
+                parse_object_literal,
+            );
+
+            Variant {
+                name: Ident {
+                    name: ident_name(&name),
+                    span: (&name).into(),
+                    raw: false,
+                },
+                body,
+                span,
+            }
+        })
+        .collect();
+
+    Ast::UnionDeclaration(UnionDeclaration {
+        doc: None,
+        export,
+        name: Ident {
+            name: ident_name(&name),
+            span: (&name).into(),
+            raw: false,
+        },
+        discriminant,
+        variants,
+        span,
+    })
+}
+
+fn parse_brand_declaration(pair: Pair) -> Ast {
+    let span: Span = (&pair).into();
+    let inner = pair.into_inner();
+
+    let export = inner
+        .peek()
+        .map(|p| p.as_rule() == Rule::export)
+        .unwrap_or(false);
+
+    let name = inner.clone().find(match_tag("name")).unwrap();
+
+    let base = inner.clone().find(match_tag("base")).map(parse).unwrap();
+
+    Ast::BrandDeclaration(BrandDeclaration {
+        doc: None,
+        export,
+        name: Ident {
+            name: ident_name(&name),
+            span: (&name).into(),
+            raw: false,
+        },
+        base: base.into(),
+        span,
+    })
+}
+
+fn parse_pattern_declaration(pair: Pair) -> Ast {
+    let span: Span = (&pair).into();
+    let inner = pair.into_inner();
+
+    let name = inner
+        .clone()
+        .find(match_tag("name"))
+        .map(parse_ident)
+        .unwrap();
+
+    let params = inner
+        .clone()
+        .filter(match_tag("param"))
+        .map(parse_ident)
+        .collect();
+
+    let body = inner.clone().find(match_tag("body")).map(parse).unwrap();
+
+    Ast::PatternDeclaration(PatternDeclaration {
+        doc: None,
+        name,
+        params,
+        body: body.into(),
+        span,
+    })
+}
+
 fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypeParameter> {
     // Track the order of the inserted parametes
-    let mut ordered_params: Vec<&str> = Default::default();
+    let mut ordered_params: Vec<String> = Default::default();
 
-    let mut params: HashMap<&str, TypeParameter> = inner
+    let mut params: HashMap<String, TypeParameter> = inner
         .clone()
         .find(match_tag("parameters"))
-        .map(|p| -> HashMap<&str, TypeParameter> {
+        .map(|p| -> HashMap<String, TypeParameter> {
             p.into_inner()
                 .map(|pair| {
                     let span: Span = (&pair).into();
-                    let str = pair.as_str();
-                    let name = str.to_string();
+                    let is_const = pair
+                        .clone()
+                        .into_inner()
+                        .any(|inner| inner.as_node_tag() == Some("modifier"));
+                    let ident = pair.into_inner().find(match_tag("name")).unwrap();
+                    let name_span: Span = (&ident).into();
+                    let name = ident_name(&ident);
 
-                    ordered_params.push(str);
+                    ordered_params.push(name.clone());
 
                     (
-                        str,
+                        name.clone(),
                         TypeParameter {
                             span,
-                            name,
+                            name: Ident {
+                                name,
+                                span: name_span,
+                                raw: false,
+                            },
                             constraint: None,
                             default: None,
                             rest: false,
+                            is_const,
+                            clause_spans: vec![],
                         },
                     )
                 })
@@ -751,7 +1622,8 @@ fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypePara
                 let name = inner.next().unwrap();
                 assert_eq!(name.as_node_tag(), Some("constraint_name"));
                 assert_eq!(name.as_rule(), Rule::ident);
-                let name = name.as_str();
+                let name_span: Span = (&name).into();
+                let name = ident_name(&name);
 
                 assert_eq!(inner.next().unwrap().as_rule(), Rule::extends);
 
@@ -760,7 +1632,7 @@ fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypePara
                 assert_eq!(body.as_rule(), Rule::expr);
                 let body = parse(body);
 
-                let param = match params.get_mut(name) {
+                let param = match params.get_mut(&name) {
                     Some(param) => param,
                     None => {
                         let error = Error::<Rule>::new_from_span(
@@ -778,6 +1650,7 @@ fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypePara
                 };
 
                 param.constraint = Some(body);
+                param.clause_spans.push(name_span);
             });
     }
 
@@ -790,14 +1663,15 @@ fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypePara
                 let name = inner.next().unwrap();
                 assert_eq!(name.as_node_tag(), Some("name"));
                 assert_eq!(name.as_rule(), Rule::ident);
-                let name = name.as_str();
+                let name_span: Span = (&name).into();
+                let name = ident_name(&name);
 
                 let body = inner.next().unwrap();
                 assert_eq!(body.as_node_tag(), Some("value"));
                 assert_eq!(body.as_rule(), Rule::expr);
                 let body = parse(body);
 
-                let param = match params.get_mut(name) {
+                let param = match params.get_mut(&name) {
                     Some(param) => param,
                     None => {
                         let error = Error::<Rule>::new_from_span(
@@ -815,6 +1689,7 @@ fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypePara
                 };
 
                 param.default = Some(body);
+                param.clause_spans.push(name_span);
             });
     };
 
@@ -827,11 +1702,18 @@ fn parse_definition_options(inner: pest::iterators::Pairs<Rule>) -> Vec<TypePara
 fn parse_ident(pair: Pair) -> Ident {
     assert_ast!(pair, Rule::ident);
     Ident {
-        name: pair.as_str().to_string(),
+        name: ident_name(&pair),
         span: pair.as_span().into(),
+        raw: false,
     }
 }
 
+/// The name of an identifier, normalized to NFC so that differently encoded spellings of the same
+/// name compare equal.
+fn ident_name(pair: &Pair) -> String {
+    pair.as_str().nfc().collect()
+}
+
 /// Returns a `String` with the contents of a string literal (without the quotes).
 fn parse_string_literal(pair: Pair) -> String {
     assert_ast!(pair, Rule::string);
@@ -866,7 +1748,14 @@ fn parse_import_statement(pair: Pair) -> Ast {
                     let span: Span = (&pair).into();
                     let mut inner = pair.into_inner();
                     let name = inner.next().unwrap();
-                    let name = parse_ident(name);
+                    let name = match name.as_rule() {
+                        Rule::string => Ident {
+                            span: (&name).into(),
+                            name: parse_string_literal(name),
+                            raw: true,
+                        },
+                        _ => parse_ident(name),
+                    };
                     let alias = inner.next().map(parse_ident);
 
                     ImportSpecifier {
@@ -880,7 +1769,9 @@ fn parse_import_statement(pair: Pair) -> Ast {
             ImportClause::Named(specs)
         }
         Rule::namespace_import => {
-            todo!()
+            let alias = import_clause.into_inner().next().map(parse_ident).unwrap();
+
+            ImportClause::Namespace { alias }
         }
         _ => parse_error!(pair),
     };
@@ -905,28 +1796,41 @@ fn parse_if_expr(pair: Pair) -> Ast {
 
     let then_branch = inner.find(match_tag("then")).map(parse).unwrap().into();
 
-    let else_branch = inner
-        .find(match_tag("else"))
-        .map(parse)
-        .unwrap_or_else(|| Ast::NeverKeyword(span))
-        .into();
+    let pass = inner.clone().any(|p| p.as_node_tag() == Some("pass"));
+    let else_branch = inner.find(match_tag("else")).map(parse).map(Rc::new);
 
-    match condition {
-        // Other conditions are desugared later in the simplification step
-        Ast::ExtendsInfixOp(ExtendsInfixOp { .. })
-        | Ast::ExtendsPrefixOp(ExtendsPrefixOp { .. }) => Ast::IfExpr(IfExpr {
-            span,
-            condition: Rc::new(condition),
-            then_branch,
-            else_branch: Some(else_branch),
-        }),
-        _ => unreachable!(),
-    }
+    // A condition that isn't one is reported once its macros are expanded, as what a macro such
+    // as `all!` expands to must be a condition as well, see `if_expr::check_condition`.
+    Ast::IfExpr(IfExpr {
+        span,
+        condition: Rc::new(condition),
+        then_branch,
+        else_branch,
+        pass,
+    })
 }
 
 fn parse_tuple(pair: Pair) -> Ast {
     let span: Span = (&pair).into();
-    let items = pair.clone().into_inner().map(parse).collect();
+    let items = pair
+        .clone()
+        .into_inner()
+        .map(|pair| match pair.as_rule() {
+            Rule::skipped => parse_skipped(pair, Rule::element_recovery),
+            _ => parse(pair),
+        })
+        .collect();
+
+    Ast::Tuple(Tuple { span, items })
+}
+
+/// A list of conditions, a tuple whose items are extends expressions.
+fn parse_condition_list(pair: Pair) -> Ast {
+    let span: Span = (&pair).into();
+    let items = pair
+        .into_inner()
+        .map(|pair| parse_extends_expr(pair.into_inner()))
+        .collect();
 
     Ast::Tuple(Tuple { span, items })
 }
@@ -937,7 +1841,7 @@ fn parse_object_literal(pair: Pair) -> TypeLiteral {
     let mut properties = Vec::new();
 
     for prop_pair in object_property_rules {
-        let span: Span = (&pair).into();
+        let span: Span = (&prop_pair).into();
 
         match prop_pair.as_rule() {
             Rule::object_property => {
@@ -967,6 +1871,14 @@ fn parse_object_literal(pair: Pair) -> TypeLiteral {
                     value,
                 });
             }
+            // The property stands for the skipped text, see `SyntaxError`.
+            Rule::skipped => properties.push(ObjectProperty {
+                span,
+                readonly: false,
+                optional: false,
+                key: ObjectPropertyKey::Key(String::new()),
+                value: parse_skipped(prop_pair, Rule::property_recovery),
+            }),
             _ => unreachable!(
                 "unexpected rule while parsing object literal: {:?}",
                 prop_pair.as_rule()
@@ -977,9 +1889,40 @@ fn parse_object_literal(pair: Pair) -> TypeLiteral {
     TypeLiteral { properties, span }
 }
 
+/// The node standing for the text the grammar skipped where `rule` failed, with the error of
+/// parsing the text, and the `,` or closing bracket ending it, as `rule` on its own.
+fn parse_skipped(pair: Pair, rule: Rule) -> Ast {
+    let span: Span = (&pair).into();
+    let rest = &pair.get_input()[pair.as_span().start()..];
+
+    let mut diagnostic = match parse_rule(rule, rest) {
+        Err(error) => humanize_pest(*error, rest),
+        // The text parses on its own, but not where it is written.
+        Ok(_) => Diagnostic::error(
+            format!("unexpected `{}`", pair.as_str()),
+            Span::new(0, pair.as_str().len()),
+        )
+        .with_code(codes::SYNTAX_ERROR),
+    };
+
+    let shift = |inner: Span| Span::new(span.start + inner.start, span.start + inner.end);
+
+    diagnostic.span = shift(diagnostic.span);
+
+    if let Some(suggestion) = &mut diagnostic.suggestion {
+        suggestion.span = shift(suggestion.span);
+    }
+
+    Ast::SyntaxError(SyntaxError {
+        span,
+        diagnostic: Box::new(diagnostic),
+    })
+}
+
 fn parse_property_key_inner(key: Pair) -> ObjectPropertyKey {
     match key.as_rule() {
-        Rule::ident => ObjectPropertyKey::Key(key.as_str().to_string()),
+        Rule::ident => ObjectPropertyKey::Key(ident_name(&key)),
+        Rule::string => ObjectPropertyKey::Key(parse_string_literal(key)),
         Rule::index_property_key => parse_index_property_key(key),
         Rule::computed_property_key => {
             let inner = key.into_inner().next().unwrap();
@@ -996,7 +1939,7 @@ fn parse_index_property_key(key: Pair) -> ObjectPropertyKey {
 
     let [index, iterable, remap_clause] = take_tags!(inner, ["index", "iterable", "remap_clause"]);
 
-    let key = index.unwrap().as_str().to_string();
+    let key = ident_name(&index.unwrap());
     let iterable = parse(iterable.unwrap());
     let remapped_as = remap_clause.map(parse);
 
@@ -1053,6 +1996,7 @@ mod parser_tests {
     use pest::consumes_to;
     use pest::fails_with;
     use pest::parses_to;
+    use pest::error::InputLocation;
     use pretty_assertions::{assert_eq, assert_ne};
     use rstest::rstest;
     use std::assert_matches::assert_matches;
@@ -1068,7 +2012,7 @@ mod parser_tests {
         "A::Equals(T, any)",
         sexp!(
             (apply
-                (receiver :: (segments (ident . "A") (ident . "Equals")))
+                (receiver #"." (lhs ident . "A") (rhs ident . "Equals") (kind . namespace))
                 (args (ident . "T") any))
         )
     )]
@@ -1092,6 +2036,70 @@ mod parser_tests {
         }
     }
 
+    #[rstest]
+    #[case::keyof("keyof T", sexp!((builtin (name . keyof) (argument ident . "T"))), "keyof T")]
+    #[case::keyof_twice(
+        "keyof keyof T",
+        sexp!((builtin (name . keyof) (argument builtin (name . keyof) (argument ident . "T")))),
+        "keyof keyof T"
+    )]
+    #[case::readonly_array(
+        "readonly T[]",
+        sexp!((builtin (name . readonly) (argument array ident . "T"))),
+        "readonly T[]"
+    )]
+    #[case::readonly_tuple(
+        "readonly [T, U]",
+        sexp!((builtin (name . readonly) (argument tuple (items (ident . "T") (ident . "U"))))),
+        "readonly [T, U]"
+    )]
+    #[case::keyof_access(
+        "keyof T[number]",
+        sexp!((builtin (name . keyof) (argument #"." (lhs ident . "T") (rhs primitive number) (kind . index)))),
+        "keyof T[number]"
+    )]
+    #[case::spacing(
+        "keyof   T  [ K ]",
+        sexp!((builtin (name . keyof) (argument #"." (lhs ident . "T") (rhs ident . "K") (kind . index)))),
+        "keyof T[K]"
+    )]
+    #[case::keyof_application(
+        "keyof Record(K, V)",
+        sexp!((builtin (name . keyof) (argument apply (receiver ident . "Record") (args (ident . "K") (ident . "V"))))),
+        "keyof Record<K, V>"
+    )]
+    #[case::union_operand(
+        "keyof T | U",
+        sexp!((#"|" (types (builtin (name . keyof) (argument ident . "T")) (ident . "U")))),
+        "keyof T | U"
+    )]
+    #[case::parenthesized_union(
+        "keyof (T | U)",
+        sexp!((builtin (name . keyof) (argument #"|" (types (ident . "T") (ident . "U"))))),
+        "keyof (T | U)"
+    )]
+    #[case::call_indexed(
+        "keyof(T)[number]",
+        sexp!((#"." (lhs builtin (name . keyof) (argument ident . "T")) (rhs primitive number) (kind . index))),
+        "(keyof T)[number]"
+    )]
+    fn unary_builtin_precedence(
+        #[case] input: &str,
+        #[case] expected_sexp: lexpr::Value,
+        #[case] expected_ts: &str,
+    ) {
+        use crate::typescript::Pretty as _;
+
+        let pairs = NewtypeParser::parse(Rule::expr, input).unwrap();
+        let actual = parse_expr(pairs);
+
+        assert_eq!(
+            actual.to_sexp().unwrap().to_string(),
+            expected_sexp.to_string()
+        );
+        assert_eq!(actual.render_pretty_ts(80), expected_ts);
+    }
+
     #[rstest]
     // literals
     #[case("1", "1")]
@@ -1108,17 +2116,346 @@ mod parser_tests {
     fn test_parse_expr_typescript_repr(#[case] input: &str, #[case] expected: &str) {
         use crate::parser;
 
-        let result = parser::NewtypeParser::parse(Rule::expr, input);
+        let result = parser::NewtypeParser::parse(Rule::expr, input);
+
+        match result {
+            Ok(pairs) => {
+                let actual = parser::parse_expr(pairs).simplify().render_pretty_ts(80);
+
+                pretty_assertions::assert_eq!(actual, expected);
+            }
+            Err(err) => {
+                panic!("{}", err);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::object_literal("type A as {a: 1, b: 2,}", "type A as {a: 1, b: 2}")]
+    #[case::tuple("type A as [1, 2,]", "type A as [1, 2]")]
+    #[case::match_arms(
+        "type A as match X do 1 -> 2, 3 -> 4, end",
+        "type A as match X do 1 -> 2, 3 -> 4 end"
+    )]
+    #[case::match_else_arm(
+        "type A as match X do 1 -> 2, else -> 4, end",
+        "type A as match X do 1 -> 2, else -> 4 end"
+    )]
+    #[case::cond_arms(
+        "type A as cond do X <: 1 -> 2, X <: 3 -> 4, end",
+        "type A as cond do X <: 1 -> 2, X <: 3 -> 4 end"
+    )]
+    #[case::application_arguments("type A as B(1, 2,)", "type A as B(1, 2)")]
+    #[case::macro_arguments("type A as dbg!(1,)", "type A as dbg!(1)")]
+    #[case::import_clause("import { A, B, } from :a", "import { A, B } from :a")]
+    #[case::let_bindings("type A as let a = 1, in a", "type A as let a = 1 in a")]
+    #[case::type_parameters("type A(x, y,) as x", "type A(x, y) as x")]
+    #[case::where_clause(
+        "type A(x) where x <: 1, as x",
+        "type A(x) where x <: 1 as x"
+    )]
+    #[case::defaults_clause(
+        "type A(x) defaults x = 1, as x",
+        "type A(x) defaults x = 1 as x"
+    )]
+    #[case::function_parameters("type A as (a: 1, b: 2,) => 3", "type A as (a: 1, b: 2) => 3")]
+    fn trailing_comma(#[case] with_comma: &str, #[case] without_comma: &str) {
+        assert_eq!(
+            parse!(with_comma).to_sexp().unwrap().to_string(),
+            parse!(without_comma).to_sexp().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn multi_line_layouts_have_trailing_commas() {
+        let source = r#"
+            type Alias(First, Second, Third) as { first: First, second: Second, third: Third }
+            import { First, Second, Third } from :module
+        "#;
+
+        let expected = dedent!(
+            r#"
+            type Alias<
+                First,
+                Second,
+                Third,
+            > =
+                {
+                    first: First,
+                    second: Second,
+                    third: Third,
+                };
+
+            import type {
+                First,
+                Second,
+                Third,
+            } from 'module';
+            "#
+        );
+
+        let actual = parse!(source.trim()).simplify().render_pretty_ts(30);
+
+        assert_eq!(actual.trim(), expected.trim());
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        assert_typescript!(
+            "type A = 1;",
+            r#"
+            /* outer /* inner */ still a comment */
+            type A as /* a /* b */ c */ 1
+            "#
+        );
+    }
+
+    #[test]
+    fn doc_comments_are_emitted_as_jsdoc() {
+        assert_typescript!(
+            r#"
+            /** Single line. */
+            type A = 1;
+
+            type B = 2 extends 2 ? 3 : never;
+
+            /**
+             * First line.
+             * Second line.
+             */
+            export interface I {
+                x: 1;
+            }
+            "#,
+            r#"
+            /** Single line. */
+            type A as 1
+
+            /** Dropped, as it is not the comment nearest to `I`. */
+            /* Plain comments are not documentation. */
+            type B as if 2 <: 2 then 3 end
+
+            /**
+             * First line.
+             * Second line.
+             */
+            export interface I { x: 1 }
+            "#
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let source = "type A as 1\n/* outer /* inner */\ntype B as 2";
+
+        let error = parse_newtype_program(source).unwrap_err();
+
+        assert_eq!(
+            error.variant.message(),
+            "unterminated block comment",
+            "{error}"
+        );
+        assert_eq!(error.location, InputLocation::Span((12, 14)));
+    }
+
+    /// `typeof` is reserved, but there are no values to query the type of.
+    #[rstest]
+    #[case("type A as typeof X")]
+    #[case("type A as typeof(X)")]
+    fn typeof_is_a_syntax_error(#[case] source: &str) {
+        let diagnostic = parse_source(source).unwrap_err();
+
+        assert_eq!(diagnostic.message, "expected an expression, found `typeof`");
+        assert_eq!(diagnostic.span.start, "type A as ".len());
+    }
+
+    fn nesting_error(source: &str, limit: usize) -> (String, usize) {
+        let diagnostic = with_max_nesting(limit, || parse_source(source)).unwrap_err();
+
+        (diagnostic.message, diagnostic.span.start)
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let source = format!("type A as {}1{}", "[".repeat(1000), "]".repeat(1000));
+
+        assert_eq!(
+            nesting_error(&source, DEFAULT_MAX_NESTING),
+            (
+                "expression nesting exceeds limit of 512".to_string(),
+                "type A as ".len() + 512
+            )
+        );
+    }
+
+    #[rstest]
+    #[case("keyof ", "")]
+    #[case("?", "")]
+    #[case("readonly ", "")]
+    #[case("", "[]")]
+    #[case("", "[0]")]
+    #[case("", "(1)")]
+    fn deep_operator_chains(#[case] prefix: &str, #[case] postfix: &str) {
+        let source = format!("type A as {}T{}", prefix.repeat(2000), postfix.repeat(2000));
+
+        let offset = match prefix {
+            "" => "type A as T".len() + 512 * postfix.len(),
+            prefix => "type A as ".len() + 512 * prefix.len(),
+        };
+
+        assert_eq!(
+            nesting_error(&source, DEFAULT_MAX_NESTING),
+            (
+                "expression nesting exceeds limit of 512".to_string(),
+                offset
+            )
+        );
+    }
+
+    #[test]
+    fn operator_chains_under_the_limit() {
+        let source = format!("type A as [{}]", vec!["keyof T[]"; 1000].join(", "));
+
+        assert!(with_max_nesting(4, || parse_source(&source)).is_ok());
+    }
+
+    #[rstest]
+    #[case("type A as [[[{a: (1)}]]]", 17)]
+    #[case("type A as [[[[[1]]]]]", 14)]
+    #[case("type A as [1, [2, [3, [4, [5]]]]]", 26)]
+    fn nesting_limit(#[case] source: &str, #[case] offset: usize) {
+        assert_eq!(
+            nesting_error(source, 4),
+            ("expression nesting exceeds limit of 4".to_string(), offset)
+        );
+    }
+
+    /// Conditionals nest without brackets, their nesting is counted as they are parsed.
+    #[test]
+    fn nested_conditionals() {
+        let source = "type A(T) as if T <: 1 then if T <: 2 then if T <: 3 then 3 end end end";
+
+        assert_eq!(
+            nesting_error(source, 2),
+            (
+                "expression nesting exceeds limit of 2".to_string(),
+                source.find("T <: 3").unwrap()
+            )
+        );
+        assert!(with_max_nesting(3, || parse_source(source)).is_ok());
+    }
+
+    mod streaming {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn spans(nodes: &[Ast]) -> Vec<(usize, usize)> {
+            nodes
+                .iter()
+                .map(|node| (node.as_span().start, node.as_span().end))
+                .collect()
+        }
+
+        #[test]
+        fn as_parsed_program() {
+            let source = dedent!(
+                r#"
+                // A comment before the first statement.
+                type A as 1
+
+                /** Documents `B`. */
+                export interface B { b: A }
+
+                /* Not documentation
+                type G as 1
+                */
+                union C = D { d: string } | E
+
+                type H as {
+                union: string,
+                brand: string
+                }
+                import { F } from "./f"
+                "#
+            );
+
+            let Ast::Program(Program {
+                statements: expected,
+                ..
+            }) = parse_newtype_program(source).unwrap()
+            else {
+                unreachable!()
+            };
+
+            let streamed: Vec<_> = crate::parser::statements(source, DEFAULT_MAX_NESTING)
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            assert_eq!(streamed, expected);
+            assert_eq!(spans(&streamed), spans(&expected));
+        }
+
+        #[test]
+        fn syntax_error() {
+            let source = "type A as 1\ntype B as\ntype C as 3";
+
+            let results: Vec<_> = crate::parser::statements(source, DEFAULT_MAX_NESTING).collect();
+
+            let [Ok(_), Err(diagnostic)] = results.as_slice() else {
+                panic!("expected a statement and an error, got {results:?}");
+            };
+
+            assert_eq!(&source[diagnostic.span.start..], "type C as 3");
+        }
+
+        #[test]
+        fn unterminated_block_comment() {
+            let source = "type A as 1\n/* outer /* inner */\ntype B as 2";
+
+            let results: Vec<_> = crate::parser::statements(source, DEFAULT_MAX_NESTING).collect();
+
+            let [Ok(_), Err(diagnostic)] = results.as_slice() else {
+                panic!("expected a statement and an error, got {results:?}");
+            };
+
+            assert_eq!(diagnostic.message, "unterminated block comment");
+            assert_eq!((diagnostic.span.start, diagnostic.span.end), (12, 14));
+        }
 
-        match result {
-            Ok(pairs) => {
-                let actual = parser::parse_expr(pairs).simplify().render_pretty_ts(80);
+        #[test]
+        fn deep_nesting() {
+            let source = "type A as [[1]]\ntype B as [[[1]]]";
 
-                pretty_assertions::assert_eq!(actual, expected);
-            }
-            Err(err) => {
-                panic!("{}", err);
+            let results: Vec<_> = crate::parser::statements(source, 2).collect();
+
+            let [Ok(_), Err(diagnostic)] = results.as_slice() else {
+                panic!("expected a statement and an error, got {results:?}");
+            };
+
+            assert_eq!(diagnostic.message, "expression nesting exceeds limit of 2");
+            assert_eq!(&source[diagnostic.span.start..], "[1]]]");
+        }
+
+        /// A program of 100k indented statements is parsed one statement at a time, each from the
+        /// text up to the next one rather than from the rest of the program.
+        #[test]
+        fn large_program() {
+            let source = (0..100_000)
+                .map(|i| format!("  type A{i} as {{value: {i}}}"))
+                .join("\n");
+
+            let mut count = 0;
+
+            for parsed in crate::parser::statements(&source, DEFAULT_MAX_NESTING) {
+                let span = parsed.unwrap().as_span();
+                assert_eq!(
+                    source[span.start..span.end].trim(),
+                    format!("type A{count} as {{value: {count}}}")
+                );
+                count += 1;
             }
+
+            assert_eq!(count, 100_000);
         }
     }
 
@@ -1285,6 +2622,43 @@ mod parser_tests {
         );
     }
 
+    #[test]
+    fn import_statement_string_names() {
+        assert_typescript!(
+            r#"import type { 'a-b' as AB, C } from 'a';"#,
+            r#"import { "a-b" as AB, C } from :a"#
+        );
+        assert!(parse_source(r#"import { "a-b" } from :a"#).is_err());
+    }
+
+    // Names of other modules are kept, even if TypeScript reserves them as local names.
+    #[test]
+    fn import_statement_reserved_names() {
+        assert_typescript!(
+            "import type { default as X } from 'n';\n\ntype A = X;",
+            "import { default as X } from :n\ntype A as X"
+        );
+        assert_typescript!(
+            "import type * as M from 'm';\n\ntype A = M.default;",
+            "import * as M from :m\ntype A as M::default"
+        );
+    }
+
+    #[test]
+    fn global_is_a_name_outside_declarations() {
+        assert_typescript!(
+            "type global = 1;\n\ntype A = global;\n\ntype B = {global: global};",
+            "type global as 1\ntype A as global\ntype B as { global: global }"
+        );
+
+        let Ast::Program(Program { statements, .. }) =
+            parse_source("global type G as 1\nglobal interface I {}").unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(statements.iter().all(Ast::is_global));
+    }
+
     #[test]
     fn array_access_single_quote() {
         assert_typescript!(expr, "A['field']", "A['field']");
@@ -1347,6 +2721,19 @@ mod parser_tests {
         fn unknown() {
             assert_typescript!("type A = unknown;", "type A as unknown");
         }
+
+        #[test]
+        fn symbol() {
+            assert_typescript!(
+                "type A = symbol | unique symbol;",
+                "type A as symbol | unique   symbol"
+            );
+        }
+
+        #[test]
+        fn unique_is_a_name_on_its_own() {
+            assert_typescript!("type A = [unique, symbol];", "type A as [unique, symbol]");
+        }
     }
 
     mod literals {
@@ -1503,6 +2890,79 @@ mod parser_tests {
         }
     }
 
+    /// Control expressions are closed by `end`, so they can be used as arm and branch bodies
+    /// without parentheses.
+    mod nested_control_expressions {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn match_in_cond_arm() {
+            assert_eq!(
+                sexpr!("cond do a <: b -> match c do 1 -> 2, else -> 3 end, else -> 4 end")
+                    .unwrap(),
+                sexp!(
+                    (#"cond-expr"
+                        (arms
+                            ((condition #"extends-infix-op" (lhs ident . "a") (op . extends) (rhs ident . "b"))
+                             (body match
+                                (value ident . "c")
+                                (arms ((pattern number . "1") (body number . "2")))
+                                (#"else-arm" number . "3"))))
+                        (#"else-arm" (number . "4")))
+                )
+            );
+        }
+
+        #[test]
+        fn if_in_match_arm() {
+            assert_eq!(
+                sexpr!("match a do 1 -> if a <: b then c else d end, 2 -> 3 end").unwrap(),
+                sexp!(
+                    (match
+                        (value ident . "a")
+                        (arms
+                            ((pattern number . "1")
+                             (body if
+                                (condition #"extends-infix-op" (lhs ident . "a") (op . extends) (rhs ident . "b"))
+                                (#"then-branch" ident . "c")
+                                (#"else-branch" (ident . "d"))))
+                            ((pattern number . "2") (body number . "3")))
+                        (#"else-arm" . never))
+                )
+            );
+        }
+
+        #[test]
+        fn let_in_if_branch() {
+            assert_eq!(
+                sexpr!("if a <: b then let x = 1 in x else 2 end").unwrap(),
+                sexp!(
+                    (if
+                        (condition #"extends-infix-op" (lhs ident . "a") (op . extends) (rhs ident . "b"))
+                        (#"then-branch" let (("x" (number . "1"))) (ident . "x"))
+                        (#"else-branch" (number . "2")))
+                )
+            );
+        }
+
+        #[test]
+        fn let_in_match_arm_stops_at_arm_separator() {
+            assert_eq!(
+                sexpr!("match a do 1 -> let x = 1 in x, 2 -> 3 end").unwrap(),
+                sexp!(
+                    (match
+                        (value ident . "a")
+                        (arms
+                            ((pattern number . "1")
+                             (body let (("x" (number . "1"))) (ident . "x")))
+                            ((pattern number . "2") (body number . "3")))
+                        (#"else-arm" . never))
+                )
+            );
+        }
+    }
+
     mod if_expr {
         const R: Rule = if_expr;
         use super::*;
@@ -1726,11 +3186,9 @@ mod parser_tests {
             assert_typescript!(
                 if_expr,
                 r#"
-                a extends b
-                    ? never
-                    : c extends d
-                        ? x
-                        : never
+                a extends b ? never
+                : c extends d ? x
+                : never
                 "#,
                 r#"
                 if not (a <: b) then
@@ -1744,11 +3202,9 @@ mod parser_tests {
             assert_typescript!(
                 if_expr,
                 r#"
-                a extends b
-                    ? never
-                    : c extends d
-                        ? never
-                        : x
+                a extends b ? never
+                : c extends d ? never
+                : x
                 "#,
                 r#"
                 if not (a <: b) then
@@ -1765,11 +3221,9 @@ mod parser_tests {
             assert_typescript!(
                 if_expr,
                 r#"
-                a extends b
-                    ? never
-                    : c extends d
-                        ? x
-                        : never
+                a extends b ? never
+                : c extends d ? x
+                : never
                 "#,
                 r#"
                 if not (a <: b) and c <: d then
@@ -1823,6 +3277,49 @@ mod parser_tests {
                 "if x <: keyof(y) then 1 end"
             );
         }
+
+        #[test]
+        fn non_distributive() {
+            assert_typescript!(
+                "type A<T> = [T] extends [string | number] ? 1 : [T] extends [boolean] ? 0 : 2;",
+                "type A(T) as if not (T </:! string | number) then 1 else if T <:! boolean then 0 else 2 end end"
+            );
+        }
+
+        #[test]
+        fn distributive_by_default() {
+            assert_typescript!(
+                "type A<T> = T extends string | number ? 1 : 0;",
+                "type A(T) as if T <: string | number then 1 else 0 end"
+            );
+        }
+
+        #[test]
+        fn non_distributive_through_and() {
+            assert_typescript!(
+                r#"
+                type A<T, U> =
+                    [T] extends [1]
+                        ? U extends 2
+                            ? [T | U] extends [3]
+                                ? 4
+                                : 5
+                            : 5
+                        : 5;
+                "#,
+                "type A(T, U) as if T <:! 1 and U <: 2 and T | U <:! 3 then 4 else 5 end"
+            );
+        }
+
+        #[test]
+        fn non_distributive_sexpr() {
+            assert_sexpr!(
+                Rule::extends_expr,
+                crate::parser::parse_extends_expr,
+                "A <:! B",
+                lexpr::sexp!((#"extends-infix-op" (lhs #"ident" . "A") (op . #"extends") (rhs #"ident" . "B") (#"non-distributive" . #t)))
+            );
+        }
     }
 
     #[test]
@@ -1838,6 +3335,32 @@ mod parser_tests {
         );
     }
 
+    #[test]
+    fn for_in_with_modifiers() {
+        assert_typescript!(
+            map_expr,
+            r#"
+            { readonly [k in t as `get_${k}`]?: 1 }
+            "#,
+            r#"
+            map readonly ?k in t as `get_${k}` do 1 end
+            "#
+        );
+    }
+
+    #[test]
+    fn for_in_removing_modifiers() {
+        assert_typescript!(
+            map_expr,
+            r#"
+            { -readonly [k in t]-?: 1 }
+            "#,
+            r#"
+            map -readonly -?k in t do 1 end
+            "#
+        );
+    }
+
     mod object_literal {
         const R: Rule = object_literal;
         use super::*;
@@ -1876,6 +3399,23 @@ mod parser_tests {
         fn computed_property() {
             assert_typescript!(R, "{[K]: T}", "{[K]: T}");
         }
+
+        #[test]
+        fn computed_symbol_property() {
+            assert_typescript!(
+                "type Brand = unique symbol;\n\ntype Branded = {[Brand]: true, readonly value: string};",
+                "type Brand as unique symbol\ntype Branded as {[Brand]: true, readonly value: string}"
+            );
+        }
+
+        #[test]
+        fn reserved_word_keys_are_quoted() {
+            assert_typescript!(
+                R,
+                "{'new': 1, 'default': 2, async: 3}",
+                "{new: 1, default: 2, async: 3}"
+            );
+        }
     }
 
     mod tuple {
@@ -1941,6 +3481,31 @@ mod parser_tests {
             assert_typescript!(R, "type A<x, y, z> = 1", "type A(x, y, z) as 1");
         }
 
+        #[test]
+        fn unicode_identifiers() {
+            assert_typescript!(
+                R,
+                "type Größe<値> = {größe: 値}",
+                "type Größe(値) as { größe: 値 }"
+            );
+        }
+
+        #[test]
+        fn identifiers_are_nfc_normalized() {
+            // "é" as a single code point in the declaration, and as "e" with a combining acute
+            // accent in the reference.
+            let composed = sexpr!(R, "type A(caf\u{e9}) as caf\u{e9}").unwrap();
+            let decomposed = sexpr!(R, "type A(caf\u{e9}) as cafe\u{301}").unwrap();
+
+            pretty_assertions::assert_eq!(composed, decomposed);
+
+            assert_typescript!(
+                R,
+                "type A<caf\u{e9}> = caf\u{e9}",
+                "type A(cafe\u{301}) as cafe\u{301}"
+            );
+        }
+
         #[test]
         fn generic_with_guard() {
             assert_typescript!(R, "type A<x, y, z> = 1", "type A(x, y, z) as 1");
@@ -1979,6 +3544,81 @@ mod parser_tests {
         }
     }
 
+    mod union_declaration {
+        use super::*;
+
+        #[test]
+        fn default_discriminant() {
+            assert_typescript!(
+                r#"
+                export type Circle = {kind: 'Circle', radius: number};
+
+                export type Square = {kind: 'Square', size: number};
+
+                export type Point = {kind: 'Point'};
+
+                /** Shapes */
+                export type Shape = Circle | Square | Point;
+                "#,
+                r#"
+                /** Shapes */
+                export union Shape = Circle { radius: number } | Square { size: number } | Point
+                "#
+            );
+        }
+
+        #[test]
+        fn custom_discriminant() {
+            assert_typescript!(
+                r#"
+                type Click = {type: 'Click', x: number};
+
+                type Key = {type: 'Key', code: string};
+
+                type Event = Click | Key;
+                "#,
+                r#"
+                union Event by "type" =
+                    | Click { x: number }
+                    | Key { code: string }
+                "#
+            );
+        }
+
+        #[test]
+        fn exhaustive_match() {
+            let source = dedent!(
+                r#"
+                union Shape = Circle { radius: number } | Square { size: number }
+
+                type Size(S) as match S do
+                    Circle -> S["radius"],
+                    Square -> S["size"],
+                end
+                "#
+            );
+
+            let output = crate::compile::compile(source, &Default::default()).unwrap();
+
+            pretty_assertions::assert_eq!(output.diagnostics, vec![]);
+            pretty_assertions::assert_eq!(
+                output.typescript.trim(),
+                dedent!(
+                    r#"
+                    type Circle = {kind: 'Circle', radius: number};
+
+                    type Square = {kind: 'Square', size: number};
+
+                    type Shape = Circle | Square;
+
+                    type Size<S> = S extends Circle ? S['radius'] : S extends Square ? S['size'] : never;
+                    "#
+                )
+                .trim()
+            );
+        }
+    }
+
     mod interface {
         const R: Rule = Rule::interface;
         use super::*;
@@ -1996,6 +3636,27 @@ mod parser_tests {
             );
         }
 
+        #[test]
+        fn fluent() {
+            assert_typescript!(
+                R,
+                r#"
+                interface Query<T> {
+                    where: (field: keyof T, value: unknown) => this;
+                    limit: (count: number) => this;
+                    run: () => Promise<T[]>;
+                }
+                "#,
+                r#"
+                interface Query(T) {
+                    where: (field: keyof T, value: unknown) => this,
+                    limit: (count: number) => this,
+                    run: () => Promise(T[]),
+                }
+                "#
+            );
+        }
+
         #[test]
         fn params() {
             assert_typescript!(
@@ -2167,7 +3828,6 @@ mod parser_tests {
         use super::*;
         use pretty_assertions::assert_eq;
 
-        #[ignore = "whitespace issues"]
         #[test]
         fn typescript_no_output() {
             assert_typescript!(
@@ -2226,6 +3886,83 @@ mod parser_tests {
         assert_typescript!(expr, "keyof keyof A", "keyof(keyof(A))");
     }
 
+    #[test]
+    fn type_guard() {
+        assert_typescript!(
+            "type IsString = (value: unknown) => value is string | String;",
+            "type IsString as (value: unknown) => value is string | String"
+        );
+    }
+
+    #[test]
+    fn assertion_signatures() {
+        assert_typescript!(
+            r#"
+            type Assert = (condition: unknown, message: string) => asserts condition;
+
+            type AssertString = (value: unknown) => asserts value is string;
+            "#,
+            r#"
+            type Assert as (condition: unknown, message: string) => asserts condition
+            type AssertString as (value: unknown) => asserts value is string
+            "#
+        );
+    }
+
+    #[test]
+    fn predicates_are_not_return_types() {
+        // `asserts` and `is` are only keywords in a predicate.
+        assert_typescript!(
+            "type F = (is: asserts) => asserts;",
+            "type F as (is: asserts) => asserts"
+        );
+    }
+
+    #[test]
+    fn ambient_modules() {
+        assert_typescript!(
+            r#"
+            import type { User } from './user';
+
+            declare module 'express' {
+                import { Session } from 'express-session';
+
+                /** The signed in user. */
+                interface Request {
+                    user: User;
+                    session: Session;
+                }
+
+                type Handler = (request: Request) => void;
+            }
+
+            declare module 'empty' {}
+            "#,
+            r#"
+            import { User } from "./user"
+
+            declare module "express" do
+                import { Session } from "express-session"
+
+                /** The signed in user. */
+                interface Request {user: User, session: Session}
+
+                type Handler as (request: Request) => void
+            end
+
+            declare module :empty do end
+            "#
+        );
+    }
+
+    #[test]
+    fn ambient_modules_do_not_nest() {
+        assert!(
+            parse_newtype_program("declare module :a do\n    declare module :b do end\nend")
+                .is_err()
+        );
+    }
+
     #[test]
     fn match_expr() {
         assert_typescript!(
@@ -2242,18 +3979,26 @@ mod parser_tests {
         );
     }
 
+    #[test]
+    fn dependent_constraint() {
+        assert_typescript!(
+            r#"
+            type Get<T, K extends keyof T> = T[K];
+            "#,
+            r#"
+            type Get(T, K) where K <: keyof(T) as T[K]
+            "#
+        );
+    }
+
     #[test]
     fn cond_expr() {
         assert_typescript!(
             r#"
             type A<x> =
-                x extends number
-                    ? 1
-                    : x extends {}
-                        ? x extends {a: 1}
-                            ? 2
-                            : never
-                        : never;
+                x extends number ? 1
+                : x extends {} ? x extends {a: 1} ? 2 : never
+                : never;
             "#,
             r#"
             type A(x) as cond do
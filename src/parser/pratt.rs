@@ -1,42 +1,130 @@
-use pest::pratt_parser::PrattParser;
+/// Precedence and associativity of the operators, in a table for expressions and one for
+/// conditions. An operator is added with its rule in the grammar, a line in its table, and the node
+/// it builds in [`crate::parser::parse_expr`] or [`crate::parser::parse_extends_expr`].
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 
 use crate::parser::Rule;
 
-lazy_static::lazy_static! {
-    pub(crate) static ref EXPR_PARSER: PrattParser<Rule> = {
-        use pest::pratt_parser::{Assoc::*, Op};
-        use Rule::*;
-
-        // Precedence is defined lowest to highest
-        PrattParser::new()
-            .op(Op::infix(union, Left))
-            .op(Op::infix(intersection, Left))
-            .op(Op::infix(pipe, Left))
-            .op(Op::postfix(application))
-            .op(Op::postfix(array_modifier))
-            .op(Op::prefix(infer))
-            .op(Op::infix(colon2, Left) | Op::infix(dot_op, Left))
-            .op(Op::postfix(indexed_access))
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fixity {
+    Prefix,
+    Postfix,
+    Infix(Assoc),
+}
+
+/// Operators binding as tightly as one another.
+type Level = &'static [(Rule, Fixity)];
+
+/// Operators of expressions, from the loosest binding level to the tightest.
+#[rustfmt::skip]
+pub(crate) const EXPR_OPERATORS: &[Level] = {
+    use Assoc::*;
+    use Fixity::*;
+    use Rule::*;
+
+    &[
+        &[(union, Infix(Left))],
+        &[(intersection, Infix(Left))],
+        &[(pipe, Infix(Left))],
+        &[(builtin_prefix, Prefix)],
+        &[(application, Postfix)],
+        &[(array_modifier, Postfix)],
+        &[(infer, Prefix)],
+        &[(colon2, Infix(Left)), (dot_op, Infix(Left))],
+        &[(indexed_access, Postfix)],
+    ]
+};
+
+/// Operators of conditions, from the loosest binding level to the tightest.
+#[rustfmt::skip]
+pub(crate) const CONDITION_OPERATORS: &[Level] = {
+    use Assoc::*;
+    use Fixity::*;
+    use Rule::*;
 
-    pub(crate) static ref EXTENDS_PARSER: PrattParser<Rule> = {
-        use pest::pratt_parser::{Assoc::*, Op};
-        use Rule::*;
-
-        // Precedence is defined lowest to highest
-        PrattParser::new()
-            .op(
-                Op::infix(or, Left)
-                | Op::infix(and, Left)
-            )
-            .op(
-                Op::infix(extends, Left)
-                | Op::infix(not_extends, Left)
-                | Op::infix(equals, Left)
-                | Op::infix(not_equals, Left)
-                | Op::infix(strict_equals, Left)
-                | Op::infix(strict_not_equals, Left)
-            )
-            .op(Op::prefix(not))
+    &[
+        &[(or, Infix(Left)), (and, Infix(Left))],
+        &[
+            (extends, Infix(Left)),
+            (not_extends, Infix(Left)),
+            (equals, Infix(Left)),
+            (not_equals, Infix(Left)),
+            (strict_equals, Infix(Left)),
+            (strict_not_equals, Infix(Left)),
+        ],
+        &[(not, Prefix)],
+    ]
+};
+
+/// The rules of the operators of `table` with the fixity `kind`, what a parse error expects in
+/// place of an unknown operator.
+pub(crate) fn rules(table: &[Level], kind: fn(Fixity) -> bool) -> Vec<Rule> {
+    table
+        .iter()
+        .flat_map(|level| level.iter())
+        .filter(|(_, fixity)| kind(*fixity))
+        .map(|(rule, _)| *rule)
+        .collect()
+}
+
+impl Fixity {
+    pub(crate) fn is_prefix(self) -> bool {
+        self == Fixity::Prefix
+    }
+
+    pub(crate) fn is_postfix(self) -> bool {
+        self == Fixity::Postfix
+    }
+
+    pub(crate) fn is_infix(self) -> bool {
+        matches!(self, Fixity::Infix(_))
+    }
+}
+
+fn build(table: &[Level]) -> PrattParser<Rule> {
+    let op = |&(rule, fixity): &(Rule, Fixity)| match fixity {
+        Fixity::Prefix => Op::prefix(rule),
+        Fixity::Postfix => Op::postfix(rule),
+        Fixity::Infix(assoc) => Op::infix(rule, assoc),
     };
+
+    table.iter().fold(PrattParser::new(), |parser, level| {
+        let (first, rest) = level.split_first().expect("a level has an operator");
+        parser.op(rest.iter().map(op).fold(op(first), |ops, next| ops | next))
+    })
+}
+
+lazy_static::lazy_static! {
+    pub(crate) static ref EXPR_PARSER: PrattParser<Rule> = build(EXPR_OPERATORS);
+
+    pub(crate) static ref EXTENDS_PARSER: PrattParser<Rule> = build(CONDITION_OPERATORS);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::parse;
+    use pretty_assertions::assert_eq;
+
+    /// Expressions and their trees, a source line followed by the s-expression of its tree, the
+    /// entries separated by blank lines. Every parse of an operator is in it, a change of the
+    /// tables has to leave them as they are.
+    const CORPUS: &str = include_str!("corpus.txt");
+
+    #[test]
+    fn corpus() {
+        let (expected, actual): (Vec<_>, Vec<_>) = CORPUS
+            .split("\n\n")
+            .map(|entry| {
+                let (source, sexp) = entry.trim().split_once('\n').unwrap();
+                let tree = parse!(crate::parser::Rule::expr, source);
+
+                (
+                    format!("{source}\n{sexp}"),
+                    format!("{source}\n{}", tree.to_sexp().unwrap()),
+                )
+            })
+            .unzip();
+
+        assert_eq!(actual.join("\n\n"), expected.join("\n\n"));
+    }
 }
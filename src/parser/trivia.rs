@@ -0,0 +1,232 @@
+/// Scanning of the comments the grammar discards as trivia.
+///
+/// Block comments nest, so `/* a /* b */ c */` is a single comment.
+
+/// Returns the documentation of the declaration that directly follows `gap`, where `gap` is the
+/// text between the last token of the previous statement and the start of the declaration. Besides
+/// trivia it can only contain the keywords and punctuation that close the previous statement.
+///
+/// Only the last comment of the gap is considered, and only if it is a doc comment (`/** ... */`).
+pub(crate) fn doc_comment(gap: &str) -> Option<String> {
+    let mut last = None;
+    let mut rest = gap;
+
+    while let Some(offset) = rest.find(|c: char| !c.is_whitespace()) {
+        rest = &rest[offset..];
+
+        if rest.starts_with("//") {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            last = Some(&rest[..len]);
+            rest = &rest[len..];
+        } else if rest.starts_with("/*") {
+            let len = block_comment_len(rest)?;
+            last = Some(&rest[..len]);
+            rest = &rest[len..];
+        } else {
+            last = None;
+            rest = &rest[rest.chars().next().map_or(1, char::len_utf8)..];
+        }
+    }
+
+    let comment = last?;
+
+    if comment == "/**/" || !comment.starts_with("/**") {
+        return None;
+    }
+
+    let body = &comment[3..comment.len() - 2];
+
+    let lines = body
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .skip_while(|line| line.is_empty())
+        .collect::<Vec<_>>();
+
+    let len = lines.len() - lines.iter().rev().take_while(|l| l.is_empty()).count();
+
+    Some(lines[..len].join("\n"))
+}
+
+/// Returns the offset of the first block comment opener in `source` that is never closed.
+pub(crate) fn unterminated_block_comment(source: &str) -> Option<usize> {
+    let mut offset = 0;
+
+    while offset < source.len() {
+        let rest = &source[offset..];
+
+        if rest.starts_with("//") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+        } else if rest.starts_with("/*") {
+            match block_comment_len(rest) {
+                Some(len) => offset += len,
+                None => return Some(offset),
+            }
+        } else if let Some(quote @ ('"' | '\'' | '`')) = rest.chars().next() {
+            offset += rest[1..].find(quote).map_or(rest.len(), |end| end + 2);
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    None
+}
+
+/// Returns the offset of the first bracket of `source` opened inside `limit` others, outside of
+/// comments and strings. Pest recurses into each, so the source is checked before it is parsed.
+pub(crate) fn bracket_nesting_over(source: &str, limit: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut offset = 0;
+
+    while offset < source.len() {
+        let rest = &source[offset..];
+
+        if rest.starts_with("//") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+        } else if rest.starts_with("/*") {
+            offset += block_comment_len(rest).unwrap_or(rest.len());
+        } else if let Some(quote @ ('"' | '\'' | '`')) = rest.chars().next() {
+            offset += rest[1..].find(quote).map_or(rest.len(), |end| end + 2);
+        } else {
+            match rest.as_bytes()[0] {
+                b'(' | b'[' | b'{' if depth == limit => return Some(offset),
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    None
+}
+
+/// Returns the offset and the text, up to the end of the line, of each line of `source` starting
+/// with `pragma`, leading whitespace aside, outside of comments and strings.
+pub(crate) fn pragma_lines<'a>(source: &'a str, pragma: &str) -> Vec<(usize, &'a str)> {
+    let mut lines = vec![];
+    let mut offset = 0;
+    // Whether only whitespace precedes `offset` on its line.
+    let mut line_start = true;
+
+    while offset < source.len() {
+        let rest = &source[offset..];
+
+        if line_start && rest.starts_with(pragma) {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            lines.push((offset, &rest[..len]));
+            offset += len;
+        } else if rest.starts_with("//") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+        } else if rest.starts_with("/*") {
+            offset += block_comment_len(rest).unwrap_or(rest.len());
+            line_start = false;
+        } else if let Some(quote @ ('"' | '\'' | '`')) = rest.chars().next() {
+            offset += rest[1..].find(quote).map_or(rest.len(), |end| end + 2);
+            line_start = false;
+        } else {
+            let c = rest.chars().next().unwrap();
+            line_start = c == '\n' || (line_start && c.is_whitespace());
+            offset += c.len_utf8();
+        }
+    }
+
+    lines
+}
+
+/// Length of the (possibly nested) block comment at the start of `text`, including its
+/// delimiters, or `None` if it is unterminated.
+fn block_comment_len(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut offset = 0;
+
+    while offset < text.len() {
+        let rest = &text[offset..];
+
+        if rest.starts_with("/*") {
+            depth += 1;
+            offset += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            offset += 2;
+
+            if depth == 0 {
+                return Some(offset);
+            }
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[test]
+    fn pragma_lines_outside_of_strings_and_comments() {
+        let source =
+            "#source \"a\" line 1\n  #source \"b\" line 2\ntype A as '\n#source' // #source\n\
+                      /*\n#source */ type B as 1 #source\n#sourced";
+
+        assert_eq!(
+            pragma_lines(source, "#source"),
+            vec![
+                (0, "#source \"a\" line 1"),
+                (21, "#source \"b\" line 2"),
+                (106, "#sourced")
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("", None)]
+    #[case("\n\n", None)]
+    #[case("/* plain */", None)]
+    #[case("/**/", None)]
+    #[case("// line", None)]
+    #[case("/** doc */", Some("doc"))]
+    #[case("/** doc */\n// trailing", None)]
+    #[case("/** first */ /** second */", Some("second"))]
+    #[case("/**\n * one\n * two\n */\n", Some("one\ntwo"))]
+    #[case("/** outer /* inner */ */", Some("outer /* inner */"))]
+    #[case(" end\n/** doc */\n", Some("doc"))]
+    #[case("/** doc */ end\n", None)]
+    fn test_doc_comment(#[case] gap: &str, #[case] expected: Option<&str>) {
+        assert_eq!(doc_comment(gap).as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case("type A as 1", None)]
+    #[case("/* a /* b */ c */", None)]
+    #[case("type A as '/*'", None)]
+    #[case("// /*\ntype A as 1", None)]
+    #[case("type A as 1 /* a", Some(12))]
+    #[case("/* a /* b */ c", Some(0))]
+    fn test_unterminated_block_comment(#[case] source: &str, #[case] expected: Option<usize>) {
+        assert_eq!(unterminated_block_comment(source), expected);
+    }
+
+    #[rstest]
+    #[case("type A as [[1]]", 2, None)]
+    #[case("type A as [[1]]", 1, Some(11))]
+    #[case("type A as {a: (1)}", 1, Some(14))]
+    #[case("type A as [1] | [2]", 1, None)]
+    #[case("type A as '[[[' | [1]", 1, None)]
+    #[case("// [[[\ntype A as /* [[[ */ [1]", 1, None)]
+    fn test_bracket_nesting_over(
+        #[case] source: &str,
+        #[case] limit: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(bracket_nesting_over(source, limit), expected);
+    }
+}
@@ -0,0 +1,170 @@
+//! The items of the crate that programs embedding the compiler can rely on. Removing one, or
+//! changing what it is, is a breaking change. `tests/public-api.txt` records them with the rest of
+//! the public surface, the modules the binary uses included: the test of this module fails until
+//! it is updated with a change to any of them.
+//!
+//! ```
+//! use newtype::prelude::*;
+//!
+//! let output = compile("type Pair(T) as [T, T]", &CompileOptions::default()).unwrap();
+//!
+//! assert_eq!(output.typescript, "type Pair<T> = [T, T];\n\n");
+//! ```
+
+pub use crate::ast::build;
+pub use crate::ast::visit::{Visit, VisitMut};
+pub use crate::ast::{Ast, Span};
+pub use crate::compile::{compile, compile_streaming, CompileOptions, CompileOutput, OutputKind};
+pub use crate::config::Config;
+pub use crate::diagnostic::{Diagnostic, ErrorFormat, Severity};
+pub use crate::parser::{parse_source, parse_type};
+pub use crate::source::SourceFile;
+pub use crate::typescript::Pretty;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use itertools::Itertools;
+    use pretty_assertions::assert_eq;
+
+    /// The identifier `text` starts with.
+    fn ident(text: &str) -> &str {
+        let end = text
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(text.len());
+
+        &text[..end]
+    }
+
+    /// The public items of the module `module` at `file`, and of its public modules, one per line
+    /// with their kind: the items, the `pub use`s, the fields of structs, the variants of enums
+    /// and the methods of traits and of inherent impls. The items are read off the lines starting
+    /// them, as rustfmt lays them out.
+    fn items(module: &str, file: &Path, items: &mut Vec<String>) {
+        let source = std::fs::read_to_string(file).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        // Where the modules of the file are.
+        let dir = match file.file_stem().unwrap().to_str().unwrap() {
+            "lib" | "mod" => file.parent().unwrap().to_path_buf(),
+            stem => file.with_file_name(stem),
+        };
+        // The struct, enum, trait or inherent impl the lines are in, with its kind.
+        let mut container: Option<(&str, &str)> = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            let previous = lines[..i]
+                .iter()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .copied()
+                .unwrap_or("");
+
+            if *line == "}" {
+                container = None;
+            } else if let Some(rest) = line.strip_prefix("pub mod ") {
+                // Modules behind a feature aren't compiled by default.
+                if previous.starts_with("#[cfg(") {
+                    continue;
+                }
+
+                let name = ident(rest);
+                let path = format!("{module}::{name}");
+                items.push(format!("mod {path}"));
+
+                if rest.ends_with(';') {
+                    let file = dir.join(format!("{name}.rs"));
+                    let file = match file.exists() {
+                        true => file,
+                        false => dir.join(name).join("mod.rs"),
+                    };
+
+                    self::items(&path, &file, items);
+                }
+            } else if line.starts_with("pub use ") {
+                let statement = lines[i..]
+                    .iter()
+                    .map(|line| line.trim())
+                    .take_while_inclusive(|line| !line.ends_with(';'))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let statement = statement
+                    .trim_start_matches("pub use ")
+                    .trim_end_matches(';')
+                    .replace("{ ", "{")
+                    .replace(", }", "}")
+                    .replace(" }", "}");
+
+                items.push(format!("use {module}: {statement}"));
+            } else if let Some(rest) = line.strip_prefix("pub ") {
+                let rest = ["const fn ", "async fn ", "unsafe fn "]
+                    .iter()
+                    .find_map(|prefix| rest.strip_prefix(prefix).map(|rest| ("fn", rest)))
+                    .or_else(|| {
+                        [
+                            "fn", "struct", "enum", "trait", "type", "const", "static", "union",
+                        ]
+                        .iter()
+                        .find_map(|kind| Some((*kind, rest.strip_prefix(kind)?.strip_prefix(' ')?)))
+                    });
+
+                if let Some((kind, rest)) = rest {
+                    let name = ident(rest);
+                    items.push(format!("{kind} {module}::{name}"));
+
+                    if matches!(kind, "struct" | "enum" | "trait") && line.ends_with('{') {
+                        container = Some((kind, name));
+                    }
+                }
+            } else if let Some(rest) = line.strip_prefix("macro_rules! ") {
+                if previous == "#[macro_export]" {
+                    items.push(format!("macro crate::{}", ident(rest)));
+                }
+            } else if line.starts_with("impl") && line.ends_with('{') && !line.contains(" for ") {
+                let rest = match line.strip_prefix("impl<") {
+                    Some(rest) => rest.split_once("> ").unwrap().1,
+                    None => &line["impl ".len()..],
+                };
+
+                container = Some(("impl", ident(rest)));
+            } else if let (Some((kind, name)), Some(member)) =
+                (container, line.strip_prefix("    "))
+            {
+                let member = match kind {
+                    "impl" => member
+                        .strip_prefix("pub fn ")
+                        .or_else(|| member.strip_prefix("pub const fn "))
+                        .map(|rest| ("fn", rest))
+                        .or_else(|| Some(("const", member.strip_prefix("pub const ")?))),
+                    "struct" => member.strip_prefix("pub ").map(|rest| ("field", rest)),
+                    "enum" if member.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                        Some(("variant", member))
+                    }
+                    "trait" => member.strip_prefix("fn ").map(|rest| ("fn", rest)),
+                    _ => None,
+                };
+
+                if let Some((kind, rest)) = member {
+                    items.push(format!("{kind} {module}::{name}::{}", ident(rest)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn public_api() {
+        let mut listed = vec![];
+        items(
+            "crate",
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src/lib.rs"),
+            &mut listed,
+        );
+        listed.sort();
+
+        assert_eq!(
+            listed.join("\n") + "\n",
+            include_str!("../tests/public-api.txt"),
+            "the public API changed, update tests/public-api.txt if it is on purpose"
+        );
+    }
+}
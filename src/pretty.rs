@@ -1,10 +1,184 @@
 /// Utility functions for pretty printing
+use std::cell::Cell;
+
 use pretty::RcDoc;
 
+/// Quote of the string literals of the emitted TypeScript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quote {
+    #[default]
+    Single,
+    Double,
+}
+
+/// Layout details of the emitted TypeScript that don't change its meaning, see [`with_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    /// Columns of each level of indentation.
+    pub indent: isize,
+    pub quote: Quote,
+    /// Spaces inside the braces of an object type on one line, `{ a: 1 }`.
+    pub bracket_spacing: bool,
+    /// An object type an alias stands for starts on the line of the `=`.
+    pub hug_object_types: bool,
+    /// A union broken over several lines has a `|` before its first member too.
+    pub leading_pipe: bool,
+    /// The members of object types are separated by `;` rather than `,`.
+    pub semicolons: bool,
+}
+
+impl Style {
+    /// The compiler's own layout.
+    pub const DEFAULT: Self = Self {
+        indent: 4,
+        quote: Quote::Single,
+        bracket_spacing: false,
+        hug_object_types: false,
+        leading_pipe: false,
+        semicolons: false,
+    };
+
+    /// Prettier's defaults for type declarations, so that formatting the output with Prettier
+    /// doesn't change it. Aliases end in `;`, as do the members of interfaces and of object types
+    /// broken over several lines. The layout of conditional types broken over several lines is
+    /// the compiler's own.
+    pub const PRETTIER: Self = Self {
+        indent: 2,
+        quote: Quote::Double,
+        bracket_spacing: true,
+        hug_object_types: true,
+        leading_pipe: true,
+        semicolons: true,
+    };
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+thread_local! {
+    static STYLE: Cell<Style> = const { Cell::new(Style::DEFAULT) };
+}
+
+/// Runs `f` with the documents it builds laid out in `style`. Emission reads the style as it
+/// builds the document, rather than rewriting the text it renders.
+pub(crate) fn with_style<T>(style: Style, f: impl FnOnce() -> T) -> T {
+    /// Restores the style `f` replaced, even if it panics.
+    struct Restore(Style);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            STYLE.set(self.0);
+        }
+    }
+
+    let _restore = Restore(STYLE.replace(style));
+    f()
+}
+
+/// The style documents are built in, see [`with_style`].
+pub(crate) fn style() -> Style {
+    STYLE.get()
+}
+
+/// Columns of a level of indentation in the current style.
+pub(crate) fn indent() -> isize {
+    style().indent
+}
+
+/// Where text is written in the emitted TypeScript, which decides what is escaped in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Delimiter {
+    /// Between the quotes of a string literal.
+    Quote(char),
+    /// In the text of a template literal, between its placeholders.
+    Template,
+}
+
+/// `text` escaped to be read back as itself between `delimiter`: backslashes, the delimiter, line
+/// breaks, and the `${` starting a placeholder of a template. The one place the emitter escapes
+/// text, for the string literals, the quoted keys of objects and the text of templates alike.
+pub(crate) fn escape(text: &str, delimiter: Delimiter) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, delimiter) {
+            ('\\', _) => escaped.push_str("\\\\"),
+            ('\n', _) => escaped.push_str("\\n"),
+            ('\r', _) => escaped.push_str("\\r"),
+            // Line terminators in strings only since ES2019.
+            ('\u{2028}', _) => escaped.push_str("\\u2028"),
+            ('\u{2029}', _) => escaped.push_str("\\u2029"),
+            (c, Delimiter::Quote(quote)) if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ('`', Delimiter::Template) => escaped.push_str("\\`"),
+            ('$', Delimiter::Template) if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            (c, _) => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// A TypeScript string with the value `string`, in the quotes of the current style.
 pub(crate) fn string_literal(string: &str) -> RcDoc<()> {
-    RcDoc::text("\'")
-        .append(RcDoc::text(string.replace("\'", "\\\'")))
-        .append(RcDoc::text("\'"))
+    let quote = match style().quote {
+        Quote::Single => '\'',
+        Quote::Double => '"',
+    };
+
+    RcDoc::text(format!(
+        "{quote}{}{quote}",
+        escape(string, Delimiter::Quote(quote))
+    ))
+}
+
+/// The TypeScript template literal of `template`, written as in a program: between backticks,
+/// text taken as it is, like that of a string, and placeholders `${T}`. A `${` without its `}` is
+/// text.
+pub(crate) fn template_literal(template: &str) -> RcDoc<()> {
+    let mut rest = template
+        .strip_prefix('`')
+        .and_then(|inner| inner.strip_suffix('`'))
+        .unwrap_or(template);
+    let mut out = String::from("`");
+
+    while let Some(start) = rest.find("${") {
+        let Some(end) = placeholder_end(&rest[start..]) else {
+            break;
+        };
+
+        out += &escape(&rest[..start], Delimiter::Template);
+        out += &rest[start..start + end];
+        rest = &rest[start + end..];
+    }
+
+    out += &escape(rest, Delimiter::Template);
+    out.push('`');
+
+    RcDoc::text(out)
+}
+
+/// Length of the placeholder at the start of `rest`, up to the `}` closing its `${`.
+pub(crate) fn placeholder_end(rest: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (i, c) in rest.char_indices().skip(1) {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 1 => return Some(i + 1),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
 }
 
 pub(crate) fn double_quote(doc: RcDoc<()>) -> RcDoc<()> {
@@ -25,3 +199,89 @@ where
 {
     RcDoc::text(left).append(doc).append(RcDoc::text(right))
 }
+
+/// A comma that is only emitted when the enclosing group is laid out on multiple lines.
+pub(crate) fn trailing_comma<'a>() -> RcDoc<'a, ()> {
+    RcDoc::text(",").flat_alt(RcDoc::nil())
+}
+
+/// A JSDoc comment, followed by a line break.
+pub(crate) fn jsdoc<'a>(doc: &str) -> RcDoc<'a, ()> {
+    let doc = doc.replace("*/", "*\\/");
+
+    let comment = match doc.lines().collect::<Vec<_>>().as_slice() {
+        [] => RcDoc::text("/** */"),
+        [line] => RcDoc::text(format!("/** {line} */")),
+        lines => {
+            let lines = lines.iter().map(|line| {
+                let line = format!(" * {line}");
+                RcDoc::text(line.trim_end().to_string()).append(RcDoc::hardline())
+            });
+
+            RcDoc::text("/**")
+                .append(RcDoc::hardline())
+                .append(RcDoc::concat(lines))
+                .append(" */")
+        }
+    };
+
+    comment.append(RcDoc::hardline())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn rendered(doc: RcDoc<()>) -> String {
+        let mut out = Vec::new();
+        doc.render(80, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[rstest]
+    #[case("it's", "'it\\'s'")]
+    #[case("say \"hi\"", "'say \"hi\"'")]
+    #[case("a\\b", "'a\\\\b'")]
+    #[case("line\nbreak\r", "'line\\nbreak\\r'")]
+    #[case("`${T}`", "'`${T}`'")]
+    fn string_literals(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(rendered(string_literal(value)), expected);
+    }
+
+    #[test]
+    fn double_quoted_string_literals() {
+        let style = Style {
+            quote: Quote::Double,
+            ..Style::DEFAULT
+        };
+
+        assert_eq!(
+            with_style(style, || rendered(string_literal("it's \"a\""))),
+            "\"it's \\\"a\\\"\""
+        );
+    }
+
+    #[test]
+    fn style_is_restored_on_panic() {
+        let panicked = std::panic::catch_unwind(|| {
+            with_style(Style::PRETTIER, || panic!("emission failed"));
+        });
+
+        assert!(panicked.is_err());
+        assert_eq!(style(), Style::DEFAULT);
+    }
+
+    #[rstest]
+    #[case("`a${T}b`", "`a${T}b`")]
+    #[case("`${{a: ${B}}}`", "`${{a: ${B}}}`")]
+    #[case("`cost: $${T}`", "`cost: $${T}`")]
+    #[case("`a${b`", "`a\\${b`")]
+    #[case("`a\\b ${T} \\`", "`a\\\\b ${T} \\\\`")]
+    #[case("`a`b`", "`a\\`b`")]
+    #[case("`two\nlines`", "`two\\nlines`")]
+    fn template_literals(#[case] template: &str, #[case] expected: &str) {
+        assert_eq!(rendered(template_literal(template)), expected);
+    }
+}
@@ -0,0 +1,702 @@
+/// Queries of a parsed program at a position of its source or in a range of it, for editors, and of
+/// the nodes matching a pattern, for lints and codemods.
+use std::{cell::RefCell, collections::BTreeMap, ops::Range};
+
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        visit::{walk_ast, walk_ast_mut, Visit, VisitMut},
+        Access, AccessKind, ApplyGeneric, Ast, ObjectProperty, ObjectPropertyKey, Program, Span,
+        Walk,
+    },
+    diagnostic::Diagnostic,
+    error::humanize_pest,
+    lint::Signatures,
+    parser::{parse_newtype_expr, STATEMENT_KEYWORDS},
+    rewrite::{declarations, names_in_scope, Declaration, SymbolKind},
+    runtime::MacroRegistry,
+};
+
+/// Keywords that start an expression.
+const EXPRESSION_KEYWORDS: &[&str] = &["if", "match", "cond", "let", "map"];
+
+/// Types written as a keyword.
+const TYPE_KEYWORDS: &[&str] = &[
+    "any",
+    "unknown",
+    "never",
+    "true",
+    "false",
+    "string",
+    "number",
+    "boolean",
+    "bigint",
+    "symbol",
+    "unique symbol",
+    "object",
+    "void",
+    "null",
+    "undefined",
+];
+
+const BUILTINS: &[&str] = &["keyof", "typeof", "readonly"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// Position of the declaration of the name, to look up its documentation.
+    pub declaration: Declaration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A name declared by the program.
+    Symbol(SymbolKind),
+    /// A property of the object type before a `.`.
+    Property,
+    Keyword,
+    /// A type written as a keyword, such as `string`.
+    Type,
+    /// A builtin operator on types, such as `keyof`.
+    Builtin,
+    Macro,
+}
+
+/// What can be written at a position.
+#[derive(Debug, Clone, PartialEq)]
+enum Position {
+    /// Between statements.
+    Statement,
+    /// After `lhs.`.
+    Member(Ast),
+    /// The right side of `<:` or the pattern of a match arm, which is a type.
+    Pattern,
+    Expression,
+}
+
+/// What can be written at `offset` in `program`: the names in scope and the keywords that fit the
+/// position, the statement keywords between statements, or the properties of the object type
+/// before a `.`.
+pub fn completions_at(program: &Ast, offset: usize) -> Vec<CompletionItem> {
+    let keywords = |labels: &[&str], kind| {
+        labels
+            .iter()
+            .map(|label| CompletionItem {
+                label: label.to_string(),
+                kind,
+                declaration: None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let position = position(program, offset);
+
+    let mut items = match position {
+        Position::Statement => return keywords(STATEMENT_KEYWORDS, CompletionKind::Keyword),
+        Position::Member(lhs) => return members(program, &lhs),
+        Position::Pattern | Position::Expression => names_in_scope(program, offset)
+            .into_iter()
+            .map(|(label, symbol)| CompletionItem {
+                label,
+                kind: CompletionKind::Symbol(symbol.kind),
+                declaration: symbol.declaration,
+            })
+            .collect::<Vec<_>>(),
+    };
+
+    if position == Position::Expression {
+        items.extend(keywords(EXPRESSION_KEYWORDS, CompletionKind::Keyword));
+    }
+
+    items.extend(keywords(TYPE_KEYWORDS, CompletionKind::Type));
+    items.extend(keywords(BUILTINS, CompletionKind::Builtin));
+    items.extend(MacroRegistry::default().names().map(|name| CompletionItem {
+        label: format!("{name}!"),
+        kind: CompletionKind::Macro,
+        declaration: None,
+    }));
+
+    items
+}
+
+/// Classifies `offset` by the innermost nodes holding it.
+fn position(program: &Ast, offset: usize) -> Position {
+    let path = path(program, offset);
+
+    let contains = |node: &Ast| holds(node.as_span(), offset);
+
+    let Some(innermost) = path.last() else {
+        return Position::Statement;
+    };
+
+    if is_declaration(innermost) {
+        return Position::Statement;
+    }
+
+    for node in path.iter().rev() {
+        match node {
+            Ast::Access(Access {
+                lhs,
+                rhs,
+                kind: AccessKind::Dot,
+                ..
+            }) if contains(rhs) => return Position::Member((**lhs).clone()),
+            Ast::ExtendsInfixOp(op) if contains(&op.rhs) => return Position::Pattern,
+            Ast::ExtendsExpr(expr) if contains(&expr.rhs) => return Position::Pattern,
+            Ast::MatchExpr(expr) if expr.arms.iter().any(|arm| contains(&arm.pattern)) => {
+                return Position::Pattern
+            }
+            _ => {}
+        }
+    }
+
+    Position::Expression
+}
+
+/// Whether the node is a program, a statement or what a statement declares, whose innermost
+/// node holding an offset is not in an expression.
+fn is_declaration(node: &Ast) -> bool {
+    matches!(
+        node,
+        Ast::Program(_)
+            | Ast::Statement(_)
+            | Ast::TypeAlias(_)
+            | Ast::Interface(_)
+            | Ast::UnionDeclaration(_)
+            | Ast::BrandDeclaration(_)
+            | Ast::PatternDeclaration(_)
+            | Ast::ImportStatement(_)
+            | Ast::ExportDefault(_)
+            | Ast::AmbientModule(_)
+    )
+}
+
+/// The properties of the object type `lhs` names, an alias of an object literal or an interface
+/// declared by `program`.
+fn members(program: &Ast, lhs: &Ast) -> Vec<CompletionItem> {
+    let (Ast::Program(Program { statements, .. }), Ast::Ident(name)) = (program, lhs) else {
+        return vec![];
+    };
+
+    let properties: &[ObjectProperty] = statements
+        .iter()
+        .find_map(|statement| match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(alias) if alias.name.name == name.name => {
+                    match alias.body.as_ref() {
+                        Ast::TypeLiteral(literal) => Some(literal.properties.as_slice()),
+                        _ => None,
+                    }
+                }
+                Ast::Interface(interface) if interface.name == name.name => {
+                    Some(interface.definition.as_slice())
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    properties
+        .iter()
+        .filter_map(|property| match &property.key {
+            ObjectPropertyKey::Key(key) => Some(CompletionItem {
+                label: key.clone(),
+                kind: CompletionKind::Property,
+                declaration: Some((property.span.start, property.span.end)),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `span` holds `offset`, which can be at its end, right after the text being completed.
+fn holds(span: Span, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
+
+/// The nodes holding `offset`, outermost first. The nodes that don't hold it are skipped along
+/// with their children, which they hold.
+fn path(program: &Ast, offset: usize) -> Vec<Ast> {
+    let nodes = RefCell::new(vec![]);
+
+    program.traverse_ctl(
+        (),
+        &|node, ctx| {
+            if holds(node.as_span(), offset) {
+                nodes.borrow_mut().push(node.clone());
+                (node, ctx, Walk::Continue)
+            } else {
+                (node, ctx, Walk::Skip)
+            }
+        },
+        &|node, ctx| (node, ctx),
+    );
+
+    nodes.into_inner()
+}
+
+/// A label an editor shows before an argument of an application, `Get(T: obj, K: "a")`: the name
+/// of the parameter the argument is for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hint {
+    /// Position of the label, the start of the argument, or the closing parenthesis of the
+    /// application for a parameter left to its default.
+    pub offset: usize,
+    /// The name of the parameter, `...Name` for a rest parameter, which labels the first of the
+    /// arguments it takes.
+    pub label: String,
+    /// Whether the application leaves the parameter to its default.
+    pub defaulted: bool,
+}
+
+/// The labels of the arguments of the applications of `program` starting in `range` whose callee
+/// is an alias or an interface the program declares, in order. An argument that is the name of its
+/// parameter has no label, `Get(T, K)` needs none.
+pub fn parameter_hints(program: &Ast, range: Range<usize>) -> Vec<Hint> {
+    let signatures = Signatures::new(program);
+    let declarations = declarations(program);
+
+    // A callee resolving to another declaration than the program's, such as a type parameter of
+    // the same name, isn't the alias the signatures have.
+    let shadowed = |callee: &Ast| {
+        let Ast::Ident(ident) = callee else {
+            return false;
+        };
+
+        declarations
+            .get(&(ident.span.start, ident.span.end))
+            .is_some_and(|declaration| !declares(program, &ident.name, *declaration))
+    };
+
+    let hints = RefCell::new(vec![]);
+
+    program.prewalk((), &|node, ctx| {
+        let Ast::ApplyGeneric(ApplyGeneric {
+            receiver,
+            args,
+            span,
+        }) = &node
+        else {
+            return (node, ctx);
+        };
+
+        if !range.contains(&span.start) || shadowed(receiver) {
+            return (node, ctx);
+        }
+
+        let Some(params) = signatures.resolve(receiver) else {
+            return (node, ctx);
+        };
+
+        let mut hints = hints.borrow_mut();
+
+        for (index, param) in params.iter().enumerate() {
+            let name = &param.name.name;
+
+            match args.get(index) {
+                Some(Ast::Ident(arg)) if arg.name == *name => {}
+                Some(arg) => hints.push(Hint {
+                    offset: arg.as_span().start,
+                    label: if param.rest {
+                        format!("...{name}")
+                    } else {
+                        name.clone()
+                    },
+                    defaulted: false,
+                }),
+                None if param.default.is_some() => hints.push(Hint {
+                    offset: span.end.saturating_sub(1),
+                    label: name.clone(),
+                    defaulted: true,
+                }),
+                None => {}
+            }
+        }
+
+        (node, ctx)
+    });
+
+    hints.into_inner()
+}
+
+/// Whether `declaration` is the position of the name of the alias `name` of `program`.
+fn declares(program: &Ast, name: &str, declaration: (usize, usize)) -> bool {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return false;
+    };
+
+    statements.iter().any(|statement| match statement {
+        Ast::Statement(inner) => match inner.as_ref() {
+            Ast::TypeAlias(alias) => {
+                alias.name.name == name
+                    && (alias.name.span.start, alias.name.span.end) == declaration
+            }
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+/// A node matching the pattern of [`find`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'a> {
+    pub node: &'a Ast,
+    pub span: Span,
+    /// The subtrees the metavariables of the pattern stand for, by name without the `$`.
+    pub bindings: BTreeMap<String, &'a Ast>,
+}
+
+/// The nodes of `program` matching `pattern`, outermost first. The pattern is a type expression in
+/// which `$name` stands for any subtree, the same subtree wherever it is repeated:
+/// `if $x <: string then $y else $y end` finds the conditionals with identical branches. The rest
+/// of the pattern matches the nodes written the same way, wherever they are.
+pub fn find<'a>(program: &'a Ast, pattern: &str) -> Result<Vec<Match<'a>>, Diagnostic> {
+    let pattern = parse_newtype_expr(pattern).map_err(|err| humanize_pest(*err, pattern))?;
+
+    let mut search = Search {
+        pattern: &pattern,
+        matches: vec![],
+    };
+    search.visit_ast(program);
+
+    Ok(search.matches)
+}
+
+struct Search<'p, 'a> {
+    pattern: &'p Ast,
+    matches: Vec<Match<'a>>,
+}
+
+impl<'a> Visit<'a> for Search<'_, 'a> {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        let mut bindings = BTreeMap::new();
+
+        if unify(self.pattern, node, &mut bindings) {
+            self.matches.push(Match {
+                node,
+                span: node.as_span(),
+                bindings,
+            });
+        }
+
+        walk_ast(self, node)
+    }
+}
+
+/// Whether `node` matches `pattern`, binding its metavariables, consistently with those already
+/// bound.
+fn unify<'a>(pattern: &Ast, node: &'a Ast, bindings: &mut BTreeMap<String, &'a Ast>) -> bool {
+    if let Ast::Ident(ident) = pattern {
+        if let Some(name) = ident.name.strip_prefix('$') {
+            return match bindings.get(name) {
+                Some(bound) => *bound == node,
+                None => {
+                    bindings.insert(name.to_string(), node);
+                    true
+                }
+            };
+        }
+    }
+
+    if std::mem::discriminant(pattern) != std::mem::discriminant(node) {
+        return false;
+    }
+
+    let (pattern_children, children) = (children(pattern), children(node));
+
+    // Nodes are equal whatever their spans.
+    shape(pattern) == shape(node)
+        && pattern_children.len() == children.len()
+        && pattern_children
+            .into_iter()
+            .zip(children)
+            .all(|(pattern, node)| unify(pattern, node, bindings))
+}
+
+/// The nodes directly under `node`.
+fn children(node: &Ast) -> Vec<&Ast> {
+    struct Children<'a>(Vec<&'a Ast>);
+
+    impl<'a> Visit<'a> for Children<'a> {
+        fn visit_ast(&mut self, node: &'a Ast) {
+            self.0.push(node);
+        }
+    }
+
+    let mut children = Children(vec![]);
+    walk_ast(&mut children, node);
+    children.0
+}
+
+/// `node` with the nodes directly under it replaced by the same placeholder, to compare what is
+/// left.
+fn shape(node: &Ast) -> Ast {
+    struct Holes;
+
+    impl VisitMut for Holes {
+        fn visit_ast_mut(&mut self, node: &mut Ast) {
+            *node = Ast::noop();
+        }
+    }
+
+    let mut shape = node.clone();
+    walk_ast_mut(&mut Holes, &mut shape);
+    shape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    /// Completions at the `|` in `source`, which is removed.
+    fn completions(source: &str) -> Vec<CompletionItem> {
+        let offset = source.find('|').unwrap();
+        let source = source.replacen('|', "", 1);
+
+        completions_at(&parse!(&source), offset)
+    }
+
+    fn labels(items: &[CompletionItem], kind: CompletionKind) -> Vec<&str> {
+        items
+            .iter()
+            .filter(|item| item.kind == kind)
+            .map(|item| item.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn match_arm_body() {
+        let source = "type A(T, U) as match T do 1 -> |U, else -> 2 end\ntype B as 1";
+        let items = completions(source);
+
+        assert_eq!(
+            labels(&items, CompletionKind::Symbol(SymbolKind::TypeParameter)),
+            vec!["T", "U"]
+        );
+        assert_eq!(
+            labels(&items, CompletionKind::Symbol(SymbolKind::Alias)),
+            vec!["A", "B"]
+        );
+        assert!(labels(&items, CompletionKind::Keyword).contains(&"match"));
+
+        let param = items.iter().find(|item| item.label == "T").unwrap();
+        assert_eq!(param.declaration, Some((7, 8)));
+    }
+
+    #[test]
+    fn pattern() {
+        let items = completions("type A(T) as if T <: |string then 1 else 2 end");
+
+        assert_eq!(labels(&items, CompletionKind::Keyword), Vec::<&str>::new());
+        assert!(labels(&items, CompletionKind::Type).contains(&"string"));
+        assert_eq!(
+            labels(&items, CompletionKind::Symbol(SymbolKind::TypeParameter)),
+            vec!["T"]
+        );
+    }
+
+    #[test]
+    fn members_after_dot() {
+        let items = completions("interface User {id: number, name: string}\ntype Id as User.|id");
+
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.label.as_str(), item.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("id", CompletionKind::Property),
+                ("name", CompletionKind::Property),
+            ]
+        );
+
+        let items = completions("type P as {a: 1, ?b: 2}\ntype A as P.|a");
+
+        assert_eq!(labels(&items, CompletionKind::Property), vec!["a", "b"]);
+        assert_eq!(items[0].declaration, Some((11, 15)));
+    }
+
+    fn found(source: &str, pattern: &str) -> Vec<(String, Vec<(String, String)>)> {
+        let program = parse!(source);
+
+        find(&program, pattern)
+            .unwrap()
+            .into_iter()
+            .map(|found| {
+                let bindings = found
+                    .bindings
+                    .iter()
+                    .map(|(name, node)| {
+                        let span = node.as_span();
+                        (name.clone(), source[span.start..span.end].to_string())
+                    })
+                    .collect();
+
+                (
+                    source[found.span.start..found.span.end].to_string(),
+                    bindings,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn identical_branches() {
+        let source = "type A(T) as [if T <: string then {a: 1} else {a: 1} end, T]";
+
+        assert_eq!(
+            found(source, "if $x <: string then $y else $y end"),
+            vec![(
+                "if T <: string then {a: 1} else {a: 1} end".to_string(),
+                vec![
+                    ("x".to_string(), "T".to_string()),
+                    ("y".to_string(), "{a: 1}".to_string())
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn repeated_metavariables_bind_the_same_subtree() {
+        let source = dedent!(
+            r#"
+            type A(T) as if T <: string then 1 else 2 end
+            type B(T) as if T <: number then {a: [1]} else {a: [1]} end
+            "#
+        );
+
+        let found = found(source, "if $x <: $y then $z else $z end");
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].1,
+            vec![
+                ("x".to_string(), "T".to_string()),
+                ("y".to_string(), "number".to_string()),
+                ("z".to_string(), "{a: [1]}".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn ground_subtrees_are_compared() {
+        let source = "type A(T) as [if T <: number then 1 else 1 end, keyof(T), keyof(U)]";
+
+        assert_eq!(found(source, "if $x <: string then $y else $y end"), vec![]);
+        assert_eq!(
+            found(source, "keyof($x)")
+                .into_iter()
+                .map(|(node, _)| node)
+                .collect::<Vec<_>>(),
+            vec!["keyof(T)", "keyof(U)"]
+        );
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let program = parse!("type A as 1");
+
+        assert!(find(&program, "if $x <:").is_err());
+    }
+
+    /// `source` with the hints of `range` in it, `label: ` before an argument and `/* label */`
+    /// for a parameter left to its default. The last parameter of `Tuple` is a rest parameter.
+    fn hinted(source: &str, range: Range<usize>) -> (String, Vec<Hint>) {
+        let mut program = parse!(source);
+
+        struct Rest;
+
+        impl VisitMut for Rest {
+            fn visit_ast_mut(&mut self, node: &mut Ast) {
+                if let Ast::TypeAlias(alias) = node {
+                    if alias.name.name == "Tuple" {
+                        alias.params.last_mut().unwrap().rest = true;
+                    }
+                }
+                walk_ast_mut(self, node);
+            }
+        }
+
+        Rest.visit_ast_mut(&mut program);
+
+        let hints = parameter_hints(&program, range);
+        let mut hinted = source.to_string();
+
+        for hint in hints.iter().rev() {
+            let label = if hint.defaulted {
+                format!("/* {} */", hint.label)
+            } else {
+                format!("{}: ", hint.label)
+            };
+
+            hinted.insert_str(hint.offset, &label);
+        }
+
+        (hinted, hints)
+    }
+
+    #[test]
+    fn parameter_hints_of_applications() {
+        let source = dedent!(
+            r#"
+            type Get(T, K) as T[K]
+            type Page(Items, Size) defaults Size = 10 as {items: Items, size: Size}
+            type Tuple(First, Rest) as [First, Rest]
+            type A as Get({a: 1}, :a)
+            type B as Page(string[])
+            type C as Tuple(1, 2, 3)
+            type D(T, K) as Get(T, Get(K, :b))
+            type E(Get) as Get(1)
+            type F as Missing(1)
+            "#
+        );
+
+        let (hinted, hints) = hinted(source, 0..source.len());
+
+        assert_eq!(
+            hinted,
+            dedent!(
+                r#"
+                type Get(T, K) as T[K]
+                type Page(Items, Size) defaults Size = 10 as {items: Items, size: Size}
+                type Tuple(First, Rest) as [First, Rest]
+                type A as Get(T: {a: 1}, K: :a)
+                type B as Page(Items: string[]/* Size */)
+                type C as Tuple(First: 1, ...Rest: 2, 3)
+                type D(T, K) as Get(T, K: Get(T: K, K: :b))
+                type E(Get) as Get(1)
+                type F as Missing(1)
+                "#
+            )
+        );
+        assert_eq!(
+            serde_json::to_value(&hints[3]).unwrap(),
+            serde_json::json!({"offset": 186, "label": "Size", "defaulted": true})
+        );
+    }
+
+    #[test]
+    fn parameter_hints_in_a_range() {
+        let source = "type Get(T, K) as T[K]\ntype A as Get(1, 2)\ntype B as Get(3, 4)";
+        let start = source.find("type B").unwrap();
+
+        assert_eq!(
+            hinted(source, start..source.len()).0,
+            "type Get(T, K) as T[K]\ntype A as Get(1, 2)\ntype B as Get(T: 3, K: 4)"
+        );
+    }
+
+    #[test]
+    fn top_level() {
+        let items = completions("type A as 1\n|");
+
+        assert_eq!(labels(&items, CompletionKind::Keyword), STATEMENT_KEYWORDS);
+        assert_eq!(items.len(), STATEMENT_KEYWORDS.len());
+    }
+}
@@ -0,0 +1,719 @@
+/// The interactive session of `newtype repl`. Each line is a statement, declared in the session, a
+/// type expression, compiled with the declarations of the session in scope, or a command:
+///
+/// ```text
+/// > type Wrap(T) as {value: T}
+/// defined `Wrap`
+/// > Wrap(1)
+/// Wrap<1>
+/// = {value: 1}
+/// ```
+///
+/// A line is compiled as part of the source of the whole session, behind a `#source` pragma so
+/// that its diagnostics point into the line rather than into the generated source.
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    ast::{let_expr, ApplyGeneric, Ast, Bindings, Program, TypeAlias},
+    compile::{compile, CompileOptions},
+    diagnostic::{Diagnostic, Severity},
+    lint::always_extends,
+    names::GENERATED_PREFIX,
+    parser::{parse_source, parse_type, STATEMENT_KEYWORDS},
+    source::SourceFile,
+    typescript::Pretty,
+};
+
+/// Commands of the session, with what they do, as `:help` lists them.
+const COMMANDS: &[(&str, &str)] = &[
+    (
+        ":sexpr EXPR",
+        "print the syntax tree of an expression as an s-expression",
+    ),
+    (
+        ":simplify EXPR",
+        "print an expression simplified, in the DSL",
+    ),
+    (
+        ":load FILE",
+        "declare the statements of a file in the session",
+    ),
+    (":clear", "remove every declaration of the session"),
+    (":help", "print this list"),
+    (":quit", "end the session"),
+];
+
+/// Most times the aliases of an expression are expanded to fold it.
+const FOLD_STEPS: usize = 32;
+
+/// Longest DSL source a folded expression can have, past which it is left unfolded.
+const FOLD_MAX_LEN: usize = 4096;
+
+/// Declarations entered so far and the options the lines are compiled with.
+pub struct Session {
+    /// The statements of the session, as a program, a redeclared name replacing the statement
+    /// that declared it.
+    program: Ast,
+    options: CompileOptions,
+}
+
+/// A text compiled after the statements of the session.
+struct Checked {
+    /// The program the source parses to.
+    program: Ast,
+    /// Offset of the text in the source.
+    start: usize,
+    /// The warnings in the text, rendered.
+    warnings: String,
+    typescript: String,
+}
+
+/// What a line of the session asks for.
+enum Line<'a> {
+    Empty,
+    Command(&'a str, &'a str),
+    Statement,
+    /// `let NAME = EXPR`, declaring an alias.
+    Let(&'a str, usize),
+    Expression,
+}
+
+impl Session {
+    pub fn new(options: CompileOptions) -> Self {
+        Self {
+            program: Ast::Program(Program {
+                statements: vec![],
+                span: Default::default(),
+            }),
+            options,
+        }
+    }
+
+    /// The statements of the session, as a program.
+    pub fn program(&self) -> &Ast {
+        &self.program
+    }
+
+    /// Evaluates a line of input, returning what to print, without a final line break. Empty when
+    /// there is nothing to print.
+    pub fn eval(&mut self, line: &str) -> String {
+        match classify(line) {
+            Line::Empty => String::new(),
+            Line::Command(command, argument) => self.command(command, argument),
+            Line::Statement => self.define("", "", line),
+            Line::Let(name, start) => {
+                let padded = " ".repeat(line[..start].chars().count()) + &line[start..];
+                self.define(&format!("type {name} as"), "", &padded)
+            }
+            Line::Expression => self.evaluate(line),
+        }
+    }
+
+    fn command(&mut self, command: &str, argument: &str) -> String {
+        match command {
+            ":sexpr" => match parse_type(argument) {
+                Ok(node) => match node.to_sexp() {
+                    Ok(sexp) => sexp.to_string(),
+                    Err(err) => format!("error: {err}"),
+                },
+                Err(diagnostic) => diagnostic.render(&SourceFile::new("", argument)),
+            },
+            ":simplify" => match self.expression(argument) {
+                Ok((_, simplified)) => simplified.body.to_dl().trim_end().to_string(),
+                Err(rendered) => rendered,
+            },
+            ":load" if !argument.is_empty() => match std::fs::read_to_string(argument) {
+                Ok(text) => self.define("", argument, &text),
+                Err(err) => format!("error: can't read `{argument}`: {err}"),
+            },
+            ":clear" => {
+                *self = Self::new(std::mem::take(&mut self.options));
+                "cleared the session".to_string()
+            }
+            ":help" => help(),
+            _ => format!("error: unknown command `{command}`\n{}", help()),
+        }
+    }
+
+    /// Declares the statements of `text`, which follow `header` in the source compiled. They
+    /// replace the statements of the session declaring the same names.
+    fn define(&mut self, header: &str, file: &str, text: &str) -> String {
+        // The names declared are those of the text parsed after the session as it is, a syntax
+        // error being reported the same either way.
+        let prefix = source(&self.program, header, file);
+        let parsed = match parse_source(&(prefix.clone() + text)) {
+            Ok(program) => program,
+            Err(err) => {
+                return err
+                    .render(&SourceFile::new("", prefix + text))
+                    .trim_end()
+                    .into()
+            }
+        };
+
+        let replaced = added(&parsed, prefix.len());
+        let names: Vec<&str> = replaced.iter().filter_map(declared_name).collect();
+
+        let Ast::Program(session) = &self.program else {
+            unreachable!("the session is a program")
+        };
+
+        let kept = Ast::Program(Program {
+            statements: session
+                .statements
+                .iter()
+                .filter(|statement| !declared_name(statement).is_some_and(|n| names.contains(&n)))
+                .cloned()
+                .collect(),
+            span: Default::default(),
+        });
+
+        let checked = match self.check(&kept, header, file, text) {
+            Ok(checked) => checked,
+            Err(rendered) => return rendered,
+        };
+
+        let Ast::Program(mut session) = kept else {
+            unreachable!("the session is a program")
+        };
+
+        let added = added(&checked.program, checked.start);
+        let names: Vec<String> = added
+            .iter()
+            .filter_map(declared_name)
+            .map(|name| format!("`{name}`"))
+            .collect();
+
+        session.statements.extend(added);
+        self.program = Ast::Program(session);
+
+        let reply = match names.as_slice() {
+            [] => "ok".to_string(),
+            names => format!("defined {}", names.join(", ")),
+        };
+
+        join([checked.warnings, reply])
+    }
+
+    /// The TypeScript of an expression, followed by the type it folds to when it is closed.
+    fn evaluate(&self, line: &str) -> String {
+        let checked = match self.check(&self.program, &header(), "", line) {
+            Ok(checked) => checked,
+            Err(rendered) => return rendered,
+        };
+
+        let typescript = emitted(&checked.typescript);
+
+        let folded = match self.simplify(&checked.program) {
+            Ok((aliases, expression)) => fold(&expression.body, &aliases)
+                .map(|folded| folded.render_pretty_ts(self.options.width))
+                .map(|folded| folded.trim_end().to_string())
+                .filter(|folded| *folded != typescript)
+                .map(|folded| format!("= {folded}")),
+            Err(_) => None,
+        };
+
+        join([checked.warnings, typescript, folded.unwrap_or_default()])
+    }
+
+    /// The alias `expression` is compiled as, simplified, with the simplified aliases of the
+    /// session.
+    fn expression(
+        &self,
+        expression: &str,
+    ) -> Result<(HashMap<String, TypeAlias>, TypeAlias), String> {
+        let checked = self.check(&self.program, &header(), "", expression)?;
+
+        self.simplify(&checked.program).map_err(|diagnostics| {
+            let file = SourceFile::new("", source(&self.program, &header(), "") + expression);
+            render(&diagnostics, &file)
+        })
+    }
+
+    /// The simplified aliases of `program`, with the one an expression is compiled as taken out.
+    fn simplify(
+        &self,
+        program: &Ast,
+    ) -> Result<(HashMap<String, TypeAlias>, TypeAlias), Vec<Diagnostic>> {
+        let (simplified, _) =
+            program.simplify_with_macros(&self.options.macros, self.options.brand_encoding)?;
+
+        let Ast::Program(Program { statements, .. }) = simplified else {
+            unreachable!("a program simplifies to a program")
+        };
+
+        let mut aliases: HashMap<String, TypeAlias> = statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Ast::Statement(statement) => match statement.inner.as_ref() {
+                    Ast::TypeAlias(alias) => Some((alias.name.name.clone(), alias.clone())),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+
+        let expression = aliases
+            .remove(&it())
+            .expect("the expression is declared as an alias");
+
+        Ok((aliases, expression))
+    }
+
+    /// Parses and compiles `text` after the statements of `session` and `header`, failing with
+    /// the errors rendered.
+    fn check(
+        &self,
+        session: &Ast,
+        header: &str,
+        file: &str,
+        text: &str,
+    ) -> Result<Checked, String> {
+        let prefix = source(session, header, file);
+        let start = prefix.len();
+        let source = prefix + text;
+        let rendered = SourceFile::new("", source.as_str());
+
+        let program =
+            parse_source(&source).map_err(|err| err.render(&rendered).trim_end().to_string())?;
+
+        match compile(&source, &self.options) {
+            Ok(output) => {
+                let warnings: Vec<Diagnostic> = output
+                    .diagnostics
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.span.start >= start)
+                    .collect();
+
+                Ok(Checked {
+                    program,
+                    start,
+                    warnings: render(&warnings, &rendered),
+                    typescript: output.typescript,
+                })
+            }
+            Err(diagnostics) => {
+                let errors: Vec<Diagnostic> = diagnostics
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                    .collect();
+
+                Err(render(&errors, &rendered))
+            }
+        }
+    }
+}
+
+/// Reads lines from `input` until it ends or a `:quit` command, writing a prompt before each line
+/// and what it evaluates to after it.
+pub fn run(input: impl BufRead, mut output: impl Write, session: &mut Session) -> io::Result<()> {
+    let mut lines = input.lines();
+
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            writeln!(output)?;
+            return Ok(());
+        };
+
+        if line.trim() == ":quit" {
+            return Ok(());
+        }
+
+        let reply = session.eval(&line);
+
+        if !reply.is_empty() {
+            writeln!(output, "{reply}")?;
+        }
+    }
+}
+
+fn classify(line: &str) -> Line<'_> {
+    let trimmed = line.trim();
+
+    if trimmed.is_empty() {
+        return Line::Empty;
+    }
+
+    if trimmed.starts_with(':') {
+        let (command, argument) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        return Line::Command(command, argument.trim());
+    }
+
+    let first = trimmed
+        .split(char::is_whitespace)
+        .next()
+        .unwrap_or_default();
+
+    if STATEMENT_KEYWORDS.contains(&first) || trimmed.starts_with('@') {
+        return Line::Statement;
+    }
+
+    // A `let` with an `in` is an expression, one without declares an alias.
+    if first == "let" && parse_type(line).is_err() {
+        let binding = &line[line.find("let").unwrap() + "let".len()..];
+
+        if let Some((name, _)) = binding.split_once('=') {
+            let name = name.trim();
+            let is_name = name
+                .chars()
+                .next()
+                .is_some_and(|c| unicode_ident::is_xid_start(c) || c == '_' || c == '$')
+                && name
+                    .chars()
+                    .all(|c| unicode_ident::is_xid_continue(c) || c == '$');
+
+            if is_name {
+                let start = line.len() - binding.len() + binding.find('=').unwrap() + 1;
+                return Line::Let(name, start);
+            }
+        }
+    }
+
+    Line::Expression
+}
+
+/// Name of the alias an expression is compiled as.
+fn it() -> String {
+    format!("{GENERATED_PREFIX}repl")
+}
+
+fn header() -> String {
+    format!("type {} as", it())
+}
+
+/// Source of the statements of `session`, followed by `header` and a pragma numbering the lines
+/// after it from the first line of `file`.
+fn source(session: &Ast, header: &str, file: &str) -> String {
+    let mut source = session.to_dl();
+
+    if !source.is_empty() && !source.ends_with('\n') {
+        source.push('\n');
+    }
+
+    format!("{source}{header}\n#source \"{file}\" line 1\n")
+}
+
+/// The statements of `program` that end after `start`, those of the text compiled.
+fn added(program: &Ast, start: usize) -> Vec<Ast> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .filter(|statement| statement.as_span().end > start)
+        .cloned()
+        .collect()
+}
+
+/// Name a statement declares, if it declares one.
+fn declared_name(statement: &Ast) -> Option<&str> {
+    let Ast::Statement(statement) = statement else {
+        return None;
+    };
+
+    match statement.inner.as_ref() {
+        Ast::TypeAlias(alias) => Some(&alias.name.name),
+        Ast::Interface(interface) => Some(&interface.name),
+        Ast::UnionDeclaration(union) => Some(&union.name.name),
+        Ast::BrandDeclaration(brand) => Some(&brand.name.name),
+        Ast::PatternDeclaration(pattern) => Some(&pattern.name.name),
+        _ => None,
+    }
+}
+
+/// The TypeScript of the alias an expression is compiled as, without the declaration around it.
+fn emitted(typescript: &str) -> String {
+    let declaration = format!("type {} = ", it());
+
+    let Some(start) = typescript.find(&declaration) else {
+        return String::new();
+    };
+
+    let rest = &typescript[start + declaration.len()..];
+    let end = rest.find("\n\n").unwrap_or(rest.len());
+
+    rest[..end].trim_end().trim_end_matches(';').to_string()
+}
+
+/// `node` with the aliases expanded and the conditional types deciding on literals replaced by a
+/// branch, or `None` if it doesn't fold to a closed type, one without names or conditionals.
+fn fold(node: &Ast, aliases: &HashMap<String, TypeAlias>) -> Option<Ast> {
+    let mut node = node.clone();
+
+    for _ in 0..FOLD_STEPS {
+        if is_closed(&node) {
+            return Some(node);
+        }
+
+        let (next, _) = node.postwalk((), &|node, ctx| (step(node, aliases), ctx));
+
+        if next == node || next.to_dl().len() > FOLD_MAX_LEN {
+            return None;
+        }
+
+        node = next;
+    }
+
+    is_closed(&node).then_some(node)
+}
+
+fn step(node: Ast, aliases: &HashMap<String, TypeAlias>) -> Ast {
+    match &node {
+        Ast::Ident(ident) => match aliases.get(&ident.name) {
+            Some(alias) if alias.params.is_empty() => alias.body.as_ref().clone(),
+            _ => node,
+        },
+        Ast::ApplyGeneric(ApplyGeneric { receiver, args, .. }) => match receiver.as_ref() {
+            Ast::Ident(ident) => aliases
+                .get(&ident.name)
+                .and_then(|alias| apply(alias, args))
+                .unwrap_or(node),
+            _ => node,
+        },
+        Ast::ExtendsExpr(expr) => match always_extends(&expr.lhs, &expr.rhs) {
+            Some(true) => expr.then_branch.as_ref().clone(),
+            Some(false) => expr.else_branch.as_ref().clone(),
+            None => node,
+        },
+        _ => node,
+    }
+}
+
+/// The body of `alias` with its parameters replaced by `args`, or their defaults, or `None` if
+/// they don't match.
+fn apply(alias: &TypeAlias, args: &[Ast]) -> Option<Ast> {
+    if args.len() > alias.params.len() || alias.params.iter().any(|param| param.rest) {
+        return None;
+    }
+
+    let mut bindings = Bindings::new();
+
+    for (i, param) in alias.params.iter().enumerate() {
+        let value = match args.get(i) {
+            Some(arg) => arg.clone(),
+            None => let_expr::substitute(param.default.as_ref()?, &bindings),
+        };
+
+        bindings.insert(param.name.clone(), value);
+    }
+
+    Some(let_expr::substitute(&alias.body, &bindings))
+}
+
+fn is_closed(node: &Ast) -> bool {
+    !node.any(|node| {
+        matches!(
+            node,
+            Ast::Ident(_) | Ast::ApplyGeneric(_) | Ast::ExtendsExpr(_) | Ast::Infer(_)
+        )
+    })
+}
+
+fn render(diagnostics: &[Diagnostic], file: &SourceFile) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render(file))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+/// The non-empty parts, a line each.
+fn join<const N: usize>(parts: [String; N]) -> String {
+    parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn help() -> String {
+    let width = COMMANDS
+        .iter()
+        .map(|(usage, _)| usage.len())
+        .max()
+        .unwrap_or(0);
+
+    COMMANDS
+        .iter()
+        .map(|(usage, description)| format!("  {usage:width$}  {description}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use textwrap_macros::dedent;
+
+    /// What `session` prints for each of `lines`, after the line.
+    fn transcript(session: &mut Session, lines: &[&str]) -> String {
+        let mut output = vec![];
+        run(lines.join("\n").as_bytes(), &mut output, session).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn scripted_session() {
+        let mut session = Session::new(CompileOptions::default());
+
+        assert_eq!(
+            transcript(
+                &mut session,
+                &["type Wrap(T) as {value: T}", "Wrap(1)", "", "Wrap(string)"]
+            ),
+            dedent!(
+                "
+                > defined `Wrap`
+                > Wrap<1>
+                = {value: 1}
+                > > Wrap<string>
+                = {value: string}
+                > 
+                "
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn quit_ends_the_session() {
+        let mut session = Session::new(CompileOptions::default());
+
+        assert_eq!(
+            transcript(&mut session, &["type A as 1", ":quit", "A"]),
+            "> defined `A`\n> "
+        );
+    }
+
+    #[rstest]
+    #[case("IsString('a')", "IsString<'a'>\n= true")]
+    #[case("IsString(1)", "IsString<1>\n= false")]
+    #[case("Both(1)", "Both<1>\n= [false, 1]")]
+    #[case("Pair", "Pair\n= [1, 2]")]
+    #[case("Wrap(T)", "Wrap<T>")]
+    #[case("IsString(number | 'a')", "IsString<number | 'a'>")]
+    #[case("{value: 1}", "{value: 1}")]
+    fn expressions_fold_when_closed(#[case] expression: &str, #[case] expected: &str) {
+        let mut session = Session::new(CompileOptions::default());
+
+        for line in [
+            "type Wrap(T) as {value: T}",
+            "type IsString(T) as if T <: string then true else false end",
+            "type Both(T, U) defaults U = T as [IsString(T), U]",
+            "type Pair as [1, 2]",
+        ] {
+            session.eval(line);
+        }
+
+        assert_eq!(session.eval(expression), expected);
+    }
+
+    #[test]
+    fn redeclared_names_replace_their_declaration() {
+        let mut session = Session::new(CompileOptions::default());
+
+        session.eval("type Wrap(T) as {value: T}");
+        session.eval("type Other as 1");
+
+        assert_eq!(session.eval("type Wrap(T) as [T]"), "defined `Wrap`");
+        assert_eq!(session.eval("Wrap(1)"), "Wrap<1>\n= [1]");
+        assert_eq!(
+            session.program().to_dl(),
+            "type Other as 1\n\ntype Wrap(T) as [T]\n"
+        );
+    }
+
+    #[test]
+    fn let_declares_an_alias() {
+        let mut session = Session::new(CompileOptions::default());
+
+        assert_eq!(session.eval("let one = 1"), "defined `one`");
+        assert_eq!(session.eval("[one]"), "[one]\n= [1]");
+        assert_eq!(session.eval("let a = 2 in [a]"), "[2]");
+    }
+
+    #[test]
+    fn errors_point_into_the_line() {
+        let mut session = Session::new(CompileOptions::default());
+
+        session.eval("type Wrap(T) as {value: T}");
+
+        assert_eq!(
+            session.eval("merge!(Wrap(1), 2)"),
+            dedent!(
+                "
+                error[DL0012]: `merge!` expects object types, found a number
+                 --> 1:17
+                  |
+                1 | merge!(Wrap(1), 2)
+                  |                 ^
+                  = help: run `newtype explain DL0012` for more information
+                "
+            )
+            .trim()
+        );
+
+        assert_eq!(
+            session.eval("let bad = (1"),
+            dedent!(
+                "
+                error[DL0001]: expected `,`, `)`, or an operator, found end of input
+                 --> 1:13
+                  |
+                1 |           (1
+                  |             ^
+                  = help: run `newtype explain DL0001` for more information
+                "
+            )
+            .trim()
+        );
+
+        assert_eq!(session.program().to_dl(), "type Wrap(T) as {value: T}\n");
+    }
+
+    #[test]
+    fn commands() {
+        let mut session = Session::new(CompileOptions::default());
+
+        session.eval("type Wrap(T) as {value: T}");
+
+        assert_eq!(
+            session.eval(":sexpr Wrap(1)"),
+            r#"(apply (receiver ident . "Wrap") (args (number . "1")))"#
+        );
+        assert_eq!(session.eval(":simplify let a = 1 in Wrap(a)"), "Wrap(1)");
+        assert_eq!(session.eval(":clear"), "cleared the session");
+        assert_eq!(session.eval("Wrap(1)"), "Wrap<1>");
+        assert!(session
+            .eval(":unknown")
+            .starts_with("error: unknown command `:unknown`\n  :sexpr EXPR"));
+    }
+
+    #[test]
+    fn load_declares_the_statements_of_a_file() {
+        let path = temp_dir().join("types.dl");
+        std::fs::write(&path, "type Wrap(T) as {value: T}\n\ntype One as 1\n").unwrap();
+
+        let mut session = Session::new(CompileOptions::default());
+
+        assert_eq!(
+            session.eval(&format!(":load {}", path.display())),
+            "defined `Wrap`, `One`"
+        );
+        assert_eq!(session.eval("Wrap(One)"), "Wrap<One>\n= {value: 1}");
+    }
+}
@@ -0,0 +1,896 @@
+/// Targeted edits of source text, for editor quick-fixes: the rest of the file is left as it was
+/// written instead of being reformatted.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use crate::{
+    ast::{
+        cond_expr::CondExpr,
+        if_expr::IfExpr,
+        let_expr::LetExpr,
+        match_expr::MatchExpr,
+        pattern_declaration::PatternDeclaration,
+        visit::{
+            walk_ast, walk_cond_expr, walk_interface, walk_let_expr, walk_match_expr,
+            walk_object_property, walk_program, walk_type_parameter, Visit,
+        },
+        Ast, ExtendsExpr, Ident, ImportClause, Interface, MappedType, ObjectProperty,
+        ObjectPropertyKey, Program, Span, TypeAlias, TypeParameter,
+    },
+    diagnostic::{Diagnostic, Suggestion},
+    dsl,
+    error::humanize_pest,
+    lint::{self, Level},
+    parser::parse_newtype_program,
+    source::SourceFile,
+};
+
+pub mod rename;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    /// The range is not a range of the source.
+    OutOfBounds { start: usize, end: usize },
+    /// The range doesn't cover exactly one node, or a run of statements.
+    NotANode { start: usize, end: usize },
+    /// The edited source doesn't parse.
+    Invalid(Diagnostic),
+    /// There is no name declared in the program at the offset.
+    NoSymbol { offset: usize },
+    /// The new name is not an identifier.
+    InvalidName(String),
+    /// The new name is already in scope at a place the renamed name is, where it would clash with
+    /// or capture the other name.
+    NameInScope {
+        name: String,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl Display for RewriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RewriteError::OutOfBounds { start, end } => {
+                write!(f, "{start}..{end} is not a range of the source")
+            }
+            RewriteError::NotANode { start, end } => {
+                write!(f, "{start}..{end} doesn't cover a node of the program")
+            }
+            RewriteError::Invalid(diagnostic) => {
+                write!(f, "the edited source is invalid: {}", diagnostic.message)
+            }
+            RewriteError::NoSymbol { offset } => {
+                write!(f, "no name declared in the program at {offset}")
+            }
+            RewriteError::InvalidName(name) => write!(f, "`{name}` is not an identifier"),
+            RewriteError::NameInScope { name, start, end } => {
+                write!(f, "`{name}` is already in scope at {start}..{end}")
+            }
+        }
+    }
+}
+
+/// A replacement of the text at `start..end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Replaces the node of `program`, parsed from `source`, at `start..end` with `replacement`, or
+/// the statements the range covers. The range may include the whitespace around the node. Fails
+/// if the edited source doesn't parse.
+pub fn replace_span(
+    source: &str,
+    program: &Ast,
+    (start, end): (usize, usize),
+    replacement: &str,
+) -> Result<String, RewriteError> {
+    if start > end
+        || end > source.len()
+        || !source.is_char_boundary(start)
+        || !source.is_char_boundary(end)
+    {
+        return Err(RewriteError::OutOfBounds { start, end });
+    }
+
+    let range = trim(source, start, end);
+
+    let covers_statements = match program {
+        Ast::Program(Program { statements, .. }) => {
+            let starts = statements
+                .iter()
+                .any(|statement| trim_span(source, statement.as_span()).0 == range.0);
+            let ends = statements
+                .iter()
+                .any(|statement| trim_span(source, statement.as_span()).1 == range.1);
+
+            starts && ends
+        }
+        _ => false,
+    };
+
+    let mut spans = NodeSpans::default();
+    spans.visit_ast(program);
+
+    if !covers_statements && !spans.0.iter().any(|span| trim_span(source, *span) == range) {
+        return Err(RewriteError::NotANode { start, end });
+    }
+
+    apply(
+        source,
+        &[Edit {
+            start,
+            end,
+            text: replacement.to_string(),
+        }],
+    )
+}
+
+/// Renames the name declared in `program`, parsed from `source`, that is declared or referenced at
+/// `offset`.
+pub fn rename_symbol(
+    source: &str,
+    program: &Ast,
+    offset: usize,
+    new_name: &str,
+) -> Result<String, RewriteError> {
+    apply(source, &rename_edits(program, offset, new_name)?)
+}
+
+/// The edits renaming the name at `offset`: one for its declaration and one for every reference
+/// to it in its scope. Names declared without a position of their own, like the key of a mapped
+/// type, and names the program doesn't declare can't be renamed. Fails if another `new_name` is in
+/// scope at the declaration or at a reference, as the renamed name would clash with it or capture
+/// the references to it.
+pub fn rename_edits(
+    program: &Ast,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<Edit>, RewriteError> {
+    if !dsl::is_ident(new_name) {
+        return Err(RewriteError::InvalidName(new_name.to_string()));
+    }
+
+    let (declaration, edits) = symbol_edits(program, offset, new_name)?;
+
+    for edit in &edits {
+        let in_scope = names_in_scope(program, edit.start);
+
+        if in_scope
+            .get(new_name)
+            .is_some_and(|symbol| symbol.declaration != Some(declaration))
+        {
+            return Err(RewriteError::NameInScope {
+                name: new_name.to_string(),
+                start: edit.start,
+                end: edit.end,
+            });
+        }
+    }
+
+    Ok(edits)
+}
+
+/// The declaration of the name at `offset`, and the edits renaming it without checking the new
+/// name.
+fn symbol_edits(
+    program: &Ast,
+    offset: usize,
+    new_name: &str,
+) -> Result<((usize, usize), Vec<Edit>), RewriteError> {
+    let mut resolver = Resolver::default();
+    resolver.visit_ast(program);
+
+    let declaration = resolver
+        .references
+        .iter()
+        .find(|(span, _)| span.start <= offset && offset < span.end)
+        .and_then(|(_, declaration)| *declaration)
+        .ok_or(RewriteError::NoSymbol { offset })?;
+
+    let edits = resolver
+        .references
+        .iter()
+        .filter(|(_, other)| *other == Some(declaration))
+        .map(|(span, _)| Edit {
+            start: span.start,
+            end: span.end,
+            text: new_name.to_string(),
+        })
+        .collect();
+
+    Ok((declaration, edits))
+}
+
+/// Applies a suggestion to `source`, which `program` is parsed from.
+pub fn apply_suggestion(
+    source: &str,
+    program: &Ast,
+    suggestion: &Suggestion,
+) -> Result<String, RewriteError> {
+    if suggestion.rename {
+        rename_symbol(
+            source,
+            program,
+            suggestion.span.start,
+            &suggestion.replacement,
+        )
+    } else {
+        apply(
+            source,
+            &[Edit {
+                start: suggestion.span.start,
+                end: suggestion.span.end,
+                text: suggestion.replacement.clone(),
+            }],
+        )
+    }
+}
+
+/// Applies the suggestions of the lints to `source` one at a time, checking the program again after
+/// each one as it moves the spans of the others. Returns the fixed source and the diagnostics that
+/// were fixed. The diagnostics of lints allowed by `levels` or by a pragma are left alone.
+pub fn fix(
+    source: &str,
+    levels: &BTreeMap<String, Level>,
+) -> Result<(String, Vec<Diagnostic>), Diagnostic> {
+    let suggested = |source: &str| {
+        let program = parse_newtype_program(source).map_err(|err| humanize_pest(*err, source))?;
+
+        let diagnostic = lint::apply_levels(
+            lint::apply_pragmas(
+                lint::check(&program),
+                &SourceFile::new("", source),
+                &program,
+            ),
+            levels,
+        )
+        .into_iter()
+        .find(|diagnostic| diagnostic.suggestion.is_some());
+
+        Ok::<_, Diagnostic>((program, diagnostic))
+    };
+
+    let mut source = source.to_string();
+    let mut fixed = vec![];
+
+    while let (program, Some(diagnostic)) = suggested(&source)? {
+        let suggestion = diagnostic.suggestion.as_deref().unwrap();
+
+        source = apply_suggestion(&source, &program, suggestion)
+            .map_err(|err| Diagnostic::error(err.to_string(), suggestion.span))?;
+
+        fixed.push(diagnostic);
+    }
+
+    Ok((source, fixed))
+}
+
+/// Applies edits that don't overlap, then checks that the result parses.
+pub fn apply(source: &str, edits: &[Edit]) -> Result<String, RewriteError> {
+    let mut edits = edits.to_vec();
+    edits.sort_by_key(|edit| edit.start);
+
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+
+    for edit in edits {
+        out += &source[copied..edit.start];
+        out += &edit.text;
+        copied = edit.end;
+    }
+
+    out += &source[copied..];
+
+    parse_newtype_program(&out).map_err(|err| RewriteError::Invalid(humanize_pest(*err, &out)))?;
+
+    Ok(out)
+}
+
+/// The range without the whitespace at its ends.
+fn trim(source: &str, start: usize, end: usize) -> (usize, usize) {
+    let text = &source[start..end];
+    let leading = text.len() - text.trim_start().len();
+    let trailing = text.len() - text.trim_end().len();
+
+    if leading == text.len() {
+        (start, start)
+    } else {
+        (start + leading, end - trailing)
+    }
+}
+
+fn trim_span(source: &str, span: Span) -> (usize, usize) {
+    trim(
+        source,
+        span.start.min(source.len()),
+        span.end.min(source.len()),
+    )
+}
+
+/// Spans of every node, parameter, property and arm of a tree.
+#[derive(Default)]
+struct NodeSpans(Vec<Span>);
+
+impl<'a> Visit<'a> for NodeSpans {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        self.0.push(node.as_span());
+        walk_ast(self, node);
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident) {
+        self.0.push(ident.span);
+    }
+
+    fn visit_type_parameter(&mut self, param: &'a TypeParameter) {
+        self.0.push(param.span);
+        walk_type_parameter(self, param);
+    }
+
+    fn visit_object_property(&mut self, property: &'a ObjectProperty) {
+        self.0.push(property.span);
+        walk_object_property(self, property);
+    }
+
+    fn visit_match_expr(&mut self, expr: &'a MatchExpr) {
+        self.0.extend(expr.arms.iter().map(|arm| arm.span));
+        walk_match_expr(self, expr);
+    }
+
+    fn visit_cond_expr(&mut self, cond: &'a CondExpr) {
+        self.0.extend(cond.arms.iter().map(|arm| arm.span));
+        walk_cond_expr(self, cond);
+    }
+}
+
+/// Position of a declaration, `None` for a name declared without one.
+pub type Declaration = Option<(usize, usize)>;
+
+/// What declares a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Alias,
+    Interface,
+    Union,
+    Variant,
+    Brand,
+    /// A pattern synonym.
+    Pattern,
+    Import,
+    Namespace,
+    TypeParameter,
+    /// A binding of a `let`.
+    Binding,
+    /// A type inferred by a condition, `?T`.
+    Inferred,
+    /// The key of a mapped type or of an index property.
+    Key,
+}
+
+/// A name in scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub declaration: Declaration,
+}
+
+type Scope = HashMap<String, Symbol>;
+
+/// The names in scope at `offset` in `program`, with what declares them. A name shadowed by
+/// another is left out.
+pub fn names_in_scope(program: &Ast, offset: usize) -> BTreeMap<String, Symbol> {
+    let mut resolver = Resolver {
+        at: Some(offset),
+        ..Default::default()
+    };
+
+    resolver.visit_ast(program);
+    resolver.in_scope_at
+}
+
+/// The declaration of every name of `program` declared in it, by the position of the name.
+pub fn declarations(program: &Ast) -> HashMap<(usize, usize), (usize, usize)> {
+    let mut resolver = Resolver::default();
+    resolver.visit_ast(program);
+
+    resolver
+        .references
+        .into_iter()
+        .filter_map(|(span, declaration)| Some(((span.start, span.end), declaration?)))
+        .collect()
+}
+
+/// Resolves every name of a program to its declaration, following the scopes of the language:
+/// the declarations of the program, the parameters of an alias or interface, the bindings of a
+/// `let`, the keys of mapped types and the types inferred by a condition, which are in scope of
+/// its `then` branch.
+#[derive(Default)]
+struct Resolver {
+    scopes: Vec<Scope>,
+    /// Every name, declarations included, and its declaration if the program has one for it.
+    references: Vec<(Span, Declaration)>,
+    /// Offset to collect the names in scope at, into `in_scope_at`.
+    at: Option<usize>,
+    in_scope_at: BTreeMap<String, Symbol>,
+}
+
+impl Resolver {
+    fn scoped(&mut self, names: Scope, walk: impl FnOnce(&mut Self)) {
+        self.scopes.push(names);
+        walk(self);
+        self.scopes.pop();
+    }
+
+    fn declare(ident: &Ident, kind: SymbolKind) -> (String, Symbol) {
+        let declaration = Some((ident.span.start, ident.span.end));
+
+        (ident.name.clone(), Symbol { kind, declaration })
+    }
+
+    /// A name declared without a position of its own.
+    fn declare_unspanned(name: &str, kind: SymbolKind) -> (String, Symbol) {
+        let declaration = None;
+
+        (name.to_string(), Symbol { kind, declaration })
+    }
+
+    /// Scope of the types inferred in `pattern`.
+    fn inferred(pattern: &Ast) -> Scope {
+        #[derive(Default)]
+        struct Inferred(Scope);
+
+        impl<'a> Visit<'a> for Inferred {
+            fn visit_infer(&mut self, binding: &'a Ast) {
+                if let Ast::Ident(ident) = binding {
+                    self.0
+                        .extend([Resolver::declare(ident, SymbolKind::Inferred)]);
+                }
+            }
+        }
+
+        let mut inferred = Inferred::default();
+        inferred.visit_ast(pattern);
+        inferred.0
+    }
+
+    fn params(params: &[TypeParameter]) -> Scope {
+        params
+            .iter()
+            .map(|param| Self::declare(&param.name, SymbolKind::TypeParameter))
+            .collect()
+    }
+
+    /// Notes the names in scope when `span` holds the offset they are collected at. The nodes
+    /// holding it are visited outermost first, so the names are those of the innermost one.
+    fn visit_span(&mut self, span: Span) {
+        if !self.at.is_some_and(|at| span.start <= at && at <= span.end) {
+            return;
+        }
+
+        self.in_scope_at.clear();
+
+        for scope in self.scopes.iter().rev() {
+            for (name, symbol) in scope {
+                self.in_scope_at.entry(name.clone()).or_insert(*symbol);
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Resolver {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        self.visit_span(node.as_span());
+        walk_ast(self, node);
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident) {
+        self.visit_span(ident.span);
+
+        let declaration = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.name))
+            .and_then(|symbol| symbol.declaration);
+
+        self.references.push((ident.span, declaration));
+    }
+
+    fn visit_program(&mut self, program: &'a Program) {
+        let mut names = HashMap::new();
+
+        for statement in &program.statements {
+            let Ast::Statement(inner) = statement else {
+                continue;
+            };
+
+            match inner.as_ref() {
+                Ast::TypeAlias(alias) => {
+                    names.extend([Self::declare(&alias.name, SymbolKind::Alias)])
+                }
+                Ast::UnionDeclaration(union) => names.extend(
+                    std::iter::once(Self::declare(&union.name, SymbolKind::Union)).chain(
+                        union
+                            .variants
+                            .iter()
+                            .map(|variant| Self::declare(&variant.name, SymbolKind::Variant)),
+                    ),
+                ),
+                Ast::BrandDeclaration(brand) => {
+                    names.extend([Self::declare(&brand.name, SymbolKind::Brand)])
+                }
+                Ast::PatternDeclaration(pattern) => {
+                    names.extend([Self::declare(&pattern.name, SymbolKind::Pattern)])
+                }
+                Ast::ImportStatement(import) => match &import.import_clause {
+                    ImportClause::Named(specifiers) => {
+                        names.extend(specifiers.iter().map(|specifier| {
+                            Self::declare(
+                                specifier
+                                    .alias
+                                    .as_ref()
+                                    .unwrap_or(&specifier.module_export_name),
+                                SymbolKind::Import,
+                            )
+                        }))
+                    }
+                    ImportClause::Namespace { alias } => {
+                        names.extend([Self::declare(alias, SymbolKind::Namespace)])
+                    }
+                },
+                // Interfaces have no position for their name.
+                Ast::Interface(interface) => {
+                    names.extend([Self::declare_unspanned(
+                        &interface.name,
+                        SymbolKind::Interface,
+                    )]);
+                }
+                _ => {}
+            }
+        }
+
+        self.scoped(names, |resolver| walk_program(resolver, program));
+    }
+
+    fn visit_type_alias(&mut self, alias: &'a TypeAlias) {
+        self.visit_ident(&alias.name);
+
+        self.scoped(Self::params(&alias.params), |resolver| {
+            for param in &alias.params {
+                resolver.visit_type_parameter(param);
+            }
+
+            resolver.visit_ast(&alias.body);
+        });
+    }
+
+    fn visit_type_parameter(&mut self, param: &'a TypeParameter) {
+        walk_type_parameter(self, param);
+
+        let (_, Symbol { declaration, .. }) = Self::declare(&param.name, SymbolKind::TypeParameter);
+
+        self.references
+            .extend(param.clause_spans.iter().map(|span| (*span, declaration)));
+    }
+
+    fn visit_pattern_declaration(&mut self, pattern: &'a PatternDeclaration) {
+        self.visit_ident(&pattern.name);
+
+        let params = pattern
+            .params
+            .iter()
+            .map(|param| Self::declare(param, SymbolKind::TypeParameter))
+            .collect();
+
+        self.scoped(params, |resolver| {
+            for param in &pattern.params {
+                resolver.visit_ident(param);
+            }
+
+            resolver.visit_ast(&pattern.body);
+        });
+    }
+
+    fn visit_interface(&mut self, interface: &'a Interface) {
+        self.scoped(Self::params(&interface.params), |resolver| {
+            walk_interface(resolver, interface)
+        });
+    }
+
+    fn visit_let_expr(&mut self, expr: &'a LetExpr) {
+        let names = expr
+            .bindings
+            .iter()
+            .map(|binding| Self::declare(&binding.name, SymbolKind::Binding))
+            .collect();
+
+        self.scoped(names, |resolver| walk_let_expr(resolver, expr));
+    }
+
+    fn visit_mapped_type(&mut self, mapped: &'a MappedType) {
+        self.visit_ast(&mapped.iterable);
+
+        let names = Scope::from([Self::declare_unspanned(&mapped.index, SymbolKind::Key)]);
+
+        self.scoped(names, |resolver| {
+            if let Some(remapped_as) = &mapped.remapped_as {
+                resolver.visit_ast(remapped_as);
+            }
+
+            resolver.visit_ast(&mapped.body);
+        });
+    }
+
+    fn visit_object_property(&mut self, property: &'a ObjectProperty) {
+        let ObjectPropertyKey::Index(index) = &property.key else {
+            return walk_object_property(self, property);
+        };
+
+        self.visit_ast(&index.iterable);
+
+        let names = Scope::from([Self::declare_unspanned(&index.key, SymbolKind::Key)]);
+
+        self.scoped(names, |resolver| {
+            if let Some(remapped_as) = &index.remapped_as {
+                resolver.visit_ast(remapped_as);
+            }
+
+            resolver.visit_ast(&property.value);
+        });
+    }
+
+    fn visit_if_expr(&mut self, expr: &'a IfExpr) {
+        self.scoped(Self::inferred(&expr.condition), |resolver| {
+            resolver.visit_ast(&expr.condition);
+            resolver.visit_ast(&expr.then_branch);
+        });
+
+        if let Some(else_branch) = &expr.else_branch {
+            self.visit_ast(else_branch);
+        }
+    }
+
+    fn visit_extends_expr(&mut self, expr: &'a ExtendsExpr) {
+        self.visit_ast(&expr.lhs);
+
+        self.scoped(Self::inferred(&expr.rhs), |resolver| {
+            resolver.visit_ast(&expr.rhs);
+            resolver.visit_ast(&expr.then_branch);
+        });
+
+        self.visit_ast(&expr.else_branch);
+    }
+
+    fn visit_match_expr(&mut self, expr: &'a MatchExpr) {
+        self.visit_ast(&expr.value);
+
+        for arm in &expr.arms {
+            self.scoped(Self::inferred(&arm.pattern), |resolver| {
+                resolver.visit_ast(&arm.pattern);
+                resolver.visit_ast(&arm.body);
+            });
+        }
+
+        self.visit_ast(&expr.else_arm);
+    }
+
+    fn visit_cond_expr(&mut self, cond: &'a CondExpr) {
+        for arm in &cond.arms {
+            self.scoped(Self::inferred(&arm.condition), |resolver| {
+                resolver.visit_ast(&arm.condition);
+                resolver.visit_ast(&arm.body);
+            });
+        }
+
+        if let Some(else_arm) = &cond.else_arm {
+            self.visit_ast(else_arm);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn rename(source: &str, at: &str, new_name: &str) -> Result<String, RewriteError> {
+        rename_symbol(source, &parse!(source), source.find(at).unwrap(), new_name)
+    }
+
+    #[test]
+    fn rename_type_parameter() {
+        let source = "type Pair(T) where T <: string as [T, {a: T, b: T[]}] | Other(T)\n\
+                      type T as 1\n\
+                      type Other(T) as T";
+
+        let renamed = rename(source, "T) where", "Item").unwrap();
+
+        assert_eq!(
+            renamed,
+            "type Pair(Item) where Item <: string as [Item, {a: Item, b: Item[]}] | Other(Item)\n\
+             type T as 1\n\
+             type Other(T) as T"
+        );
+
+        let edits = rename_edits(&parse!(source), source.find("T[]").unwrap(), "Item").unwrap();
+        assert_eq!(edits.len(), 6, "the declaration and five references");
+    }
+
+    #[test]
+    fn rename_across_scopes() {
+        let source = "type A as if B <: [?U] then U else never end\ntype B as [A, B]";
+
+        assert_eq!(
+            rename(source, "B as", "C").unwrap(),
+            "type A as if C <: [?U] then U else never end\ntype C as [A, C]"
+        );
+        assert_eq!(
+            rename(source, "U]", "Item").unwrap(),
+            "type A as if B <: [?Item] then Item else never end\ntype B as [A, B]"
+        );
+    }
+
+    #[test]
+    fn rename_errors() {
+        let source = "type A(T) as map K in keyof(T) do Record(K, string) end";
+
+        assert_eq!(
+            rename(source, "Record", "R"),
+            Err(RewriteError::NoSymbol { offset: 34 })
+        );
+        assert_eq!(
+            rename(source, "K,", "Key"),
+            Err(RewriteError::NoSymbol { offset: 41 })
+        );
+        assert_eq!(
+            rename(source, "T)", "not valid"),
+            Err(RewriteError::InvalidName("not valid".to_string()))
+        );
+    }
+
+    #[test]
+    fn rename_into_a_name_in_scope() {
+        let source = "type A(T, U) as [T, U]\ntype B as A(1, 2)";
+        let name_in_scope = |name: &str, at: &str| RewriteError::NameInScope {
+            name: name.to_string(),
+            start: source.find(at).unwrap(),
+            end: source.find(at).unwrap() + 1,
+        };
+
+        // It would clash with the other parameter.
+        assert_eq!(rename(source, "T,", "U"), Err(name_in_scope("U", "T,")));
+        // It would clash with the other alias.
+        assert_eq!(rename(source, "B", "A"), Err(name_in_scope("A", "B")));
+        // The parameter would shadow the alias in its body.
+        assert_eq!(rename(source, "T,", "A"), Err(name_in_scope("A", "T,")));
+        assert_eq!(
+            rename(source, "T,", "T"),
+            Ok("type A(T, U) as [T, U]\ntype B as A(1, 2)".to_string())
+        );
+    }
+
+    #[test]
+    fn splice_match_arm() {
+        let source = "type A(T) as match T do 1 -> :one, else -> :other end";
+        let start = source.find("1 ->").unwrap();
+        let end = source.find(", else").unwrap();
+
+        assert_eq!(
+            replace_span(
+                source,
+                &parse!(source),
+                (start, end),
+                "1 -> :one, 2 -> :two"
+            ),
+            Ok("type A(T) as match T do 1 -> :one, 2 -> :two, else -> :other end".to_string())
+        );
+    }
+
+    #[test]
+    fn replace_statements() {
+        let source = "type A as 1\ntype B as 2\ntype C as 3\n";
+        let start = source.find("type B").unwrap();
+        let end = source.find("type C").unwrap();
+
+        assert_eq!(
+            replace_span(source, &parse!(source), (start, end), "type D as 4\n"),
+            Ok("type A as 1\ntype D as 4\ntype C as 3\n".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        let source = "type A as [1, 2]";
+        let program = parse!(source);
+
+        // `[1, ` is not a node.
+        assert_eq!(
+            replace_span(source, &program, (10, 14), "3"),
+            Err(RewriteError::NotANode { start: 10, end: 14 })
+        );
+        assert_eq!(
+            replace_span(source, &program, (10, 100), "3"),
+            Err(RewriteError::OutOfBounds {
+                start: 10,
+                end: 100
+            })
+        );
+
+        let Err(RewriteError::Invalid(diagnostic)) = replace_span(source, &program, (11, 12), "if")
+        else {
+            panic!("expected the edited source to be invalid");
+        };
+
+        assert!(diagnostic.message.starts_with("expected"), "{diagnostic:?}");
+    }
+
+    #[test]
+    fn fix_fixture() {
+        let source = dedent!(
+            r#"
+            union Shape = new {a: 1} | Square {b: 2}
+            type Area(infer, S) as match S do new -> infer, else -> 0 end
+
+            // allow(shadowed_global)
+            type Record as 1
+            type Partial(K) as [Record, K]
+            export type Required as Partial(1)
+            "#
+        );
+
+        let (fixed, diagnostics) = fix(source, &BTreeMap::new()).unwrap();
+
+        assert_eq!(
+            fixed,
+            dedent!(
+                r#"
+                union Shape = new_ {a: 1} | Square {b: 2}
+                type Area(infer_, S) as match S do new_ -> infer_, else -> 0 end
+
+                // allow(shadowed_global)
+                type Record as 1
+                type Partial_(K) as [Record, K]
+                export type Required as Partial_(1)
+                "#
+            )
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.suggestion.as_ref().unwrap().message.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "rename it to `new_`",
+                "rename it to `infer_`",
+                "rename it to `Partial_`"
+            ]
+        );
+
+        // Fixing again changes nothing, allowed lints are left alone.
+        assert_eq!(
+            fix(&fixed, &BTreeMap::new()).unwrap(),
+            (fixed.clone(), vec![])
+        );
+
+        let levels = BTreeMap::from([(lint::SHADOWED_GLOBAL.to_string(), Level::Allow)]);
+
+        assert!(fix(source, &levels).unwrap().0.contains("type Partial(K)"));
+    }
+
+    #[test]
+    fn fix_syntax_error() {
+        let diagnostic = fix("type new as", &BTreeMap::new()).unwrap_err();
+
+        assert_eq!(
+            diagnostic.code,
+            Some(crate::diagnostic::codes::SYNTAX_ERROR.id)
+        );
+    }
+}
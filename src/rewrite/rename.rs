@@ -0,0 +1,811 @@
+/// Renaming of a declaration across the modules of a project: its references in the module
+/// declaring it, and in the modules importing it, by name, through a namespace or with an import
+/// type.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::{declarations, names_in_scope, symbol_edits, Edit, RewriteError};
+use crate::{
+    ast::{
+        visit::{walk_access, Visit},
+        Access, AccessKind, Ast, ImportClause, ImportStatement, ImportType, Program, Span,
+    },
+    compile::project::{self, Module, ModuleDiagnostic},
+    diagnostic::Diagnostic,
+    dsl,
+    error::humanize_pest,
+    parser::parse_newtype_program,
+};
+
+/// The declaration to rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The declaration, or a reference to it, at a byte offset of a module, `types.dl:120`. A
+    /// reference to a name imported from another module stands for its declaration there.
+    At { path: PathBuf, offset: usize },
+    /// The declaration of a name in a module, `types.dl::User`.
+    Named { path: PathBuf, name: String },
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        if let Some((path, name)) = target.rsplit_once("::") {
+            return Ok(Target::Named {
+                path: path.into(),
+                name: name.to_string(),
+            });
+        }
+
+        match target.rsplit_once(':') {
+            Some((path, offset)) => Ok(Target::At {
+                path: path.into(),
+                offset: offset
+                    .parse()
+                    .map_err(|_| format!("`{offset}` is not an offset"))?,
+            }),
+            None => Err(format!(
+                "`{target}` is neither `FILE:OFFSET` nor `FILE::NAME`"
+            )),
+        }
+    }
+}
+
+impl Target {
+    fn path(&self) -> &Path {
+        match self {
+            Target::At { path, .. } | Target::Named { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RenameError {
+    /// The target isn't a declaration of the project that can be renamed.
+    Invalid(String),
+    /// Modules that don't parse, or the places where the new name is already in scope.
+    Diagnostics(Vec<ModuleDiagnostic>),
+}
+
+impl Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenameError::Invalid(message) => write!(f, "{message}"),
+            RenameError::Diagnostics(diagnostics) => write!(
+                f,
+                "{}",
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.diagnostic.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        }
+    }
+}
+
+/// The edits of a rename, by the path of the module they apply to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renamed {
+    /// The name the declaration had.
+    pub old_name: String,
+    /// The modules with edits, sorted by path.
+    pub edits: Vec<(PathBuf, Vec<Edit>)>,
+}
+
+impl Renamed {
+    /// The renamed sources of the modules with edits.
+    pub fn apply(&self, modules: &[Module]) -> Vec<(PathBuf, String)> {
+        self.edits
+            .iter()
+            .map(|(path, edits)| {
+                let module = modules.iter().find(|module| module.path == *path).unwrap();
+
+                (
+                    path.clone(),
+                    super::apply(&module.source, edits).expect("renamed modules parse"),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The edits renaming the top-level declaration `target` of `modules` to `new_name`, in the module
+/// declaring it and in every module importing it. Fails without an edit if `new_name` is already
+/// in scope where the declaration or a reference to it is, with an error for each of these places.
+/// Renaming a declaration to its own name has no edits.
+pub fn rename(modules: &[Module], target: &Target, new_name: &str) -> Result<Renamed, RenameError> {
+    if !dsl::is_ident(new_name) {
+        return Err(RenameError::Invalid(
+            RewriteError::InvalidName(new_name.to_string()).to_string(),
+        ));
+    }
+
+    let sources = project::sources(modules);
+    let mut programs = vec![];
+    let mut syntax_errors = vec![];
+
+    for (module, file) in modules.iter().zip(sources.ids()) {
+        match parse_newtype_program(&module.source) {
+            Ok(program) => programs.push((module, file, program)),
+            Err(err) => syntax_errors.push(ModuleDiagnostic {
+                path: module.path.clone(),
+                diagnostic: humanize_pest(*err, &module.source).with_file(file),
+            }),
+        }
+    }
+
+    // A module that doesn't parse could reference the declaration.
+    if !syntax_errors.is_empty() {
+        return Err(RenameError::Diagnostics(syntax_errors));
+    }
+
+    // A module importing a module that isn't in the project could import the declaration through
+    // it, a module that is in the project under another path.
+    let unresolved: Vec<_> = programs
+        .iter()
+        .flat_map(|(module, file, program)| {
+            unresolved_imports(&module.path, program, modules)
+                .into_iter()
+                .map(|(specifier, span)| ModuleDiagnostic {
+                    path: module.path.clone(),
+                    diagnostic: Diagnostic::error(
+                        format!("can't resolve the module `{specifier}`"),
+                        span,
+                    )
+                    .with_note("the rename could miss the references it imports")
+                    .with_file(*file),
+                })
+        })
+        .collect();
+
+    if !unresolved.is_empty() {
+        return Err(RenameError::Diagnostics(unresolved));
+    }
+
+    // A name imported from another module is renamed where it is declared.
+    let target = match target {
+        Target::At { path, offset } => programs
+            .iter()
+            .find(|(module, _, _)| module.path == *path)
+            .and_then(|(_, _, program)| imported(path, program, *offset))
+            .map_or_else(
+                || target.clone(),
+                |(path, name)| Target::Named { path, name },
+            ),
+        Target::Named { .. } => target.clone(),
+    };
+    let target = &target;
+
+    let (declaring, _, program) = programs
+        .iter()
+        .find(|(module, _, _)| module.path == target.path())
+        .ok_or_else(|| {
+            RenameError::Invalid(format!(
+                "there is no module `{}` in the project",
+                target.path().display()
+            ))
+        })?;
+
+    let (old_name, declaration) = declaration(program, target)?;
+
+    if old_name == new_name {
+        return Ok(Renamed {
+            old_name,
+            edits: vec![],
+        });
+    }
+
+    let mut edits: BTreeMap<PathBuf, Vec<Edit>> = BTreeMap::new();
+    let mut collisions = vec![];
+
+    for (module, file, program) in &programs {
+        // The names of the module, whose scopes the new name must not be in, and the names of
+        // members of a namespace or of another module.
+        let (names, members) = if module.path == declaring.path {
+            let (_, names) = symbol_edits(program, declaration.0, new_name)
+                .map_err(|err| RenameError::Invalid(format!("{}: {err}", module.path.display())))?;
+
+            (names, vec![])
+        } else {
+            importing_edits(&module.path, program, &declaring.path, &old_name, new_name)
+        };
+
+        for edit in &names {
+            if names_in_scope(program, edit.start).contains_key(new_name) {
+                collisions.push(ModuleDiagnostic {
+                    path: module.path.clone(),
+                    diagnostic: Diagnostic::error(
+                        format!(
+                            "`{new_name}` is already in scope here, it can't rename `{old_name}`"
+                        ),
+                        Span::new(edit.start, edit.end),
+                    )
+                    .with_file(*file),
+                });
+            }
+        }
+
+        let mut module_edits: Vec<_> = names.into_iter().chain(members).collect();
+        module_edits.sort_by_key(|edit| edit.start);
+
+        if !module_edits.is_empty() {
+            edits.insert(module.path.clone(), module_edits);
+        }
+    }
+
+    if !collisions.is_empty() {
+        return Err(RenameError::Diagnostics(collisions));
+    }
+
+    Ok(Renamed {
+        old_name,
+        edits: edits.into_iter().collect(),
+    })
+}
+
+/// The relative specifiers of the imports and import types of `program`, the module at `path`,
+/// that import no module of `modules`, and their positions.
+fn unresolved_imports<'a>(
+    path: &Path,
+    program: &'a Ast,
+    modules: &[Module],
+) -> Vec<(&'a str, Span)> {
+    struct Specifiers<'a>(Vec<(&'a str, Span)>);
+
+    impl<'a> Visit<'a> for Specifiers<'a> {
+        fn visit_import_statement(&mut self, import: &'a ImportStatement) {
+            self.0.push((&import.module, import.span));
+        }
+
+        fn visit_import_type(&mut self, import: &'a ImportType) {
+            self.0.push((&import.module, import.span));
+        }
+    }
+
+    let mut specifiers = Specifiers(vec![]);
+    specifiers.visit_ast(program);
+
+    specifiers
+        .0
+        .into_iter()
+        .filter(|(specifier, _)| {
+            project::is_relative(specifier)
+                && !project::resolve(path, specifier)
+                    .is_some_and(|imported| modules.iter().any(|module| module.path == imported))
+        })
+        .collect()
+}
+
+/// The name and the position of the top-level declaration `target` names in `program`.
+fn declaration(program: &Ast, target: &Target) -> Result<(String, (usize, usize)), RenameError> {
+    let Ast::Program(Program { statements, .. }) = program else {
+        unreachable!("modules are programs")
+    };
+
+    let declared: Vec<_> = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Ast::Statement(statement) => match statement.inner.as_ref() {
+                Ast::TypeAlias(alias) => Some(&alias.name),
+                Ast::UnionDeclaration(union) => Some(&union.name),
+                Ast::BrandDeclaration(brand) => Some(&brand.name),
+                Ast::PatternDeclaration(pattern) => Some(&pattern.name),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    let path = target.path().display();
+
+    match target {
+        Target::Named { name, .. } => declared
+            .iter()
+            .find(|ident| ident.name == *name)
+            .map(|ident| (ident.name.clone(), (ident.span.start, ident.span.end)))
+            .ok_or_else(|| RenameError::Invalid(format!("`{path}` declares no `{name}`"))),
+        Target::At { offset, .. } => {
+            let declaration = declarations(program)
+                .into_iter()
+                .find(|((start, end), _)| start <= offset && offset < end)
+                .map(|(_, declaration)| declaration)
+                .ok_or_else(|| {
+                    RenameError::Invalid(RewriteError::NoSymbol { offset: *offset }.to_string())
+                })?;
+
+            declared
+                .iter()
+                .find(|ident| (ident.span.start, ident.span.end) == declaration)
+                .map(|ident| (ident.name.clone(), declaration))
+                .ok_or_else(|| {
+                    RenameError::Invalid(format!(
+                        "the name at {path}:{offset} isn't a top-level declaration of the module"
+                    ))
+                })
+        }
+    }
+}
+
+/// The module the name at `offset` of the module at `path` is imported from, and the name the
+/// module exports it as: a name imported by a specifier, or a member of a namespace or an import
+/// type. `None` for a name declared by the module itself.
+fn imported(path: &Path, program: &Ast, offset: usize) -> Option<(PathBuf, String)> {
+    /// The member at `offset` of a namespace or an import type.
+    struct Member<'a> {
+        path: &'a Path,
+        offset: usize,
+        /// The namespaces the module imports, by the position of their name.
+        namespaces: HashMap<(usize, usize), PathBuf>,
+        declarations: HashMap<(usize, usize), (usize, usize)>,
+        found: Option<(PathBuf, String)>,
+    }
+
+    impl<'a> Visit<'a> for Member<'a> {
+        fn visit_access(&mut self, access: &'a Access) {
+            walk_access(self, access);
+
+            let Ast::Ident(member) = access.rhs.as_ref() else {
+                return;
+            };
+
+            if access.kind == AccessKind::Index
+                || !(member.span.start <= self.offset && self.offset < member.span.end)
+            {
+                return;
+            }
+
+            let module = match access.lhs.as_ref() {
+                Ast::Ident(namespace) => self
+                    .declarations
+                    .get(&(namespace.span.start, namespace.span.end))
+                    .and_then(|declaration| self.namespaces.get(declaration))
+                    .cloned(),
+                Ast::ImportType(import) => project::resolve(self.path, &import.module),
+                _ => None,
+            };
+
+            if let Some(module) = module {
+                self.found = Some((module, member.name.clone()));
+            }
+        }
+    }
+
+    let Ast::Program(Program { statements, .. }) = program else {
+        unreachable!("modules are programs")
+    };
+
+    let within = |ident: &crate::ast::Ident| ident.span.start <= offset && offset < ident.span.end;
+    let declarations = declarations(program);
+    let declaration = declarations
+        .iter()
+        .find(|((start, end), _)| *start <= offset && offset < *end)
+        .map(|(_, declaration)| *declaration);
+
+    let mut namespaces = HashMap::new();
+
+    for statement in statements {
+        let Ast::Statement(statement) = statement else {
+            continue;
+        };
+        let Ast::ImportStatement(import) = statement.inner.as_ref() else {
+            continue;
+        };
+        let Some(module) = project::resolve(path, &import.module) else {
+            continue;
+        };
+
+        match &import.import_clause {
+            ImportClause::Named(specifiers) => {
+                for specifier in specifiers {
+                    let name = &specifier.module_export_name;
+                    let local = specifier.alias.as_ref().unwrap_or(name);
+
+                    if within(name)
+                        || within(local)
+                        || declaration == Some((local.span.start, local.span.end))
+                    {
+                        return Some((module, name.name.clone()));
+                    }
+                }
+            }
+            ImportClause::Namespace { alias } => {
+                namespaces.insert((alias.span.start, alias.span.end), module);
+            }
+        }
+    }
+
+    let mut member = Member {
+        path,
+        offset,
+        namespaces,
+        declarations,
+        found: None,
+    };
+    member.visit_ast(program);
+
+    member.found
+}
+
+/// The edits renaming `old_name`, declared by the module at `declaring`, in the module at `path`
+/// importing it: those of the specifiers importing it by its name and of their references, then
+/// those of the names of members, the specifiers importing it under an alias and the accesses of
+/// it through a namespace or an import type.
+fn importing_edits(
+    path: &Path,
+    program: &Ast,
+    declaring: &Path,
+    old_name: &str,
+    new_name: &str,
+) -> (Vec<Edit>, Vec<Edit>) {
+    /// The names that refer to the module `declaring` before a `::` or `.`, namespace imports and
+    /// import types.
+    struct Accesses<'a> {
+        path: &'a Path,
+        declaring: &'a Path,
+        old_name: &'a str,
+        namespaces: Vec<(usize, usize)>,
+        declarations: HashMap<(usize, usize), (usize, usize)>,
+        edits: Vec<Edit>,
+        new_name: &'a str,
+    }
+
+    impl<'a> Visit<'a> for Accesses<'a> {
+        fn visit_access(&mut self, access: &'a Access) {
+            walk_access(self, access);
+
+            let Ast::Ident(member) = access.rhs.as_ref() else {
+                return;
+            };
+
+            let imported = match access.lhs.as_ref() {
+                _ if access.kind == AccessKind::Index || member.name != self.old_name => false,
+                Ast::Ident(namespace) => self
+                    .declarations
+                    .get(&(namespace.span.start, namespace.span.end))
+                    .is_some_and(|declaration| self.namespaces.contains(declaration)),
+                Ast::ImportType(import) => {
+                    project::resolve(self.path, &import.module).as_deref() == Some(self.declaring)
+                }
+                _ => false,
+            };
+
+            if imported {
+                self.edits.push(Edit {
+                    start: member.span.start,
+                    end: member.span.end,
+                    text: self.new_name.to_string(),
+                });
+            }
+        }
+    }
+
+    let Ast::Program(Program { statements, .. }) = program else {
+        unreachable!("modules are programs")
+    };
+
+    let mut names = vec![];
+    let mut members = vec![];
+    let mut namespaces = vec![];
+
+    let imports = statements.iter().filter_map(|statement| match statement {
+        Ast::Statement(statement) => match statement.inner.as_ref() {
+            Ast::ImportStatement(import)
+                if project::resolve(path, &import.module).as_deref() == Some(declaring) =>
+            {
+                Some(import)
+            }
+            _ => None,
+        },
+        _ => None,
+    });
+
+    for import in imports {
+        match &import.import_clause {
+            ImportClause::Named(specifiers) => {
+                for specifier in specifiers {
+                    let name = &specifier.module_export_name;
+
+                    if name.name != old_name {
+                        continue;
+                    }
+
+                    match &specifier.alias {
+                        // The module refers to it by its alias.
+                        Some(_) => members.push(Edit {
+                            start: name.span.start,
+                            end: name.span.end,
+                            text: new_name.to_string(),
+                        }),
+                        None => names.extend(
+                            symbol_edits(program, name.span.start, new_name)
+                                .map(|(_, edits)| edits)
+                                .unwrap_or_default(),
+                        ),
+                    }
+                }
+            }
+            ImportClause::Namespace { alias } => {
+                namespaces.push((alias.span.start, alias.span.end));
+            }
+        }
+    }
+
+    let mut accesses = Accesses {
+        path,
+        declaring,
+        old_name,
+        namespaces,
+        declarations: declarations(program),
+        edits: members,
+        new_name,
+    };
+    accesses.visit_ast(program);
+
+    (names, accesses.edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use textwrap_macros::dedent;
+
+    fn fixture() -> Vec<Module> {
+        [
+            (
+                "api.dl",
+                dedent!(
+                    r#"
+                    import { User, Id } from "./types"
+                    export type Response as {user: User, id: Id}
+                    export type Account as {owner: User}
+                    "#
+                ),
+            ),
+            (
+                "types.dl",
+                dedent!(
+                    r#"
+                    export type User as {name: string, friends: User[]}
+                    export type Id as string
+                    "#
+                ),
+            ),
+            (
+                "views/profile.dl",
+                dedent!(
+                    r#"
+                    import * as types from "../types.dl"
+                    import { User as Person } from "../types.dl"
+                    export type Profile as {
+                        user: types::User,
+                        friend: Person,
+                        raw: import("../types.dl")::User,
+                    }
+                    "#
+                ),
+            ),
+        ]
+        .into_iter()
+        .map(|(path, source)| Module {
+            path: path.into(),
+            source: source.to_string(),
+        })
+        .collect()
+    }
+
+    fn user() -> Target {
+        "types.dl::User".parse().unwrap()
+    }
+
+    #[test]
+    fn rename_across_modules() {
+        let modules = fixture();
+        let renamed = rename(&modules, &user(), "Member").unwrap();
+
+        assert_eq!(
+            renamed
+                .edits
+                .iter()
+                .map(|(path, edits)| (path.to_str().unwrap(), edits.len()))
+                .collect::<Vec<_>>(),
+            vec![("api.dl", 3), ("types.dl", 2), ("views/profile.dl", 3)]
+        );
+        assert_eq!(
+            renamed.apply(&modules),
+            vec![
+                (
+                    "api.dl".into(),
+                    dedent!(
+                        r#"
+                        import { Member, Id } from "./types"
+                        export type Response as {user: Member, id: Id}
+                        export type Account as {owner: Member}
+                        "#
+                    )
+                    .to_string()
+                ),
+                (
+                    "types.dl".into(),
+                    dedent!(
+                        r#"
+                        export type Member as {name: string, friends: Member[]}
+                        export type Id as string
+                        "#
+                    )
+                    .to_string()
+                ),
+                (
+                    "views/profile.dl".into(),
+                    dedent!(
+                        r#"
+                        import * as types from "../types.dl"
+                        import { Member as Person } from "../types.dl"
+                        export type Profile as {
+                            user: types::Member,
+                            friend: Person,
+                            raw: import("../types.dl")::Member,
+                        }
+                        "#
+                    )
+                    .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rename_at_a_reference() {
+        let modules = fixture();
+        let offset = modules[1].source.find("User[]").unwrap();
+        let at = format!("types.dl:{offset}").parse().unwrap();
+
+        assert_eq!(
+            rename(&modules, &at, "Member").unwrap(),
+            rename(&modules, &user(), "Member").unwrap()
+        );
+    }
+
+    /// A name imported from the declaring module, by a specifier, through a namespace or with an
+    /// import type, at `name` in the first occurrence of `at`.
+    #[rstest]
+    #[case("api.dl", "user: User", "User")]
+    #[case("api.dl", "User, Id", "User")]
+    #[case("views/profile.dl", "types::User", "User")]
+    #[case("views/profile.dl", "User as Person", "User")]
+    #[case("views/profile.dl", "User as Person", "Person")]
+    #[case("views/profile.dl", "friend: Person", "Person")]
+    #[case("views/profile.dl", "\")::User", "User")]
+    fn rename_at_an_imported_name(#[case] path: &str, #[case] at: &str, #[case] name: &str) {
+        let modules = fixture();
+        let module = modules
+            .iter()
+            .find(|module| module.path == Path::new(path))
+            .unwrap();
+        let offset = module.source.find(at).unwrap() + at.find(name).unwrap();
+        let at = format!("{path}:{offset}").parse().unwrap();
+
+        assert_eq!(
+            rename(&modules, &at, "Member").unwrap(),
+            rename(&modules, &user(), "Member").unwrap()
+        );
+    }
+
+    #[test]
+    fn collisions_are_refused() {
+        let modules = fixture();
+
+        let Err(RenameError::Diagnostics(collisions)) = rename(&modules, &user(), "Account") else {
+            panic!("expected collisions");
+        };
+
+        let source = &modules[0].source;
+
+        assert_eq!(
+            collisions
+                .iter()
+                .map(|ModuleDiagnostic { path, diagnostic }| (
+                    path.to_str().unwrap(),
+                    diagnostic.message.as_str(),
+                    source[..diagnostic.span.end].lines().last().unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "api.dl",
+                    "`Account` is already in scope here, it can't rename `User`",
+                    "import { User"
+                ),
+                (
+                    "api.dl",
+                    "`Account` is already in scope here, it can't rename `User`",
+                    "export type Response as {user: User"
+                ),
+                (
+                    "api.dl",
+                    "`Account` is already in scope here, it can't rename `User`",
+                    "export type Account as {owner: User"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unresolved_imports_are_refused() {
+        let mut modules = fixture();
+        modules[0].source = modules[0].source.replace("\"./types\"", "\"./models\"");
+
+        let Err(RenameError::Diagnostics(diagnostics)) = rename(&modules, &user(), "Member") else {
+            panic!("expected an unresolved import");
+        };
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|ModuleDiagnostic { path, diagnostic }| (
+                    path.to_str().unwrap(),
+                    diagnostic.message.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![("api.dl", "can't resolve the module `./models`")]
+        );
+    }
+
+    #[test]
+    fn renaming_to_the_same_name() {
+        assert_eq!(
+            rename(&fixture(), &user(), "User").unwrap(),
+            Renamed {
+                old_name: "User".to_string(),
+                edits: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_targets() {
+        let error = |target: &str, new_name: &str| {
+            rename(&fixture(), &target.parse().unwrap(), new_name)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error("types.dl::Group", "Team"),
+            "`types.dl` declares no `Group`"
+        );
+        assert_eq!(
+            error("users.dl::User", "Member"),
+            "there is no module `users.dl` in the project"
+        );
+        assert_eq!(
+            error("api.dl:0", "Member"),
+            "no name declared in the program at 0"
+        );
+        assert_eq!(
+            rename(
+                &[Module {
+                    path: "a.dl".into(),
+                    source: "type A(T) as T".to_string()
+                }],
+                &"a.dl:13".parse().unwrap(),
+                "U"
+            )
+            .unwrap_err()
+            .to_string(),
+            "the name at a.dl:13 isn't a top-level declaration of the module"
+        );
+        assert_eq!(error("types.dl::User", "1"), "`1` is not an identifier");
+        assert_eq!(
+            "types.dl".parse::<Target>(),
+            Err("`types.dl` is neither `FILE:OFFSET` nor `FILE::NAME`".to_string())
+        );
+    }
+}
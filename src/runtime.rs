@@ -1,4 +1,493 @@
-use crate::ast::*;
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    ast::{
+        access::Aliases,
+        brand_declaration::BrandEncoding,
+        consed::Consed,
+        if_expr::MissingElse,
+        origin::{self, Origin, OriginId},
+        *,
+    },
+    diagnostic::{codes, Diagnostic, Severity},
+    names::Gensym,
+    parser::parse_newtype_expr,
+};
+
+/// Expansion of a macro call, failing with a diagnostic when the macro can't be applied to the
+/// arguments.
+pub type Macro = Box<dyn Fn(&MacroCall, &Helpers) -> Result<Ast, Diagnostic>>;
+
+/// The macros a program can call, by name without the `!`. The default registry has the builtin
+/// macros, which [`MacroRegistry::register`] can replace.
+pub struct MacroRegistry {
+    macros: BTreeMap<String, Macro>,
+}
+
+impl MacroRegistry {
+    /// A registry without any macro, not even the builtin ones.
+    pub fn empty() -> Self {
+        Self {
+            macros: BTreeMap::new(),
+        }
+    }
+
+    /// Registers `expand` as the macro `name!`, replacing the macro of that name if there is one.
+    ///
+    /// The arguments of the call are not simplified, [`Helpers::simplify`] simplifies them.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        expand: impl Fn(&MacroCall, &Helpers) -> Result<Ast, Diagnostic> + 'static,
+    ) -> &mut Self {
+        self.macros.insert(name.into(), Box::new(expand));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Macro> {
+        self.macros.get(name)
+    }
+
+    /// Names of the registered macros, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.macros.keys().map(String::as_str)
+    }
+}
+
+impl Default for MacroRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry
+            .register("dbg", |call, _| {
+                let [node] = arguments(call)?;
+                Ok(builtin::dbg(node.to_owned()))
+            })
+            .register("assert_equal", |call, _| {
+                let [lhs, rhs] = arguments(call)?;
+                builtin::assert_equal(lhs.to_owned(), rhs.to_owned())
+            })
+            .register("unquote", |call, _| {
+                let [node] = arguments(call)?;
+                Ok(builtin::unquote(node.to_owned()))
+            })
+            .register("merge", |call, helpers| {
+                let [lhs, rhs] = arguments(call)?;
+                Ok(builtin::merge(helpers.simplify(lhs), helpers.simplify(rhs)))
+            })
+            .register("deep_readonly", |call, helpers| {
+                let [node] = arguments(call)?;
+                Ok(builtin::deep(
+                    Deep::Readonly,
+                    helpers.simplify(node),
+                    helpers,
+                ))
+            })
+            .register("deep_partial", |call, helpers| {
+                let [node] = arguments(call)?;
+                Ok(builtin::deep(
+                    Deep::Partial,
+                    helpers.simplify(node),
+                    helpers,
+                ))
+            })
+            .register("mutable", |call, helpers| {
+                let [node] = arguments(call)?;
+                Ok(builtin::deep(
+                    Deep::Mutable,
+                    helpers.simplify(node),
+                    helpers,
+                ))
+            })
+            .register("keys_of", |call, helpers| {
+                let [node] = arguments(call)?;
+                Ok(builtin::keys_of(helpers.simplify(node), helpers))
+            })
+            .register("values_of", |call, helpers| {
+                let [node] = arguments(call)?;
+                Ok(builtin::values_of(helpers.simplify(node), helpers))
+            })
+            .register("index_by", |call, helpers| {
+                let [union, discriminant] = arguments(call)?;
+
+                let Ast::TypeString(TypeString { ty: key, .. }) = discriminant else {
+                    return Err(Diagnostic::error(
+                        format!(
+                            "`{}` expects the name of the discriminant, found `{discriminant}`",
+                            call.name
+                        ),
+                        discriminant.as_span(),
+                    )
+                    .with_note("e.g. `:kind`")
+                    .with_code(codes::INVALID_MACRO_ARGUMENT));
+                };
+
+                Ok(builtin::index_by(helpers.simplify(union), key, helpers))
+            })
+            .register("all", |call, helpers| {
+                let conditions = conditions(call, helpers)?;
+                Ok(builtin::chain(InfixOp::And, conditions, call.span))
+            })
+            .register("any", |call, helpers| {
+                let conditions = conditions(call, helpers)?;
+                Ok(builtin::chain(InfixOp::Or, conditions, call.span))
+            });
+
+        registry
+    }
+}
+
+impl std::fmt::Debug for MacroRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+/// The arguments of `call`, which must be exactly `N`.
+pub fn arguments<const N: usize>(call: &MacroCall) -> Result<&[Ast; N], Diagnostic> {
+    call.args.as_slice().try_into().map_err(|_| {
+        Diagnostic::error(
+            format!(
+                "`{}` expects {N} argument{}, found {}",
+                call.name,
+                if N == 1 { "" } else { "s" },
+                call.args.len()
+            ),
+            call.span,
+        )
+        .with_code(codes::INVALID_MACRO_ARGUMENT)
+    })
+}
+
+/// The conditions listed by the only argument of `call`, simplified. The argument must be a tuple
+/// of conditions, `[A <: B, C <: D]`.
+pub fn conditions(call: &MacroCall, helpers: &Helpers) -> Result<Vec<Ast>, Diagnostic> {
+    let [list] = arguments(call)?;
+
+    let Ast::Tuple(Tuple { items, .. }) = list else {
+        return Err(Diagnostic::error(
+            format!("`{}` expects a list of conditions", call.name),
+            list.as_span(),
+        )
+        .with_note("e.g. `[A <: B, C <: D]`")
+        .with_code(codes::INVALID_MACRO_ARGUMENT));
+    };
+
+    items
+        .iter()
+        .map(|item| match item {
+            Ast::ExtendsInfixOp(_) | Ast::ExtendsPrefixOp(_) => Ok(helpers.simplify(item)),
+            _ => Err(Diagnostic::error(
+                format!("`{}` expects a condition, found `{}`", call.name, item),
+                item.as_span(),
+            )
+            .with_note("e.g. `A <: B`")
+            .with_code(codes::INVALID_MACRO_ARGUMENT)),
+        })
+        .collect()
+}
+
+/// Modifier applied to every level of a type by `deep_readonly!`, `deep_partial!` and
+/// `mutable!`, which removes `readonly`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deep {
+    Readonly,
+    Partial,
+    Mutable,
+}
+
+impl Deep {
+    pub fn name(self) -> &'static str {
+        match self {
+            Deep::Readonly => "deep_readonly",
+            Deep::Partial => "deep_partial",
+            Deep::Mutable => "mutable",
+        }
+    }
+
+    /// Declaration of `name`, the recursive mapped type applying the modifier to any type.
+    /// Functions and primitives are left as they are, mapped types keep arrays and tuples.
+    ///
+    /// Arrays are mapped by their element when `readonly` is removed, a readonly array being
+    /// made an array rather than an object of its keys.
+    fn helper(self, name: &Ident) -> Ast {
+        let key = match self {
+            Deep::Readonly | Deep::Mutable => "readonly K",
+            Deep::Partial => "?K",
+        };
+
+        let map = format!(
+            "map {key} in keyof(T) do {name}(T[K]) end",
+            name = name.name
+        );
+
+        let source = match self {
+            Deep::Readonly | Deep::Partial => format!(
+                "if T <: (...args: any) => any then T \
+                 else if T <: object then {map} \
+                 else T end end"
+            ),
+            Deep::Mutable => format!(
+                "if T <: (...args: any) => any then T \
+                 else if T <: ReadonlyArray(?U) then \
+                     if number <: T[\"length\"] then {name}(U)[] else {map} end \
+                 else if T <: object then {map} \
+                 else T end end end",
+                name = name.name
+            ),
+        };
+
+        let body = parse_newtype_expr(&source)
+            .expect("helper should parse")
+            .simplify();
+
+        // The source can only add `readonly`, the mapped types of `mutable!` remove it.
+        let (body, _) = body.postwalk((), &|node, ctx| match node {
+            Ast::MappedType(mapped) if self == Deep::Mutable => (
+                Ast::MappedType(MappedType {
+                    readonly_mod: Some(MappingModifier::Remove),
+                    ..mapped
+                }),
+                ctx,
+            ),
+            node => (node, ctx),
+        });
+
+        Ast::Statement(
+            Ast::TypeAlias(TypeAlias {
+                doc: None,
+                export: false,
+                global: false,
+                default: false,
+                name: name.clone(),
+                params: vec![TypeParameter::new(
+                    "T".to_string(),
+                    None,
+                    None,
+                    false,
+                    Span::default(),
+                )],
+                body: body.into(),
+                span: Span::default(),
+            })
+            .into(),
+        )
+    }
+}
+
+/// State of the expansion of the macros of a program: the macros it can call, the diagnostics of
+/// the calls that failed, and the type aliases macros generate. Each alias is declared once, at
+/// the end of the program, however many calls use it.
+#[derive(Debug)]
+pub struct Helpers<'a> {
+    program: &'a Ast,
+    macros: &'a MacroRegistry,
+    diagnostics: RefCell<Vec<Diagnostic>>,
+    /// Created on first use, as most programs need no helper.
+    gensym: RefCell<Option<Gensym>>,
+    declared: RefCell<Vec<(Deep, Ident)>>,
+    /// Created on first use, as most programs access no property of an alias.
+    aliases: RefCell<Option<Aliases>>,
+    /// Names of the aliases whose bodies are being simplified, see [`Helpers::expanding`].
+    expanding: RefCell<Vec<String>>,
+    /// Number of helpers already taken by [`Helpers::take_declarations`].
+    taken: Cell<usize>,
+    brands: BrandEncoding,
+    missing_else: MissingElse,
+    /// Origin of the tree of the call being expanded, see [`Helpers::origin_of`].
+    expansion: Cell<Option<OriginId>>,
+}
+
+impl<'a> Helpers<'a> {
+    pub fn new(program: &'a Ast, macros: &'a MacroRegistry) -> Self {
+        Self {
+            program,
+            macros,
+            diagnostics: RefCell::new(vec![]),
+            gensym: RefCell::new(None),
+            declared: RefCell::new(vec![]),
+            aliases: RefCell::new(None),
+            expanding: RefCell::new(vec![]),
+            taken: Cell::new(0),
+            brands: BrandEncoding::default(),
+            missing_else: MissingElse::default(),
+            expansion: Cell::new(None),
+        }
+    }
+
+    /// Tags brands as `brands` says instead of with a symbol.
+    pub fn with_brands(self, brands: BrandEncoding) -> Self {
+        Self { brands, ..self }
+    }
+
+    pub fn brands(&self) -> BrandEncoding {
+        self.brands
+    }
+
+    /// Falls back to what `missing_else` says in an `if` or `cond` without `else`.
+    pub fn with_missing_else(self, missing_else: MissingElse) -> Self {
+        Self {
+            missing_else,
+            ..self
+        }
+    }
+
+    pub fn missing_else(&self) -> MissingElse {
+        self.missing_else
+    }
+
+    /// Simplifies the argument of a macro, expanding the macros it calls.
+    pub fn simplify(&self, ast: &Ast) -> Ast {
+        ast.simplify_with(self)
+    }
+
+    /// Expands `call` with the registered macro of its name. A call that fails is replaced by
+    /// `never` and its diagnostic kept for [`Helpers::take_diagnostics`].
+    pub fn expand(&self, call: &MacroCall) -> Ast {
+        self.expand_in(call, self.expansion.get())
+    }
+
+    /// Origin of the node at `span`, from the spans of the arms it is in, see
+    /// [`origin::innermost`]: that of the call being expanded outside of any.
+    pub(crate) fn origin_of(&self, arms: &[Span], span: Span) -> Option<OriginId> {
+        origin::innermost(arms, span).or(self.expansion.get())
+    }
+
+    /// [`Helpers::expand`], for a call written at `origin`. Its tree is tagged as expanded from
+    /// the macro, and the diagnostics of the macro tell where the call is.
+    pub(crate) fn expand_in(&self, call: &MacroCall, origin: Option<OriginId>) -> Ast {
+        let name = call.name.strip_suffix('!').unwrap_or(&call.name);
+
+        let expansion = OriginId::new(
+            Origin::MacroExpansion {
+                name: call.name.clone(),
+            },
+            origin,
+        );
+        let outer = self.expansion.replace(Some(expansion));
+
+        let expanded = match self.macros.get(name) {
+            Some(expand) => expand(call, self),
+            None => Err(
+                Diagnostic::error(format!("unknown macro `{}`", call.name), call.span)
+                    .with_note(format!(
+                        "the macros are {}",
+                        self.macros
+                            .names()
+                            .map(|name| format!("`{name}!`"))
+                            .join(", ")
+                    ))
+                    .with_code(codes::UNKNOWN_MACRO),
+            ),
+        };
+
+        self.expansion.set(outer);
+
+        match expanded {
+            // A tree expanded from a call the macro made is already nested in its expansion.
+            Ok(tree) if tree.as_span().origin().is_some_and(|o| o.is_in(expansion)) => tree,
+            Ok(tree) => tree.with_origin(expansion),
+            Err(diagnostic) => {
+                self.diagnostics
+                    .borrow_mut()
+                    .push(diagnostic.with_origin(origin));
+                Ast::NeverKeyword(call.span)
+            }
+        }
+    }
+
+    /// Reports a warning or a note about a call that could be expanded.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        let origin = self.expansion.get().and_then(OriginId::parent);
+
+        self.diagnostics
+            .borrow_mut()
+            .push(diagnostic.with_origin(origin));
+    }
+
+    /// Diagnostics of the calls expanded so far, the errors of those that failed and the
+    /// diagnostics macros [reported](Helpers::report).
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
+    }
+
+    /// The object literal `name` stands for, if it refers to an alias of one of the program, see
+    /// [`Aliases`].
+    pub fn object(&self, name: &Ident) -> Option<TypeLiteral> {
+        self.aliases
+            .borrow_mut()
+            .get_or_insert_with(|| Aliases::new(self.program))
+            .object(name)
+            .cloned()
+    }
+
+    /// The body of the alias of the program `name` refers to, if it has no type parameters.
+    pub fn alias(&self, name: &Ident) -> Option<Ast> {
+        self.aliases
+            .borrow_mut()
+            .get_or_insert_with(|| Aliases::new(self.program))
+            .body(name)
+            .cloned()
+    }
+
+    /// Runs `f` while the body of the alias `name` refers to is being simplified, `None` when it
+    /// already is: a recursive alias would be expanded forever.
+    pub fn expanding<T>(&self, name: &Ident, f: impl FnOnce() -> T) -> Option<T> {
+        if self.expanding.borrow().contains(&name.name) {
+            return None;
+        }
+
+        self.expanding.borrow_mut().push(name.name.clone());
+        let result = f();
+        self.expanding.borrow_mut().pop();
+
+        Some(result)
+    }
+
+    /// Name of the helper of `deep`, declared the first time it is asked for.
+    pub fn deep(&self, deep: Deep) -> Ident {
+        let mut declared = self.declared.borrow_mut();
+
+        if let Some((_, name)) = declared.iter().find(|(kind, _)| *kind == deep) {
+            return name.clone();
+        }
+
+        let name = self
+            .gensym
+            .borrow_mut()
+            .get_or_insert_with(|| Gensym::new(self.program))
+            .fresh(deep.name());
+
+        declared.push((deep, name.clone()));
+
+        name
+    }
+
+    /// Statements declaring the helpers used since the last call, in order of first use.
+    pub fn take_declarations(&self) -> Vec<Ast> {
+        let declared = self.declared.borrow();
+        let taken = self.taken.replace(declared.len());
+
+        declared[taken..]
+            .iter()
+            .map(|(deep, name)| deep.helper(name))
+            .collect()
+    }
+
+    /// Keeps `names` from being given to helpers, names of a program simplified one statement at
+    /// a time.
+    pub fn reserve<'n>(&self, names: impl IntoIterator<Item = &'n str>) {
+        self.gensym
+            .borrow_mut()
+            .get_or_insert_with(|| Gensym::new(self.program))
+            .reserve(names);
+    }
+}
 
 pub mod builtin {
     use UnionType;
@@ -7,20 +496,540 @@ pub mod builtin {
 
     use super::*;
 
+    /// Prints `tree` to stderr, to keep it out of the emitted TypeScript, and returns it.
     pub fn dbg(tree: Ast) -> Ast {
-        println!("{:#?}", tree);
+        eprintln!("{:#?}", tree);
         tree
     }
 
-    pub fn assert_equal(left: Ast, right: Ast) -> Ast {
-        pretty_assertions::assert_eq!(left, right);
-
+    /// Nothing if `left` and `right` are the same tree, spans aside, an error at both otherwise.
+    pub fn assert_equal(left: Ast, right: Ast) -> Result<Ast, Diagnostic> {
         let span = left.as_span().merge(&right.as_span());
 
-        Ast::NoOp(span)
+        if left != right {
+            return Err(Diagnostic::error(
+                format!("assertion failed: `{left}` is not equal to `{right}`"),
+                span,
+            )
+            .with_code(codes::ASSERTION_FAILED));
+        }
+
+        Ok(Ast::NoOp(span))
+    }
+
+    /// Merges two object types, the properties of `rhs` taking precedence.
+    ///
+    /// Object literals are folded into a single literal. A key declared by both takes the value of
+    /// `rhs`, merged recursively when both values are object literals. When the key is optional in
+    /// `rhs` it may be missing from it, so the value is either of them and it stays as optional as
+    /// it is in `lhs`. Any other types are merged as `Omit<lhs, keyof rhs> & rhs`.
+    pub fn merge(lhs: Ast, rhs: Ast) -> Ast {
+        match (&lhs, &rhs) {
+            (Ast::TypeLiteral(left), Ast::TypeLiteral(right))
+                if is_foldable(left) && is_foldable(right) =>
+            {
+                Ast::TypeLiteral(merge_literals(left, right))
+            }
+            _ => {
+                let span = lhs.as_span().merge(&rhs.as_span());
+
+                let omit = Ast::ApplyGeneric(ApplyGeneric {
+                    receiver: Ast::Ident(Ident {
+                        name: "Omit".to_string(),
+                        span: Span::default(),
+                        raw: false,
+                    })
+                    .into(),
+                    args: vec![
+                        lhs,
+                        Ast::Builtin(Builtin {
+                            name: BuiltinKeyword::Keyof,
+                            argument: rhs.clone().into(),
+                            span: Span::default(),
+                        }),
+                    ],
+                    span: Span::default(),
+                });
+
+                Ast::IntersectionType(IntersectionType {
+                    types: vec![omit, rhs],
+                    span,
+                })
+            }
+        }
+    }
+
+    /// Only literals keyed by names can be folded, other keys may overlap.
+    fn is_foldable(literal: &TypeLiteral) -> bool {
+        literal
+            .iter()
+            .all(|property| matches!(property.key, ObjectPropertyKey::Key(_)))
+    }
+
+    fn merge_literals(lhs: &TypeLiteral, rhs: &TypeLiteral) -> TypeLiteral {
+        let mut properties = lhs.properties.clone();
+
+        for property in rhs.iter() {
+            let Some(existing) = properties.iter_mut().find(|p| p.key == property.key) else {
+                properties.push(property.clone());
+                continue;
+            };
+
+            let value = if property.optional {
+                union(
+                    vec![existing.value.clone(), property.value.clone()],
+                    Span::default(),
+                )
+            } else {
+                match (&existing.value, &property.value) {
+                    (Ast::TypeLiteral(_), Ast::TypeLiteral(_)) => {
+                        merge(existing.value.clone(), property.value.clone())
+                    }
+                    _ => property.value.clone(),
+                }
+            };
+
+            *existing = ObjectProperty {
+                readonly: property.readonly,
+                optional: existing.optional && property.optional,
+                key: property.key.clone(),
+                value,
+                span: property.span,
+            };
+        }
+
+        TypeLiteral {
+            properties,
+            span: lhs.span.merge(&rhs.span),
+        }
+    }
+
+    /// Applies `deep` to every level of `tree`. Object literals, tuples and arrays are folded, the
+    /// properties becoming readonly or optional and the tuples and arrays readonly. The types that
+    /// can't be folded are passed to the helper of `deep`.
+    pub fn deep(deep: Deep, tree: Ast, helpers: &Helpers) -> Ast {
+        let apply = |name: &str, arg: Ast| {
+            let span = arg.as_span();
+
+            Ast::ApplyGeneric(ApplyGeneric {
+                receiver: Ast::Ident(Ident {
+                    name: name.to_string(),
+                    span: Span::default(),
+                    raw: false,
+                })
+                .into(),
+                args: vec![arg],
+                span,
+            })
+        };
+
+        match tree {
+            Ast::TypeLiteral(TypeLiteral { properties, span }) => Ast::TypeLiteral(TypeLiteral {
+                properties: properties
+                    .into_iter()
+                    .map(|property| ObjectProperty {
+                        readonly: match deep {
+                            Deep::Readonly => true,
+                            Deep::Partial => property.readonly,
+                            Deep::Mutable => false,
+                        },
+                        optional: property.optional || deep == Deep::Partial,
+                        value: self::deep(deep, property.value, helpers),
+                        ..property
+                    })
+                    .collect(),
+                span,
+            }),
+            Ast::Tuple(Tuple { items, span }) => {
+                let tuple = Ast::Tuple(Tuple {
+                    items: items
+                        .into_iter()
+                        .map(|item| self::deep(deep, item, helpers))
+                        .collect(),
+                    span,
+                });
+
+                match deep {
+                    Deep::Readonly => apply("Readonly", tuple),
+                    Deep::Partial => apply("Partial", tuple),
+                    Deep::Mutable => tuple,
+                }
+            }
+            Ast::Array(item) => {
+                let item = self::deep(deep, item.as_ref().clone(), helpers);
+
+                match deep {
+                    Deep::Readonly => apply("ReadonlyArray", item),
+                    Deep::Partial | Deep::Mutable => Ast::Array(item.into()),
+                }
+            }
+            // What `deep_readonly!` folds to is folded back.
+            Ast::ApplyGeneric(ApplyGeneric {
+                receiver,
+                args,
+                span,
+            }) if deep == Deep::Mutable => {
+                match (
+                    receiver.as_ident().map(|ident| ident.name.as_str()),
+                    args.as_slice(),
+                ) {
+                    (Some("ReadonlyArray"), [item]) => {
+                        Ast::Array(self::deep(deep, item.clone(), helpers).into())
+                    }
+                    (Some("Readonly"), [inner]) => self::deep(deep, inner.clone(), helpers),
+                    _ => apply(
+                        &helpers.deep(deep).name,
+                        Ast::ApplyGeneric(ApplyGeneric {
+                            receiver,
+                            args,
+                            span,
+                        }),
+                    ),
+                }
+            }
+            // As is the operator form, `readonly T[]` and `readonly [T]`.
+            Ast::Builtin(Builtin {
+                name: BuiltinKeyword::Readonly,
+                argument,
+                ..
+            }) if deep == Deep::Mutable
+                && matches!(argument.as_ref(), Ast::Array(_) | Ast::Tuple(_)) =>
+            {
+                self::deep(deep, argument.as_ref().clone(), helpers)
+            }
+            Ast::UnionType(UnionType { types, span }) => Ast::UnionType(UnionType {
+                types: types
+                    .into_iter()
+                    .map(|ty| self::deep(deep, ty, helpers))
+                    .collect(),
+                span,
+            }),
+            Ast::IntersectionType(IntersectionType { types, span }) => {
+                Ast::IntersectionType(IntersectionType {
+                    types: types
+                        .into_iter()
+                        .map(|ty| self::deep(deep, ty, helpers))
+                        .collect(),
+                    span,
+                })
+            }
+            Ast::TypeNumber(_)
+            | Ast::TypeString(_)
+            | Ast::TemplateString(_)
+            | Ast::Primitive(..)
+            | Ast::TrueKeyword(_)
+            | Ast::FalseKeyword(_)
+            | Ast::NeverKeyword(_)
+            | Ast::UnknownKeyword(_)
+            | Ast::AnyKeyword(_)
+            | Ast::ThisType(_)
+            | Ast::FunctionType(_) => tree,
+            tree => apply(&helpers.deep(deep).name, tree),
+        }
+    }
+
+    /// The keys of an object literal as a union of string literals, `keyof` any other type.
+    pub fn keys_of(tree: Ast, helpers: &Helpers) -> Ast {
+        match &tree {
+            Ast::TypeLiteral(literal) if is_foldable(literal) => union(
+                literal
+                    .iter()
+                    .map(|property| match &property.key {
+                        ObjectPropertyKey::Key(key) => Ast::TypeString(TypeString {
+                            ty: key.clone(),
+                            span: property.span,
+                        }),
+                        _ => unreachable!("foldable literals only have named keys"),
+                    })
+                    .collect(),
+                tree.as_span(),
+            ),
+            _ => {
+                not_folded("keys_of!", &tree, helpers);
+                keyof(tree)
+            }
+        }
+    }
+
+    /// The types of the values of an object literal as a union, `T[keyof T]` for any other type
+    /// `T`. An optional property may be missing, so its value may be `undefined`.
+    pub fn values_of(tree: Ast, helpers: &Helpers) -> Ast {
+        match &tree {
+            Ast::TypeLiteral(literal) if is_foldable(literal) => {
+                let values = literal
+                    .iter()
+                    .flat_map(|property| {
+                        let undefined = Ast::Primitive(PrimitiveType::Undefined, Span::default());
+
+                        std::iter::once(property.value.clone())
+                            .chain(property.optional.then_some(undefined))
+                    })
+                    .collect();
+
+                union(values, tree.as_span())
+            }
+            _ => {
+                not_folded("values_of!", &tree, helpers);
+
+                let span = tree.as_span();
+
+                Ast::Access(Access {
+                    lhs: tree.clone().into(),
+                    rhs: keyof(tree).into(),
+                    kind: AccessKind::Index,
+                    span,
+                })
+            }
+        }
+    }
+
+    /// The members of the union `tree` by the value of their discriminant `key`, `{circle: Circle,
+    /// square: Square}`. Folded when every member is an object literal or the name of an alias of
+    /// one, `tree` being the union or the name of an alias of it. Members with the same value are
+    /// joined. Any other type is left to TypeScript, as
+    /// `{[K in T['kind']]: Extract<T, {kind: K}>}`.
+    ///
+    /// A folded member without the discriminant, or whose discriminant isn't a string literal or a
+    /// union of them, is an error, and the call stands for `never`.
+    pub fn index_by(tree: Ast, key: &str, helpers: &Helpers) -> Ast {
+        let span = tree.as_span();
+
+        let Some(members) = tagged_members(&tree, helpers) else {
+            return extract_by(tree, key);
+        };
+
+        let mut properties: Vec<ObjectProperty> = vec![];
+        let mut failed = false;
+
+        for (member, literal) in members {
+            let values = match discriminants(&member, &literal, key) {
+                Ok(values) => values,
+                Err(diagnostic) => {
+                    helpers.report(diagnostic);
+                    failed = true;
+                    continue;
+                }
+            };
+
+            for value in values {
+                let key = ObjectPropertyKey::Key(value);
+
+                match properties.iter_mut().find(|property| property.key == key) {
+                    Some(existing) => {
+                        existing.value = union(vec![existing.value.clone(), member.clone()], span)
+                    }
+                    None => properties.push(ObjectProperty {
+                        readonly: false,
+                        optional: false,
+                        key,
+                        value: member.clone(),
+                        span: member.as_span(),
+                    }),
+                }
+            }
+        }
+
+        if failed {
+            Ast::NeverKeyword(span)
+        } else {
+            Ast::TypeLiteral(TypeLiteral { properties, span })
+        }
+    }
+
+    /// The members of the union `tree` with the object literal each is, `None` unless they all
+    /// are one or name an alias of one. A name of an alias is followed one level, unless it is
+    /// already being followed.
+    fn tagged_members(tree: &Ast, helpers: &Helpers) -> Option<Vec<(Ast, TypeLiteral)>> {
+        let tree = match tree {
+            Ast::Ident(name) => helpers
+                .alias(name)
+                .and_then(|body| helpers.expanding(name, || helpers.simplify(&body)))
+                .unwrap_or_else(|| tree.clone()),
+            tree => tree.clone(),
+        };
+
+        let members = match tree {
+            Ast::UnionType(UnionType { types, .. }) => types,
+            tree => vec![tree],
+        };
+
+        members
+            .into_iter()
+            .map(|member| {
+                let literal = match &member {
+                    Ast::TypeLiteral(literal) => literal.clone(),
+                    Ast::Ident(name) => helpers.object(name)?,
+                    _ => return None,
+                };
+
+                is_foldable(&literal).then_some((member, literal))
+            })
+            .collect()
+    }
+
+    /// The values of the discriminant `key` of `member`, the object literal `literal`.
+    fn discriminants(
+        member: &Ast,
+        literal: &TypeLiteral,
+        key: &str,
+    ) -> Result<Vec<String>, Diagnostic> {
+        let Some(property) = literal
+            .iter()
+            .find(|property| matches!(&property.key, ObjectPropertyKey::Key(name) if name == key))
+        else {
+            return Err(Diagnostic::error(
+                format!("`{member}` has no property `{key}`"),
+                member.as_span(),
+            )
+            .with_note(format!(
+                "`index_by!` indexes the members of the union by `{key}`"
+            ))
+            .with_code(codes::MISSING_PROPERTY));
+        };
+
+        let values = match &property.value {
+            Ast::UnionType(UnionType { types, .. }) => types.iter().collect(),
+            value => vec![value],
+        };
+
+        values
+            .into_iter()
+            .map(|value| match value {
+                Ast::TypeString(TypeString { ty, .. }) => Ok(ty.clone()),
+                _ => Err(Diagnostic::error(
+                    format!(
+                        "the discriminant `{key}` of `{member}` is `{}`, not a string literal",
+                        property.value
+                    ),
+                    property.value.as_span(),
+                )
+                .with_code(codes::INVALID_MACRO_ARGUMENT)),
+            })
+            .collect()
+    }
+
+    /// `{[K in tree[key]]: Extract<tree, {key: K}>}`, the index named after none of the names of
+    /// `tree`.
+    fn extract_by(tree: Ast, key: &str) -> Ast {
+        let span = tree.as_span();
+
+        let index = std::iter::once("K".to_string())
+            .chain((1..).map(|i| format!("K{i}")))
+            .find(|index| {
+                !tree.any(|node| matches!(node, Ast::Ident(ident) if &ident.name == index))
+            })
+            .unwrap();
+
+        let ident = |name: &str| {
+            Ast::Ident(Ident {
+                name: name.to_string(),
+                span: Span::default(),
+                raw: false,
+            })
+        };
+
+        let discriminant = Ast::TypeString(TypeString {
+            ty: key.to_string(),
+            span: Span::default(),
+        });
+
+        let tagged = Ast::TypeLiteral(TypeLiteral {
+            properties: vec![ObjectProperty {
+                readonly: false,
+                optional: false,
+                key: ObjectPropertyKey::Key(key.to_string()),
+                value: ident(&index),
+                span: Span::default(),
+            }],
+            span: Span::default(),
+        });
+
+        Ast::MappedType(MappedType {
+            iterable: Ast::Access(Access {
+                lhs: tree.clone().into(),
+                rhs: discriminant.into(),
+                kind: AccessKind::Index,
+                span,
+            })
+            .into(),
+            body: Ast::ApplyGeneric(ApplyGeneric {
+                receiver: ident("Extract").into(),
+                args: vec![tree, tagged],
+                span,
+            })
+            .into(),
+            index,
+            remapped_as: None,
+            readonly_mod: None,
+            optional_mod: None,
+            span,
+        })
+    }
+
+    /// `conditions` joined by `op` from left to right, so that they are tested in order. Without
+    /// any, the condition is the identity of `op`: `true` for `and`, `false` for `or`.
+    pub fn chain(op: InfixOp, conditions: Vec<Ast>, span: Span) -> Ast {
+        let identity = match op {
+            InfixOp::And => Ast::TrueKeyword(span),
+            _ => Ast::FalseKeyword(span),
+        };
+
+        conditions
+            .into_iter()
+            .reduce(|lhs, rhs| {
+                let span = lhs.as_span().merge(&rhs.as_span());
+
+                Ast::ExtendsInfixOp(ExtendsInfixOp {
+                    lhs: lhs.into(),
+                    op: op.clone(),
+                    rhs: rhs.into(),
+                    non_distributive: false,
+                    span,
+                })
+            })
+            .unwrap_or(identity)
+    }
+
+    /// Notes that the object literal `tree` is left to TypeScript as it has keys that can't be
+    /// folded. Other types are never folded, there is nothing to note.
+    fn not_folded(name: &str, tree: &Ast, helpers: &Helpers) {
+        if let Ast::TypeLiteral(_) = tree {
+            helpers.report(
+                Diagnostic::new(
+                    Severity::Note,
+                    format!("`{name}` can't fold an object with index signatures or computed keys"),
+                    tree.as_span(),
+                )
+                .with_note("it is left to TypeScript to evaluate"),
+            );
+        }
+    }
+
+    fn keyof(tree: Ast) -> Ast {
+        let span = tree.as_span();
+
+        Ast::Builtin(Builtin {
+            name: BuiltinKeyword::Keyof,
+            argument: tree.into(),
+            span,
+        })
+    }
+
+    /// `types` as a single normalized union, `never` when there are none.
+    fn union(types: Vec<Ast>, span: Span) -> Ast {
+        let mut types = normalize_union(types);
+
+        match types.len() {
+            0 => Ast::NeverKeyword(span),
+            1 => types.remove(0),
+            _ => Ast::UnionType(UnionType { types, span }),
+        }
     }
 
     pub fn unquote(tree: Ast) -> Ast {
+        // Generated programs test the same types in many conditionals.
+        let consed = RefCell::new(Consed::default());
+
         let (out, _) = tree.prewalk((), &|tree, acc| {
             let span = tree.as_span();
 
@@ -32,19 +1041,21 @@ pub mod builtin {
                     rhs,
                     then_branch,
                     else_branch,
+                    non_distributive,
                     ..
-                }) => match lhs.is_subtype(&rhs) {
+                }) => match consed.borrow_mut().is_subtype(lhs, rhs) {
                     ExtendsResult::True => (then_branch.into(), acc),
+                    // Wrapped in a tuple, `never` and `any` are types like any other, assignable to
+                    // everything, rather than distributed over.
+                    ExtendsResult::Never | ExtendsResult::Both if non_distributive => {
+                        (then_branch.into(), acc)
+                    }
                     ExtendsResult::False => (else_branch.into(), acc),
                     ExtendsResult::Never => (Ast::NeverKeyword(span), acc),
-                    ExtendsResult::Both => {
-                        let value = Ast::UnionType(UnionType {
-                            types: vec![then_branch.into(), else_branch.into()],
-                            span,
-                        });
-
-                        (value, acc)
-                    }
+                    ExtendsResult::Both => (
+                        union(vec![then_branch.into(), else_branch.into()], span),
+                        acc,
+                    ),
                 },
 
                 Ast::MappedType(_) => todo!(),
@@ -98,8 +1109,483 @@ mod tests {
             }
         }
 
+        mod merge {
+            use super::*;
+            use crate::typescript::Pretty as _;
+            use pretty_assertions::assert_eq;
+
+            fn merged(source: &str) -> String {
+                ast!(source).simplify().render_pretty_ts(80)
+            }
+
+            #[test]
+            fn nested_literals() {
+                assert_eq!(
+                    merged("merge!({a: {b: 1, c: 2}, d: 3}, {a: {c: 4, e: 5}, f: 6})"),
+                    "{a: {b: 1, c: 4, e: 5}, d: 3, f: 6}"
+                );
+            }
+
+            #[test]
+            fn generic_fallback() {
+                assert_eq!(
+                    merged("merge!(Defaults, {a: 1})"),
+                    "Omit<Defaults, keyof {a: 1}> & {a: 1}"
+                );
+                assert_eq!(merged("merge!({a: {b: 1}}, {a: B})"), "{a: B}");
+            }
+
+            #[test]
+            fn optionality() {
+                assert_eq!(
+                    merged("merge!({?a: 1, b: 2, ?c: 3}, {a: 1, ?b: 3, ?c: 4})"),
+                    "{a: 1, b: 2 | 3, c?: 3 | 4}"
+                );
+            }
+        }
+
+        mod deep {
+            use super::*;
+            use crate::typescript::Pretty as _;
+            use pretty_assertions::assert_eq;
+            use textwrap_macros::dedent;
+
+            fn expanded(source: &str) -> String {
+                parse!(source)
+                    .simplify()
+                    .render_pretty_ts(80)
+                    .trim()
+                    .to_string()
+            }
+
+            #[test]
+            fn literal_folding() {
+                assert_eq!(
+                    ast!("deep_readonly!({a: {b: 1, readonly c: 2}, ?d: 3 | {e: 4}})")
+                        .simplify()
+                        .render_pretty_ts(120),
+                    "{readonly a: {readonly b: 1, readonly c: 2}, readonly d?: 3 | {readonly e: 4}}"
+                );
+                assert_eq!(
+                    ast!("deep_partial!({a: {b: 1}, c: () => {d: 1}})")
+                        .simplify()
+                        .render_pretty_ts(80),
+                    "{a?: {b?: 1}, c?: () => {d: 1}}"
+                );
+            }
+
+            #[test]
+            fn arrays_and_tuples() {
+                assert_eq!(
+                    ast!("deep_readonly!({a: [1, {b: string[]}], c: {d: 1}[]})")
+                        .simplify()
+                        .render_pretty_ts(120),
+                    "{readonly a: Readonly<[1, {readonly b: ReadonlyArray<string>}]>, \
+                     readonly c: ReadonlyArray<{readonly d: 1}>}"
+                );
+                assert_eq!(
+                    ast!("deep_partial!([{a: 1}[], 2])")
+                        .simplify()
+                        .render_pretty_ts(80),
+                    "Partial<[{a?: 1}[], 2]>"
+                );
+            }
+
+            #[test]
+            fn generic_helper_is_declared_once() {
+                assert_eq!(
+                    expanded(
+                        "type A(T) as deep_readonly!(T)\n\
+                         type B(T) as deep_readonly!({a: T})\n\
+                         type C(T) as deep_readonly!([T])"
+                    ),
+                    dedent!(
+                        "
+                        type A<T> = __dl_deep_readonly_0<T>;
+
+                        type B<T> = {readonly a: __dl_deep_readonly_0<T>};
+
+                        type C<T> = Readonly<[__dl_deep_readonly_0<T>]>;
+
+                        type __dl_deep_readonly_0<T> =
+                            T extends ((...args: any) => any) ? T
+                            : T extends object ? { readonly [K in keyof T]: __dl_deep_readonly_0<T[K]> }
+                            : T;
+                        "
+                    )
+                    .trim()
+                );
+            }
+
+            #[test]
+            fn mutable_literals() {
+                assert_eq!(
+                    ast!("mutable!({readonly a: {readonly b: 1}, ?c: [1, {readonly d: 2}]})")
+                        .simplify()
+                        .render_pretty_ts(80),
+                    "{a: {b: 1}, c?: [1, {d: 2}]}"
+                );
+                assert_eq!(
+                    ast!("mutable!(deep_readonly!({a: [1, {b: string[]}], c: {d: 1}[]}))")
+                        .simplify()
+                        .render_pretty_ts(80),
+                    "{a: [1, {b: string[]}], c: {d: 1}[]}"
+                );
+                assert_eq!(
+                    ast!("mutable!(readonly string[])")
+                        .simplify()
+                        .render_pretty_ts(80),
+                    "string[]"
+                );
+                assert_eq!(
+                    ast!("mutable!({a: readonly [1, {readonly b: readonly string[]}]})")
+                        .simplify()
+                        .render_pretty_ts(80),
+                    "{a: [1, {b: string[]}]}"
+                );
+            }
+
+            #[test]
+            fn mutable_helper() {
+                assert_eq!(
+                    expanded("type A(T) as mutable!(T)\ntype B(T) as mutable!({readonly a: T[]})"),
+                    dedent!(
+                        "
+                        type A<T> = __dl_mutable_0<T>;
+
+                        type B<T> = {a: __dl_mutable_0<T>[]};
+
+                        type __dl_mutable_0<T> =
+                            T extends ((...args: any) => any) ? T
+                            : T extends ReadonlyArray<infer U>
+                                ? number extends T['length']
+                                    ? __dl_mutable_0<U>[]
+                                    : { -readonly [K in keyof T]: __dl_mutable_0<T[K]> }
+                            : T extends object ? { -readonly [K in keyof T]: __dl_mutable_0<T[K]> }
+                            : T;
+                        "
+                    )
+                    .trim()
+                );
+            }
+
+            #[test]
+            fn helpers_are_shared_between_macros() {
+                let output = expanded(
+                    "type A(T) as deep_readonly!(T)\n\
+                     type B(T) as mutable!(A(T))\n\
+                     type C(T) as mutable!(deep_readonly!([T]))\n\
+                     type D(T) as deep_readonly!({a: T})",
+                );
+
+                assert_eq!(
+                    output.lines().take(7).collect::<Vec<_>>(),
+                    vec![
+                        "type A<T> = __dl_deep_readonly_0<T>;",
+                        "",
+                        "type B<T> = __dl_mutable_0<A<T>>;",
+                        "",
+                        "type C<T> = [__dl_mutable_0<__dl_deep_readonly_0<T>>];",
+                        "",
+                        "type D<T> = {readonly a: __dl_deep_readonly_0<T>};",
+                    ]
+                );
+                assert_eq!(output.matches("type __dl_deep_readonly_0<T> =").count(), 1);
+                assert_eq!(output.matches("type __dl_mutable_0<T> =").count(), 1);
+            }
+
+            #[test]
+            fn helper_names_are_fresh() {
+                let output = expanded(
+                    "type __dl_deep_partial_0 as 1\n\
+                     type A(T) as deep_partial!(T)",
+                );
+
+                assert!(
+                    output.contains("type A<T> = __dl_deep_partial_1<T>;"),
+                    "{output}"
+                );
+                assert!(output.contains("type __dl_deep_partial_1<T> ="), "{output}");
+            }
+        }
+
+        mod keys_and_values {
+            use super::*;
+            use crate::{diagnostic::Severity, typescript::Pretty as _};
+            use pretty_assertions::assert_eq;
+
+            fn expanded(source: &str) -> (String, Vec<(Severity, String)>) {
+                let (tree, diagnostics) = ast!(source)
+                    .simplify_with_macros(&MacroRegistry::default(), BrandEncoding::default())
+                    .unwrap();
+
+                (
+                    tree.render_pretty_ts(80),
+                    diagnostics
+                        .into_iter()
+                        .map(|diagnostic| (diagnostic.severity, diagnostic.message))
+                        .collect(),
+                )
+            }
+
+            #[test]
+            fn folded_keys() {
+                assert_eq!(
+                    expanded(r#"keys_of!({a: 1, "b-c": 2, ?d: 3})"#),
+                    ("'a' | 'b-c' | 'd'".to_string(), vec![])
+                );
+                assert_eq!(expanded("keys_of!({})"), ("never".to_string(), vec![]));
+            }
+
+            #[test]
+            fn folded_values() {
+                assert_eq!(
+                    expanded("values_of!({a: string, b: 1, c: string, ?d: 1})"),
+                    ("string | 1 | undefined".to_string(), vec![])
+                );
+                assert_eq!(
+                    expanded("values_of!({a: {b: 1}})"),
+                    ("{b: 1}".to_string(), vec![])
+                );
+            }
+
+            #[test]
+            fn generic_fallback() {
+                assert_eq!(expanded("keys_of!(T)"), ("keyof T".to_string(), vec![]));
+                assert_eq!(
+                    expanded("values_of!(T)"),
+                    ("T[keyof T]".to_string(), vec![])
+                );
+            }
+
+            #[test]
+            fn index_signatures_are_not_folded() {
+                assert_eq!(
+                    expanded("keys_of!({a: 1, [K in string]: 2})"),
+                    (
+                        "keyof {a: 1, [K in string]: 2}".to_string(),
+                        vec![(
+                            Severity::Note,
+                            "`keys_of!` can't fold an object with index signatures or computed \
+                             keys"
+                                .to_string()
+                        )]
+                    )
+                );
+                assert_eq!(
+                    expanded("values_of!({[k]: 1})").1,
+                    vec![(
+                        Severity::Note,
+                        "`values_of!` can't fold an object with index signatures or computed keys"
+                            .to_string()
+                    )]
+                );
+            }
+        }
+
+        mod index_by {
+            use super::*;
+            use crate::{
+                compile::{compile, CompileOptions},
+                typescript::Pretty as _,
+            };
+            use pretty_assertions::assert_eq;
+            use textwrap_macros::dedent;
+
+            fn expanded(source: &str) -> String {
+                let (tree, _) = ast!(source)
+                    .simplify_with_macros(&MacroRegistry::default(), BrandEncoding::default())
+                    .unwrap();
+
+                tree.render_pretty_ts(80)
+            }
+
+            fn errors(source: &str) -> Vec<(String, &str)> {
+                compile(source, &CompileOptions::default())
+                    .unwrap_err()
+                    .into_iter()
+                    .map(|diagnostic| {
+                        (
+                            diagnostic.message,
+                            &source[diagnostic.span.start..diagnostic.span.end],
+                        )
+                    })
+                    .collect()
+            }
+
+            #[test]
+            fn aliases_are_folded() {
+                let source = dedent!(
+                    "
+                    type Circle as {kind: :circle, radius: number}
+                    type Square as {kind: :square, side: number}
+                    type Triangle as {kind: :triangle, base: number}
+                    type Shape as Circle | Square | Triangle
+                    type ByKind as index_by!(Shape, :kind)
+                    "
+                );
+
+                let typescript = compile(source, &CompileOptions::default())
+                    .unwrap()
+                    .typescript;
+
+                assert!(
+                    typescript.contains(
+                        "type ByKind = {circle: Circle, square: Square, triangle: Triangle};"
+                    ),
+                    "{typescript}"
+                );
+            }
+
+            #[test]
+            fn literals_are_folded() {
+                assert_eq!(
+                    expanded("index_by!({kind: :a, x: 1} | {kind: :b | :c}, :kind)"),
+                    "{a: {kind: 'a', x: 1}, b: {kind: 'b' | 'c'}, c: {kind: 'b' | 'c'}}"
+                );
+                assert_eq!(
+                    expanded(r#"index_by!({"type": :a} | {"type": :a, b: 1}, "type")"#),
+                    "{a: {type: 'a'} | {type: 'a', b: 1}}"
+                );
+            }
+
+            #[test]
+            fn generic_fallback() {
+                assert_eq!(
+                    expanded("index_by!(T, :kind)"),
+                    "{ [K in T['kind']]: Extract<T, {kind: K}> }"
+                );
+                assert_eq!(
+                    expanded("index_by!(K | {kind: :a}, :kind)"),
+                    "{ [K1 in (K | {kind: 'a'})['kind']]: Extract<K | {kind: 'a'}, {kind: K1}> }"
+                );
+            }
+
+            #[test]
+            fn missing_discriminants() {
+                let source = dedent!(
+                    "
+                    type Circle as {radius: number}
+                    type Square as {kind: string}
+                    type Shape as Circle | Square | {kind: :triangle}
+                    type ByKind as index_by!(Shape, :kind)
+                    "
+                );
+
+                assert_eq!(
+                    errors(source),
+                    vec![
+                        ("`Circle` has no property `kind`".to_string(), "Circle"),
+                        (
+                            "the discriminant `kind` of `Square` is `string`, not a string literal"
+                                .to_string(),
+                            "string"
+                        ),
+                    ]
+                );
+            }
+
+            #[test]
+            fn recursive_aliases() {
+                let typescript =
+                    compile("type R as index_by!(R, :kind)", &CompileOptions::default())
+                        .unwrap()
+                        .typescript;
+
+                assert!(
+                    typescript.contains("type R = { [K in R['kind']]: Extract<R, {kind: K}> };"),
+                    "{typescript}"
+                );
+            }
+
+            #[test]
+            fn discriminant_names() {
+                assert_eq!(
+                    errors("type A as index_by!({kind: :a}, kind)"),
+                    vec![(
+                        "`index_by!` expects the name of the discriminant, found `kind`"
+                            .to_string(),
+                        "kind"
+                    )]
+                );
+            }
+        }
+
+        mod conditions {
+            use super::*;
+            use crate::typescript::Pretty as _;
+            use pretty_assertions::assert_eq;
+
+            fn expanded(source: &str) -> Result<String, Vec<(String, &str)>> {
+                ast!(source)
+                    .simplify_with_macros(&MacroRegistry::default(), BrandEncoding::default())
+                    .map(|(tree, _)| tree.render_pretty_ts(80).split_whitespace().join(" "))
+                    .map_err(|diagnostics| {
+                        diagnostics
+                            .into_iter()
+                            .map(|diagnostic| {
+                                let span = diagnostic.span;
+                                (diagnostic.message, &source[span.start..span.end])
+                            })
+                            .collect()
+                    })
+            }
+
+            #[test]
+            fn all_in_order() {
+                assert_eq!(
+                    expanded(
+                        "if all!([A <: string, B <: number, not (C <: boolean)]) then 1 else 0 end"
+                    ),
+                    Ok(
+                        "A extends string ? B extends number ? C extends boolean ? 0 : 1 : 0 : 0"
+                            .to_string()
+                    )
+                );
+            }
+
+            #[test]
+            fn any_in_order() {
+                assert_eq!(
+                    expanded("cond do any!([A <: 1, A <: 2]) -> :low, else -> :high end"),
+                    Ok("A extends 1 ? 'low' : A extends 2 ? 'low' : 'high'".to_string())
+                );
+            }
+
+            #[test]
+            fn empty_lists() {
+                assert_eq!(
+                    expanded("if any!([]) then 1 else 0 end"),
+                    Ok("0".to_string())
+                );
+                assert_eq!(
+                    expanded("if all!([]) then 1 else 0 end"),
+                    Ok("1".to_string())
+                );
+            }
+
+            #[test]
+            fn bad_arguments() {
+                assert_eq!(
+                    expanded("if all!(A) then 1 else 0 end"),
+                    Err(vec![(
+                        "`all!` expects a list of conditions".to_string(),
+                        "A"
+                    )])
+                );
+                assert_eq!(
+                    expanded("if any!([A <: 1, B]) then 1 else 0 end"),
+                    Err(vec![(
+                        "`any!` expects a condition, found `B`".to_string(),
+                        "B"
+                    )])
+                );
+            }
+        }
+
         mod assert_equal {
             use super::*;
+            use crate::compile::{compile, CompileOptions};
             use pretty_assertions::assert_eq;
             use rstest::rstest;
             use serde_lexpr::to_value;
@@ -131,7 +1617,7 @@ mod tests {
                 };
 
                 assert_eq!(
-                    to_value(runtime::builtin::assert_equal(left, right))
+                    to_value(runtime::builtin::assert_equal(left, right).unwrap())
                         .unwrap()
                         .to_string(),
                     lexpr::sexp!(#"no-op").to_string()
@@ -141,7 +1627,7 @@ mod tests {
             #[test]
             fn equal_values() {
                 assert_eq!(
-                    to_value(runtime::builtin::assert_equal(ast!("1"), ast!("1")))
+                    to_value(runtime::builtin::assert_equal(ast!("1"), ast!("1")).unwrap())
                         .unwrap()
                         .to_string(),
                     lexpr::sexp!(#"no-op").to_string()
@@ -151,7 +1637,7 @@ mod tests {
             #[test]
             fn equal_values_with_whitespace() {
                 assert_eq!(
-                    to_value(runtime::builtin::assert_equal(ast!(" 1 "), ast!("1")))
+                    to_value(runtime::builtin::assert_equal(ast!(" 1 "), ast!("1")).unwrap())
                         .unwrap()
                         .to_string(),
                     lexpr::sexp!(#"no-op").to_string()
@@ -159,10 +1645,171 @@ mod tests {
             }
 
             #[test]
-            #[should_panic(expected = "assertion failed")]
             fn diff_values() {
-                runtime::builtin::assert_equal(ast!("1"), ast!("2"));
+                let diagnostic = runtime::builtin::assert_equal(ast!("1"), ast!("2")).unwrap_err();
+
+                assert_eq!(
+                    diagnostic.message,
+                    "assertion failed: `1` is not equal to `2`"
+                );
+                assert_eq!(diagnostic.code, Some(codes::ASSERTION_FAILED.id));
+            }
+
+            #[test]
+            fn diff_values_in_source() {
+                let diagnostics = compile(
+                    "export type A as assert_equal!(1, 2)",
+                    &CompileOptions::default(),
+                )
+                .unwrap_err();
+
+                assert_eq!(
+                    diagnostics[0].message,
+                    "assertion failed: `1` is not equal to `2`"
+                );
+            }
+        }
+    }
+
+    mod registry {
+        use super::*;
+        use crate::typescript::Pretty as _;
+        use pretty_assertions::assert_eq;
+
+        /// The macro `endpoint!(:name)`, the type of a request to `/api/<name>`.
+        fn endpoint(call: &MacroCall, _: &Helpers) -> Result<Ast, Diagnostic> {
+            let [name] = arguments(call)?;
+
+            let Ast::TypeString(TypeString { ty, .. }) = name else {
+                return Err(Diagnostic::error(
+                    "expected the name of an endpoint",
+                    name.as_span(),
+                ));
+            };
+
+            let source = format!(r#"{{method: "GET", path: "/api/{ty}"}}"#);
+
+            Ok(ast!(&source))
+        }
+
+        fn expanded(source: &str, macros: &MacroRegistry) -> Result<String, Vec<Diagnostic>> {
+            ast!(source)
+                .simplify_with_macros(macros, BrandEncoding::default())
+                .map(|(tree, _)| tree.render_pretty_ts(80))
+        }
+
+        #[test]
+        fn custom_macro() {
+            let mut macros = MacroRegistry::default();
+            macros.register("endpoint", endpoint);
+
+            assert_eq!(
+                expanded("merge!(endpoint!(:users), {auth: true})", &macros),
+                Ok("{method: 'GET', path: '/api/users', auth: true}".to_string())
+            );
+
+            let diagnostics = expanded("endpoint!(1)", &macros).unwrap_err();
+            assert_eq!(diagnostics[0].message, "expected the name of an endpoint");
+        }
+
+        #[test]
+        fn builtin_overridden() {
+            let mut macros = MacroRegistry::default();
+            macros.register("merge", |call, helpers| {
+                let [lhs, _] = arguments(call)?;
+                Ok(helpers.simplify(lhs))
+            });
+
+            assert_eq!(
+                expanded("merge!({a: 1}, {b: 2})", &macros),
+                Ok("{a: 1}".to_string())
+            );
+            assert_eq!(
+                expanded("deep_readonly!({a: 1})", &macros),
+                Ok("{readonly a: 1}".to_string())
+            );
+        }
+
+        #[test]
+        fn unknown_macro() {
+            let mut macros = MacroRegistry::empty();
+            macros
+                .register("endpoint", endpoint)
+                .register("merge", |call, _| Ok(call.args[0].clone()));
+
+            let source = "[1, missing!(:users)]";
+            let diagnostics = expanded(source, &macros).unwrap_err();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].message, "unknown macro `missing!`");
+            assert_eq!(
+                &source[diagnostics[0].span.start..diagnostics[0].span.end],
+                "missing!(:users)"
+            );
+            assert_eq!(
+                diagnostics[0].notes,
+                vec!["the macros are `endpoint!`, `merge!`"]
+            );
+            assert_eq!(diagnostics[0].code, Some(codes::UNKNOWN_MACRO.id));
+        }
+
+        /// Macros `outer!(x)` expanding to `inner!(x)`, expanding to `failing!(x)`, which fails.
+        fn nested() -> MacroRegistry {
+            fn call(name: &str, call: &MacroCall, helpers: &Helpers) -> Ast {
+                helpers.simplify(&Ast::MacroCall(MacroCall {
+                    name: name.to_string(),
+                    ..call.clone()
+                }))
             }
+
+            let mut macros = MacroRegistry::default();
+            macros
+                .register("outer", |c, helpers| Ok(call("inner!", c, helpers)))
+                .register("inner", |c, helpers| Ok(call("failing!", c, helpers)))
+                .register("failing", |call, _| {
+                    Err(Diagnostic::error("can't expand", call.span))
+                });
+            macros
+        }
+
+        #[test]
+        fn expansion_backtrace() {
+            let diagnostics = expanded("[1, outer!(2)]", &nested()).unwrap_err();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].message, "can't expand");
+            assert_eq!(
+                diagnostics[0].notes,
+                vec![
+                    "expanded from macro `inner!`",
+                    "expanded from macro `outer!`"
+                ]
+            );
+        }
+
+        #[test]
+        fn expansion_in_an_arm() {
+            let source = "match T do 1 -> :a, 2 -> :b, 3 -> inner!(:c), else -> :d end";
+            let diagnostics = expanded(source, &nested()).unwrap_err();
+
+            assert_eq!(
+                diagnostics[0].notes,
+                vec![
+                    "expanded from macro `inner!`",
+                    "in the 3rd arm of this `match`"
+                ]
+            );
+
+            let diagnostics = expanded("cond do T <: 1 -> failing!(1) end", &nested()).unwrap_err();
+
+            assert_eq!(diagnostics[0].notes, vec!["in the 1st arm of this `cond`"]);
+        }
+
+        #[test]
+        fn wrong_number_of_arguments() {
+            let diagnostics = expanded("dbg!(1, 2)", &MacroRegistry::default()).unwrap_err();
+
+            assert_eq!(diagnostics[0].message, "`dbg!` expects 1 argument, found 2");
         }
     }
 }
@@ -0,0 +1,492 @@
+/// Source files and the positions in them. Spans are byte offsets; a [`SourceFile`] indexes the
+/// start of each of its lines once, so that finding the line and column of an offset is a binary
+/// search rather than a scan of the text before it.
+///
+/// Columns are counted in characters for the terminal and in UTF-16 code units for the language
+/// server protocol, which is what editors send and expect.
+///
+/// A file generated from templates can say where its lines come from with `#source` pragmas, as
+/// C does with `#line`:
+///
+/// ```text
+/// #source "templates/user.dl.tmpl" line 42
+/// type User as {id: number}
+/// ```
+///
+/// The line after the pragma is line 42 of the template, and so on until the next pragma, which
+/// diagnostics report instead of the position in the generated file. The grammar skips the
+/// pragmas as trivia. A `#source` in a string or a comment is not one, and a malformed one is
+/// ignored, with a warning, see [`malformed_pragmas`].
+use std::{num::NonZeroU32, ops::Range};
+
+use crate::{ast::Span, diagnostic::Diagnostic, parser::trivia};
+
+/// Index of a file in a [`SourceMap`], plus one, so that an `Option<FileId>` is no larger than
+/// the id: it keeps a [`Diagnostic`](crate::diagnostic::Diagnostic) under the size clippy warns
+/// about returning unboxed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(NonZeroU32);
+
+impl FileId {
+    fn new(index: usize) -> Self {
+        FileId(NonZeroU32::new(index as u32 + 1).unwrap())
+    }
+
+    fn index(self) -> usize {
+        self.0.get() as usize - 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// Path of the file, as shown to the user, or empty for standard input.
+    pub name: String,
+    pub text: String,
+    /// Byte offset of the start of each line, the first being 0.
+    line_starts: Vec<usize>,
+    /// The `#source` pragmas of the file, in the order of the lines they remap.
+    remaps: Vec<Remap>,
+}
+
+/// Lines remapped by a `#source` pragma.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Remap {
+    /// Zero-based line after the pragma, the first it remaps.
+    line: usize,
+    /// Path of the file the lines come from.
+    file: String,
+    /// One-based line of `file` that `line` is.
+    origin: usize,
+}
+
+/// Where a position of a file comes from, see [`SourceFile::origin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Origin<'a> {
+    pub file: &'a str,
+    /// One-based line.
+    pub line: usize,
+    /// One-based column, in characters.
+    pub column: usize,
+}
+
+/// A position as the language server protocol has it: zero-based line, and column in UTF-16 code
+/// units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let remaps = remaps(&text);
+
+        Self {
+            name: name.into(),
+            text,
+            line_starts,
+            remaps,
+        }
+    }
+
+    /// Zero-based line of the byte `offset`. Offsets past the end are on the last line.
+    fn line_index(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// Byte range of the zero-based `line`, without its line break.
+    fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+
+        // `\r\n` line breaks.
+        let end = if self.text[start..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+
+        start..end
+    }
+
+    /// The text before the byte `offset` on its line, up to the character `offset` is in.
+    fn line_prefix(&self, offset: usize) -> (usize, &str) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_index(offset);
+        let start = self.line_starts[line];
+
+        let end = (start..=offset)
+            .rev()
+            .find(|&i| self.text.is_char_boundary(i))
+            .unwrap_or(start);
+
+        (line, &self.text[start..end])
+    }
+
+    /// One-based line and column, in characters, of the byte `offset`.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let (line, prefix) = self.line_prefix(offset);
+
+        (line + 1, prefix.chars().count() + 1)
+    }
+
+    /// Where the byte `offset` comes from: the file and line the last `#source` pragma before it
+    /// names, counting the lines since, or this file and [`SourceFile::line_col`] without one.
+    pub fn origin(&self, offset: usize) -> Origin<'_> {
+        let (line, column) = self.line_col(offset);
+
+        match self.remaps.iter().rev().find(|remap| remap.line < line) {
+            Some(remap) => Origin {
+                file: &remap.file,
+                line: remap.origin + line - 1 - remap.line,
+                column,
+            },
+            None => Origin {
+                file: &self.name,
+                line,
+                column,
+            },
+        }
+    }
+
+    /// The text of the one-based `line`, without its line break, or `""` past the last line.
+    pub fn line(&self, line: usize) -> &str {
+        match line.checked_sub(1) {
+            Some(line) if line < self.line_starts.len() => &self.text[self.line_range(line)],
+            _ => "",
+        }
+    }
+
+    /// The text at the byte `range`, clamped to the file and widened to whole characters.
+    pub fn snippet(&self, range: Range<usize>) -> &str {
+        let len = self.text.len();
+        let start = (0..=range.start.min(len))
+            .rev()
+            .find(|&i| self.text.is_char_boundary(i))
+            .unwrap_or(0);
+        let end = (range.end.clamp(start, len)..=len)
+            .find(|&i| self.text.is_char_boundary(i))
+            .unwrap_or(len);
+
+        &self.text[start..end]
+    }
+
+    /// The language server position of the byte `offset`.
+    pub fn lsp_position(&self, offset: usize) -> Position {
+        let (line, prefix) = self.line_prefix(offset);
+
+        Position {
+            line: line as u32,
+            character: prefix.encode_utf16().count() as u32,
+        }
+    }
+
+    /// The byte offset of a language server position. Positions past the end of their line are at
+    /// its end, and lines past the end of the file at the end of the file, as the protocol asks.
+    pub fn lsp_offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+
+        if line >= self.line_starts.len() {
+            return self.text.len();
+        }
+
+        let range = self.line_range(line);
+        let mut units = 0;
+
+        for (i, c) in self.text[range.clone()].char_indices() {
+            if units >= position.character as usize {
+                return range.start + i;
+            }
+
+            units += c.len_utf16();
+        }
+
+        range.end
+    }
+}
+
+/// The `#source "path" line N` pragmas of `text`, each on a line of its own.
+fn remaps(text: &str) -> Vec<Remap> {
+    trivia::pragma_lines(text, "#source")
+        .into_iter()
+        .filter_map(|(offset, pragma)| {
+            let (file, origin) = parse_pragma(pragma)?;
+
+            Some(Remap {
+                line: text[..offset].matches('\n').count() + 1,
+                file: file.to_string(),
+                origin,
+            })
+        })
+        .collect()
+}
+
+/// The path and the line of a `#source "path" line N` pragma, N counting from 1.
+fn parse_pragma(pragma: &str) -> Option<(&str, usize)> {
+    let rest = pragma.strip_prefix("#source")?;
+    let (file, rest) = rest
+        .strip_prefix(char::is_whitespace)?
+        .trim_start()
+        .strip_prefix('"')?
+        .split_once('"')?;
+    let origin = rest
+        .trim()
+        .strip_prefix("line")?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&origin| origin > 0)?;
+
+    Some((file, origin))
+}
+
+/// Warnings for the `#source` pragmas of `text` that don't say where the lines after them come
+/// from, which [`SourceFile`] ignores.
+pub(crate) fn malformed_pragmas(text: &str) -> Vec<Diagnostic> {
+    trivia::pragma_lines(text, "#source")
+        .into_iter()
+        .filter(|(_, pragma)| parse_pragma(pragma).is_none())
+        .map(|(offset, pragma)| {
+            Diagnostic::warning(
+                "malformed `#source` pragma",
+                Span::new(offset, offset + pragma.trim_end().len()),
+            )
+            .with_note("expected `#source \"path\" line N`, N counting from 1")
+        })
+        .collect()
+}
+
+/// The files of a project, each with an id diagnostics refer to it by.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn add(&mut self, file: SourceFile) -> FileId {
+        self.files.push(file);
+        FileId::new(self.files.len() - 1)
+    }
+
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.index()]
+    }
+
+    /// The ids of the files, in the order they were added.
+    pub fn ids(&self) -> impl Iterator<Item = FileId> {
+        (0..self.files.len()).map(FileId::new)
+    }
+
+    /// The id of the file named `name`.
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(FileId::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("é\né", 0, (1, 1))]
+    #[case("é\né", 3, (2, 1))]
+    #[case("é\néx", 5, (2, 2))]
+    #[case("ab", 10, (1, 3))]
+    #[case("", 0, (1, 1))]
+    #[case("a\n", 2, (2, 1))]
+    // Inside `é`, at the column of the character.
+    #[case("xé", 2, (1, 2))]
+    #[case("a\r\nb", 3, (2, 1))]
+    #[case("🦀 = T", 4, (1, 2))]
+    fn line_col_counts_characters(
+        #[case] text: &str,
+        #[case] offset: usize,
+        #[case] expected: (usize, usize),
+    ) {
+        assert_eq!(SourceFile::new("", text).line_col(offset), expected);
+    }
+
+    #[test]
+    fn lines() {
+        let file = SourceFile::new("a.dl", "type A as 1\r\ntype B as 2\n");
+
+        assert_eq!(file.line(1), "type A as 1");
+        assert_eq!(file.line(2), "type B as 2");
+        assert_eq!(file.line(3), "");
+        assert_eq!(file.line(4), "");
+        assert_eq!(file.line(0), "");
+    }
+
+    // `🦀` is 4 bytes and 2 UTF-16 code units, `é` 2 bytes and 1 unit.
+    #[rstest]
+    #[case(0, Position { line: 0, character: 0 })]
+    #[case(4, Position { line: 0, character: 2 })]
+    #[case(7, Position { line: 0, character: 5 })]
+    #[case(8, Position { line: 0, character: 6 })]
+    #[case(9, Position { line: 1, character: 0 })]
+    #[case(11, Position { line: 1, character: 1 })]
+    #[case(12, Position { line: 1, character: 2 })]
+    fn lsp_positions(#[case] offset: usize, #[case] position: Position) {
+        let file = SourceFile::new("", "🦀 = T\né!");
+
+        assert_eq!(file.lsp_position(offset), position);
+        assert_eq!(file.lsp_offset(position), offset);
+    }
+
+    #[test]
+    fn lsp_positions_past_the_end() {
+        let file = SourceFile::new("", "ab\ncd");
+
+        assert_eq!(
+            file.lsp_offset(Position {
+                line: 0,
+                character: 9
+            }),
+            2
+        );
+        assert_eq!(
+            file.lsp_offset(Position {
+                line: 9,
+                character: 0
+            }),
+            5
+        );
+        assert_eq!(
+            file.lsp_position(9),
+            Position {
+                line: 1,
+                character: 2
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(0..4, "type")]
+    #[case(10..20, "1")]
+    #[case(11..11, "")]
+    #[case(20..30, "")]
+    #[case(0..0, "")]
+    fn snippets_at_the_boundaries(#[case] range: Range<usize>, #[case] expected: &str) {
+        assert_eq!(SourceFile::new("", "type A as 1").snippet(range), expected);
+    }
+
+    #[test]
+    fn snippets_are_whole_characters() {
+        let file = SourceFile::new("", "aéb");
+
+        assert_eq!(file.snippet(2..3), "é");
+        assert_eq!(file.snippet(1..2), "é");
+    }
+
+    #[test]
+    fn source_pragmas_remap_the_lines_after_them() {
+        let file = SourceFile::new(
+            "out.dl",
+            "type A as 1\n#source \"a.tmpl\" line 42\ntype B as 2\n\n  type C as 3\n\
+             #source \"b.tmpl\" line 7\ntype D as 4",
+        );
+        let origin = |text: &str| file.origin(file.text.find(text).unwrap());
+
+        assert_eq!(
+            origin("A"),
+            Origin {
+                file: "out.dl",
+                line: 1,
+                column: 6
+            }
+        );
+        assert_eq!(
+            origin("B"),
+            Origin {
+                file: "a.tmpl",
+                line: 42,
+                column: 6
+            }
+        );
+        assert_eq!(
+            origin("C"),
+            Origin {
+                file: "a.tmpl",
+                line: 44,
+                column: 8
+            }
+        );
+        assert_eq!(
+            origin("D"),
+            Origin {
+                file: "b.tmpl",
+                line: 7,
+                column: 6
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("#source a.tmpl line 1")]
+    #[case("#source \"a.tmpl\"")]
+    #[case("#source \"a.tmpl\" line x")]
+    #[case("#source \"a.tmpl\" line 0")]
+    #[case("#sourced \"a.tmpl\" line 1")]
+    fn malformed_source_pragmas_are_ignored(#[case] pragma: &str) {
+        let text = format!("{pragma}\ntype A as 1");
+        let file = SourceFile::new("out.dl", text.clone());
+
+        assert_eq!(
+            file.origin(file.text.find("type").unwrap()),
+            Origin {
+                file: "out.dl",
+                line: 2,
+                column: 1
+            }
+        );
+        assert_eq!(
+            malformed_pragmas(&text)
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.span.end))
+                .collect::<Vec<_>>(),
+            vec![("malformed `#source` pragma", pragma.len())]
+        );
+    }
+
+    #[test]
+    fn source_pragmas_in_strings_and_comments_are_not_pragmas() {
+        let text = "type A as 'a\n#source \"a.tmpl\" line 42\n'\n\
+                    /* #source \"b.tmpl\" line 7 */\n\
+                    // #source \"c.tmpl\" line 7\n\
+                    type B as 2";
+        let file = SourceFile::new("out.dl", text);
+
+        assert_eq!(
+            file.origin(file.text.find("type B").unwrap()),
+            Origin {
+                file: "out.dl",
+                line: 6,
+                column: 1
+            }
+        );
+        assert_eq!(malformed_pragmas(text), vec![]);
+    }
+
+    #[test]
+    fn source_map() {
+        let mut sources = SourceMap::default();
+        let a = sources.add(SourceFile::new("a.dl", "type A as 1"));
+        let b = sources.add(SourceFile::new("b.dl", "type B as 2"));
+
+        assert_eq!(sources.get(b).name, "b.dl");
+        assert_eq!(sources.find("a.dl"), Some(a));
+        assert_eq!(sources.find("c.dl"), None);
+    }
+}
@@ -0,0 +1,326 @@
+/// Strict mode, set by `--strict` or the `strict` option, for builds that must not let a problem
+/// through. On top of the usual checks it:
+///
+/// - reports the warnings of the lints whose level isn't set as errors,
+/// - rejects the constructs the compiler can't lower yet, listed in [`INCOMPLETE`], up front,
+///   where they would otherwise make it panic,
+/// - requires the patterns of a match over a closed domain to cover it without a wildcard, see
+///   [`lint::wildcard_matches`].
+use std::collections::BTreeMap;
+
+use crate::{
+    ast::{
+        visit::{walk_ast, walk_extends_infix_op, walk_macro_call, Visit},
+        Ast, ExtendsInfixOp, InfixOp, MacroCall, Span,
+    },
+    diagnostic::{codes, Diagnostic, Severity},
+    lint::{self, Level},
+};
+
+/// A construct the simplifier doesn't lower yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Incomplete {
+    /// `T = U` in a condition.
+    Equals,
+    /// `T != U` in a condition.
+    NotEquals,
+    /// `unquote!(dbg!(T))`, macros are expanded after `unquote!` reads its argument.
+    MacroInUnquote,
+    /// `unquote!(map K in T do V end)`.
+    MapInUnquote,
+    /// `unquote!(if T <: U then A else B end)`, the argument of `unquote!` isn't simplified.
+    SugarInUnquote,
+}
+
+/// Every construct the simplifier doesn't lower yet. Remove a construct once it is lowered.
+pub const INCOMPLETE: &[Incomplete] = &[
+    Incomplete::Equals,
+    Incomplete::NotEquals,
+    Incomplete::MacroInUnquote,
+    Incomplete::MapInUnquote,
+    Incomplete::SugarInUnquote,
+];
+
+impl Incomplete {
+    pub fn description(self) -> &'static str {
+        match self {
+            Incomplete::Equals => "`=` in a condition",
+            Incomplete::NotEquals => "`!=` in a condition",
+            Incomplete::MacroInUnquote => "a macro call in the argument of `unquote!`",
+            Incomplete::MapInUnquote => "`map` in the argument of `unquote!`",
+            Incomplete::SugarInUnquote => {
+                "a construct TypeScript doesn't have, such as `if`, in the argument of `unquote!`"
+            }
+        }
+    }
+
+    /// The construct `node` of the argument of `unquote!` is, if it can't be lowered.
+    fn in_unquote(node: &Ast) -> Option<Self> {
+        match node {
+            Ast::MacroCall(_) => Some(Incomplete::MacroInUnquote),
+            Ast::MappedType(_) => Some(Incomplete::MapInUnquote),
+            node if !node.is_typescript_feature() => Some(Incomplete::SugarInUnquote),
+            _ => None,
+        }
+    }
+}
+
+/// The errors strict mode adds for `program`.
+pub fn check(program: &Ast) -> Vec<Diagnostic> {
+    let mut lowerings = Lowerings(vec![]);
+    lowerings.visit_ast(program);
+
+    let mut diagnostics = lowerings.0;
+    diagnostics.extend(lint::wildcard_matches(program));
+    diagnostics
+}
+
+/// Turns the warnings of the lints whose level isn't set in `levels` into errors. A lint set to
+/// `"warn"` explicitly is still a warning.
+pub fn deny_warnings(
+    diagnostics: Vec<Diagnostic>,
+    levels: &BTreeMap<String, Level>,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| match diagnostic.lint {
+            Some(lint)
+                if diagnostic.severity == Severity::Warning && !levels.contains_key(lint) =>
+            {
+                Diagnostic {
+                    severity: Severity::Error,
+                    ..diagnostic
+                }
+                .with_note(format!("`--strict` denies `{lint}`"))
+            }
+            _ => diagnostic,
+        })
+        .collect()
+}
+
+struct Lowerings(Vec<Diagnostic>);
+
+impl Lowerings {
+    fn report(&mut self, incomplete: Incomplete, span: Span) {
+        self.0.push(
+            Diagnostic::error(
+                format!("{} can't be compiled yet", incomplete.description()),
+                span,
+            )
+            .with_note("without `--strict` the compiler fails on it")
+            .with_code(codes::INCOMPLETE_LOWERING),
+        );
+    }
+}
+
+impl<'a> Visit<'a> for Lowerings {
+    fn visit_extends_infix_op(&mut self, op: &'a ExtendsInfixOp) {
+        match op.op {
+            InfixOp::Equals => self.report(Incomplete::Equals, op.span),
+            InfixOp::NotEquals => self.report(Incomplete::NotEquals, op.span),
+            _ => {}
+        }
+
+        walk_extends_infix_op(self, op)
+    }
+
+    fn visit_macro_call(&mut self, call: &'a MacroCall) {
+        if call.name == "unquote!" {
+            let mut first = FirstInUnquote(None);
+
+            for arg in &call.args {
+                first.visit_ast(arg);
+            }
+
+            if let Some((incomplete, span)) = first.0 {
+                self.report(incomplete, span);
+            }
+        }
+
+        walk_macro_call(self, call)
+    }
+}
+
+/// The first node, in the order `unquote!` reads its argument, it can't lower.
+struct FirstInUnquote(Option<(Incomplete, Span)>);
+
+impl<'a> Visit<'a> for FirstInUnquote {
+    fn visit_ast(&mut self, node: &'a Ast) {
+        if self.0.is_some() {
+            return;
+        }
+
+        match Incomplete::in_unquote(node) {
+            Some(incomplete) => self.0 = Some((incomplete, node.as_span())),
+            None => walk_ast(self, node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, compile_streaming, CompileOptions};
+    use pretty_assertions::assert_eq;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    fn strict() -> CompileOptions {
+        CompileOptions {
+            strict: true,
+            ..Default::default()
+        }
+    }
+
+    fn messages(source: &str, options: &CompileOptions) -> Vec<(Severity, String)> {
+        let diagnostics = match compile(source, options) {
+            Ok(output) => output.diagnostics,
+            Err(diagnostics) => diagnostics,
+        };
+
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message))
+            .collect()
+    }
+
+    fn example(incomplete: Incomplete) -> &'static str {
+        match incomplete {
+            Incomplete::Equals => "type A(T) as if T = 1 then 1 else 2 end",
+            Incomplete::NotEquals => "type A(T) as cond do T != 1 -> 1, else -> 2 end",
+            Incomplete::MacroInUnquote => "type A as unquote!([dbg!(1)])",
+            Incomplete::MapInUnquote => "type A as unquote!(map K in :a do 1 end)",
+            Incomplete::SugarInUnquote => "type A(T) as unquote!(if T <: 1 then 1 else 2 end)",
+        }
+    }
+
+    // Each construct listed still makes the simplifier fail, and is an error in strict mode
+    // rather than a panic.
+    #[test]
+    fn incomplete_lowerings_are_in_sync() {
+        for &incomplete in INCOMPLETE {
+            let source = example(incomplete);
+
+            assert!(
+                catch_unwind(AssertUnwindSafe(|| compile(
+                    source,
+                    &CompileOptions::default()
+                )))
+                .is_err(),
+                "{incomplete:?} is lowered, remove it from `INCOMPLETE`"
+            );
+
+            assert_eq!(
+                messages(source, &strict()),
+                vec![(
+                    Severity::Error,
+                    format!("{} can't be compiled yet", incomplete.description())
+                )]
+            );
+            assert!(compile_streaming(source, &strict(), |_| {}).is_err());
+        }
+    }
+
+    // Constructs that aren't incomplete lowerings but programs the compiler rejects, with an error
+    // rather than a panic.
+    #[test]
+    fn invalid_constructs_are_errors() {
+        for (source, message) in [
+            (
+                "type A(T) as cond do T <: 1 -> 1, _ -> 4 end",
+                "`_` is not a condition",
+            ),
+            (
+                "type A(T) as if T then 1 else 2 end",
+                "`T` is not a condition",
+            ),
+            (
+                "type A(T) as if ?X then 1 else 2 end",
+                "`?X` is not a condition",
+            ),
+            (
+                "type A(T) as if T <: 1 and ?X then 1 else 2 end",
+                "`?X` is not a condition",
+            ),
+            (
+                "type A(T) as if not ?X then 1 else 2 end",
+                "`not` may only be used with a condition",
+            ),
+            (
+                "export type A as assert_equal!(1, 2)",
+                "assertion failed: `1` is not equal to `2`",
+            ),
+        ] {
+            assert_eq!(
+                messages(source, &strict()),
+                vec![(Severity::Error, message.to_string())],
+                "{source}"
+            );
+            assert!(compile_streaming(source, &strict(), |_| {}).is_err());
+        }
+    }
+
+    #[test]
+    fn lint_warnings_are_errors() {
+        let source = "type A(l, I) as l";
+
+        assert_eq!(
+            messages(source, &CompileOptions::default()),
+            vec![(
+                Severity::Warning,
+                "`I` is easily confused with `l`".to_string()
+            )]
+        );
+        assert_eq!(
+            messages(source, &strict()),
+            vec![(
+                Severity::Error,
+                "`I` is easily confused with `l`".to_string()
+            )]
+        );
+
+        let options = CompileOptions {
+            lints: [(lint::CONFUSABLE_NAMES.to_string(), Level::Warn)].into(),
+            ..strict()
+        };
+
+        assert_eq!(
+            messages(source, &options),
+            vec![(
+                Severity::Warning,
+                "`I` is easily confused with `l`".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn matches_over_closed_domains_need_no_wildcard() {
+        let wildcards = "type A(T) where T <: :a | :b | :c as match T do :a -> 1, else -> 2 end\n\
+                         type B(T, U) where T <: :a | :b, U <: 1 | 2 as\n\
+                         match T, U do (:a, _) -> 1, else -> 2 end";
+
+        assert_eq!(messages(wildcards, &CompileOptions::default()), vec![]);
+        assert_eq!(
+            messages(wildcards, &strict()),
+            vec![
+                (
+                    Severity::Error,
+                    "`match` leaves a wildcard to cover every member of the constraint of `T`"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "`match` leaves a wildcard to cover every combination of the constraints of \
+                     `T`, `U`"
+                        .to_string()
+                ),
+            ]
+        );
+
+        let exhaustive =
+            "type A(T) where T <: :a | :b as match T do :a -> 1, :b -> 2, else -> 3 end\n\
+                          type B(T, U) where T <: :a | :b, U <: 1 | 2 as\n\
+                          match T, U do (:a, _) -> 1, (:b, number) -> 2, (:a | :b, _) -> 3 end";
+
+        assert_eq!(messages(exhaustive, &strict()), vec![]);
+    }
+}
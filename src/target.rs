@@ -0,0 +1,208 @@
+/// The version of TypeScript the output is written for, set by `--target` or the `target`
+/// option. Constructs the target can't read are errors rather than output it would reject.
+///
+/// ```text
+/// type Tuple(const T) as T
+/// ```
+///
+/// is only emitted for TypeScript 5.0 and later.
+use std::{fmt, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::{
+    ast::{
+        visit::{walk_type_parameter, Visit},
+        Ast, Span, TypeParameter,
+    },
+    config,
+    diagnostic::{codes, Diagnostic},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TsVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl TsVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// The latest version a construct of the language requires.
+    pub const LATEST: Self = CONST_TYPE_PARAMETERS;
+}
+
+impl Default for TsVersion {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+impl fmt::Display for TsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for TsVersion {
+    type Err = String;
+
+    /// `5.0`, or `5` for `5.0`. A patch version, as in `4.7.4`, is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("`{s}` is not a TypeScript version, such as `5.0`");
+
+        let mut parts = s.trim().split('.');
+        let mut number = |required: bool| match parts.next() {
+            Some(part) => part.parse::<u32>().map_err(|_| error()),
+            None if required => Err(error()),
+            None => Ok(0),
+        };
+
+        let major = number(true)?;
+        let minor = number(false)?;
+        number(false)?;
+
+        if parts.next().is_some() {
+            return Err(error());
+        }
+
+        Ok(Self::new(major, minor))
+    }
+}
+
+impl TryFrom<String> for TsVersion {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// `type A(const T)`, TypeScript 5.0.
+pub const CONST_TYPE_PARAMETERS: TsVersion = TsVersion::new(5, 0);
+
+/// Errors for the constructs of `program` that TypeScript `target` can't read.
+pub fn check(program: &Ast, target: TsVersion) -> Vec<Diagnostic> {
+    let mut gates = Gates {
+        target,
+        diagnostics: vec![],
+    };
+    gates.visit_ast(program);
+    gates.diagnostics
+}
+
+struct Gates {
+    target: TsVersion,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Gates {
+    fn require(&mut self, version: TsVersion, construct: &str, span: Span) {
+        if self.target >= version {
+            return;
+        }
+
+        self.diagnostics.push(
+            Diagnostic::error(
+                format!(
+                    "{construct} need TypeScript {version}, the target is {}",
+                    self.target
+                ),
+                span,
+            )
+            .with_note(format!(
+                "set `target = \"{version}\"` in {} or pass `--target {version}`",
+                config::FILE_NAME
+            ))
+            .with_code(codes::UNSUPPORTED_BY_TARGET),
+        );
+    }
+}
+
+impl<'a> Visit<'a> for Gates {
+    fn visit_type_parameter(&mut self, param: &'a TypeParameter) {
+        if param.is_const {
+            self.require(CONST_TYPE_PARAMETERS, "`const` type parameters", param.span);
+        }
+
+        walk_type_parameter(self, param)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compile::{compile, CompileOptions},
+        test_support::parse,
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("5.0", Ok(TsVersion::new(5, 0)))]
+    #[case("4.7.4", Ok(TsVersion::new(4, 7)))]
+    #[case("5", Ok(TsVersion::new(5, 0)))]
+    #[case("five", Err("`five` is not a TypeScript version, such as `5.0`".to_string()))]
+    #[case("5.0.0.1", Err("`5.0.0.1` is not a TypeScript version, such as `5.0`".to_string()))]
+    fn parse_version(#[case] source: &str, #[case] expected: Result<TsVersion, String>) {
+        assert_eq!(source.parse::<TsVersion>(), expected);
+    }
+
+    #[test]
+    fn serialized() {
+        assert_eq!(
+            parse!("type A(const T) as T")
+                .to_sexp()
+                .unwrap()
+                .to_string(),
+            "(program (statements (statement type (export . #f) (name . \"A\") (params ((name . \
+             \"T\") (constraint) (default) (rest . #f) (const . #t))) (body ident . \"T\"))))"
+        );
+    }
+
+    #[test]
+    fn const_parameters_need_5_0() {
+        let options = CompileOptions {
+            target: TsVersion::new(4, 7),
+            ..Default::default()
+        };
+
+        let diagnostics = compile("type A(const T) as T", &options).unwrap_err();
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.message.as_str(),
+                    diagnostic.span.start,
+                    diagnostic.span.end
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "`const` type parameters need TypeScript 5.0, the target is 4.7",
+                7,
+                14
+            )]
+        );
+    }
+
+    #[test]
+    fn const_parameters_at_5_0() {
+        let options = CompileOptions {
+            target: TsVersion::new(5, 0),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile("type A(const T, U) where T <: string[] as T", &options)
+                .unwrap()
+                .typescript
+                .trim(),
+            "type A<const T extends string[], U> = T;"
+        );
+    }
+}
@@ -1,5 +1,54 @@
+use std::{
+    path::PathBuf,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
 use crate::{ast::Ast, parser::parse_newtype_program};
 
+/// A new, empty directory for a test to write files to.
+pub(crate) fn temp_dir() -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "newtype-test-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Type checks `files`, pairs of a file name and its contents, with `tsc` in strict mode. Returns
+/// the errors reported, or `None` if `tsc` is not installed so that callers can skip the check.
+pub(crate) fn tsc(files: &[(&str, &str)]) -> Option<Result<(), String>> {
+    Command::new("tsc").arg("--version").output().ok()?;
+
+    let dir = temp_dir();
+
+    for (name, contents) in files {
+        std::fs::write(dir.join(name), contents).unwrap();
+    }
+
+    let output = Command::new("tsc")
+        .current_dir(&dir)
+        .args(["--noEmit", "--strict", "--module", "esnext"])
+        .args(["--moduleResolution", "bundler"])
+        .args(files.iter().map(|(name, _)| name))
+        .output()
+        .unwrap();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    if output.status.success() {
+        Some(Ok(()))
+    } else {
+        Some(Err(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+}
+
 macro_rules! assert_sexpr {
     ($rule:expr, $processor:expr, $input:expr, $expected:expr) => {{
         let pairs = crate::parser::NewtypeParser::parse($rule, $input).unwrap();
@@ -36,9 +85,7 @@ macro_rules! sexpr {
             .next()
             .unwrap();
 
-        let ast = parser::parse(pair);
-
-        serde_lexpr::to_value(ast)
+        parser::parse(pair).to_sexp()
     }};
 
     ($input:expr) => {
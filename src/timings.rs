@@ -0,0 +1,178 @@
+/// Wall-clock instrumentation for the compiler passes.
+use std::{fmt::Display, time::Duration};
+
+use serde_derive::Serialize;
+
+use crate::ast::Ast;
+
+/// Runs `$body` as the compiler pass `$name`, recording its duration (and the size of the tree it
+/// produced) in the [`PassTimings`] `$ctx`.
+macro_rules! pass_scope {
+    ($name:expr, $ctx:expr, $body:expr) => {{
+        let start = std::time::Instant::now();
+        let out = $body;
+        $ctx.record($name, start.elapsed(), &out);
+        out
+    }};
+}
+
+pub(crate) use pass_scope;
+
+/// Output format of the timings report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TimingsFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PassTiming {
+    pub name: &'static str,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    /// Number of nodes in the tree produced by the pass, if the pass produces a tree.
+    pub nodes: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PassTimings {
+    #[serde(skip)]
+    enabled: bool,
+    passes: Vec<PassTiming>,
+}
+
+impl PassTimings {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            passes: vec![],
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records a run of the pass `name`. A pass run again, as for each statement of a program
+    /// compiled one statement at a time, adds to the duration and nodes of its first run.
+    pub fn record<T: NodeCount>(&mut self, name: &'static str, duration: Duration, out: &T) {
+        if !self.enabled {
+            return;
+        }
+
+        if let Some(pass) = self.passes.iter_mut().find(|pass| pass.name == name) {
+            pass.duration += duration;
+            pass.nodes = match (pass.nodes, out.node_count()) {
+                (Some(nodes), Some(more)) => Some(nodes + more),
+                (nodes, more) => nodes.or(more),
+            };
+            return;
+        }
+
+        self.passes.push(PassTiming {
+            name,
+            duration,
+            nodes: out.node_count(),
+        });
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PassTiming> {
+        self.passes.iter()
+    }
+
+    pub fn total(&self) -> Duration {
+        self.passes.iter().map(|pass| pass.duration).sum()
+    }
+
+    pub fn render(&self, format: TimingsFormat) -> String {
+        match format {
+            TimingsFormat::Table => self.to_string(),
+            TimingsFormat::Json => serde_json::to_string_pretty(self).unwrap(),
+        }
+    }
+}
+
+impl Display for PassTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .passes
+            .iter()
+            .map(|pass| pass.name.len())
+            .chain(["pass".len(), "total".len()])
+            .max()
+            .unwrap_or_default();
+
+        writeln!(f, "{:<width$}  {:>12}  {:>8}", "pass", "time (ms)", "nodes")?;
+
+        for pass in &self.passes {
+            let nodes = match pass.nodes {
+                Some(count) => count.to_string(),
+                None => "-".to_string(),
+            };
+
+            writeln!(
+                f,
+                "{:<width$}  {:>12.3}  {:>8}",
+                pass.name,
+                millis(pass.duration),
+                nodes
+            )?;
+        }
+
+        writeln!(f, "{:<width$}  {:>12.3}", "total", millis(self.total()))
+    }
+}
+
+/// Anything a compiler pass can produce.
+pub trait NodeCount {
+    fn node_count(&self) -> Option<usize>;
+}
+
+impl NodeCount for Ast {
+    fn node_count(&self) -> Option<usize> {
+        Some(Ast::node_count(self))
+    }
+}
+
+impl<T> NodeCount for Vec<T> {
+    fn node_count(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<T: NodeCount> NodeCount for Option<T> {
+    fn node_count(&self) -> Option<usize> {
+        self.as_ref().and_then(NodeCount::node_count)
+    }
+}
+
+impl NodeCount for String {
+    fn node_count(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// An output together with the diagnostics of the pass.
+impl<T: NodeCount, D> NodeCount for (T, Vec<D>) {
+    fn node_count(&self) -> Option<usize> {
+        self.0.node_count()
+    }
+}
+
+impl<T: NodeCount, E> NodeCount for Result<T, E> {
+    fn node_count(&self) -> Option<usize> {
+        self.as_ref().ok().and_then(NodeCount::node_count)
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn serialize_millis<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_f64(millis(*duration))
+}
@@ -7,3 +7,111 @@ pub trait Pretty {
 
     fn to_ts(&self) -> ::pretty::RcDoc<()>;
 }
+
+/// Words TypeScript reserves in every position a name can appear in. `this` is left out as it is
+/// also the polymorphic `this` type.
+pub const RESERVED_WORDS: &[&str] = &[
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Words that are only keywords in some positions. They are valid names, but easily misread.
+pub const CONTEXTUAL_KEYWORDS: &[&str] = &[
+    "abstract",
+    "accessor",
+    "any",
+    "as",
+    "asserts",
+    "async",
+    "await",
+    "bigint",
+    "boolean",
+    "constructor",
+    "declare",
+    "from",
+    "get",
+    "global",
+    "infer",
+    "is",
+    "keyof",
+    "module",
+    "namespace",
+    "never",
+    "number",
+    "object",
+    "of",
+    "out",
+    "override",
+    "readonly",
+    "require",
+    "satisfies",
+    "set",
+    "string",
+    "symbol",
+    "type",
+    "undefined",
+    "unique",
+    "unknown",
+];
+
+/// Whether `name` is a valid TypeScript identifier, ignoring reserved words.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| unicode_ident::is_xid_start(c) || c == '$' || c == '_')
+        && chars.all(|c| {
+            unicode_ident::is_xid_continue(c) || matches!(c, '$' | '\u{200C}' | '\u{200D}')
+        })
+}
+
+pub fn is_reserved_word(name: &str) -> bool {
+    RESERVED_WORDS.contains(&name)
+}
+
+pub fn is_contextual_keyword(name: &str) -> bool {
+    CONTEXTUAL_KEYWORDS.contains(&name)
+}
@@ -0,0 +1,528 @@
+/// Zod schemas mirroring the declarations of a simplified program, for `--emit zod`. Experimental.
+///
+/// Each alias or interface becomes a constant named after it with a `Schema` suffix, in a file of
+/// its own next to the TypeScript, such as `user.zod.ts` for `user.ts`:
+///
+/// ```text
+/// export type User as {name: string, ?role: :admin | :guest}
+/// ```
+///
+/// ```ts
+/// export const UserSchema = z.object({name: z.string(), role: z.union([z.literal('admin'), z.literal('guest')]).optional()});
+/// ```
+///
+/// Only the structural subset of the language has a schema: primitives, literals, object types,
+/// arrays, tuples and unions of them, and references to other declarations of the module. A
+/// declaration using anything else, a conditional or mapped type for instance, gets no schema and
+/// a warning instead, and so do the declarations referring to it. Recursive declarations need a
+/// type annotation in Zod, and get none either.
+use std::collections::{HashMap, HashSet};
+
+use pretty::RcDoc as D;
+
+use crate::{
+    analysis::dependency_graph,
+    ast::{
+        Ast, Interface, ObjectProperty, ObjectPropertyKey, PrimitiveType, Program, Span, Tuple,
+        TypeAlias, TypeLiteral, TypeNumber, TypeString, UnionType,
+    },
+    diagnostic::{codes, Diagnostic},
+    pretty::{indent, string_literal, style, trailing_comma},
+    typescript::{is_identifier, is_reserved_word},
+};
+
+/// Suffix of the name of a schema, after the name of its declaration.
+pub const SCHEMA_SUFFIX: &str = "Schema";
+
+/// Extension of the schema file, in place of `.ts`.
+pub const EXTENSION: &str = "zod.ts";
+
+/// The schema file of `program`, and a warning for each declaration left without a schema.
+pub fn emit(program: &Ast, width: usize) -> (String, Vec<Diagnostic>) {
+    let Ast::Program(Program { statements, .. }) = program else {
+        return (String::new(), vec![]);
+    };
+
+    let mut declarations: Vec<(&str, bool, Result<Body<'_>, Unsupported>)> = vec![];
+
+    for statement in statements {
+        let (name, export, body) = match statement {
+            Ast::Statement(inner) => match inner.as_ref() {
+                Ast::TypeAlias(alias) => (
+                    &alias.name.name,
+                    alias.export || alias.default,
+                    alias_body(alias),
+                ),
+                Ast::Interface(interface) => (
+                    &interface.name,
+                    interface.export || interface.default,
+                    interface_body(interface),
+                ),
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        // Merged interfaces are one type, of which the schema would be the last declaration.
+        match declarations.iter().position(|(other, ..)| other == name) {
+            Some(i) => {
+                declarations[i].2 = Err(Unsupported::new(
+                    "declarations merged under one name",
+                    statement.as_span(),
+                ))
+            }
+            None => declarations.push((name, export, body)),
+        }
+    }
+
+    let graph = dependency_graph(program);
+    let recursive: HashSet<&str> = graph.cycles.iter().flatten().map(String::as_str).collect();
+
+    let index: HashMap<&str, usize> = declarations
+        .iter()
+        .enumerate()
+        .map(|(i, (name, ..))| (*name, i))
+        .collect();
+
+    let mut dependencies = vec![vec![]; declarations.len()];
+
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) =
+            (index.get(edge.from.as_str()), index.get(edge.to.as_str()))
+        {
+            dependencies[from].push(to);
+        }
+    }
+
+    // The declarations in the order their schemas are emitted, each after those it refers to.
+    fn visit(i: usize, dependencies: &[Vec<usize>], visited: &mut [bool], order: &mut Vec<usize>) {
+        if visited[i] {
+            return;
+        }
+
+        visited[i] = true;
+
+        for &j in &dependencies[i] {
+            visit(j, dependencies, visited, order);
+        }
+
+        order.push(i);
+    }
+
+    let mut order = vec![];
+    let mut visited = vec![false; declarations.len()];
+
+    for i in 0..declarations.len() {
+        visit(i, &dependencies, &mut visited, &mut order);
+    }
+
+    let mut emitted: HashSet<&str> = HashSet::new();
+    let mut schemas = vec![];
+    let mut diagnostics = vec![];
+
+    for i in order {
+        let (name, export, body) = &declarations[i];
+
+        let schema = body.as_ref().map_err(Clone::clone).and_then(|body| {
+            if recursive.contains(name) {
+                return Err(Unsupported::new("recursive declarations", body.span));
+            }
+
+            body.references
+                .iter()
+                .find(|reference| !emitted.contains(reference.0.as_str()))
+                .map_or(Ok(()), |(reference, span)| {
+                    Err(Unsupported {
+                        note: if index.contains_key(reference.as_str()) {
+                            format!("it refers to `{reference}`, which has no schema")
+                        } else {
+                            format!(
+                                "it refers to `{reference}`, which isn't declared in the module"
+                            )
+                        },
+                        span: *span,
+                    })
+                })?;
+
+            Ok(&body.doc)
+        });
+
+        match schema {
+            Ok(doc) => {
+                emitted.insert(name);
+                schemas.push(
+                    D::text(if *export { "export const " } else { "const " })
+                        .append(format!("{name}{SCHEMA_SUFFIX}"))
+                        .append(" = ")
+                        .append(doc.clone())
+                        .append(";"),
+                );
+            }
+            Err(Unsupported { note, span }) => diagnostics.push(
+                Diagnostic::warning(format!("`{name}` has no Zod schema"), span)
+                    .with_note(note)
+                    .with_code(codes::NO_ZOD_SCHEMA),
+            ),
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+
+    if schemas.is_empty() {
+        return (String::new(), diagnostics);
+    }
+
+    let doc = D::text("import { z } from ")
+        .append(string_literal("zod"))
+        .append(";")
+        .append(D::hardline())
+        .append(D::hardline())
+        .append(D::intersperse(schemas, D::hardline().append(D::hardline())))
+        .append(D::hardline());
+
+    let mut out = String::new();
+    doc.render_fmt(width, &mut out).unwrap();
+
+    (out, diagnostics)
+}
+
+/// The schema of a declaration, before the declarations it refers to are known to have one.
+struct Body<'a> {
+    doc: D<'a, ()>,
+    /// The declarations named in the schema, and where.
+    references: Vec<(String, Span)>,
+    span: Span,
+}
+
+/// Why a declaration has no schema, and where.
+#[derive(Debug, Clone)]
+struct Unsupported {
+    note: String,
+    span: Span,
+}
+
+impl Unsupported {
+    fn new(construct: &str, span: Span) -> Self {
+        Self {
+            note: format!("Zod schemas can't express {construct}"),
+            span,
+        }
+    }
+}
+
+fn alias_body(alias: &TypeAlias) -> Result<Body<'_>, Unsupported> {
+    if !alias.params.is_empty() {
+        return Err(Unsupported::new("generic types", alias.params[0].span));
+    }
+
+    Body::new(alias.body.as_span(), |references| {
+        schema(&alias.body, references)
+    })
+}
+
+fn interface_body(interface: &Interface) -> Result<Body<'_>, Unsupported> {
+    if !interface.params.is_empty() {
+        return Err(Unsupported::new("generic types", interface.params[0].span));
+    }
+
+    if interface.extends.is_some() {
+        return Err(Unsupported::new(
+            "interfaces extending another",
+            interface.span,
+        ));
+    }
+
+    Body::new(interface.span, |references| {
+        object(&interface.definition, references)
+    })
+}
+
+impl<'a> Body<'a> {
+    fn new(
+        span: Span,
+        schema: impl FnOnce(&mut Vec<(String, Span)>) -> Result<D<'a, ()>, Unsupported>,
+    ) -> Result<Self, Unsupported> {
+        let mut references = vec![];
+        let doc = schema(&mut references)?;
+
+        Ok(Self {
+            doc,
+            references,
+            span,
+        })
+    }
+}
+
+/// The schema of the type `node`, naming the declarations it refers to in `references`.
+fn schema<'a>(
+    node: &'a Ast,
+    references: &mut Vec<(String, Span)>,
+) -> Result<D<'a, ()>, Unsupported> {
+    let unsupported = |construct: &str| Err(Unsupported::new(construct, node.as_span()));
+
+    let doc = match node {
+        Ast::Primitive(primitive, _) => match primitive {
+            PrimitiveType::Boolean => call("boolean", D::nil()),
+            PrimitiveType::Number => call("number", D::nil()),
+            PrimitiveType::String => call("string", D::nil()),
+            PrimitiveType::Symbol => call("symbol", D::nil()),
+            PrimitiveType::BigInt => call("bigint", D::nil()),
+            PrimitiveType::Void => call("void", D::nil()),
+            PrimitiveType::Undefined => call("undefined", D::nil()),
+            PrimitiveType::Null => call("null", D::nil()),
+            PrimitiveType::Object => return unsupported("`object`"),
+            PrimitiveType::UniqueSymbol => return unsupported("`unique symbol`"),
+        },
+        Ast::TypeString(TypeString { ty, .. }) => call("literal", string_literal(ty)),
+        Ast::TypeNumber(TypeNumber { ty, .. }) => call("literal", D::text(ty.clone())),
+        Ast::TrueKeyword(_) => call("literal", D::text("true")),
+        Ast::FalseKeyword(_) => call("literal", D::text("false")),
+        Ast::AnyKeyword(_) => call("any", D::nil()),
+        Ast::UnknownKeyword(_) => call("unknown", D::nil()),
+        Ast::NeverKeyword(_) => call("never", D::nil()),
+        Ast::Array(item) => call("array", schema(item, references)?),
+        Ast::Tuple(Tuple { items, .. }) => call("tuple", list(items, references)?),
+        Ast::UnionType(UnionType { types, .. }) => call("union", list(types, references)?),
+        Ast::TypeLiteral(TypeLiteral { properties, .. }) => object(properties, references)?,
+        Ast::Ident(ident) => {
+            references.push((ident.name.clone(), ident.span));
+            D::text(format!("{}{SCHEMA_SUFFIX}", ident.name))
+        }
+        Ast::CondExpr(_) | Ast::ExtendsExpr(_) => return unsupported("conditional types"),
+        Ast::MappedType(_) => return unsupported("mapped types"),
+        Ast::Infer(_) => return unsupported("`infer`"),
+        Ast::IntersectionType(_) => return unsupported("intersections"),
+        Ast::ApplyGeneric(_) => return unsupported("applications of generic types"),
+        Ast::Access(_) => return unsupported("indexed access types"),
+        Ast::Builtin(_) => return unsupported("type operators"),
+        Ast::FunctionType(_) => return unsupported("function types"),
+        Ast::TemplateString(_) => return unsupported("template literal types"),
+        Ast::ImportType(_) => return unsupported("import types"),
+        Ast::ThisType(_) => return unsupported("`this`"),
+        _ => return unsupported("this type"),
+    };
+
+    Ok(doc)
+}
+
+/// `z.object({...})`, of which the optional properties are `.optional()`.
+fn object<'a>(
+    properties: &'a [ObjectProperty],
+    references: &mut Vec<(String, Span)>,
+) -> Result<D<'a, ()>, Unsupported> {
+    if properties.is_empty() {
+        return Ok(call("object", D::text("{}")));
+    }
+
+    let mut members = vec![];
+
+    for property in properties {
+        let key = match &property.key {
+            ObjectPropertyKey::Key(key) if is_identifier(key) && !is_reserved_word(key) => {
+                D::text(key.clone())
+            }
+            ObjectPropertyKey::Key(key) => string_literal(key),
+            ObjectPropertyKey::Index(_) => {
+                return Err(Unsupported::new(
+                    "index signatures",
+                    property.value.as_span(),
+                ))
+            }
+            ObjectPropertyKey::Computed(_) => {
+                return Err(Unsupported::new("computed keys", property.value.as_span()))
+            }
+        };
+
+        let value = schema(&property.value, references)?;
+        let value = if property.optional {
+            value.append(".optional()")
+        } else {
+            value
+        };
+
+        members.push(key.append(": ").append(value));
+    }
+
+    let line = if style().bracket_spacing {
+        D::line()
+    } else {
+        D::line_()
+    };
+
+    let members = line
+        .clone()
+        .append(D::intersperse(members, D::text(",").append(D::line())))
+        .append(trailing_comma())
+        .nest(indent())
+        .append(line);
+
+    Ok(call(
+        "object",
+        D::text("{").append(members).append("}").group(),
+    ))
+}
+
+/// `[a, b]`, the schemas of `items`.
+fn list<'a>(
+    items: &'a [Ast],
+    references: &mut Vec<(String, Span)>,
+) -> Result<D<'a, ()>, Unsupported> {
+    let items = items
+        .iter()
+        .map(|item| schema(item, references))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(D::text("[")
+        .append(
+            D::line_()
+                .append(D::intersperse(items, D::text(",").append(D::line())))
+                .append(trailing_comma())
+                .nest(indent()),
+        )
+        .append(D::line_())
+        .append("]")
+        .group())
+}
+
+/// `z.<function>(<argument>)`.
+fn call<'a>(function: &str, argument: D<'a, ()>) -> D<'a, ()> {
+    D::text(format!("z.{function}("))
+        .append(argument)
+        .append(")")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, compile_streaming, CompileOptions, OutputKind};
+    use pretty_assertions::assert_eq;
+    use textwrap_macros::dedent;
+
+    fn zod(source: &str) -> (String, Vec<(String, String)>) {
+        let options = CompileOptions {
+            output_kind: OutputKind::Zod,
+            ..Default::default()
+        };
+
+        let output = compile(source, &options).unwrap();
+
+        (
+            output.zod.unwrap(),
+            output
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| (diagnostic.message, diagnostic.notes.join("; ")))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn supported() {
+        assert_eq!(
+            zod(include_str!("../tests/zod/supported.dl")),
+            (
+                include_str!("../tests/zod/supported.zod.ts").to_string(),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn mixed() {
+        let note = |note: &str| note.to_string();
+
+        assert_eq!(
+            zod(include_str!("../tests/zod/mixed.dl")),
+            (
+                include_str!("../tests/zod/mixed.zod.ts").to_string(),
+                vec![
+                    (
+                        "`Keys` has no Zod schema".to_string(),
+                        note("Zod schemas can't express generic types")
+                    ),
+                    (
+                        "`Name` has no Zod schema".to_string(),
+                        note("Zod schemas can't express generic types")
+                    ),
+                    (
+                        "`Flags` has no Zod schema".to_string(),
+                        note("Zod schemas can't express mapped types")
+                    ),
+                    (
+                        "`Entry` has no Zod schema".to_string(),
+                        note("it refers to `Flags`, which has no schema")
+                    ),
+                    (
+                        "`Page` has no Zod schema".to_string(),
+                        note("it refers to `Entry`, which has no schema")
+                    ),
+                    (
+                        "`Tree` has no Zod schema".to_string(),
+                        note("Zod schemas can't express recursive declarations")
+                    ),
+                ]
+            )
+        );
+    }
+
+    // A declaration named with the suffix has a schema with the suffix twice, rather than taking
+    // the name of the schema of another.
+    #[test]
+    fn names_ending_in_the_suffix() {
+        let source = dedent!(
+            r#"
+            export type UserSchema as {version: 1 | 2}
+            export type User as {name: string, schema: UserSchema}
+            "#
+        );
+
+        assert_eq!(
+            zod(source).0,
+            dedent!(
+                r#"
+                import { z } from 'zod';
+
+                export const UserSchemaSchema = z.object({version: z.union([z.literal(1), z.literal(2)])});
+
+                export const UserSchema = z.object({name: z.string(), schema: UserSchemaSchema});
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn declarations_are_ordered_by_use() {
+        let source = "export type A as {b: B}\ntype B as [C, C]\ntype C as 1";
+
+        assert_eq!(
+            zod(source).0,
+            dedent!(
+                r#"
+                import { z } from 'zod';
+
+                const CSchema = z.literal(1);
+
+                const BSchema = z.tuple([CSchema, CSchema]);
+
+                export const ASchema = z.object({b: BSchema});
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn types_are_still_emitted() {
+        let options = CompileOptions {
+            output_kind: OutputKind::Zod,
+            ..Default::default()
+        };
+
+        let output = compile("export type A(T) as T[]", &options).unwrap();
+
+        assert_eq!(output.typescript.trim(), "export type A<T> = T[];");
+        assert_eq!(output.zod.unwrap(), "");
+        assert!(compile_streaming("type A as 1", &options, |_| {}).is_err());
+    }
+}
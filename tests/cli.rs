@@ -0,0 +1,174 @@
+//! Runs the binary on files, checking how it exits.
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A new, empty directory for a test to write files to.
+fn temp_dir() -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "newtype-cli-test-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Runs the binary in `dir` with `args`.
+fn newtype(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_newtype"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn config_errors_fail() {
+    let dir = temp_dir();
+    std::fs::write(dir.join("definitelylisp.toml"), "strict = \"yes\"\n").unwrap();
+    std::fs::write(dir.join("a.dl"), "type A as 1\n").unwrap();
+
+    let output = newtype(&dir, &["-i", "a.dl"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn import_errors_fail() {
+    let dir = temp_dir();
+    std::fs::write(dir.join("schema.json"), r#"{"type": 5}"#).unwrap();
+    std::fs::write(
+        dir.join("schema.graphql"),
+        "scalar Date\ntype A { a: Date }\n",
+    )
+    .unwrap();
+
+    for (args, error) in [
+        (
+            &["import", "dts", "missing.d.ts"][..],
+            "error: can't read `missing.d.ts`",
+        ),
+        (
+            &["import", "graphql", "missing.graphql"],
+            "error: can't read `missing.graphql`",
+        ),
+        (
+            &["import", "json-schema", "missing.json"],
+            "error: can't read `missing.json`",
+        ),
+        (&["import", "json-schema", "schema.json"], "error"),
+        (&["import", "graphql", "schema.graphql"], "error"),
+        (
+            &["import", "graphql", "schema.graphql", "--scalar", "Date"],
+            "expected `NAME=TYPE`, found `Date`",
+        ),
+        (
+            &["import", "graphql", "schema.graphql", "--scalar", "Date=("],
+            "error",
+        ),
+    ] {
+        let output = newtype(&dir, args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+
+        assert_eq!(output.status.code(), Some(1), "{args:?}: {stderr}");
+        assert!(stderr.starts_with(error), "{args:?}: {stderr}");
+        assert!(output.stdout.is_empty(), "{args:?}");
+    }
+}
+
+#[test]
+fn missing_files_are_errors() {
+    let dir = temp_dir();
+    std::fs::write(dir.join("a.dl"), "type A as 1\n").unwrap();
+
+    for args in [
+        &["-i", "missing.dl"][..],
+        &["stats", "missing.dl"],
+        &["graph", "missing.dl"],
+        &["ast", "missing.dl"],
+        &["describe", "missing.dl", "A"],
+        &["diff", "a.dl", "missing.dl"],
+        &["fix", "missing.dl"],
+        &["check", "a.dl", "missing.dl"],
+    ] {
+        let output = newtype(&dir, args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+
+        assert_eq!(output.status.code(), Some(1), "{args:?}: {stderr}");
+        assert!(
+            stderr.starts_with("error: can't read `missing.dl`"),
+            "{args:?}: {stderr}"
+        );
+    }
+}
+
+// Doc comments of the arguments are the help, where a link to an item is printed verbatim.
+#[test]
+fn help_has_no_doc_links() {
+    for command in [
+        &[][..],
+        &["import", "json-schema"],
+        &["import", "dts"],
+        &["import", "graphql"],
+        &["stats"],
+        &["describe"],
+        &["diff"],
+        &["rename"],
+        &["check"],
+    ] {
+        let output = newtype(&temp_dir(), &[command, &["--help"]].concat());
+        let help = String::from_utf8(output.stdout).unwrap();
+
+        assert!(!help.contains("[`"), "{command:?}: {help}");
+    }
+}
+
+#[test]
+fn missing_roots_are_errors() {
+    let output = newtype(
+        &temp_dir(),
+        &["rename", "a.dl::A", "B", "--root", "missing"],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.starts_with("error: can't read `missing`"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn project_errors_fail() {
+    let dir = temp_dir();
+    std::fs::create_dir(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/a.dl"), "export type A as 1\n").unwrap();
+    // A file where the output directory would be created.
+    std::fs::write(dir.join("out"), "").unwrap();
+
+    for (args, error) in [
+        (
+            ["-i", "missing", "--out-dir", "dist"],
+            "error: can't read `missing`",
+        ),
+        (
+            ["-i", "src", "--out-dir", "out"],
+            "error: can't write to `out`",
+        ),
+    ] {
+        let output = newtype(&dir, &args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+
+        assert_eq!(output.status.code(), Some(1), "{args:?}: {stderr}");
+        assert!(stderr.starts_with(error), "{args:?}: {stderr}");
+    }
+}
@@ -0,0 +1,13 @@
+interface Named {
+  name: string;
+}
+
+export interface Repository<T> extends Named {
+  get: (id: string) => T;
+  list: (offset: number, limit: number) => T[];
+  cache?: { hits: number; misses: number };
+  kind: "repository";
+}
+
+export interface Empty {}
+
@@ -0,0 +1,19 @@
+type Point = { x: number; y: number };
+
+export type User = {
+  id: string;
+  name?: string;
+  readonly roles: string[];
+  settings: { theme: "light" | "dark"; "font-size": number };
+  address: {
+    street: string;
+    city: string;
+    country: string;
+    postal_code: string;
+  };
+};
+
+type Quoted = { it: "it's" };
+
+type Empty = {};
+
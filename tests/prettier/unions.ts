@@ -0,0 +1,16 @@
+import type { Named } from "./interfaces";
+
+export type Status =
+  | "pending"
+  | "active"
+  | "suspended"
+  | "deleted"
+  | "archived"
+  | "unknown";
+
+type Small = 1 | 2 | 3;
+
+type Events = [{ kind: "open" }, { kind: "close"; reason: string }];
+
+export default Status;
+
@@ -0,0 +1,3 @@
+import { z } from 'zod';
+
+export const IdSchema = z.string();
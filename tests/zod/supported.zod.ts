@@ -0,0 +1,21 @@
+import { z } from 'zod';
+
+export const RoleSchema = z.union([z.literal('admin'), z.literal('guest'), z.literal('owner')]);
+
+export const AddressSchema = z.object({street: z.string(), city: z.string(), zip: z.number().optional()});
+
+export const UserSchema = z.object({
+    id: z.bigint(),
+    name: z.string(),
+    active: z.boolean(),
+    role: RoleSchema.optional(),
+    'e-mail': z.union([z.string(), z.null()]),
+    address: AddressSchema,
+    tags: z.array(z.string()),
+    position: z.tuple([z.number(), z.number()]),
+    meta: z.unknown(),
+});
+
+export const TeamSchema = z.object({name: z.string(), members: z.array(UserSchema)});
+
+const IdSchema = z.union([z.string(), z.literal(1), z.literal(true)]);